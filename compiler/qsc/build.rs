@@ -1,7 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::process::Command;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 fn main() {
     let git_hash = Command::new("git")
@@ -12,4 +18,55 @@ fn main() {
             |o| String::from_utf8(o.stdout).expect("output should be parsable string"),
         );
     println!("cargo:rustc-env=QSHARP_GIT_HASH={git_hash}");
+    println!("cargo:rustc-env=QSC_BUILD_ID={}", build_id());
+}
+
+/// Hashes the sources of the compiler crates and the Q# libraries, so that every build whose
+/// sources differ has a different id, including builds with uncommitted changes. The id only
+/// needs to be stable within one build, so the standard hasher is enough.
+fn build_id() -> String {
+    let manifest_dir =
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("manifest dir should be set"));
+    let compiler = manifest_dir
+        .parent()
+        .expect("crate should be in the compiler directory");
+    let repo = compiler
+        .parent()
+        .expect("compiler directory should be in the repository");
+    let mut roots = fs::read_dir(compiler)
+        .expect("compiler directory should be readable")
+        .filter_map(|entry| Some(entry.ok()?.path().join("src")))
+        .filter(|src| src.is_dir())
+        .collect::<Vec<_>>();
+    roots.push(repo.join("library"));
+    roots.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for root in &roots {
+        println!("cargo:rerun-if-changed={}", root.display());
+        let mut files = Vec::new();
+        collect_files(root, &mut files);
+        files.sort();
+        for file in files {
+            file.strip_prefix(repo).unwrap_or(&file).hash(&mut hasher);
+            fs::read(&file).unwrap_or_default().hash(&mut hasher);
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    format!("{:016x}", hasher.finish())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.is_dir() {
+            if path.file_name().is_some_and(|name| name != "target") {
+                collect_files(&path, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
 }
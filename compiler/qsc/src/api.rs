@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The stable API for hosts that embed the interpreter, such as language bindings and notebook
+//! kernels. The items here keep their paths and signatures for as long as the major part of
+//! [`API_VERSION`] stays the same, even when the crates they are implemented in change. Hosts
+//! should prefer these paths over the modules of `qsc` that the items are re-exported from.
+//!
+//! Items in [`unstable`] are available to hosts that need them, but can change in any release.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error;
+
+pub use crate::{
+    compile::{Error as CompileError, Warning},
+    interpret::{Error, InterpretResult, Interpreter, InterruptHandle, Value},
+    stable_id::StableId,
+    target::Profile,
+    LanguageFeatures, PackageType, SourceMap, TargetCapabilityFlags,
+};
+pub use qsc_eval::output::{CursorReceiver, GenericReceiver, Receiver};
+
+/// Items that hosts can use, but that do not follow [`API_VERSION`].
+pub mod unstable {
+    pub use crate::{ast, fir, hir, rir};
+}
+
+/// The version of the stable API. The minor part goes up when items are added, and the major
+/// part goes up when items are changed or removed.
+pub const API_VERSION: Version = Version {
+    major: 1,
+    minor: 0,
+    patch: 0,
+};
+
+/// The optional parts of the interpreter that this build has. Features that depend on how the
+/// crate was built are only listed when they are enabled.
+const FEATURES: &[&str] = &[
+    "circuits",
+    "codegen",
+    "completions",
+    "debugger",
+    "docs",
+    "estimates",
+    "formatting",
+    "hover",
+    "kernels",
+    "manifests",
+    "metadata",
+    "mitigation",
+    "packages",
+    "precompiled",
+    "redefinition",
+    "reducers",
+    "references",
+    "warnings",
+];
+
+/// A semantic version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// Parses a version of the form `major.minor.patch`, where the minor and patch parts can be
+    /// left out.
+    #[must_use]
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.').map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// Whether a host written against the `required` version can use this version. It can when
+    /// the major parts are the same and this version is not older.
+    #[must_use]
+    pub fn supports(self, required: Version) -> bool {
+        self.major == required.major && self >= required
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// An error from negotiating the version of the API with a host.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum VersionError {
+    #[error("`{0}` is not a version")]
+    Invalid(String),
+
+    #[error("API version {required} is not supported, the supported version is {supported}")]
+    Unsupported {
+        required: Version,
+        supported: Version,
+    },
+}
+
+/// The version of the stable API that this build implements.
+#[must_use]
+pub fn version() -> Version {
+    API_VERSION
+}
+
+/// The version of the compiler, which can change independently of the API version.
+#[must_use]
+pub fn compiler_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Checks that this build supports the API version that a host was written against, returning
+/// the version that it implements.
+///
+/// # Errors
+///
+/// Returns an error if the version cannot be parsed or is not supported.
+pub fn negotiate(required: &str) -> Result<Version, VersionError> {
+    let required =
+        Version::parse(required).ok_or_else(|| VersionError::Invalid(required.to_string()))?;
+    if API_VERSION.supports(required) {
+        Ok(API_VERSION)
+    } else {
+        Err(VersionError::Unsupported {
+            required,
+            supported: API_VERSION,
+        })
+    }
+}
+
+/// The optional features that this build has, in alphabetical order.
+#[must_use]
+pub fn features() -> Vec<&'static str> {
+    let mut features = FEATURES.to_vec();
+    if cfg!(feature = "distributed") {
+        features.push("distributed");
+    }
+    if cfg!(feature = "opentelemetry") {
+        features.push("opentelemetry");
+    }
+    features.sort_unstable();
+    features
+}
+
+/// Whether this build has the named feature.
+#[must_use]
+pub fn has_feature(name: &str) -> bool {
+    features().contains(&name)
+}
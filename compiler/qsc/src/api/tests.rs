@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    features, has_feature, negotiate, version, GenericReceiver, Interpreter, LanguageFeatures,
+    PackageType, SourceMap, TargetCapabilityFlags, Version, VersionError, API_VERSION,
+};
+
+#[test]
+fn versions_are_parsed() {
+    let version = |major, minor, patch| {
+        Some(Version {
+            major,
+            minor,
+            patch,
+        })
+    };
+    assert_eq!(Version::parse("1.2.3"), version(1, 2, 3));
+    assert_eq!(Version::parse("1.2"), version(1, 2, 0));
+    assert_eq!(Version::parse(" 1 "), version(1, 0, 0));
+    for invalid in ["", "1.", "1.x", "1.2.3.4", "-1", "v1"] {
+        assert_eq!(Version::parse(invalid), None, "{invalid}");
+    }
+    assert_eq!(API_VERSION.to_string(), "1.0.0");
+}
+
+#[test]
+fn newer_versions_with_the_same_major_part_are_supported() {
+    let supported = Version {
+        major: 1,
+        minor: 2,
+        patch: 0,
+    };
+    for (required, expected) in [
+        ("1.0", true),
+        ("1.2.0", true),
+        ("1.2.1", false),
+        ("1.3", false),
+        ("0.9", false),
+        ("2.0", false),
+    ] {
+        let required = Version::parse(required).expect("version should parse");
+        assert_eq!(supported.supports(required), expected, "{required}");
+    }
+}
+
+#[test]
+fn negotiate_returns_the_implemented_version() {
+    assert_eq!(negotiate("1.0"), Ok(version()));
+    assert_eq!(
+        negotiate("one"),
+        Err(VersionError::Invalid("one".to_string()))
+    );
+    assert_eq!(
+        negotiate("2.0").map_err(|error| error.to_string()),
+        Err("API version 2.0.0 is not supported, the supported version is 1.0.0".to_string())
+    );
+}
+
+#[test]
+fn features_are_sorted_and_queryable() {
+    let features = features();
+    let mut sorted = features.clone();
+    sorted.sort_unstable();
+    assert_eq!(features, sorted);
+    assert!(has_feature("warnings"));
+    assert!(!has_feature("time-travel"));
+}
+
+#[test]
+fn interpreter_can_be_used_through_the_api() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let mut sink = std::io::sink();
+    let mut out = GenericReceiver::new(&mut sink);
+    let value = interpreter
+        .eval_fragments(&mut out, "1 + 2")
+        .expect("fragment should evaluate");
+    assert_eq!(value.to_string(), "3");
+}
@@ -0,0 +1,533 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Benchmarks of the evaluator whose results can be stored as JSON and compared against a
+//! baseline, so that performance regressions are caught both by CI and by users checking their
+//! own machines. The `qsbench` binary runs the built-in benchmarks from the command line.
+//!
+//! Each benchmark is sampled several times, and a change against the baseline is only reported
+//! when Welch's t-test finds it significant and it is larger than a threshold, so that noise in
+//! the measurements is not mistaken for a regression.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Instant,
+};
+
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    interpret::{self, output::GenericReceiver, Interpreter},
+    target::Profile,
+};
+
+/// A program whose evaluation is timed.
+#[derive(Clone, Copy, Debug)]
+pub struct Benchmark {
+    pub name: &'static str,
+    /// The source code that the entry expression can use.
+    pub source: &'static str,
+    /// The entry expression that is evaluated in each sample.
+    pub expr: &'static str,
+}
+
+/// The built-in benchmarks, which cover classical control flow, data structures, simulation and
+/// library calls.
+pub const BENCHMARKS: &[Benchmark] = &[
+    Benchmark {
+        name: "classical loop",
+        source: "",
+        expr: "{ mutable sum = 0; for i in 0..100000 { set sum += i; } sum }",
+    },
+    Benchmark {
+        name: "array append",
+        source: "",
+        expr: "{ mutable items = []; for i in 0..2000 { set items += [i]; } Length(items) }",
+    },
+    Benchmark {
+        name: "recursive calls",
+        source: "namespace Bench {
+            function Fibonacci(n : Int) : Int {
+                if n < 2 { n } else { Fibonacci(n - 1) + Fibonacci(n - 2) }
+            }
+        }",
+        expr: "Bench.Fibonacci(18)",
+    },
+    Benchmark {
+        name: "teleportation",
+        source: "namespace Bench {
+            operation Teleport(msg : Qubit, target : Qubit) : Unit {
+                use here = Qubit();
+                H(here);
+                CNOT(here, target);
+                CNOT(msg, here);
+                H(msg);
+                if M(msg) == One { Z(target); }
+                if M(here) == One { X(target); }
+                Reset(here);
+            }
+
+            operation TeleportMany(count : Int) : Unit {
+                use (msg, target) = (Qubit(), Qubit());
+                for _ in 1..count {
+                    Rx(0.7, msg);
+                    Teleport(msg, target);
+                    Reset(msg);
+                    Reset(target);
+                }
+            }
+        }",
+        expr: "Bench.TeleportMany(200)",
+    },
+    Benchmark {
+        name: "GHZ state",
+        source: "",
+        expr: "{
+            use qs = Qubit[16];
+            H(qs[0]);
+            for i in 1..15 { CNOT(qs[0], qs[i]); }
+            MResetEachZ(qs)
+        }",
+    },
+    Benchmark {
+        name: "quantum Fourier transform",
+        source: "",
+        expr: "{
+            use qs = Qubit[10];
+            ApplyToEach(H, qs);
+            Microsoft.Quantum.Canon.ApplyQFT(qs);
+            ResetAll(qs);
+        }",
+    },
+];
+
+/// The filter for the benchmarks whose names contain the given text, or all of them if it is
+/// empty.
+pub fn benchmarks(filter: &str) -> impl Iterator<Item = &'static Benchmark> + '_ {
+    BENCHMARKS
+        .iter()
+        .filter(move |benchmark| benchmark.name.contains(filter))
+}
+
+/// The timings of one benchmark.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    /// The time each sample took, in seconds.
+    pub samples: Vec<f64>,
+}
+
+impl Measurement {
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        mean(&self.samples)
+    }
+}
+
+/// The timings of a run of benchmarks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Results {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Results {
+    /// Converts the results into a JSON object that lists the samples of each benchmark, in
+    /// seconds, by name.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "benchmarks": self
+                .measurements
+                .iter()
+                .map(|measurement| json!({
+                    "name": measurement.name,
+                    "samples": measurement.samples,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Reads results in the format produced by [`Results::to_json`].
+    /// # Errors
+    /// If the JSON is not in that format.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, Error> {
+        let benchmarks = value
+            .get("benchmarks")
+            .and_then(serde_json::Value::as_array)
+            .ok_or(Error::Missing("benchmarks"))?;
+        let measurements = benchmarks
+            .iter()
+            .map(|benchmark| {
+                let name = benchmark
+                    .get("name")
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or(Error::Missing("name"))?;
+                let samples = benchmark
+                    .get("samples")
+                    .and_then(serde_json::Value::as_array)
+                    .ok_or(Error::Missing("samples"))?
+                    .iter()
+                    .map(|sample| {
+                        sample
+                            .as_f64()
+                            .ok_or_else(|| Error::InvalidSample(name.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Measurement {
+                    name: name.to_string(),
+                    samples,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { measurements })
+    }
+
+    fn get(&self, name: &str) -> Option<&Measurement> {
+        self.measurements
+            .iter()
+            .find(|measurement| measurement.name == name)
+    }
+}
+
+impl Display for Results {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for measurement in &self.measurements {
+            writeln!(
+                f,
+                "{}: {} ± {} ({} samples)",
+                measurement.name,
+                format_seconds(measurement.mean()),
+                format_seconds(std_dev(&measurement.samples)),
+                measurement.samples.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("benchmark results are missing `{0}`")]
+    Missing(&'static str),
+    #[error("benchmark results for `{0}` have a sample that is not a number")]
+    InvalidSample(String),
+}
+
+/// Runs each benchmark once to warm up and then the given number of times, timing each run on a
+/// new simulator.
+/// # Errors
+/// If a benchmark fails to compile or its evaluation fails, the errors are returned with the name
+/// of the benchmark.
+pub fn run<'a>(
+    benchmarks: impl IntoIterator<Item = &'a Benchmark>,
+    samples: usize,
+) -> Result<Results, (&'static str, Vec<interpret::Error>)> {
+    let mut measurements = Vec::new();
+    for benchmark in benchmarks {
+        let fail = |errors| (benchmark.name, errors);
+        let sources = SourceMap::new([(benchmark.name.into(), benchmark.source.into())], None);
+        let mut interpreter = Interpreter::new(
+            true,
+            sources,
+            PackageType::Lib,
+            Profile::Unrestricted.into(),
+            LanguageFeatures::default(),
+        )
+        .map_err(fail)?;
+
+        let mut out = std::io::sink();
+        let mut receiver = GenericReceiver::new(&mut out);
+        let mut sample = || {
+            let start = Instant::now();
+            interpreter
+                .run(&mut receiver, benchmark.expr)
+                .and_then(|result| result)
+                .map(|_| start.elapsed().as_secs_f64())
+        };
+        sample().map_err(fail)?;
+        let samples = (0..samples)
+            .map(|_| sample())
+            .collect::<Result<_, _>>()
+            .map_err(fail)?;
+        measurements.push(Measurement {
+            name: benchmark.name.to_string(),
+            samples,
+        });
+    }
+    Ok(Results { measurements })
+}
+
+/// How a benchmark changed from the baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The benchmark is significantly slower.
+    Regressed,
+    /// The benchmark is significantly faster.
+    Improved,
+    /// The change is not significant or is smaller than the threshold.
+    Unchanged,
+    /// The baseline has no results for the benchmark.
+    New,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Verdict::Regressed => write!(f, "regressed"),
+            Verdict::Improved => write!(f, "improved"),
+            Verdict::Unchanged => write!(f, "unchanged"),
+            Verdict::New => write!(f, "new"),
+        }
+    }
+}
+
+/// When a change is reported by [`compare`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    /// The largest p-value of the t-test for which a change is significant.
+    pub significance: f64,
+    /// The smallest relative change in the mean that is reported, such as `0.05` for 5%.
+    pub change: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            significance: 0.05,
+            change: 0.05,
+        }
+    }
+}
+
+/// The change in one benchmark from the baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub name: String,
+    /// The mean time of the baseline, in seconds, if the baseline has the benchmark.
+    pub baseline: Option<f64>,
+    /// The mean time of the current results, in seconds.
+    pub current: f64,
+    /// The two-sided p-value of Welch's t-test between the samples, if the baseline has the
+    /// benchmark.
+    pub p_value: Option<f64>,
+    pub verdict: Verdict,
+}
+
+/// The changes in a run of benchmarks from the baseline.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comparison {
+    pub changes: Vec<Change>,
+}
+
+impl Comparison {
+    pub fn regressions(&self) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(|change| change.verdict == Verdict::Regressed)
+    }
+
+    #[must_use]
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for change in &self.changes {
+            match (change.baseline, change.p_value) {
+                (Some(baseline), Some(p_value)) => writeln!(
+                    f,
+                    "{}: {} -> {} ({:+.1}%, p = {p_value:.3}): {}",
+                    change.name,
+                    format_seconds(baseline),
+                    format_seconds(change.current),
+                    (change.current / baseline - 1.0) * 100.0,
+                    change.verdict
+                )?,
+                _ => writeln!(
+                    f,
+                    "{}: {}: {}",
+                    change.name,
+                    format_seconds(change.current),
+                    change.verdict
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares each of the current results against the baseline's results for the benchmark with
+/// the same name.
+#[must_use]
+pub fn compare(baseline: &Results, current: &Results, thresholds: Thresholds) -> Comparison {
+    let changes = current
+        .measurements
+        .iter()
+        .map(|measurement| {
+            let current_mean = measurement.mean();
+            let Some(base) = baseline.get(&measurement.name) else {
+                return Change {
+                    name: measurement.name.clone(),
+                    baseline: None,
+                    current: current_mean,
+                    p_value: None,
+                    verdict: Verdict::New,
+                };
+            };
+
+            let baseline_mean = base.mean();
+            let p_value = welch_t_test(&base.samples, &measurement.samples);
+            let relative = current_mean / baseline_mean - 1.0;
+            let verdict = if p_value > thresholds.significance || relative.abs() < thresholds.change
+            {
+                Verdict::Unchanged
+            } else if relative > 0.0 {
+                Verdict::Regressed
+            } else {
+                Verdict::Improved
+            };
+            Change {
+                name: measurement.name.clone(),
+                baseline: Some(baseline_mean),
+                current: current_mean,
+                p_value: Some(p_value),
+                verdict,
+            }
+        })
+        .collect();
+    Comparison { changes }
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds >= 1.0 {
+        format!("{seconds:.3} s")
+    } else if seconds >= 1e-3 {
+        format!("{:.3} ms", seconds * 1e3)
+    } else {
+        format!("{:.3} µs", seconds * 1e6)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// The sample variance, with Bessel's correction.
+#[allow(clippy::cast_precision_loss)]
+fn variance(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64
+}
+
+fn std_dev(samples: &[f64]) -> f64 {
+    variance(samples).sqrt()
+}
+
+/// The two-sided p-value of Welch's t-test for the samples having the same mean. Samples without
+/// any variance have a p-value of 1 when their means are equal and 0 otherwise.
+#[allow(clippy::cast_precision_loss)]
+fn welch_t_test(first: &[f64], second: &[f64]) -> f64 {
+    let first_len = first.len() as f64;
+    let second_len = second.len() as f64;
+    let first_error = variance(first) / first_len;
+    let second_error = variance(second) / second_len;
+    let diff = mean(first) - mean(second);
+    if first_error + second_error == 0.0 {
+        return if diff == 0.0 { 1.0 } else { 0.0 };
+    }
+
+    let t = diff / (first_error + second_error).sqrt();
+    // The Welch–Satterthwaite approximation of the degrees of freedom.
+    let df = (first_error + second_error).powi(2)
+        / (first_error.powi(2) / (first_len - 1.0).max(1.0)
+            + second_error.powi(2) / (second_len - 1.0).max(1.0));
+    student_t_two_sided(t, df)
+}
+
+/// The probability that a Student's t-distributed variable with `df` degrees of freedom is
+/// further from zero than `t`.
+fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`, evaluated with a continued fraction.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    // The continued fraction converges quickly only below this point, so the symmetry
+    // `I_x(a, b) = 1 - I_(1-x)(b, a)` is used above it.
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(b, a, 1.0 - x);
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp() / a;
+
+    // Lentz's method for the continued fraction.
+    let mut f = 1.0;
+    let mut c = 1.0;
+    let mut d = 0.0;
+    for i in 0..=400 {
+        let m = f64::from(i / 2);
+        let numerator = if i == 0 {
+            1.0
+        } else if i % 2 == 0 {
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m))
+        } else {
+            -((a + m) * (a + b + m) * x) / ((a + 2.0 * m) * (a + 2.0 * m + 1.0))
+        };
+        d = 1.0 + numerator * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        d = 1.0 / d;
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        let delta = c * d;
+        f *= delta;
+        if (1.0 - delta).abs() < 1e-12 {
+            break;
+        }
+    }
+    front * (f - 1.0)
+}
+
+/// The natural logarithm of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_78,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut y = x;
+    let series = COEFFICIENTS.iter().fold(1.000_000_000_190_015, |sum, c| {
+        y += 1.0;
+        sum + c / y
+    });
+    -tmp + (2.506_628_274_631_000_7 * series / x).ln()
+}
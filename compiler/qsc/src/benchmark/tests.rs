@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    benchmarks, compare, run, student_t_two_sided, welch_t_test, Benchmark, Measurement, Results,
+    Thresholds, Verdict, BENCHMARKS,
+};
+
+fn results(measurements: &[(&str, &[f64])]) -> Results {
+    Results {
+        measurements: measurements
+            .iter()
+            .map(|(name, samples)| Measurement {
+                name: (*name).to_string(),
+                samples: samples.to_vec(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn built_in_benchmarks_run() {
+    let results = run(BENCHMARKS, 1).expect("benchmarks should run");
+    assert_eq!(results.measurements.len(), BENCHMARKS.len());
+    assert!(results
+        .measurements
+        .iter()
+        .all(|measurement| measurement.samples.len() == 1 && measurement.samples[0] > 0.0));
+}
+
+#[test]
+fn failing_benchmark_is_named() {
+    let benchmark = Benchmark {
+        name: "broken",
+        source: "",
+        expr: "Foo()",
+    };
+    let (name, errors) = run([&benchmark], 1).expect_err("benchmark should fail");
+    assert_eq!(name, "broken");
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn benchmarks_are_filtered_by_name() {
+    let names = benchmarks("loop").map(|b| b.name).collect::<Vec<_>>();
+    assert_eq!(names, ["classical loop"]);
+    assert_eq!(benchmarks("").count(), BENCHMARKS.len());
+}
+
+#[test]
+fn results_roundtrip_through_json() {
+    let results = results(&[("a", &[0.5, 0.25]), ("b", &[1.0])]);
+    let json = results.to_json();
+    assert_eq!(
+        json.to_string(),
+        r#"{"benchmarks":[{"name":"a","samples":[0.5,0.25]},{"name":"b","samples":[1.0]}]}"#
+    );
+    assert_eq!(Results::from_json(&json), Ok(results));
+}
+
+#[test]
+fn malformed_json_is_rejected() {
+    let json = serde_json::json!({ "benchmarks": [{ "name": "a", "samples": ["slow"] }] });
+    expect_test::expect!["benchmark results for `a` have a sample that is not a number"].assert_eq(
+        &Results::from_json(&json)
+            .expect_err("results should be invalid")
+            .to_string(),
+    );
+    assert!(Results::from_json(&serde_json::json!({})).is_err());
+}
+
+#[test]
+fn t_distribution_matches_known_values() {
+    // With one degree of freedom the t-distribution is the Cauchy distribution, for which
+    // P(|T| > 1) = 1/2.
+    assert!((student_t_two_sided(1.0, 1.0) - 0.5).abs() < 1e-9);
+    assert!((student_t_two_sided(0.0, 5.0) - 1.0).abs() < 1e-9);
+    // The two-sided 5% critical value for 10 degrees of freedom.
+    assert!((student_t_two_sided(2.228_138_85, 10.0) - 0.05).abs() < 1e-6);
+}
+
+#[test]
+fn t_test_of_identical_samples_is_not_significant() {
+    let samples = [1.0, 1.1, 0.9, 1.05, 0.95];
+    assert!((welch_t_test(&samples, &samples) - 1.0).abs() < 1e-9);
+    assert!((welch_t_test(&[1.0, 1.0], &[1.0, 1.0]) - 1.0).abs() < 1e-9);
+    assert!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0]).abs() < 1e-9);
+}
+
+#[test]
+fn significant_slowdown_is_a_regression() {
+    let baseline = results(&[("a", &[1.0, 1.01, 0.99, 1.02, 0.98])]);
+    let current = results(&[("a", &[1.5, 1.51, 1.49, 1.52, 1.48])]);
+    let comparison = compare(&baseline, &current, Thresholds::default());
+    assert_eq!(comparison.changes[0].verdict, Verdict::Regressed);
+    assert!(comparison.has_regressions());
+
+    let comparison = compare(&current, &baseline, Thresholds::default());
+    assert_eq!(comparison.changes[0].verdict, Verdict::Improved);
+    assert!(!comparison.has_regressions());
+}
+
+#[test]
+fn noisy_or_small_changes_are_unchanged() {
+    let baseline = results(&[("noisy", &[1.0, 2.0, 0.5, 1.5]), ("small", &[1.0, 1.001])]);
+    let current = results(&[
+        ("noisy", &[1.2, 0.6, 2.1, 1.4]),
+        ("small", &[1.01, 1.011]),
+        ("added", &[1.0]),
+    ]);
+    let comparison = compare(&baseline, &current, Thresholds::default());
+    let verdicts = comparison
+        .changes
+        .iter()
+        .map(|change| change.verdict)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verdicts,
+        [Verdict::Unchanged, Verdict::Unchanged, Verdict::New]
+    );
+}
+
+#[test]
+fn comparison_is_displayed_per_benchmark() {
+    let baseline = results(&[("a", &[1.0, 1.0])]);
+    let current = results(&[("a", &[2.0, 2.0]), ("b", &[0.002])]);
+    expect_test::expect![[r#"
+        a: 1.000 s -> 2.000 s (+100.0%, p = 0.000): regressed
+        b: 2.000 ms: new
+    "#]]
+    .assert_eq(&compare(&baseline, &current, Thresholds::default()).to_string());
+}
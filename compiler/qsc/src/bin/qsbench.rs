@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Runs the evaluator benchmarks and compares them against a baseline.
+
+allocator::assign_global!();
+
+use clap::{crate_version, Parser};
+use miette::{Context, IntoDiagnostic, Report};
+use qsc::benchmark::{self, Results, Thresholds};
+use std::{fs, path::PathBuf, process::ExitCode};
+
+#[derive(Debug, Parser)]
+#[command(name = "qsbench", version = concat!(crate_version!(), " (", env!("QSHARP_GIT_HASH"), ")"))]
+#[command(author, about, next_line_help = true)]
+struct Cli {
+    /// Only run the benchmarks whose names contain the given text.
+    #[arg(long, default_value = "")]
+    filter: String,
+
+    /// The number of times each benchmark is timed.
+    #[arg(long, default_value_t = 20)]
+    samples: usize,
+
+    /// Write the results as JSON to the given file.
+    #[arg(long, value_name = "FILE")]
+    save: Option<PathBuf>,
+
+    /// Compare the results against the JSON results in the given file, exiting with a failure
+    /// if any benchmark regressed.
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// The largest p-value for which a change is significant.
+    #[arg(long, default_value_t = Thresholds::default().significance)]
+    significance: f64,
+
+    /// The smallest relative change in the mean time that is reported.
+    #[arg(long, default_value_t = Thresholds::default().change)]
+    threshold: f64,
+}
+
+fn main() -> miette::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    // Read the baseline first so that a bad path fails before the benchmarks run.
+    let baseline = cli
+        .baseline
+        .as_ref()
+        .map(|path| {
+            let contents = fs::read_to_string(path)
+                .into_diagnostic()
+                .with_context(|| format!("could not read baseline {}", path.display()))?;
+            let json = serde_json::from_str(&contents)
+                .into_diagnostic()
+                .with_context(|| format!("baseline {} is not valid JSON", path.display()))?;
+            Results::from_json(&json)
+                .into_diagnostic()
+                .with_context(|| format!("could not read baseline {}", path.display()))
+        })
+        .transpose()?;
+
+    let results = match benchmark::run(benchmark::benchmarks(&cli.filter), cli.samples) {
+        Ok(results) => results,
+        Err((name, errors)) => {
+            eprintln!("benchmark `{name}` failed");
+            for error in errors {
+                eprintln!("error: {:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    print!("{results}");
+
+    if let Some(path) = &cli.save {
+        let json = serde_json::to_string_pretty(&results.to_json()).into_diagnostic()?;
+        fs::write(path, json)
+            .into_diagnostic()
+            .with_context(|| format!("could not write results to {}", path.display()))?;
+    }
+
+    if let Some(baseline) = baseline {
+        let thresholds = Thresholds {
+            significance: cli.significance,
+            change: cli.threshold,
+        };
+        let comparison = benchmark::compare(&baseline, &results, thresholds);
+        println!();
+        print!("{comparison}");
+        if comparison.has_regressions() {
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -3,15 +3,23 @@
 
 allocator::assign_global!();
 
-use clap::{crate_version, ArgGroup, Parser, ValueEnum};
+use clap::{crate_version, ArgGroup, Parser, Subcommand, ValueEnum};
 use log::info;
 use miette::{Context, IntoDiagnostic, Report};
 use qsc::hir::PackageId;
-use qsc::{compile::compile, PassContext};
-use qsc_codegen::qir::fir_to_qir;
+use qsc::{
+    cache::{CacheKey, DiskCache},
+    codegen::{manifest::Manifest, CodegenTarget, Registry},
+    compile::compile,
+    doc,
+    formatter::format_source,
+    packages::{compile_dependencies, compile_dependencies_cached},
+    precompiled, qir_check, PassContext,
+};
+use qsc_codegen::{qir::fir_to_rir, stats::package_stats};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::{
-    compile::{PackageStore, SourceContents, SourceMap, SourceName},
+    compile::{PackageStore, SourceContents, SourceMap, SourceName, StdFeatures},
     error::WithSource,
 };
 use qsc_hir::hir::Package;
@@ -22,7 +30,7 @@ use std::{
     concat, fs,
     io::{self, Read},
     path::{Path, PathBuf},
-    process::ExitCode,
+    process::{self, ExitCode},
     string::String,
 };
 
@@ -51,7 +59,11 @@ impl From<Profile> for qsc::target::Profile {
 #[derive(Debug, Parser)]
 #[command(version = concat!(crate_version!(), " (", env!("QSHARP_GIT_HASH"), ")"), arg_required_else_help(false))]
 #[clap(group(ArgGroup::new("input").args(["entry", "sources"]).required(false).multiple(true)))]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Disable automatic inclusion of the standard library.
     #[arg(long)]
     nostdlib: bool,
@@ -76,6 +88,28 @@ struct Cli {
     #[arg(short, long)]
     profile: Option<Profile>,
 
+    /// Code generation target that `--emit qir` generates code with, such as `qir`.
+    #[arg(long, value_name = "NAME", default_value = "qir")]
+    target: String,
+
+    /// Compile the callable with the given fully qualified name, which must take no arguments, to
+    /// a standalone QIR module `<SYMBOL>.ll` whose entry point is the C function `<SYMBOL>`, and
+    /// declare the functions in `kernels.h`. Can be given more than once. Each module is also
+    /// compiled to an object file `<SYMBOL>.o`, to link with a QIR runtime, when an LLVM
+    /// toolchain is available.
+    #[arg(long = "kernel", value_name = "CALLABLE")]
+    kernels: Vec<String>,
+
+    /// Path to the clang that compiles kernels to object files. Without it, kernels are compiled
+    /// with the `clang` found on the path if there is one, and only the modules are written if
+    /// there is not.
+    #[arg(long, value_name = "PATH")]
+    clang: Option<PathBuf>,
+
+    /// Number of shots to record in the manifest written by `--emit manifest`.
+    #[arg(long)]
+    shots: Option<u32>,
+
     /// Q# source files to compile, or `-` to read from stdin.
     #[arg()]
     sources: Vec<PathBuf>,
@@ -87,30 +121,91 @@ struct Cli {
     /// Language features to compile with
     #[arg(short, long)]
     features: Vec<String>,
+
+    /// Cache generated QIR and the compiled standard library in <DIR> and reuse them when the
+    /// inputs have not changed.
+    #[arg(long = "cache-dir", value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Write the documentation of the public callables and types of Q# source files, or of the
+    /// standard library when no files are given, as `docs.json` and a Markdown file for each item.
+    Doc {
+        /// Q# source files to document, or `-` to read from stdin.
+        #[arg()]
+        sources: Vec<PathBuf>,
+
+        /// Write the documentation to <DIR>.
+        #[arg(long = "outdir", value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Format Q# source files in place. Comments are kept, and files with syntax errors are left
+    /// as they are.
+    Fmt {
+        /// Q# source files to format, or `-` to read from stdin and write to stdout.
+        #[arg(required = true)]
+        sources: Vec<PathBuf>,
+
+        /// List the files that are not formatted instead of formatting them.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Check that a QIR module conforms to a QIR profile, and list the parts of it that do not.
+    QirCheck {
+        /// QIR module to check, in LLVM text format, or `-` to read from stdin.
+        #[arg()]
+        module: PathBuf,
+
+        /// Profile to check against. Defaults to the profile the entry point declares.
+        #[arg(short, long)]
+        profile: Option<Profile>,
+    },
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Emit {
     Hir,
+    Manifest,
     Qir,
+    Stats,
 }
 
 fn main() -> miette::Result<ExitCode> {
     env_logger::init();
     let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Doc { sources, out_dir }) => return emit_docs(&sources, &out_dir),
+        Some(Command::Fmt { sources, check }) => return format_sources(&sources, check),
+        Some(Command::QirCheck { module, profile }) => return check_qir(&module, profile),
+        None => {}
+    }
     let mut store = PackageStore::new(qsc::compile::core());
     let mut dependencies = Vec::new();
     let profile: qsc::target::Profile = cli.profile.unwrap_or_default().into();
     let capabilities = profile.into();
-    let package_type = if cli.emit.contains(&Emit::Qir) {
+    let package_type = if cli.emit.contains(&Emit::Qir) || cli.emit.contains(&Emit::Manifest) {
         PackageType::Exe
     } else {
         PackageType::Lib
     };
 
-    if !cli.nostdlib {
-        dependencies.push(store.insert(qsc::compile::std(&store, capabilities)));
-    }
+    let cache = cli.cache_dir.as_ref().map(DiskCache::new);
+    // With a cache, each package is also known by its key, which the packages built against it
+    // include in their own keys.
+    let mut keyed_dependencies = Vec::new();
+    let std = (!cli.nostdlib).then(|| match &cache {
+        Some(cache) => {
+            let std = precompiled::std(&store, capabilities, StdFeatures::all(), cache);
+            let std_key = precompiled::std_key(capabilities, StdFeatures::all());
+            let std_id = store.insert(std);
+            keyed_dependencies.push((std_id, std_key));
+            std_id
+        }
+        None => store.insert(qsc::compile::std(&store, capabilities)),
+    });
+    dependencies.extend(std);
 
     let mut features = LanguageFeatures::from_iter(cli.features);
 
@@ -119,6 +214,8 @@ fn main() -> miette::Result<ExitCode> {
         .iter()
         .map(read_source)
         .collect::<miette::Result<Vec<_>>>()?;
+    let mut dependency_sources = Vec::new();
+    let mut nostdlib = cli.nostdlib;
 
     if sources.is_empty() {
         let fs = StdFs;
@@ -134,12 +231,33 @@ fn main() -> miette::Result<ExitCode> {
                     }
                 };
 
-                let (mut project_sources, language_features) =
-                    project.package_graph_sources.into_sources_temporary();
-
-                sources.append(&mut project_sources);
+                let graph = project.package_graph_sources;
+                let errors = if let Some(cache) = &cache {
+                    let std = keyed_dependencies.first().copied();
+                    let (project_dependencies, errors) =
+                        compile_dependencies_cached(&mut store, std, &graph, capabilities, cache);
+                    dependencies = project_dependencies.iter().map(|&(id, _)| id).collect();
+                    keyed_dependencies = project_dependencies;
+                    errors
+                } else {
+                    let (project_dependencies, errors) =
+                        compile_dependencies(&mut store, std, &graph, capabilities);
+                    dependencies = project_dependencies;
+                    errors
+                };
+                if !errors.is_empty() {
+                    for error in errors {
+                        eprintln!("{:?}", Report::new(error));
+                    }
+                    return Ok(ExitCode::FAILURE);
+                }
 
-                features.merge(LanguageFeatures::from_iter(language_features));
+                for (_, info) in graph.dependency_order() {
+                    dependency_sources.extend(info.sources.iter().cloned());
+                }
+                sources = graph.root.sources;
+                features.merge(graph.root.language_features);
+                nostdlib |= graph.root.no_stdlib;
             } else {
                 eprintln!("{} must have a parent directory", qsharp_json.display());
                 return Ok(ExitCode::FAILURE);
@@ -147,23 +265,95 @@ fn main() -> miette::Result<ExitCode> {
         }
     }
 
+    if !cli.kernels.is_empty() {
+        let out_dir = cli.out_dir.as_ref().map_or(".".as_ref(), PathBuf::as_path);
+        // Kernels are compiled from sources alone, so the sources of dependencies go with them.
+        let sources = SourceMap::new([sources, dependency_sources].concat(), None);
+        return emit_kernels(
+            out_dir,
+            &sources,
+            features,
+            capabilities,
+            &cli.kernels,
+            cli.clang.as_deref(),
+        );
+    }
+
+    let targets = Registry::default();
+    let Some(target) = targets.get(&cli.target) else {
+        eprintln!(
+            "unknown code generation target `{}`, expected one of: {}",
+            cli.target,
+            targets.names().collect::<Vec<_>>().join(", ")
+        );
+        return Ok(ExitCode::FAILURE);
+    };
+
     let entry = cli.entry.unwrap_or_default();
-    let sources = SourceMap::new(sources, Some(entry.into()));
-    let (unit, errors) = compile(
-        &store,
-        &dependencies,
-        sources,
-        package_type,
-        capabilities,
+    let out_dir = cli.out_dir.as_ref().map_or(".".as_ref(), PathBuf::as_path);
+    let cache_key = cache_key(
+        &[&sources[..], &dependency_sources[..]].concat(),
+        &entry,
         features,
+        capabilities,
+        nostdlib,
     );
+    if let Some(cache) = &cache {
+        if cli.emit == [Emit::Qir] {
+            if let Some(code) = cache.get(cache_key, target.name()) {
+                info!("Using cached code from: {}", cache.dir().display());
+                write_code(out_dir, target, &code)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+        }
+    }
+
+    let sources = SourceMap::new(sources, Some(entry.into()));
+    let (unit, errors) = match &cache {
+        Some(cache) => {
+            let (unit, _, errors) = precompiled::compile_package(
+                &store,
+                &keyed_dependencies,
+                sources,
+                package_type,
+                capabilities,
+                features,
+                cache,
+            );
+            (unit, errors)
+        }
+        None => compile(
+            &store,
+            &dependencies,
+            sources,
+            package_type,
+            capabilities,
+            features,
+        ),
+    };
     let package_id = store.insert(unit);
     let unit = store.get(package_id).expect("package should be in store");
 
-    let out_dir = cli.out_dir.as_ref().map_or(".".as_ref(), PathBuf::as_path);
     for emit in &cli.emit {
         match emit {
             Emit::Hir => emit_hir(&unit.package, out_dir)?,
+            Emit::Stats => emit_stats(&unit.package, out_dir)?,
+            Emit::Manifest => {
+                if capabilities == TargetCapabilityFlags::all() {
+                    eprintln!("manifest generation is not supported for unrestricted profile");
+                    return Ok(ExitCode::FAILURE);
+                }
+                if errors.is_empty() {
+                    if let Err(reports) =
+                        emit_manifest(out_dir, &store, package_id, capabilities, cli.shots)
+                    {
+                        for report in reports {
+                            eprintln!("{report:?}");
+                        }
+                        return Ok(ExitCode::FAILURE);
+                    }
+                }
+            }
             Emit::Qir => {
                 if package_type != PackageType::Exe {
                     eprintln!("QIR generation is only supported for executable packages");
@@ -174,11 +364,20 @@ fn main() -> miette::Result<ExitCode> {
                     return Ok(ExitCode::FAILURE);
                 }
                 if errors.is_empty() {
-                    if let Err(reports) = emit_qir(out_dir, &store, package_id, capabilities) {
-                        for report in reports {
-                            eprintln!("{report:?}");
+                    match emit_code(out_dir, &store, package_id, capabilities, target) {
+                        Ok(code) => {
+                            if let Some(cache) = &cache {
+                                if let Err(err) = cache.put(cache_key, target.name(), &code) {
+                                    info!("Could not write generated code to cache: {err}");
+                                }
+                            }
+                        }
+                        Err(reports) => {
+                            for report in reports {
+                                eprintln!("{report:?}");
+                            }
+                            return Ok(ExitCode::FAILURE);
                         }
-                        return Ok(ExitCode::FAILURE);
                     }
                 }
             }
@@ -215,6 +414,113 @@ fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceCont
     }
 }
 
+fn emit_docs(paths: &[PathBuf], out_dir: &Path) -> miette::Result<ExitCode> {
+    let capabilities = TargetCapabilityFlags::all();
+    let (mut store, std_id) = qsc::compile::shared_store(capabilities, Some(StdFeatures::all()));
+    let std_id = std_id.expect("store should have std");
+    let items = if paths.is_empty() {
+        store
+            .iter()
+            .flat_map(|(_, unit)| doc::extract(&unit.package))
+            .collect::<Vec<_>>()
+    } else {
+        let sources = paths
+            .iter()
+            .map(read_source)
+            .collect::<miette::Result<Vec<_>>>()?;
+        let (unit, errors) = compile(
+            &store,
+            &[std_id],
+            SourceMap::new(sources, None),
+            PackageType::Lib,
+            capabilities,
+            LanguageFeatures::default(),
+        );
+        if !errors.is_empty() {
+            for error in errors {
+                eprintln!("{:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+        doc::extract(&unit.package)
+    };
+
+    for item in &items {
+        let path = out_dir.join(item.markdown_path());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .into_diagnostic()
+                .with_context(|| format!("could not create directory `{}`", dir.display()))?;
+        }
+        fs::write(&path, item.to_markdown())
+            .into_diagnostic()
+            .with_context(|| format!("could not emit documentation file `{}`", path.display()))?;
+    }
+
+    let path = out_dir.join("docs.json");
+    info!(
+        "Writing documentation index to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, doc::to_json(&items).to_string())
+        .into_diagnostic()
+        .with_context(|| format!("could not emit documentation file `{}`", path.display()))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn format_sources(paths: &[PathBuf], check: bool) -> miette::Result<ExitCode> {
+    let mut exit_code = ExitCode::SUCCESS;
+    for path in paths {
+        let (name, contents) = read_source(path)?;
+        let formatted = match format_source(&contents) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                let sources = SourceMap::new([(name, contents)], None);
+                for error in errors {
+                    eprintln!("{:?}", Report::new(WithSource::from_map(&sources, error)));
+                }
+                exit_code = ExitCode::FAILURE;
+                continue;
+            }
+        };
+
+        if check {
+            if formatted != *contents {
+                eprintln!("{name} is not formatted");
+                exit_code = ExitCode::FAILURE;
+            }
+        } else if path.as_os_str() == "-" {
+            print!("{formatted}");
+        } else if formatted != *contents {
+            info!("Formatting {name}");
+            fs::write(path, formatted)
+                .into_diagnostic()
+                .with_context(|| format!("could not write source file `{}`", path.display()))?;
+        }
+    }
+    Ok(exit_code)
+}
+
+fn check_qir(path: &Path, profile: Option<Profile>) -> miette::Result<ExitCode> {
+    let (name, contents) = read_source(path)?;
+    let Some(profile) = profile
+        .map(Into::into)
+        .or_else(|| qir_check::declared_profile(&contents))
+    else {
+        eprintln!("{name} does not declare a known profile, so one must be given with --profile");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let report = qir_check::check(&contents, profile);
+    if report.passed() {
+        print!("{name}: {report}");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprint!("{name}: {report}");
+        Ok(ExitCode::FAILURE)
+    }
+}
+
 fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
     let path = dir.as_ref().join("hir.txt");
     info!(
@@ -226,12 +532,180 @@ fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
         .with_context(|| format!("could not emit HIR file `{}`", path.display()))
 }
 
-fn emit_qir(
+fn emit_stats(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
+    let path = dir.as_ref().join("stats.txt");
+    info!(
+        "Writing statistics output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, package_stats(package).to_string())
+        .into_diagnostic()
+        .with_context(|| format!("could not emit statistics file `{}`", path.display()))
+}
+
+fn cache_key(
+    sources: &[(SourceName, SourceContents)],
+    entry: &str,
+    features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    nostdlib: bool,
+) -> CacheKey {
+    let capabilities = capabilities.bits().to_string();
+    let features: Vec<String> = features.into();
+    let mut parts = vec![
+        capabilities.as_str(),
+        if nostdlib { "nostdlib" } else { "std" },
+        entry,
+    ];
+    parts.extend(features.iter().map(String::as_str));
+    for (name, contents) in sources {
+        parts.push(name);
+        parts.push(contents);
+    }
+    CacheKey::from_parts(parts)
+}
+
+fn write_code(out_dir: &Path, target: &dyn CodegenTarget, code: &str) -> miette::Result<()> {
+    let path = out_dir.join(format!("{}.{}", target.name(), target.extension()));
+    info!(
+        "Writing {} output file to: {}",
+        target.name(),
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(&path, code)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit {} file `{}`", target.name(), path.display()))
+}
+
+fn emit_kernels(
+    out_dir: &Path,
+    sources: &SourceMap,
+    features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    callables: &[String],
+    clang: Option<&Path>,
+) -> miette::Result<ExitCode> {
+    if capabilities == TargetCapabilityFlags::all() {
+        eprintln!("kernel generation is not supported for unrestricted profile");
+        return Ok(ExitCode::FAILURE);
+    }
+    let kernels = match qsc::codegen::get_kernels(sources, features, capabilities, callables) {
+        Ok(kernels) => kernels,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    for kernel in &kernels {
+        let path = out_dir.join(format!("{}.ll", kernel.symbol));
+        info!(
+            "Writing kernel `{}` to: {}",
+            kernel.callable,
+            path.to_str().unwrap_or_default()
+        );
+        fs::write(&path, &kernel.qir)
+            .into_diagnostic()
+            .with_context(|| format!("could not emit kernel file `{}`", path.display()))?;
+    }
+    let header = qsc::codegen::kernel_header(
+        kernels
+            .iter()
+            .map(|kernel| (kernel.callable.as_str(), kernel.symbol.as_str())),
+    );
+    let path = out_dir.join("kernels.h");
+    fs::write(&path, header)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit kernel header `{}`", path.display()))?;
+
+    for kernel in &kernels {
+        let module = out_dir.join(format!("{}.ll", kernel.symbol));
+        let object = out_dir.join(format!("{}.o", kernel.symbol));
+        info!(
+            "Compiling kernel `{}` to: {}",
+            kernel.callable,
+            object.to_str().unwrap_or_default()
+        );
+        let output = process::Command::new(clang.unwrap_or("clang".as_ref()))
+            .arg("-c")
+            .arg(&module)
+            .arg("-o")
+            .arg(&object)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                eprintln!("could not compile kernel file `{}`", module.display());
+                return Ok(ExitCode::FAILURE);
+            }
+            // Without a toolchain, the modules are left for the build of the application to
+            // compile, unless a toolchain was asked for.
+            Err(err) if err.kind() == io::ErrorKind::NotFound && clang.is_none() => {
+                eprintln!(
+                    "clang was not found, so kernels were not compiled to object files; \
+                     compile the `.ll` files in `{}` with an LLVM toolchain",
+                    out_dir.display()
+                );
+                break;
+            }
+            Err(err) => {
+                return Err(err).into_diagnostic().with_context(|| {
+                    format!("could not run clang to compile `{}`", module.display())
+                });
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn emit_code(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+    target: &dyn CodegenTarget,
+) -> Result<String, Vec<Report>> {
+    let program = emit_rir(store, package_id, capabilities)?;
+    let code = target.generate(&program).map_err(|message| {
+        vec![Report::new(qsc::interpret::Error::CodegenTarget(
+            target.name().to_string(),
+            message,
+        ))]
+    })?;
+    write_code(out_dir, target, &code).map_err(|err| vec![err])?;
+    Ok(code)
+}
+
+fn emit_manifest(
     out_dir: &Path,
     store: &PackageStore,
     package_id: PackageId,
     capabilities: TargetCapabilityFlags,
+    shots: Option<u32>,
 ) -> Result<(), Vec<Report>> {
+    let program = emit_rir(store, package_id, capabilities)?;
+    let manifest = Manifest::new(&program, shots);
+    let path = out_dir.join("manifest.json");
+    info!(
+        "Writing manifest output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    let contents =
+        serde_json::to_string_pretty(&manifest.to_json()).expect("manifest should serialize");
+    fs::write(&path, contents)
+        .into_diagnostic()
+        .with_context(|| format!("could not emit manifest file `{}`", path.display()))
+        .map_err(|err| vec![err])
+}
+
+fn emit_rir(
+    store: &PackageStore,
+    package_id: PackageId,
+    capabilities: TargetCapabilityFlags,
+) -> Result<qsc::rir::Program, Vec<Report>> {
     let (fir_store, fir_package_id) = qsc_passes::lower_hir_to_fir(store, package_id);
     let package = fir_store.get(fir_package_id);
     let entry = ProgramEntry {
@@ -253,30 +727,14 @@ fn emit_qir(
     }
     let compute_properties = results.expect("should have compute properties");
 
-    match fir_to_qir(&fir_store, capabilities, Some(compute_properties), &entry) {
-        Ok(qir) => {
-            let path = out_dir.join("qir.ll");
-            info!(
-                "Writing QIR output file to: {}",
-                path.to_str().unwrap_or_default()
-            );
-            fs::write(&path, qir)
-                .into_diagnostic()
-                .with_context(|| format!("could not emit QIR file `{}`", path.display()))
-                .map_err(|err| vec![err])
-        }
-        Err(error) => {
-            let source_package = match error.span() {
-                Some(span) => span.package,
-                None => package_id,
-            };
-            let unit = store
-                .get(source_package)
-                .expect("package should be in store");
-            Err(vec![Report::new(WithSource::from_map(
-                &unit.sources,
-                error,
-            ))])
-        }
-    }
+    fir_to_rir(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|error| {
+        let source_package = match error.span() {
+            Some(span) => span.package,
+            None => package_id,
+        };
+        let unit = store
+            .get(source_package)
+            .expect("package should be in store");
+        vec![Report::new(WithSource::from_map(&unit.sources, error))]
+    })
 }
@@ -7,7 +7,7 @@ use clap::{crate_version, Parser};
 use miette::{Context, IntoDiagnostic, Report, Result};
 use num_bigint::BigUint;
 use num_complex::Complex64;
-use qsc::interpret::{self, InterpretResult, Interpreter};
+use qsc::interpret::{self, ChromeTrace, InterpretResult, Interpreter};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_eval::{
     output::{self, Receiver},
@@ -23,6 +23,7 @@ use std::{
     path::{Path, PathBuf},
     process::ExitCode,
     string::String,
+    time::Instant,
 };
 
 #[derive(Debug, Parser)]
@@ -56,6 +57,16 @@ struct Cli {
     /// Compile the given files and interactive snippets in debug mode.
     #[arg(long)]
     debug: bool,
+
+    /// Seed the simulator and the random number generator, so that measurement outcomes and
+    /// random numbers are the same on every run.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// With --exec, write a timeline of compilation, evaluation and each simulator operation to
+    /// the given file in the Chrome trace event format.
+    #[arg(long, value_name = "FILE", requires = "exec")]
+    trace: Option<PathBuf>,
 }
 
 struct TerminalReceiver;
@@ -119,6 +130,8 @@ fn main() -> miette::Result<ExitCode> {
     }
 
     if cli.exec {
+        let mut trace = ChromeTrace::new();
+        let compile_start = Instant::now();
         let mut interpreter = match (if cli.debug {
             Interpreter::new_with_debug
         } else {
@@ -138,9 +151,21 @@ fn main() -> miette::Result<ExitCode> {
                 return Ok(ExitCode::FAILURE);
             }
         };
-        return Ok(print_exec_result(
-            interpreter.eval_entry(&mut TerminalReceiver),
-        ));
+        interpreter.set_seed(cli.seed);
+        interrupt_on_ctrl_c(&interpreter);
+        let Some(trace_path) = cli.trace else {
+            return Ok(print_exec_result(
+                interpreter.eval_entry(&mut TerminalReceiver),
+            ));
+        };
+
+        trace.record("compile", "compile", compile_start, None);
+        let exit_code =
+            print_exec_result(interpreter.eval_entry_traced(&mut TerminalReceiver, &mut trace));
+        fs::write(&trace_path, trace.to_json().to_string())
+            .into_diagnostic()
+            .with_context(|| format!("could not write trace to `{}`", trace_path.display()))?;
+        return Ok(exit_code);
     }
 
     let mut interpreter = match (if cli.debug {
@@ -163,6 +188,9 @@ fn main() -> miette::Result<ExitCode> {
         }
     };
 
+    interpreter.set_seed(cli.seed);
+    interrupt_on_ctrl_c(&interpreter);
+
     if let Some(entry) = cli.entry {
         print_interpret_result(interpreter.eval_fragments(&mut TerminalReceiver, &entry));
     }
@@ -172,6 +200,16 @@ fn main() -> miette::Result<ExitCode> {
     Ok(ExitCode::SUCCESS)
 }
 
+/// Makes Ctrl+C interrupt the running evaluation instead of ending the process, so that a line
+/// that never finishes, such as an endless `repeat` loop, can be stopped without losing the
+/// session. Presses while no evaluation is running are ignored.
+fn interrupt_on_ctrl_c(interpreter: &Interpreter) {
+    let handle = interpreter.interrupt_handle();
+    if let Err(error) = ctrlc::set_handler(move || handle.interrupt()) {
+        eprintln!("warning: Ctrl+C cannot interrupt evaluation: {error}");
+    }
+}
+
 fn repl(interpreter: &mut Interpreter, receiver: &mut impl Receiver) -> io::Result<()> {
     print_prompt(false);
 
@@ -190,7 +228,9 @@ fn repl(interpreter: &mut Interpreter, receiver: &mut impl Receiver) -> io::Resu
             }
         }
 
-        if !line.trim().is_empty() {
+        if let Some(query) = line.trim().strip_prefix(":search") {
+            print_search_results(interpreter, query.trim());
+        } else if !line.trim().is_empty() {
             print_interpret_result(interpreter.eval_fragments(receiver, &line));
         }
 
@@ -220,6 +260,25 @@ fn print_prompt(continuation: bool) {
     io::stdout().flush().expect("standard out should flush");
 }
 
+fn print_search_results(interpreter: &Interpreter, query: &str) {
+    let symbols = interpreter.symbols();
+    let results = symbols.search(query, 20);
+    if results.is_empty() {
+        println!("no symbols match `{query}`");
+    }
+    for symbol in results {
+        println!("{}: {}", symbol.full_name(), symbol.signature);
+        if let Some(summary) = symbol
+            .summary
+            .lines()
+            .next()
+            .filter(|line| !line.is_empty())
+        {
+            println!("    {summary}");
+        }
+    }
+}
+
 fn print_interpret_result(result: InterpretResult) {
     match result {
         Ok(Value::Tuple(items)) if items.is_empty() => {}
@@ -0,0 +1,115 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! An on-disk cache of compilation outputs keyed by a hash of everything that went into
+//! producing them, so that repeated builds of unchanged sources can reuse prior work across
+//! processes.
+
+#[cfg(test)]
+mod tests;
+
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Identifies the build of the compiler, from a hash of the sources of the compiler crates and
+/// the Q# libraries computed by the build script.
+pub const BUILD_ID: &str = env!("QSC_BUILD_ID");
+
+/// A SHA-256 digest of the inputs to a compilation. Unlike `std::hash::DefaultHasher`, the
+/// digest does not change between processes or toolchain versions, so it can be used on disk,
+/// and it is wide enough that an entry is never mistaken for another whose inputs differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey([u8; 32]);
+
+impl CacheKey {
+    /// Computes a key from the given parts. Each part is length-prefixed, so that moving text
+    /// from one part to the next produces a different key.
+    pub fn from_parts<'a>(parts: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut hasher = Sha256::new();
+        // The build is included so that outputs from a different compiler are not reused. The
+        // crate version alone is not enough, since development builds all share one.
+        for part in [env!("CARGO_PKG_VERSION"), BUILD_ID]
+            .into_iter()
+            .chain(parts)
+        {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        Self(hasher.finalize().into())
+    }
+}
+
+impl Display for CacheKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory of cached outputs. Each entry is stored in its own file named after its key
+/// and kind, e.g. `0123…cdef.qir`.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached output of the given kind, if there is one.
+    #[must_use]
+    pub fn get(&self, key: CacheKey, kind: &str) -> Option<String> {
+        fs::read_to_string(self.path(key, kind)).ok()
+    }
+
+    /// Stores an output, replacing any existing entry for the same key and kind.
+    ///
+    /// The entry is written to a temporary file first and then renamed, so that concurrent
+    /// processes never observe a partially written entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be created or written to.
+    pub fn put(&self, key: CacheKey, kind: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key, kind);
+        let temp = self
+            .dir
+            .join(format!("{key}.{kind}.{}.tmp", std::process::id()));
+        fs::write(&temp, contents)?;
+        if let Err(err) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Removes all entries from the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry cannot be removed.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn path(&self, key: CacheKey, kind: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{kind}"))
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{CacheKey, DiskCache};
+
+fn temp_cache(name: &str) -> DiskCache {
+    let dir = std::env::temp_dir().join(format!("qsc-cache-test-{}-{name}", std::process::id()));
+    let cache = DiskCache::new(dir);
+    cache.clear().expect("cache should be cleared");
+    cache
+}
+
+#[test]
+fn key_is_stable_for_same_parts() {
+    assert_eq!(
+        CacheKey::from_parts(["a.qs", "namespace A {}"]),
+        CacheKey::from_parts(["a.qs", "namespace A {}"])
+    );
+}
+
+#[test]
+fn key_depends_on_part_boundaries() {
+    assert_ne!(
+        CacheKey::from_parts(["ab", "c"]),
+        CacheKey::from_parts(["a", "bc"])
+    );
+}
+
+#[test]
+fn key_is_written_as_fixed_width_hex() {
+    let key = CacheKey::from_parts([]).to_string();
+    assert_eq!(key.len(), 64);
+    assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[test]
+fn missing_entry_is_none() {
+    let cache = temp_cache("missing");
+    assert!(cache.get(CacheKey::from_parts(["x"]), "qir").is_none());
+}
+
+#[test]
+fn entry_round_trips() {
+    let cache = temp_cache("round_trip");
+    let key = CacheKey::from_parts(["x"]);
+    cache
+        .put(key, "qir", "contents")
+        .expect("entry should be written");
+    assert_eq!(cache.get(key, "qir").as_deref(), Some("contents"));
+    assert!(cache.get(key, "hir").is_none());
+
+    cache
+        .put(key, "qir", "updated")
+        .expect("entry should be written");
+    assert_eq!(cache.get(key, "qir").as_deref(), Some("updated"));
+
+    cache.clear().expect("cache should be cleared");
+    assert!(cache.get(key, "qir").is_none());
+}
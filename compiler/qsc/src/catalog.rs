@@ -0,0 +1,142 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Message catalogs let hosts replace the message and help text of diagnostics, for example to
+//! translate them or to use phrasing suited to beginners. Entries are keyed by the stable
+//! diagnostic code, such as `Qsc.Resolve.NotFound`, and by locale.
+
+#[cfg(test)]
+mod tests;
+
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode};
+use rustc_hash::FxHashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// The placeholder in catalog text that is replaced with the original message of the diagnostic.
+pub const MESSAGE_PLACEHOLDER: &str = "{message}";
+
+/// The replacement text for a single diagnostic code. Either part may be left as `None` to keep
+/// the original text of the diagnostic. Occurrences of [`MESSAGE_PLACEHOLDER`] are replaced with
+/// the original message, which includes details such as the names involved.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CatalogEntry {
+    pub message: Option<String>,
+    pub help: Option<String>,
+}
+
+/// A set of catalog entries for any number of locales.
+///
+/// Locales are matched from most to least specific, so an entry for `fr` is used for `fr-CA`
+/// when there is no entry for `fr-CA` itself. Entries added for the empty locale apply to every
+/// locale that has no more specific entry.
+#[derive(Clone, Debug, Default)]
+pub struct MessageCatalog {
+    entries: FxHashMap<(String, String), CatalogEntry>,
+}
+
+impl MessageCatalog {
+    /// Adds the entry for the given locale and diagnostic code, returning the entry it replaces.
+    pub fn insert(
+        &mut self,
+        locale: impl Into<String>,
+        code: impl Into<String>,
+        entry: CatalogEntry,
+    ) -> Option<CatalogEntry> {
+        self.entries.insert((locale.into(), code.into()), entry)
+    }
+
+    /// Returns the entry that applies to the given diagnostic code in the given locale.
+    #[must_use]
+    pub fn get(&self, locale: &str, code: &str) -> Option<&CatalogEntry> {
+        let mut locale = locale;
+        loop {
+            if let Some(entry) = self.entries.get(&(locale.to_string(), code.to_string())) {
+                return Some(entry);
+            }
+            if locale.is_empty() {
+                return None;
+            }
+            locale = locale.rfind(['-', '_']).map_or("", |end| &locale[..end]);
+        }
+    }
+
+    /// Wraps the diagnostic so that its message and help text come from the catalog entry for its
+    /// code in the given locale, when there is one. Everything else, including labels and source
+    /// code, is taken from the original diagnostic.
+    #[must_use]
+    pub fn localize<'a>(&'a self, locale: &str, diagnostic: &'a dyn Diagnostic) -> Localized<'a> {
+        let entry = diagnostic
+            .code()
+            .and_then(|code| self.get(locale, &code.to_string()));
+        Localized { diagnostic, entry }
+    }
+}
+
+/// A diagnostic with its message and help text replaced from a [`MessageCatalog`].
+pub struct Localized<'a> {
+    diagnostic: &'a dyn Diagnostic,
+    entry: Option<&'a CatalogEntry>,
+}
+
+impl Localized<'_> {
+    fn replace(&self, text: &str) -> String {
+        text.replace(MESSAGE_PLACEHOLDER, &self.diagnostic.to_string())
+    }
+}
+
+impl Display for Localized<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.entry.and_then(|entry| entry.message.as_ref()) {
+            Some(message) => f.write_str(&self.replace(message)),
+            None => Display::fmt(self.diagnostic, f),
+        }
+    }
+}
+
+impl Debug for Localized<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Localized")
+            .field("message", &self.to_string())
+            .field("entry", &self.entry)
+            .finish()
+    }
+}
+
+impl std::error::Error for Localized<'_> {}
+
+impl Diagnostic for Localized<'_> {
+    fn code<'b>(&'b self) -> Option<Box<dyn Display + 'b>> {
+        self.diagnostic.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.diagnostic.severity()
+    }
+
+    fn help<'b>(&'b self) -> Option<Box<dyn Display + 'b>> {
+        match self.entry.and_then(|entry| entry.help.as_ref()) {
+            Some(help) => Some(Box::new(self.replace(help))),
+            None => self.diagnostic.help(),
+        }
+    }
+
+    fn url<'b>(&'b self) -> Option<Box<dyn Display + 'b>> {
+        self.diagnostic.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.diagnostic.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.diagnostic.labels()
+    }
+
+    fn related<'b>(&'b self) -> Option<Box<dyn Iterator<Item = &'b dyn Diagnostic> + 'b>> {
+        self.diagnostic.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.diagnostic.diagnostic_source()
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{CatalogEntry, MessageCatalog};
+use crate::interpret::{Error, Interpreter};
+use miette::Diagnostic;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+fn not_found_error() -> Error {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let mut sink = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut sink);
+    interpreter
+        .eval_fragments(&mut receiver, "Undefined()")
+        .expect_err("line should fail")
+        .remove(0)
+}
+
+fn catalog() -> MessageCatalog {
+    let mut catalog = MessageCatalog::default();
+    catalog.insert(
+        "fr",
+        "Qsc.Resolve.NotFound",
+        CatalogEntry {
+            message: Some("introuvable : {message}".to_string()),
+            help: Some("vérifiez l'orthographe".to_string()),
+        },
+    );
+    catalog.insert(
+        "",
+        "Qsc.Resolve.NotFound",
+        CatalogEntry {
+            message: None,
+            help: Some(
+                "Check the spelling of the name and that its namespace is open.".to_string(),
+            ),
+        },
+    );
+    catalog
+}
+
+#[test]
+fn entries_fall_back_to_less_specific_locales() {
+    let catalog = catalog();
+    let fr = catalog
+        .get("fr-CA", "Qsc.Resolve.NotFound")
+        .expect("entry should be found");
+    assert_eq!(fr.help.as_deref(), Some("vérifiez l'orthographe"));
+    let default = catalog
+        .get("de_DE", "Qsc.Resolve.NotFound")
+        .expect("entry should be found");
+    assert!(default.message.is_none());
+    assert!(catalog.get("fr", "Qsc.Eval.UserFail").is_none());
+}
+
+#[test]
+fn localized_diagnostic_replaces_message_and_help() {
+    let error = not_found_error();
+    let catalog = catalog();
+    let localized = catalog.localize("fr", &error);
+    assert_eq!(localized.to_string(), "introuvable : `Undefined` not found");
+    assert_eq!(
+        localized.help().map(|help| help.to_string()).as_deref(),
+        Some("vérifiez l'orthographe")
+    );
+    assert_eq!(
+        localized.code().map(|code| code.to_string()).as_deref(),
+        Some("Qsc.Resolve.NotFound")
+    );
+    assert_eq!(
+        localized.labels().map(Iterator::count),
+        error.labels().map(Iterator::count)
+    );
+}
+
+#[test]
+fn localized_diagnostic_keeps_original_text_without_entry() {
+    let error = not_found_error();
+    let catalog = catalog();
+    let localized = catalog.localize("en-US", &error);
+    assert_eq!(localized.to_string(), error.to_string());
+    assert_eq!(
+        localized.help().map(|help| help.to_string()).as_deref(),
+        Some("Check the spelling of the name and that its namespace is open.")
+    );
+
+    let empty = MessageCatalog::default();
+    let localized = empty.localize("en-US", &error);
+    assert_eq!(localized.to_string(), error.to_string());
+    assert!(localized.help().is_none());
+}
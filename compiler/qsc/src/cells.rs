@@ -0,0 +1,433 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Dependency analysis for notebook cells. Given the cells a notebook has already interpreted and
+//! a new cell, reports which earlier cells define the symbols the new cell refers to, so that
+//! notebook frontends can mark cells as stale or re-run only the cells that are affected by a
+//! change. [`cells_to_rerun`] builds on this to plan which cells to re-run, and in what order,
+//! after a cell is edited.
+//!
+//! The analysis is syntactic: cells are parsed but not compiled, so it can be run on cells that
+//! have not been interpreted yet, or that fail to compile. A name refers to the latest earlier
+//! cell that defines it, the same way later cells shadow earlier ones in the interpreter, and
+//! names bound inside the new cell itself, such as local variables and callable parameters, are
+//! not dependencies.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_ast::{
+    ast::{
+        Attr, Block, CallableDecl, Expr, ExprKind, Item, ItemKind, Namespace, Pat, PatKind, Path,
+        SpecBody, SpecDecl, Stmt, StmtKind, TopLevelNode,
+    },
+    visit::{self, Visitor},
+};
+use qsc_data_structures::language_features::LanguageFeatures;
+use rustc_hash::FxHashSet;
+use std::rc::Rc;
+
+/// An earlier cell that a cell depends on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    /// The index of the earlier cell.
+    pub cell: usize,
+    /// The symbols defined by the earlier cell that the dependent cell refers to, as written in
+    /// the dependent cell, in the order they are first referenced.
+    pub symbols: Vec<String>,
+}
+
+/// Returns the earlier cells that the given cell depends on, ordered by cell index. The earlier
+/// cells are given in the order they were interpreted.
+#[must_use]
+pub fn dependencies(
+    cells: &[&str],
+    cell: &str,
+    language_features: LanguageFeatures,
+) -> Vec<Dependency> {
+    let earlier = cells
+        .iter()
+        .map(|cell| Analysis::new(cell, language_features))
+        .collect::<Vec<_>>();
+    resolve(
+        &earlier.iter().collect::<Vec<_>>(),
+        &Analysis::new(cell, language_features),
+    )
+}
+
+/// Returns the cells to re-run, in order, after the cell at index `edited` changes from
+/// `previous` to its current source in `cells`: the edited cell itself, followed by every later
+/// cell that depends on it directly or through other re-run cells. Dependencies are taken from
+/// both versions of the edited cell, so cells that referred to a definition the edit removed are
+/// re-run too.
+///
+/// # Panics
+/// Panics if `edited` is not the index of a cell.
+#[must_use]
+pub fn cells_to_rerun(
+    cells: &[&str],
+    edited: usize,
+    previous: &str,
+    language_features: LanguageFeatures,
+) -> Vec<usize> {
+    assert!(edited < cells.len(), "edited cell should exist");
+    let current = cells
+        .iter()
+        .map(|cell| Analysis::new(cell, language_features))
+        .collect::<Vec<_>>();
+    let previous = Analysis::new(previous, language_features);
+
+    let mut rerun = vec![edited];
+    for (index, cell) in current.iter().enumerate().skip(edited + 1) {
+        let mut earlier = current[..index].iter().collect::<Vec<_>>();
+        let mut depends = resolve(&earlier, cell);
+        earlier[edited] = &previous;
+        depends.extend(resolve(&earlier, cell));
+        if depends
+            .iter()
+            .any(|dependency| rerun.contains(&dependency.cell))
+        {
+            rerun.push(index);
+        }
+    }
+    rerun
+}
+
+/// The definitions and references of a cell.
+struct Analysis {
+    definitions: Definitions,
+    /// The symbols the cell refers to that it does not bind itself, in the order they appear.
+    symbols: Vec<String>,
+    /// The namespaces opened anywhere in the cell.
+    opens: Vec<Open>,
+}
+
+impl Analysis {
+    fn new(cell: &str, language_features: LanguageFeatures) -> Self {
+        let (nodes, _) = qsc_parse::top_level_nodes(cell, language_features);
+        let definitions = Definitions::new(&nodes);
+        let mut references = References::new(&definitions);
+        for node in &nodes {
+            match node {
+                TopLevelNode::Namespace(namespace) => references.visit_namespace(namespace),
+                TopLevelNode::Stmt(stmt) => references.visit_stmt(stmt),
+            }
+        }
+        Self {
+            definitions,
+            symbols: references.symbols,
+            opens: references.opens,
+        }
+    }
+}
+
+/// Finds the earlier cells that define the symbols the cell refers to.
+fn resolve(earlier: &[&Analysis], cell: &Analysis) -> Vec<Dependency> {
+    let opens = earlier
+        .iter()
+        .flat_map(|analysis| &analysis.definitions.opens)
+        .chain(&cell.opens)
+        .collect::<Vec<_>>();
+    let mut dependencies: Vec<Dependency> = Vec::new();
+    for symbol in &cell.symbols {
+        let Some(index) = candidates(symbol, &opens).find_map(|candidate| {
+            earlier
+                .iter()
+                .rposition(|analysis| analysis.definitions.contains(&candidate))
+        }) else {
+            continue;
+        };
+        match dependencies
+            .iter_mut()
+            .find(|dependency| dependency.cell == index)
+        {
+            Some(dependency) => {
+                if !dependency.symbols.contains(symbol) {
+                    dependency.symbols.push(symbol.clone());
+                }
+            }
+            None => dependencies.push(Dependency {
+                cell: index,
+                symbols: vec![symbol.clone()],
+            }),
+        }
+    }
+    dependencies.sort_by_key(|dependency| dependency.cell);
+    dependencies
+}
+
+/// The names a reference could resolve to, most specific first: the name as written, the
+/// variable whose field it accesses, and the name qualified by each opened namespace.
+fn candidates<'a>(symbol: &'a str, opens: &'a [&'a Open]) -> impl Iterator<Item = String> + 'a {
+    let first = symbol.split_once('.').map(|(first, _)| first.to_string());
+    let opened = opens
+        .iter()
+        .rev()
+        .filter_map(move |open| match &open.alias {
+            Some(alias) => symbol
+                .strip_prefix(alias.as_ref())
+                .and_then(|rest| rest.strip_prefix('.'))
+                .map(|rest| format!("{}.{rest}", open.namespace)),
+            None => Some(format!("{}.{symbol}", open.namespace)),
+        });
+    std::iter::once(symbol.to_string())
+        .chain(first)
+        .chain(opened)
+}
+
+/// An `open` of a namespace, or a glob import, with an optional alias.
+struct Open {
+    namespace: Rc<str>,
+    alias: Option<Rc<str>>,
+}
+
+/// The symbols a cell defines at the top level.
+#[derive(Default)]
+struct Definitions {
+    /// Top-level items by name, items in namespaces by their fully qualified name, and the
+    /// namespaces themselves.
+    items: FxHashSet<Rc<str>>,
+    /// Variables bound by top-level statements.
+    variables: FxHashSet<Rc<str>>,
+    opens: Vec<Open>,
+}
+
+impl Definitions {
+    fn new(nodes: &[TopLevelNode]) -> Self {
+        let mut definitions = Self::default();
+        for node in nodes {
+            match node {
+                TopLevelNode::Namespace(namespace) => {
+                    let name = namespace.name.name();
+                    // Opens inside a namespace only apply to that namespace.
+                    let (mut items, mut opens) = (FxHashSet::default(), Vec::new());
+                    for item in &namespace.items {
+                        add_item(item, &mut items, &mut opens);
+                    }
+                    definitions
+                        .items
+                        .extend(items.iter().map(|item| format!("{name}.{item}").into()));
+                    definitions.items.insert(name);
+                }
+                TopLevelNode::Stmt(stmt) => match &*stmt.kind {
+                    StmtKind::Item(item) => {
+                        add_item(item, &mut definitions.items, &mut definitions.opens);
+                    }
+                    StmtKind::Local(_, pat, _) | StmtKind::Qubit(_, pat, _, None) => {
+                        bind(pat, &mut definitions.variables);
+                    }
+                    _ => {}
+                },
+            }
+        }
+        definitions
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.items.contains(name) || self.variables.contains(name)
+    }
+}
+
+/// Adds the names that the item makes available, and the namespaces that it opens.
+fn add_item(item: &Item, names: &mut FxHashSet<Rc<str>>, opens: &mut Vec<Open>) {
+    match &*item.kind {
+        ItemKind::Callable(decl) => {
+            names.insert(decl.name.name.clone());
+        }
+        ItemKind::Ty(name, _) => {
+            names.insert(name.name.clone());
+        }
+        ItemKind::Struct(decl) => {
+            names.insert(decl.name.name.clone());
+        }
+        ItemKind::Open(namespace, alias) => opens.push(Open {
+            namespace: namespace.name(),
+            alias: alias.as_ref().map(|alias| alias.name.clone()),
+        }),
+        ItemKind::ImportOrExport(decl) if decl.is_import() => {
+            for import in &decl.items {
+                if import.is_glob {
+                    opens.push(Open {
+                        namespace: path_name(&import.path).into(),
+                        alias: import.alias.as_ref().map(|alias| alias.name.clone()),
+                    });
+                } else {
+                    names.insert(
+                        import
+                            .alias
+                            .as_ref()
+                            .unwrap_or(&import.path.name)
+                            .name
+                            .clone(),
+                    );
+                }
+            }
+        }
+        ItemKind::Err | ItemKind::ImportOrExport(_) => {}
+    }
+}
+
+/// The dotted name of the path.
+fn path_name(path: &Path) -> String {
+    match &path.segments {
+        Some(segments) => format!("{}.{}", segments.name(), path.name.name),
+        None => path.name.name.to_string(),
+    }
+}
+
+/// Adds the names bound by the pattern.
+fn bind(pat: &Pat, names: &mut FxHashSet<Rc<str>>) {
+    match &*pat.kind {
+        PatKind::Bind(name, _) => {
+            names.insert(name.name.clone());
+        }
+        PatKind::Paren(pat) => bind(pat, names),
+        PatKind::Tuple(pats) => pats.iter().for_each(|pat| bind(pat, names)),
+        PatKind::Discard(_) | PatKind::Elided | PatKind::Err => {}
+    }
+}
+
+/// Collects the paths a cell refers to that are not bound inside the cell itself.
+struct References {
+    scopes: Vec<FxHashSet<Rc<str>>>,
+    symbols: Vec<String>,
+    /// The namespaces opened anywhere in the cell.
+    opens: Vec<Open>,
+}
+
+impl References {
+    fn new(current: &Definitions) -> Self {
+        // Items are visible throughout the cell that declares them, but variables are only
+        // visible after their declaration, so only the items are bound up front.
+        Self {
+            scopes: vec![current.items.clone()],
+            symbols: Vec::new(),
+            opens: Vec::new(),
+        }
+    }
+
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn bind(&mut self, pat: &Pat) {
+        bind(
+            pat,
+            self.scopes.last_mut().expect("there should be a scope"),
+        );
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(FxHashSet::default());
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl<'a> Visitor<'a> for References {
+    fn visit_namespace(&mut self, namespace: &'a Namespace) {
+        namespace
+            .items
+            .iter()
+            .for_each(|item| self.visit_item(item));
+    }
+
+    fn visit_attr(&mut self, _: &'a Attr) {}
+
+    fn visit_item(&mut self, item: &'a Item) {
+        add_item(item, &mut FxHashSet::default(), &mut self.opens);
+        match &*item.kind {
+            ItemKind::Open(namespace, _) => {
+                let name = namespace.name();
+                if !self.is_bound(&name) {
+                    self.symbols.push(name.to_string());
+                }
+            }
+            _ => visit::walk_item(self, item),
+        }
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        self.scoped(|this| {
+            this.bind(&decl.input);
+            visit::walk_callable_decl(this, decl);
+        });
+    }
+
+    fn visit_spec_decl(&mut self, decl: &'a SpecDecl) {
+        self.scoped(|this| {
+            if let SpecBody::Impl(pat, _) = &decl.body {
+                this.bind(pat);
+            }
+            visit::walk_spec_decl(this, decl);
+        });
+    }
+
+    fn visit_block(&mut self, block: &'a Block) {
+        self.scoped(|this| visit::walk_block(this, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match &*stmt.kind {
+            StmtKind::Local(_, pat, value) => {
+                self.visit_expr(value);
+                self.visit_pat(pat);
+                self.bind(pat);
+            }
+            StmtKind::Qubit(_, pat, init, block) => {
+                self.visit_qubit_init(init);
+                self.visit_pat(pat);
+                match block {
+                    Some(block) => self.scoped(|this| {
+                        this.bind(pat);
+                        visit::walk_block(this, block);
+                    }),
+                    None => self.bind(pat),
+                }
+            }
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::Lambda(_, pat, body) => self.scoped(|this| {
+                this.visit_pat(pat);
+                this.bind(pat);
+                this.visit_expr(body);
+            }),
+            ExprKind::For(pat, iter, block) => {
+                self.visit_expr(iter);
+                self.scoped(|this| {
+                    this.visit_pat(pat);
+                    this.bind(pat);
+                    visit::walk_block(this, block);
+                });
+            }
+            // The condition and fixup of a repeat loop can see the variables bound by its body.
+            ExprKind::Repeat(body, until, fixup) => self.scoped(|this| {
+                visit::walk_block(this, body);
+                this.visit_expr(until);
+                if let Some(fixup) = fixup {
+                    this.visit_block(fixup);
+                }
+            }),
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_path(&mut self, path: &'a Path) {
+        // A path whose first segment is a local variable is a field access on that variable.
+        let first = match &path.segments {
+            Some(segments) => segments
+                .iter()
+                .next()
+                .map_or(&path.name.name, |ident| &ident.name),
+            None => &path.name.name,
+        };
+        let symbol = path_name(path);
+        if !self.is_bound(first) && !self.is_bound(&symbol) {
+            self.symbols.push(symbol);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{cells_to_rerun, dependencies, Dependency};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+
+fn check(cells: &[&str], cell: &str, expected: &[(usize, &[&str])]) {
+    let actual = dependencies(cells, cell, LanguageFeatures::default());
+    let expected = expected
+        .iter()
+        .map(|(cell, symbols)| Dependency {
+            cell: *cell,
+            symbols: symbols.iter().map(ToString::to_string).collect(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn variables_and_callables_are_dependencies() {
+    check(
+        &[
+            "let x = 1;",
+            "function Double(n : Int) : Int { n * 2 }",
+            "Message(\"unrelated\");",
+        ],
+        "Double(x)",
+        &[(0, &["x"]), (1, &["Double"])],
+    );
+}
+
+#[test]
+fn latest_definition_is_the_dependency() {
+    check(
+        &["let x = 1;", "let x = 2;", "let y = x;"],
+        "x + 1",
+        &[(1, &["x"])],
+    );
+}
+
+#[test]
+fn names_bound_in_the_cell_are_not_dependencies() {
+    check(
+        &["let x = 1;", "let n = 2;", "let q = 3;"],
+        indoc! {"
+            let x = 5;
+            function Add(n : Int) : Int { n + x }
+            operation Run() : Unit {
+                use q = Qubit();
+                for i in 0..Add(1) {}
+                let f = n -> n + 1;
+            }
+            x
+        "},
+        &[],
+    );
+}
+
+#[test]
+fn variables_are_only_bound_after_their_declaration() {
+    check(
+        &["let x = 1;"],
+        "let y = x; let x = 2; x + y",
+        &[(0, &["x"])],
+    );
+}
+
+#[test]
+fn items_are_visible_before_their_declaration() {
+    check(
+        &["function Helper() : Int { 1 }"],
+        "let y = Helper(); function Helper() : Int { 2 }",
+        &[],
+    );
+}
+
+#[test]
+fn field_access_depends_on_the_variable() {
+    check(
+        &[
+            "struct Point { X : Int, Y : Int }",
+            "let p = new Point { X = 1, Y = 2 };",
+        ],
+        "p.X + p.Y",
+        &[(1, &["p.X", "p.Y"])],
+    );
+}
+
+#[test]
+fn namespace_items_are_found_by_qualified_and_opened_names() {
+    let library = indoc! {"
+        namespace Library {
+            function Square(x : Int) : Int { x * x }
+        }
+    "};
+    check(&[library], "Library.Square(2)", &[(0, &["Library.Square"])]);
+    check(
+        &[library],
+        "open Library; Square(2)",
+        &[(0, &["Library", "Square"])],
+    );
+    check(
+        &[library, "open Library as L;"],
+        "L.Square(2)",
+        &[(0, &["L.Square"])],
+    );
+    check(&[library], "Square(2)", &[]);
+}
+
+#[test]
+fn types_are_dependencies() {
+    check(
+        &["newtype Pair = (Int, Int);"],
+        "function Zero(p : Pair) : Int { 0 }",
+        &[(0, &["Pair"])],
+    );
+}
+
+#[test]
+fn cells_with_errors_are_analyzed() {
+    check(&["let x = 1;", "let y = ;"], "x", &[(0, &["x"])]);
+}
+
+fn rerun(cells: &[&str], edited: usize, previous: &str) -> Vec<usize> {
+    cells_to_rerun(cells, edited, previous, LanguageFeatures::default())
+}
+
+#[test]
+fn rerun_includes_transitive_dependents_in_order() {
+    let cells = [
+        "let x = 1;",
+        "let y = x + 1;",
+        "let unrelated = 5;",
+        "let z = y * 2;",
+        "Message($\"{unrelated}\");",
+    ];
+    assert_eq!(rerun(&cells, 0, "let x = 0;"), vec![0, 1, 3]);
+    assert_eq!(rerun(&cells, 2, "let unrelated = 4;"), vec![2, 4]);
+    assert_eq!(rerun(&cells, 4, cells[4]), vec![4]);
+}
+
+#[test]
+fn rerun_skips_dependents_of_shadowed_definitions() {
+    let cells = ["let x = 1;", "let x = 2;", "x + 1"];
+    assert_eq!(rerun(&cells, 0, "let x = 0;"), vec![0]);
+}
+
+#[test]
+fn rerun_includes_dependents_of_removed_definitions() {
+    let cells = [
+        "function Helper() : Int { 1 }",
+        "function Unused() : Int { 2 }",
+        "Helper()",
+    ];
+    assert_eq!(
+        rerun(&cells, 1, "function Helper() : Int { 3 }"),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn rerun_includes_dependents_of_renamed_variables() {
+    let cells = ["let y = 1;", "let x = y;", "x"];
+    assert_eq!(rerun(&cells, 0, "let x = 1;"), vec![0, 1, 2]);
+}
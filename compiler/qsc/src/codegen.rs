@@ -4,29 +4,135 @@
 #[cfg(test)]
 mod tests;
 
-use qsc_codegen::qir::fir_to_qir;
+pub mod manifest;
+
+pub use qsc_codegen::{
+    kernel::header as kernel_header,
+    target::{CodegenTarget, Qir, Registry},
+};
+
+use qsc_codegen::{kernel, qir::fir_to_rir};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::{
-    compile::{PackageStore, SourceMap},
+    compile::{SourceMap, StdFeatures},
     error::WithSource,
 };
 use qsc_partial_eval::ProgramEntry;
 use qsc_passes::{PackageType, PassContext};
+use rustc_hash::FxHashMap;
 
 use crate::{compile, interpret::Error};
 
+use self::manifest::Manifest;
+
 pub fn get_qir(
     sources: SourceMap,
     language_features: LanguageFeatures,
     capabilities: TargetCapabilityFlags,
 ) -> Result<String, Vec<Error>> {
+    get_code(sources, language_features, capabilities, &Qir)
+}
+
+/// A callable compiled to a standalone QIR module, whose entry point is a C function that can be
+/// called from classical code. See [`qsc_codegen::kernel`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Kernel {
+    /// The fully qualified name of the callable, such as `Test.Main`.
+    pub callable: String,
+    /// The C symbol of the entry point, such as `Test_Main`.
+    pub symbol: String,
+    pub qir: String,
+}
+
+/// Compiles each of the callables, which must take no arguments, to a kernel, using the sources
+/// as a library with the callable as the entry expression. The declarations of the kernels are
+/// given by [`kernel_header`]. Callables whose kernels would have the same symbol are rejected.
+pub fn get_kernels(
+    sources: &SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    callables: &[String],
+) -> Result<Vec<Kernel>, Vec<Error>> {
+    // Different names can join to the same symbol, such as `A_B.Run` and `A.B_Run`, whose kernels
+    // could not be linked together.
+    let mut callables_by_symbol = FxHashMap::<String, &String>::default();
+    let symbols = callables
+        .iter()
+        .map(|callable| {
+            let symbol = kernel::symbol(callable)
+                .ok_or_else(|| vec![Error::InvalidKernelName(callable.clone())])?;
+            if let Some(other) = callables_by_symbol.insert(symbol.clone(), callable) {
+                return Err(vec![Error::KernelSymbolCollision(
+                    other.clone(),
+                    callable.clone(),
+                    symbol,
+                )]);
+            }
+            Ok(symbol)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    callables
+        .iter()
+        .zip(symbols)
+        .map(|(callable, symbol)| {
+            let sources = SourceMap::new(
+                sources
+                    .iter()
+                    .map(|source| (source.name.clone(), source.contents.clone())),
+                Some(format!("{callable}()").into()),
+            );
+            let qir = get_code(
+                sources,
+                language_features,
+                capabilities,
+                &kernel::Kernel::new(symbol.as_str()),
+            )?;
+            Ok(Kernel {
+                callable: callable.clone(),
+                symbol,
+                qir,
+            })
+        })
+        .collect()
+}
+
+/// Compiles the sources as an executable and generates the code of their entry point for the
+/// given code generation target, like [`get_qir`] does for QIR.
+pub fn get_code(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    target: &dyn CodegenTarget,
+) -> Result<String, Vec<Error>> {
+    let program = get_rir(sources, language_features, capabilities)?;
+    target
+        .generate(&program)
+        .map_err(|message| vec![Error::CodegenTarget(target.name().to_string(), message)])
+}
+
+/// Compiles the sources as an executable and describes the program generated for their entry
+/// point, for the given number of shots if known.
+pub fn get_manifest(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+    shots: Option<u32>,
+) -> Result<Manifest, Vec<Error>> {
+    let program = get_rir(sources, language_features, capabilities)?;
+    Ok(Manifest::new(&program, shots))
+}
+
+fn get_rir(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    capabilities: TargetCapabilityFlags,
+) -> Result<qsc_rir::rir::Program, Vec<Error>> {
     if capabilities == TargetCapabilityFlags::all() {
         return Err(vec![Error::UnsupportedRuntimeCapabilities]);
     }
-    let core = compile::core();
-    let mut package_store = PackageStore::new(core);
-    let std = compile::std(&package_store, capabilities);
-    let std = package_store.insert(std);
+    let (mut package_store, std) = compile::shared_store(capabilities, Some(StdFeatures::all()));
+    let std = std.expect("package store should have std");
 
     let (unit, errors) = crate::compile::compile(
         &package_store,
@@ -69,7 +175,7 @@ pub fn get_qir(
             },
         )?;
 
-    fir_to_qir(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|e| {
+    fir_to_rir(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|e| {
         let source_package_id = match e.span() {
             Some(span) => span.package,
             None => package_id,
@@ -0,0 +1,242 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Manifests that describe a generated program to the services it is submitted to, so that they
+//! can validate a job before queueing it. The manifest is read from the program that partial
+//! evaluation produced, so it has the values that were folded to constants, such as the number
+//! of qubits, rather than the program as it was written.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_data_structures::target::TargetCapabilityFlags;
+use qsc_rir::{
+    rir::{self, CallableType, Instruction, Literal, Operand},
+    utils::get_all_block_successors,
+};
+use serde_json::json;
+
+use crate::target::Profile;
+
+/// The values that one shot of a program records, in the order it records them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Output {
+    Result,
+    Bool,
+    Int,
+    Tuple(Vec<Output>),
+    Array(Vec<Output>),
+}
+
+impl Output {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Output::Result => json!("result"),
+            Output::Bool => json!("bool"),
+            Output::Int => json!("int"),
+            Output::Tuple(items) => {
+                json!({ "tuple": items.iter().map(Output::to_json).collect::<Vec<_>>() })
+            }
+            Output::Array(items) => {
+                json!({ "array": items.iter().map(Output::to_json).collect::<Vec<_>>() })
+            }
+        }
+    }
+}
+
+/// The requirements of a generated program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    /// The capabilities that the instructions of the program use, which can be fewer than the
+    /// capabilities it was compiled for.
+    pub capabilities: TargetCapabilityFlags,
+    /// The number of qubits the program uses.
+    pub num_qubits: u32,
+    /// The number of measurement results the program stores.
+    pub num_results: u32,
+    /// The number of shots the job asks for, if known.
+    pub shots: Option<u32>,
+    /// The types of the parameters of the entry point.
+    pub parameters: Vec<rir::Ty>,
+    /// The values each shot records.
+    pub output: Vec<Output>,
+}
+
+impl Manifest {
+    #[must_use]
+    pub fn new(program: &rir::Program, shots: Option<u32>) -> Self {
+        let entry = program.get_callable(program.entry);
+        let mut capabilities = TargetCapabilityFlags::empty();
+        let mut records = Vec::new();
+        for block_id in entry_blocks(program) {
+            for instruction in &program.get_block(block_id).0 {
+                capabilities |= instruction_capabilities(program, block_id, instruction);
+                if let Instruction::Call(callable_id, args, _) = instruction {
+                    let callable = program.get_callable(*callable_id);
+                    if callable.call_type == CallableType::OutputRecording {
+                        records.push((callable.name.as_str(), args.as_slice()));
+                    }
+                }
+            }
+        }
+
+        let mut records = records.into_iter();
+        let mut output = Vec::new();
+        while let Some(record) = parse_output(record_kind(records.next()), &mut records) {
+            output.push(record);
+        }
+
+        Self {
+            capabilities,
+            num_qubits: program.num_qubits,
+            num_results: program.num_results,
+            shots,
+            parameters: entry.input_type.clone(),
+            output,
+        }
+    }
+
+    /// The most restrictive profile that has the capabilities the program uses.
+    #[must_use]
+    pub fn profile(&self) -> Profile {
+        [Profile::Base, Profile::AdaptiveRI]
+            .into_iter()
+            .find(|&profile| TargetCapabilityFlags::from(profile).contains(self.capabilities))
+            .unwrap_or(Profile::Unrestricted)
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "profile": self.profile().to_str(),
+            "capabilities": self.capabilities.iter_names().map(|(name, _)| name).collect::<Vec<_>>(),
+            "numQubits": self.num_qubits,
+            "numResults": self.num_results,
+            "shots": self.shots,
+            "parameters": self.parameters.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "output": self.output.iter().map(Output::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn entry_blocks(program: &rir::Program) -> Vec<rir::BlockId> {
+    let Some(entry) = program.get_callable(program.entry).body else {
+        return Vec::new();
+    };
+    let mut blocks = vec![entry];
+    blocks.extend(
+        get_all_block_successors(entry, program)
+            .into_iter()
+            .filter(|&block| block != entry),
+    );
+    blocks
+}
+
+fn instruction_capabilities(
+    program: &rir::Program,
+    block_id: rir::BlockId,
+    instruction: &Instruction,
+) -> TargetCapabilityFlags {
+    let branch_to = |target: rir::BlockId| {
+        if target <= block_id {
+            TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::BackwardsBranching
+        } else {
+            TargetCapabilityFlags::empty()
+        }
+    };
+    match instruction {
+        Instruction::Call(callable_id, _, variable) => {
+            let callable = program.get_callable(*callable_id);
+            let call = match callable.call_type {
+                CallableType::Reset => TargetCapabilityFlags::QubitReset,
+                CallableType::Readout => TargetCapabilityFlags::Adaptive,
+                CallableType::Measurement if callable.name.contains("mresetz") => {
+                    TargetCapabilityFlags::QubitReset
+                }
+                _ => TargetCapabilityFlags::empty(),
+            };
+            call | variable.map_or(TargetCapabilityFlags::empty(), ty_capabilities)
+        }
+        Instruction::Jump(target) => branch_to(*target),
+        Instruction::Branch(_, if_true, if_false) => {
+            TargetCapabilityFlags::Adaptive | branch_to(*if_true) | branch_to(*if_false)
+        }
+        Instruction::Return => TargetCapabilityFlags::empty(),
+        Instruction::Add(_, _, variable)
+        | Instruction::Sub(_, _, variable)
+        | Instruction::Mul(_, _, variable)
+        | Instruction::Sdiv(_, _, variable)
+        | Instruction::Srem(_, _, variable)
+        | Instruction::Shl(_, _, variable)
+        | Instruction::Ashr(_, _, variable)
+        | Instruction::Icmp(_, _, _, variable)
+        | Instruction::BitwiseNot(_, variable)
+        | Instruction::BitwiseAnd(_, _, variable)
+        | Instruction::BitwiseOr(_, _, variable)
+        | Instruction::BitwiseXor(_, _, variable) => {
+            TargetCapabilityFlags::Adaptive
+                | TargetCapabilityFlags::IntegerComputations
+                | ty_capabilities(*variable)
+        }
+        Instruction::Store(_, variable)
+        | Instruction::LogicalNot(_, variable)
+        | Instruction::LogicalAnd(_, _, variable)
+        | Instruction::LogicalOr(_, _, variable)
+        | Instruction::Phi(_, variable) => {
+            TargetCapabilityFlags::Adaptive | ty_capabilities(*variable)
+        }
+    }
+}
+
+fn ty_capabilities(variable: rir::Variable) -> TargetCapabilityFlags {
+    match variable.ty {
+        rir::Ty::Integer => {
+            TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::IntegerComputations
+        }
+        rir::Ty::Double => {
+            TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::FloatingPointComputations
+        }
+        rir::Ty::Boolean => TargetCapabilityFlags::Adaptive,
+        rir::Ty::Qubit | rir::Ty::Result | rir::Ty::Pointer => TargetCapabilityFlags::empty(),
+    }
+}
+
+enum RecordKind {
+    Value(Output),
+    Tuple(usize),
+    Array(usize),
+}
+
+fn record_kind(record: Option<(&str, &[Operand])>) -> Option<RecordKind> {
+    let (name, args) = record?;
+    let len = || match args.first() {
+        Some(Operand::Literal(Literal::Integer(len))) => usize::try_from(*len).ok(),
+        _ => None,
+    };
+    match name {
+        "__quantum__rt__result_record_output" => Some(RecordKind::Value(Output::Result)),
+        "__quantum__rt__bool_record_output" => Some(RecordKind::Value(Output::Bool)),
+        "__quantum__rt__int_record_output" => Some(RecordKind::Value(Output::Int)),
+        "__quantum__rt__tuple_record_output" => len().map(RecordKind::Tuple),
+        "__quantum__rt__array_record_output" => len().map(RecordKind::Array),
+        _ => None,
+    }
+}
+
+/// Builds the output of one record, reading the records of the items of tuples and arrays that
+/// follow it.
+fn parse_output<'a>(
+    kind: Option<RecordKind>,
+    records: &mut impl Iterator<Item = (&'a str, &'a [Operand])>,
+) -> Option<Output> {
+    let mut items = |len: usize| {
+        (0..len)
+            .map_while(|_| parse_output(record_kind(records.next()), records))
+            .collect()
+    };
+    match kind? {
+        RecordKind::Value(output) => Some(output),
+        RecordKind::Tuple(len) => Some(Output::Tuple(items(len))),
+        RecordKind::Array(len) => Some(Output::Array(items(len))),
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Manifest, Output};
+use crate::{codegen::get_manifest, target::Profile};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+use qsc_rir::builder;
+
+#[test]
+fn bell_program_needs_base_profile() {
+    let manifest = Manifest::new(&builder::bell_program(), Some(100));
+    assert_eq!(manifest.capabilities, TargetCapabilityFlags::empty());
+    assert_eq!(manifest.profile(), Profile::Base);
+    expect![[r#"
+        {
+          "capabilities": [],
+          "numQubits": 2,
+          "numResults": 2,
+          "output": [
+            {
+              "array": [
+                "result",
+                "result"
+              ]
+            }
+          ],
+          "parameters": [],
+          "profile": "Base",
+          "shots": 100
+        }"#]]
+    .assert_eq(
+        &serde_json::to_string_pretty(&manifest.to_json()).expect("manifest should serialize"),
+    );
+}
+
+#[test]
+fn teleport_program_needs_branching_and_reset() {
+    let manifest = Manifest::new(&builder::teleport_program(), None);
+    assert_eq!(
+        manifest.capabilities,
+        TargetCapabilityFlags::Adaptive | TargetCapabilityFlags::QubitReset
+    );
+    assert_eq!(manifest.profile(), Profile::AdaptiveRI);
+    assert_eq!(manifest.num_qubits, 3);
+    assert_eq!(manifest.output, [Output::Result]);
+    assert_eq!(
+        manifest.to_json()["capabilities"],
+        serde_json::json!(["Adaptive", "QubitReset"])
+    );
+    assert!(manifest.to_json()["shots"].is_null());
+}
+
+#[test]
+fn manifest_has_folded_values_of_the_program() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Result[], Int) {
+                let n = 3;
+                use qs = Qubit[n];
+                let rs = MResetEachZ(qs);
+                mutable count = 0;
+                for r in rs {
+                    if r == One {
+                        set count += 1;
+                    }
+                }
+                (rs, count)
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let manifest = get_manifest(
+        sources,
+        LanguageFeatures::default(),
+        Profile::AdaptiveRI.into(),
+        None,
+    )
+    .expect("manifest should be generated");
+    assert_eq!(manifest.num_qubits, 3);
+    assert_eq!(manifest.num_results, 3);
+    assert_eq!(manifest.profile(), Profile::AdaptiveRI);
+    assert!(manifest
+        .capabilities
+        .contains(TargetCapabilityFlags::IntegerComputations));
+    assert_eq!(
+        manifest.output,
+        [Output::Tuple(vec![
+            Output::Array(vec![Output::Result, Output::Result, Output::Result]),
+            Output::Int,
+        ])]
+    );
+}
@@ -5,7 +5,7 @@ use expect_test::expect;
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::compile::SourceMap;
 
-use crate::codegen::get_qir;
+use crate::codegen::{get_kernels, get_qir};
 
 #[test]
 fn code_with_errors_returns_errors() {
@@ -689,3 +689,83 @@ mod adaptive_ri_profile {
         "#]].assert_eq(&qir);
     }
 }
+
+#[test]
+fn each_callable_is_compiled_to_a_kernel() {
+    let source = "namespace Test {
+            operation Bell() : (Result, Result) {
+                use (q0, q1) = (Qubit(), Qubit());
+                H(q0);
+                CNOT(q0, q1);
+                (MResetZ(q0), MResetZ(q1))
+            }
+            operation Flip() : Result {
+                use q = Qubit();
+                X(q);
+                MResetZ(q)
+            }
+        }";
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let kernels = get_kernels(
+        &sources,
+        LanguageFeatures::default(),
+        TargetCapabilityFlags::empty(),
+        &["Test.Bell".to_string(), "Test.Flip".to_string()],
+    )
+    .expect("kernels should compile");
+
+    let symbols = kernels
+        .iter()
+        .map(|kernel| kernel.symbol.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, ["Test_Bell", "Test_Flip"]);
+    for kernel in &kernels {
+        let definition = format!("define void @{}() #0 {{", kernel.symbol);
+        assert!(kernel.qir.contains(&definition), "{}", kernel.qir);
+        assert!(!kernel.qir.contains("ENTRYPOINT__main"), "{}", kernel.qir);
+    }
+    assert!(kernels[0].qir.contains("__quantum__qis__cx__body"));
+    assert!(!kernels[1].qir.contains("__quantum__qis__cx__body"));
+}
+
+#[test]
+fn kernel_name_must_be_a_callable_path() {
+    let sources = SourceMap::new([("test.qs".into(), "namespace Test {}".into())], None);
+    let errors = get_kernels(
+        &sources,
+        LanguageFeatures::default(),
+        TargetCapabilityFlags::empty(),
+        &["Test.Main(); Test.Other".to_string()],
+    )
+    .expect_err("the name should be rejected");
+    expect![[r#"
+        [
+            InvalidKernelName(
+                "Test.Main(); Test.Other",
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
+
+#[test]
+fn kernels_with_the_same_symbol_are_rejected() {
+    let sources = SourceMap::new([("test.qs".into(), "namespace Test {}".into())], None);
+    let errors = get_kernels(
+        &sources,
+        LanguageFeatures::default(),
+        TargetCapabilityFlags::empty(),
+        &["A_B.Run".to_string(), "A.B_Run".to_string()],
+    )
+    .expect_err("the names should be rejected");
+    expect![[r#"
+        [
+            KernelSymbolCollision(
+                "A_B.Run",
+                "A.B_Run",
+                "A_B_Run",
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
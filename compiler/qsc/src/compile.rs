@@ -1,18 +1,27 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
+use crate::{cache::DiskCache, precompiled};
 use miette::{Diagnostic, Report};
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, SourceMap},
+    compile::{CompileUnit, PackageStore, SourceMap, StdFeatures},
     error::WithSource,
 };
 use qsc_hir::hir::PackageId;
-use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use qsc_passes::{run_analysis_passes, run_core_passes, run_default_passes, PackageType};
+use rustc_hash::FxHashMap;
+use std::{cell::RefCell, rc::Rc};
 use thiserror::Error;
 
 pub type Error = WithSource<ErrorKind>;
 
+/// A warning from the passes, which does not stop compilation.
+pub type Warning = WithSource<qsc_passes::Warning>;
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 #[diagnostic(transparent)]
 #[error(transparent)]
@@ -75,6 +84,44 @@ pub fn compile(
     process_compile_unit(store, package_type, unit)
 }
 
+/// Compiles a package from its source representation like [`compile`], but does not stop at the
+/// first phase that reports errors. The returned unit always has a best-effort typed HIR, with
+/// error nodes where the source could not be resolved or type checked, and the errors include the
+/// diagnostics of every phase that can run on such a package, like the borrow checker. This is
+/// meant for tools like the language service that serve features on code that is being edited.
+///
+/// When the frontend reports no errors, this is the same as [`compile`].
+#[must_use]
+pub fn compile_partial(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: TargetCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> (CompileUnit, Vec<Error>) {
+    let mut unit = qsc_frontend::compile::compile(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+    );
+    if unit.errors.is_empty() {
+        return process_compile_unit(store, package_type, unit);
+    }
+
+    // The transforming passes assume a package without errors, so only the analyses run.
+    let mut errors = Vec::new();
+    for error in unit.errors.drain(..) {
+        errors.push(WithSource::from_map(&unit.sources, error.into()));
+    }
+    for error in run_analysis_passes(&unit) {
+        errors.push(WithSource::from_map(&unit.sources, error.into()));
+    }
+    (unit, errors)
+}
+
 #[must_use]
 #[allow(clippy::module_name_repetitions)]
 fn process_compile_unit(
@@ -124,7 +171,21 @@ pub fn core() -> CompileUnit {
 /// Panics if the standard library does not compile without errors.
 #[must_use]
 pub fn std(store: &PackageStore, capabilities: TargetCapabilityFlags) -> CompileUnit {
-    let mut unit = qsc_frontend::compile::std(store, capabilities);
+    std_with_features(store, capabilities, StdFeatures::all())
+}
+
+/// Compiles the standard library with only the selected optional feature groups.
+///
+/// # Panics
+///
+/// Panics if the standard library does not compile without errors.
+#[must_use]
+pub fn std_with_features(
+    store: &PackageStore,
+    capabilities: TargetCapabilityFlags,
+    features: StdFeatures,
+) -> CompileUnit {
+    let mut unit = qsc_frontend::compile::std_with_features(store, capabilities, features);
     let pass_errors = run_default_passes(store.core(), &mut unit, PackageType::Lib);
     if pass_errors.is_empty() {
         unit
@@ -137,3 +198,72 @@ pub fn std(store: &PackageStore, capabilities: TargetCapabilityFlags) -> Compile
         panic!("could not compile standard library")
     }
 }
+
+thread_local! {
+    /// Core and the standard libraries compiled on this thread. The HIR is not thread safe, so
+    /// each thread compiles its own.
+    static LIBRARIES: RefCell<Libraries> = RefCell::default();
+}
+
+#[derive(Default)]
+struct Libraries {
+    core: Option<Rc<CompileUnit>>,
+    std: FxHashMap<(TargetCapabilityFlags, StdFeatures), Rc<CompileUnit>>,
+}
+
+/// Creates a package store with core and, unless `std_features` is `None`, a standard library
+/// with the selected feature groups. Core and each standard library are compiled the first time
+/// that a store needs them on the current thread, and are then shared by the stores created on
+/// the thread after that, so that a host that creates many interpreters compiles them once.
+///
+/// Returns the store along with the ID of the standard library, if any.
+///
+/// # Panics
+///
+/// Panics if the core library or the standard library does not compile without errors.
+#[must_use]
+pub fn shared_store(
+    capabilities: TargetCapabilityFlags,
+    std_features: Option<StdFeatures>,
+) -> (PackageStore, Option<PackageId>) {
+    shared_store_with(capabilities, std_features, std_with_features)
+}
+
+/// Creates a package store like [`shared_store`], except that a standard library that was not
+/// yet compiled on the current thread is loaded from the cache, or compiled and saved to it, as
+/// by [`precompiled::std`]. Hosts that start many processes pay for compiling it only once.
+///
+/// # Panics
+///
+/// Panics if the core library or the standard library does not compile without errors.
+#[must_use]
+pub fn shared_store_cached(
+    capabilities: TargetCapabilityFlags,
+    std_features: Option<StdFeatures>,
+    cache: &DiskCache,
+) -> (PackageStore, Option<PackageId>) {
+    shared_store_with(
+        capabilities,
+        std_features,
+        |store, capabilities, features| precompiled::std(store, capabilities, features, cache),
+    )
+}
+
+fn shared_store_with(
+    capabilities: TargetCapabilityFlags,
+    std_features: Option<StdFeatures>,
+    compile_std: impl FnOnce(&PackageStore, TargetCapabilityFlags, StdFeatures) -> CompileUnit,
+) -> (PackageStore, Option<PackageId>) {
+    LIBRARIES.with_borrow_mut(|libraries| {
+        let core = libraries.core.get_or_insert_with(|| Rc::new(core()));
+        let mut store = PackageStore::with_shared_core(core.clone());
+        let std = std_features.map(|features| {
+            let std = libraries
+                .std
+                .entry((capabilities, features))
+                .or_insert_with(|| Rc::new(compile_std(&store, capabilities, features)));
+            store.insert_shared(std.clone())
+        });
+        (store, std)
+    })
+}
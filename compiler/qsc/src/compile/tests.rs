@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    compile, compile_partial, core, shared_store, shared_store_cached, CompileUnit, Error,
+    ErrorKind,
+};
+use crate::cache::DiskCache;
+use indoc::indoc;
+use miette::Diagnostic;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{PackageStore, SourceMap, StdFeatures};
+use qsc_hir::hir::{ItemKind, PackageId};
+use qsc_passes::PackageType;
+
+type CompileFn = fn(
+    &PackageStore,
+    &[PackageId],
+    SourceMap,
+    PackageType,
+    TargetCapabilityFlags,
+    LanguageFeatures,
+) -> (CompileUnit, Vec<Error>);
+
+fn compile_with(compile_fn: CompileFn, source: &str) -> (CompileUnit, Vec<Error>) {
+    let mut store = PackageStore::new(core());
+    let std_id = store.insert(super::std(&store, TargetCapabilityFlags::all()));
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    compile_fn(
+        &store,
+        &[std_id],
+        sources,
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+}
+
+fn codes(errors: &[Error]) -> Vec<String> {
+    errors
+        .iter()
+        .map(|error| error.code().expect("error should have a code").to_string())
+        .collect()
+}
+
+const BROKEN: &str = indoc! {"
+    namespace Test {
+        function Foo() : Unit {
+            let x = 1;
+            set x = 2;
+            let y : Int = true;
+        }
+    }
+"};
+
+#[test]
+fn partial_reports_pass_errors_alongside_frontend_errors() {
+    let (_, errors) = compile_with(compile_partial, BROKEN);
+    assert_eq!(
+        codes(&errors),
+        vec!["Qsc.TypeCk.TyMismatch", "Qsc.BorrowCk.Mutability"]
+    );
+}
+
+#[test]
+fn full_compile_stops_at_frontend_errors() {
+    let (_, errors) = compile_with(compile, BROKEN);
+    assert_eq!(codes(&errors), vec!["Qsc.TypeCk.TyMismatch"]);
+}
+
+#[test]
+fn partial_keeps_items_of_broken_package() {
+    let (unit, errors) = compile_with(compile_partial, BROKEN);
+    assert!(!errors.is_empty());
+    assert!(unit.package.items.values().any(|item| matches!(
+        &item.kind,
+        ItemKind::Callable(decl) if decl.name.name.as_ref() == "Foo"
+    )));
+}
+
+#[test]
+fn partial_runs_all_passes_without_frontend_errors() {
+    let source = indoc! {"
+        namespace Test {
+            function Foo() : Unit {
+                use q = Qubit();
+            }
+        }
+    "};
+    let (_, errors) = compile_with(compile_partial, source);
+    assert!(!errors.is_empty());
+    assert!(errors
+        .iter()
+        .all(|error| matches!(error.error(), ErrorKind::Pass(_))));
+    assert_eq!(codes(&errors), codes(&compile_with(compile, source).1));
+}
+
+fn unit(store: &PackageStore, id: PackageId) -> &CompileUnit {
+    store.get(id).expect("package should be in store")
+}
+
+#[test]
+fn shared_store_compiles_libraries_once_per_thread() {
+    let (first, first_std) = shared_store(TargetCapabilityFlags::all(), Some(StdFeatures::all()));
+    let (second, second_std) = shared_store(TargetCapabilityFlags::all(), Some(StdFeatures::all()));
+    let first_std = first_std.expect("store should have std");
+    let second_std = second_std.expect("store should have std");
+    assert!(std::ptr::eq(
+        unit(&first, first_std),
+        unit(&second, second_std)
+    ));
+    assert!(std::ptr::eq(
+        unit(&first, PackageId::CORE),
+        unit(&second, PackageId::CORE)
+    ));
+
+    let (other, other_std) = shared_store(TargetCapabilityFlags::empty(), Some(StdFeatures::all()));
+    let other_std = other_std.expect("store should have std");
+    assert!(!std::ptr::eq(
+        unit(&first, first_std),
+        unit(&other, other_std)
+    ));
+    assert!(std::ptr::eq(
+        unit(&first, PackageId::CORE),
+        unit(&other, PackageId::CORE)
+    ));
+}
+
+#[test]
+fn shared_store_without_std_has_only_core() {
+    let (store, std) = shared_store(TargetCapabilityFlags::all(), None);
+    assert!(std.is_none());
+    assert_eq!(store.iter().count(), 1);
+}
+
+#[test]
+fn packages_compile_against_shared_std() {
+    let (store, std) = shared_store(TargetCapabilityFlags::all(), Some(StdFeatures::all()));
+    let std = std.expect("store should have std");
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            "namespace Test { function Pi() : Double { Microsoft.Quantum.Math.PI() } }".into(),
+        )],
+        None,
+    );
+    let (_, errors) = compile(
+        &store,
+        &[std],
+        sources,
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn shared_store_cached_loads_std_saved_by_another_thread() {
+    let dir = std::env::temp_dir().join(format!("qsc-compile-test-{}-shared", std::process::id()));
+    DiskCache::new(&dir)
+        .clear()
+        .expect("cache should be cleared");
+    // Each thread has its own libraries, so each of these threads needs the standard library anew.
+    let std_has_ast = || {
+        let dir = dir.clone();
+        std::thread::spawn(move || {
+            let (store, std) = shared_store_cached(
+                TargetCapabilityFlags::all(),
+                Some(StdFeatures::all()),
+                &DiskCache::new(dir),
+            );
+            let std = std.expect("store should have std");
+            !unit(&store, std).ast.package.nodes.is_empty()
+        })
+        .join()
+        .expect("thread should not panic")
+    };
+    assert!(std_has_ast(), "first thread should compile std");
+    assert!(!std_has_ast(), "second thread should load std from cache");
+}
@@ -0,0 +1,161 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Completions for the word before the cursor in a line of input, such as for tab completion in
+//! a REPL. Unlike the completions of the language service, these only depend on the names that
+//! are in scope for the next fragment of an interactive session, so they do not need the line to
+//! be parsed.
+
+#[cfg(test)]
+mod tests;
+
+use crate::symbols::{SymbolIndex, SymbolKind};
+use qsc_data_structures::namespaces::PRELUDE;
+use qsc_eval::VariableInfo;
+use rustc_hash::FxHashSet;
+use std::rc::Rc;
+
+/// The kind of a completion. Completions are ordered by kind, and then by label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompletionKind {
+    Variable,
+    Function,
+    Operation,
+    Type,
+    Namespace,
+    Keyword,
+}
+
+impl From<SymbolKind> for CompletionKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Namespace => Self::Namespace,
+            SymbolKind::Function => Self::Function,
+            SymbolKind::Operation => Self::Operation,
+            SymbolKind::Type => Self::Type,
+        }
+    }
+}
+
+/// A name that completes the word before the cursor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompletionItem {
+    /// The text that replaces the part of the word after its last `.`.
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The signature of a callable or type, the type of a variable, or the full name of a
+    /// namespace. Keywords have no detail.
+    pub detail: Option<String>,
+}
+
+/// Completes the word before the cursor, which is a byte offset into the line. A word that
+/// contains a `.` is completed with the members of the namespace before the last `.`, which can
+/// also be the alias of an open namespace. Other words are completed with the locals, the items
+/// of the prelude and of the open namespaces, the items declared outside of a namespace, the
+/// top-level namespaces and the keywords. Names match when they start with the rest of the word,
+/// ignoring case.
+#[must_use]
+pub fn completions(
+    line: &str,
+    cursor: usize,
+    symbols: &SymbolIndex,
+    opens: &[(String, Option<Rc<str>>)],
+    locals: &[VariableInfo],
+) -> Vec<CompletionItem> {
+    let word = word_before(line, cursor);
+    let mut items = Vec::new();
+    if let Some((qualifier, partial)) = word.rsplit_once('.') {
+        let namespace = opens
+            .iter()
+            .find(|(_, alias)| alias.as_deref() == Some(qualifier))
+            .map_or(qualifier, |(namespace, _)| namespace.as_str());
+        items.extend(
+            symbols
+                .symbols()
+                .iter()
+                .filter(|symbol| &*symbol.namespace == namespace && matches(&symbol.name, partial))
+                .map(|symbol| CompletionItem {
+                    label: symbol.name.to_string(),
+                    kind: symbol.kind.into(),
+                    detail: Some(symbol.signature.clone()),
+                }),
+        );
+    } else {
+        let namespaces = PRELUDE
+            .iter()
+            .map(|namespace| namespace.join("."))
+            .chain(
+                opens
+                    .iter()
+                    .filter(|(_, alias)| alias.is_none())
+                    .map(|(namespace, _)| namespace.clone()),
+            )
+            .collect::<FxHashSet<_>>();
+        items.extend(
+            locals
+                .iter()
+                .filter(|local| matches(&local.name, word))
+                .map(|local| CompletionItem {
+                    label: local.name.to_string(),
+                    kind: CompletionKind::Variable,
+                    detail: Some(local.type_name.clone()),
+                }),
+        );
+        items.extend(
+            symbols
+                .symbols()
+                .iter()
+                .filter(|symbol| {
+                    // Namespaces are only in scope at the top level, and other items when their
+                    // namespace is open.
+                    let in_scope = if symbol.kind == SymbolKind::Namespace {
+                        symbol.namespace.is_empty()
+                    } else {
+                        symbol.namespace.is_empty()
+                            || namespaces.contains(symbol.namespace.as_ref())
+                    };
+                    in_scope && matches(&symbol.name, word)
+                })
+                .map(|symbol| CompletionItem {
+                    label: symbol.name.to_string(),
+                    kind: symbol.kind.into(),
+                    detail: Some(symbol.signature.clone()),
+                }),
+        );
+        items.extend(
+            qsc_parse::grammar::keywords()
+                .filter(|keyword| matches(keyword, word))
+                .map(|keyword| CompletionItem {
+                    label: keyword.to_string(),
+                    kind: CompletionKind::Keyword,
+                    detail: None,
+                }),
+        );
+    }
+
+    // The same name can be declared by more than one package, or again in a later fragment.
+    items.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.label.cmp(&b.label)));
+    items.dedup_by(|a, b| a.kind == b.kind && a.label == b.label);
+    items
+}
+
+/// The identifier characters and dots that come directly before the cursor.
+fn word_before(line: &str, cursor: usize) -> &str {
+    let mut end = cursor.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let before = &line[..end];
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.')
+        .last()
+        .map_or(end, |(index, _)| index);
+    &before[start..]
+}
+
+fn matches(name: &str, partial: &str) -> bool {
+    name.get(..partial.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(partial))
+}
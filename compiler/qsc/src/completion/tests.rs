@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{CompletionItem, CompletionKind};
+use crate::{incremental::Compiler, PackageType, SourceMap};
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+
+fn compiler(fragments: &str) -> Compiler {
+    let mut compiler = Compiler::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("compiler should be created");
+    if !fragments.is_empty() {
+        let increment = compiler
+            .compile_fragments_fail_fast("fragments", fragments)
+            .expect("fragments should compile");
+        compiler.update(increment);
+    }
+    compiler
+}
+
+fn labels(items: &[CompletionItem], kind: CompletionKind) -> Vec<&str> {
+    items
+        .iter()
+        .filter(|item| item.kind == kind)
+        .map(|item| item.label.as_str())
+        .collect()
+}
+
+#[test]
+fn keywords_are_completed() {
+    let items = compiler("").completions("mut", 3);
+    assert_eq!(labels(&items, CompletionKind::Keyword), ["mutable"]);
+    assert_eq!(items.last().and_then(|item| item.detail.as_ref()), None);
+}
+
+#[test]
+fn prelude_callables_are_completed_with_signatures() {
+    let items = compiler("").completions("mresetz", 7);
+    let item = items
+        .iter()
+        .find(|item| item.label == "MResetZ")
+        .expect("MResetZ should be completed");
+    assert_eq!(item.kind, CompletionKind::Operation);
+    assert!(
+        item.detail
+            .as_deref()
+            .is_some_and(|detail| detail.starts_with("operation MResetZ")),
+        "{item:?}"
+    );
+}
+
+#[test]
+fn callables_from_closed_namespaces_are_not_completed() {
+    let items = compiler("").completions("Reverse", 7);
+    assert_eq!(labels(&items, CompletionKind::Function), Vec::<&str>::new());
+}
+
+#[test]
+fn qualified_names_are_completed_with_members_of_the_namespace() {
+    let compiler = compiler("");
+    let items = compiler.completions("Microsoft.Quantum.Arr", 21);
+    assert_eq!(labels(&items, CompletionKind::Namespace), ["Arrays"]);
+    let items = compiler.completions("Microsoft.Quantum.Arrays.Reversed", 33);
+    assert_eq!(labels(&items, CompletionKind::Function), ["Reversed"]);
+}
+
+#[test]
+fn fragments_add_items_and_opens() {
+    let compiler = compiler(indoc::indoc! {"
+        function Forty() : Int { 40 }
+        open Microsoft.Quantum.Arrays;
+        open Microsoft.Quantum.Math as M;
+    "});
+    let items = compiler.completions("let x = Fort", 12);
+    assert_eq!(labels(&items, CompletionKind::Function), ["Forty"]);
+    let items = compiler.completions("Revers(", 6);
+    assert_eq!(labels(&items, CompletionKind::Function), ["Reversed"]);
+    let items = compiler.completions("M.AbsI", 6);
+    assert_eq!(labels(&items, CompletionKind::Function), ["AbsI"]);
+}
+
+#[test]
+fn word_ends_at_the_cursor() {
+    let items = compiler("").completions("mutable x = 1;", 3);
+    assert_eq!(labels(&items, CompletionKind::Keyword), ["mutable"]);
+    assert!(compiler("").completions("é", 1).len() > 1);
+}
@@ -0,0 +1,395 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A suite of semantic tests for the standard library that runs against any simulator backend.
+//! Alternative backends, such as stabilizer or GPU simulators, run the suite to show that programs
+//! behave on them as they do on the sparse simulator before they are offered to users.
+//!
+//! Every case is an entry expression with a deterministic result, so the suite compares the
+//! formatted result against the expected value rather than comparing state vectors, which
+//! backends are not required to expose.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::{backend::Backend, val};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+use crate::{
+    interpret::{output::GenericReceiver, Interpreter},
+    target::Profile,
+};
+
+/// The gates a case needs, so that backends that only support some of them can run the cases
+/// they support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// Clifford gates, measurements and resets, which stabilizer simulators can run.
+    Clifford,
+    /// Gates outside the Clifford group, such as `T`, `CCNOT` and rotations.
+    NonClifford,
+    /// Library operations built on top of the intrinsics, such as arithmetic and state
+    /// preparation.
+    Library,
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Category::Clifford => "clifford",
+            Category::NonClifford => "non-clifford",
+            Category::Library => "library",
+        })
+    }
+}
+
+/// A single test in the suite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Case {
+    pub name: &'static str,
+    pub category: Category,
+    /// The entry expression, which has the standard library available.
+    pub expr: &'static str,
+    /// The expected result, formatted like the interpreter formats values.
+    pub expected: &'static str,
+}
+
+/// The cases in the suite.
+pub const CASES: &[Case] = &[
+    Case {
+        name: "x_flips_qubit",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); X(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "y_flips_qubit",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); Y(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "h_is_self_inverse",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); H(q); H(q); MResetZ(q) }",
+        expected: "Zero",
+    },
+    Case {
+        name: "h_conjugates_z_to_x",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); H(q); Z(q); H(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "s_squared_is_z",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); H(q); S(q); S(q); H(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "adjoint_s_undoes_s",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); H(q); S(q); Adjoint S(q); H(q); MResetZ(q) }",
+        expected: "Zero",
+    },
+    Case {
+        name: "cnot_flips_target_when_control_is_one",
+        category: Category::Clifford,
+        expr: "{ use (c, t) = (Qubit(), Qubit()); X(c); CNOT(c, t); (MResetZ(c), MResetZ(t)) }",
+        expected: "(One, One)",
+    },
+    Case {
+        name: "cnot_keeps_target_when_control_is_zero",
+        category: Category::Clifford,
+        expr: "{ use (c, t) = (Qubit(), Qubit()); CNOT(c, t); (MResetZ(c), MResetZ(t)) }",
+        expected: "(Zero, Zero)",
+    },
+    Case {
+        name: "cy_flips_target_when_control_is_one",
+        category: Category::Clifford,
+        expr: "{ use (c, t) = (Qubit(), Qubit()); X(c); CY(c, t); (MResetZ(c), MResetZ(t)) }",
+        expected: "(One, One)",
+    },
+    Case {
+        name: "cz_kicks_phase_back_to_control",
+        category: Category::Clifford,
+        expr: "{ use (c, t) = (Qubit(), Qubit()); H(c); X(t); CZ(c, t); H(c); X(t); MResetZ(c) }",
+        expected: "One",
+    },
+    Case {
+        name: "swap_exchanges_qubits",
+        category: Category::Clifford,
+        expr: "{ use (a, b) = (Qubit(), Qubit()); X(a); SWAP(a, b); (MResetZ(a), MResetZ(b)) }",
+        expected: "(Zero, One)",
+    },
+    Case {
+        name: "bell_pair_measurements_agree",
+        category: Category::Clifford,
+        expr: "{ use (a, b) = (Qubit(), Qubit()); H(a); CNOT(a, b); MResetZ(a) == MResetZ(b) }",
+        expected: "true",
+    },
+    Case {
+        name: "m_does_not_reset",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); X(q); let first = M(q); let second = M(q); Reset(q); (first, second) }",
+        expected: "(One, One)",
+    },
+    Case {
+        name: "reset_returns_qubit_to_zero",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); X(q); Reset(q); MResetZ(q) }",
+        expected: "Zero",
+    },
+    Case {
+        name: "measure_in_x_basis",
+        category: Category::Clifford,
+        expr: "{ use q = Qubit(); H(q); let r = Measure([PauliX], [q]); Reset(q); r }",
+        expected: "Zero",
+    },
+    Case {
+        name: "measure_joint_parity",
+        category: Category::Clifford,
+        expr: "{ use qs = Qubit[2]; X(qs[0]); X(qs[1]); let r = Measure([PauliZ, PauliZ], qs); ResetAll(qs); r }",
+        expected: "Zero",
+    },
+    Case {
+        name: "check_all_zero_after_uncomputation",
+        category: Category::Clifford,
+        expr: "{ use qs = Qubit[2]; H(qs[0]); CNOT(qs[0], qs[1]); CNOT(qs[0], qs[1]); H(qs[0]); Microsoft.Quantum.Diagnostics.CheckAllZero(qs) }",
+        expected: "true",
+    },
+    Case {
+        name: "t_to_the_fourth_is_z",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); H(q); T(q); T(q); T(q); T(q); H(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "adjoint_t_undoes_t",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); H(q); T(q); Adjoint T(q); H(q); MResetZ(q) }",
+        expected: "Zero",
+    },
+    Case {
+        name: "ccnot_needs_both_controls",
+        category: Category::NonClifford,
+        expr: "{ use (a, b, t) = (Qubit(), Qubit(), Qubit()); X(a); CCNOT(a, b, t); let first = M(t); X(b); CCNOT(a, b, t); let second = M(t); ResetAll([a, b, t]); (first, second) }",
+        expected: "(Zero, One)",
+    },
+    Case {
+        name: "controlled_x_with_many_controls",
+        category: Category::NonClifford,
+        expr: "{ use (cs, t) = (Qubit[3], Qubit()); ApplyToEach(X, cs); Controlled X(cs, t); let r = MResetZ(t); ResetAll(cs); r }",
+        expected: "One",
+    },
+    Case {
+        name: "rx_by_pi_flips_qubit",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); Rx(Microsoft.Quantum.Math.PI(), q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "ry_by_pi_flips_qubit",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); Ry(Microsoft.Quantum.Math.PI(), q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "rz_by_pi_flips_phase",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); H(q); Rz(Microsoft.Quantum.Math.PI(), q); H(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "r1_by_pi_flips_phase",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); H(q); R1(Microsoft.Quantum.Math.PI(), q); H(q); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "rxx_by_pi_flips_both_qubits",
+        category: Category::NonClifford,
+        expr: "{ use qs = Qubit[2]; Rxx(Microsoft.Quantum.Math.PI(), qs[0], qs[1]); (MResetZ(qs[0]), MResetZ(qs[1])) }",
+        expected: "(One, One)",
+    },
+    Case {
+        name: "rzz_by_pi_flips_both_phases",
+        category: Category::NonClifford,
+        expr: "{ use qs = Qubit[2]; ApplyToEach(H, qs); Rzz(Microsoft.Quantum.Math.PI(), qs[0], qs[1]); ApplyToEach(H, qs); (MResetZ(qs[0]), MResetZ(qs[1])) }",
+        expected: "(One, One)",
+    },
+    Case {
+        name: "exp_of_x_by_half_pi_flips_qubit",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); Exp([PauliX], Microsoft.Quantum.Math.PI() / 2.0, [q]); MResetZ(q) }",
+        expected: "One",
+    },
+    Case {
+        name: "controlled_ry_by_pi_flips_target",
+        category: Category::NonClifford,
+        expr: "{ use (c, t) = (Qubit(), Qubit()); X(c); Controlled Ry([c], (Microsoft.Quantum.Math.PI(), t)); let r = MResetZ(t); Reset(c); r }",
+        expected: "One",
+    },
+    Case {
+        name: "adjoint_rotations_undo_rotations",
+        category: Category::NonClifford,
+        expr: "{ use q = Qubit(); Rx(0.3, q); Ry(0.7, q); Rz(1.1, q); Adjoint Rz(1.1, q); Adjoint Ry(0.7, q); Adjoint Rx(0.3, q); Microsoft.Quantum.Diagnostics.CheckZero(q) }",
+        expected: "true",
+    },
+    Case {
+        name: "xor_in_place_encodes_integer",
+        category: Category::Library,
+        expr: "{ use qs = Qubit[4]; ApplyXorInPlace(11, qs); MeasureInteger(qs) }",
+        expected: "11",
+    },
+    Case {
+        name: "increment_wraps_around",
+        category: Category::Library,
+        expr: "{ use qs = Qubit[3]; ApplyXorInPlace(6, qs); Microsoft.Quantum.Unstable.Arithmetic.IncByI(3, qs); MeasureInteger(qs) }",
+        expected: "1",
+    },
+    Case {
+        name: "ripple_carry_adder_adds_registers",
+        category: Category::Library,
+        expr: "{ use (xs, ys) = (Qubit[3], Qubit[4]); ApplyXorInPlace(5, xs); ApplyXorInPlace(6, ys); Microsoft.Quantum.Unstable.Arithmetic.RippleCarryTTKIncByLE(xs, ys); (MeasureInteger(xs), MeasureInteger(ys)) }",
+        expected: "(5, 11)",
+    },
+    Case {
+        name: "qft_is_undone_by_its_adjoint",
+        category: Category::Library,
+        expr: "{ use qs = Qubit[3]; ApplyXorInPlace(5, qs); ApplyQFT(qs); Adjoint ApplyQFT(qs); MeasureInteger(qs) }",
+        expected: "5",
+    },
+    Case {
+        name: "prepare_pure_state_prepares_basis_state",
+        category: Category::Library,
+        expr: "{ use qs = Qubit[2]; Microsoft.Quantum.Unstable.StatePreparation.PreparePureStateD([0.0, 0.0, 0.0, 1.0], qs); MeasureInteger(qs) }",
+        expected: "3",
+    },
+];
+
+/// The cases in the given categories.
+pub fn cases(categories: &[Category]) -> impl Iterator<Item = &'static Case> + '_ {
+    CASES
+        .iter()
+        .filter(|case| categories.contains(&case.category))
+}
+
+/// The outcome of a case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// The case ran to completion with a different result from the expected one.
+    Mismatch {
+        actual: String,
+    },
+    /// The case failed to compile or to run.
+    Error(String),
+}
+
+/// The outcome of a single case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CaseReport {
+    pub case: Case,
+    pub outcome: Outcome,
+}
+
+/// The outcome of every case that was run, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub cases: Vec<CaseReport>,
+}
+
+impl Report {
+    /// Whether every case passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.cases
+            .iter()
+            .all(|case| case.outcome == Outcome::Passed)
+    }
+
+    /// The cases that did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseReport> {
+        self.cases
+            .iter()
+            .filter(|case| case.outcome != Outcome::Passed)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for CaseReport { case, outcome } in &self.cases {
+            match outcome {
+                Outcome::Passed => writeln!(f, "{} ({}): passed", case.name, case.category)?,
+                Outcome::Mismatch { actual } => {
+                    writeln!(f, "{} ({}): failed", case.name, case.category)?;
+                    writeln!(f, "  expected `{}`, found `{actual}`", case.expected)?;
+                }
+                Outcome::Error(message) => {
+                    writeln!(f, "{} ({}): failed", case.name, case.category)?;
+                    writeln!(f, "  {message}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs the cases, each on a new backend from `new_backend`. The standard library is compiled
+/// once and shared by every case. Backends must implement every gate the cases use, since an
+/// unimplemented gate panics, so backends that only implement some gates should run only the
+/// categories they support, as selected with [`cases`].
+#[must_use]
+pub fn run<'a, B>(
+    cases: impl IntoIterator<Item = &'a Case>,
+    mut new_backend: impl FnMut() -> B,
+) -> Report
+where
+    B: Backend,
+    B::ResultType: Into<val::Result>,
+{
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("standard library should compile");
+    let mut sink = std::io::sink();
+    let mut out = GenericReceiver::new(&mut sink);
+
+    let cases = cases
+        .into_iter()
+        .map(|case| {
+            let mut backend = new_backend();
+            let outcome = match interpreter.run_with_sim(&mut backend, &mut out, case.expr) {
+                Ok(Ok(value)) => {
+                    let actual = value.to_string();
+                    if actual == case.expected {
+                        Outcome::Passed
+                    } else {
+                        Outcome::Mismatch { actual }
+                    }
+                }
+                Ok(Err(errors)) | Err(errors) => Outcome::Error(
+                    errors
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                ),
+            };
+            CaseReport {
+                case: *case,
+                outcome,
+            }
+        })
+        .collect();
+    Report { cases }
+}
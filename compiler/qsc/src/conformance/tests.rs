@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{cases, run, Case, Category, Outcome, CASES};
+use crate::SparseSim;
+use expect_test::expect;
+
+#[test]
+fn sparse_simulator_passes_every_case() {
+    let report = run(CASES, SparseSim::new);
+    assert!(report.passed(), "{report}");
+    assert_eq!(report.cases.len(), CASES.len());
+}
+
+#[test]
+fn cases_are_selected_by_category() {
+    let selected = cases(&[Category::Clifford]).collect::<Vec<_>>();
+    assert!(!selected.is_empty());
+    assert!(selected
+        .iter()
+        .all(|case| case.category == Category::Clifford));
+    assert!(selected.len() < CASES.len());
+}
+
+#[test]
+fn case_names_are_unique() {
+    let mut names = CASES.iter().map(|case| case.name).collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), CASES.len());
+}
+
+#[test]
+fn mismatches_and_errors_are_reported() {
+    let cases = [
+        Case {
+            name: "wrong_expectation",
+            category: Category::Clifford,
+            expr: "{ use q = Qubit(); X(q); MResetZ(q) }",
+            expected: "Zero",
+        },
+        Case {
+            name: "does_not_compile",
+            category: Category::Clifford,
+            expr: "Undefined()",
+            expected: "()",
+        },
+    ];
+    let report = run(&cases, SparseSim::new);
+    assert!(!report.passed());
+    assert_eq!(report.failures().count(), 2);
+    assert_eq!(
+        report.cases[0].outcome,
+        Outcome::Mismatch {
+            actual: "One".to_string()
+        }
+    );
+    expect![[r#"
+        wrong_expectation (clifford): failed
+          expected `Zero`, found `One`
+    "#]]
+    .assert_eq(
+        &report
+            .to_string()
+            .lines()
+            .take(2)
+            .map(|line| format!("{line}\n"))
+            .collect::<String>(),
+    );
+    assert!(matches!(report.cases[1].outcome, Outcome::Error(_)));
+}
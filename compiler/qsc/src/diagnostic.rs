@@ -0,0 +1,61 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A registry of the diagnostic codes that have extended explanations, so that hosts can map the
+//! codes of structured diagnostics, such as those from [`crate::interpret::Error::to_structured`],
+//! to their documentation, and group or filter diagnostics by the part of the compiler that
+//! reports them.
+
+#[cfg(test)]
+mod tests;
+
+use crate::explain;
+
+/// A diagnostic code and its documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Code {
+    /// The code, such as `Qsc.Resolve.NotFound`.
+    pub code: &'static str,
+    /// The code without its last component, such as `Qsc.Resolve`, which is shared by the codes
+    /// that the same part of the compiler reports.
+    pub category: &'static str,
+    /// The first paragraph of the explanation.
+    pub summary: &'static str,
+    /// The extended explanation, formatted as Markdown.
+    pub explanation: &'static str,
+}
+
+impl Code {
+    fn new(code: &'static str) -> Option<Self> {
+        Some(Self {
+            code,
+            category: code.rsplit_once('.').map_or(code, |(category, _)| category),
+            summary: explain::summary(code)?,
+            explanation: explain::explain(code)?,
+        })
+    }
+}
+
+/// Every registered diagnostic code, in the order they are documented.
+pub fn codes() -> impl Iterator<Item = Code> {
+    explain::codes().filter_map(Code::new)
+}
+
+/// The registered diagnostic code, if there is one.
+#[must_use]
+pub fn lookup(code: &str) -> Option<Code> {
+    codes().find(|registered| registered.code == code)
+}
+
+/// The categories of the registered codes, without duplicates, in the order they are first
+/// documented.
+#[must_use]
+pub fn categories() -> Vec<&'static str> {
+    let mut categories = Vec::new();
+    for code in codes() {
+        if !categories.contains(&code.category) {
+            categories.push(code.category);
+        }
+    }
+    categories
+}
@@ -0,0 +1,33 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{categories, codes, lookup};
+use crate::explain;
+
+#[test]
+fn every_explained_code_is_registered() {
+    assert_eq!(codes().count(), explain::codes().count());
+    for code in codes() {
+        assert!(code.code.starts_with(code.category), "{code:?}");
+        assert!(code.explanation.starts_with(code.summary), "{code:?}");
+    }
+}
+
+#[test]
+fn lookup_finds_registered_codes() {
+    let code = lookup("Qsc.Resolve.NotFound").expect("code should be registered");
+    assert_eq!(code.category, "Qsc.Resolve");
+    assert_eq!(Some(code.summary), explain::summary("Qsc.Resolve.NotFound"));
+    assert!(lookup("Qsc.Resolve").is_none());
+    assert!(lookup("Qsc.Unknown.Code").is_none());
+}
+
+#[test]
+fn categories_are_unique() {
+    let categories = categories();
+    assert!(categories.contains(&"Qsc.Resolve"), "{categories:?}");
+    assert!(categories.contains(&"Qsc.Interpret"), "{categories:?}");
+    for (i, category) in categories.iter().enumerate() {
+        assert!(!categories[i + 1..].contains(category), "{category}");
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Documentation extracted from the doc comments of a compiled package, so that reference docs,
+//! such as those of the standard library, are generated from the source instead of being kept up
+//! to date by hand. Each public callable and type is rendered as JSON for tools and as Markdown
+//! for readers.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{stable_id::StableId, symbols::join};
+use qsc_doc_gen::display::{
+    hir_callable_signature, hir_udt_signature, increase_header_level, parse_doc_for_summary,
+};
+use qsc_hir::{
+    hir::{CallableKind, Item, ItemKind, Package, Visibility},
+    ty::FunctorSetValue,
+};
+use serde_json::json;
+use std::{fmt::Write, rc::Rc};
+
+/// The kind of a documented item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocKind {
+    Function,
+    Operation,
+    Type,
+}
+
+impl DocKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            DocKind::Function => "function",
+            DocKind::Operation => "operation",
+            DocKind::Type => "type",
+        }
+    }
+}
+
+/// The documentation of a public callable or type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocItem {
+    pub id: StableId,
+    /// The namespace the item is declared in.
+    pub namespace: Rc<str>,
+    pub name: Rc<str>,
+    pub kind: DocKind,
+    /// The declaration of the item, such as `function Length<'T>(a : 'T[]) : Int`.
+    pub signature: String,
+    /// The summary section of the doc comment, on one line.
+    pub summary: String,
+    /// The whole doc comment, as Markdown.
+    pub doc: Rc<str>,
+    /// The functors that the callable supports. Functions and types support none.
+    pub functors: FunctorSetValue,
+}
+
+impl DocItem {
+    /// The fully qualified name of the item.
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", self.namespace, self.name)
+    }
+
+    /// The relative path of the item's Markdown file, `<namespace>/<name>.md`.
+    #[must_use]
+    pub fn markdown_path(&self) -> String {
+        format!("{}/{}.md", self.namespace, self.name)
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": self.id.as_str(),
+            "namespace": self.namespace.as_ref(),
+            "name": self.name.as_ref(),
+            "kind": self.kind.as_str(),
+            "signature": self.signature,
+            "summary": self.summary,
+            "doc": self.doc.as_ref(),
+            "functors": functor_names(self.functors),
+        })
+    }
+
+    /// Renders the item as a Markdown page with the signature, the supported functors and the
+    /// doc comment, whose headers are moved down a level to go under the page title.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let title = match self.kind {
+            DocKind::Function => "function",
+            DocKind::Operation => "operation",
+            DocKind::Type => "user defined type",
+        };
+        let mut markdown = format!(
+            "# {} {title}\n\nNamespace: {}\n\n```qsharp\n{}\n```\n",
+            self.name, self.namespace, self.signature
+        );
+        let functors = functor_names(self.functors);
+        if !functors.is_empty() {
+            let _ = write!(markdown, "\nSupports: {}\n", functors.join(", "));
+        }
+        let doc = increase_header_level(&self.doc);
+        if !doc.is_empty() {
+            let _ = write!(markdown, "\n{doc}\n");
+        }
+        markdown
+    }
+}
+
+/// Extracts the documentation of the public callables and types declared in the namespaces of
+/// the package, ordered by namespace and then by name. Internal items and items nested in
+/// callables are left out, since they cannot be used from other packages.
+#[must_use]
+pub fn extract(package: &Package) -> Vec<DocItem> {
+    let mut items = package
+        .items
+        .values()
+        .filter(|item| item.visibility == Visibility::Public)
+        .filter_map(|item| doc_item(package, item))
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        a.namespace
+            .cmp(&b.namespace)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    items
+}
+
+/// Renders the items as a JSON array of the objects from [`DocItem::to_json`].
+#[must_use]
+pub fn to_json(items: &[DocItem]) -> serde_json::Value {
+    serde_json::Value::Array(items.iter().map(DocItem::to_json).collect())
+}
+
+fn doc_item(package: &Package, item: &Item) -> Option<DocItem> {
+    let namespace = match &package.items.get(item.parent?)?.kind {
+        ItemKind::Namespace(name, _) => join(name),
+        _ => return None,
+    };
+    let (name, kind, signature, functors) = match &item.kind {
+        ItemKind::Callable(decl) => (
+            decl.name.name.clone(),
+            match decl.kind {
+                CallableKind::Function => DocKind::Function,
+                CallableKind::Operation => DocKind::Operation,
+            },
+            hir_callable_signature(decl).to_string(),
+            decl.functors,
+        ),
+        ItemKind::Ty(name, udt) => (
+            name.name.clone(),
+            DocKind::Type,
+            hir_udt_signature(udt).to_string(),
+            FunctorSetValue::Empty,
+        ),
+        ItemKind::Namespace(..) => return None,
+    };
+
+    Some(DocItem {
+        id: StableId::new(package, item)?,
+        namespace: namespace.into(),
+        name,
+        kind,
+        signature,
+        summary: parse_doc_for_summary(&item.doc)
+            .replace("\r\n", " ")
+            .replace('\n', " "),
+        doc: item.doc.clone(),
+        functors,
+    })
+}
+
+fn functor_names(functors: FunctorSetValue) -> Vec<&'static str> {
+    match functors {
+        FunctorSetValue::Empty => vec![],
+        FunctorSetValue::Adj => vec!["Adj"],
+        FunctorSetValue::Ctl => vec!["Ctl"],
+        FunctorSetValue::CtlAdj => vec!["Adj", "Ctl"],
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{extract, to_json, DocItem, DocKind};
+use crate::{compile, PackageStore, SourceMap};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_hir::ty::FunctorSetValue;
+use qsc_passes::PackageType;
+
+fn docs(source: &str) -> Vec<DocItem> {
+    let capabilities = TargetCapabilityFlags::all();
+    let mut store = PackageStore::new(compile::core());
+    let dependencies = vec![store.insert(compile::std(&store, capabilities))];
+    let (unit, errors) = compile::compile(
+        &store,
+        &dependencies,
+        SourceMap::new([("test.qs".into(), source.into())], None),
+        PackageType::Lib,
+        capabilities,
+        LanguageFeatures::default(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    extract(&unit.package)
+}
+
+const SOURCE: &str = indoc! {"
+    namespace Sample.Gates {
+        /// # Summary
+        /// Prepares a Bell state
+        /// on two qubits.
+        ///
+        /// # Input
+        /// ## q1
+        /// The first qubit.
+        operation PrepareBell(q1 : Qubit, q2 : Qubit) : Unit is Adj + Ctl {
+            function Nested() : Unit {}
+            H(q1);
+            CNOT(q1, q2);
+        }
+
+        internal function Helper() : Unit {}
+
+        newtype Pair = (First : Int, Second : Int);
+    }
+
+    namespace Sample.Arithmetic {
+        /// Adds two integers.
+        function AddInts(a : Int, b : Int) : Int { a + b }
+    }
+"};
+
+#[test]
+fn public_items_are_ordered_by_namespace_and_name() {
+    let names = docs(SOURCE)
+        .iter()
+        .map(DocItem::full_name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "Sample.Arithmetic.AddInts",
+            "Sample.Gates.Pair",
+            "Sample.Gates.PrepareBell"
+        ]
+    );
+}
+
+#[test]
+fn items_have_signatures_and_functors() {
+    let docs = docs(SOURCE);
+    let bell = &docs[2];
+    assert_eq!(bell.kind, DocKind::Operation);
+    assert_eq!(bell.functors, FunctorSetValue::CtlAdj);
+    assert_eq!(bell.summary, "Prepares a Bell state on two qubits.");
+    assert_eq!(docs[0].kind, DocKind::Function);
+    assert_eq!(docs[0].functors, FunctorSetValue::Empty);
+    assert_eq!(docs[0].summary, "Adds two integers.");
+    assert_eq!(
+        docs[1].signature,
+        "newtype Pair = (First : Int, Second : Int)"
+    );
+}
+
+#[test]
+fn json() {
+    let docs = docs(SOURCE);
+    expect![[r##"
+        [
+          {
+            "doc": "Adds two integers.",
+            "functors": [],
+            "id": "function:Sample.Arithmetic.AddInts",
+            "kind": "function",
+            "name": "AddInts",
+            "namespace": "Sample.Arithmetic",
+            "signature": "function AddInts(a : Int, b : Int) : Int",
+            "summary": "Adds two integers."
+          },
+          {
+            "doc": "",
+            "functors": [],
+            "id": "type:Sample.Gates.Pair",
+            "kind": "type",
+            "name": "Pair",
+            "namespace": "Sample.Gates",
+            "signature": "newtype Pair = (First : Int, Second : Int)",
+            "summary": ""
+          },
+          {
+            "doc": "# Summary\nPrepares a Bell state\non two qubits.\n\n# Input\n## q1\nThe first qubit.",
+            "functors": [
+              "Adj",
+              "Ctl"
+            ],
+            "id": "operation:Sample.Gates.PrepareBell",
+            "kind": "operation",
+            "name": "PrepareBell",
+            "namespace": "Sample.Gates",
+            "signature": "operation PrepareBell(q1 : Qubit, q2 : Qubit) : Unit is Adj + Ctl",
+            "summary": "Prepares a Bell state on two qubits."
+          }
+        ]"##]]
+    .assert_eq(&serde_json::to_string_pretty(&to_json(&docs)).expect("docs should serialize"));
+}
+
+#[test]
+fn markdown() {
+    let docs = docs(SOURCE);
+    assert_eq!(docs[2].markdown_path(), "Sample.Gates/PrepareBell.md");
+    expect![[r#"
+        # PrepareBell operation
+
+        Namespace: Sample.Gates
+
+        ```qsharp
+        operation PrepareBell(q1 : Qubit, q2 : Qubit) : Unit is Adj + Ctl
+        ```
+
+        Supports: Adj, Ctl
+
+        ## Summary
+        Prepares a Bell state
+        on two qubits.
+
+        ## Input
+        ### q1
+        The first qubit.
+    "#]]
+    .assert_eq(&docs[2].to_markdown());
+    expect![[r#"
+        # Pair user defined type
+
+        Namespace: Sample.Gates
+
+        ```qsharp
+        newtype Pair = (First : Int, Second : Int)
+        ```
+    "#]]
+    .assert_eq(&docs[1].to_markdown());
+}
+
+#[test]
+fn standard_library_is_documented() {
+    let store = PackageStore::new(compile::core());
+    let std = compile::std(&store, TargetCapabilityFlags::all());
+    let docs = extract(&std.package);
+    let convert = docs
+        .iter()
+        .find(|item| item.full_name() == "Microsoft.Quantum.Convert.IntAsDouble")
+        .expect("standard library should document IntAsDouble");
+    assert_eq!(convert.kind, DocKind::Function);
+    assert!(!convert.summary.is_empty());
+}
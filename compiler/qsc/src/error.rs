@@ -6,7 +6,7 @@ use qsc_frontend::compile::PackageStore;
 use std::fmt::{self, Debug, Display, Formatter};
 use thiserror::Error;
 
-pub use qsc_frontend::error::WithSource;
+pub use qsc_frontend::error::{DiagnosticLabel, Severity, StructuredDiagnostic, WithSource};
 
 #[derive(Clone, Debug, Error)]
 pub struct WithStack<E> {
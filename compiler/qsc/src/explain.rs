@@ -0,0 +1,125 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Extended explanations for diagnostic codes, in the spirit of `rustc --explain`. Each
+//! explanation describes what the diagnostic means and how to fix it, usually with examples.
+
+#[cfg(test)]
+mod tests;
+
+use crate::interpret::Error;
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode};
+use std::fmt::{self, Display, Formatter};
+
+const EXPLANATIONS: &str = include_str!("explain/explanations.md");
+
+/// Returns the extended explanation for the given diagnostic code, such as
+/// `Qsc.Resolve.NotFound`, formatted as Markdown.
+#[must_use]
+pub fn explain(code: &str) -> Option<&'static str> {
+    sections().find_map(|(name, text)| (name == code).then_some(text))
+}
+
+/// Returns the first paragraph of the extended explanation for the given diagnostic code, which
+/// summarizes the explanation in a sentence or two.
+#[must_use]
+pub fn summary(code: &str) -> Option<&'static str> {
+    explain(code).map(|text| text.split("\n\n").next().unwrap_or(text).trim())
+}
+
+/// Returns every diagnostic code that has an extended explanation.
+pub fn codes() -> impl Iterator<Item = &'static str> {
+    sections().map(|(name, _)| name)
+}
+
+fn sections() -> impl Iterator<Item = (&'static str, &'static str)> {
+    EXPLANATIONS.split("\n## ").skip(1).map(|section| {
+        let (name, text) = section.split_once('\n').unwrap_or((section, ""));
+        (name.trim(), text.trim())
+    })
+}
+
+/// An error with the summary of its extended explanation appended to its help text.
+#[derive(Clone, Debug)]
+pub struct Explained {
+    error: Box<Error>,
+    summary: &'static str,
+}
+
+impl Explained {
+    /// Wraps the error if its code has an explanation, and otherwise returns it unchanged.
+    #[must_use]
+    pub fn wrap(error: Error) -> Error {
+        if matches!(error, Error::Explained(_)) {
+            return error;
+        }
+        match error.code().and_then(|code| summary(&code.to_string())) {
+            Some(summary) => Error::Explained(Self {
+                error: Box::new(error),
+                summary,
+            }),
+            None => error,
+        }
+    }
+
+    /// The original error.
+    #[must_use]
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// The summary appended to the help text of the error.
+    #[must_use]
+    pub fn summary(&self) -> &'static str {
+        self.summary
+    }
+}
+
+impl Display for Explained {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Explained {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl Diagnostic for Explained {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.error.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(match self.error.help() {
+            Some(help) => Box::new(format!("{help}\n{}", self.summary)),
+            None => Box::new(self.summary),
+        })
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.error.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.error.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.error.diagnostic_source()
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{codes, explain, summary};
+use crate::interpret::{Error, Interpreter};
+use miette::Diagnostic;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use rustc_hash::FxHashSet;
+
+fn errors(explain_errors: bool, line: &str) -> Vec<Error> {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_explain_errors(explain_errors);
+    let mut sink = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut sink);
+    interpreter
+        .eval_fragments(&mut receiver, line)
+        .expect_err("line should fail")
+}
+
+#[test]
+fn every_code_has_a_summary_and_is_unique() {
+    let mut seen = FxHashSet::default();
+    for code in codes() {
+        assert!(code.starts_with("Qsc."), "{code}");
+        assert!(seen.insert(code), "duplicate explanation for {code}");
+        let summary = summary(code).expect("code should have an explanation");
+        assert!(!summary.is_empty(), "{code}");
+        assert!(!summary.contains('\n'), "{code}");
+    }
+}
+
+#[test]
+fn explain_returns_the_full_explanation() {
+    let explanation = explain("Qsc.Resolve.NotFound").expect("code should have an explanation");
+    assert!(explanation.starts_with(summary("Qsc.Resolve.NotFound").expect("summary should exist")));
+    assert!(explanation.contains("```qsharp"), "{explanation}");
+    assert!(explain("Qsc.Resolve").is_none());
+    assert!(explain("Qsc.Unknown.Code").is_none());
+}
+
+#[test]
+fn interpreter_appends_summary_to_help() {
+    let plain = errors(false, "Undefined()").remove(0);
+    let explained = errors(true, "Undefined()").remove(0);
+    let summary = summary("Qsc.Resolve.NotFound").expect("summary should exist");
+
+    assert!(plain.help().is_none());
+    assert_eq!(
+        explained.help().map(|help| help.to_string()).as_deref(),
+        Some(summary)
+    );
+    assert_eq!(explained.to_string(), plain.to_string());
+    assert_eq!(
+        explained.code().map(|code| code.to_string()).as_deref(),
+        Some("Qsc.Resolve.NotFound")
+    );
+    assert_eq!(
+        explained.labels().map(Iterator::count),
+        plain.labels().map(Iterator::count)
+    );
+}
+
+#[test]
+fn interpreter_keeps_stack_trace_of_explained_runtime_errors() {
+    let error = errors(true, "function Fails() : Unit { fail \"boom\"; } Fails()").remove(0);
+    assert!(matches!(error, Error::Explained(_)));
+    assert!(error.stack_trace().is_some());
+    let help = error
+        .help()
+        .map(|help| help.to_string())
+        .unwrap_or_default();
+    assert!(
+        help.ends_with(summary("Qsc.Eval.UserFail").expect("summary should exist")),
+        "{help}"
+    );
+}
@@ -0,0 +1,264 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Golden-file testing for hosts. A [`Session`] records what an interpreter prints and returns,
+//! including messages, state dumps, values and errors, as normalized text with the random seeds
+//! pinned, and [`assert_golden`] compares that text against a file checked in next to the host's
+//! tests. Like `expect_test`, running the tests with `UPDATE_EXPECT=1` rewrites the files
+//! instead of comparing them.
+
+#[cfg(test)]
+mod tests;
+
+use std::{fmt::Write, fs, path::Path};
+
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::{output, state::format_state_id};
+
+use crate::interpret::{output::Receiver, InterpretResult, Interpreter, Value};
+
+/// Amplitudes are written with this many decimal places, so that the transcript does not depend
+/// on rounding noise in the last digits.
+const PRECISION: usize = 6;
+
+/// An interpreter session whose inputs and outputs are recorded in a transcript.
+pub struct Session {
+    interpreter: Interpreter,
+    transcript: String,
+}
+
+impl Session {
+    /// Starts recording the interpreter, with both of its random seeds pinned to 0. The
+    /// transcript starts with the interpreter's session metadata, one `# key: value` line per
+    /// key, so that a golden file shows which session it was recorded from.
+    #[must_use]
+    pub fn new(interpreter: Interpreter) -> Self {
+        let mut transcript = String::new();
+        for (key, value) in interpreter.metadata() {
+            writeln!(transcript, "# {key}: {value}").expect("writing to a string should succeed");
+        }
+        let mut session = Self {
+            interpreter,
+            transcript,
+        };
+        session.set_seed(0);
+        session
+    }
+
+    /// Pins both the quantum and the classical random seeds of the interpreter.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.interpreter.set_seed(Some(seed));
+    }
+
+    /// Evaluates the fragments and records them along with their output and result. Errors are
+    /// recorded rather than returned, so that the golden file shows them too.
+    pub fn eval(&mut self, fragments: &str) -> &mut Self {
+        self.record_input(fragments);
+        let mut receiver = TranscriptReceiver(&mut self.transcript);
+        let result = self.interpreter.eval_fragments(&mut receiver, fragments);
+        self.record_result(result);
+        self
+    }
+
+    /// Runs the entry expression on a new simulator, like [`Interpreter::run`], and records it
+    /// along with its output and result.
+    pub fn run(&mut self, expr: &str) -> &mut Self {
+        self.record_input(expr);
+        let mut receiver = TranscriptReceiver(&mut self.transcript);
+        let result = self
+            .interpreter
+            .run(&mut receiver, expr)
+            .and_then(|result| result);
+        self.record_result(result);
+        self
+    }
+
+    /// The interpreter being recorded.
+    pub fn interpreter(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// The transcript recorded so far.
+    #[must_use]
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// Compares the transcript against the golden file at the path. See [`assert_golden`].
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        assert_golden(path, &self.transcript);
+    }
+
+    fn record_input(&mut self, input: &str) {
+        for line in input.trim().lines() {
+            writeln!(self.transcript, "> {line}").expect("writing to a string should succeed");
+        }
+    }
+
+    fn record_result(&mut self, result: InterpretResult) {
+        match result {
+            Ok(value) if value == Value::unit() => {}
+            Ok(value) => {
+                writeln!(self.transcript, "{value}").expect("writing to a string should succeed");
+            }
+            Err(errors) => {
+                for error in errors {
+                    writeln!(self.transcript, "error: {}", error_chain(&error))
+                        .expect("writing to a string should succeed");
+                }
+            }
+        }
+    }
+}
+
+/// The messages of the error and each of its sources, so that wrappers such as "runtime error"
+/// are followed by the error that caused them.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut messages = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(error) = source {
+        let message = error.to_string();
+        if messages.last() != Some(&message) {
+            messages.push(message);
+        }
+        source = error.source();
+    }
+    messages.join(": ")
+}
+
+/// Writes output to the transcript, one line per message and one line per basis state of a
+/// dump.
+struct TranscriptReceiver<'a>(&'a mut String);
+
+impl Receiver for TranscriptReceiver<'_> {
+    fn state(
+        &mut self,
+        state: Vec<(BigUint, Complex64)>,
+        qubit_count: usize,
+    ) -> Result<(), output::Error> {
+        self.0.push_str("DumpMachine:\n");
+        for (id, amplitude) in state {
+            let (re, im) = (round(amplitude.re), round(amplitude.im));
+            if re == 0.0 && im == 0.0 {
+                continue;
+            }
+            writeln!(
+                self.0,
+                "  {}: {re:.PRECISION$}{im:+.PRECISION$}i",
+                format_state_id(&id, qubit_count)
+            )
+            .expect("writing to a string should succeed");
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), output::Error> {
+        self.0.push_str(msg);
+        self.0.push('\n');
+        Ok(())
+    }
+}
+
+/// Rounds to the transcript's precision, turning `-0.0` into `0.0` so that the sign of
+/// vanishing amplitudes does not show up in the transcript.
+fn round(x: f64) -> f64 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    let scale = 10f64.powi(PRECISION as i32);
+    let rounded = (x * scale).round() / scale;
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+/// Normalizes text for comparison: line endings become `\n`, trailing whitespace is removed from
+/// every line and the text ends with exactly one newline.
+#[must_use]
+pub fn normalize(text: &str) -> String {
+    let mut normalized = text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string();
+    normalized.push('\n');
+    normalized
+}
+
+/// Compares the text against the golden file at the path after normalizing both, panicking with
+/// a line diff if they differ or the file does not exist. When the `UPDATE_EXPECT` environment
+/// variable is set, the file is written with the normalized text instead, creating any missing
+/// directories.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    check_golden(
+        path.as_ref(),
+        actual,
+        std::env::var_os("UPDATE_EXPECT").is_some(),
+    );
+}
+
+fn check_golden(path: &Path, actual: &str, update: bool) {
+    let actual = normalize(actual);
+    if update {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("golden file directory should be created");
+        }
+        fs::write(path, &actual).expect("golden file should be written");
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(path) else {
+        panic!(
+            "golden file {} does not exist, rerun with UPDATE_EXPECT=1 to create it",
+            path.display()
+        );
+    };
+    let expected = normalize(&expected);
+    assert!(
+        expected == actual,
+        "output does not match golden file {}, rerun with UPDATE_EXPECT=1 to update it\n{}",
+        path.display(),
+        diff(&expected, &actual)
+    );
+}
+
+/// A line diff from `expected` to `actual`, with removed lines prefixed by `-`, added lines by
+/// `+` and unchanged lines by a space.
+#[must_use]
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    // The length of the longest common subsequence of each pair of suffixes.
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            writeln!(diff, "  {}", expected[i]).expect("writing to a string should succeed");
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || common[i][j + 1] >= common[i + 1][j])
+        {
+            writeln!(diff, "+ {}", actual[j]).expect("writing to a string should succeed");
+            j += 1;
+        } else {
+            writeln!(diff, "- {}", expected[i]).expect("writing to a string should succeed");
+            i += 1;
+        }
+    }
+    diff
+}
@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{check_golden, diff, normalize, Session};
+use crate::{
+    interpret::Interpreter, LanguageFeatures, PackageType, SourceMap, TargetCapabilityFlags,
+};
+use expect_test::expect;
+
+fn session() -> Session {
+    let interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    Session::new(interpreter)
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("qsc-golden-test-{}", std::process::id()))
+        .join(name)
+}
+
+#[test]
+fn transcript_starts_with_session_metadata() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_metadata("notebook", "bell.ipynb");
+    interpreter.set_metadata("experiment", "baseline");
+    let mut session = Session::new(interpreter);
+    session.eval("1");
+    expect![[r#"
+        # experiment: baseline
+        # notebook: bell.ipynb
+        > 1
+        1
+    "#]]
+    .assert_eq(session.transcript());
+}
+
+#[test]
+fn transcript_records_inputs_output_values_and_errors() {
+    let mut session = session();
+    session
+        .eval("use q = Qubit();")
+        .eval("H(q); Microsoft.Quantum.Diagnostics.DumpMachine();")
+        .eval(r#"Message("hello"); 1 + 2"#)
+        .eval("Reset(q);")
+        .eval("Foo()");
+    expect![[r#"
+        > use q = Qubit();
+        > H(q); Microsoft.Quantum.Diagnostics.DumpMachine();
+        DumpMachine:
+          |0⟩: 0.707107+0.000000i
+          |1⟩: 0.707107+0.000000i
+        > Message("hello"); 1 + 2
+        hello
+        3
+        > Reset(q);
+        > Foo()
+        error: `Foo` not found
+    "#]]
+    .assert_eq(session.transcript());
+}
+
+#[test]
+fn pinned_seed_makes_measurements_repeatable() {
+    let program = "use qs = Qubit[8]; ApplyToEach(H, qs); MResetEachZ(qs)";
+    let first = session().eval(program).transcript().to_string();
+    let second = session().eval(program).transcript().to_string();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn normalize_trims_whitespace_and_line_endings() {
+    assert_eq!(normalize("a  \r\nb\t\n\n\n"), "a\nb\n");
+    assert_eq!(normalize("a"), "a\n");
+}
+
+#[test]
+fn diff_marks_removed_and_added_lines() {
+    expect![[r#"
+          a
+        - b
+        + c
+          d
+        + e
+    "#]]
+    .assert_eq(&diff("a\nb\nd\n", "a\nc\nd\ne\n"));
+}
+
+#[test]
+fn golden_file_is_written_then_matched() {
+    let path = temp_path("written").join("transcript.txt");
+    check_golden(&path, "> 1\n1  \n", true);
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("golden file should be readable"),
+        "> 1\n1\n"
+    );
+    check_golden(&path, "> 1\r\n1\r\n", false);
+    std::fs::remove_dir_all(temp_path("written")).expect("temp dir should be removed");
+}
+
+#[test]
+#[should_panic(expected = "output does not match golden file")]
+fn golden_file_mismatch_panics() {
+    let path = temp_path("mismatch.txt");
+    check_golden(&path, "> 1\n1\n", true);
+    check_golden(&path, "> 1\n2\n", false);
+}
+
+#[test]
+#[should_panic(expected = "does not exist")]
+fn missing_golden_file_panics() {
+    check_golden(&temp_path("missing.txt"), "> 1\n1\n", false);
+}
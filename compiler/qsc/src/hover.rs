@@ -0,0 +1,98 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The declaration, documentation and type of the name at an offset in a source, for hover and
+//! signature help. The name is found with [`qsc_frontend::lookup`], and this adds the parts that
+//! need the rest of the package store, such as the signature of an item from a dependency.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_data_structures::span::Span;
+use qsc_doc_gen::display::{hir_callable_signature, hir_udt_signature};
+use qsc_frontend::{
+    compile::PackageStore,
+    lookup::{self, Target},
+};
+use qsc_hir::hir::{ItemKind, PackageId};
+use std::rc::Rc;
+
+/// Information about a name in a source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hover {
+    pub name: Rc<str>,
+    /// The span of the name, relative to the source it is in.
+    pub span: Span,
+    /// The declaration of what the name refers to, such as the signature of a callable or
+    /// `x : Int` for a local variable.
+    pub declaration: String,
+    /// The doc comment of the item that the name refers to. Locals have no doc comment.
+    pub doc: String,
+    /// The type of the expression or pattern that the name is part of, with the type arguments of
+    /// generic callables filled in.
+    pub ty: Option<String>,
+    /// Where what the name refers to is declared, if it has a declaration in source.
+    pub definition: Option<Definition>,
+}
+
+/// The declaration of an item or local.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Definition {
+    pub package: PackageId,
+    /// The span of the declared name, relative to the package's source map.
+    pub span: Span,
+}
+
+/// Finds the name at the offset into the named source, searching the given packages of the store
+/// for the source.
+#[must_use]
+pub fn hover(
+    store: &PackageStore,
+    packages: &[PackageId],
+    source: &str,
+    offset: u32,
+) -> Option<Hover> {
+    let (package, unit, source) = packages.iter().find_map(|&id| {
+        let unit = store.get(id)?;
+        Some((id, unit, unit.sources.find_by_name(source)?))
+    })?;
+    let found = lookup::lookup(unit, source.offset + offset)?;
+    let ty = found.ty.as_ref().map(qsc_hir::ty::Ty::display);
+
+    let (declaration, doc, definition) = match found.target {
+        Target::Item(id) => {
+            let package = id.package.unwrap_or(package);
+            let item = store.get(package)?.package.items.get(id.item)?;
+            let (declaration, span) = match &item.kind {
+                ItemKind::Callable(decl) => {
+                    (hir_callable_signature(decl).to_string(), decl.name.span)
+                }
+                ItemKind::Ty(name, udt) => (hir_udt_signature(udt).to_string(), name.span),
+                ItemKind::Namespace(name, _) => (format!("namespace {}", name.name()), name.span()),
+            };
+            (
+                declaration,
+                item.doc.to_string(),
+                Some(Definition { package, span }),
+            )
+        }
+        Target::Local(id) => (
+            ty.as_ref().map_or_else(
+                || found.name.to_string(),
+                |ty| format!("{} : {ty}", found.name),
+            ),
+            String::new(),
+            lookup::local_declaration(unit, id).map(|span| Definition { package, span }),
+        ),
+        Target::Param(_) => (found.name.to_string(), String::new(), None),
+    };
+
+    Some(Hover {
+        name: found.name,
+        span: found.span - source.offset,
+        declaration,
+        doc,
+        ty,
+        definition,
+    })
+}
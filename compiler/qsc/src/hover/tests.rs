@@ -0,0 +1,82 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::interpret::Interpreter;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::output::CursorReceiver;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::io::Cursor;
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        /// # Summary
+        /// Doubles an integer.
+        function Double(x : Int) : Int { x * 2 }
+    }
+"};
+
+fn interpreter(fragments: &[&str]) -> Interpreter {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::new([("test.qs".into(), SOURCE.into())], None),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    for fragment in fragments {
+        let mut cursor = Cursor::new(Vec::<u8>::new());
+        let mut receiver = CursorReceiver::new(&mut cursor);
+        interpreter
+            .eval_fragments(&mut receiver, fragment)
+            .expect("fragment should evaluate");
+    }
+    interpreter
+}
+
+fn offset(text: &str, name: &str) -> u32 {
+    let index = text.find(name).expect("name should occur in the text");
+    u32::try_from(index).expect("offset should fit into u32")
+}
+
+#[test]
+fn callable_from_sources_has_signature_and_doc() {
+    let fragment = "let y = Test.Double(21);";
+    let interpreter = interpreter(&[fragment]);
+    let hover = interpreter
+        .hover("line_0", offset(fragment, "Double"))
+        .expect("name should be found");
+    assert_eq!(hover.name.as_ref(), "Double");
+    assert_eq!(hover.declaration, "function Double(x : Int) : Int");
+    assert!(hover.doc.contains("Doubles an integer."), "{}", hover.doc);
+    assert_eq!(hover.ty.as_deref(), Some("(Int -> Int)"));
+    assert_eq!(hover.span.lo, offset(fragment, "Double"));
+    assert!(hover.definition.is_some());
+}
+
+#[test]
+fn local_from_earlier_fragment_has_type() {
+    let interpreter = interpreter(&["let y = Test.Double(21);", "y + 1"]);
+    let hover = interpreter
+        .hover("line_1", 0)
+        .expect("name should be found");
+    assert_eq!(hover.declaration, "y : Int");
+    assert_eq!(hover.doc, "");
+    assert!(hover.definition.is_some());
+}
+
+#[test]
+fn parameter_in_sources_is_found() {
+    let interpreter = interpreter(&[]);
+    let hover = interpreter
+        .hover("test.qs", offset(SOURCE, "x * 2"))
+        .expect("name should be found");
+    assert_eq!(hover.declaration, "x : Int");
+}
+
+#[test]
+fn unknown_source_finds_nothing() {
+    assert_eq!(interpreter(&[]).hover("missing.qs", 0), None);
+}
@@ -1,19 +1,27 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::compile::{self, compile, core, std};
+use crate::{
+    compile::{self, compile, shared_store},
+    completion::{self, CompletionItem},
+    hover::{self, Hover},
+    references::ReferenceIndex,
+    symbols::SymbolIndex,
+};
 use miette::Diagnostic;
 
 use qsc_ast::ast;
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
 
+pub use qsc_frontend::incremental::is_source_file;
 use qsc_frontend::{
-    compile::{OpenPackageStore, PackageStore, SourceMap},
+    compile::{OpenPackageStore, PackageStore, SourceMap, StdFeatures},
     error::WithSource,
     incremental::Increment,
 };
 use qsc_hir::hir::PackageId;
 use qsc_passes::{PackageType, PassContext};
+use std::rc::Rc;
 
 /// An incremental Q# compiler.
 pub struct Compiler {
@@ -43,15 +51,51 @@ impl Compiler {
         capabilities: TargetCapabilityFlags,
         language_features: LanguageFeatures,
     ) -> Result<Self, Errors> {
-        let core = core();
-        let mut store = PackageStore::new(core);
-        let mut dependencies = Vec::new();
-        if include_std {
-            let std = std(&store, capabilities);
-            let id = store.insert(std);
-            dependencies.push(id);
-        }
+        Self::new_with_std_features(
+            include_std.then_some(StdFeatures::all()),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+    }
+
+    /// Creates a new incremental compiler, compiling the passed in sources against a
+    /// standard library containing only the selected feature groups, or without the
+    /// standard library if `std_features` is `None`.
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_std_features(
+        std_features: Option<StdFeatures>,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+    ) -> Result<Self, Errors> {
+        let (store, std) = shared_store(capabilities, std_features);
+
+        Self::with_dependencies(
+            store,
+            std.into_iter().collect(),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+    }
 
+    /// Creates a new incremental compiler, compiling the passed in sources against packages that
+    /// are already in the store, such as the standard library and the dependencies of a project.
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn with_dependencies(
+        mut store: PackageStore,
+        mut dependencies: Vec<PackageId>,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+    ) -> Result<Self, Errors> {
         let (unit, errors) = compile(
             &store,
             &dependencies,
@@ -120,6 +164,37 @@ impl Compiler {
         self.compile_fragments(source_name, source_contents, fail_on_error)
     }
 
+    /// Compiles a whole Q# source file, like one that is part of a project, rather than fragments.
+    /// Items outside of a `namespace` block are placed in a namespace named after the source.
+    ///
+    /// Like [`Compiler::compile_fragments_fail_fast`], this method does *not* update the current
+    /// compilation.
+    pub fn compile_source_fail_fast(
+        &mut self,
+        source_name: &str,
+        source_contents: &str,
+    ) -> Result<Increment, Errors> {
+        let (core, unit) = self.store.get_open_mut();
+
+        let mut increment =
+            self.frontend
+                .compile_source(unit, source_name, source_contents, |e| {
+                    fail_on_error(into_errors(e))
+                })?;
+
+        let pass_errors = self.passes.run_default_passes(
+            &mut increment.hir,
+            &mut unit.assigner,
+            core,
+            PackageType::Lib,
+        );
+        fail_on_error(into_errors_with_source(pass_errors, &unit.sources))?;
+        self.passes
+            .check_redefinitions(&unit.package, &increment.redefinitions);
+
+        Ok(increment)
+    }
+
     /// Compiles Q# ast fragments. Fragments are Q# code that can contain
     /// top-level statements as well as namespaces. A notebook cell
     /// or an interpreter entry is an example of fragments.
@@ -176,6 +251,8 @@ impl Compiler {
             );
 
             accumulate_errors(into_errors_with_source(pass_errors, &unit.sources))?;
+            self.passes
+                .check_redefinitions(&unit.package, &increment.redefinitions);
         }
 
         Ok(increment)
@@ -222,6 +299,8 @@ impl Compiler {
             );
 
             accumulate_errors(into_errors_with_source(pass_errors, &unit.sources))?;
+            self.passes
+                .check_redefinitions(&unit.package, &increment.redefinitions);
         }
 
         Ok(increment)
@@ -267,6 +346,60 @@ impl Compiler {
         self.frontend.update(unit, new);
     }
 
+    /// Takes the warnings for the fragments and sources compiled since the warnings were last
+    /// taken. Warnings are only produced when compilation gets as far as the passes.
+    pub fn take_warnings(&mut self) -> Vec<compile::Warning> {
+        let (_, unit) = self.store.get_open_mut();
+        self.passes
+            .take_warnings()
+            .into_iter()
+            .map(|warning| WithSource::from_map(&unit.sources, warning))
+            .collect()
+    }
+
+    /// The namespaces opened by the top-level statements of the fragments compiled so far, with
+    /// the alias each one was opened as.
+    #[must_use]
+    pub fn opens(&self) -> Vec<(String, Option<Rc<str>>)> {
+        self.frontend.opens()
+    }
+
+    /// Indexes the items available to the compilation, including the internal items declared in
+    /// its sources and fragments.
+    #[must_use]
+    pub fn symbols(&self) -> SymbolIndex {
+        SymbolIndex::new(
+            self.package_store(),
+            &[self.source_package_id(), self.package_id()],
+        )
+    }
+
+    /// Indexes the declarations and uses of names in the compilation and its dependencies, for
+    /// go-to-definition and find-all-references.
+    #[must_use]
+    pub fn references(&self) -> ReferenceIndex {
+        ReferenceIndex::new(self.package_store())
+    }
+
+    /// Completes the word before the cursor, which is a byte offset into the line, with the names
+    /// that are in scope for the next fragment. See [`completion::completions`].
+    #[must_use]
+    pub fn completions(&self, line: &str, cursor: usize) -> Vec<CompletionItem> {
+        completion::completions(line, cursor, &self.symbols(), &self.opens(), &[])
+    }
+
+    /// Finds the name at the offset into a source or fragment of the compilation. See
+    /// [`hover::hover`].
+    #[must_use]
+    pub fn hover(&self, source: &str, offset: u32) -> Option<Hover> {
+        hover::hover(
+            self.package_store(),
+            &[self.package_id(), self.source_package_id()],
+            source,
+            offset,
+        )
+    }
+
     /// Returns a reference to the underlying package store.
     #[must_use]
     pub fn package_store(&self) -> &PackageStore {
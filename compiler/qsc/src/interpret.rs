@@ -1,7 +1,16 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod checkpoint;
+#[cfg(not(any(target_family = "wasm")))]
+mod chrome_trace;
 mod debug;
+mod estimate;
+mod events;
+mod format;
+mod oracle;
+mod reducer;
+mod sandbox;
 
 #[cfg(test)]
 mod tests;
@@ -12,27 +21,52 @@ mod debugger_tests;
 #[cfg(test)]
 mod circuit_tests;
 
-use std::rc::Rc;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, sync::Arc};
+
+pub use checkpoint::{Autosave, Checkpoint};
+#[cfg(not(any(target_family = "wasm")))]
+pub use chrome_trace::{ChromeTrace, TracedSim};
+pub use estimate::ResourceEstimate;
+pub use events::Event;
+pub use format::{TupleLayout, ValueFormat, ValueFormatter};
+pub use oracle::{Error as OracleError, Oracle};
+pub use reducer::{Fold, MajorityVote, Parity, Reducer};
+pub use sandbox::{Error as SandboxError, Sandbox};
 
 pub use qsc_eval::{
+    backend::{Differential, DivergenceKind},
     debug::Frame,
-    output::{self, GenericReceiver},
+    hooks::StmtHooks,
+    interrupt::InterruptHandle,
+    output::{
+        self, GenericReceiver, LimitedReceiver, OutputKind, OutputLimit, OutputLimitExceeded,
+        RoutingReceiver,
+    },
+    trace::CallTrace,
     val::Closure,
     val::Range as ValueRange,
     val::Result,
     val::Value,
-    StepAction, StepResult,
+    StepAction, StepResult, VariableInfo,
 };
 use qsc_lowerer::{map_fir_package_to_hir, map_hir_package_to_fir};
 use qsc_partial_eval::ProgramEntry;
 use qsc_rca::PackageStoreComputeProperties;
 
 use crate::{
-    error::{self, WithStack},
+    cache::DiskCache,
+    compile,
+    completion::{self, CompletionItem},
+    error::{self, DiagnosticLabel, StructuredDiagnostic, WithStack},
+    hover::Hover,
     incremental::Compiler,
     location::Location,
+    packages,
+    shots::shot_seeds,
+    symbols::SymbolIndex,
+    telemetry,
 };
-use debug::format_call_stack;
+use debug::{format_call_stack, format_frames};
 use miette::Diagnostic;
 use num_bigint::BigUint;
 use num_complex::Complex;
@@ -40,7 +74,11 @@ use qsc_circuit::{
     operations::entry_expr_for_qubit_operation, Builder as CircuitBuilder, Circuit,
     Config as CircuitConfig,
 };
-use qsc_codegen::qir::fir_to_qir;
+use qsc_codegen::{
+    decompile::{write_callable_specs, write_package},
+    qir::{fir_to_rir, rir_to_qir},
+    target::{CodegenTarget, Registry as CodegenRegistry},
+};
 use qsc_data_structures::{
     functors::FunctorApp,
     language_features::LanguageFeatures,
@@ -48,10 +86,14 @@ use qsc_data_structures::{
     span::Span,
     target::TargetCapabilityFlags,
 };
+#[cfg(not(any(target_family = "wasm")))]
+use qsc_eval::backend::{MappedSim, Precision};
 use qsc_eval::{
-    backend::{Backend, Chain as BackendChain, SparseSim},
+    backend::{Backend, Chain as BackendChain, Folded, NoiseModel, NoisySim, SparseSim},
+    hooks::CheckpointHook,
     output::Receiver,
-    val, Env, State, VariableInfo,
+    state::{state_fidelity, QuantumState},
+    val, Env, State,
 };
 use qsc_fir::fir::{self, ExecGraphNode, Global, PackageStoreLookup};
 use qsc_fir::{
@@ -59,11 +101,12 @@ use qsc_fir::{
     visit::{self, Visitor},
 };
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, Source, SourceMap},
+    compile::{CompileUnit, PackageStore, Source, SourceMap, StdFeatures},
     error::WithSource,
     incremental::Increment,
 };
 use qsc_passes::{PackageType, PassContext};
+use qsc_project::PackageGraphSources;
 use rustc_hash::FxHashSet;
 use thiserror::Error;
 
@@ -72,9 +115,59 @@ impl Error {
     pub fn stack_trace(&self) -> &Option<String> {
         match &self {
             Error::Eval(err) => err.stack_trace(),
+            Error::Explained(err) => err.error().stack_trace(),
             _ => &None,
         }
     }
+
+    /// Whether evaluation stopped because the host triggered the interpreter's
+    /// [`InterruptHandle`], rather than because of an error in the program.
+    #[must_use]
+    pub fn is_interrupted(&self) -> bool {
+        match &self {
+            Error::Eval(err) => matches!(err.error().error(), qsc_eval::Error::Interrupted(_)),
+            Error::Explained(err) => err.error().is_interrupted(),
+            _ => false,
+        }
+    }
+
+    /// The error in a structured form, with the labels of the error it wraps, if any, resolved
+    /// to ranges in the given encoding.
+    #[must_use]
+    pub fn to_structured(&self, encoding: Encoding) -> StructuredDiagnostic {
+        StructuredDiagnostic::new(self, self.resolve_labels(encoding))
+    }
+
+    /// The error as a JSON object. See [`StructuredDiagnostic::to_json`].
+    #[must_use]
+    pub fn to_json(&self, encoding: Encoding) -> serde_json::Value {
+        self.to_structured(encoding).to_json()
+    }
+
+    fn resolve_labels(&self, encoding: Encoding) -> Vec<DiagnosticLabel> {
+        match self {
+            Error::Compile(err) => err.resolve_labels(encoding),
+            Error::Pass(err) => err.resolve_labels(encoding),
+            Error::Eval(err) => err.error().resolve_labels(encoding),
+            Error::PartialEvaluation(err) => err.resolve_labels(encoding),
+            Error::Sandbox(err) => err.resolve_labels(encoding),
+            Error::Explained(err) => err.error().resolve_labels(encoding),
+            Error::Circuit(_)
+            | Error::NoEntryPoint
+            | Error::UnsupportedRuntimeCapabilities
+            | Error::UnknownCodegenTarget(_)
+            | Error::CodegenTarget(..)
+            | Error::InvalidKernelName(_)
+            | Error::KernelSymbolCollision(..)
+            | Error::NotAnOperation
+            | Error::UnsupportedCheckpointValue(_)
+            | Error::CheckpointMismatch
+            | Error::Simulator(_)
+            | Error::CheckpointRestoreNotFresh
+            | Error::CheckpointStateUnsupported(_)
+            | Error::Oracle(_) => Vec::new(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -97,6 +190,24 @@ pub enum Error {
     #[error("unsupported runtime capabilities for code generation")]
     #[diagnostic(code("Qsc.Interpret.UnsupportedRuntimeCapabilities"))]
     UnsupportedRuntimeCapabilities,
+    #[error("code generation target `{0}` is not registered")]
+    #[diagnostic(code("Qsc.Interpret.UnknownCodegenTarget"))]
+    UnknownCodegenTarget(String),
+    #[error("code generation target `{0}` cannot generate the program: {1}")]
+    #[diagnostic(code("Qsc.Interpret.CodegenTarget"))]
+    CodegenTarget(String, String),
+    #[error("`{0}` cannot be compiled to a kernel because it is not the name of a callable")]
+    #[diagnostic(code("Qsc.Interpret.InvalidKernelName"))]
+    #[diagnostic(help("provide the fully qualified name of the callable, such as `Test.Main`"))]
+    InvalidKernelName(String),
+    #[error(
+        "`{0}` and `{1}` cannot both be compiled to kernels because both would be named `{2}`"
+    )]
+    #[diagnostic(code("Qsc.Interpret.KernelSymbolCollision"))]
+    #[diagnostic(help(
+        "compile the callables to separate sets of kernels, or rename one of them"
+    ))]
+    KernelSymbolCollision(String, String, String),
     #[error("expression does not evaluate to an operation")]
     #[diagnostic(code("Qsc.Interpret.NotAnOperation"))]
     #[diagnostic(help("provide the name of a callable or a lambda expression"))]
@@ -104,6 +215,41 @@ pub enum Error {
     #[error("partial evaluation error")]
     #[diagnostic(transparent)]
     PartialEvaluation(#[from] WithSource<qsc_partial_eval::Error>),
+    #[error("variable `{0}` holds a value that cannot be saved in a checkpoint")]
+    #[diagnostic(code("Qsc.Interpret.UnsupportedCheckpointValue"))]
+    UnsupportedCheckpointValue(String),
+    #[error("checkpoint does not match the sources of this interpreter")]
+    #[diagnostic(code("Qsc.Interpret.CheckpointMismatch"))]
+    #[diagnostic(help(
+        "create the interpreter with the same sources and options as the checkpointed session"
+    ))]
+    CheckpointMismatch,
+    #[error("the simulator could not be created: {0}")]
+    #[diagnostic(code("Qsc.Interpret.Simulator"))]
+    Simulator(String),
+    #[error("checkpoints can only be restored into a newly created interpreter")]
+    #[diagnostic(code("Qsc.Interpret.CheckpointRestoreNotFresh"))]
+    CheckpointRestoreNotFresh,
+    #[error("the simulator cannot restore the checkpointed quantum state: {0}")]
+    #[diagnostic(code("Qsc.Interpret.CheckpointStateUnsupported"))]
+    CheckpointStateUnsupported(String),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Explained(crate::explain::Explained),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Sandbox(#[from] WithSource<SandboxError>),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Oracle(#[from] OracleError),
+}
+
+/// A qubit that is still allocated, along with the call stack that allocated it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QubitAllocation {
+    pub id: usize,
+    /// The formatted call stack of the allocation, innermost call first.
+    pub call_stack: String,
 }
 
 /// A Q# interpreter.
@@ -127,8 +273,13 @@ pub struct Interpreter {
     /// is made up of the initial sources passed in when creating the interpreter.
     /// This ID is valid both for the FIR store and the `PackageStore`.
     source_package: PackageId,
-    /// The default simulator backend.
-    sim: BackendChain<SparseSim, CircuitBuilder>,
+    /// Creates the simulator backends that programs run on, or returns why one cannot be created.
+    new_backend: Rc<dyn Fn() -> std::result::Result<BoxedBackend, String>>,
+    /// The number of times every gate is folded in the programs run on a new simulator.
+    gate_folds: usize,
+    /// The simulator backend of the session, with a circuit builder chained to it so that the
+    /// operations applied so far can be drawn.
+    sim: BackendChain<BoxedBackend, CircuitBuilder>,
     /// The quantum seed, if any. This is cached here so that it can be used in calls to
     /// `run_internal` which use a passed instance of the simulator instead of the one above.
     quantum_seed: Option<u64>,
@@ -137,10 +288,99 @@ pub struct Interpreter {
     classical_seed: Option<u64>,
     /// The evaluator environment.
     env: Env,
+    /// Every fragment passed to the compiler so far, in order, used to create checkpoints.
+    history: Vec<checkpoint::Fragment>,
+    /// The number of statements between the checkpoints saved of running evaluations, and the
+    /// hook that saves them, if enabled.
+    autosave: Option<(usize, Rc<RefCell<checkpoint::AutosaveHook>>)>,
+    /// The evaluation restored from a checkpoint of a running evaluation, until it is resumed.
+    paused: Option<State>,
+    /// The named receivers that output is routed to by the `_routed` evaluation methods.
+    receivers: RoutingReceiver,
+    /// The named reducers that the values of the shots run by [`Interpreter::reduce_shots`] are
+    /// added to.
+    reducers: BTreeMap<String, Box<dyn Reducer>>,
+    /// Whether returned errors have the summary of their extended explanation appended.
+    explain_errors: bool,
+    /// Renders the values returned by evaluation for display.
+    value_formatter: Rc<dyn ValueFormatter>,
+    /// The limit on the output of each evaluation.
+    output_limit: OutputLimit,
+    /// The notice for the output dropped by the most recent evaluation, if any.
+    output_limit_exceeded: Option<OutputLimitExceeded>,
+    /// The limits for running untrusted code, if enabled.
+    sandbox: Option<Sandbox>,
+    /// The targets that [`Interpreter::codegen`] can generate code for.
+    codegen_targets: CodegenRegistry,
+    /// The warnings for the most recently compiled fragments.
+    warnings: Vec<crate::compile::Warning>,
+    /// Key/value pairs that the host attached to the session, such as a notebook id.
+    metadata: BTreeMap<String, String>,
 }
 
 pub type InterpretResult = std::result::Result<Value, Vec<Error>>;
 
+/// The reductions of the values of the shots run by [`Interpreter::reduce_shots`].
+#[derive(Debug)]
+pub struct ReducedShots {
+    /// The number of shots that were run.
+    pub shots: usize,
+    /// The number of shots that failed with a runtime error. Their values are not added to the
+    /// reducers.
+    pub failed: usize,
+    /// The errors of the first shot that failed, if any.
+    pub first_error: Option<Vec<Error>>,
+    /// The reduction of every registered reducer, by name.
+    pub values: BTreeMap<String, Value>,
+}
+
+/// A simulator backend chosen when the interpreter is created, such as a full-state, stabilizer
+/// or tracing simulator, that reports a measurement of `One` as `true`.
+pub type BoxedBackend = Box<dyn Backend<ResultType = bool>>;
+
+/// What is known about a value returned by evaluation beyond the value itself, so that hosts can
+/// choose how to render it, such as plotting a `Double[]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueMetadata {
+    /// The static type of the value, as inferred by the compiler. Type parameters and types that
+    /// could not be inferred are kept as they are, so this may be less specific than the value.
+    pub ty: qsc_hir::ty::Ty,
+    /// The length of each dimension of an array, outermost first, or empty if the value is not
+    /// an array. The dimensions stop at the first level where the arrays differ in length or
+    /// where the elements are not arrays, so a rectangular `Double[][]` has two dimensions and a
+    /// jagged one has only one.
+    pub shape: Vec<usize>,
+}
+
+impl ValueMetadata {
+    #[must_use]
+    pub fn new(ty: qsc_hir::ty::Ty, value: &Value) -> Self {
+        let mut shape = Vec::new();
+        let mut level = vec![value];
+        loop {
+            let mut lengths = level.iter().map(|value| match value {
+                Value::Array(items) => Some(items.len()),
+                _ => None,
+            });
+            let Some(Some(len)) = lengths.next() else {
+                break;
+            };
+            if lengths.any(|other| other != Some(len)) {
+                break;
+            }
+            shape.push(len);
+            level = level
+                .into_iter()
+                .flat_map(|value| match value {
+                    Value::Array(items) => items.iter().collect::<Vec<_>>(),
+                    _ => Vec::new(),
+                })
+                .collect();
+        }
+        Self { ty, shape }
+    }
+}
+
 impl Interpreter {
     /// Creates a new incremental compiler, compiling the passed in sources.
     /// # Errors
@@ -154,7 +394,7 @@ impl Interpreter {
     ) -> std::result::Result<Self, Vec<Error>> {
         Self::new_internal(
             false,
-            std,
+            std.then_some(StdFeatures::all()),
             sources,
             package_type,
             capabilities,
@@ -174,25 +414,247 @@ impl Interpreter {
     ) -> std::result::Result<Self, Vec<Error>> {
         Self::new_internal(
             true,
+            std.then_some(StdFeatures::all()),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+    }
+
+    /// Creates a new incremental compiler, compiling the passed in sources against a standard
+    /// library containing only the selected feature groups. Hosts that never use the optional
+    /// namespaces can use this to reduce startup time and memory use.
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_std_features(
+        std_features: StdFeatures,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        Self::new_internal(
+            false,
+            Some(std_features),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+    }
+
+    /// Creates a new incremental compiler like [`Interpreter::new`], loading the standard library
+    /// from the cache instead of compiling it when an earlier process saved it there, see
+    /// [`compile::shared_store_cached`].
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_cache(
+        std: bool,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+        cache: &DiskCache,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let (store, std) =
+            compile::shared_store_cached(capabilities, std.then_some(StdFeatures::all()), cache);
+        Self::with_dependencies(
+            store,
+            std.into_iter().collect(),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+    }
+
+    /// Creates a new incremental compiler like [`Interpreter::new`], with both the quantum and
+    /// the classical random seeds set, so that measurement outcomes and random numbers are the
+    /// same on every run. See [`Interpreter::set_seed`].
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_seed(
+        std: bool,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+        seed: u64,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let mut interpreter =
+            Self::new(std, sources, package_type, capabilities, language_features)?;
+        interpreter.set_seed(Some(seed));
+        Ok(interpreter)
+    }
+
+    /// Creates a new incremental compiler like [`Interpreter::new`] that runs programs on the
+    /// backends made by `new_backend` instead of the sparse simulator. One backend is made for
+    /// the session, which fragments and the entry point are evaluated on, and another for every
+    /// run that starts from a new simulator, such as [`Interpreter::run`].
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_backend<B>(
+        std: bool,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+        new_backend: impl Fn() -> B + 'static,
+    ) -> std::result::Result<Self, Vec<Error>>
+    where
+        B: Backend<ResultType = bool> + 'static,
+    {
+        let mut interpreter =
+            Self::new(std, sources, package_type, capabilities, language_features)?;
+        interpreter.new_backend = Rc::new(move || Ok(Box::new(new_backend()) as BoxedBackend));
+        interpreter.sim = sim_circuit_backend(interpreter.new_sim()?);
+        Ok(interpreter)
+    }
+
+    /// Creates a new incremental compiler like [`Interpreter::new`] that stores simulated
+    /// amplitudes with the given precision, for the session and for every run. Double precision
+    /// uses the sparse simulator, as [`Interpreter::new`] does. Single precision uses a dense
+    /// in-memory [`MappedSim`], which halves the memory of each stored amplitude at the cost of
+    /// rounding error that is reported by [`Interpreter::error_estimate`] and
+    /// [`Interpreter::run_with_error_estimate`].
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned. If the simulator memory
+    /// cannot be allocated, an error is returned.
+    #[cfg(not(any(target_family = "wasm")))]
+    pub fn new_with_precision(
+        std: bool,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+        precision: Precision,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let mut interpreter =
+            Self::new(std, sources, package_type, capabilities, language_features)?;
+        if precision == Precision::Single {
+            interpreter.new_backend = Rc::new(|| {
+                MappedSim::in_memory(Precision::Single)
+                    .map(|sim| Box::new(sim) as BoxedBackend)
+                    .map_err(|err| err.to_string())
+            });
+            interpreter.sim = sim_circuit_backend(interpreter.new_sim()?);
+        }
+        Ok(interpreter)
+    }
+
+    /// Creates a new incremental compiler like [`Interpreter::new`] that runs programs on the
+    /// sparse simulator with the given noise applied to its operations, so that each run samples
+    /// the behavior of the program on noisy hardware. See [`NoiseModel`].
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn new_with_noise(
+        std: bool,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+        noise: NoiseModel,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        Self::new_with_backend(
             std,
             sources,
             package_type,
             capabilities,
             language_features,
+            move || NoisySim::new(SparseSim::new(), noise.clone()),
         )
     }
 
     fn new_internal(
         dbg: bool,
-        std: bool,
+        std_features: Option<StdFeatures>,
         sources: SourceMap,
         package_type: PackageType,
         capabilities: TargetCapabilityFlags,
         language_features: LanguageFeatures,
     ) -> std::result::Result<Self, Vec<Error>> {
-        let compiler = Compiler::new(std, sources, package_type, capabilities, language_features)
-            .map_err(into_errors)?;
+        let span = telemetry::Span::new(telemetry::COMPILE_SOURCES);
+        let compiler = Compiler::new_with_std_features(
+            std_features,
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+        .map_err(into_errors)
+        .inspect_err(|errors| span.fail(errors))?;
 
+        Self::from_compiler(dbg, compiler, capabilities, &span)
+    }
+
+    /// Creates a new incremental compiler, compiling the passed in sources against packages that
+    /// are already in the store, such as the standard library and the dependencies of a project.
+    /// Hosts that create many interpreters can build each store with [`compile::shared_store`],
+    /// or with libraries that they compiled or loaded themselves, so that the interpreters do not
+    /// each compile the standard library.
+    /// # Errors
+    /// If compiling the sources fails, compiler errors are returned.
+    pub fn with_dependencies(
+        store: PackageStore,
+        dependencies: Vec<qsc_hir::hir::PackageId>,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+        language_features: LanguageFeatures,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let span = telemetry::Span::new(telemetry::COMPILE_SOURCES);
+        let compiler = Compiler::with_dependencies(
+            store,
+            dependencies,
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        )
+        .map_err(into_errors)
+        .inspect_err(|errors| span.fail(errors))?;
+
+        Self::from_compiler(false, compiler, capabilities, &span)
+    }
+
+    /// Creates a new incremental compiler for a project, compiling each package of its package
+    /// graph against its own dependencies and then the sources of the root package, which are
+    /// the sources that fragments are evaluated with. See [`packages::compile_dependencies`].
+    /// # Errors
+    /// If compiling any of the packages fails, compiler errors are returned.
+    pub fn from_package_graph(
+        graph: PackageGraphSources,
+        entry: Option<Arc<str>>,
+        package_type: PackageType,
+        capabilities: TargetCapabilityFlags,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let needs_std =
+            !graph.root.no_stdlib || graph.packages.values().any(|info| !info.no_stdlib);
+        let (mut store, std) =
+            compile::shared_store(capabilities, needs_std.then_some(StdFeatures::all()));
+        let (dependencies, errors) =
+            packages::compile_dependencies(&mut store, std, &graph, capabilities);
+        if !errors.is_empty() {
+            return Err(into_errors(errors));
+        }
+
+        Self::with_dependencies(
+            store,
+            dependencies,
+            SourceMap::new(graph.root.sources, entry),
+            package_type,
+            capabilities,
+            graph.root.language_features,
+        )
+    }
+
+    fn from_compiler(
+        dbg: bool,
+        compiler: Compiler,
+        capabilities: TargetCapabilityFlags,
+        span: &telemetry::Span,
+    ) -> std::result::Result<Self, Vec<Error>> {
         let mut fir_store = fir::PackageStore::new();
         for (id, unit) in compiler.package_store() {
             fir_store.insert(
@@ -223,7 +685,8 @@ impl Interpreter {
                     .into_iter()
                     .map(|error| Error::Pass(WithSource::from_map(&source_package.sources, error)))
                     .collect::<Vec<_>>()
-            })?;
+            })
+            .inspect_err(|errors| span.fail(errors))?;
         }
 
         Ok(Self {
@@ -233,11 +696,26 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new().with_debug(dbg),
             env: Env::default(),
-            sim: sim_circuit_backend(),
+            new_backend: Rc::new(|| Ok(sparse_sim())),
+            gate_folds: 0,
+            sim: sim_circuit_backend(sparse_sim()),
             quantum_seed: None,
             classical_seed: None,
             package,
             source_package: map_hir_package_to_fir(source_package_id),
+            history: Vec::new(),
+            autosave: None,
+            paused: None,
+            receivers: RoutingReceiver::default(),
+            reducers: BTreeMap::new(),
+            explain_errors: false,
+            value_formatter: Rc::new(ValueFormat::default()),
+            output_limit: OutputLimit::default(),
+            output_limit_exceeded: None,
+            sandbox: None,
+            codegen_targets: CodegenRegistry::default(),
+            warnings: Vec::new(),
+            metadata: BTreeMap::new(),
         })
     }
 
@@ -269,11 +747,26 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new(),
             env: Env::default(),
-            sim: sim_circuit_backend(),
+            new_backend: Rc::new(|| Ok(sparse_sim())),
+            gate_folds: 0,
+            sim: sim_circuit_backend(sparse_sim()),
             quantum_seed: None,
             classical_seed: None,
             package: map_hir_package_to_fir(package_id),
             source_package: map_hir_package_to_fir(source_package_id),
+            history: Vec::new(),
+            autosave: None,
+            paused: None,
+            receivers: RoutingReceiver::default(),
+            reducers: BTreeMap::new(),
+            explain_errors: false,
+            value_formatter: Rc::new(ValueFormat::default()),
+            output_limit: OutputLimit::default(),
+            output_limit_exceeded: None,
+            sandbox: None,
+            codegen_targets: CodegenRegistry::default(),
+            warnings: Vec::new(),
+            metadata: BTreeMap::new(),
         })
     }
 
@@ -285,6 +778,284 @@ impl Interpreter {
     pub fn set_classical_seed(&mut self, seed: Option<u64>) {
         self.classical_seed = seed;
     }
+
+    /// Folds every gate of the programs that run on a new simulator, such as with
+    /// [`Interpreter::run`] and [`Interpreter::reduce_shots`], replacing a gate `G` with
+    /// `G (G† G)ᵏ` for `k` folds. This does not change what the programs compute, but it scales
+    /// the gate noise of a noisy backend, such as one created with [`Interpreter::new_with_noise`],
+    /// by `2k + 1`, as zero-noise extrapolation needs. Programs run as written with zero folds,
+    /// which is the default. See [`Folded`].
+    pub fn set_gate_folds(&mut self, folds: usize) {
+        self.gate_folds = folds;
+    }
+
+    /// The number of times every gate is folded, see [`Interpreter::set_gate_folds`].
+    #[must_use]
+    pub fn gate_folds(&self) -> usize {
+        self.gate_folds
+    }
+
+    /// Creates a simulator for a program to run on, with its gates folded if requested.
+    fn new_sim(&self) -> std::result::Result<BoxedBackend, Vec<Error>> {
+        let sim = (self.new_backend)().map_err(|reason| vec![Error::Simulator(reason)])?;
+        Ok(if self.gate_folds == 0 {
+            sim
+        } else {
+            Box::new(Folded::new(sim, self.gate_folds))
+        })
+    }
+
+    /// Sets both the quantum and the classical random seeds, or clears them when `None`. With a
+    /// seed set, evaluating the same fragments or entry expressions in a new interpreter produces
+    /// the same measurement outcomes and random numbers, which makes nondeterministic failures
+    /// reproducible.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.set_quantum_seed(seed);
+        self.set_classical_seed(seed);
+    }
+
+    /// Traces calls to the callables matched by the given filter, sending a message to the
+    /// receiver on each entry and exit, or disables tracing when `None`. Tracing applies to
+    /// fragments as well as to entry expressions.
+    pub fn set_call_trace(&mut self, call_trace: Option<CallTrace>) {
+        self.env.set_call_trace(call_trace);
+    }
+
+    /// Sets the host callbacks invoked before and after each evaluated statement, or removes them
+    /// when `None`. Statements are only reported for code lowered with debug information, so the
+    /// interpreter must be created with [`Interpreter::new_with_debug`] for the hooks to be invoked.
+    ///
+    /// Hooks cannot be set while a sandbox is enabled, so this does nothing in that case.
+    pub fn set_stmt_hooks(&mut self, stmt_hooks: Option<Rc<RefCell<dyn StmtHooks>>>) {
+        if self.sandbox.is_none() {
+            self.env.set_stmt_hooks(stmt_hooks);
+        }
+    }
+
+    /// Periodically saves a checkpoint of the running evaluation to the given file, or stops when
+    /// `None`. This bounds the work lost when the host is interrupted during a long evaluation:
+    /// restoring the last checkpoint with [`Interpreter::restore`] and calling
+    /// [`Interpreter::resume`] continues the evaluation from the statement it was about to start.
+    ///
+    /// Checkpoints are taken at statement boundaries of evaluated fragments, so the interpreter
+    /// must be created with [`Interpreter::new_with_debug`] for them to be saved. Entry
+    /// expressions run on a new environment and are not saved. A checkpoint that cannot be saved
+    /// does not fail the evaluation; see [`Interpreter::take_autosave_error`].
+    pub fn set_autosave(&mut self, autosave: Option<Autosave>) {
+        self.autosave = autosave.map(|autosave| {
+            let hook = checkpoint::AutosaveHook {
+                path: autosave.path,
+                session: self.session_checkpoint(),
+                error: None,
+            };
+            (autosave.interval, Rc::new(RefCell::new(hook)))
+        });
+        self.env.set_checkpoint_hook(self.checkpoint_hook());
+    }
+
+    /// Takes the error of the last checkpoint that [`Interpreter::set_autosave`] could not save,
+    /// such as when the file cannot be written or a value being evaluated cannot be saved.
+    pub fn take_autosave_error(&mut self) -> Option<std::io::Error> {
+        self.autosave
+            .as_ref()
+            .and_then(|(_, hook)| hook.borrow_mut().error.take())
+    }
+
+    fn checkpoint_hook(&self) -> Option<(usize, Rc<RefCell<dyn CheckpointHook>>)> {
+        self.autosave
+            .as_ref()
+            .map(|(interval, hook)| (*interval, hook.clone() as Rc<RefCell<dyn CheckpointHook>>))
+    }
+
+    /// Appends the summary of the extended explanation for each error code, as returned by
+    /// [`crate::explain::summary`], to the help text of errors returned from evaluation. This is
+    /// intended for hosts aimed at newcomers to Q#.
+    pub fn set_explain_errors(&mut self, explain_errors: bool) {
+        self.explain_errors = explain_errors;
+    }
+
+    /// Returns a handle that interrupts evaluation in this interpreter when triggered, for hosts
+    /// to call from a Ctrl+C handler or another thread. Evaluation stops at the next statement or
+    /// loop iteration with an error for which [`Error::is_interrupted`] is true.
+    ///
+    /// The session stays usable after an interruption: top-level variables keep the values they
+    /// had when evaluation stopped, and the scopes of callables that were running are left. Each
+    /// evaluation clears the handle when it starts, so triggering it while nothing is running has
+    /// no effect.
+    #[must_use]
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.env.interrupt_handle().clone()
+    }
+
+    /// Sets how values returned by evaluation are rendered by [`Interpreter::format_value`]. By
+    /// default, values are rendered the same way as their `Display` implementation.
+    pub fn set_value_formatter(&mut self, value_formatter: Rc<dyn ValueFormatter>) {
+        self.value_formatter = value_formatter;
+    }
+
+    /// Renders a value returned by evaluation with the interpreter's value formatter.
+    #[must_use]
+    pub fn format_value(&self, value: &Value) -> String {
+        self.value_formatter.format(value)
+    }
+
+    /// Limits the output of each evaluation, so that a program producing output in a long loop
+    /// cannot flood the host. Output past the limit is dropped while evaluation continues, and
+    /// [`Interpreter::output_limit_exceeded`] reports what was dropped. There is no limit by
+    /// default.
+    pub fn set_output_limit(&mut self, output_limit: OutputLimit) {
+        self.output_limit = output_limit;
+    }
+
+    /// Returns the notice for the output dropped by the most recent evaluation because it
+    /// exceeded the output limit, or `None` if all of its output was forwarded to the receiver.
+    #[must_use]
+    pub fn output_limit_exceeded(&self) -> Option<OutputLimitExceeded> {
+        self.output_limit_exceeded
+    }
+
+    /// Enables the limits of the sandbox for running untrusted code, or disables them when
+    /// `None`. Enabling a sandbox removes the statement hooks, replaces the output limit with the
+    /// sandbox's, and checks the sources the interpreter was created with, since they were
+    /// compiled before the sandbox existed. Disabling it removes the qubit, time and output limits.
+    /// # Errors
+    /// Returns the uses of denied namespaces in the interpreter's sources, in which case the
+    /// sandbox is not enabled.
+    pub fn set_sandbox(&mut self, sandbox: Option<Sandbox>) -> std::result::Result<(), Vec<Error>> {
+        let Some(sandbox) = sandbox else {
+            self.sandbox = None;
+            self.env.set_qubit_limit(None);
+            self.output_limit = OutputLimit::default();
+            return Ok(());
+        };
+
+        let unit = self
+            .compiler
+            .package_store()
+            .get(self.compiler.source_package_id())
+            .expect("source package should be in the store");
+        let errors = sandbox.check(self.compiler.package_store(), &unit.package);
+        if !errors.is_empty() {
+            return Err(self.explain(
+                errors
+                    .into_iter()
+                    .map(|error| Error::Sandbox(WithSource::from_map(&unit.sources, error)))
+                    .collect(),
+            ));
+        }
+
+        self.env.set_stmt_hooks(None);
+        self.env.set_qubit_limit(Some(sandbox.max_qubits));
+        self.output_limit = sandbox.output_limit;
+        self.sandbox = Some(sandbox);
+        Ok(())
+    }
+
+    /// Ends the session, releasing the simulator state, the top-level variables and the
+    /// registered receivers before returning. Dropping the interpreter does the same, so this is
+    /// for hosts that want the teardown to happen at a known point, such as a grading service
+    /// that runs many short sessions.
+    pub fn close(self) {
+        drop(self);
+    }
+
+    /// Releases the simulator memory first, since it can be far larger than the rest of the
+    /// session, and then the state that refers to host resources.
+    fn release(&mut self) {
+        self.sim.close();
+        self.env = Env::default();
+        self.receivers = RoutingReceiver::default();
+        self.history.clear();
+    }
+
+    fn explain(&self, errors: Vec<Error>) -> Vec<Error> {
+        if self.explain_errors {
+            errors
+                .into_iter()
+                .map(crate::explain::Explained::wrap)
+                .collect()
+        } else {
+            errors
+        }
+    }
+
+    /// Creates the environment for a standalone evaluation, which shares the session's call
+    /// tracing configuration and statement hooks but none of its bindings.
+    fn entry_env(&self) -> Env {
+        let mut env = Env::default();
+        env.set_call_trace(self.env.call_trace().cloned());
+        env.set_stmt_hooks(self.env.stmt_hooks().cloned());
+        env.set_interrupt_handle(self.env.interrupt_handle().clone());
+        env.set_qubit_limit(self.env.qubit_limit());
+        env
+    }
+
+    /// Opens the given namespaces for all subsequently evaluated fragments, in addition to the
+    /// namespaces that are always opened by the prelude. Namespaces can come from the standard
+    /// library, the sources the interpreter was created with or any other package in the store.
+    /// # Errors
+    /// Returns a vector of errors if any of the namespaces do not exist.
+    pub fn extend_prelude(&mut self, namespaces: &[&str]) -> std::result::Result<(), Vec<Error>> {
+        if namespaces.is_empty() {
+            return Ok(());
+        }
+
+        let opens = namespaces
+            .iter()
+            .map(|namespace| format!("open {namespace};"))
+            .collect::<String>();
+        let increment = self.compile_fragments("<prelude>".to_string(), opens)?;
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        self.eval_increment(&mut out, increment).map(|_| ())
+    }
+
+    /// The namespaces that are open for the fragments of the session, in the order they were
+    /// opened, with the alias each one was opened as. This includes the namespaces opened by
+    /// [`Interpreter::extend_prelude`], but not the standard prelude.
+    #[must_use]
+    pub fn opens(&self) -> Vec<(String, Option<Rc<str>>)> {
+        self.compiler.opens()
+    }
+
+    /// Defines an operation with the given name in the session that applies the oracle, so that
+    /// later fragments and entry expressions can call it. See [`Oracle`] for the type of the
+    /// operation for each kind of oracle.
+    /// # Errors
+    /// Returns a vector of errors if the name is not an identifier or the oracle is not valid.
+    pub fn define_oracle(
+        &mut self,
+        name: &str,
+        oracle: &Oracle,
+    ) -> std::result::Result<(), Vec<Error>> {
+        let declaration = oracle
+            .declaration(name)
+            .map_err(|error| vec![error.into()])?;
+        let increment = self.compile_fragments(format!("<oracle {name}>"), declaration)?;
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        self.eval_increment(&mut out, increment).map(|_| ())
+    }
+
+    /// Defines an operation with the given name in the session that applies the gates of the
+    /// circuit to a qubit array, so that later fragments and entry expressions can call it. See
+    /// [`qsc_circuit::CircuitBuilder::to_qsharp`] for the type of the operation.
+    /// # Errors
+    /// Returns a vector of errors if the name is not an identifier.
+    pub fn define_circuit(
+        &mut self,
+        name: &str,
+        circuit: &qsc_circuit::CircuitBuilder,
+    ) -> std::result::Result<(), Vec<Error>> {
+        let declaration = circuit
+            .to_qsharp(name)
+            .map_err(|error| vec![error.into()])?;
+        let increment = self.compile_fragments(format!("<circuit {name}>"), declaration)?;
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        self.eval_increment(&mut out, increment).map(|_| ())
+    }
+
     /// Executes the entry expression until the end of execution.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -292,17 +1063,23 @@ impl Interpreter {
         &mut self,
         receiver: &mut impl Receiver,
     ) -> std::result::Result<Value, Vec<Error>> {
-        let graph = self.get_entry_exec_graph()?;
+        let graph = self
+            .get_entry_exec_graph()
+            .map_err(|errors| self.explain(errors))?;
         eval(
             self.source_package,
             self.classical_seed,
             graph,
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.entry_env(),
             &mut self.sim,
             receiver,
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
         )
+        .map_err(|errors| self.explain(errors))
     }
 
     /// Executes the entry expression until the end of execution, using the given simulator backend
@@ -312,7 +1089,9 @@ impl Interpreter {
         sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
         receiver: &mut impl Receiver,
     ) -> std::result::Result<Value, Vec<Error>> {
-        let graph = self.get_entry_exec_graph()?;
+        let graph = self
+            .get_entry_exec_graph()
+            .map_err(|errors| self.explain(errors))?;
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
         }
@@ -322,10 +1101,31 @@ impl Interpreter {
             graph,
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.entry_env(),
             sim,
             receiver,
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
         )
+        .map_err(|errors| self.explain(errors))
+    }
+
+    /// Executes the entry expression like [`Interpreter::eval_entry_with_sim`] on a new
+    /// simulator, recording the evaluation and each operation in the simulator in the trace.
+    /// # Errors
+    /// If the parsing of the fragments fails, an error is returned.
+    /// If there is a runtime error when interpreting the fragments, an error is returned.
+    #[cfg(not(any(target_family = "wasm")))]
+    pub fn eval_entry_traced(
+        &mut self,
+        receiver: &mut impl Receiver,
+        trace: &mut ChromeTrace,
+    ) -> std::result::Result<Value, Vec<Error>> {
+        let mut sim = self.new_sim()?;
+        trace.span("evaluate entry", chrome_trace::EVAL, |trace| {
+            self.eval_entry_with_sim(&mut TracedSim::new(&mut sim, trace), receiver)
+        })
     }
 
     fn get_entry_exec_graph(&self) -> std::result::Result<Rc<[ExecGraphNode]>, Vec<Error>> {
@@ -346,13 +1146,88 @@ impl Interpreter {
         fragments: &str,
     ) -> InterpretResult {
         let label = self.next_line_label();
+        self.compile_fragments(label, fragments.to_string())
+            .and_then(|increment| self.eval_increment(receiver, increment))
+            .map_err(|errors| self.explain(errors))
+    }
 
-        let increment = self
-            .compiler
-            .compile_fragments_fail_fast(&label, fragments)
-            .map_err(into_errors)?;
+    /// Evaluates the given fragments like [`Interpreter::eval_fragments`], and also returns the
+    /// static type and shape of the resulting value. The type is that of the last fragment if it
+    /// is an expression without a trailing semicolon, and `Unit` otherwise.
+    /// # Errors
+    /// If the parsing of the fragments fails, an error is returned.
+    /// If the compilation of the fragments fails, an error is returned.
+    /// If there is a runtime error when interpreting the fragments, an error is returned.
+    pub fn eval_fragments_with_metadata(
+        &mut self,
+        receiver: &mut impl Receiver,
+        fragments: &str,
+    ) -> std::result::Result<(Value, ValueMetadata), Vec<Error>> {
+        let label = self.next_line_label();
+        self.compile_fragments(label, fragments.to_string())
+            .and_then(|increment| {
+                let ty = match increment.0.hir.stmts.last().map(|stmt| &stmt.kind) {
+                    Some(qsc_hir::hir::StmtKind::Expr(expr)) => expr.ty.clone(),
+                    _ => qsc_hir::ty::Ty::UNIT,
+                };
+                let value = self.eval_increment(receiver, increment)?;
+                let metadata = ValueMetadata::new(ty, &value);
+                Ok((value, metadata))
+            })
+            .map_err(|errors| self.explain(errors))
+    }
 
-        self.eval_increment(receiver, increment)
+    /// Evaluates the given fragments like [`Interpreter::eval_fragments`], and also returns the
+    /// warnings for the fragments, such as for unused variables or unreachable code. Warnings do
+    /// not stop the fragments from being evaluated.
+    /// # Errors
+    /// If the parsing of the fragments fails, an error is returned.
+    /// If the compilation of the fragments fails, an error is returned.
+    /// If there is a runtime error when interpreting the fragments, an error is returned.
+    pub fn eval_fragments_with_warnings(
+        &mut self,
+        receiver: &mut impl Receiver,
+        fragments: &str,
+    ) -> std::result::Result<(Value, Vec<crate::compile::Warning>), Vec<Error>> {
+        let label = self.next_line_label();
+        self.compile_fragments(label, fragments.to_string())
+            .and_then(|increment| {
+                let warnings = std::mem::take(&mut self.warnings);
+                let value = self.eval_increment(receiver, increment)?;
+                Ok((value, warnings))
+            })
+            .map_err(|errors| self.explain(errors))
+    }
+
+    /// Evaluates the given fragments like [`Interpreter::eval_fragments`], but reports each stage
+    /// of compilation and evaluation, along with any output, as an [`Event`] to the given callback.
+    /// The final event is always either [`Event::Result`] or [`Event::Error`].
+    pub fn eval_fragments_with_events(
+        &mut self,
+        fragments: &str,
+        on_event: &mut impl FnMut(Event),
+    ) {
+        let label = self.next_line_label();
+        on_event(Event::CompileStarted {
+            label: label.clone(),
+        });
+        let result = self
+            .compile_fragments(label, fragments.to_string())
+            .and_then(|increment| {
+                on_event(Event::EvalStarted);
+                let mut receiver = events::EventReceiver {
+                    on_event: &mut *on_event,
+                };
+                let result = self.eval_increment(&mut receiver, increment);
+                if let Some(notice) = self.output_limit_exceeded {
+                    on_event(Event::OutputLimitExceeded(notice));
+                }
+                result
+            });
+        on_event(match result {
+            Ok(value) => Event::Result(value),
+            Err(errors) => Event::Error(self.explain(errors)),
+        });
     }
 
     /// It is assumed that if there were any parse errors on the fragments, the caller would have
@@ -361,57 +1236,559 @@ impl Interpreter {
     ///  # Errors
     /// If the compilation of the fragments fails, an error is returned.
     /// If there is a runtime error when interpreting the fragments, an error is returned.
-    pub fn eval_ast_fragments(
-        &mut self,
-        receiver: &mut impl Receiver,
-        fragments: &str,
-        package: qsc_ast::ast::Package,
-    ) -> InterpretResult {
-        let label = self.next_line_label();
+    pub fn eval_ast_fragments(
+        &mut self,
+        receiver: &mut impl Receiver,
+        fragments: &str,
+        package: qsc_ast::ast::Package,
+    ) -> InterpretResult {
+        let label = self.next_line_label();
+        let result = self
+            .compiler
+            .compile_ast_fragments_fail_fast(&label, fragments, package)
+            .map_err(into_errors);
+        self.record_fragment(
+            label,
+            fragments.to_string(),
+            checkpoint::FragmentKind::Fragments,
+            result,
+        )
+        .and_then(|increment| self.eval_increment(receiver, increment))
+        .map_err(|errors| self.explain(errors))
+    }
+
+    /// Interprets a whole Q# source file, such as a file pasted into a REPL, rather than
+    /// fragments. The file may contain namespaces and any number of items, but no top-level
+    /// statements; items outside of a `namespace` block are placed in a namespace named after
+    /// the file, as they are in a project. The file is added to the source map under the given
+    /// name, so errors in it refer to the file and its lines rather than to an interpreter line.
+    ///
+    /// If the file declares exactly one callable with the `@EntryPoint()` attribute, that
+    /// callable is run and its result returned. Otherwise the result is `Unit`.
+    /// Use [`crate::incremental::is_source_file`] to tell whether pasted input is a whole file.
+    /// # Errors
+    /// If the parsing or compilation of the file fails, an error is returned.
+    /// If there is a runtime error when running the entry point, an error is returned.
+    pub fn interpret_source(
+        &mut self,
+        receiver: &mut impl Receiver,
+        name: &str,
+        contents: &str,
+    ) -> InterpretResult {
+        self.compile_source(name.to_string(), contents.to_string())
+            .and_then(|increment| {
+                let entry_point = entry_point_path(&increment.0.hir);
+                self.eval_increment(receiver, increment)?;
+                match entry_point {
+                    Some(path) => {
+                        let label = self.next_line_label();
+                        self.compile_fragments(label, format!("{path}()"))
+                            .and_then(|increment| self.eval_increment(receiver, increment))
+                    }
+                    None => Ok(Value::unit()),
+                }
+            })
+            .map_err(|errors| self.explain(errors))
+    }
+
+    /// Compiles and lowers the fragments, recording them in the history.
+    fn compile_fragments(
+        &mut self,
+        label: String,
+        source: String,
+    ) -> std::result::Result<(Increment, Vec<ExecGraphNode>), Vec<Error>> {
+        let span = telemetry::Span::new(telemetry::COMPILE_FRAGMENTS);
+        span.metadata(&self.metadata);
+        let result = self
+            .compiler
+            .compile_fragments_fail_fast(&label, &source)
+            .map_err(into_errors);
+        self.record_fragment(label, source, checkpoint::FragmentKind::Fragments, result)
+            .inspect_err(|errors| span.fail(errors))
+    }
+
+    /// Compiles and lowers a whole source file, recording it in the history.
+    fn compile_source(
+        &mut self,
+        name: String,
+        source: String,
+    ) -> std::result::Result<(Increment, Vec<ExecGraphNode>), Vec<Error>> {
+        let result = self
+            .compiler
+            .compile_source_fail_fast(&name, &source)
+            .map_err(into_errors);
+        self.record_fragment(name, source, checkpoint::FragmentKind::SourceFile, result)
+    }
+
+    fn record_fragment(
+        &mut self,
+        label: String,
+        source: String,
+        kind: checkpoint::FragmentKind,
+        result: std::result::Result<Increment, Vec<Error>>,
+    ) -> std::result::Result<(Increment, Vec<ExecGraphNode>), Vec<Error>> {
+        self.warnings = self.compiler.take_warnings();
+        let lowered = result.and_then(|increment| {
+            let (graph, _) = self.lower(&increment)?;
+            Ok((increment, graph))
+        });
+        self.history.push(checkpoint::Fragment {
+            label,
+            source,
+            compiled: lowered.is_ok(),
+            kind,
+        });
+        lowered
+    }
+
+    fn eval_increment(
+        &mut self,
+        receiver: &mut impl Receiver,
+        (increment, graph): (Increment, Vec<ExecGraphNode>),
+    ) -> InterpretResult {
+        // Updating the compiler state with the new AST/HIR nodes
+        // is not necessary for the interpreter to function, as all
+        // the state required for evaluation already exists in the
+        // FIR store. It could potentially save some memory
+        // *not* to do hold on to the AST/HIR, but it is done
+        // here to keep the package stores consistent.
+        self.compiler.update(increment);
+
+        if self.paused.take().is_some() {
+            // The paused evaluation is abandoned, along with the scopes restored for it.
+            self.env.leave_scopes_above(1);
+        }
+        if let Some((_, hook)) = &self.autosave {
+            hook.borrow_mut().session = self.session_checkpoint();
+        }
+
+        eval(
+            self.package,
+            self.classical_seed,
+            graph.into(),
+            self.compiler.package_store(),
+            &self.fir_store,
+            &mut self.env,
+            &mut self.sim,
+            receiver,
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
+        )
+    }
+
+    /// Runs the given entry expression on a new instance of the environment and simulator,
+    /// but using the current compilation.
+    pub fn run(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+    ) -> std::result::Result<InterpretResult, Vec<Error>> {
+        let mut sim = self.new_sim()?;
+        self.run_with_sim(&mut sim, receiver, expr)
+    }
+
+    /// Runs the given entry expression like [`Interpreter::run`], recording its compilation, its
+    /// evaluation and each operation in the simulator in the trace.
+    /// # Errors
+    /// Returns an error if the expression fails to compile.
+    #[cfg(not(any(target_family = "wasm")))]
+    pub fn run_traced(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        trace: &mut ChromeTrace,
+    ) -> std::result::Result<InterpretResult, Vec<Error>> {
+        let (graph, _) = trace
+            .span("compile entry expression", chrome_trace::COMPILE, |_| {
+                self.compile_entry_expr(expr)
+            })
+            .map_err(|errors| self.explain(errors))?;
+
+        let mut sim = self.new_sim()?;
+        if self.quantum_seed.is_some() {
+            sim.set_seed(self.quantum_seed);
+        }
+        Ok(trace.span("evaluate", chrome_trace::EVAL, |trace| {
+            eval(
+                self.package,
+                self.classical_seed,
+                graph.into(),
+                self.compiler.package_store(),
+                &self.fir_store,
+                &mut self.entry_env(),
+                &mut TracedSim::new(&mut sim, trace),
+                receiver,
+                self.output_limit,
+                &mut self.output_limit_exceeded,
+                self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
+            )
+            .map_err(|errors| self.explain(errors))
+        }))
+    }
+
+    /// Runs the given entry expression like [`Interpreter::run`] once per shot, compiling it only
+    /// once. Every shot starts from a new environment and simulator, so that no state carries
+    /// over between shots, and the output of every shot is sent to the receiver in order.
+    ///
+    /// When seeds are set with [`Interpreter::set_quantum_seed`] or
+    /// [`Interpreter::set_classical_seed`], each shot uses seeds derived from them and the
+    /// shot's index, as in [`crate::shots`], so that the shots are repeatable without all
+    /// producing the same outcome.
+    /// # Errors
+    /// Returns an error if the expression fails to compile.
+    pub fn run_shots(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        shots: usize,
+    ) -> std::result::Result<Vec<InterpretResult>, Vec<Error>> {
+        let mut results = Vec::with_capacity(shots);
+        self.for_each_shot(receiver, expr, shots, |result| results.push(result))?;
+        Ok(results)
+    }
+
+    /// Registers a reducer under the given name for the values of the shots run by
+    /// [`Interpreter::reduce_shots`], replacing and returning any reducer previously registered
+    /// under that name.
+    pub fn add_reducer(
+        &mut self,
+        name: impl Into<String>,
+        reducer: Box<dyn Reducer>,
+    ) -> Option<Box<dyn Reducer>> {
+        self.reducers.insert(name.into(), reducer)
+    }
+
+    /// Unregisters and returns the reducer registered under the given name.
+    pub fn remove_reducer(&mut self, name: &str) -> Option<Box<dyn Reducer>> {
+        self.reducers.remove(name)
+    }
+
+    /// Runs the given entry expression once per shot like [`Interpreter::run_shots`], adding the
+    /// value of every shot to the registered reducers instead of returning it, so that the
+    /// values of all the shots are never held at once.
+    /// # Errors
+    /// Returns an error if the expression fails to compile.
+    pub fn reduce_shots(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        shots: usize,
+    ) -> std::result::Result<ReducedShots, Vec<Error>> {
+        let mut reducers = std::mem::take(&mut self.reducers);
+        let mut reduced = ReducedShots {
+            shots,
+            failed: 0,
+            first_error: None,
+            values: BTreeMap::new(),
+        };
+        let run = self.for_each_shot(receiver, expr, shots, |result| match result {
+            Ok(value) => {
+                for reducer in reducers.values_mut() {
+                    reducer.add(&value);
+                }
+            }
+            Err(errors) => {
+                reduced.failed += 1;
+                if reduced.first_error.is_none() {
+                    reduced.first_error = Some(errors);
+                }
+            }
+        });
+        reduced.values = reducers
+            .iter_mut()
+            .map(|(name, reducer)| (name.clone(), reducer.finish()))
+            .collect();
+        self.reducers = reducers;
+        run.map(|()| reduced)
+    }
+
+    /// Compiles the entry expression once and runs it once per shot, as described in
+    /// [`Interpreter::run_shots`], passing the result of every shot to `on_shot` in order.
+    fn for_each_shot(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        shots: usize,
+        mut on_shot: impl FnMut(InterpretResult),
+    ) -> std::result::Result<(), Vec<Error>> {
+        let (graph, _) = self
+            .compile_entry_expr(expr)
+            .map_err(|errors| self.explain(errors))?;
+        let graph: Rc<[ExecGraphNode]> = graph.into();
+
+        for shot in 0..shots {
+            let span = telemetry::Span::new(telemetry::SHOT);
+            span.metadata(&self.metadata);
+            span.attribute("qsc.shot", i64::try_from(shot).unwrap_or(i64::MAX));
+            let mut sim = self.new_sim()?;
+            if let Some(seed) = self.quantum_seed {
+                sim.set_seed(Some(shot_seeds(seed, shot).0));
+            }
+            let classical_seed = self.classical_seed.map(|seed| shot_seeds(seed, shot).1);
+            let result = eval(
+                self.package,
+                classical_seed,
+                graph.clone(),
+                self.compiler.package_store(),
+                &self.fir_store,
+                &mut self.entry_env(),
+                &mut sim,
+                receiver,
+                self.output_limit,
+                &mut self.output_limit_exceeded,
+                self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
+            )
+            .map_err(|errors| self.explain(errors));
+            on_shot(result);
+        }
+        Ok(())
+    }
+
+    /// Registers a receiver under the given name for the given kinds of output produced by
+    /// [`Interpreter::eval_fragments_routed`] and [`Interpreter::run_routed`], replacing and
+    /// returning any receiver previously registered under that name. Each kind of output is sent
+    /// to every receiver registered for it, so hosts can, for example, send program messages to
+    /// one stream and state dumps to a rich display.
+    pub fn add_receiver(
+        &mut self,
+        name: impl Into<String>,
+        kinds: &[OutputKind],
+        receiver: Box<dyn Receiver>,
+    ) -> Option<Box<dyn Receiver>> {
+        self.receivers.insert(name, kinds, receiver)
+    }
+
+    /// Unregisters and returns the receiver registered under the given name.
+    pub fn remove_receiver(&mut self, name: &str) -> Option<Box<dyn Receiver>> {
+        self.receivers.remove(name)
+    }
+
+    /// Evaluates the given fragments like [`Interpreter::eval_fragments`], routing output to the
+    /// registered receivers.
+    /// # Errors
+    /// If the compilation of the fragments fails, an error is returned.
+    /// If there is a runtime error when interpreting the fragments, an error is returned.
+    pub fn eval_fragments_routed(&mut self, fragments: &str) -> InterpretResult {
+        let mut receivers = std::mem::take(&mut self.receivers);
+        let result = self.eval_fragments(&mut receivers, fragments);
+        self.receivers = receivers;
+        result
+    }
 
-        let increment = self
-            .compiler
-            .compile_ast_fragments_fail_fast(&label, fragments, package)
-            .map_err(into_errors)?;
+    /// Runs the given entry expression like [`Interpreter::run`], routing output to the
+    /// registered receivers.
+    /// # Errors
+    /// Returns an error if the expression fails to compile.
+    pub fn run_routed(&mut self, expr: &str) -> std::result::Result<InterpretResult, Vec<Error>> {
+        let mut receivers = std::mem::take(&mut self.receivers);
+        let result = self.run(&mut receivers, expr);
+        self.receivers = receivers;
+        result
+    }
 
-        self.eval_increment(receiver, increment)
+    /// Attaches a key/value pair to the session, replacing the value of the key if it is already
+    /// set. Metadata is not used by the interpreter itself, but is carried along for hosts to
+    /// trace what they produce back to the session: it is added to telemetry spans as
+    /// `qsc.session.<key>` attributes, saved in checkpoints, and included in
+    /// [`Interpreter::export`].
+    pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata.insert(key.into(), value.into());
     }
 
-    fn eval_increment(
-        &mut self,
-        receiver: &mut impl Receiver,
-        increment: Increment,
-    ) -> InterpretResult {
-        let (graph, _) = self.lower(&increment)?;
+    /// Removes a key from the session metadata, returning its value if it was set.
+    pub fn remove_metadata(&mut self, key: &str) -> Option<String> {
+        self.metadata.remove(key)
+    }
 
-        // Updating the compiler state with the new AST/HIR nodes
-        // is not necessary for the interpreter to function, as all
-        // the state required for evaluation already exists in the
-        // FIR store. It could potentially save some memory
-        // *not* to do hold on to the AST/HIR, but it is done
-        // here to keep the package stores consistent.
-        self.compiler.update(increment);
+    /// The metadata attached to the session with [`Interpreter::set_metadata`], sorted by key.
+    #[must_use]
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
 
-        eval(
-            self.package,
-            self.classical_seed,
-            graph.into(),
+    /// Converts the result of an evaluation into a JSON object with the session metadata, so
+    /// that results and error reports saved by the host can be traced back to the session. A
+    /// value is stored under `value` like its `application/json` MIME representation, and errors
+    /// are stored under `errors` as structured diagnostics with their stack traces, if any.
+    #[must_use]
+    pub fn export(&self, result: &InterpretResult, encoding: Encoding) -> serde_json::Value {
+        let mut export = serde_json::json!({ "metadata": self.metadata });
+        match result {
+            Ok(value) => export["value"] = crate::mime::value_to_json(value),
+            Err(errors) => {
+                export["errors"] = errors
+                    .iter()
+                    .map(|error| {
+                        let mut json = error.to_json(encoding);
+                        if let Some(stack_trace) = error.stack_trace() {
+                            json["stackTrace"] = stack_trace.as_str().into();
+                        }
+                        json
+                    })
+                    .collect();
+            }
+        }
+        export
+    }
+
+    /// Continues the evaluation restored by [`Interpreter::restore`] from a checkpoint that was
+    /// saved while it was running, see [`Interpreter::set_autosave`]. Returns `None` if there is
+    /// no such evaluation.
+    pub fn resume(&mut self, receiver: &mut impl Receiver) -> Option<InterpretResult> {
+        let state = self.paused.take()?;
+        if let Some((_, hook)) = &self.autosave {
+            hook.borrow_mut().session = self.session_checkpoint();
+        }
+        let fir_store = &self.fir_store;
+        let result = eval_with(
             self.compiler.package_store(),
-            &self.fir_store,
+            fir_store,
             &mut self.env,
             &mut self.sim,
             receiver,
-        )
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
+            |env, sim, receiver| qsc_eval::resume(state, fir_store, env, sim, receiver),
+        );
+        Some(result.map_err(|errors| self.explain(errors)))
     }
 
-    /// Runs the given entry expression on a new instance of the environment and simulator,
-    /// but using the current compilation.
-    pub fn run(
-        &mut self,
-        receiver: &mut impl Receiver,
-        expr: &str,
-    ) -> std::result::Result<InterpretResult, Vec<Error>> {
-        self.run_with_sim(&mut SparseSim::new(), receiver, expr)
+    /// The parts of a checkpoint that describe how the session was built, without any state.
+    fn session_checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            lines: self.lines,
+            fragments: self.history.clone(),
+            quantum_seed: self.quantum_seed,
+            classical_seed: self.classical_seed,
+            qubit_count: 0,
+            state: Vec::new(),
+            variables: Vec::new(),
+            allocations: Vec::new(),
+            metadata: self.metadata.clone(),
+            running: None,
+        }
+    }
+
+    /// Saves the state of the session: the fragments and entry expressions compiled so far, the
+    /// variables bound by top-level statements, the simulator state and the call stacks that
+    /// allocated its qubits, the seeds and the metadata.
+    /// Hosts running long sessions can call this periodically between evaluations and save the
+    /// result with [`Checkpoint::save`], then use [`Interpreter::restore`] after an interruption
+    /// to continue where they left off.
+    ///
+    /// This takes checkpoints between evaluations. Checkpoints of a long evaluation while it runs
+    /// are saved by [`Interpreter::set_autosave`]. Random number generators restart from their
+    /// seeds rather than from their position at the time of the checkpoint.
+    /// # Errors
+    /// Returns an error if a variable holds a value that cannot be saved.
+    pub fn checkpoint(&mut self) -> std::result::Result<Checkpoint, Vec<Error>> {
+        let (state, qubit_count) = self.sim.capture_quantum_state();
+        let variables = self
+            .env
+            .global_variables()
+            .map(|(id, var)| (id, var.clone()))
+            .collect::<Vec<_>>();
+        let errors = variables
+            .iter()
+            .filter(|(_, var)| !checkpoint::is_supported(&var.value, qubit_count))
+            .map(|(_, var)| Error::UnsupportedCheckpointValue(var.name.to_string()))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Checkpoint {
+            qubit_count,
+            state,
+            variables,
+            allocations: self
+                .env
+                .qubit_allocations()
+                .map(|(id, frames)| (id, frames.to_vec()))
+                .collect(),
+            ..self.session_checkpoint()
+        })
+    }
+
+    /// Restores a session saved with [`Interpreter::checkpoint`]. The interpreter must be newly
+    /// created, with the same sources and options as the one that was checkpointed. Saved
+    /// metadata is restored for the keys that have not already been set on this interpreter. If
+    /// the checkpoint was saved while an evaluation was running, that evaluation is left paused
+    /// for [`Interpreter::resume`] to continue.
+    /// # Errors
+    /// Returns an error if the interpreter has already evaluated fragments, if the saved
+    /// fragments no longer compile the way they did when the checkpoint was taken, or if the
+    /// simulator cannot prepare the saved state, such as a backend passed to
+    /// [`Interpreter::new_with_backend`] that runs on hardware.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> std::result::Result<(), Vec<Error>> {
+        if !self.history.is_empty() {
+            return Err(vec![Error::CheckpointRestoreNotFresh]);
+        }
+
+        // The graph of the last fragment, which a running evaluation was started with.
+        let mut graph = None;
+        for fragment in &checkpoint.fragments {
+            let (label, source) = (fragment.label.clone(), fragment.source.clone());
+            let lowered = match fragment.kind {
+                checkpoint::FragmentKind::Fragments => self.compile_fragments(label, source),
+                checkpoint::FragmentKind::SourceFile => self.compile_source(label, source),
+                checkpoint::FragmentKind::EntryExpr => {
+                    let compiled = self.compile_entry_expr(&source).is_ok();
+                    if compiled != fragment.compiled {
+                        return Err(vec![Error::CheckpointMismatch]);
+                    }
+                    graph = None;
+                    continue;
+                }
+            };
+            if lowered.is_ok() != fragment.compiled {
+                return Err(vec![Error::CheckpointMismatch]);
+            }
+            graph = lowered.ok().map(|(increment, graph)| {
+                self.compiler.update(increment);
+                graph
+            });
+        }
+        self.lines = checkpoint.lines;
+
+        self.env = self.entry_env();
+        self.env.set_checkpoint_hook(self.checkpoint_hook());
+        for (id, var) in &checkpoint.variables {
+            self.env.bind_variable_in_top_frame(*id, var.clone());
+        }
+        self.sim
+            .restore_quantum_state(&checkpoint.state, checkpoint.qubit_count)
+            .map_err(|reason| vec![Error::CheckpointStateUnsupported(reason)])?;
+        // The restored qubits stay allocated, so they count toward the sandbox qubit limit and
+        // show up in `allocated_qubits` like the originals did.
+        self.env
+            .set_qubit_allocations(checkpoint.allocations.iter().cloned());
+        self.set_quantum_seed(checkpoint.quantum_seed);
+        self.set_classical_seed(checkpoint.classical_seed);
+        for (key, value) in &checkpoint.metadata {
+            self.metadata
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+
+        if let Some(saved) = &checkpoint.running {
+            let state = graph
+                .and_then(|graph| {
+                    State::resume(
+                        self.package,
+                        graph.into(),
+                        saved.clone(),
+                        &self.fir_store,
+                        &mut self.env,
+                        self.classical_seed,
+                    )
+                })
+                .ok_or_else(|| vec![Error::CheckpointMismatch])?;
+            self.paused = Some(state);
+        }
+        Ok(())
     }
 
     /// Gets the current quantum state of the simulator.
@@ -419,23 +1796,148 @@ impl Interpreter {
         self.sim.capture_quantum_state()
     }
 
+    /// Gets an estimate of the numerical error accumulated in the current quantum state of the
+    /// session, or `None` if the simulator does not estimate its error. See
+    /// [`Interpreter::new_with_precision`].
+    pub fn error_estimate(&mut self) -> Option<f64> {
+        self.sim.error_estimate()
+    }
+
+    /// Gets the current quantum state of the simulator like [`Interpreter::get_quantum_state`],
+    /// as a [`QuantumState`] that hosts can render themselves, such as from its probabilities or
+    /// the Bloch vectors of its qubits.
+    pub fn quantum_state(&mut self) -> QuantumState {
+        let (amplitudes, qubit_count) = self.sim.capture_quantum_state();
+        QuantumState::new(amplitudes, qubit_count)
+    }
+
+    /// Lists the qubits allocated by evaluated fragments that have not been released, with the
+    /// call stacks that allocated them. In an interactive session these are typically qubits
+    /// from top-level `use` statements, which stay allocated until the session ends.
+    #[must_use]
+    pub fn allocated_qubits(&self) -> Vec<QubitAllocation> {
+        self.env
+            .qubit_allocations()
+            .map(|(id, frames)| QubitAllocation {
+                id,
+                call_stack: format_frames(
+                    self.compiler.package_store(),
+                    &self.fir_store,
+                    frames.to_vec(),
+                ),
+            })
+            .collect()
+    }
+
+    /// Formats [`Interpreter::allocated_qubits`] as a report for display, so that users can find
+    /// qubits that were left allocated, for example by a `use` statement that a refactoring
+    /// moved out of its block.
+    #[must_use]
+    pub fn qubit_leak_report(&self) -> String {
+        let allocations = self.allocated_qubits();
+        if allocations.is_empty() {
+            return "No qubits are allocated.\n".to_string();
+        }
+        let mut report = format!(
+            "{} {} still allocated:\n",
+            allocations.len(),
+            if allocations.len() == 1 {
+                "qubit is"
+            } else {
+                "qubits are"
+            }
+        );
+        for allocation in allocations {
+            report.push_str(&format!(
+                "Qubit {} allocated\n{}",
+                allocation.id, allocation.call_stack
+            ));
+        }
+        report
+    }
+
+    /// Computes the fidelity between the simulator state of this interpreter and that of
+    /// `other`, which is 1 when the states are equal up to global phase and 0 when they are
+    /// orthogonal. This is useful for checking an optimized implementation against a reference
+    /// one. Qubits are matched in allocation order.
+    /// Returns `None` if the two simulators have different numbers of qubits allocated.
+    pub fn state_fidelity_with(&mut self, other: &mut Interpreter) -> Option<f64> {
+        let (state, qubit_count) = self.sim.capture_quantum_state();
+        let (other_state, other_qubit_count) = other.sim.capture_quantum_state();
+        (qubit_count == other_qubit_count).then(|| state_fidelity(&state, &other_state))
+    }
+
+    /// Gets the global phase, in radians, accumulated by the simulator separately from the
+    /// relative phases of its state. The amplitudes returned by
+    /// [`Interpreter::get_quantum_state`] already include this phase.
+    pub fn get_global_phase(&mut self) -> f64 {
+        self.sim.global_phase()
+    }
+
     /// Get the current circuit representation of the program.
     pub fn get_circuit(&self) -> Circuit {
         self.sim.chained.snapshot()
     }
 
+    /// Gets the variables bound by top-level statements so far, in the order they were bound,
+    /// such as for a variables pane in a REPL.
+    #[must_use]
+    pub fn get_locals(&self) -> Vec<VariableInfo> {
+        user_variables(self.env.get_variables_in_frame(0))
+    }
+
     /// Performs QIR codegen using the given entry expression on a new instance of the environment
     /// and simulator but using the current compilation.
+    ///
+    /// The QIR targets the profile of the interpreter, or the Base profile if the interpreter is
+    /// unrestricted, in which case the fragments evaluated so far are only required to support
+    /// the Base profile where the entry expression reaches them.
     pub fn qirgen(&mut self, expr: &str) -> std::result::Result<String, Vec<Error>> {
-        if self.capabilities == TargetCapabilityFlags::all() {
-            return Err(vec![Error::UnsupportedRuntimeCapabilities]);
+        let program = self.entry_rir(expr)?;
+        Ok(rir_to_qir(&program))
+    }
+
+    /// Generates code for the given entry expression with the code generation target of the
+    /// given name, on a new instance of the environment and simulator but using the current
+    /// compilation, like [`Interpreter::qirgen`] does for QIR. The `qir` target is registered
+    /// by default, and others can be added with [`Interpreter::register_codegen_target`].
+    pub fn codegen(&mut self, expr: &str, target: &str) -> std::result::Result<String, Vec<Error>> {
+        if self.codegen_targets.get(target).is_none() {
+            return Err(vec![Error::UnknownCodegenTarget(target.to_string())]);
         }
+        let program = self.entry_rir(expr)?;
+        let target = self
+            .codegen_targets
+            .get(target)
+            .expect("target should be registered");
+        target
+            .generate(&program)
+            .map_err(|message| vec![Error::CodegenTarget(target.name().to_string(), message)])
+    }
+
+    /// Adds a code generation target that [`Interpreter::codegen`] can select by its name,
+    /// replacing the target with the same name if there is one.
+    pub fn register_codegen_target(&mut self, target: impl CodegenTarget + 'static) {
+        self.codegen_targets.register(target);
+    }
+
+    /// The names of the code generation targets that [`Interpreter::codegen`] can select.
+    pub fn codegen_targets(&self) -> impl Iterator<Item = &str> {
+        self.codegen_targets.names()
+    }
 
+    fn entry_rir(&mut self, expr: &str) -> std::result::Result<qsc_rir::rir::Program, Vec<Error>> {
         // Compile the expression. This operation will set the expression as
         // the entry-point in the FIR store.
         let (graph, compute_properties) = self.compile_entry_expr(expr)?;
 
-        let Some(compute_properties) = compute_properties else {
+        let capabilities = if self.capabilities == TargetCapabilityFlags::all() {
+            // Capability analysis does not run for unrestricted sessions, so code generation
+            // analyzes the store itself and partial evaluation reports unsupported features.
+            TargetCapabilityFlags::empty()
+        } else if compute_properties.is_some() {
+            self.capabilities
+        } else {
             // This can only happen if capability analysis was not run. This would be a bug
             // and we are in a bad state and can't proceed.
             panic!("internal error: compute properties not set after lowering entry expression");
@@ -451,14 +1953,7 @@ impl Interpreter {
             )
                 .into(),
         };
-        // Generate QIR
-        fir_to_qir(
-            &self.fir_store,
-            self.capabilities,
-            Some(compute_properties),
-            &entry,
-        )
-        .map_err(|e| {
+        fir_to_rir(&self.fir_store, capabilities, compute_properties, &entry).map_err(|e| {
             let hir_package_id = match e.span() {
                 Some(span) => span.package,
                 None => map_fir_package_to_hir(self.package),
@@ -504,7 +1999,7 @@ impl Interpreter {
         };
 
         let circuit = if simulate {
-            let mut sim = sim_circuit_backend();
+            let mut sim = sim_circuit_backend(self.new_sim()?);
 
             self.run_with_sim_no_output(entry_expr, &mut sim)?;
 
@@ -530,7 +2025,9 @@ impl Interpreter {
         receiver: &mut impl Receiver,
         expr: &str,
     ) -> std::result::Result<InterpretResult, Vec<Error>> {
-        let (graph, _) = self.compile_entry_expr(expr)?;
+        let (graph, _) = self
+            .compile_entry_expr(expr)
+            .map_err(|errors| self.explain(errors))?;
 
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
@@ -542,10 +2039,85 @@ impl Interpreter {
             graph.into(),
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.entry_env(),
             sim,
             receiver,
-        ))
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
+        )
+        .map_err(|errors| self.explain(errors)))
+    }
+
+    /// Runs the given entry expression on a backend that counts the resources it uses, such as
+    /// qubits, gates and circuit depth, instead of simulating it. See [`ResourceEstimate`].
+    /// # Errors
+    /// Returns a vector of errors if compiling or evaluating the entry expression fails.
+    pub fn estimate(&mut self, expr: &str) -> std::result::Result<ResourceEstimate, Vec<Error>> {
+        let mut counter = estimate::Counter::default();
+        self.run_with_sim_no_output(Some(expr.to_string()), &mut counter)?;
+        Ok(counter.finish())
+    }
+
+    /// Runs the given entry expression like [`Interpreter::run`], returning along with the result
+    /// an estimate of the numerical error accumulated in the final state, so that callers can
+    /// judge whether the precision of the interpreter, see [`Interpreter::new_with_precision`],
+    /// is accurate enough for their program. The estimate is `None` for simulators that do not
+    /// estimate their error, such as the default double precision one.
+    /// # Errors
+    /// Returns an error if the expression fails to compile or the simulator cannot be created.
+    pub fn run_with_error_estimate(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+    ) -> std::result::Result<(InterpretResult, Option<f64>), Vec<Error>> {
+        let mut sim = self.new_sim()?;
+        let result = self.run_with_sim(&mut sim, receiver, expr)?;
+        Ok((result, sim.error_estimate()))
+    }
+
+    /// Runs the given entry expression like [`Interpreter::run_with_sim`], executing every
+    /// operation on both backends of `sim` and returning, along with the result, the places where
+    /// the backends diverged, as described by [`Differential`]. Divergences are attributed to the
+    /// statement that was running when the interpreter was created with debug information and is
+    /// not sandboxed. Statement hooks set with [`Interpreter::set_stmt_hooks`] are not invoked
+    /// during the run.
+    /// # Errors
+    /// Returns an error if the expression fails to compile.
+    pub fn run_differential<T1, T2>(
+        &mut self,
+        sim: &mut Differential<T1, T2>,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        position_encoding: Encoding,
+    ) -> std::result::Result<(InterpretResult, Vec<Divergence>), Vec<Error>>
+    where
+        T1: Backend<ResultType = bool>,
+        T2: Backend<ResultType = bool>,
+    {
+        let stmt_hooks = self.env.stmt_hooks().cloned();
+        if self.sandbox.is_none() {
+            self.env.set_stmt_hooks(Some(sim.stmt_hooks()));
+        }
+        let result = self.run_with_sim(sim, receiver, expr);
+        self.env.set_stmt_hooks(stmt_hooks);
+        let divergences = sim
+            .divergences()
+            .iter()
+            .map(|divergence| Divergence {
+                operation: divergence.operation,
+                kind: divergence.kind,
+                location: divergence.stmt.map(|stmt| {
+                    Location::from(
+                        stmt.span,
+                        stmt.package,
+                        self.compiler.package_store(),
+                        position_encoding,
+                    )
+                }),
+            })
+            .collect();
+        Ok((result?, divergences))
     }
 
     fn run_with_sim_no_output(
@@ -574,25 +2146,50 @@ impl Interpreter {
             graph,
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.entry_env(),
             sim,
             &mut out,
+            self.output_limit,
+            &mut self.output_limit_exceeded,
+            self.sandbox.as_ref().map(|sandbox| sandbox.max_time),
         )
     }
 
+    /// Compiles and lowers the entry expression, recording it in the history, since compiling it
+    /// allocates ids just like compiling fragments does.
     fn compile_entry_expr(
         &mut self,
         expr: &str,
     ) -> std::result::Result<(Vec<ExecGraphNode>, Option<PackageStoreComputeProperties>), Vec<Error>>
     {
+        let result = self.compile_and_lower_entry_expr(expr);
+        self.history.push(checkpoint::Fragment {
+            label: String::new(),
+            source: expr.to_string(),
+            compiled: result.is_ok(),
+            kind: checkpoint::FragmentKind::EntryExpr,
+        });
+        result
+    }
+
+    fn compile_and_lower_entry_expr(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<(Vec<ExecGraphNode>, Option<PackageStoreComputeProperties>), Vec<Error>>
+    {
+        let span = telemetry::Span::new(telemetry::COMPILE_ENTRY);
+        span.metadata(&self.metadata);
         let increment = self
             .compiler
             .compile_entry_expr(expr)
-            .map_err(into_errors)?;
+            .map_err(into_errors)
+            .inspect_err(|errors| span.fail(errors))?;
 
         // `lower` will update the entry expression in the FIR store,
         // and it will always return an empty list of statements.
-        let (graph, compute_properties) = self.lower(&increment)?;
+        let (graph, compute_properties) = self
+            .lower(&increment)
+            .inspect_err(|errors| span.fail(errors))?;
 
         // The AST and HIR packages in `increment` only contain an entry
         // expression and no statements. The HIR *can* contain items if the entry
@@ -616,6 +2213,22 @@ impl Interpreter {
         unit_addition: &qsc_frontend::incremental::Increment,
     ) -> core::result::Result<(Vec<ExecGraphNode>, Option<PackageStoreComputeProperties>), Vec<Error>>
     {
+        if let Some(sandbox) = &self.sandbox {
+            let errors = sandbox.check(self.compiler.package_store(), &unit_addition.hir);
+            if !errors.is_empty() {
+                let source_package = self
+                    .compiler
+                    .package_store()
+                    .get(map_fir_package_to_hir(self.package))
+                    .expect("package should exist in the package store");
+                return Err(errors
+                    .into_iter()
+                    .map(|error| {
+                        Error::Sandbox(WithSource::from_map(&source_package.sources, error))
+                    })
+                    .collect());
+            }
+        }
         if self.capabilities != TargetCapabilityFlags::all() {
             return self.run_fir_passes(unit_addition);
         }
@@ -673,15 +2286,7 @@ impl Interpreter {
         &mut self,
         operation_expr: &str,
     ) -> std::result::Result<(&qsc_hir::hir::Item, FunctorApp), Vec<Error>> {
-        let mut sink = std::io::sink();
-        let mut out = GenericReceiver::new(&mut sink);
-        let (store_item_id, functor_app) = match self.eval_fragments(&mut out, operation_expr)? {
-            Value::Closure(b) => (b.id, b.functor),
-            Value::Global(item_id, functor_app) => (item_id, functor_app),
-            _ => return Err(vec![Error::NotAnOperation]),
-        };
-        let package = map_fir_package_to_hir(store_item_id.package);
-        let local_item_id = crate::hir::LocalItemId::from(usize::from(store_item_id.item));
+        let (package, local_item_id, functor_app) = self.eval_to_callable(operation_expr)?;
         let unit = self
             .compiler
             .package_store()
@@ -694,11 +2299,92 @@ impl Interpreter {
             .expect("item should exist in the package");
         Ok((item, functor_app))
     }
+
+    /// Evaluate an expression that evaluates to a callable, and return the HIR package ID,
+    /// item ID and functor application for the callable.
+    fn eval_to_callable(
+        &mut self,
+        callable_expr: &str,
+    ) -> std::result::Result<
+        (qsc_hir::hir::PackageId, crate::hir::LocalItemId, FunctorApp),
+        Vec<Error>,
+    > {
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        let (store_item_id, functor_app) = match self.eval_fragments(&mut out, callable_expr)? {
+            Value::Closure(b) => (b.id, b.functor),
+            Value::Global(item_id, functor_app) => (item_id, functor_app),
+            _ => return Err(vec![Error::NotAnOperation]),
+        };
+        let package = map_fir_package_to_hir(store_item_id.package);
+        let local_item_id = crate::hir::LocalItemId::from(usize::from(store_item_id.item));
+        Ok((package, local_item_id, functor_app))
+    }
+
+    /// Generates Q# source for a callable with all of its specializations written out
+    /// explicitly, including the ones generated by the compiler (e.g. `adjoint auto`).
+    ///
+    /// `callable` can be the name of a callable or any expression that evaluates to one,
+    /// e.g. `Sample.Main` or `Microsoft.Quantum.Canon.ApplyToEach`.
+    pub fn explicit_specializations(
+        &mut self,
+        callable: &str,
+    ) -> std::result::Result<String, Vec<Error>> {
+        let (package, item, _) = self.eval_to_callable(callable)?;
+        write_callable_specs(self.compiler.package_store(), package, item)
+            .ok_or_else(|| vec![Error::NotAnOperation])
+    }
+
+    /// Generates Q# source for the sources the interpreter was created with, as they are
+    /// after compiler passes have run.
+    #[must_use]
+    pub fn generated_source(&self) -> String {
+        let store = self.compiler.package_store();
+        write_package(store, self.compiler.source_package_id()).unwrap_or_default()
+    }
+
+    /// Indexes the items available to the session, including internal items declared in the
+    /// interpreter's sources or fragments, for symbol search.
+    #[must_use]
+    pub fn symbols(&self) -> SymbolIndex {
+        self.compiler.symbols()
+    }
+
+    /// Finds the name at the offset into one of the interpreter's sources or fragments, and
+    /// returns its declaration, doc comment and type, such as for hover in a notebook.
+    #[must_use]
+    pub fn hover(&self, source: &str, offset: u32) -> Option<Hover> {
+        self.compiler.hover(source, offset)
+    }
+
+    /// Completes the word before the cursor, which is a byte offset into the line, with the
+    /// variables bound so far and the names that are in scope for the next fragment, such as for
+    /// tab completion in a REPL.
+    #[must_use]
+    pub fn completions(&self, line: &str, cursor: usize) -> Vec<CompletionItem> {
+        completion::completions(
+            line,
+            cursor,
+            &self.symbols(),
+            &self.compiler.opens(),
+            &self.get_locals(),
+        )
+    }
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn sparse_sim() -> BoxedBackend {
+    Box::new(SparseSim::new())
 }
 
-fn sim_circuit_backend() -> BackendChain<SparseSim, CircuitBuilder> {
+fn sim_circuit_backend(sim: BoxedBackend) -> BackendChain<BoxedBackend, CircuitBuilder> {
     BackendChain::new(
-        SparseSim::new(),
+        sim,
         CircuitBuilder::new(CircuitConfig {
             // When using in conjunction with the simulator,
             // the circuit builder should *not* perform base profile
@@ -733,6 +2419,8 @@ pub struct Debugger {
     position_encoding: Encoding,
     /// The current state of the evaluator.
     state: State,
+    /// The statements set with [`Debugger::set_breakpoint`].
+    breakpoints: Vec<StmtId>,
 }
 
 impl Debugger {
@@ -756,6 +2444,7 @@ impl Debugger {
             interpreter,
             position_encoding,
             state: State::new(source_package_id, entry_exec_graph, None),
+            breakpoints: Vec::new(),
         })
     }
 
@@ -787,6 +2476,100 @@ impl Debugger {
             })
     }
 
+    /// Sets a breakpoint on the innermost statement that contains the utf-8 byte offset in the
+    /// source with the given name, or when no statement contains it, such as for indentation,
+    /// on the first statement after it. The breakpoint pauses [`Debugger::step_into`],
+    /// [`Debugger::step_over`], [`Debugger::step_out`] and [`Debugger::resume`] before the
+    /// statement runs.
+    /// Returns the statement, or `None` if there is no such source or statement.
+    pub fn set_breakpoint(&mut self, path: &str, offset: u32) -> Option<BreakpointSpan> {
+        let unit = self.source_package();
+        let contents = unit.sources.find_by_name(path)?.contents.clone();
+        let offsets = |span: &BreakpointSpan| {
+            (
+                span.range
+                    .start
+                    .to_utf8_byte_offset(self.position_encoding, &contents),
+                span.range
+                    .end
+                    .to_utf8_byte_offset(self.position_encoding, &contents),
+            )
+        };
+        let spans = self.get_breakpoints(path);
+        let innermost = spans
+            .iter()
+            .filter(|span| {
+                let (lo, hi) = offsets(span);
+                lo <= offset && offset < hi
+            })
+            .min_by_key(|span| {
+                let (lo, hi) = offsets(span);
+                hi - lo
+            });
+        let span = innermost
+            .or_else(|| spans.iter().find(|span| offsets(span).0 >= offset))
+            .copied()?;
+        let id = StmtId::from(span.id);
+        if !self.breakpoints.contains(&id) {
+            self.breakpoints.push(id);
+        }
+        Some(span)
+    }
+
+    /// Removes every breakpoint set with [`Debugger::set_breakpoint`].
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Runs until the next statement, entering any callable called by the current one.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub fn step_into(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        self.step(receiver, StepAction::In)
+    }
+
+    /// Runs until the next statement of the current callable, or of its caller once it returns.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub fn step_over(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        self.step(receiver, StepAction::Next)
+    }
+
+    /// Runs until the current callable returns to its caller.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub fn step_out(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        self.step(receiver, StepAction::Out)
+    }
+
+    /// Continues running until a breakpoint is hit or the entry point returns.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub fn resume(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        self.step(receiver, StepAction::Continue)
+    }
+
+    fn step(
+        &mut self,
+        receiver: &mut impl Receiver,
+        step: StepAction,
+    ) -> std::result::Result<StepResult, Vec<Error>> {
+        let breakpoints = self.breakpoints.clone();
+        self.eval_step(receiver, &breakpoints, step)
+    }
+
     #[must_use]
     pub fn get_stack_frames(&self) -> Vec<StackFrame> {
         let frames = self.state.get_stack_frames();
@@ -855,12 +2638,15 @@ impl Debugger {
 
     #[must_use]
     pub fn get_locals(&self) -> Vec<VariableInfo> {
-        self.interpreter
-            .env
-            .get_variables_in_top_frame()
-            .into_iter()
-            .filter(|v| !v.name.starts_with('@'))
-            .collect()
+        user_variables(self.interpreter.env.get_variables_in_top_frame())
+    }
+
+    /// Gets the variables in scope in a frame of the call stack, where depth `0` is the top
+    /// level and depth `n` is the `n`th frame returned by [`Debugger::get_stack_frames`],
+    /// counting from 1. Frames deeper than the call stack have no variables.
+    #[must_use]
+    pub fn get_locals_in_frame(&self, depth: usize) -> Vec<VariableInfo> {
+        user_variables(self.interpreter.env.get_variables_in_frame(depth))
     }
 
     fn source_package(&self) -> &CompileUnit {
@@ -872,6 +2658,14 @@ impl Debugger {
     }
 }
 
+/// Removes the variables introduced by the compiler, whose names start with `@`.
+fn user_variables(variables: Vec<VariableInfo>) -> Vec<VariableInfo> {
+    variables
+        .into_iter()
+        .filter(|v| !v.name.starts_with('@'))
+        .collect()
+}
+
 /// Wrapper function for `qsc_eval::eval` that handles error conversion.
 #[allow(clippy::too_many_arguments)]
 fn eval(
@@ -883,17 +2677,68 @@ fn eval(
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
+    output_limit: OutputLimit,
+    output_limit_exceeded: &mut Option<OutputLimitExceeded>,
+    time_limit: Option<std::time::Duration>,
 ) -> InterpretResult {
-    qsc_eval::eval(
-        package,
-        classical_seed,
-        exec_graph,
+    eval_with(
+        package_store,
         fir_store,
         env,
         sim,
         receiver,
+        output_limit,
+        output_limit_exceeded,
+        time_limit,
+        |env, sim, receiver| {
+            qsc_eval::eval(
+                package,
+                classical_seed,
+                exec_graph,
+                fir_store,
+                env,
+                sim,
+                receiver,
+            )
+        },
     )
-    .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
+}
+
+/// Runs an evaluation with the output and time limits applied, converting its errors.
+#[allow(clippy::too_many_arguments)]
+fn eval_with<S, R>(
+    package_store: &PackageStore,
+    fir_store: &fir::PackageStore,
+    env: &mut Env,
+    sim: &mut S,
+    receiver: &mut R,
+    output_limit: OutputLimit,
+    output_limit_exceeded: &mut Option<OutputLimitExceeded>,
+    time_limit: Option<std::time::Duration>,
+    eval: impl FnOnce(
+        &mut Env,
+        &mut S,
+        &mut LimitedReceiver<'_, R>,
+    ) -> std::result::Result<Value, (qsc_eval::Error, Vec<Frame>)>,
+) -> InterpretResult
+where
+    S: Backend,
+    S::ResultType: Into<val::Result>,
+    R: Receiver,
+{
+    let span = telemetry::Span::new(telemetry::EVAL);
+    env.interrupt_handle().clear();
+    #[cfg(not(any(target_family = "wasm")))]
+    let _watchdog =
+        time_limit.map(|limit| sandbox::Watchdog::start(env.interrupt_handle().clone(), limit));
+    #[cfg(any(target_family = "wasm"))]
+    let _ = time_limit;
+    let mut receiver = LimitedReceiver::new(receiver, output_limit);
+    let result = eval(env, sim, &mut receiver)
+        .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
+        .inspect_err(|errors| span.fail(errors));
+    *output_limit_exceeded = receiver.exceeded();
+    result
 }
 
 /// Represents a stack frame for debugging.
@@ -906,6 +2751,16 @@ pub struct StackFrame {
     pub location: Location,
 }
 
+/// A place where the backends of a differential run diverged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The operation after which the backends disagreed, such as `h` or `mresetz`.
+    pub operation: &'static str,
+    pub kind: DivergenceKind,
+    /// The statement that was running, if known.
+    pub location: Option<Location>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct BreakpointSpan {
     /// The id of the statement representing the breakpoint location.
@@ -1019,6 +2874,35 @@ fn eval_error(
     vec![error::from_eval(error, package_store, stack_trace).into()]
 }
 
+/// Returns the fully qualified name of the only callable in the package marked with the
+/// `@EntryPoint()` attribute, if there is exactly one.
+fn entry_point_path(package: &qsc_hir::hir::Package) -> Option<String> {
+    let mut entry_points = package.items.values().filter_map(|item| match &item.kind {
+        qsc_hir::hir::ItemKind::Callable(decl)
+            if item.attrs.contains(&qsc_hir::hir::Attr::EntryPoint) =>
+        {
+            let namespace = item
+                .parent
+                .and_then(|parent| match &package.items.get(parent)?.kind {
+                    qsc_hir::hir::ItemKind::Namespace(name, _) => Some(
+                        name.iter()
+                            .map(|ident| ident.name.as_ref())
+                            .collect::<Vec<_>>()
+                            .join("."),
+                    ),
+                    _ => None,
+                });
+            Some(match namespace {
+                Some(namespace) => format!("{namespace}.{}", decl.name.name),
+                None => decl.name.name.to_string(),
+            })
+        }
+        _ => None,
+    });
+    let path = entry_points.next()?;
+    entry_points.next().is_none().then_some(path)
+}
+
 fn into_errors(errors: Vec<crate::compile::Error>) -> Vec<Error> {
     errors
         .into_iter()
@@ -0,0 +1,645 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Saving and restoring interpreter sessions, so that the state built up by a long series of
+//! evaluated fragments survives a crash or restart of the host process.
+//!
+//! A checkpoint records the fragments and entry expressions that were compiled, the values bound
+//! by top-level statements, the simulator state vector, the call stacks that allocated the live
+//! qubits and the session metadata. Restoring recompiles them in their original order, which
+//! reproduces the same item and variable ids, and then rebinds the values and prepares the saved
+//! state on fresh qubits.
+//!
+//! Checkpoints are taken between evaluations, or periodically by an [`Autosave`] while an
+//! evaluation runs. A checkpoint of a running evaluation also records where the evaluator was:
+//! the call stack, the values of the expressions being evaluated and the scopes of the running
+//! callables. Restoring it leaves the evaluation paused at the statement it was about to start.
+//!
+//! The format is a line-oriented text file. Floating point numbers are stored as their bit
+//! patterns so that amplitudes round-trip exactly.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::{BigInt, BigUint};
+use num_complex::Complex;
+use qsc_data_structures::{functors::FunctorApp, span::Span};
+use qsc_eval::{
+    debug::Frame,
+    hooks::CheckpointHook,
+    val::{self, Closure, Qubit, Value},
+    Env, SavedState, Variable,
+};
+use qsc_fir::fir::{LocalItemId, LocalVarId, PackageId, Pauli, StoreItemId};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+const HEADER: &str = "qsharp-checkpoint 3";
+
+/// A fragment passed to the interpreter, along with whether it compiled. Fragments that failed to
+/// compile are kept so that replaying them allocates ids exactly as the original session did.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Fragment {
+    pub label: String,
+    pub source: String,
+    pub compiled: bool,
+    pub kind: FragmentKind,
+}
+
+/// How a fragment was compiled, which decides how it is compiled again on restore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum FragmentKind {
+    /// Statements and items evaluated in the session, such as with
+    /// [`crate::interpret::Interpreter::eval_fragments`].
+    Fragments,
+    /// A whole source file passed to [`crate::interpret::Interpreter::interpret_source`].
+    SourceFile,
+    /// An entry expression compiled to be run, such as by [`crate::interpret::Interpreter::run`].
+    /// It binds no variables, but it allocates ids for the items and lambdas it defines.
+    EntryExpr,
+}
+
+/// A saved interpreter session. See [`crate::interpret::Interpreter::checkpoint`].
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub(super) lines: u32,
+    pub(super) fragments: Vec<Fragment>,
+    pub(super) quantum_seed: Option<u64>,
+    pub(super) classical_seed: Option<u64>,
+    pub(super) qubit_count: usize,
+    pub(super) state: Vec<(BigUint, Complex<f64>)>,
+    pub(super) variables: Vec<(LocalVarId, Variable)>,
+    pub(super) allocations: Vec<(usize, Vec<Frame>)>,
+    pub(super) metadata: BTreeMap<String, String>,
+    /// The evaluation of the last fragment, if the checkpoint was taken while it was running.
+    pub(super) running: Option<SavedState>,
+}
+
+/// Periodically saves checkpoints of running evaluations, see
+/// [`crate::interpret::Interpreter::set_autosave`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Autosave {
+    /// The file that each checkpoint replaces.
+    pub path: PathBuf,
+    /// The number of statements to evaluate between checkpoints.
+    pub interval: usize,
+}
+
+/// Saves a checkpoint combining the session, as it was when the running evaluation started,
+/// with the state of the evaluation.
+pub(super) struct AutosaveHook {
+    pub path: PathBuf,
+    pub session: Checkpoint,
+    /// The error of the last checkpoint that could not be saved, if any.
+    pub error: Option<io::Error>,
+}
+
+impl CheckpointHook for AutosaveHook {
+    fn checkpoint(
+        &mut self,
+        saved: SavedState,
+        env: &Env,
+        (state, qubit_count): (Vec<(BigUint, Complex<f64>)>, usize),
+    ) {
+        let variables = env
+            .global_variables()
+            .map(|(id, var)| (id, var.clone()))
+            .collect::<Vec<_>>();
+        let mut values = variables
+            .iter()
+            .map(|(_, var)| &var.value)
+            .chain(&saved.val_register)
+            .chain(saved.val_stack.iter().flatten())
+            .chain(
+                saved
+                    .scopes
+                    .iter()
+                    .flat_map(|(_, bindings)| bindings.iter().map(|(_, var)| &var.value)),
+            );
+        if !values.all(|value| is_supported(value, qubit_count)) {
+            self.error = Some(invalid("a value being evaluated cannot be saved"));
+            return;
+        }
+
+        let checkpoint = Checkpoint {
+            qubit_count,
+            state,
+            variables,
+            allocations: env
+                .qubit_allocations()
+                .map(|(id, frames)| (id, frames.to_vec()))
+                .collect(),
+            running: Some(saved),
+            ..self.session.clone()
+        };
+        if let Err(error) = checkpoint.save(&self.path) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to the given path. The file is written next to its destination and
+    /// then renamed, so an interruption while saving leaves any previous checkpoint intact.
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(self.to_string().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    /// Reads a checkpoint previously written with [`Checkpoint::save`].
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a valid checkpoint.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a checkpoint from its text form.
+    /// # Errors
+    /// Returns an error of kind `InvalidData` if the text is not a valid checkpoint.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut reader = Reader { rest: text };
+        for expected in HEADER.split(' ') {
+            reader.expect(expected)?;
+        }
+
+        let mut checkpoint = Checkpoint {
+            lines: 0,
+            fragments: Vec::new(),
+            quantum_seed: None,
+            classical_seed: None,
+            qubit_count: 0,
+            state: Vec::new(),
+            variables: Vec::new(),
+            allocations: Vec::new(),
+            metadata: BTreeMap::new(),
+            running: None,
+        };
+        loop {
+            match reader.token()? {
+                "lines" => checkpoint.lines = reader.number()?,
+                "seeds" => {
+                    checkpoint.quantum_seed = reader.seed()?;
+                    checkpoint.classical_seed = reader.seed()?;
+                }
+                entry @ ("fragment" | "source" | "entry") => {
+                    checkpoint.fragments.push(Fragment {
+                        label: reader.string()?.to_string(),
+                        source: reader.string()?.to_string(),
+                        compiled: reader.number::<u8>()? != 0,
+                        kind: match entry {
+                            "fragment" => FragmentKind::Fragments,
+                            "source" => FragmentKind::SourceFile,
+                            _ => FragmentKind::EntryExpr,
+                        },
+                    });
+                }
+                "meta" => {
+                    let key = reader.string()?.to_string();
+                    let value = reader.string()?.to_string();
+                    checkpoint.metadata.insert(key, value);
+                }
+                "qubits" => checkpoint.qubit_count = reader.number()?,
+                "amp" => {
+                    let index = BigUint::parse_bytes(reader.token()?.as_bytes(), 16)
+                        .ok_or_else(|| invalid("invalid basis state index"))?;
+                    let re = reader.double()?;
+                    let im = reader.double()?;
+                    checkpoint.state.push((index, Complex::new(re, im)));
+                }
+                "var" => {
+                    let binding = reader.binding()?;
+                    checkpoint.variables.push(binding);
+                }
+                "alloc" => {
+                    let qubit = reader.number()?;
+                    let len: usize = reader.number()?;
+                    let frames = (0..len)
+                        .map(|_| reader.frame())
+                        .collect::<io::Result<_>>()?;
+                    checkpoint.allocations.push((qubit, frames));
+                }
+                "level" => {
+                    let running = checkpoint.running.get_or_insert_with(SavedState::default);
+                    running.idx.push(reader.number()?);
+                    running.val_stack.push(reader.values()?);
+                }
+                "register" => {
+                    let running = checkpoint.running.get_or_insert_with(SavedState::default);
+                    running.val_register = Some(reader.value()?);
+                }
+                "frame" => {
+                    let running = checkpoint.running.get_or_insert_with(SavedState::default);
+                    running.frames.push(reader.frame()?);
+                }
+                "scope" => {
+                    let frame_id = reader.number()?;
+                    let len: usize = reader.number()?;
+                    let bindings = (0..len)
+                        .map(|_| reader.binding())
+                        .collect::<io::Result<_>>()?;
+                    let running = checkpoint.running.get_or_insert_with(SavedState::default);
+                    running.scopes.push((frame_id, bindings));
+                }
+                "end" => return Ok(checkpoint),
+                other => return Err(invalid(&format!("unexpected entry `{other}`"))),
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "lines {}", self.lines)?;
+        writeln!(
+            f,
+            "seeds {} {}",
+            fmt_seed(self.quantum_seed),
+            fmt_seed(self.classical_seed)
+        )?;
+        for (key, value) in &self.metadata {
+            writeln!(f, "meta {} {}", fmt_string(key), fmt_string(value))?;
+        }
+        for fragment in &self.fragments {
+            writeln!(
+                f,
+                "{} {} {} {}",
+                match fragment.kind {
+                    FragmentKind::Fragments => "fragment",
+                    FragmentKind::SourceFile => "source",
+                    FragmentKind::EntryExpr => "entry",
+                },
+                fmt_string(&fragment.label),
+                fmt_string(&fragment.source),
+                u8::from(fragment.compiled)
+            )?;
+        }
+        writeln!(f, "qubits {}", self.qubit_count)?;
+        for (index, amp) in &self.state {
+            writeln!(
+                f,
+                "amp {index:x} {:x} {:x}",
+                amp.re.to_bits(),
+                amp.im.to_bits()
+            )?;
+        }
+        for (id, var) in &self.variables {
+            let mut line = "var ".to_string();
+            write_binding(&mut line, *id, var)?;
+            writeln!(f, "{line}")?;
+        }
+        for (qubit, frames) in &self.allocations {
+            let mut line = format!("alloc {qubit} {}", frames.len());
+            for frame in frames {
+                line.push(' ');
+                write_frame(&mut line, frame)?;
+            }
+            writeln!(f, "{line}")?;
+        }
+        if let Some(running) = &self.running {
+            for (idx, values) in running.idx.iter().zip(&running.val_stack) {
+                let mut line = format!("level {idx} {}", values.len());
+                write_values(&mut line, values.iter())?;
+                writeln!(f, "{line}")?;
+            }
+            if let Some(value) = &running.val_register {
+                let mut line = "register ".to_string();
+                write_value(&mut line, value)?;
+                writeln!(f, "{line}")?;
+            }
+            for frame in &running.frames {
+                let mut line = "frame ".to_string();
+                write_frame(&mut line, frame)?;
+                writeln!(f, "{line}")?;
+            }
+            for (frame_id, bindings) in &running.scopes {
+                let mut line = format!("scope {frame_id} {}", bindings.len());
+                for (id, var) in bindings {
+                    line.push(' ');
+                    write_binding(&mut line, *id, var)?;
+                }
+                writeln!(f, "{line}")?;
+            }
+        }
+        writeln!(f, "end")
+    }
+}
+
+/// Returns whether the value can be stored in a checkpoint. Values produced by partial
+/// evaluation, such as unresolved variables and result ids, only have meaning inside the
+/// evaluation that created them.
+pub(super) fn is_supported(value: &Value, qubit_count: usize) -> bool {
+    match value {
+        Value::Array(items) => items.iter().all(|item| is_supported(item, qubit_count)),
+        Value::Tuple(items) => items.iter().all(|item| is_supported(item, qubit_count)),
+        Value::Closure(closure) => closure
+            .fixed_args
+            .iter()
+            .all(|item| is_supported(item, qubit_count)),
+        Value::Qubit(q) => q.0 < qubit_count,
+        Value::Result(val::Result::Id(_)) | Value::Var(_) => false,
+        Value::BigInt(_)
+        | Value::Bool(_)
+        | Value::Double(_)
+        | Value::Global(..)
+        | Value::Int(_)
+        | Value::Pauli(_)
+        | Value::Range(_)
+        | Value::Result(val::Result::Val(_))
+        | Value::String(_) => true,
+    }
+}
+
+fn fmt_seed(seed: Option<u64>) -> String {
+    seed.map_or_else(|| "-".to_string(), |seed| seed.to_string())
+}
+
+/// Strings are length-prefixed so they may contain whitespace and newlines.
+fn fmt_string(s: &str) -> String {
+    format!("{}:{s}", s.len())
+}
+
+fn write_value(out: &mut String, value: &Value) -> std::fmt::Result {
+    match value {
+        Value::Array(items) => {
+            write!(out, "array {}", items.len())?;
+            write_values(out, items.iter())
+        }
+        Value::BigInt(v) => write!(out, "bigint {v}"),
+        Value::Bool(v) => write!(out, "bool {v}"),
+        Value::Closure(closure) => {
+            write!(out, "closure ")?;
+            write_callable(out, closure.id, closure.functor)?;
+            write!(out, " {}", closure.fixed_args.len())?;
+            write_values(out, closure.fixed_args.iter())
+        }
+        Value::Double(v) => write!(out, "double {:x}", v.to_bits()),
+        Value::Global(id, functor) => {
+            write!(out, "global ")?;
+            write_callable(out, *id, *functor)
+        }
+        Value::Int(v) => write!(out, "int {v}"),
+        Value::Pauli(p) => write!(
+            out,
+            "pauli {}",
+            match p {
+                Pauli::I => "I",
+                Pauli::X => "X",
+                Pauli::Y => "Y",
+                Pauli::Z => "Z",
+            }
+        ),
+        Value::Qubit(q) => write!(out, "qubit {}", q.0),
+        Value::Range(range) => write!(
+            out,
+            "range {} {} {}",
+            range
+                .start
+                .map_or_else(|| "-".to_string(), |v| v.to_string()),
+            range.step,
+            range.end.map_or_else(|| "-".to_string(), |v| v.to_string())
+        ),
+        Value::Result(val::Result::Val(v)) => write!(out, "result {}", u8::from(*v)),
+        Value::String(s) => write!(out, "string {}", fmt_string(s)),
+        Value::Tuple(items) => {
+            write!(out, "tuple {}", items.len())?;
+            write_values(out, items.iter())
+        }
+        Value::Result(val::Result::Id(_)) | Value::Var(_) => {
+            panic!("value should have been checked before writing")
+        }
+    }
+}
+
+fn write_values<'a>(out: &mut String, values: impl Iterator<Item = &'a Value>) -> std::fmt::Result {
+    for value in values {
+        out.push(' ');
+        write_value(out, value)?;
+    }
+    Ok(())
+}
+
+fn write_callable(out: &mut String, id: StoreItemId, functor: FunctorApp) -> std::fmt::Result {
+    write!(
+        out,
+        "{} {} {} {}",
+        usize::from(id.package),
+        usize::from(id.item),
+        u8::from(functor.adjoint),
+        functor.controlled
+    )
+}
+
+fn write_binding(out: &mut String, id: LocalVarId, var: &Variable) -> std::fmt::Result {
+    write!(
+        out,
+        "{} {} {} {} ",
+        usize::from(id),
+        var.span.lo,
+        var.span.hi,
+        fmt_string(&var.name)
+    )?;
+    write_value(out, &var.value)
+}
+
+fn write_frame(out: &mut String, frame: &Frame) -> std::fmt::Result {
+    write!(
+        out,
+        "{} {} {} ",
+        frame.span.lo,
+        frame.span.hi,
+        usize::from(frame.caller)
+    )?;
+    write_callable(out, frame.id, frame.functor)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid checkpoint: {message}"),
+    )
+}
+
+struct Reader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Reader<'a> {
+    fn token(&mut self) -> io::Result<&'a str> {
+        let rest = self.rest.trim_start();
+        if rest.is_empty() {
+            return Err(invalid("unexpected end of file"));
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (token, rest) = rest.split_at(end);
+        self.rest = rest;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> io::Result<()> {
+        let token = self.token()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(invalid(&format!("expected `{expected}`, found `{token}`")))
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> io::Result<T> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| invalid(&format!("expected a number, found `{token}`")))
+    }
+
+    fn seed(&mut self) -> io::Result<Option<u64>> {
+        let token = self.token()?;
+        if token == "-" {
+            Ok(None)
+        } else {
+            token
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid(&format!("expected a seed, found `{token}`")))
+        }
+    }
+
+    fn bound(&mut self) -> io::Result<Option<i64>> {
+        let token = self.token()?;
+        if token == "-" {
+            Ok(None)
+        } else {
+            token
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid(&format!("expected a range bound, found `{token}`")))
+        }
+    }
+
+    fn double(&mut self) -> io::Result<f64> {
+        let token = self.token()?;
+        u64::from_str_radix(token, 16)
+            .map(f64::from_bits)
+            .map_err(|_| invalid(&format!("expected a double, found `{token}`")))
+    }
+
+    fn string(&mut self) -> io::Result<&'a str> {
+        let rest = self.rest.trim_start();
+        let colon = rest.find(':').ok_or_else(|| invalid("expected a string"))?;
+        let len: usize = rest[..colon]
+            .parse()
+            .map_err(|_| invalid("expected a string length"))?;
+        let body = &rest[colon + 1..];
+        let s = body
+            .get(..len)
+            .ok_or_else(|| invalid("string is truncated"))?;
+        self.rest = &body[len..];
+        Ok(s)
+    }
+
+    fn value(&mut self) -> io::Result<Value> {
+        Ok(match self.token()? {
+            "array" => Value::Array(Rc::new(self.values()?)),
+            "bigint" => {
+                let token = self.token()?;
+                Value::BigInt(
+                    token.parse::<BigInt>().map_err(|_| {
+                        invalid(&format!("expected a big integer, found `{token}`"))
+                    })?,
+                )
+            }
+            "bool" => Value::Bool(self.number()?),
+            "closure" => {
+                let (id, functor) = self.callable()?;
+                Value::Closure(Box::new(Closure {
+                    fixed_args: self.values()?.into(),
+                    id,
+                    functor,
+                }))
+            }
+            "double" => Value::Double(self.double()?),
+            "global" => {
+                let (id, functor) = self.callable()?;
+                Value::Global(id, functor)
+            }
+            "int" => Value::Int(self.number()?),
+            "pauli" => Value::Pauli(match self.token()? {
+                "I" => Pauli::I,
+                "X" => Pauli::X,
+                "Y" => Pauli::Y,
+                "Z" => Pauli::Z,
+                other => return Err(invalid(&format!("unknown Pauli `{other}`"))),
+            }),
+            "qubit" => Value::Qubit(Qubit(self.number()?)),
+            "range" => Value::Range(Box::new(val::Range {
+                start: self.bound()?,
+                step: self.number()?,
+                end: self.bound()?,
+            })),
+            "result" => Value::Result(val::Result::Val(self.number::<u8>()? != 0)),
+            "string" => Value::String(self.string()?.into()),
+            "tuple" => Value::Tuple(self.values()?.into()),
+            other => return Err(invalid(&format!("unknown value kind `{other}`"))),
+        })
+    }
+
+    fn values(&mut self) -> io::Result<Vec<Value>> {
+        let len: usize = self.number()?;
+        (0..len).map(|_| self.value()).collect()
+    }
+
+    fn binding(&mut self) -> io::Result<(LocalVarId, Variable)> {
+        let id = LocalVarId::from(self.number::<u32>()?);
+        let lo = self.number()?;
+        let hi = self.number()?;
+        let name: Rc<str> = self.string()?.into();
+        let value = self.value()?;
+        Ok((
+            id,
+            Variable {
+                name,
+                value,
+                span: Span { lo, hi },
+            },
+        ))
+    }
+
+    fn frame(&mut self) -> io::Result<Frame> {
+        let lo = self.number()?;
+        let hi = self.number()?;
+        let caller = PackageId::from(self.number::<usize>()?);
+        let (id, functor) = self.callable()?;
+        Ok(Frame {
+            span: Span { lo, hi },
+            id,
+            caller,
+            functor,
+        })
+    }
+
+    fn callable(&mut self) -> io::Result<(StoreItemId, FunctorApp)> {
+        let package = PackageId::from(self.number::<usize>()?);
+        let item = LocalItemId::from(self.number::<usize>()?);
+        let adjoint = self.number::<u8>()? != 0;
+        let controlled = self.number()?;
+        Ok((
+            StoreItemId { package, item },
+            FunctorApp {
+                adjoint,
+                controlled,
+            },
+        ))
+    }
+}
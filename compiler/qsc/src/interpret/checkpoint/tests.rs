@@ -0,0 +1,516 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Autosave, Checkpoint};
+use crate::interpret::{Error, Interpreter, Sandbox};
+use indoc::indoc;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::{
+    backend::{Backend, SparseSim},
+    output::CursorReceiver,
+    val::Value,
+};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::io::Cursor;
+
+fn sources() -> SourceMap {
+    SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    function Double(x : Int) : Int { x * 2 }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    )
+}
+
+fn interpreter() -> Interpreter {
+    Interpreter::new(
+        true,
+        sources(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created")
+}
+
+fn debug_interpreter() -> Interpreter {
+    Interpreter::new_with_debug(
+        true,
+        sources(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created")
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("qsc-{name}-{}", std::process::id()))
+}
+
+fn line(interpreter: &mut Interpreter, line: &str) -> (Result<Value, Vec<Error>>, String) {
+    let mut cursor = Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    (
+        interpreter.eval_fragments(&mut receiver, line),
+        receiver.dump(),
+    )
+}
+
+fn eval(interpreter: &mut Interpreter, source: &str) -> Value {
+    let (result, output) = line(interpreter, source);
+    result.unwrap_or_else(|errors| panic!("{source} should evaluate: {errors:?}\n{output}"))
+}
+
+/// Checkpoints the interpreter, writes the checkpoint to text and restores it from that text
+/// into a new interpreter.
+fn round_trip(interpreter: &mut Interpreter) -> Interpreter {
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+    let text = checkpoint.to_string();
+    let parsed = Checkpoint::parse(&text).expect("checkpoint should parse");
+    assert_eq!(parsed.to_string(), text);
+
+    let mut restored = self::interpreter();
+    restored
+        .restore(&parsed)
+        .expect("checkpoint should be restored");
+    restored
+}
+
+#[test]
+fn classical_values_are_restored() {
+    let mut interpreter = interpreter();
+    eval(
+        &mut interpreter,
+        indoc! {r#"
+            let i = 42;
+            mutable d = 1.0 / 3.0;
+            set d += 1.0;
+            let s = "with spaces\nand 12:colons";
+            let nested = ([1, 2], (true, PauliY, 0..2..10), 10L, One);
+            let f = Test.Double;
+            let g = Adjoint H;
+            let c = x -> x + i;
+        "#},
+    );
+
+    let mut restored = round_trip(&mut interpreter);
+    assert_eq!(
+        eval(&mut restored, "(i, d, s, nested)").to_string(),
+        eval(&mut interpreter, "(i, d, s, nested)").to_string()
+    );
+    assert_eq!(eval(&mut restored, "f(c(1))"), Value::Int(86));
+}
+
+#[test]
+fn items_and_line_labels_are_restored() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "function Triple(x : Int) : Int { x * 3 }");
+    let (result, _) = line(&mut interpreter, "let broken = Undefined();");
+    assert!(result.is_err());
+    eval(&mut interpreter, "let x = Triple(2);");
+
+    let mut restored = round_trip(&mut interpreter);
+    assert_eq!(eval(&mut restored, "Triple(x)"), Value::Int(18));
+    assert_eq!(restored.lines, interpreter.lines);
+}
+
+#[test]
+fn source_files_are_restored() {
+    let mut interpreter = interpreter();
+    let mut cursor = Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    interpreter
+        .interpret_source(
+            &mut receiver,
+            "Pasted.qs",
+            "function Triple(x : Int) : Int { x * 3 }",
+        )
+        .expect("source should compile");
+    eval(&mut interpreter, "let y = Pasted.Triple(2);");
+
+    let mut restored = round_trip(&mut interpreter);
+    assert_eq!(eval(&mut restored, "y + Pasted.Triple(1)"), Value::Int(9));
+}
+
+#[test]
+fn entry_expressions_before_checkpoint_are_restored() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let a = 1;");
+    let mut cursor = Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    // Compiling entry expressions allocates ids for their locals and lambdas, failed or not.
+    let result = interpreter
+        .run(&mut receiver, "{ let b = 2; let f = x -> x + b; f(a) }")
+        .expect("entry expression should compile");
+    assert_eq!(result.expect("entry expression should run"), Value::Int(3));
+    assert!(interpreter.run(&mut receiver, "Undefined()").is_err());
+    eval(&mut interpreter, "let c = a + 10; let g = y -> y * c;");
+
+    let mut restored = round_trip(&mut interpreter);
+    assert_eq!(
+        eval(&mut restored, "(a, c, g(2))").to_string(),
+        "(1, 11, 22)"
+    );
+}
+
+#[test]
+fn quantum_state_is_restored() {
+    let mut interpreter = interpreter();
+    eval(
+        &mut interpreter,
+        indoc! {"
+            use qs = Qubit[3];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            Ry(0.3, qs[2]);
+            T(qs[2]);
+            S(qs[1]);
+        "},
+    );
+
+    let mut restored = round_trip(&mut interpreter);
+    let (expected, expected_count) = interpreter.get_quantum_state();
+    let (actual, actual_count) = restored.get_quantum_state();
+    assert_eq!(actual_count, expected_count);
+    assert_eq!(actual.len(), expected.len());
+    for ((expected_index, expected_amp), (actual_index, actual_amp)) in
+        expected.iter().zip(actual.iter())
+    {
+        assert_eq!(actual_index, expected_index);
+        assert!(
+            (actual_amp - expected_amp).norm() < 1e-9,
+            "{actual_amp} != {expected_amp}"
+        );
+    }
+
+    // The restored qubits are usable from the restored variables.
+    eval(
+        &mut restored,
+        "Adjoint S(qs[1]); CNOT(qs[0], qs[1]); H(qs[0]);",
+    );
+    assert_eq!(
+        eval(&mut restored, "(M(qs[0]), M(qs[1]))").to_string(),
+        "(Zero, Zero)"
+    );
+}
+
+#[test]
+fn qubit_allocations_are_restored() {
+    let mut interpreter = interpreter();
+    eval(
+        &mut interpreter,
+        indoc! {"
+            use a = Qubit();
+            use bs = Qubit[2];
+        "},
+    );
+
+    let mut restored = round_trip(&mut interpreter);
+    assert_eq!(restored.allocated_qubits().len(), 3);
+    assert_eq!(restored.allocated_qubits(), interpreter.allocated_qubits());
+    assert_eq!(
+        restored.qubit_leak_report(),
+        interpreter.qubit_leak_report()
+    );
+
+    // The restored qubits count toward the sandbox limit.
+    restored
+        .set_sandbox(Some(Sandbox {
+            max_qubits: 4,
+            ..Sandbox::default()
+        }))
+        .expect("sandbox should be set");
+    eval(&mut restored, "{ use q = Qubit(); }");
+    let (result, _) = line(&mut restored, "{ use qs = Qubit[2]; }");
+    let errors = result.expect_err("allocation should fail");
+    assert!(
+        matches!(
+            &errors[..],
+            [Error::Eval(error)]
+                if matches!(error.error().error(), qsc_eval::Error::QubitLimitExceeded(4, _))
+        ),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn seeds_are_restored() {
+    let mut interpreter = interpreter();
+    interpreter.set_quantum_seed(Some(1));
+    interpreter.set_classical_seed(Some(2));
+    eval(&mut interpreter, "let x = 1;");
+
+    let restored = round_trip(&mut interpreter);
+    assert_eq!(restored.quantum_seed, Some(1));
+    assert_eq!(restored.classical_seed, Some(2));
+}
+
+#[test]
+fn metadata_is_restored() {
+    let mut interpreter = interpreter();
+    interpreter.set_metadata("notebook", "analysis 1.ipynb");
+    interpreter.set_metadata("tag", "run:3\nretry");
+    eval(&mut interpreter, "let x = 1;");
+
+    let restored = round_trip(&mut interpreter);
+    assert_eq!(restored.metadata(), interpreter.metadata());
+
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+    let mut restored = self::interpreter();
+    restored.set_metadata("tag", "resumed");
+    restored
+        .restore(&checkpoint)
+        .expect("checkpoint should be restored");
+    assert_eq!(
+        restored.metadata().get("notebook").map(String::as_str),
+        Some("analysis 1.ipynb")
+    );
+    assert_eq!(
+        restored.metadata().get("tag").map(String::as_str),
+        Some("resumed")
+    );
+}
+
+#[test]
+fn restore_into_used_interpreter_is_error() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let x = 1;");
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+
+    let errors = interpreter
+        .restore(&checkpoint)
+        .expect_err("restore should fail");
+    assert!(matches!(errors[..], [Error::CheckpointRestoreNotFresh]));
+}
+
+#[test]
+fn restore_with_different_sources_is_error() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let x = Test.Double(1);");
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+
+    let mut other = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let errors = other.restore(&checkpoint).expect_err("restore should fail");
+    assert!(matches!(errors[..], [Error::CheckpointMismatch]));
+}
+
+#[test]
+fn truncated_checkpoint_is_error() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let x = 1;");
+    let text = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken")
+        .to_string();
+
+    let truncated = &text[..text.len() - "end\n".len()];
+    let error = Checkpoint::parse(truncated).expect_err("parse should fail");
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn checkpoint_is_saved_and_loaded() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let x = 5;");
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+
+    let path = temp_path("checkpoint");
+    checkpoint.save(&path).expect("checkpoint should be saved");
+    let loaded = Checkpoint::load(&path).expect("checkpoint should be loaded");
+    std::fs::remove_file(&path).expect("checkpoint should be removed");
+
+    let mut restored = self::interpreter();
+    restored
+        .restore(&loaded)
+        .expect("checkpoint should be restored");
+    assert_eq!(eval(&mut restored, "x"), Value::Int(5));
+}
+
+/// A host backend that, like hardware, cannot prepare an arbitrary state.
+struct NoRestore(SparseSim);
+
+impl Backend for NoRestore {
+    type ResultType = bool;
+
+    fn mresetz(&mut self, q: usize) -> bool {
+        self.0.mresetz(q)
+    }
+    fn qubit_allocate(&mut self) -> usize {
+        self.0.qubit_allocate()
+    }
+    fn qubit_release(&mut self, q: usize) {
+        self.0.qubit_release(q);
+    }
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.0.capture_quantum_state()
+    }
+}
+
+#[test]
+fn restore_on_backend_without_state_restore_is_error() {
+    let mut interpreter = interpreter();
+    eval(&mut interpreter, "let x = 1;");
+    let checkpoint = interpreter
+        .checkpoint()
+        .expect("checkpoint should be taken");
+
+    let mut host = Interpreter::new_with_backend(
+        true,
+        sources(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        || NoRestore(SparseSim::new()),
+    )
+    .expect("interpreter should be created");
+    let errors = host.restore(&checkpoint).expect_err("restore should fail");
+    assert!(matches!(errors[..], [Error::CheckpointStateUnsupported(_)]));
+}
+
+const COUNT: &str = indoc! {"
+    operation Count(n : Int) : Int {
+        use q = Qubit();
+        mutable ones = 0;
+        for i in 1..n {
+            X(q);
+            if M(q) == One {
+                set ones += i;
+            }
+        }
+        Reset(q);
+        ones
+    }
+"};
+
+#[test]
+fn running_evaluation_is_saved_and_resumed() {
+    let path = temp_path("autosave");
+    let mut interpreter = debug_interpreter();
+    eval(&mut interpreter, COUNT);
+    eval(&mut interpreter, "let base = 100;");
+    interpreter.set_autosave(Some(Autosave {
+        path: path.clone(),
+        interval: 7,
+    }));
+    eval(&mut interpreter, "let total = base + Count(10);");
+    assert!(interpreter.take_autosave_error().is_none());
+
+    // The last checkpoint was saved while `Count` was running, since no statement of the
+    // fragment starts after it returns.
+    let checkpoint = Checkpoint::load(&path).expect("checkpoint should be loaded");
+    std::fs::remove_file(&path).expect("checkpoint should be removed");
+    let running = checkpoint
+        .running
+        .as_ref()
+        .expect("checkpoint should have a running evaluation");
+    assert!(!running.frames.is_empty());
+    assert_eq!(
+        Checkpoint::parse(&checkpoint.to_string())
+            .expect("checkpoint should parse")
+            .to_string(),
+        checkpoint.to_string()
+    );
+
+    let mut restored = debug_interpreter();
+    restored
+        .restore(&checkpoint)
+        .expect("checkpoint should be restored");
+    assert_eq!(restored.allocated_qubits().len(), 1);
+    let mut cursor = Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    let result = restored
+        .resume(&mut receiver)
+        .expect("evaluation should be paused");
+    assert_eq!(result.expect("evaluation should finish"), Value::unit());
+    assert!(restored.resume(&mut receiver).is_none());
+    assert!(restored.allocated_qubits().is_empty());
+    assert_eq!(eval(&mut restored, "total"), Value::Int(125));
+}
+
+#[test]
+fn evaluation_after_restore_abandons_paused_evaluation() {
+    let path = temp_path("abandoned");
+    let mut interpreter = debug_interpreter();
+    eval(&mut interpreter, COUNT);
+    interpreter.set_autosave(Some(Autosave {
+        path: path.clone(),
+        interval: 5,
+    }));
+    eval(&mut interpreter, "let a = 1; let b = Count(4);");
+    let checkpoint = Checkpoint::load(&path).expect("checkpoint should be loaded");
+    std::fs::remove_file(&path).expect("checkpoint should be removed");
+
+    let mut restored = debug_interpreter();
+    restored
+        .restore(&checkpoint)
+        .expect("checkpoint should be restored");
+    assert_eq!(eval(&mut restored, "a + 1"), Value::Int(2));
+    let mut cursor = Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    assert!(restored.resume(&mut receiver).is_none());
+}
+
+#[test]
+fn autosave_to_unwritable_path_is_reported() {
+    let mut interpreter = debug_interpreter();
+    eval(&mut interpreter, COUNT);
+    interpreter.set_autosave(Some(Autosave {
+        path: temp_path("missing").join("checkpoint"),
+        interval: 1,
+    }));
+    assert_eq!(eval(&mut interpreter, "Count(2)"), Value::Int(1));
+    assert!(interpreter.take_autosave_error().is_some());
+    assert!(interpreter.take_autosave_error().is_none());
+}
+
+#[test]
+fn running_checkpoint_with_changed_program_is_error() {
+    let path = temp_path("changed");
+    let mut interpreter = debug_interpreter();
+    eval(&mut interpreter, COUNT);
+    interpreter.set_autosave(Some(Autosave {
+        path: path.clone(),
+        interval: 5,
+    }));
+    eval(&mut interpreter, "Count(4)");
+    let mut checkpoint = Checkpoint::load(&path).expect("checkpoint should be loaded");
+    std::fs::remove_file(&path).expect("checkpoint should be removed");
+
+    checkpoint
+        .running
+        .as_mut()
+        .expect("checkpoint should have a running evaluation")
+        .idx
+        .pop();
+    let errors = debug_interpreter()
+        .restore(&checkpoint)
+        .expect_err("restore should fail");
+    assert!(matches!(errors[..], [Error::CheckpointMismatch]));
+}
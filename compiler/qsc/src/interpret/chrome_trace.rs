@@ -0,0 +1,311 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, Instant};
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{backend::Backend, val::Value};
+use serde_json::json;
+
+/// The category of the spans for compiling sources and entry expressions.
+pub(super) const COMPILE: &str = "compile";
+/// The category of the spans for evaluating a program.
+pub(super) const EVAL: &str = "eval";
+/// The category of the spans for applying gates in the simulator.
+pub(super) const GATE: &str = "gate";
+/// The category of the spans for measurements and resets.
+pub(super) const MEASUREMENT: &str = "measurement";
+/// The category of the spans for allocating and releasing qubits.
+pub(super) const QUBIT: &str = "qubit";
+
+/// A timeline of the phases of a run, such as compilation, evaluation and each gate and
+/// measurement in the simulator, that can be exported in the Chrome trace event format and opened
+/// in trace viewers such as Perfetto or `chrome://tracing`.
+pub struct ChromeTrace {
+    start: Instant,
+    spans: Vec<Span>,
+}
+
+struct Span {
+    name: String,
+    category: &'static str,
+    start: Duration,
+    duration: Duration,
+    args: Option<serde_json::Value>,
+}
+
+impl Default for ChromeTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChromeTrace {
+    /// Starts a trace whose timestamps are relative to now.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Records a span from `start` until now, with optional arguments that trace viewers show
+    /// alongside it.
+    pub fn record(
+        &mut self,
+        name: impl Into<String>,
+        category: &'static str,
+        start: Instant,
+        args: Option<serde_json::Value>,
+    ) {
+        let end = Instant::now();
+        self.spans.push(Span {
+            name: name.into(),
+            category,
+            start: start.saturating_duration_since(self.start),
+            duration: end.saturating_duration_since(start),
+            args,
+        });
+    }
+
+    /// Records a span for the time that `f` takes. Spans recorded by `f` are nested in it.
+    pub fn span<T>(
+        &mut self,
+        name: impl Into<String>,
+        category: &'static str,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let start = Instant::now();
+        let value = f(self);
+        self.record(name, category, start, None);
+        value
+    }
+
+    /// The number of spans recorded so far.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Converts the trace into a JSON object in the Chrome trace event format, with every span as
+    /// a complete event and timestamps in microseconds.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        let metadata = json!({
+            "name": "process_name",
+            "ph": "M",
+            "pid": 1,
+            "tid": 1,
+            "args": { "name": "Q#" },
+        });
+        let events = std::iter::once(metadata).chain(self.spans.iter().map(|span| {
+            let mut event = json!({
+                "name": span.name,
+                "cat": span.category,
+                "ph": "X",
+                "ts": micros(span.start),
+                "dur": micros(span.duration),
+                "pid": 1,
+                "tid": 1,
+            });
+            if let Some(args) = &span.args {
+                event["args"] = args.clone();
+            }
+            event
+        }));
+        json!({
+            "traceEvents": events.collect::<Vec<_>>(),
+            "displayTimeUnit": "ns",
+        })
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1e6
+}
+
+/// A backend that records a span in a [`ChromeTrace`] for every operation that it forwards to
+/// the backend it wraps.
+pub struct TracedSim<'a, B> {
+    sim: &'a mut B,
+    trace: &'a mut ChromeTrace,
+}
+
+impl<'a, B: Backend> TracedSim<'a, B> {
+    pub fn new(sim: &'a mut B, trace: &'a mut ChromeTrace) -> Self {
+        Self { sim, trace }
+    }
+
+    fn traced<T>(
+        &mut self,
+        name: &'static str,
+        category: &'static str,
+        qubits: &[usize],
+        f: impl FnOnce(&mut B) -> T,
+    ) -> T {
+        let start = Instant::now();
+        let value = f(&mut *self.sim);
+        self.trace
+            .record(name, category, start, Some(json!({ "qubits": qubits })));
+        value
+    }
+}
+
+impl<B: Backend> Backend for TracedSim<'_, B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.traced("CCX", GATE, &[ctl0, ctl1, q], |sim| sim.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.traced("CX", GATE, &[ctl, q], |sim| sim.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.traced("CY", GATE, &[ctl, q], |sim| sim.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.traced("CZ", GATE, &[ctl, q], |sim| sim.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.traced("H", GATE, &[q], |sim| sim.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.traced("M", MEASUREMENT, &[q], |sim| sim.m(q))
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.traced("MResetZ", MEASUREMENT, &[q], |sim| sim.mresetz(q))
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.traced("Reset", MEASUREMENT, &[q], |sim| sim.reset(q));
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.traced("Rx", GATE, &[q], |sim| sim.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.traced("Rxx", GATE, &[q0, q1], |sim| sim.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.traced("Ry", GATE, &[q], |sim| sim.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.traced("Ryy", GATE, &[q0, q1], |sim| sim.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.traced("Rz", GATE, &[q], |sim| sim.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.traced("Rzz", GATE, &[q0, q1], |sim| sim.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.traced("S†", GATE, &[q], |sim| sim.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.traced("S", GATE, &[q], |sim| sim.s(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.traced("SWAP", GATE, &[q0, q1], |sim| sim.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.traced("T†", GATE, &[q], |sim| sim.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.traced("T", GATE, &[q], |sim| sim.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.traced("X", GATE, &[q], |sim| sim.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.traced("Y", GATE, &[q], |sim| sim.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.traced("Z", GATE, &[q], |sim| sim.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let start = Instant::now();
+        let q = self.sim.qubit_allocate();
+        self.trace
+            .record("allocate", QUBIT, start, Some(json!({ "qubits": [q] })));
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.traced("release", QUBIT, &[q], |sim| sim.qubit_release(q));
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.sim.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.sim.qubit_is_zero(q)
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.sim.restore_quantum_state(state, qubit_count)
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        self.sim.global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        self.sim.error_estimate()
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        let start = Instant::now();
+        let value = self.sim.custom_intrinsic(name, arg);
+        if value.is_some() {
+            self.trace.record(name, GATE, start, None);
+        }
+        value
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.sim.set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        self.sim.close();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        self.sim.take_failure()
+    }
+}
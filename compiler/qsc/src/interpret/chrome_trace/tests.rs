@@ -0,0 +1,123 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{ChromeTrace, COMPILE, EVAL, GATE, MEASUREMENT, QUBIT};
+use crate::interpret::{GenericReceiver, Interpreter};
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::val::Value;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::time::Instant;
+
+fn interpreter() -> Interpreter {
+    Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created")
+}
+
+/// The name and category of each complete event in the trace, in the order they were recorded.
+fn spans(trace: &ChromeTrace) -> Vec<(String, String)> {
+    let json = trace.to_json();
+    json["traceEvents"]
+        .as_array()
+        .expect("trace events should be an array")
+        .iter()
+        .filter(|event| event["ph"] == "X")
+        .map(|event| {
+            (
+                event["name"]
+                    .as_str()
+                    .expect("name should be a string")
+                    .to_string(),
+                event["cat"]
+                    .as_str()
+                    .expect("category should be a string")
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn nested_spans_are_recorded_as_complete_events() {
+    let mut trace = ChromeTrace::new();
+    let value = trace.span("outer", EVAL, |trace| {
+        trace.record(
+            "inner",
+            GATE,
+            Instant::now(),
+            Some(serde_json::json!({ "qubits": [0] })),
+        );
+        7
+    });
+    assert_eq!(value, 7);
+    assert_eq!(trace.len(), 2);
+
+    let json = trace.to_json();
+    let events = json["traceEvents"]
+        .as_array()
+        .expect("trace events should be an array");
+    assert_eq!(events[0]["ph"], "M");
+    let (inner, outer) = (&events[1], &events[2]);
+    assert_eq!(inner["args"]["qubits"], serde_json::json!([0]));
+    assert!(outer.get("args").is_none());
+    let (inner_start, outer_start) = (
+        inner["ts"].as_f64().expect("timestamp should be a number"),
+        outer["ts"].as_f64().expect("timestamp should be a number"),
+    );
+    let (inner_end, outer_end) = (
+        inner_start + inner["dur"].as_f64().expect("duration should be a number"),
+        outer_start + outer["dur"].as_f64().expect("duration should be a number"),
+    );
+    assert!(outer_start <= inner_start && inner_end <= outer_end);
+}
+
+#[test]
+fn run_traced_records_compilation_evaluation_and_operations() {
+    let mut interpreter = interpreter();
+    let mut trace = ChromeTrace::new();
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let result = interpreter
+        .run_traced(
+            &mut receiver,
+            "{ use (a, b) = (Qubit(), Qubit()); H(a); CNOT(a, b); let r = M(b); ResetAll([a, b]); r == r }",
+            &mut trace,
+        )
+        .expect("compilation should succeed");
+    assert_eq!(result.expect("run should succeed"), Value::Bool(true));
+
+    let spans = spans(&trace);
+    let has = |name: &str, category: &str| spans.iter().any(|(n, c)| n == name && c == category);
+    assert!(has("compile entry expression", COMPILE), "{spans:?}");
+    assert!(has("evaluate", EVAL), "{spans:?}");
+    assert!(has("allocate", QUBIT), "{spans:?}");
+    assert!(has("H", GATE), "{spans:?}");
+    assert!(has("CX", GATE), "{spans:?}");
+    assert!(has("M", MEASUREMENT), "{spans:?}");
+    assert!(has("release", QUBIT), "{spans:?}");
+    assert_eq!(
+        spans.last(),
+        Some(&("evaluate".to_string(), EVAL.to_string()))
+    );
+}
+
+#[test]
+fn run_traced_records_compilation_that_fails() {
+    let mut interpreter = interpreter();
+    let mut trace = ChromeTrace::new();
+    let mut out = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut out);
+    interpreter
+        .run_traced(&mut receiver, "Foo()", &mut trace)
+        .expect_err("compilation should fail");
+    assert_eq!(
+        spans(&trace),
+        [("compile entry expression".to_string(), COMPILE.to_string())]
+    );
+}
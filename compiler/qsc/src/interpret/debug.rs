@@ -22,7 +22,18 @@ pub(crate) fn format_call_stack(
     let mut trace = String::new();
     trace.push_str(&format!("Error: {error}\n"));
     trace.push_str("Call stack:\n");
+    trace.push_str(&format_frames(store, globals, frames));
+    trace
+}
 
+/// Formats the frames innermost first, one `at` line per frame.
+#[must_use]
+pub(crate) fn format_frames(
+    store: &PackageStore,
+    globals: &impl PackageStoreLookup,
+    frames: Vec<Frame>,
+) -> String {
+    let mut trace = String::new();
     let mut frames = frames;
     frames.reverse();
 
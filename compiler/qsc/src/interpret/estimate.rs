@@ -0,0 +1,209 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{backend::Backend, val::Value};
+
+/// The resources that a program uses when it runs, counted by
+/// [`super::Interpreter::estimate`] without simulating the quantum state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceEstimate {
+    /// The largest number of qubits that are allocated at the same time.
+    pub num_qubits: usize,
+    /// The number of qubit allocations, including qubits that reuse released ones.
+    pub total_qubits: usize,
+    /// The number of T gates and their adjoints. The T gates that a decomposition of CCNOT gates
+    /// or rotations would need are not included.
+    pub t_count: usize,
+    /// The number of CNOT gates.
+    pub cnot_count: usize,
+    /// The number of CCNOT gates.
+    pub ccnot_count: usize,
+    /// The number of single- and two-qubit rotations.
+    pub rotation_count: usize,
+    /// The number of single-qubit measurements.
+    pub measurement_count: usize,
+    /// The number of layers of gates, measurements and resets, where each layer acts on every
+    /// qubit at most once.
+    pub depth: usize,
+}
+
+/// Backend implementation that counts the resources of a program. Measurements always
+/// return `Zero`, so the counts follow the path through the program for those outcomes.
+#[derive(Default)]
+pub(super) struct Counter {
+    estimate: ResourceEstimate,
+    /// The number of layers that have an operation on each qubit.
+    layers: Vec<usize>,
+    free: Vec<usize>,
+    live: usize,
+}
+
+impl Counter {
+    pub(super) fn finish(self) -> ResourceEstimate {
+        self.estimate
+    }
+
+    fn schedule(&mut self, qubits: &[usize]) {
+        let layer = qubits
+            .iter()
+            .map(|&q| self.layers[q])
+            .max()
+            .unwrap_or_default()
+            + 1;
+        for &q in qubits {
+            self.layers[q] = layer;
+        }
+        self.estimate.depth = self.estimate.depth.max(layer);
+    }
+
+    fn rotation(&mut self, qubits: &[usize]) {
+        self.estimate.rotation_count += 1;
+        self.schedule(qubits);
+    }
+
+    fn measurement(&mut self, q: usize) -> bool {
+        self.estimate.measurement_count += 1;
+        self.schedule(&[q]);
+        false
+    }
+}
+
+impl Backend for Counter {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.estimate.ccnot_count += 1;
+        self.schedule(&[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.estimate.cnot_count += 1;
+        self.schedule(&[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.schedule(&[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.schedule(&[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn rx(&mut self, _theta: f64, q: usize) {
+        self.rotation(&[q]);
+    }
+
+    fn rxx(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.rotation(&[q0, q1]);
+    }
+
+    fn ry(&mut self, _theta: f64, q: usize) {
+        self.rotation(&[q]);
+    }
+
+    fn ryy(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.rotation(&[q0, q1]);
+    }
+
+    fn rz(&mut self, _theta: f64, q: usize) {
+        self.rotation(&[q]);
+    }
+
+    fn rzz(&mut self, _theta: f64, q0: usize, q1: usize) {
+        self.rotation(&[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.schedule(&[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.estimate.t_count += 1;
+        self.schedule(&[q]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.estimate.t_count += 1;
+        self.schedule(&[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.schedule(&[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.estimate.total_qubits += 1;
+        self.live += 1;
+        self.estimate.num_qubits = self.estimate.num_qubits.max(self.live);
+        self.free.pop().unwrap_or_else(|| {
+            self.layers.push(0);
+            self.layers.len() - 1
+        })
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.live -= 1;
+        self.free.push(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        (Vec::new(), 0)
+    }
+
+    fn qubit_is_zero(&mut self, _q: usize) -> bool {
+        // Because `qubit_is_zero` is called on every qubit release, this must return
+        // true to avoid a panic.
+        true
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, _arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            // Cached estimates are counted again each time, as if caching had begun.
+            "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
+            "GlobalPhase"
+            | "EndEstimateCaching"
+            | "AccountForEstimatesInternal"
+            | "BeginRepeatEstimatesInternal"
+            | "EndRepeatEstimatesInternal"
+            | "AccountForArithmeticInternal" => Some(Ok(Value::unit())),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Counter, ResourceEstimate};
+use qsc_eval::backend::Backend;
+
+#[test]
+fn gates_are_counted_in_layers() {
+    let mut counter = Counter::default();
+    let (a, b, c) = (
+        counter.qubit_allocate(),
+        counter.qubit_allocate(),
+        counter.qubit_allocate(),
+    );
+    counter.h(a);
+    counter.t(b);
+    counter.cx(a, b);
+    counter.rz(0.5, c);
+    counter.ccx(a, b, c);
+    counter.tadj(c);
+    assert!(!counter.mresetz(a));
+    counter.qubit_release(a);
+    let d = counter.qubit_allocate();
+    counter.x(d);
+    assert_eq!(
+        counter.finish(),
+        ResourceEstimate {
+            num_qubits: 3,
+            total_qubits: 4,
+            t_count: 2,
+            cnot_count: 1,
+            ccnot_count: 1,
+            rotation_count: 1,
+            measurement_count: 1,
+            // H and T, CNOT, CCNOT, the measurement and adjoint T, and then X on the qubit that
+            // reuses the measured one.
+            depth: 5,
+        }
+    );
+}
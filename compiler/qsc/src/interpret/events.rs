@@ -0,0 +1,70 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Error;
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::{output, output::Receiver, val::Value};
+
+/// An event in the lifecycle of evaluating a line with
+/// [`super::Interpreter::eval_fragments_with_events`].
+///
+/// Every line produces a `CompileStarted` event first and exactly one `Result` or `Error` event
+/// last. `EvalStarted` is only produced when compilation succeeds, and is followed by the output
+/// produced during evaluation, in order.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// Compilation of the line with the given label started.
+    CompileStarted { label: String },
+    /// Compilation succeeded and evaluation started.
+    EvalStarted,
+    /// The program produced a message, such as through `Message`.
+    Message(String),
+    /// The program dumped the quantum state, such as through `DumpMachine`.
+    State {
+        state: Vec<(BigUint, Complex64)>,
+        qubit_count: usize,
+    },
+    /// The global phase that accompanies the most recent `State` event.
+    GlobalPhase(f64),
+    /// A call trace message, produced when call tracing is enabled.
+    Trace(String),
+    /// Output was dropped because it exceeded the interpreter's output limit. This follows the
+    /// last output that was forwarded.
+    OutputLimitExceeded(output::OutputLimitExceeded),
+    /// Evaluation finished with the given value.
+    Result(Value),
+    /// Compilation or evaluation failed with the given errors.
+    Error(Vec<Error>),
+}
+
+/// Forwards the output of an evaluation to an event callback.
+pub(super) struct EventReceiver<'a, F: FnMut(Event)> {
+    pub(super) on_event: &'a mut F,
+}
+
+impl<F: FnMut(Event)> Receiver for EventReceiver<'_, F> {
+    fn state(
+        &mut self,
+        state: Vec<(BigUint, Complex64)>,
+        qubit_count: usize,
+    ) -> Result<(), output::Error> {
+        (self.on_event)(Event::State { state, qubit_count });
+        Ok(())
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), output::Error> {
+        (self.on_event)(Event::Message(msg.to_string()));
+        Ok(())
+    }
+
+    fn global_phase(&mut self, phase: f64) -> Result<(), output::Error> {
+        (self.on_event)(Event::GlobalPhase(phase));
+        Ok(())
+    }
+
+    fn trace(&mut self, msg: &str) -> Result<(), output::Error> {
+        (self.on_event)(Event::Trace(msg.to_string()));
+        Ok(())
+    }
+}
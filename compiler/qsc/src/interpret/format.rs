@@ -0,0 +1,144 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_data_structures::double::DoubleFormat;
+use qsc_eval::val::Value;
+use std::fmt::Write;
+
+/// How tuples are laid out when rendered.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TupleLayout {
+    /// Every tuple on a single line, as in `(1, (2, 3))`.
+    #[default]
+    Inline,
+    /// Every tuple that contains another tuple with one element per line, indented by four spaces
+    /// for each level of nesting.
+    Multiline,
+}
+
+/// Renders the values returned by an [`super::Interpreter`], so that hosts can align results
+/// with their own display conventions. Values are rendered part by part, and implementations
+/// choose how each part looks by overriding the methods below. The defaults match the `Display`
+/// implementation of [`Value`].
+pub trait ValueFormatter {
+    /// Renders a `Double`.
+    fn format_double(&self, value: f64) -> String {
+        Value::Double(value).to_string()
+    }
+
+    /// The number of elements of an array to render before the rest are elided, or `None` to
+    /// render every element.
+    fn max_array_elements(&self) -> Option<usize> {
+        None
+    }
+
+    /// How tuples are laid out.
+    fn tuple_layout(&self) -> TupleLayout {
+        TupleLayout::Inline
+    }
+
+    /// Renders the value.
+    fn format(&self, value: &Value) -> String {
+        let mut output = String::new();
+        write_value(self, value, 0, &mut output);
+        output
+    }
+}
+
+/// A [`ValueFormatter`] configured with options. The default options render values the same
+/// way as their `Display` implementation. `Double` values are rendered the same way in every
+/// locale, so hosts get the same text whatever their culture settings.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ValueFormat {
+    /// The number of digits after the decimal point for `Double` values, or `None` for as many as
+    /// are needed to represent the value exactly.
+    pub double_precision: Option<usize>,
+    /// The magnitude of the decimal exponent at which `Double` values are rendered in scientific
+    /// notation, or `None` to never use scientific notation. See [`DoubleFormat`].
+    pub double_scientific_threshold: Option<u32>,
+    /// The number of elements of an array to render before the rest are elided, or `None` to
+    /// render every element.
+    pub max_array_elements: Option<usize>,
+    pub tuple_layout: TupleLayout,
+}
+
+impl ValueFormatter for ValueFormat {
+    fn format_double(&self, value: f64) -> String {
+        DoubleFormat {
+            precision: self.double_precision,
+            scientific_threshold: self.double_scientific_threshold,
+        }
+        .format(value)
+    }
+
+    fn max_array_elements(&self) -> Option<usize> {
+        self.max_array_elements
+    }
+
+    fn tuple_layout(&self) -> TupleLayout {
+        self.tuple_layout
+    }
+}
+
+fn write_value<F: ValueFormatter + ?Sized>(
+    formatter: &F,
+    value: &Value,
+    depth: usize,
+    output: &mut String,
+) {
+    match value {
+        Value::Array(items) => {
+            let shown = formatter
+                .max_array_elements()
+                .map_or(items.len(), |max| max.min(items.len()));
+            output.push('[');
+            for (index, item) in items[..shown].iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_value(formatter, item, depth, output);
+            }
+            if shown < items.len() {
+                if shown > 0 {
+                    output.push_str(", ");
+                }
+                let _ = write!(output, "... {} more", items.len() - shown);
+            }
+            output.push(']');
+        }
+        Value::Double(value) => output.push_str(&formatter.format_double(*value)),
+        Value::Tuple(items)
+            if formatter.tuple_layout() == TupleLayout::Multiline
+                && items.iter().any(|item| matches!(item, Value::Tuple(_))) =>
+        {
+            let indent = "    ".repeat(depth + 1);
+            output.push_str("(\n");
+            for item in items.iter() {
+                output.push_str(&indent);
+                write_value(formatter, item, depth + 1, output);
+                output.push_str(",\n");
+            }
+            output.push_str(&"    ".repeat(depth));
+            output.push(')');
+        }
+        Value::Tuple(items) => {
+            output.push('(');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write_value(formatter, item, depth, output);
+            }
+            if items.len() == 1 {
+                output.push(',');
+            }
+            output.push(')');
+        }
+        _ => {
+            let _ = write!(output, "{value}");
+        }
+    }
+}
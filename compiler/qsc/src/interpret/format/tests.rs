@@ -0,0 +1,121 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{TupleLayout, ValueFormat, ValueFormatter};
+use expect_test::expect;
+use qsc_eval::val::Value;
+use std::rc::Rc;
+
+fn tuple(items: Vec<Value>) -> Value {
+    Value::Tuple(items.into())
+}
+
+fn array(items: Vec<Value>) -> Value {
+    Value::Array(Rc::new(items))
+}
+
+fn sample() -> Value {
+    tuple(vec![
+        Value::Int(1),
+        tuple(vec![Value::Double(2.0), Value::Double(1.0 / 3.0)]),
+        array((0..5).map(Value::Int).collect()),
+        tuple(vec![Value::String("s".into())]),
+    ])
+}
+
+#[test]
+fn default_format_matches_display() {
+    let value = sample();
+    assert_eq!(ValueFormat::default().format(&value), value.to_string());
+    assert_eq!(ValueFormat::default().format(&Value::unit()), "()");
+}
+
+#[test]
+fn double_precision() {
+    let format = ValueFormat {
+        double_precision: Some(3),
+        ..ValueFormat::default()
+    };
+    expect![[r#"(1, (2.000, 0.333), [0, 1, 2, 3, 4], (s,))"#]].assert_eq(&format.format(&sample()));
+}
+
+#[test]
+fn double_scientific_threshold() {
+    let format = ValueFormat {
+        double_scientific_threshold: Some(2),
+        ..ValueFormat::default()
+    };
+    expect![[r#"[1.0e-3, 0.5, 12.0, 1.5e3]"#]].assert_eq(&format.format(&array(vec![
+        Value::Double(0.001),
+        Value::Double(0.5),
+        Value::Double(12.0),
+        Value::Double(1500.0),
+    ])));
+
+    let format = ValueFormat {
+        double_precision: Some(2),
+        ..format
+    };
+    expect![[r#"[1.00e-3, 0.50, 12.00, 1.50e3]"#]].assert_eq(&format.format(&array(vec![
+        Value::Double(0.001),
+        Value::Double(0.5),
+        Value::Double(12.0),
+        Value::Double(1500.0),
+    ])));
+}
+
+#[test]
+fn max_array_elements() {
+    let format = ValueFormat {
+        max_array_elements: Some(2),
+        ..ValueFormat::default()
+    };
+    expect![[r#"[0, 1, ... 3 more]"#]]
+        .assert_eq(&format.format(&array((0..5).map(Value::Int).collect())));
+    expect![[r#"[0, 1]"#]].assert_eq(&format.format(&array((0..2).map(Value::Int).collect())));
+
+    let format = ValueFormat {
+        max_array_elements: Some(0),
+        ..ValueFormat::default()
+    };
+    expect![[r#"[... 2 more]"#]]
+        .assert_eq(&format.format(&array((0..2).map(Value::Int).collect())));
+}
+
+#[test]
+fn multiline_tuples() {
+    let format = ValueFormat {
+        tuple_layout: TupleLayout::Multiline,
+        ..ValueFormat::default()
+    };
+    let value = tuple(vec![
+        Value::Int(1),
+        tuple(vec![
+            Value::Int(2),
+            tuple(vec![Value::Int(3), Value::Int(4)]),
+        ]),
+        array(vec![tuple(vec![Value::Int(5), Value::Int(6)])]),
+    ]);
+    expect![[r#"
+        (
+            1,
+            (
+                2,
+                (3, 4),
+            ),
+            [(5, 6)],
+        )"#]]
+    .assert_eq(&format.format(&value));
+}
+
+#[test]
+fn custom_formatter() {
+    struct Scientific;
+    impl ValueFormatter for Scientific {
+        fn format_double(&self, value: f64) -> String {
+            format!("{value:e}")
+        }
+    }
+    expect![[r#"(1, (2e0, 3.333333333333333e-1), [0, 1, 2, 3, 4], (s,))"#]]
+        .assert_eq(&Scientific.format(&sample()));
+}
@@ -0,0 +1,102 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+const ORACLES: &str = "Microsoft.Quantum.Unstable.Oracles";
+
+/// A classical description of an oracle that a host can define as an operation in the session
+/// with [`super::Interpreter::define_oracle`], so that problem instances generated at run time
+/// can be used by Q# code without writing Q# source for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Oracle {
+    /// The bit-flip oracle of a Boolean function on `n` qubits, given by its value on each of
+    /// the 2ⁿ inputs in little-endian format. It is defined as an operation of type
+    /// `(Qubit[], Qubit) => Unit is Adj + Ctl` that maps |𝑥〉|𝑦〉 to |𝑥〉|𝑦 ⊕ table[𝑥]〉.
+    TruthTable(Vec<bool>),
+    /// The phase oracle of a Boolean function on `n` qubits, given by its value on each of the
+    /// 2ⁿ inputs in little-endian format. It is defined as an operation of type
+    /// `Qubit[] => Unit is Adj + Ctl` that maps |𝑥〉 to (-1)^table[𝑥] |𝑥〉.
+    PhaseTruthTable(Vec<bool>),
+    /// The permutation of the 2ⁿ basis states of `n` qubits that maps |𝑥〉 to |permutation[𝑥]〉,
+    /// in little-endian format. It is defined as an operation of type
+    /// `Qubit[] => Unit is Adj + Ctl`.
+    Permutation(Vec<u64>),
+}
+
+impl Oracle {
+    /// Generates the Q# declaration of the oracle as an operation with the given name.
+    pub(super) fn declaration(&self, name: &str) -> Result<String, Error> {
+        if !is_identifier(name) {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        let len = match self {
+            Oracle::TruthTable(table) | Oracle::PhaseTruthTable(table) => table.len(),
+            Oracle::Permutation(permutation) => permutation.len(),
+        };
+        if !len.is_power_of_two() {
+            return Err(Error::InvalidLength(len));
+        }
+
+        Ok(match self {
+            Oracle::TruthTable(table) => format!(
+                "operation {name}(inputs : Qubit[], target : Qubit) : Unit is Adj + Ctl {{ \
+                 {ORACLES}.ApplyOracleFromTruthTable({}, inputs, target); }}",
+                bool_array(table)
+            ),
+            Oracle::PhaseTruthTable(table) => format!(
+                "operation {name}(inputs : Qubit[]) : Unit is Adj + Ctl {{ \
+                 {ORACLES}.ApplyPhaseOracleFromTruthTable({}, inputs); }}",
+                bool_array(table)
+            ),
+            Oracle::Permutation(permutation) => {
+                let mut seen = vec![false; len];
+                for &image in permutation {
+                    match usize::try_from(image).ok().and_then(|i| seen.get_mut(i)) {
+                        Some(slot) if !*slot => *slot = true,
+                        _ => return Err(Error::NotAPermutation(len)),
+                    }
+                }
+                let images = permutation
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "operation {name}(qubits : Qubit[]) : Unit is Adj + Ctl {{ \
+                     {ORACLES}.ApplyPermutationOracle([{images}], qubits); }}"
+                )
+            }
+        })
+    }
+}
+
+fn bool_array(values: &[bool]) -> String {
+    let values = values
+        .iter()
+        .map(|value| if *value { "true" } else { "false" })
+        .collect::<Vec<_>>();
+    format!("[{}]", values.join(", "))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("`{0}` is not a valid name for an oracle")]
+    #[diagnostic(code("Qsc.Interpret.OracleInvalidName"))]
+    #[diagnostic(help("oracle names must be Q# identifiers, without a namespace"))]
+    InvalidName(String),
+    #[error("oracle has {0} entries, which is not a power of two")]
+    #[diagnostic(code("Qsc.Interpret.OracleInvalidLength"))]
+    #[diagnostic(help("provide an entry for every basis state of the qubits the oracle acts on"))]
+    InvalidLength(usize),
+    #[error("oracle does not map the integers below {0} to each other one to one")]
+    #[diagnostic(code("Qsc.Interpret.OracleNotAPermutation"))]
+    NotAPermutation(usize),
+}
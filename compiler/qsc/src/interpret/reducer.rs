@@ -0,0 +1,131 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Reducers that post-process the values of the shots run by
+//! [`super::Interpreter::reduce_shots`]. Each value is added to the reducers as soon as its shot
+//! finishes and is then dropped, so that a run of millions of shots only keeps the reductions in
+//! memory.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_eval::val::{self, Value};
+use rustc_hash::FxHashMap;
+
+/// A post-processing step that combines the values of many shots into one value.
+pub trait Reducer {
+    /// Adds the value of a shot.
+    fn add(&mut self, value: &Value);
+
+    /// Returns the reduction of the values added since the last call, and starts over.
+    fn finish(&mut self) -> Value;
+}
+
+/// The most frequent value of the shots, or `()` if there were none. Ties go to the value that
+/// was seen first. Values are told apart by how they are displayed.
+#[derive(Default)]
+pub struct MajorityVote {
+    /// The values seen, by their rendering, with the number of shots and the order they were
+    /// first seen in.
+    counts: FxHashMap<String, (usize, usize, Value)>,
+}
+
+impl MajorityVote {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Reducer for MajorityVote {
+    fn add(&mut self, value: &Value) {
+        let seen = self.counts.len();
+        self.counts
+            .entry(value.to_string())
+            .or_insert_with(|| (0, seen, value.clone()))
+            .0 += 1;
+    }
+
+    fn finish(&mut self) -> Value {
+        std::mem::take(&mut self.counts)
+            .into_values()
+            .max_by(|(a, a_seen, _), (b, b_seen, _)| a.cmp(b).then(b_seen.cmp(a_seen)))
+            .map_or_else(Value::unit, |(_, _, value)| value)
+    }
+}
+
+/// The expected parity of the measurement results of the shots, as a `Double` from `1.0` when
+/// every shot measured an even number of `One`s to `-1.0` when every shot measured an odd
+/// number, which is the expectation value of the product of the measured Pauli Z observables.
+/// Results are counted however they are nested in arrays and tuples. There is no expectation
+/// without shots, so then the reduction is `NaN`.
+#[derive(Default)]
+pub struct Parity {
+    shots: usize,
+    odd: usize,
+}
+
+impl Parity {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Reducer for Parity {
+    fn add(&mut self, value: &Value) {
+        self.shots += 1;
+        if is_odd(value) {
+            self.odd += 1;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Runs have far fewer than 2^52 shots.
+    fn finish(&mut self) -> Value {
+        let Self { shots, odd } = std::mem::take(self);
+        let even = shots - odd;
+        Value::Double((even as f64 - odd as f64) / shots as f64)
+    }
+}
+
+/// Whether the value has an odd number of `One` results.
+fn is_odd(value: &Value) -> bool {
+    match value {
+        Value::Result(val::Result::Val(one)) => *one,
+        Value::Array(items) => items.iter().filter(|item| is_odd(item)).count() % 2 == 1,
+        Value::Tuple(items) => items.iter().filter(|item| is_odd(item)).count() % 2 == 1,
+        _ => false,
+    }
+}
+
+/// A reducer that folds the values of the shots into an accumulator with a closure, such as to
+/// count the shots whose values meet a condition.
+pub struct Fold<F> {
+    init: Value,
+    acc: Value,
+    f: F,
+}
+
+impl<F: FnMut(Value, &Value) -> Value> Fold<F> {
+    /// Creates a reducer that starts every run from `init` and combines each shot's value into
+    /// the accumulator with `f`.
+    #[must_use]
+    pub fn new(init: Value, f: F) -> Self {
+        Self {
+            acc: init.clone(),
+            init,
+            f,
+        }
+    }
+}
+
+impl<F: FnMut(Value, &Value) -> Value> Reducer for Fold<F> {
+    fn add(&mut self, value: &Value) {
+        let acc = std::mem::replace(&mut self.acc, Value::unit());
+        self.acc = (self.f)(acc, value);
+    }
+
+    fn finish(&mut self) -> Value {
+        std::mem::replace(&mut self.acc, self.init.clone())
+    }
+}
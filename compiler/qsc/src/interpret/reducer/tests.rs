@@ -0,0 +1,182 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Fold, MajorityVote, Parity, Reducer};
+use crate::interpret::{GenericReceiver, Interpreter};
+use expect_test::expect;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::val::Value;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::rc::Rc;
+
+fn results(bits: &[bool]) -> Value {
+    Value::Array(Rc::new(
+        bits.iter()
+            .map(|&one| {
+                if one {
+                    Value::RESULT_ONE
+                } else {
+                    Value::RESULT_ZERO
+                }
+            })
+            .collect(),
+    ))
+}
+
+fn reduce(reducer: &mut dyn Reducer, values: &[Value]) -> Value {
+    for value in values {
+        reducer.add(value);
+    }
+    reducer.finish()
+}
+
+#[test]
+fn majority_vote_picks_most_frequent_value() {
+    let mut vote = MajorityVote::new();
+    let value = reduce(
+        &mut vote,
+        &[Value::Int(1), Value::Int(2), Value::Int(2), Value::Int(3)],
+    );
+    assert_eq!(value, Value::Int(2));
+}
+
+#[test]
+fn majority_vote_breaks_ties_by_first_seen() {
+    let mut vote = MajorityVote::new();
+    let value = reduce(
+        &mut vote,
+        &[Value::Int(3), Value::Int(1), Value::Int(1), Value::Int(3)],
+    );
+    assert_eq!(value, Value::Int(3));
+}
+
+#[test]
+fn majority_vote_starts_over_after_finish() {
+    let mut vote = MajorityVote::new();
+    reduce(&mut vote, &[Value::Int(1)]);
+    assert_eq!(vote.finish(), Value::unit());
+    assert_eq!(reduce(&mut vote, &[Value::Int(2)]), Value::Int(2));
+}
+
+#[test]
+fn parity_is_expectation_of_product_of_z() {
+    let mut parity = Parity::new();
+    let value = reduce(
+        &mut parity,
+        &[
+            results(&[false, false]),
+            results(&[true, true]),
+            results(&[true, false]),
+            Value::Tuple(vec![Value::RESULT_ONE, results(&[true, true])].into()),
+        ],
+    );
+    assert_eq!(value, Value::Double(0.0));
+    assert_eq!(
+        reduce(&mut parity, &[results(&[true, false, false])]),
+        Value::Double(-1.0)
+    );
+}
+
+#[test]
+fn parity_without_shots_is_nan() {
+    let Value::Double(value) = Parity::new().finish() else {
+        panic!("parity should be a double");
+    };
+    assert!(value.is_nan());
+}
+
+#[test]
+fn fold_combines_values_with_closure() {
+    let mut count_ones = Fold::new(Value::Int(0), |acc, value| match acc {
+        Value::Int(count) if *value == Value::RESULT_ONE => Value::Int(count + 1),
+        acc => acc,
+    });
+    let values = [Value::RESULT_ONE, Value::RESULT_ZERO, Value::RESULT_ONE];
+    assert_eq!(reduce(&mut count_ones, &values), Value::Int(2));
+    assert_eq!(count_ones.finish(), Value::Int(0));
+}
+
+#[test]
+fn interpreter_reduces_shots() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_quantum_seed(Some(42));
+    interpreter.add_reducer("vote", Box::new(MajorityVote::new()));
+    interpreter.add_reducer("parity", Box::new(Parity::new()));
+    interpreter.add_reducer(
+        "shots",
+        Box::new(Fold::new(Value::Int(0), |acc, _| match acc {
+            Value::Int(count) => Value::Int(count + 1),
+            acc => acc,
+        })),
+    );
+
+    let mut out = Vec::new();
+    let reduced = interpreter
+        .reduce_shots(
+            &mut GenericReceiver::new(&mut out),
+            "{ use qs = Qubit[2]; X(qs[0]); CNOT(qs[0], qs[1]); MResetEachZ(qs) }",
+            10,
+        )
+        .expect("compilation should succeed");
+    assert_eq!(reduced.shots, 10);
+    assert_eq!(reduced.failed, 0);
+    expect![[r#"
+        {
+            "parity": Double(
+                1.0,
+            ),
+            "shots": Int(
+                10,
+            ),
+            "vote": Array(
+                [
+                    Result(
+                        Val(
+                            true,
+                        ),
+                    ),
+                    Result(
+                        Val(
+                            true,
+                        ),
+                    ),
+                ],
+            ),
+        }
+    "#]]
+    .assert_debug_eq(&reduced.values);
+}
+
+#[test]
+fn failed_shots_are_counted_and_not_reduced() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    interpreter.add_reducer("vote", Box::new(MajorityVote::new()));
+
+    let mut out = Vec::new();
+    let reduced = interpreter
+        .reduce_shots(
+            &mut GenericReceiver::new(&mut out),
+            r#"{ fail "boom"; 0 }"#,
+            3,
+        )
+        .expect("compilation should succeed");
+    assert_eq!(reduced.failed, 3);
+    assert!(reduced.first_error.is_some());
+    assert_eq!(reduced.values["vote"], Value::unit());
+    assert!(interpreter.remove_reducer("vote").is_some());
+}
@@ -0,0 +1,274 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::time::Duration;
+#[cfg(not(any(target_family = "wasm")))]
+use std::{sync::mpsc, thread};
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+#[cfg(not(any(target_family = "wasm")))]
+use qsc_eval::interrupt::InterruptHandle;
+use qsc_eval::output::OutputLimit;
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    hir::{
+        Expr, ExprKind, ItemId, ItemKind, LocalItemId, Package, PackageId, Res, SpecBody, SpecGen,
+    },
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+use crate::verify::{item_uses, qualified_name};
+
+/// Limits for running untrusted code, such as in an online playground or a grading service,
+/// applied all at once with [`super::Interpreter::set_sandbox`]. The default configuration is
+/// meant to be safe for a shared service, and hosts can tighten or relax individual limits
+/// from there.
+///
+/// A sandboxed interpreter also never invokes host callbacks, such as
+/// [`super::StmtHooks`], and sandboxed code cannot declare intrinsic callables, which the
+/// backend would implement, so that untrusted code cannot reach the host through them. The
+/// intrinsics of the standard library still run on the backend, as they do without a sandbox.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sandbox {
+    /// The largest number of qubits that can be allocated at once.
+    pub max_qubits: usize,
+    /// The longest a single evaluation can run before it is interrupted. This is not enforced
+    /// on WebAssembly, where hosts should run the interpreter in a worker they can terminate.
+    pub max_time: Duration,
+    /// The limit on the output of each evaluation.
+    pub output_limit: OutputLimit,
+    /// The namespaces whose callables and types cannot be used, including the namespaces nested
+    /// in them, such as `Microsoft.Quantum.Diagnostics`. Neither can the callables of other
+    /// packages that use them, however indirectly.
+    pub denied_namespaces: Vec<String>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            max_qubits: 24,
+            max_time: Duration::from_secs(10),
+            output_limit: OutputLimit {
+                max_bytes: Some(1 << 20),
+                max_events: Some(10_000),
+            },
+            denied_namespaces: Vec::new(),
+        }
+    }
+}
+
+impl Sandbox {
+    /// The namespace denied by the sandbox that the fully qualified item name belongs to.
+    fn denied_namespace(&self, name: &str) -> Option<&str> {
+        self.denied_namespaces
+            .iter()
+            .find(|namespace| {
+                name.strip_prefix(namespace.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .map(String::as_str)
+    }
+
+    /// Finds the code in the package that the sandbox does not allow: uses of items from denied
+    /// namespaces, uses of callables from other packages that use such items themselves, however
+    /// indirectly, and callables declared as intrinsic, which would be implemented by the host's
+    /// backend.
+    pub(super) fn check(&self, store: &PackageStore, package: &Package) -> Vec<Error> {
+        let mut errors = intrinsic_decls(package);
+        if !self.denied_namespaces.is_empty() {
+            errors.extend(
+                item_uses(store, package, |name| self.denied_namespace(name).is_some())
+                    .into_iter()
+                    .map(|(name, span)| {
+                        let namespace = self
+                            .denied_namespace(&name)
+                            .expect("item should be in a denied namespace")
+                            .to_string();
+                        Error::DeniedNamespace(name, namespace, span)
+                    }),
+            );
+            errors.extend(self.indirect_uses(store, package));
+        }
+        errors.sort_by_key(|error| error.span().lo);
+        errors
+    }
+
+    /// Finds the uses of callables from other packages that reach an item from a denied
+    /// namespace through the callables they call. The items of the package itself are checked
+    /// where they are declared, so they are not followed.
+    fn indirect_uses(&self, store: &PackageStore, package: &Package) -> Vec<Error> {
+        let mut refs = ItemRefs::default();
+        refs.visit_package(package);
+        let mut reached = FxHashMap::default();
+        let mut errors = Vec::new();
+        for (id, span) in refs.refs {
+            let Some(package_id) = id.package else {
+                continue;
+            };
+            let Some(name) = store
+                .get(package_id)
+                .and_then(|unit| qualified_name(&unit.package, id.item))
+            else {
+                continue;
+            };
+            if self.denied_namespace(&name).is_some() {
+                // Already reported as a direct use.
+                continue;
+            }
+            let denied = reached
+                .entry((package_id, id.item))
+                .or_insert_with(|| self.reached_denied_item(store, package_id, id.item));
+            if let Some(denied) = denied {
+                let namespace = self
+                    .denied_namespace(denied)
+                    .expect("item should be in a denied namespace")
+                    .to_string();
+                errors.push(Error::DeniedIndirectly(
+                    name,
+                    denied.clone(),
+                    namespace,
+                    span,
+                ));
+            }
+        }
+        errors
+    }
+
+    /// The first item from a denied namespace that the item uses, directly or through the
+    /// callables it calls, if any.
+    fn reached_denied_item(
+        &self,
+        store: &PackageStore,
+        package_id: PackageId,
+        item_id: LocalItemId,
+    ) -> Option<String> {
+        let mut visited = FxHashSet::default();
+        let mut stack = vec![(package_id, item_id)];
+        while let Some((package_id, item_id)) = stack.pop() {
+            if !visited.insert((package_id, item_id)) {
+                continue;
+            }
+            let Some(item) = store
+                .get(package_id)
+                .and_then(|unit| unit.package.items.get(item_id))
+            else {
+                continue;
+            };
+            let mut refs = ItemRefs::default();
+            refs.visit_item(item);
+            for (id, _) in refs.refs {
+                // Items of the same package are referred to without a package.
+                let package_id = id.package.unwrap_or(package_id);
+                let name = store
+                    .get(package_id)
+                    .and_then(|unit| qualified_name(&unit.package, id.item));
+                if let Some(name) = name.filter(|name| self.denied_namespace(name).is_some()) {
+                    return Some(name);
+                }
+                stack.push((package_id, id.item));
+            }
+        }
+        None
+    }
+}
+
+/// The callables that the package declares as intrinsic.
+fn intrinsic_decls(package: &Package) -> Vec<Error> {
+    package
+        .items
+        .values()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Callable(decl)
+                if matches!(decl.body.body, SpecBody::Gen(SpecGen::Intrinsic)) =>
+            {
+                Some(Error::Intrinsic(decl.name.name.to_string(), decl.name.span))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The items that expressions refer to, with the span of each expression.
+#[derive(Default)]
+struct ItemRefs {
+    refs: Vec<(ItemId, Span)>,
+}
+
+impl<'a> Visitor<'a> for ItemRefs {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Var(Res::Item(id), _) = &expr.kind {
+            self.refs.push((*id, expr.span));
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("`{0}` cannot be used in the sandbox")]
+    #[diagnostic(code("Qsc.Interpret.SandboxDeniedNamespace"))]
+    #[diagnostic(help("the host does not allow items from the `{1}` namespace"))]
+    DeniedNamespace(String, String, #[label] Span),
+
+    #[error("`{0}` cannot be used in the sandbox")]
+    #[diagnostic(code("Qsc.Interpret.SandboxDeniedIndirectly"))]
+    #[diagnostic(help(
+        "it uses `{1}`, and the host does not allow items from the `{2}` namespace"
+    ))]
+    DeniedIndirectly(String, String, String, #[label] Span),
+
+    #[error("`{0}` cannot be declared as intrinsic in the sandbox")]
+    #[diagnostic(code("Qsc.Interpret.SandboxIntrinsic"))]
+    #[diagnostic(help(
+        "intrinsic callables are implemented by the host, which sandboxed code cannot call into"
+    ))]
+    Intrinsic(String, #[label] Span),
+}
+
+impl Error {
+    fn span(&self) -> Span {
+        match self {
+            Error::DeniedNamespace(.., span)
+            | Error::DeniedIndirectly(.., span)
+            | Error::Intrinsic(_, span) => *span,
+        }
+    }
+}
+
+/// Interrupts evaluation when it runs for longer than a time limit. The watchdog's thread ends as
+/// soon as the watchdog is dropped, so that no thread outlives the evaluation it guards.
+#[cfg(not(any(target_family = "wasm")))]
+pub(super) struct Watchdog {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+#[cfg(not(any(target_family = "wasm")))]
+impl Watchdog {
+    pub(super) fn start(interrupt: InterruptHandle, limit: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(limit) {
+                interrupt.interrupt();
+            }
+        });
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+#[cfg(not(any(target_family = "wasm")))]
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes the thread up before the limit.
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("watchdog thread should not panic");
+        }
+    }
+}
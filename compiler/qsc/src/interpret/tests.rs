@@ -4,11 +4,17 @@
 #![allow(clippy::needless_raw_string_hashes)]
 
 mod given_interpreter {
-    use crate::interpret::{Error, InterpretResult, Interpreter};
+    use crate::interpret::{
+        Differential, DivergenceKind, Error, InterpretResult, Interpreter, Sandbox, SandboxError,
+    };
+    use crate::line_column::Encoding;
     use expect_test::Expect;
     use miette::Diagnostic;
     use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
-    use qsc_eval::{output::CursorReceiver, val::Value};
+    use qsc_eval::{
+        output::{CursorReceiver, OutputLimit},
+        val::Value,
+    };
     use qsc_frontend::compile::SourceMap;
     use qsc_passes::PackageType;
     use std::{fmt::Write, io::Cursor, iter, str::from_utf8};
@@ -50,6 +56,19 @@ mod given_interpreter {
         (result, receiver.dump())
     }
 
+    fn source(
+        interpreter: &mut Interpreter,
+        name: &str,
+        contents: &str,
+    ) -> (InterpretResult, String) {
+        let mut cursor = Cursor::new(Vec::<u8>::new());
+        let mut receiver = CursorReceiver::new(&mut cursor);
+        (
+            interpreter.interpret_source(&mut receiver, name, contents),
+            receiver.dump(),
+        )
+    }
+
     mod without_sources {
         use expect_test::expect;
         use indoc::indoc;
@@ -101,6 +120,25 @@ mod given_interpreter {
             is_only_value(&result, &output, &Value::Int(3));
         }
 
+        #[test]
+        fn locals_include_top_level_bindings() {
+            let mut interpreter = get_interpreter();
+            assert!(interpreter.get_locals().is_empty());
+            let (result, output) = line(
+                &mut interpreter,
+                "let x = 1; mutable ys = [true]; { let inner = 2; }",
+            );
+            is_unit_with_output(&result, &output, "");
+            let (result, output) = line(&mut interpreter, "set ys += [false];");
+            is_unit_with_output(&result, &output, "");
+            let locals = interpreter
+                .get_locals()
+                .into_iter()
+                .map(|local| format!("{}: {} = {}", local.name, local.type_name, local.value))
+                .collect::<Vec<_>>();
+            assert_eq!(locals, ["x: Int = 1", "ys: Array = [true, false]"]);
+        }
+
         #[test]
         fn let_bindings_update_interpreter() {
             let mut interpreter = get_interpreter();
@@ -272,6 +310,1119 @@ mod given_interpreter {
             is_unit_with_output(&result, &output, "STATE:\n|0⟩: 1+0i");
         }
 
+        #[test]
+        fn extended_prelude_is_opened_for_fragments() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .extend_prelude(&["Microsoft.Quantum.Diagnostics", "Microsoft.Quantum.Math"])
+                .expect("prelude should be extended");
+            let (result, output) = line(&mut interpreter, "DumpMachine()");
+            is_unit_with_output(&result, &output, "STATE:\n|0⟩: 1+0i");
+            let (result, output) = line(&mut interpreter, "AbsI(-3)");
+            is_only_value(&result, &output, &Value::Int(3));
+        }
+
+        #[test]
+        fn extended_prelude_with_unknown_namespace_is_error() {
+            let mut interpreter = get_interpreter();
+            assert!(interpreter.extend_prelude(&["Not.A.Namespace"]).is_err());
+        }
+
+        #[test]
+        fn host_defined_oracles_can_be_called_from_fragments() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .define_oracle(
+                    "IsOdd",
+                    &crate::interpret::Oracle::TruthTable(vec![false, true, false, true]),
+                )
+                .expect("truth table oracle should be defined");
+            interpreter
+                .define_oracle(
+                    "Increment",
+                    &crate::interpret::Oracle::Permutation(vec![1, 2, 3, 0]),
+                )
+                .expect("permutation oracle should be defined");
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"{
+                    use (qs, target) = (Qubit[2], Qubit());
+                    Increment(qs);
+                    Increment(qs);
+                    Increment(qs);
+                    IsOdd(qs, target);
+                    let odd = MResetZ(target);
+                    Adjoint Increment(qs);
+                    (odd, Microsoft.Quantum.Measurement.MeasureInteger(qs))
+                }"},
+            );
+            is_only_value(
+                &result,
+                &output,
+                &Value::Tuple(vec![Value::RESULT_ONE, Value::Int(2)].into()),
+            );
+        }
+
+        #[test]
+        fn estimate_counts_resources_of_entry_expression() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Ghz(qs : Qubit[]) : Unit {
+                        H(qs[0]);
+                        for i in 1..Length(qs) - 1 {
+                            CNOT(qs[i - 1], qs[i]);
+                        }
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let estimate = interpreter
+                .estimate("{ use qs = Qubit[4]; Ghz(qs); T(qs[0]); MResetEachZ(qs) }")
+                .expect("estimate should succeed");
+            assert_eq!(
+                estimate,
+                crate::interpret::ResourceEstimate {
+                    num_qubits: 4,
+                    total_qubits: 4,
+                    t_count: 1,
+                    cnot_count: 3,
+                    ccnot_count: 0,
+                    rotation_count: 0,
+                    measurement_count: 4,
+                    depth: 5,
+                }
+            );
+        }
+
+        #[test]
+        fn host_built_circuits_can_be_called_from_fragments() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .define_circuit(
+                    "Flip",
+                    &crate::circuit::CircuitBuilder::new().x(0).cx(0, 1).m(1),
+                )
+                .expect("circuit should be defined");
+            interpreter
+                .define_circuit("Swap", &crate::circuit::CircuitBuilder::new().swap(0, 1))
+                .expect("circuit should be defined");
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"{
+                    use qs = Qubit[2];
+                    let results = Flip(qs);
+                    Adjoint Swap(qs);
+                    X(qs[1]);
+                    (results[0], MResetZ(qs[0]), MResetZ(qs[1]))
+                }"},
+            );
+            is_only_value(
+                &result,
+                &output,
+                &Value::Tuple(
+                    vec![Value::RESULT_ONE, Value::RESULT_ONE, Value::RESULT_ZERO].into(),
+                ),
+            );
+        }
+
+        #[test]
+        fn invalid_host_defined_oracles_are_errors() {
+            let mut interpreter = get_interpreter();
+            let errors = interpreter
+                .define_oracle(
+                    "Bad",
+                    &crate::interpret::Oracle::PhaseTruthTable(vec![true; 3]),
+                )
+                .expect_err("oracle with three entries should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Oracle(crate::interpret::OracleError::InvalidLength(
+                        3
+                    ))]
+                ),
+                "{errors:?}"
+            );
+            let errors = interpreter
+                .define_oracle("Bad", &crate::interpret::Oracle::Permutation(vec![0, 0]))
+                .expect_err("oracle that is not a permutation should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Oracle(
+                        crate::interpret::OracleError::NotAPermutation(2)
+                    )]
+                ),
+                "{errors:?}"
+            );
+            let errors = interpreter
+                .define_oracle(
+                    "Not.Bad",
+                    &crate::interpret::Oracle::Permutation(vec![0, 1]),
+                )
+                .expect_err("oracle with a qualified name should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Oracle(crate::interpret::OracleError::InvalidName(_))]
+                ),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn global_phase_is_tracked_separately_from_state() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "use q = Qubit(); Exp([PauliI], 0.5, [q]);",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            assert!((interpreter.get_global_phase() - 0.5).abs() < 1e-12);
+
+            let (state, _) = interpreter.get_quantum_state();
+            let expected = num_complex::Complex::from_polar(1.0, 0.5);
+            assert!((state[0].1 - expected).norm() < 1e-12);
+        }
+
+        #[test]
+        fn quantum_state_is_available_as_structured_data() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "use (a, b) = (Qubit(), Qubit()); H(a); X(b);",
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let state = interpreter.quantum_state();
+            assert_eq!(state.qubit_count, 2);
+            let probabilities = state
+                .probabilities()
+                .into_iter()
+                .map(|(index, p)| format!("{}: {p:.3}", crate::format_state_id(&index, 2)))
+                .collect::<Vec<_>>();
+            assert_eq!(probabilities, ["|01⟩: 0.500", "|11⟩: 0.500"]);
+            assert!((state.qubit_probability(0) - 0.5).abs() < 1e-12);
+            assert!((state.qubit_probability(1) - 1.0).abs() < 1e-12);
+            let (x, y, z) = state.bloch_vector(0);
+            assert!((x - 1.0).abs() < 1e-12 && y.abs() < 1e-12 && z.abs() < 1e-12);
+        }
+
+        #[test]
+        fn global_phase_is_sent_with_dump_machine() {
+            struct PhaseReceiver(Vec<f64>);
+            impl qsc_eval::output::Receiver for PhaseReceiver {
+                fn state(
+                    &mut self,
+                    _state: Vec<(num_bigint::BigUint, num_complex::Complex64)>,
+                    _qubit_count: usize,
+                ) -> Result<(), qsc_eval::output::Error> {
+                    Ok(())
+                }
+                fn message(&mut self, _msg: &str) -> Result<(), qsc_eval::output::Error> {
+                    Ok(())
+                }
+                fn global_phase(&mut self, phase: f64) -> Result<(), qsc_eval::output::Error> {
+                    self.0.push(phase);
+                    Ok(())
+                }
+            }
+
+            let mut interpreter = get_interpreter();
+            let mut receiver = PhaseReceiver(Vec::new());
+            interpreter
+                .eval_fragments(
+                    &mut receiver,
+                    "use q = Qubit(); Microsoft.Quantum.Diagnostics.DumpMachine(); Exp([PauliI], -0.25, [q]); Microsoft.Quantum.Diagnostics.DumpMachine();",
+                )
+                .expect("fragments should evaluate");
+            assert_eq!(receiver.0.len(), 2);
+            assert!(receiver.0[0].abs() < 1e-12);
+            assert!((receiver.0[1] - (std::f64::consts::TAU - 0.25)).abs() < 1e-12);
+        }
+
+        #[test]
+        fn state_fidelity_between_equivalent_sessions_is_one() {
+            let mut reference = get_interpreter();
+            let mut optimized = get_interpreter();
+            let (result, _) = line(
+                &mut reference,
+                "use qs = Qubit[2]; H(qs[0]); CNOT(qs[0], qs[1]); Rz(0.3, qs[1]);",
+            );
+            assert!(result.is_ok());
+            let (result, _) = line(
+                &mut optimized,
+                "use qs = Qubit[2]; H(qs[0]); Rz(0.3, qs[0]); CNOT(qs[0], qs[1]);",
+            );
+            assert!(result.is_ok());
+
+            let fidelity = reference
+                .state_fidelity_with(&mut optimized)
+                .expect("qubit counts should match");
+            assert!((fidelity - 1.0).abs() < 1e-9, "{fidelity}");
+
+            let (result, _) = line(&mut optimized, "X(qs[1]);");
+            assert!(result.is_ok());
+            let fidelity = reference
+                .state_fidelity_with(&mut optimized)
+                .expect("qubit counts should match");
+            assert!(fidelity.abs() < 1e-9, "{fidelity}");
+        }
+
+        #[test]
+        fn state_fidelity_with_different_qubit_counts_is_none() {
+            let mut one = get_interpreter();
+            let mut two = get_interpreter();
+            let (result, _) = line(&mut one, "use q = Qubit();");
+            assert!(result.is_ok());
+            let (result, _) = line(&mut two, "use qs = Qubit[2];");
+            assert!(result.is_ok());
+            assert!(one.state_fidelity_with(&mut two).is_none());
+        }
+
+        #[test]
+        fn qubit_leak_report_lists_live_qubits_with_call_stacks() {
+            let mut interpreter = get_interpreter();
+            assert_eq!(
+                interpreter.qubit_leak_report(),
+                "No qubits are allocated.\n"
+            );
+
+            let (result, _) = line(&mut interpreter, "use q = Qubit();");
+            assert!(result.is_ok());
+            let (result, _) = line(&mut interpreter, "{ use scoped = Qubit(); }");
+            assert!(result.is_ok());
+            let (result, _) = line(&mut interpreter, "use qs = Qubit[2];");
+            assert!(result.is_ok());
+
+            let allocations = interpreter.allocated_qubits();
+            assert_eq!(
+                allocations.iter().map(|a| a.id).collect::<Vec<_>>(),
+                vec![0, 1, 2]
+            );
+            assert!(allocations[0]
+                .call_stack
+                .contains("__quantum__rt__qubit_allocate"));
+            assert!(allocations[1].call_stack.contains("AllocateQubitArray"));
+
+            let report = interpreter.qubit_leak_report();
+            assert!(
+                report.starts_with("3 qubits are still allocated:\n"),
+                "{report}"
+            );
+            assert!(report.contains("Qubit 2 allocated\n    at "), "{report}");
+        }
+
+        #[test]
+        fn call_trace_logs_entry_and_exit_of_matching_callables() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = line(
+                &mut interpreter,
+                indoc! {"
+                    function Inner(x : Int) : Int { x + 1 }
+                    function Outer(x : Int, name : String) : Int {
+                        if x > 10 {
+                            return x;
+                        }
+                        Inner(x) * 2
+                    }
+                "},
+            );
+            assert!(result.is_ok());
+
+            interpreter.set_call_trace(Some(crate::interpret::CallTrace::new(["Outer", "In*"])));
+            let (result, output) = line(&mut interpreter, r#"Outer(3, "a") + Outer(20, "b")"#);
+            assert_eq!(result.expect("line should evaluate"), Value::Int(28));
+            expect![[r#"
+                enter Outer(3, a)
+                  enter Inner(3)
+                  exit Inner = 4
+                exit Outer = 8
+                enter Outer(20, b)
+                exit Outer = 20"#]]
+            .assert_eq(&output);
+
+            interpreter.set_call_trace(None);
+            let (result, output) = line(&mut interpreter, r#"Outer(3, "a")"#);
+            is_only_value(&result, &output, &Value::Int(8));
+        }
+
+        #[test]
+        fn call_trace_truncates_arguments_and_applies_to_entry_expressions() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_call_trace(Some(
+                crate::interpret::CallTrace::new(["H", "Length"]).with_max_value_len(4),
+            ));
+            let (result, output) = run(
+                &mut interpreter,
+                "{ use q = Qubit(); Adjoint H(q); H(q); Length([1, 2, 3, 4, 5]) }",
+            );
+            assert_eq!(
+                result
+                    .expect("program should compile")
+                    .expect("program should run"),
+                Value::Int(5)
+            );
+            expect![[r#"
+                enter Adjoint H(Qubi…)
+                exit Adjoint H
+                enter H(Qubi…)
+                exit H
+                enter Length([1, …)
+                exit Length = 5"#]]
+            .assert_eq(&output);
+        }
+
+        #[derive(Default)]
+        struct StmtRecorder {
+            events: Vec<String>,
+        }
+
+        impl qsc_eval::hooks::StmtHooks for StmtRecorder {
+            fn before_stmt(&mut self, span: qsc_eval::PackageSpan, env: &qsc_eval::Env) {
+                self.events.push(format!(
+                    "before {} with {} locals",
+                    span.span.hi - span.span.lo,
+                    env.get_variables_in_top_frame().len()
+                ));
+            }
+
+            fn after_stmt(&mut self, span: qsc_eval::PackageSpan, _env: &qsc_eval::Env) {
+                self.events
+                    .push(format!("after {}", span.span.hi - span.span.lo));
+            }
+        }
+
+        #[test]
+        fn stmt_hooks_are_invoked_around_nested_statements() {
+            let mut interpreter = Interpreter::new_with_debug(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, _) = line(
+                &mut interpreter,
+                "function F() : Int { let y = 1; return y + 1; }",
+            );
+            assert!(result.is_ok());
+
+            let recorder = std::rc::Rc::new(std::cell::RefCell::new(StmtRecorder::default()));
+            interpreter.set_stmt_hooks(Some(recorder.clone()));
+            let (result, output) = line(
+                &mut interpreter,
+                "mutable x = 0; if true { set x += 1; set x += 2; } x + F()",
+            );
+            is_only_value(&result, &output, &Value::Int(5));
+            expect![[r#"
+                [
+                    "before 14 with 0 locals",
+                    "after 14",
+                    "before 35 with 1 locals",
+                    "before 11 with 1 locals",
+                    "after 11",
+                    "before 11 with 1 locals",
+                    "after 11",
+                    "after 35",
+                    "before 7 with 1 locals",
+                    "before 10 with 0 locals",
+                    "after 10",
+                    "before 14 with 1 locals",
+                    "after 14",
+                    "after 7",
+                ]
+            "#]]
+            .assert_debug_eq(&recorder.borrow().events);
+
+            interpreter.set_stmt_hooks(None);
+            recorder.borrow_mut().events.clear();
+            let (result, _) = line(&mut interpreter, "F()");
+            assert!(result.is_ok());
+            assert!(recorder.borrow().events.is_empty());
+        }
+
+        /// A sparse simulator whose `T` gate is wrongly implemented as `S`.
+        struct FaultyT(crate::SparseSim);
+
+        impl crate::Backend for FaultyT {
+            type ResultType = bool;
+
+            fn h(&mut self, q: usize) {
+                self.0.h(q);
+            }
+            fn t(&mut self, q: usize) {
+                self.0.s(q);
+            }
+            fn mresetz(&mut self, q: usize) -> bool {
+                self.0.mresetz(q)
+            }
+            fn qubit_allocate(&mut self) -> usize {
+                self.0.qubit_allocate()
+            }
+            fn qubit_release(&mut self, q: usize) {
+                self.0.qubit_release(q);
+            }
+            fn qubit_is_zero(&mut self, q: usize) -> bool {
+                self.0.qubit_is_zero(q)
+            }
+            fn capture_quantum_state(
+                &mut self,
+            ) -> (Vec<(num_bigint::BigUint, num_complex::Complex64)>, usize) {
+                self.0.capture_quantum_state()
+            }
+            fn restore_quantum_state(
+                &mut self,
+                state: &[(num_bigint::BigUint, num_complex::Complex64)],
+                qubit_count: usize,
+            ) -> std::result::Result<(), String> {
+                self.0.restore_quantum_state(state, qubit_count)
+            }
+        }
+
+        fn debug_interpreter() -> Interpreter {
+            Interpreter::new_with_debug(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created")
+        }
+
+        #[test]
+        fn differential_run_of_equal_backends_has_no_divergences() {
+            let mut interpreter = debug_interpreter();
+            let mut sim = Differential::new(crate::SparseSim::new(), crate::SparseSim::new());
+            let mut sink = std::io::sink();
+            let mut out = qsc_eval::output::GenericReceiver::new(&mut sink);
+            let (result, divergences) = interpreter
+                .run_differential(
+                    &mut sim,
+                    &mut out,
+                    indoc! {"{
+                        use qs = Qubit[3];
+                        H(qs[0]);
+                        CNOT(qs[0], qs[1]);
+                        CNOT(qs[1], qs[2]);
+                        let results = MResetEachZ(qs);
+                        results[0] == results[1] and results[1] == results[2]
+                    }"},
+                    Encoding::Utf8,
+                )
+                .expect("expression should compile");
+            assert_eq!(result.expect("expression should run"), Value::Bool(true));
+            assert_eq!(divergences, []);
+        }
+
+        #[test]
+        fn differential_run_reports_diverging_statement() {
+            let mut interpreter = debug_interpreter();
+            let mut sim =
+                Differential::new(crate::SparseSim::new(), FaultyT(crate::SparseSim::new()));
+            let mut sink = std::io::sink();
+            let mut out = qsc_eval::output::GenericReceiver::new(&mut sink);
+            let (result, divergences) = interpreter
+                .run_differential(
+                    &mut sim,
+                    &mut out,
+                    indoc! {"{
+                        use q = Qubit();
+                        H(q);
+                        T(q);
+                        H(q);
+                        MResetZ(q)
+                    }"},
+                    Encoding::Utf8,
+                )
+                .expect("expression should compile");
+            assert!(result.is_ok());
+            assert_eq!(divergences.len(), 1, "{divergences:?}");
+            assert_eq!(divergences[0].operation, "t");
+            assert!(matches!(
+                divergences[0].kind,
+                DivergenceKind::State { fidelity } if fidelity < 1.0
+            ));
+            assert!(divergences[0].location.is_some());
+        }
+
+        fn events(interpreter: &mut Interpreter, fragments: &str) -> Vec<String> {
+            let mut events = Vec::new();
+            interpreter.eval_fragments_with_events(fragments, &mut |event| {
+                events.push(match event {
+                    crate::interpret::Event::CompileStarted { label } => {
+                        format!("compile started: {label}")
+                    }
+                    crate::interpret::Event::EvalStarted => "eval started".to_string(),
+                    crate::interpret::Event::Message(msg) => format!("message: {msg}"),
+                    crate::interpret::Event::State { qubit_count, .. } => {
+                        format!("state: {qubit_count} qubits")
+                    }
+                    crate::interpret::Event::GlobalPhase(phase) => format!("global phase: {phase}"),
+                    crate::interpret::Event::Trace(msg) => format!("trace: {msg}"),
+                    crate::interpret::Event::OutputLimitExceeded(notice) => notice.to_string(),
+                    crate::interpret::Event::Result(value) => format!("result: {value}"),
+                    crate::interpret::Event::Error(errors) => format!("errors: {}", errors.len()),
+                });
+            });
+            events
+        }
+
+        #[test]
+        fn event_stream_reports_lifecycle_and_output() {
+            let mut interpreter = get_interpreter();
+            expect![[r#"
+                [
+                    "compile started: line_0",
+                    "eval started",
+                    "message: hello",
+                    "state: 1 qubits",
+                    "global phase: 0",
+                    "result: 2",
+                ]
+            "#]]
+            .assert_debug_eq(&events(
+                &mut interpreter,
+                indoc! {r#"
+                    Message("hello");
+                    use q = Qubit();
+                    Microsoft.Quantum.Diagnostics.DumpMachine();
+                    1 + 1
+                "#},
+            ));
+        }
+
+        #[test]
+        fn output_past_limit_is_dropped() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_output_limit(OutputLimit {
+                max_bytes: None,
+                max_events: Some(3),
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {r#"
+                    for i in 1..100 {
+                        Message($"{i}");
+                    }
+                    7
+                "#},
+            );
+            assert_eq!(result.expect("line should succeed"), Value::Int(7));
+            assert_eq!(output, "1\n2\n3");
+            expect![[r#"
+                Some(
+                    OutputLimitExceeded {
+                        limit: OutputLimit {
+                            max_bytes: None,
+                            max_events: Some(
+                                3,
+                            ),
+                        },
+                        forwarded_events: 3,
+                        dropped_events: 97,
+                    },
+                )
+            "#]]
+            .assert_debug_eq(&interpreter.output_limit_exceeded());
+
+            let (result, output) = line(&mut interpreter, r#"Message("again");"#);
+            is_unit_with_output(&result, &output, "again");
+            assert_eq!(interpreter.output_limit_exceeded(), None);
+        }
+
+        #[test]
+        fn sandbox_limits_qubits() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .set_sandbox(Some(Sandbox {
+                    max_qubits: 2,
+                    ..Sandbox::default()
+                }))
+                .expect("sandbox should be set");
+            let (result, output) = line(&mut interpreter, "{ use qs = Qubit[2]; }");
+            is_only_value(&result, &output, &Value::unit());
+            let (result, _) = line(&mut interpreter, "{ use qs = Qubit[3]; }");
+            let errors = result.expect_err("allocation should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Eval(error)]
+                        if matches!(error.error().error(), qsc_eval::Error::QubitLimitExceeded(2, _))
+                ),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn call_counts_span_lines() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "Microsoft.Quantum.Diagnostics.StartCountingOperation(H);",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = line(
+                &mut interpreter,
+                "{ use q = Qubit(); H(q); Adjoint H(q); H(q); H(q); }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = line(
+                &mut interpreter,
+                "Microsoft.Quantum.Diagnostics.StopCountingOperation(H)",
+            );
+            is_only_value(&result, &output, &Value::Int(3));
+        }
+
+        #[test]
+        fn call_counting_misuse_fails() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = line(
+                &mut interpreter,
+                "Microsoft.Quantum.Diagnostics.StopCountingOperation(X)",
+            );
+            let errors = result.expect_err("stopping should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Eval(error)]
+                        if matches!(error.error().error(), qsc_eval::Error::CallableNotCounted(_))
+                ),
+                "{errors:?}"
+            );
+            let (result, _) = line(
+                &mut interpreter,
+                "{ open Microsoft.Quantum.Diagnostics; StartCountingOperation(X); StartCountingOperation(X); }",
+            );
+            let errors = result.expect_err("starting twice should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Eval(error)]
+                        if matches!(error.error().error(), qsc_eval::Error::CallableAlreadyCounted(_))
+                ),
+                "{errors:?}"
+            );
+        }
+
+        /// A sparse simulator that counts the `H` gates applied to it.
+        struct HadamardCounter {
+            sim: crate::SparseSim,
+            count: std::rc::Rc<std::cell::Cell<usize>>,
+        }
+
+        impl crate::Backend for HadamardCounter {
+            type ResultType = bool;
+
+            fn h(&mut self, q: usize) {
+                self.count.set(self.count.get() + 1);
+                self.sim.h(q);
+            }
+            fn m(&mut self, q: usize) -> bool {
+                self.sim.m(q)
+            }
+            fn mresetz(&mut self, q: usize) -> bool {
+                self.sim.mresetz(q)
+            }
+            fn qubit_allocate(&mut self) -> usize {
+                self.sim.qubit_allocate()
+            }
+            fn qubit_release(&mut self, q: usize) {
+                self.sim.qubit_release(q);
+            }
+            fn qubit_is_zero(&mut self, q: usize) -> bool {
+                self.sim.qubit_is_zero(q)
+            }
+            fn capture_quantum_state(
+                &mut self,
+            ) -> (Vec<(num_bigint::BigUint, num_complex::Complex64)>, usize) {
+                self.sim.capture_quantum_state()
+            }
+        }
+
+        #[test]
+        fn plugged_in_backend_runs_lines_and_entry_expressions() {
+            let count = std::rc::Rc::new(std::cell::Cell::new(0));
+            let backend_count = count.clone();
+            let mut interpreter = Interpreter::new_with_backend(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                move || HadamardCounter {
+                    sim: crate::SparseSim::new(),
+                    count: backend_count.clone(),
+                },
+            )
+            .expect("interpreter should be created");
+
+            let (result, output) = line(&mut interpreter, "use q = Qubit(); H(q); H(q);");
+            is_only_value(&result, &output, &Value::unit());
+            assert_eq!(count.get(), 2);
+            let (result, output) = line(&mut interpreter, "M(q)");
+            is_only_value(&result, &output, &Value::RESULT_ZERO);
+
+            let (result, output) = run(&mut interpreter, "{ use q = Qubit(); H(q); MResetZ(q) }");
+            result
+                .expect("run should compile")
+                .expect("run should succeed");
+            assert!(output.is_empty(), "{output}");
+            assert_eq!(count.get(), 3);
+        }
+
+        #[test]
+        fn sparse_simulator_runs_wide_ghz_states() {
+            let mut interpreter = Interpreter::new_with_backend(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                crate::SparseSim::new,
+            )
+            .expect("interpreter should be created");
+            let (result, output) = run(
+                &mut interpreter,
+                indoc! {"{
+                    use qs = Qubit[300];
+                    H(qs[0]);
+                    for q in qs[1...] {
+                        CNOT(qs[0], q);
+                    }
+                    let results = MResetEachZ(qs);
+                    Microsoft.Quantum.Arrays.All(r -> r == results[0], results)
+                }"},
+            );
+            is_only_value(
+                &result.expect("run should compile"),
+                &output,
+                &Value::Bool(true),
+            );
+        }
+
+        #[test]
+        fn noisy_simulator_applies_readout_errors() {
+            let noise = crate::NoiseModel::default()
+                .with_operation(
+                    "mresetz",
+                    crate::Noise {
+                        bit_flip: 1.0,
+                        ..crate::Noise::default()
+                    },
+                )
+                .expect("noise should be valid");
+            let mut interpreter = Interpreter::new_with_noise(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                noise,
+            )
+            .expect("interpreter should be created");
+            let (result, output) = run(&mut interpreter, "{ use q = Qubit(); MResetZ(q) }");
+            is_only_value(
+                &result.expect("run should compile"),
+                &output,
+                &Value::RESULT_ONE,
+            );
+            let (result, output) = run(&mut interpreter, "{ use q = Qubit(); X(q); MResetZ(q) }");
+            is_only_value(
+                &result.expect("run should compile"),
+                &output,
+                &Value::RESULT_ZERO,
+            );
+        }
+
+        #[test]
+        fn strict_stabilizer_simulator_fails_non_clifford_programs() {
+            let mut interpreter = Interpreter::new_with_backend(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                crate::StabilizerSim::strict,
+            )
+            .expect("interpreter should be created");
+            let (result, output) = run(
+                &mut interpreter,
+                "{ use (a, b) = (Qubit(), Qubit()); H(a); CNOT(a, b); S(b); MResetZ(a) == MResetZ(b) }",
+            );
+            is_only_value(
+                &result.expect("run should compile"),
+                &output,
+                &Value::Bool(true),
+            );
+
+            let (result, _) = run(
+                &mut interpreter,
+                "{ use q = Qubit(); H(q); T(q); MResetZ(q) }",
+            );
+            let errors = result
+                .expect("run should compile")
+                .expect_err("run should fail");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Eval(error)]
+                        if matches!(error.error().error(), qsc_eval::Error::IntrinsicFail(..))
+                ),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn sandbox_rejects_denied_namespaces() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .set_sandbox(Some(Sandbox {
+                    denied_namespaces: vec!["Microsoft.Quantum.Diagnostics".to_string()],
+                    ..Sandbox::default()
+                }))
+                .expect("sandbox should be set");
+            let (result, output) = line(
+                &mut interpreter,
+                "Microsoft.Quantum.Diagnostics.DumpMachine();",
+            );
+            let errors = result.expect_err("line should be rejected");
+            assert!(matches!(&errors[..], [Error::Sandbox(_)]), "{errors:?}");
+            assert_eq!(output, "");
+            let (result, output) = line(&mut interpreter, r#"Message("ok");"#);
+            is_unit_with_output(&result, &output, "ok");
+        }
+
+        #[test]
+        fn sandbox_rejects_library_callables_that_use_denied_namespaces() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .set_sandbox(Some(Sandbox {
+                    denied_namespaces: vec!["Microsoft.Quantum.Diagnostics".to_string()],
+                    ..Sandbox::default()
+                }))
+                .expect("sandbox should be set");
+            // `IntAsBoolArray` checks its input with `Microsoft.Quantum.Diagnostics.Fact`.
+            let (result, output) = line(
+                &mut interpreter,
+                "Microsoft.Quantum.Convert.IntAsBoolArray(3, 2)",
+            );
+            let errors = result.expect_err("line should be rejected");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Sandbox(error)]
+                        if matches!(error.error(), SandboxError::DeniedIndirectly(..))
+                ),
+                "{errors:?}"
+            );
+            assert_eq!(output, "");
+        }
+
+        #[test]
+        fn sandbox_rejects_intrinsic_declarations() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .set_sandbox(Some(Sandbox::default()))
+                .expect("sandbox should be set");
+            let (result, _) = line(
+                &mut interpreter,
+                "operation CallHost() : Unit { body intrinsic; }",
+            );
+            let errors = result.expect_err("line should be rejected");
+            assert!(
+                matches!(
+                    &errors[..],
+                    [Error::Sandbox(error)]
+                        if matches!(error.error(), SandboxError::Intrinsic(..))
+                ),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn sandbox_interrupts_long_evaluation() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .set_sandbox(Some(Sandbox {
+                    max_time: std::time::Duration::from_millis(50),
+                    ..Sandbox::default()
+                }))
+                .expect("sandbox should be set");
+            let (result, _) = line(&mut interpreter, "while true {}");
+            let errors = result.expect_err("loop should be interrupted");
+            assert!(errors[0].is_interrupted(), "{errors:?}");
+
+            interpreter
+                .set_sandbox(None)
+                .expect("sandbox should be removed");
+            let (result, output) = line(&mut interpreter, "{ use qs = Qubit[30]; }");
+            is_only_value(&result, &output, &Value::unit());
+        }
+
+        #[test]
+        fn close_releases_session_with_allocated_qubits() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "use qs = Qubit[3]; X(qs[0]);");
+            is_only_value(&result, &output, &Value::unit());
+            interpreter.close();
+        }
+
+        #[test]
+        fn event_stream_reports_output_limit_exceeded() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_output_limit(OutputLimit {
+                max_bytes: Some(5),
+                max_events: None,
+            });
+            expect![[r#"
+                [
+                    "compile started: line_0",
+                    "eval started",
+                    "message: abc",
+                    "message: de",
+                    "output limit exceeded: 2 further output event(s) were dropped",
+                    "result: ()",
+                ]
+            "#]]
+            .assert_debug_eq(&events(
+                &mut interpreter,
+                indoc! {r#"
+                    Message("abc");
+                    Message("de");
+                    Message("f");
+                    Message("");
+                "#},
+            ));
+        }
+
+        #[test]
+        fn event_stream_reports_compile_and_runtime_errors() {
+            let mut interpreter = get_interpreter();
+            expect![[r#"
+                [
+                    "compile started: line_0",
+                    "errors: 1",
+                ]
+            "#]]
+            .assert_debug_eq(&events(&mut interpreter, "let x = Undefined;"));
+            expect![[r#"
+                [
+                    "compile started: line_1",
+                    "eval started",
+                    "message: before",
+                    "errors: 1",
+                ]
+            "#]]
+            .assert_debug_eq(&events(
+                &mut interpreter,
+                r#"Message("before"); fail "boom";"#,
+            ));
+        }
+
+        /// A receiver that records the output it is sent into a buffer shared with the test.
+        struct SharedReceiver(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+        impl qsc_eval::output::Receiver for SharedReceiver {
+            fn state(
+                &mut self,
+                _state: Vec<(num_bigint::BigUint, num_complex::Complex64)>,
+                qubit_count: usize,
+            ) -> Result<(), qsc_eval::output::Error> {
+                self.0.borrow_mut().push(format!("state: {qubit_count}"));
+                Ok(())
+            }
+
+            fn message(&mut self, msg: &str) -> Result<(), qsc_eval::output::Error> {
+                self.0.borrow_mut().push(format!("message: {msg}"));
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn output_is_routed_to_registered_receivers() {
+            use qsc_eval::output::OutputKind;
+
+            let mut interpreter = get_interpreter();
+            let messages = std::rc::Rc::default();
+            let dumps = std::rc::Rc::default();
+            interpreter.add_receiver(
+                "messages",
+                &[OutputKind::Message, OutputKind::Diagnostic],
+                Box::new(SharedReceiver(std::rc::Rc::clone(&messages))),
+            );
+            interpreter.add_receiver(
+                "dumps",
+                &[OutputKind::State],
+                Box::new(SharedReceiver(std::rc::Rc::clone(&dumps))),
+            );
+            interpreter.set_call_trace(Some(crate::interpret::CallTrace::new(["Length"])));
+
+            let result = interpreter.eval_fragments_routed(indoc! {r#"
+                Message("hello");
+                use q = Qubit();
+                Microsoft.Quantum.Diagnostics.DumpMachine();
+                Length([1])
+            "#});
+            assert_eq!(result.expect("fragments should evaluate"), Value::Int(1));
+            assert_eq!(
+                *messages.borrow(),
+                vec![
+                    "message: hello",
+                    "message: enter Length([1])",
+                    "message: exit Length = 1"
+                ]
+            );
+            assert_eq!(*dumps.borrow(), vec!["state: 1"]);
+
+            assert!(interpreter.remove_receiver("dumps").is_some());
+            let result = interpreter
+                .run_routed("Microsoft.Quantum.Diagnostics.DumpMachine()")
+                .expect("expression should compile");
+            assert!(result.is_ok());
+            assert_eq!(dumps.borrow().len(), 1);
+            assert_eq!(messages.borrow().len(), 3);
+        }
+
+        #[test]
+        fn single_precision_interpreter_reports_error_estimate() {
+            let mut single = Interpreter::new_with_precision(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                crate::Precision::Single,
+            )
+            .expect("interpreter should be created");
+            let program =
+                "{ use q = Qubit(); for _ in 1..100 { Rx(0.1, q); } let r = M(q); Reset(q); r }";
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let (result, error) = single
+                .run_with_error_estimate(&mut receiver, program)
+                .expect("program should compile");
+            assert!(result.is_ok());
+            let error = error.expect("single precision should estimate its error");
+            assert!(error > 0.0 && error < 1e-4, "{error}");
+
+            // The session simulator has the same precision as the runs.
+            let (result, _) = line(&mut single, "use q = Qubit(); Rx(0.1, q);");
+            assert!(result.is_ok());
+            assert!(single.error_estimate().is_some_and(|error| error > 0.0));
+
+            let mut double = get_interpreter();
+            let (result, error) = double
+                .run_with_error_estimate(&mut receiver, program)
+                .expect("program should compile");
+            assert!(result.is_ok());
+            assert_eq!(error, None);
+        }
+
         #[test]
         fn open_namespace_call_same_line() {
             let mut interpreter = get_interpreter();
@@ -318,6 +1469,103 @@ mod given_interpreter {
             is_only_value(&result, &output, &Value::Int(5));
         }
 
+        #[test]
+        fn open_namespace_declared_later_on_same_line() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "open Foo; namespace Foo { function Bar() : Int { 5 } } Bar()",
+            );
+            is_only_value(&result, &output, &Value::Int(5));
+        }
+
+        #[test]
+        fn opens_are_listed_in_order() {
+            let mut interpreter = get_interpreter();
+            for fragment in [
+                "open Microsoft.Quantum.Math;",
+                "open Microsoft.Quantum.Arrays as Arrays;",
+                "open Microsoft.Quantum.Diagnostics;",
+            ] {
+                let (result, output) = line(&mut interpreter, fragment);
+                is_only_value(&result, &output, &Value::unit());
+            }
+            assert_eq!(
+                interpreter.opens(),
+                [
+                    ("Microsoft.Quantum.Math".to_string(), None),
+                    (
+                        "Microsoft.Quantum.Arrays".to_string(),
+                        Some(std::rc::Rc::from("Arrays"))
+                    ),
+                    ("Microsoft.Quantum.Diagnostics".to_string(), None),
+                ]
+            );
+            let (result, output) = line(&mut interpreter, "Arrays.Reversed([1, 2])");
+            is_only_value(
+                &result,
+                &output,
+                &Value::Array(vec![Value::Int(2), Value::Int(1)].into()),
+            );
+        }
+
+        #[test]
+        fn exported_results_include_session_metadata() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_metadata("user", "alice");
+            interpreter.set_metadata("notebook", "nb-1");
+            assert_eq!(
+                interpreter.remove_metadata("notebook").as_deref(),
+                Some("nb-1")
+            );
+
+            let (result, _) = line(&mut interpreter, "(1, [true])");
+            assert_eq!(
+                interpreter.export(&result, Encoding::Utf8),
+                serde_json::json!({ "metadata": { "user": "alice" }, "value": [1, [true]] })
+            );
+
+            let (result, _) = line(
+                &mut interpreter,
+                "function Boom() : Unit { fail \"oops\"; } Boom()",
+            );
+            let export = interpreter.export(&result, Encoding::Utf8);
+            assert_eq!(export["metadata"]["user"], "alice");
+            assert!(export.get("value").is_none());
+            let error = &export["errors"][0];
+            assert_eq!(error["code"], "Qsc.Eval.UserFail");
+            assert!(error["stackTrace"].is_string(), "{error}");
+        }
+
+        #[test]
+        fn completions_include_variables() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "let counter = 1; use q = Qubit();");
+            is_only_value(&result, &output, &Value::unit());
+            let completions = interpreter.completions("set cou", 7);
+            let first = completions.first().expect("there should be completions");
+            assert_eq!(first.label, "counter");
+            assert_eq!(first.kind, crate::completion::CompletionKind::Variable);
+            assert_eq!(first.detail.as_deref(), Some("Int"));
+            assert!(interpreter
+                .completions("X(", 2)
+                .iter()
+                .any(|item| item.label == "q"));
+        }
+
+        #[test]
+        fn failed_fragment_does_not_open_namespace() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = line(
+                &mut interpreter,
+                "open Microsoft.Quantum.Diagnostics; Undefined()",
+            );
+            assert!(result.is_err());
+            assert!(interpreter.opens().is_empty());
+            let (result, _) = line(&mut interpreter, "DumpMachine()");
+            assert!(result.is_err());
+        }
+
         #[test]
         fn mix_stmts_and_namespace_same_line() {
             let mut interpreter = get_interpreter();
@@ -446,6 +1694,32 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn runtime_error_to_json() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Main() : Unit { Microsoft.Quantum.Random.DrawRandomInt(2,1); }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, _) = line(&mut interpreter, "Main()");
+            let errors = result.expect_err("line should fail");
+            let json = errors[0].to_json(Encoding::Utf16);
+            assert_eq!(json["code"], "Qsc.Eval.EmptyRange");
+            assert_eq!(json["severity"], "error");
+            assert_eq!(json["message"], "runtime error: empty range");
+            assert_eq!(json["primary"]["source"], "line_0");
+            assert_eq!(json["primary"]["message"], "the range cannot be empty");
+            assert_eq!(
+                json["primary"]["range"],
+                serde_json::json!({
+                    "start": { "line": 0, "column": 64 },
+                    "end": { "line": 0, "column": 69 },
+                })
+            );
+            assert_eq!(json["related"], serde_json::json!([]));
+        }
+
         #[test]
         fn compiler_error_across_lines() {
             let mut interpreter = get_interpreter();
@@ -935,6 +2209,110 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn unrestricted_qirgen_targets_base_profile() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Result { use q = Qubit(); H(q); MResetZ(q) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let res = interpreter.qirgen("Foo()").expect("expected success");
+            assert!(res.contains("call void @__quantum__qis__h__body"), "{res}");
+            assert!(res.contains(r#""qir_profiles"="base_profile""#), "{res}");
+        }
+
+        #[test]
+        fn unrestricted_qirgen_fails_when_entry_expr_does_not_match_base_profile() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Unit { use q = Qubit(); if MResetZ(q) == One { X(q); } }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let errors = interpreter.qirgen("Foo()").expect_err("expected error");
+            assert!(
+                errors
+                    .iter()
+                    .all(|error| matches!(error, Error::PartialEvaluation(..))),
+                "{errors:?}"
+            );
+        }
+
+        struct ResultCount;
+
+        impl crate::codegen::CodegenTarget for ResultCount {
+            fn name(&self) -> &str {
+                "result-count"
+            }
+
+            fn extension(&self) -> &str {
+                "txt"
+            }
+
+            fn generate(&self, program: &crate::rir::Program) -> Result<String, String> {
+                if program.num_results == 0 {
+                    Err("program does not measure any qubits".to_string())
+                } else {
+                    Ok(format!("results: {}", program.num_results))
+                }
+            }
+        }
+
+        #[test]
+        fn codegen_uses_registered_target() {
+            let mut interpreter = get_interpreter();
+            interpreter.register_codegen_target(ResultCount);
+            assert_eq!(
+                interpreter.codegen_targets().collect::<Vec<_>>(),
+                ["qir", "result-count"]
+            );
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Result { use q = Qubit(); H(q); MResetZ(q) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let code = interpreter
+                .codegen("Foo()", "result-count")
+                .expect("expected success");
+            expect!["results: 1"].assert_eq(&code);
+            assert_eq!(
+                interpreter
+                    .codegen("Foo()", "qir")
+                    .expect("expected success"),
+                interpreter.qirgen("Foo()").expect("expected success")
+            );
+
+            let errors = interpreter
+                .codegen("{}", "result-count")
+                .expect_err("expected error");
+            expect![[r#"
+                [
+                    CodegenTarget(
+                        "result-count",
+                        "program does not measure any qubits",
+                    ),
+                ]
+            "#]]
+            .assert_debug_eq(&errors);
+        }
+
+        #[test]
+        fn codegen_fails_for_unknown_target() {
+            let mut interpreter = get_interpreter();
+            let errors = interpreter
+                .codegen("{}", "qasm")
+                .expect_err("expected error");
+            expect![[r#"
+                [
+                    UnknownCodegenTarget(
+                        "qasm",
+                    ),
+                ]
+            "#]]
+            .assert_debug_eq(&errors);
+        }
+
         #[test]
         fn qirgen_entry_expr_in_block() {
             let mut interpreter = Interpreter::new(
@@ -1268,6 +2646,106 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn seeded_interpreters_repeat_outcomes() {
+            let program = indoc! {"
+                {
+                    use qs = Qubit[16];
+                    ApplyToEach(H, qs);
+                    let results = MResetEachZ(qs);
+                    (results, Microsoft.Quantum.Random.DrawRandomInt(0, 1000000))
+                }
+            "};
+            let outcome = |seed| {
+                let mut interpreter = Interpreter::new_with_seed(
+                    true,
+                    SourceMap::default(),
+                    PackageType::Lib,
+                    TargetCapabilityFlags::all(),
+                    LanguageFeatures::default(),
+                    seed,
+                )
+                .expect("interpreter should be created");
+                let (result, output) = line(&mut interpreter, program);
+                assert_eq!(output, "");
+                result.expect("evaluation should succeed").to_string()
+            };
+            assert_eq!(outcome(7), outcome(7));
+            assert_ne!(outcome(7), outcome(8));
+
+            let mut interpreter = get_interpreter();
+            interpreter.set_seed(Some(7));
+            let (result, _) = line(&mut interpreter, program);
+            assert_eq!(
+                result.expect("evaluation should succeed").to_string(),
+                outcome(7)
+            );
+        }
+
+        #[test]
+        fn run_shots_collects_each_shot() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {r#"
+                    operation Flip() : Result {
+                        use q = Qubit();
+                        H(q);
+                        let r = MResetZ(q);
+                        Message($"{r}");
+                        r
+                    }
+                "#},
+            );
+            is_only_value(&result, &output, &Value::unit());
+            interpreter.set_quantum_seed(Some(42));
+
+            let mut out = Vec::new();
+            let mut receiver = crate::interpret::GenericReceiver::new(&mut out);
+            let shots = interpreter
+                .run_shots(&mut receiver, "Flip()", 40)
+                .expect("compilation should succeed")
+                .into_iter()
+                .map(|result| result.expect("shot should succeed").to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(shots.len(), 40);
+            assert!(shots.iter().any(|shot| shot == "Zero"));
+            assert!(shots.iter().any(|shot| shot == "One"));
+            let messages = String::from_utf8(out).expect("output should be UTF-8");
+            assert_eq!(messages, shots.join("\n") + "\n");
+
+            let mut sink = std::io::sink();
+            let mut receiver = crate::interpret::GenericReceiver::new(&mut sink);
+            let again = interpreter
+                .run_shots(&mut receiver, "Flip()", 40)
+                .expect("compilation should succeed")
+                .into_iter()
+                .map(|result| result.expect("shot should succeed").to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(shots, again);
+        }
+
+        #[test]
+        fn run_shots_do_not_share_state() {
+            let mut interpreter = get_interpreter();
+            let mut sink = std::io::sink();
+            let mut receiver = crate::interpret::GenericReceiver::new(&mut sink);
+            let results = interpreter
+                .run_shots(
+                    &mut receiver,
+                    "{ use q = Qubit(); let r = M(q); X(q); Reset(q); r }",
+                    3,
+                )
+                .expect("compilation should succeed");
+            for result in results {
+                assert_eq!(result.expect("shot should succeed"), Value::RESULT_ZERO);
+            }
+            assert!(interpreter.get_locals().is_empty());
+            interpreter
+                .run_shots(&mut receiver, "Foo()", 3)
+                .expect_err("compilation should fail");
+        }
+
         #[test]
         fn run_runtime_failure() {
             let mut interpreter = get_interpreter();
@@ -1320,6 +2798,247 @@ mod given_interpreter {
             let (result, output) = line(&mut interpreter, "123");
             is_only_value(&result, &output, &Value::Int(123));
         }
+
+        #[test]
+        fn source_file_runs_entry_point() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = source(
+                &mut interpreter,
+                "Pasted.qs",
+                indoc! {"
+                    namespace Pasted {
+                        function Helper() : Int { 41 }
+
+                        @EntryPoint()
+                        operation Main() : Int {
+                            Message(\"running\");
+                            Helper() + 1
+                        }
+                    }
+                "},
+            );
+            assert_eq!(result.expect("source should run"), Value::Int(42));
+            assert_eq!(output, "running");
+
+            let (result, output) = line(&mut interpreter, "Pasted.Helper()");
+            is_only_value(&result, &output, &Value::Int(41));
+        }
+
+        #[test]
+        fn source_file_without_namespace_is_named_after_file() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = source(
+                &mut interpreter,
+                "Math.qs",
+                "function Triple(x : Int) : Int { x * 3 }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let (result, output) = line(&mut interpreter, "Math.Triple(2)");
+            is_only_value(&result, &output, &Value::Int(6));
+        }
+
+        #[test]
+        fn source_file_errors_refer_to_file() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = source(
+                &mut interpreter,
+                "Broken.qs",
+                indoc! {"
+                    namespace Broken {
+                        function Answer() : Int { Missing }
+                    }
+                "},
+            );
+            is_only_error(
+                &result,
+                &output,
+                &expect![[r#"
+                    name error: `Missing` not found
+                       [Broken.qs] [Missing]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn source_file_with_statements_is_error() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = source(&mut interpreter, "Pasted.qs", "Message(\"hi\");");
+            assert!(result.is_err());
+        }
+
+        /// Triggers the interrupt handle of the interpreter every few milliseconds until the
+        /// returned sender is dropped, since evaluation ignores interruptions from before it starts.
+        fn interrupt_until_stopped(
+            interpreter: &Interpreter,
+        ) -> (std::thread::JoinHandle<()>, std::sync::mpsc::Sender<()>) {
+            let handle = interpreter.interrupt_handle();
+            let (stop, stopped) = std::sync::mpsc::channel::<()>();
+            let interrupter = std::thread::spawn(move || {
+                while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+                    stopped.recv_timeout(std::time::Duration::from_millis(10))
+                {
+                    handle.interrupt();
+                }
+            });
+            (interrupter, stop)
+        }
+
+        #[test]
+        fn interrupt_stops_evaluation_and_keeps_session() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "mutable count = 0;");
+            is_only_value(&result, &output, &Value::unit());
+
+            let (interrupter, stop) = interrupt_until_stopped(&interpreter);
+            let (result, _) = line(&mut interpreter, "while true { set count += 1; }");
+            drop(stop);
+            interrupter.join().expect("thread should not panic");
+            let errors = result.expect_err("loop should be interrupted");
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].is_interrupted(), "{errors:?}");
+
+            let (result, output) = line(&mut interpreter, "count > 0");
+            is_only_value(&result, &output, &Value::Bool(true));
+        }
+
+        #[test]
+        fn interrupt_while_idle_is_ignored() {
+            let mut interpreter = get_interpreter();
+            interpreter.interrupt_handle().interrupt();
+            let (result, output) = line(&mut interpreter, "while false {} 1");
+            is_only_value(&result, &output, &Value::Int(1));
+        }
+
+        #[test]
+        fn interrupt_stops_empty_loop() {
+            let mut interpreter = get_interpreter();
+            let (interrupter, stop) = interrupt_until_stopped(&interpreter);
+            let (result, _) = line(&mut interpreter, "while true {}");
+            drop(stop);
+            interrupter.join().expect("thread should not panic");
+            let errors = result.expect_err("loop should be interrupted");
+            assert!(errors[0].is_interrupted(), "{errors:?}");
+        }
+
+        #[test]
+        fn value_formatter_renders_results() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = line(&mut interpreter, "(1.0 / 3.0, [1, 2, 3, 4])");
+            let value = result.expect("line should evaluate");
+            assert_eq!(
+                interpreter.format_value(&value),
+                "(0.3333333333333333, [1, 2, 3, 4])"
+            );
+
+            interpreter.set_value_formatter(std::rc::Rc::new(crate::interpret::ValueFormat {
+                double_precision: Some(2),
+                max_array_elements: Some(2),
+                ..Default::default()
+            }));
+            assert_eq!(
+                interpreter.format_value(&value),
+                "(0.33, [1, 2, ... 2 more])"
+            );
+        }
+
+        #[test]
+        fn metadata_has_static_type_and_shape() {
+            let mut interpreter = get_interpreter();
+            let mut sink = std::io::sink();
+            let mut out = qsc_eval::output::GenericReceiver::new(&mut sink);
+            let mut metadata = |line: &str| {
+                let (_, metadata) = interpreter
+                    .eval_fragments_with_metadata(&mut out, line)
+                    .expect("line should evaluate");
+                (metadata.ty.to_string(), metadata.shape)
+            };
+
+            assert_eq!(
+                metadata("[1.0, 2.0, 3.0]"),
+                ("Double[]".to_string(), vec![3])
+            );
+            assert_eq!(
+                metadata("[[1, 2], [3, 4], [5, 6]]"),
+                ("Int[][]".to_string(), vec![3, 2])
+            );
+            assert_eq!(metadata("[[1], [2, 3]]"), ("Int[][]".to_string(), vec![2]));
+            assert_eq!(
+                metadata("let empty : Int[][] = [[], []]; empty"),
+                ("Int[][]".to_string(), vec![2, 0])
+            );
+            assert_eq!(metadata("(1, true)"), ("(Int, Bool)".to_string(), vec![]));
+            assert_eq!(metadata("let x = 1;"), ("Unit".to_string(), vec![]));
+        }
+
+        #[test]
+        fn warnings_are_returned_with_the_value() {
+            let mut interpreter = get_interpreter();
+            let mut sink = std::io::sink();
+            let mut out = qsc_eval::output::GenericReceiver::new(&mut sink);
+            let mut warnings = |line: &str| {
+                let (value, warnings) = interpreter
+                    .eval_fragments_with_warnings(&mut out, line)
+                    .expect("line should evaluate");
+                let warnings = warnings
+                    .iter()
+                    .map(|warning| warning.error().to_string())
+                    .collect::<Vec<_>>();
+                (value.to_string(), warnings)
+            };
+
+            assert_eq!(warnings("let x = 1;"), ("()".to_string(), vec![]));
+            assert_eq!(
+                warnings("function Unused() : Int { let y = 2; fail \"no\"; 0 }"),
+                (
+                    "()".to_string(),
+                    vec![
+                        "unused variable `y`".to_string(),
+                        "unreachable code".to_string()
+                    ]
+                )
+            );
+            assert_eq!(warnings("{ let z = x; z + 1 }"), ("2".to_string(), vec![]));
+        }
+
+        #[test]
+        fn callables_can_be_redefined() {
+            let mut interpreter = get_interpreter();
+            let mut sink = std::io::sink();
+            let mut out = qsc_eval::output::GenericReceiver::new(&mut sink);
+            let mut warnings = |line: &str| {
+                let (value, warnings) = interpreter
+                    .eval_fragments_with_warnings(&mut out, line)
+                    .expect("line should evaluate");
+                let warnings = warnings
+                    .iter()
+                    .map(|warning| warning.error().to_string())
+                    .collect::<Vec<_>>();
+                (value.to_string(), warnings)
+            };
+
+            warnings(indoc! {"
+                namespace Test {
+                    function Foo() : Int { 1 }
+                    function Bar() : Int { Foo() + 10 }
+                }
+            "});
+            assert_eq!(
+                warnings("namespace Test { function Foo() : Int { 2 } }"),
+                (
+                    "()".to_string(),
+                    vec!["`Bar` still uses the previous definition of `Foo`".to_string()]
+                )
+            );
+            assert_eq!(warnings("Test.Foo()"), ("2".to_string(), vec![]));
+            assert_eq!(warnings("Test.Bar()"), ("11".to_string(), vec![]));
+            warnings("namespace Test { function Bar() : Int { Foo() + 10 } }");
+            assert_eq!(warnings("Test.Bar()"), ("12".to_string(), vec![]));
+
+            warnings("function Baz() : Int { 3 }");
+            warnings("function Baz() : Int { 4 }");
+            assert_eq!(warnings("Baz()"), ("4".to_string(), vec![]));
+        }
     }
 
     fn get_interpreter() -> Interpreter {
@@ -1424,7 +3143,7 @@ mod given_interpreter {
         use std::{sync::Arc, vec};
 
         use super::*;
-        use crate::interpret::Debugger;
+        use crate::interpret::{Debugger, StepResult};
         use crate::line_column::Encoding;
         use expect_test::expect;
         use indoc::indoc;
@@ -1515,6 +3234,63 @@ mod given_interpreter {
             is_unit_with_output(&result, &output, "hello there...");
         }
 
+        #[test]
+        fn explicit_specializations_are_written_for_operation() {
+            let source = indoc! { r#"
+            namespace Test {
+                operation A(q : Qubit) : Unit is Adj + Ctl {
+                    X(q);
+                    H(q);
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let qsharp = interpreter
+                .explicit_specializations("Test.A")
+                .expect("specializations should be written");
+            assert!(qsharp.contains("adjoint (...)"), "{qsharp}");
+            assert!(qsharp.contains("controlled ("), "{qsharp}");
+        }
+
+        #[test]
+        fn explicit_specializations_for_non_callable_is_error() {
+            let mut interpreter = get_interpreter();
+            assert!(interpreter.explicit_specializations("1 + 2").is_err());
+        }
+
+        #[test]
+        fn extended_prelude_can_open_source_namespace() {
+            let source = indoc! { r#"
+            namespace Helpers {
+                function Answer() : Int { 42 }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            interpreter
+                .extend_prelude(&["Helpers"])
+                .expect("prelude should be extended");
+            let (result, output) = line(&mut interpreter, "Answer()");
+            is_only_value(&result, &output, &Value::Int(42));
+        }
+
         #[test]
         fn members_from_namespaced_sources_are_in_context() {
             let source = indoc! { r#"
@@ -1586,6 +3362,126 @@ mod given_interpreter {
             assert_eq!(2, bps.len());
         }
 
+        #[test]
+        fn debugger_pauses_at_breakpoint_and_steps() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Int {
+                    mutable x = 1;
+                    set x += 1;
+                    set x += 2;
+                    x
+                }
+            }"#};
+            let offset = |text: &str| {
+                u32::try_from(source.find(text).expect("text should be in source"))
+                    .expect("offset should fit in u32")
+            };
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            let breakpoint = debugger
+                .set_breakpoint("test", offset("x += 2"))
+                .expect("breakpoint should be set");
+            assert_eq!(breakpoint.range.start.line, 5);
+            assert_eq!(
+                debugger.set_breakpoint("test", offset("        x\n")),
+                debugger.set_breakpoint("test", offset("x\n    }"))
+            );
+            debugger.clear_breakpoints();
+            assert_eq!(
+                debugger.set_breakpoint("test", offset("set x += 2")),
+                Some(breakpoint)
+            );
+            assert_eq!(debugger.set_breakpoint("other", 0), None);
+
+            let mut out = std::io::sink();
+            let mut receiver = qsc_eval::output::GenericReceiver::new(&mut out);
+            let locals = |debugger: &Debugger| {
+                debugger
+                    .get_locals()
+                    .into_iter()
+                    .map(|local| format!("{} = {}", local.name, local.value))
+                    .collect::<Vec<_>>()
+            };
+            let result = debugger.resume(&mut receiver).expect("step should succeed");
+            assert!(
+                matches!(result, StepResult::BreakpointHit(id) if u32::from(id) == breakpoint.id),
+                "{result:?}"
+            );
+            assert_eq!(locals(&debugger), ["x = 2"]);
+
+            let result = debugger
+                .step_over(&mut receiver)
+                .expect("step should succeed");
+            assert!(matches!(result, StepResult::Next), "{result:?}");
+            assert_eq!(locals(&debugger), ["x = 4"]);
+
+            debugger.clear_breakpoints();
+            let result = debugger.resume(&mut receiver).expect("step should succeed");
+            assert!(
+                matches!(result, StepResult::Return(Value::Int(4))),
+                "{result:?}"
+            );
+        }
+
+        #[test]
+        fn debugger_gets_locals_of_each_frame() {
+            let source = indoc! { r#"
+            namespace Test {
+                function Inner(y : Int) : Int {
+                    let z = y * 2;
+                    z
+                }
+
+                @EntryPoint()
+                operation Main() : Int {
+                    let x = 1;
+                    Inner(x + 1)
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut debugger = Debugger::new(
+                sources,
+                TargetCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            let offset = u32::try_from(
+                source
+                    .find("        z\n")
+                    .expect("text should be in source"),
+            )
+            .expect("offset should fit in u32");
+            debugger
+                .set_breakpoint("test", offset)
+                .expect("breakpoint should be set");
+
+            let mut out = std::io::sink();
+            let mut receiver = qsc_eval::output::GenericReceiver::new(&mut out);
+            let result = debugger.resume(&mut receiver).expect("step should succeed");
+            assert!(matches!(result, StepResult::BreakpointHit(_)), "{result:?}");
+            let locals = |depth| {
+                debugger
+                    .get_locals_in_frame(depth)
+                    .into_iter()
+                    .map(|local| format!("{} = {}", local.name, local.value))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(debugger.get_stack_frames().len(), 2);
+            assert_eq!(locals(1), ["x = 1"]);
+            assert_eq!(locals(2), ["y = 2", "z = 4"]);
+            assert!(locals(3).is_empty());
+        }
+
         #[test]
         fn debugger_simple_execution_succeeds() {
             let source = indoc! { r#"
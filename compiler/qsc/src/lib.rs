@@ -1,17 +1,46 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod api;
+pub mod benchmark;
+pub mod cache;
+pub mod catalog;
+pub mod cells;
 pub mod codegen;
 pub mod compile;
+pub mod completion;
+pub mod conformance;
+pub mod diagnostic;
+pub mod doc;
 pub mod error;
+pub mod explain;
+pub mod golden;
+pub mod hover;
 pub mod incremental;
 pub mod interpret;
 pub mod location;
+pub mod mime;
+pub mod mitigation;
+pub mod packages;
+pub mod precompiled;
+pub mod qir_check;
+pub mod references;
+pub mod render;
+pub mod semantic_diff;
+pub mod shots;
+pub mod stable_id;
+pub mod symbols;
 pub mod target;
+mod telemetry;
+pub mod verify;
 
 pub use qsc_formatter::formatter;
 
-pub use qsc_frontend::compile::{CompileUnit, PackageStore, SourceContents, SourceMap, SourceName};
+pub use qsc_parse::trivia;
+
+pub use qsc_frontend::compile::{
+    CompileUnit, PackageStore, SourceContents, SourceMap, SourceName, StdFeatures,
+};
 
 pub mod resolve {
     pub use qsc_frontend::resolve::{path_as_field_accessor, Local, LocalKind, Locals, Res};
@@ -25,13 +54,18 @@ pub mod hir {
     pub use qsc_hir::{hir::*, *};
 }
 
+pub mod rir {
+    pub use qsc_rir::rir::*;
+}
+
 pub mod ast {
     pub use qsc_ast::{ast::*, *};
 }
 
 pub mod project {
     pub use qsc_project::{
-        DirEntry, EntryType, Error, FileSystem, Manifest, ManifestDescriptor, PackageCache,
+        Change, ChangeKind, DirEntry, EntryType, Error, FileSystem, Manifest, ManifestDescriptor,
+        MemoryFs, OverlayFs, PackageCache, PackageGraphSources, PackageInfo, SourceLoader, Watch,
     };
 }
 
@@ -45,11 +79,21 @@ pub mod line_column {
     pub use qsc_data_structures::line_column::{Encoding, Position, Range};
 }
 
+#[cfg(not(any(target_family = "wasm")))]
+pub use qsc_eval::backend::{MappedSim, Precision};
+
 pub use qsc_eval::{
-    backend::{Backend, SparseSim},
-    state::{fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase},
+    backend::{Backend, InvalidNoise, Noise, NoiseModel, NoisySim, SparseSim, StabilizerSim},
+    state::{
+        fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase, state_fidelity,
+        QuantumState,
+    },
 };
 
+pub mod decompile {
+    pub use qsc_codegen::decompile::{write_callable_specs, write_item, write_package};
+}
+
 pub mod linter {
     pub use qsc_linter::{run_lints, LintConfig, LintKind, LintLevel};
 }
@@ -57,5 +101,5 @@ pub mod linter {
 pub use qsc_doc_gen::{display, generate_docs};
 
 pub mod circuit {
-    pub use qsc_circuit::{operations::*, Circuit, Operation};
+    pub use qsc_circuit::{operations::*, Circuit, CircuitBuilder, Operation};
 }
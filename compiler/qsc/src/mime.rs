@@ -0,0 +1,221 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A display protocol for rich frontends, such as Jupyter. Displayable objects, like result
+//! values, state dumps and circuits, render into a bundle of representations keyed by MIME type,
+//! and the host chooses which of the representations it accepts and shows.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{interpret::Value, render};
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_circuit::Circuit;
+use qsc_eval::state::{fmt_basis_state_label, get_phase};
+use serde_json::json;
+
+/// The length above which the text of an array or tuple is rendered as a table instead of a
+/// single line, which notebooks would wrap unreadably.
+const MAX_LINE_LENGTH: usize = 100;
+
+/// The number of rows shown in tables of results.
+const MAX_TABLE_ROWS: usize = 20;
+
+/// A MIME type that objects can be rendered as.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MimeType {
+    /// `text/plain`, which every object supports.
+    Text,
+    /// `text/html`, as a fragment that is safe to embed in a page.
+    Html,
+    /// `application/json`.
+    Json,
+    /// `image/svg+xml`, as a standalone image.
+    Svg,
+}
+
+impl MimeType {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text/plain",
+            Self::Html => "text/html",
+            Self::Json => "application/json",
+            Self::Svg => "image/svg+xml",
+        }
+    }
+
+    /// Parses a MIME type, returning `None` for types that are not supported.
+    #[must_use]
+    pub fn parse(mime_type: &str) -> Option<Self> {
+        match mime_type {
+            "text/plain" => Some(Self::Text),
+            "text/html" => Some(Self::Html),
+            "application/json" => Some(Self::Json),
+            "image/svg+xml" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// The representations of an object, in the order the host listed the MIME types it accepts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MimeBundle {
+    representations: Vec<(MimeType, String)>,
+}
+
+impl MimeBundle {
+    /// The representation for the MIME type, if the bundle has one.
+    #[must_use]
+    pub fn get(&self, mime_type: MimeType) -> Option<&str> {
+        self.representations
+            .iter()
+            .find(|(other, _)| *other == mime_type)
+            .map(|(_, data)| data.as_str())
+    }
+
+    /// The representations in the bundle.
+    pub fn iter(&self) -> impl Iterator<Item = (MimeType, &str)> {
+        self.representations
+            .iter()
+            .map(|(mime_type, data)| (*mime_type, data.as_str()))
+    }
+
+    /// Converts the bundle into a JSON object keyed by MIME type, the shape that Jupyter expects
+    /// for display data. The `application/json` representation is embedded as JSON rather than
+    /// as a string.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut object = serde_json::Map::new();
+        for (mime_type, data) in &self.representations {
+            let value = match mime_type {
+                MimeType::Json => {
+                    serde_json::from_str(data).expect("JSON representation should be valid JSON")
+                }
+                _ => serde_json::Value::String(data.clone()),
+            };
+            object.insert(mime_type.as_str().to_string(), value);
+        }
+        serde_json::Value::Object(object)
+    }
+}
+
+/// An object that can be rendered into several MIME representations.
+pub trait MimeRender {
+    /// The MIME types the object can be rendered as, including [`MimeType::Text`].
+    fn mime_types(&self) -> &'static [MimeType];
+
+    /// Renders the object as the MIME type, or returns `None` if the type is not supported.
+    fn render(&self, mime_type: MimeType) -> Option<String>;
+
+    /// Renders the object as each of the accepted MIME types that it supports. The bundle always
+    /// has a `text/plain` representation, so that every host can show something, even if the
+    /// host did not list it.
+    fn mime_bundle(&self, accepted: &[MimeType]) -> MimeBundle {
+        let mut representations = Vec::new();
+        for &mime_type in accepted.iter().chain([MimeType::Text].iter()) {
+            if representations.iter().any(|(other, _)| *other == mime_type) {
+                continue;
+            }
+            if let Some(data) = self.render(mime_type) {
+                representations.push((mime_type, data));
+            }
+        }
+        MimeBundle { representations }
+    }
+}
+
+impl MimeRender for Value {
+    fn mime_types(&self) -> &'static [MimeType] {
+        &[MimeType::Text, MimeType::Json]
+    }
+
+    fn render(&self, mime_type: MimeType) -> Option<String> {
+        match mime_type {
+            MimeType::Text => {
+                let text = self.to_string();
+                if text.len() > MAX_LINE_LENGTH {
+                    Some(render::value_to_table(self, MAX_TABLE_ROWS).unwrap_or(text))
+                } else {
+                    Some(text)
+                }
+            }
+            MimeType::Json => Some(value_to_json(self).to_string()),
+            MimeType::Html | MimeType::Svg => None,
+        }
+    }
+}
+
+/// Converts a value to JSON. Arrays and tuples become JSON arrays, and numbers and Booleans
+/// become JSON numbers and Booleans. Values that JSON cannot represent exactly, like big
+/// integers and non-finite doubles, and values without a JSON counterpart, like results and
+/// Paulis, become strings with their `Display` form.
+pub(crate) fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Array(items) => items.iter().map(value_to_json).collect(),
+        Value::Tuple(items) => items.iter().map(value_to_json).collect(),
+        Value::Bool(value) => json!(value),
+        Value::Int(value) => json!(value),
+        Value::Double(value) if value.is_finite() => json!(value),
+        Value::String(value) => json!(value.as_ref()),
+        _ => json!(value.to_string()),
+    }
+}
+
+/// A dump of the quantum state, such as one produced by `DumpMachine`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateDump {
+    /// The amplitude of each basis state with a nonzero amplitude.
+    pub state: Vec<(BigUint, Complex64)>,
+    pub qubit_count: usize,
+}
+
+impl MimeRender for StateDump {
+    fn mime_types(&self) -> &'static [MimeType] {
+        &[MimeType::Text, MimeType::Html, MimeType::Json]
+    }
+
+    fn render(&self, mime_type: MimeType) -> Option<String> {
+        match mime_type {
+            MimeType::Text => Some(render::state_to_text(&self.state, self.qubit_count)),
+            MimeType::Html => Some(render::state_to_html(&self.state, self.qubit_count)),
+            MimeType::Json => {
+                let amplitudes = self
+                    .state
+                    .iter()
+                    .map(|(id, amplitude)| {
+                        json!({
+                            "basis": fmt_basis_state_label(id, self.qubit_count),
+                            "real": amplitude.re,
+                            "imag": amplitude.im,
+                            "probability": amplitude.norm_sqr(),
+                            "phase": get_phase(amplitude),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Some(
+                    json!({ "qubitCount": self.qubit_count, "amplitudes": amplitudes }).to_string(),
+                )
+            }
+            MimeType::Svg => None,
+        }
+    }
+}
+
+impl MimeRender for Circuit {
+    fn mime_types(&self) -> &'static [MimeType] {
+        &[MimeType::Text, MimeType::Json, MimeType::Svg]
+    }
+
+    fn render(&self, mime_type: MimeType) -> Option<String> {
+        match mime_type {
+            MimeType::Text => Some(self.to_string()),
+            MimeType::Json => {
+                Some(serde_json::to_string(self).expect("circuit should serialize to JSON"))
+            }
+            MimeType::Svg => Some(render::circuit_to_svg(self)),
+            MimeType::Html => None,
+        }
+    }
+}
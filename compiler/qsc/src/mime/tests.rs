@@ -0,0 +1,95 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{MimeRender, MimeType, StateDump};
+use crate::interpret::Value;
+use expect_test::expect;
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use std::rc::Rc;
+
+fn sample() -> Value {
+    Value::Tuple(
+        vec![
+            Value::Int(1),
+            Value::Double(0.5),
+            Value::Array(Rc::new(vec![Value::Bool(true), Value::RESULT_ONE])),
+            Value::String("s".into()),
+            Value::Double(f64::INFINITY),
+        ]
+        .into(),
+    )
+}
+
+#[test]
+fn mime_types_round_trip() {
+    for mime_type in [
+        MimeType::Text,
+        MimeType::Html,
+        MimeType::Json,
+        MimeType::Svg,
+    ] {
+        assert_eq!(MimeType::parse(mime_type.as_str()), Some(mime_type));
+    }
+    assert_eq!(MimeType::parse("image/png"), None);
+}
+
+#[test]
+fn value_renders_as_text_and_json() {
+    let bundle = sample().mime_bundle(&[MimeType::Json, MimeType::Svg]);
+    assert_eq!(
+        bundle
+            .iter()
+            .map(|(mime_type, _)| mime_type)
+            .collect::<Vec<_>>(),
+        [MimeType::Json, MimeType::Text]
+    );
+    expect![[r#"(1, 0.5, [true, One], s, inf)"#]]
+        .assert_eq(bundle.get(MimeType::Text).expect("bundle should have text"));
+    expect![[r#"[1,0.5,[true,"One"],"s","inf"]"#]]
+        .assert_eq(bundle.get(MimeType::Json).expect("bundle should have JSON"));
+}
+
+#[test]
+fn bundle_always_has_text() {
+    let bundle = sample().mime_bundle(&[]);
+    assert_eq!(bundle.iter().count(), 1);
+    assert!(bundle.get(MimeType::Text).is_some());
+}
+
+#[test]
+fn bundle_converts_to_jupyter_json() {
+    let bundle = Value::Int(3).mime_bundle(&[MimeType::Text, MimeType::Json]);
+    expect![[r#"{"application/json":3,"text/plain":"3"}"#]]
+        .assert_eq(&bundle.to_json().to_string());
+}
+
+#[test]
+fn state_dump_renders_as_text_html_and_json() {
+    let dump = StateDump {
+        state: vec![(BigUint::from(0_u64), Complex64::new(1.0, 0.0))],
+        qubit_count: 2,
+    };
+    let bundle = dump.mime_bundle(dump.mime_types());
+    expect![[r#"STATE:
+|00⟩: 1.0000+0.0000𝑖 (100.0000%, phase 0.0000)"#]]
+    .assert_eq(bundle.get(MimeType::Text).expect("bundle should have text"));
+    assert!(bundle
+        .get(MimeType::Html)
+        .is_some_and(|html| html.starts_with("<table>")));
+    expect![[r#"{"amplitudes":[{"basis":"00","imag":0.0,"phase":0.0,"probability":1.0,"real":1.0}],"qubitCount":2}"#]].assert_eq(bundle.get(MimeType::Json).expect("bundle should have JSON"));
+}
+
+#[test]
+fn large_value_renders_as_table() {
+    let value = Value::Array(Rc::new(
+        (0..100)
+            .map(|i| Value::Tuple(vec![Value::Int(i), Value::Bool(i % 2 == 0)].into()))
+            .collect(),
+    ));
+    let text = value
+        .render(MimeType::Text)
+        .expect("value should render as text");
+    assert_eq!(text.lines().next(), Some(" #   0  1"), "{text}");
+    assert!(text.contains("... 80 more rows"), "{text}");
+}
@@ -0,0 +1,666 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Readout-error mitigation. The readout errors of a set of qubits are characterized as a
+//! confusion matrix, the probability of measuring each bitstring given the one that was
+//! prepared, from calibration circuits that prepare basis states and measure them right away.
+//! The calibration circuits can be run on a noisy simulator with [`calibrate`], or on hardware
+//! with the counts passed to [`ConfusionMatrix::from_calibration`].
+//!
+//! The matrix is then used to correct the histograms of other programs measuring the same
+//! qubits, see [`ConfusionMatrix::mitigate`]. Bitstrings have a `0` or `1` for each qubit, in the
+//! order the qubits were measured, as made from measured values by [`bitstring`].
+//!
+//! Gate errors are mitigated by zero-noise extrapolation: a program is run with its noise scaled
+//! up by folding its gates, and the expectation values measured at each scale are extrapolated
+//! back to the limit of no noise, see [`extrapolate_zero_noise`].
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use qsc_eval::val::{self, Value};
+use thiserror::Error;
+
+use crate::interpret::{
+    self,
+    output::{GenericReceiver, Receiver},
+    Interpreter,
+};
+
+/// The most qubits that readout errors can be mitigated for, since the confusion matrix has an
+/// entry for every pair of the 2ⁿ bitstrings.
+pub const MAX_QUBITS: usize = 8;
+
+/// The number of times each bitstring was measured.
+pub type Counts = BTreeMap<String, usize>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("readout errors can be mitigated for at most {MAX_QUBITS} qubits, not {0}")]
+    TooManyQubits(usize),
+    #[error("`{0}` is not a bitstring of the measured qubits")]
+    InvalidBitstring(String),
+    #[error("there are no calibration shots for the prepared bitstring `{0}`")]
+    MissingCalibration(String),
+    #[error("there are no shots to mitigate")]
+    NoShots,
+    #[error("the confusion matrix cannot be inverted")]
+    Singular,
+    #[error("noise scale {0} is not an odd number of applications of each gate")]
+    InvalidScale(usize),
+    #[error("zero-noise extrapolation needs at least two different noise scales")]
+    TooFewScales,
+    #[error("a circuit failed to run")]
+    Run(Vec<interpret::Error>),
+}
+
+/// How the readout errors of the qubits are characterized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// The readout of each qubit is characterized on its own, from two calibration circuits that
+    /// prepare every qubit in |0〉 and then in |1〉. This assumes that readout errors are not
+    /// correlated between qubits.
+    Tensored,
+    /// The readout of the qubits is characterized together, from a calibration circuit for each
+    /// of the 2ⁿ basis states, which also captures correlated errors such as crosstalk.
+    Full,
+}
+
+/// How a histogram is corrected with the confusion matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    /// Applies the inverse of the confusion matrix to the measured distribution. The result is
+    /// unbiased, but shot noise can make some of its probabilities negative.
+    Inversion,
+    /// Finds the distribution that the confusion matrix maps closest to the measured one, in the
+    /// least-squares sense, among the distributions without negative probabilities.
+    LeastSquares,
+}
+
+/// How the expectation values measured at scaled noise are extrapolated to zero noise, taking
+/// each value to be a function of the noise scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// Fits a line to the values by least squares. This is the least sensitive to shot noise,
+    /// but it is biased when the values curve with the noise.
+    Linear,
+    /// Fits the polynomial that goes through every value, which cancels the effect of the noise
+    /// to a higher order with more scales, but also amplifies shot noise more.
+    Richardson,
+}
+
+impl Extrapolation {
+    /// Extrapolates the values measured at each noise scale, such as on hardware, to zero noise.
+    /// # Errors
+    /// Returns an error if there are fewer than two different scales.
+    pub fn extrapolate(self, scaled: &[(usize, f64)]) -> Result<f64, Error> {
+        let mut scaled = scaled.to_vec();
+        scaled.sort_by_key(|&(scale, _)| scale);
+        scaled.dedup_by_key(|&mut (scale, _)| scale);
+        let points = scaled
+            .into_iter()
+            .map(|(scale, value)| (scale_to_f64(scale), value))
+            .collect::<Vec<_>>();
+        if points.len() < 2 {
+            return Err(Error::TooFewScales);
+        }
+
+        Ok(match self {
+            Extrapolation::Linear => {
+                let (scales, values): (Vec<_>, Vec<_>) = points.iter().copied().unzip();
+                let mean_scale = mean(&scales);
+                let mean_value = mean(&values);
+                let (covariance, variance) =
+                    points
+                        .iter()
+                        .fold((0.0, 0.0), |(covariance, variance), (scale, value)| {
+                            let scale = scale - mean_scale;
+                            (
+                                covariance + scale * (value - mean_value),
+                                variance + scale * scale,
+                            )
+                        });
+                mean_value - covariance / variance * mean_scale
+            }
+            // The Lagrange form of the interpolating polynomial, evaluated at zero.
+            Extrapolation::Richardson => points
+                .iter()
+                .enumerate()
+                .map(|(i, (scale, value))| {
+                    points
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(_, (other, _))| other / (other - scale))
+                        .product::<f64>()
+                        * value
+                })
+                .sum(),
+        })
+    }
+}
+
+/// An expectation value extrapolated to zero noise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZeroNoiseEstimate {
+    /// The extrapolated value.
+    pub value: f64,
+    /// The value measured at each noise scale, from the smallest scale to the largest.
+    pub scaled: Vec<(usize, f64)>,
+}
+
+/// A circuit that prepares a basis state and measures it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalibrationCircuit {
+    /// The bitstring of the prepared basis state.
+    pub prepared: String,
+    /// The Q# expression that prepares the state and returns the measured `Result[]`.
+    pub expr: String,
+}
+
+/// A mitigated probability with its standard error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub probability: f64,
+    /// The standard error from the shot noise of both the measured histogram and the calibration,
+    /// propagated to first order.
+    pub std_error: f64,
+}
+
+/// A mitigated distribution over the bitstrings of the measured qubits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mitigated {
+    /// The number of shots of the histogram that was mitigated.
+    pub shots: usize,
+    /// The estimate of every bitstring.
+    pub distribution: BTreeMap<String, Estimate>,
+}
+
+/// The bitstring of a measured `Result` or `Result[]`, or `None` for other values.
+#[must_use]
+pub fn bitstring(value: &Value) -> Option<String> {
+    match value {
+        Value::Result(val::Result::Val(one)) => Some(if *one { "1" } else { "0" }.to_string()),
+        Value::Array(items) => items.iter().map(bitstring).collect(),
+        _ => None,
+    }
+}
+
+/// The calibration circuits for the model of the readout of `qubits` qubits.
+/// # Errors
+/// Returns an error if there are more than [`MAX_QUBITS`] qubits.
+pub fn calibration_circuits(qubits: usize, model: Model) -> Result<Vec<CalibrationCircuit>, Error> {
+    let prepared = match model {
+        Model::Tensored => vec![0, (1 << check_qubits(qubits)?) - 1],
+        Model::Full => (0..1 << check_qubits(qubits)?).collect(),
+    };
+    Ok(prepared
+        .into_iter()
+        .map(|index| {
+            let mut expr = format!("{{ use qs = Qubit[{qubits}]; ");
+            for q in (0..qubits).filter(|q| index & (1 << q) != 0) {
+                let _ = write!(expr, "X(qs[{q}]); ");
+            }
+            expr.push_str("MResetEachZ(qs) }");
+            CalibrationCircuit {
+                prepared: to_bitstring(index, qubits),
+                expr,
+            }
+        })
+        .collect())
+}
+
+/// Characterizes the readout of `qubits` qubits by running each calibration circuit for the
+/// model `shots` times on the interpreter, such as one created with
+/// [`Interpreter::new_with_noise`].
+/// # Errors
+/// Returns an error if there are too many qubits or a calibration circuit fails to run.
+pub fn calibrate(
+    interpreter: &mut Interpreter,
+    qubits: usize,
+    model: Model,
+    shots: usize,
+) -> Result<ConfusionMatrix, Error> {
+    let mut out = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut out);
+    let mut results = BTreeMap::new();
+    for circuit in calibration_circuits(qubits, model)? {
+        let mut counts = Counts::new();
+        for result in interpreter
+            .run_shots(&mut receiver, &circuit.expr, shots)
+            .map_err(Error::Run)?
+        {
+            // Calibration circuits always return a `Result[]`.
+            if let Some(bits) = bitstring(&result.map_err(Error::Run)?) {
+                *counts.entry(bits).or_default() += 1;
+            }
+        }
+        results.insert(circuit.prepared, counts);
+    }
+    ConfusionMatrix::from_calibration(qubits, model, &results)
+}
+
+/// Mitigates gate errors by zero-noise extrapolation. The entry expression is run `shots` times
+/// at each noise scale, with every gate applied that many times by folding it, see
+/// [`Interpreter::set_gate_folds`], and the shots of each scale are reduced by the reducers
+/// registered on the interpreter like [`Interpreter::reduce_shots`]. Every reduction that is a
+/// `Double`, such as the expectation value of a [`interpret::Parity`] reducer, is then
+/// extrapolated to zero noise. This only mitigates noise that is applied with the gates, such as
+/// by an interpreter created with [`Interpreter::new_with_noise`].
+///
+/// Returns the estimates by the names of their reducers.
+/// # Errors
+/// Returns an error if a scale is not odd, there are fewer than two different scales, or the
+/// expression fails to compile.
+pub fn extrapolate_zero_noise(
+    interpreter: &mut Interpreter,
+    receiver: &mut impl Receiver,
+    expr: &str,
+    shots: usize,
+    scales: &[usize],
+    extrapolation: Extrapolation,
+) -> Result<BTreeMap<String, ZeroNoiseEstimate>, Error> {
+    let mut scales = scales.to_vec();
+    scales.sort_unstable();
+    scales.dedup();
+    if let Some(&scale) = scales.iter().find(|&&scale| scale % 2 == 0) {
+        return Err(Error::InvalidScale(scale));
+    }
+    if scales.len() < 2 {
+        return Err(Error::TooFewScales);
+    }
+
+    let folds = interpreter.gate_folds();
+    let mut scaled = BTreeMap::<String, Vec<(usize, f64)>>::new();
+    for &scale in &scales {
+        interpreter.set_gate_folds(scale / 2);
+        let reduced = interpreter.reduce_shots(receiver, expr, shots);
+        interpreter.set_gate_folds(folds);
+        for (name, value) in reduced.map_err(Error::Run)?.values {
+            if let Value::Double(value) = value {
+                scaled.entry(name).or_default().push((scale, value));
+            }
+        }
+    }
+
+    scaled
+        .into_iter()
+        .map(|(name, scaled)| {
+            let value = extrapolation.extrapolate(&scaled)?;
+            Ok((name, ZeroNoiseEstimate { value, scaled }))
+        })
+        .collect()
+}
+
+/// The probability of measuring each bitstring of some qubits given the bitstring that was
+/// prepared.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfusionMatrix {
+    qubits: usize,
+    /// The probabilities, with a row for each measured bitstring and a column for each prepared
+    /// one, in row-major order.
+    probabilities: Vec<f64>,
+    /// The number of calibration shots that each column was estimated from.
+    shots: Vec<usize>,
+}
+
+impl ConfusionMatrix {
+    /// Builds the confusion matrix for the model from the counts measured by each calibration
+    /// circuit, keyed by the bitstring it prepared. For the tensored model, the statistical
+    /// error of each column is estimated as if it came from the smaller of the two calibrations.
+    /// # Errors
+    /// Returns an error if there are too many qubits, a bitstring is not one of the qubits, or
+    /// there are no counts for a calibration circuit of the model.
+    pub fn from_calibration(
+        qubits: usize,
+        model: Model,
+        results: &BTreeMap<String, Counts>,
+    ) -> Result<Self, Error> {
+        let dim = 1 << check_qubits(qubits)?;
+        let column = |prepared: usize| -> Result<(Vec<f64>, usize), Error> {
+            let bits = to_bitstring(prepared, qubits);
+            let counts = results
+                .get(&bits)
+                .ok_or_else(|| Error::MissingCalibration(bits.clone()))?;
+            let (frequencies, shots) = frequencies(counts, qubits)?;
+            if shots == 0 {
+                return Err(Error::MissingCalibration(bits));
+            }
+            Ok((frequencies, shots))
+        };
+
+        let mut probabilities = vec![0.0; dim * dim];
+        let mut shots = vec![0; dim];
+        match model {
+            Model::Full => {
+                for prepared in 0..dim {
+                    let (frequencies, count) = column(prepared)?;
+                    for (measured, frequency) in frequencies.into_iter().enumerate() {
+                        probabilities[measured * dim + prepared] = frequency;
+                    }
+                    shots[prepared] = count;
+                }
+            }
+            Model::Tensored => {
+                let (zeros, zero_shots) = column(0)?;
+                let (ones, one_shots) = column(dim - 1)?;
+                // The probability that each qubit reads one, when prepared in |0〉 and in |1〉.
+                let flip = |frequencies: &[f64], q: usize| -> f64 {
+                    (0..dim)
+                        .filter(|index| index & (1 << q) != 0)
+                        .map(|index| frequencies[index])
+                        .sum()
+                };
+                let reads_one = (0..qubits)
+                    .map(|q| [flip(&zeros, q), flip(&ones, q)])
+                    .collect::<Vec<_>>();
+                for measured in 0..dim {
+                    for prepared in 0..dim {
+                        probabilities[measured * dim + prepared] = reads_one
+                            .iter()
+                            .enumerate()
+                            .map(|(q, reads_one)| {
+                                let one = reads_one[usize::from(prepared & (1 << q) != 0)];
+                                if measured & (1 << q) == 0 {
+                                    1.0 - one
+                                } else {
+                                    one
+                                }
+                            })
+                            .product();
+                    }
+                }
+                shots.fill(zero_shots.min(one_shots));
+            }
+        }
+
+        Ok(Self {
+            qubits,
+            probabilities,
+            shots,
+        })
+    }
+
+    #[must_use]
+    pub fn qubits(&self) -> usize {
+        self.qubits
+    }
+
+    /// The probability of measuring one bitstring when the other was prepared, or `None` if
+    /// either is not a bitstring of the qubits.
+    #[must_use]
+    pub fn probability(&self, measured: &str, prepared: &str) -> Option<f64> {
+        let measured = to_index(measured, self.qubits)?;
+        let prepared = to_index(prepared, self.qubits)?;
+        Some(self.probabilities[measured * self.dim() + prepared])
+    }
+
+    /// Corrects the histogram of a program measuring the qubits for their readout errors.
+    /// # Errors
+    /// Returns an error if a bitstring is not one of the qubits, there are no shots, or the
+    /// confusion matrix cannot be inverted.
+    pub fn mitigate(&self, counts: &Counts, method: Method) -> Result<Mitigated, Error> {
+        let dim = self.dim();
+        let (measured, shots) = frequencies(counts, self.qubits)?;
+        if shots == 0 {
+            return Err(Error::NoShots);
+        }
+        let inverse = invert(&self.probabilities, dim).ok_or(Error::Singular)?;
+        let unbiased = mul_vec(&inverse, &measured, dim);
+        let probabilities = match method {
+            Method::Inversion => unbiased,
+            Method::LeastSquares => self.least_squares(&measured, project_to_simplex(&unbiased)),
+        };
+
+        // The covariance of the measured frequencies, plus that of each calibrated column
+        // weighted by the square of the probability of its prepared bitstring.
+        let mut covariance = multinomial_covariance(&measured, shots);
+        for (prepared, &probability) in probabilities.iter().enumerate() {
+            let column = (0..dim)
+                .map(|measured| self.probabilities[measured * dim + prepared])
+                .collect::<Vec<_>>();
+            let column_covariance = multinomial_covariance(&column, self.shots[prepared]);
+            for (total, entry) in covariance.iter_mut().zip(column_covariance) {
+                *total += probability * probability * entry;
+            }
+        }
+        let covariance = mul(
+            &mul(&inverse, &covariance, dim),
+            &transpose(&inverse, dim),
+            dim,
+        );
+
+        Ok(Mitigated {
+            shots,
+            distribution: probabilities
+                .into_iter()
+                .enumerate()
+                .map(|(index, probability)| {
+                    let variance = covariance[index * dim + index].max(0.0);
+                    (
+                        to_bitstring(index, self.qubits),
+                        Estimate {
+                            probability,
+                            std_error: variance.sqrt(),
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn dim(&self) -> usize {
+        1 << self.qubits
+    }
+
+    /// Minimizes ‖Ap - m‖² over the distributions p by projected gradient descent from `start`.
+    fn least_squares(&self, measured: &[f64], start: Vec<f64>) -> Vec<f64> {
+        const ITERATIONS: usize = 10_000;
+        const TOLERANCE: f64 = 1e-14;
+
+        let dim = self.dim();
+        let matrix = &self.probabilities;
+        let transposed = transpose(matrix, dim);
+        // The gradient is 2Aᵀ(Ap - m), which is Lipschitz with a constant of at most 2‖A‖₁‖A‖∞.
+        let norm_1 = (0..dim)
+            .map(|col| {
+                (0..dim)
+                    .map(|row| matrix[row * dim + col].abs())
+                    .sum::<f64>()
+            })
+            .fold(0.0, f64::max);
+        let norm_inf = matrix
+            .chunks(dim)
+            .map(|row| row.iter().map(|entry| entry.abs()).sum::<f64>())
+            .fold(0.0, f64::max);
+        let step = 1.0 / (2.0 * norm_1 * norm_inf);
+
+        let mut p = start;
+        for _ in 0..ITERATIONS {
+            let residual = mul_vec(matrix, &p, dim)
+                .into_iter()
+                .zip(measured)
+                .map(|(predicted, measured)| predicted - measured)
+                .collect::<Vec<_>>();
+            let gradient = mul_vec(&transposed, &residual, dim);
+            let next = project_to_simplex(
+                &p.iter()
+                    .zip(&gradient)
+                    .map(|(p, gradient)| p - step * 2.0 * gradient)
+                    .collect::<Vec<_>>(),
+            );
+            let change = next
+                .iter()
+                .zip(&p)
+                .map(|(next, p)| (next - p).abs())
+                .fold(0.0, f64::max);
+            p = next;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        p
+    }
+}
+
+fn check_qubits(qubits: usize) -> Result<usize, Error> {
+    if qubits > MAX_QUBITS {
+        Err(Error::TooManyQubits(qubits))
+    } else {
+        Ok(qubits)
+    }
+}
+
+/// The index of a bitstring, whose first character is the lowest bit.
+fn to_index(bits: &str, qubits: usize) -> Option<usize> {
+    if bits.len() != qubits {
+        return None;
+    }
+    bits.bytes()
+        .enumerate()
+        .try_fold(0, |index, (q, bit)| match bit {
+            b'0' => Some(index),
+            b'1' => Some(index | (1 << q)),
+            _ => None,
+        })
+}
+
+fn to_bitstring(index: usize, qubits: usize) -> String {
+    (0..qubits)
+        .map(|q| if index & (1 << q) == 0 { '0' } else { '1' })
+        .collect()
+}
+
+/// The frequency of every bitstring of the qubits, by index, and the number of shots.
+fn frequencies(counts: &Counts, qubits: usize) -> Result<(Vec<f64>, usize), Error> {
+    let mut frequencies = vec![0.0; 1 << qubits];
+    let shots = counts.values().sum::<usize>();
+    for (bits, &count) in counts {
+        let index = to_index(bits, qubits).ok_or_else(|| Error::InvalidBitstring(bits.clone()))?;
+        frequencies[index] = ratio(count, shots);
+    }
+    Ok((frequencies, shots))
+}
+
+#[allow(clippy::cast_precision_loss)] // Shot counts are far below 2^52.
+fn ratio(count: usize, shots: usize) -> f64 {
+    count as f64 / shots as f64
+}
+
+#[allow(clippy::cast_precision_loss)] // Noise scales are far below 2^52.
+fn scale_to_f64(scale: usize) -> f64 {
+    scale as f64
+}
+
+#[allow(clippy::cast_precision_loss)] // There are far fewer than 2^52 noise scales.
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// The covariance of the frequencies of a multinomial distribution estimated from `shots`
+/// samples, (diag(p) - ppᵀ) / shots.
+fn multinomial_covariance(frequencies: &[f64], shots: usize) -> Vec<f64> {
+    let dim = frequencies.len();
+    let scale = ratio(1, shots);
+    let mut covariance = vec![0.0; dim * dim];
+    for (row, p_row) in frequencies.iter().enumerate() {
+        for (col, p_col) in frequencies.iter().enumerate() {
+            let diagonal = if row == col { *p_row } else { 0.0 };
+            covariance[row * dim + col] = (diagonal - p_row * p_col) * scale;
+        }
+    }
+    covariance
+}
+
+/// Inverts a square matrix by Gauss-Jordan elimination with partial pivoting, or returns `None`
+/// if it is singular.
+fn invert(matrix: &[f64], dim: usize) -> Option<Vec<f64>> {
+    const EPSILON: f64 = 1e-12;
+
+    let mut left = matrix.to_vec();
+    let mut right = vec![0.0; dim * dim];
+    for i in 0..dim {
+        right[i * dim + i] = 1.0;
+    }
+    for col in 0..dim {
+        let pivot = (col..dim).max_by(|&a, &b| {
+            left[a * dim + col]
+                .abs()
+                .total_cmp(&left[b * dim + col].abs())
+        })?;
+        if left[pivot * dim + col].abs() < EPSILON {
+            return None;
+        }
+        for k in 0..dim {
+            left.swap(col * dim + k, pivot * dim + k);
+            right.swap(col * dim + k, pivot * dim + k);
+        }
+        let scale = left[col * dim + col];
+        for k in 0..dim {
+            left[col * dim + k] /= scale;
+            right[col * dim + k] /= scale;
+        }
+        for row in (0..dim).filter(|&row| row != col) {
+            let factor = left[row * dim + col];
+            if factor != 0.0 {
+                for k in 0..dim {
+                    left[row * dim + k] -= factor * left[col * dim + k];
+                    right[row * dim + k] -= factor * right[col * dim + k];
+                }
+            }
+        }
+    }
+    Some(right)
+}
+
+fn mul(a: &[f64], b: &[f64], dim: usize) -> Vec<f64> {
+    let mut product = vec![0.0; dim * dim];
+    for row in 0..dim {
+        for k in 0..dim {
+            let a = a[row * dim + k];
+            for col in 0..dim {
+                product[row * dim + col] += a * b[k * dim + col];
+            }
+        }
+    }
+    product
+}
+
+fn mul_vec(matrix: &[f64], vector: &[f64], dim: usize) -> Vec<f64> {
+    matrix
+        .chunks(dim)
+        .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn transpose(matrix: &[f64], dim: usize) -> Vec<f64> {
+    let mut transposed = vec![0.0; dim * dim];
+    for row in 0..dim {
+        for col in 0..dim {
+            transposed[col * dim + row] = matrix[row * dim + col];
+        }
+    }
+    transposed
+}
+
+/// The closest distribution to the vector in Euclidean distance, which subtracts the same amount
+/// from every entry and clips the entries that become negative.
+fn project_to_simplex(vector: &[f64]) -> Vec<f64> {
+    let mut sorted = vector.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let mut sum = 0.0;
+    let mut shift = 0.0;
+    for (count, value) in (1..).zip(&sorted) {
+        sum += value;
+        let candidate = (sum - 1.0) / f64::from(count);
+        if value - candidate > 0.0 {
+            shift = candidate;
+        }
+    }
+    vector
+        .iter()
+        .map(|value| (value - shift).max(0.0))
+        .collect()
+}
@@ -0,0 +1,346 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    bitstring, calibrate, calibration_circuits, extrapolate_zero_noise, ConfusionMatrix, Counts,
+    Error, Extrapolation, Method, Model, MAX_QUBITS,
+};
+use crate::interpret::{output::CursorReceiver, Interpreter, Parity};
+use expect_test::expect;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::{
+    backend::{Noise, NoiseModel},
+    val::Value,
+};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::collections::BTreeMap;
+
+fn counts(entries: &[(&str, usize)]) -> Counts {
+    entries
+        .iter()
+        .map(|&(bits, count)| (bits.to_string(), count))
+        .collect()
+}
+
+/// A qubit that reads |0〉 correctly 90% of the time and |1〉 80% of the time.
+fn one_qubit() -> ConfusionMatrix {
+    let results = BTreeMap::from([
+        ("0".to_string(), counts(&[("0", 90), ("1", 10)])),
+        ("1".to_string(), counts(&[("0", 20), ("1", 80)])),
+    ]);
+    ConfusionMatrix::from_calibration(1, Model::Full, &results)
+        .expect("calibration should be valid")
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+#[test]
+fn calibration_circuits_prepare_basis_states() {
+    let circuits = calibration_circuits(2, Model::Full).expect("two qubits should be supported");
+    expect![[r#"
+        00: { use qs = Qubit[2]; MResetEachZ(qs) }
+        10: { use qs = Qubit[2]; X(qs[0]); MResetEachZ(qs) }
+        01: { use qs = Qubit[2]; X(qs[1]); MResetEachZ(qs) }
+        11: { use qs = Qubit[2]; X(qs[0]); X(qs[1]); MResetEachZ(qs) }
+    "#]]
+    .assert_eq(
+        &circuits
+            .iter()
+            .map(|circuit| format!("{}: {}\n", circuit.prepared, circuit.expr))
+            .collect::<String>(),
+    );
+    let tensored =
+        calibration_circuits(3, Model::Tensored).expect("three qubits should be supported");
+    assert_eq!(
+        tensored
+            .iter()
+            .map(|circuit| circuit.prepared.as_str())
+            .collect::<Vec<_>>(),
+        ["000", "111"]
+    );
+}
+
+#[test]
+fn too_many_qubits() {
+    assert!(matches!(
+        calibration_circuits(MAX_QUBITS + 1, Model::Tensored),
+        Err(Error::TooManyQubits(9))
+    ));
+}
+
+#[test]
+fn bitstring_of_results() {
+    let results = Value::Array(vec![Value::RESULT_ONE, Value::RESULT_ZERO].into());
+    assert_eq!(bitstring(&results).as_deref(), Some("10"));
+    assert_eq!(bitstring(&Value::RESULT_ONE).as_deref(), Some("1"));
+    assert_eq!(bitstring(&Value::Int(1)), None);
+}
+
+#[test]
+fn tensored_matrix_is_product_of_qubit_matrices() {
+    let results = BTreeMap::from([
+        (
+            "00".to_string(),
+            counts(&[("00", 81), ("10", 9), ("01", 9), ("11", 1)]),
+        ),
+        (
+            "11".to_string(),
+            counts(&[("11", 64), ("10", 16), ("01", 16), ("00", 4)]),
+        ),
+    ]);
+    let matrix = ConfusionMatrix::from_calibration(2, Model::Tensored, &results)
+        .expect("calibration should be valid");
+    assert_eq!(matrix.qubits(), 2);
+    // Qubit 0 prepared in |0〉 reads one, and qubit 1 prepared in |1〉 reads zero.
+    assert_close(
+        matrix
+            .probability("10", "01")
+            .expect("bitstrings are valid"),
+        0.1 * 0.2,
+    );
+    assert_close(
+        matrix
+            .probability("00", "11")
+            .expect("bitstrings are valid"),
+        0.2 * 0.2,
+    );
+    assert_close(
+        matrix
+            .probability("00", "00")
+            .expect("bitstrings are valid"),
+        0.9 * 0.9,
+    );
+    assert_eq!(matrix.probability("0", "00"), None);
+}
+
+#[test]
+fn inversion_recovers_distribution() {
+    // Half |0〉 and half |1〉 reads zero 0.5 × 0.9 + 0.5 × 0.2 of the time.
+    let mitigated = one_qubit()
+        .mitigate(&counts(&[("0", 55), ("1", 45)]), Method::Inversion)
+        .expect("counts should be valid");
+    assert_eq!(mitigated.shots, 100);
+    let zero = mitigated.distribution["0"];
+    let one = mitigated.distribution["1"];
+    assert_close(zero.probability, 0.5);
+    assert_close(one.probability, 0.5);
+    assert!(zero.std_error > 0.0);
+    assert_close(zero.std_error, one.std_error);
+}
+
+#[test]
+fn inversion_can_be_negative_and_least_squares_is_not() {
+    let matrix = one_qubit();
+    let histogram = counts(&[("0", 100)]);
+    let inverted = matrix
+        .mitigate(&histogram, Method::Inversion)
+        .expect("counts should be valid");
+    assert_close(inverted.distribution["0"].probability, 0.8 / 0.7);
+    assert_close(inverted.distribution["1"].probability, -0.1 / 0.7);
+
+    let fitted = matrix
+        .mitigate(&histogram, Method::LeastSquares)
+        .expect("counts should be valid");
+    assert_close(fitted.distribution["0"].probability, 1.0);
+    assert_close(fitted.distribution["1"].probability, 0.0);
+}
+
+#[test]
+fn least_squares_matches_inversion_for_valid_distribution() {
+    let matrix = one_qubit();
+    let histogram = counts(&[("0", 55), ("1", 45)]);
+    let inverted = matrix
+        .mitigate(&histogram, Method::Inversion)
+        .expect("counts should be valid");
+    let fitted = matrix
+        .mitigate(&histogram, Method::LeastSquares)
+        .expect("counts should be valid");
+    for (bits, estimate) in &fitted.distribution {
+        assert_close(
+            estimate.probability,
+            inverted.distribution[bits].probability,
+        );
+    }
+}
+
+#[test]
+fn invalid_inputs() {
+    let matrix = one_qubit();
+    assert!(matches!(
+        matrix.mitigate(&counts(&[("01", 1)]), Method::Inversion),
+        Err(Error::InvalidBitstring(bits)) if bits == "01"
+    ));
+    assert!(matches!(
+        matrix.mitigate(&Counts::new(), Method::Inversion),
+        Err(Error::NoShots)
+    ));
+    let missing = BTreeMap::from([("0".to_string(), counts(&[("0", 1)]))]);
+    assert!(matches!(
+        ConfusionMatrix::from_calibration(1, Model::Full, &missing),
+        Err(Error::MissingCalibration(bits)) if bits == "1"
+    ));
+    let random = BTreeMap::from([
+        ("0".to_string(), counts(&[("0", 1), ("1", 1)])),
+        ("1".to_string(), counts(&[("0", 1), ("1", 1)])),
+    ]);
+    let singular = ConfusionMatrix::from_calibration(1, Model::Full, &random)
+        .expect("calibration should be valid");
+    assert!(matches!(
+        singular.mitigate(&counts(&[("0", 1)]), Method::Inversion),
+        Err(Error::Singular)
+    ));
+}
+
+#[test]
+fn calibrate_on_noisy_simulator() {
+    let flip = Noise {
+        bit_flip: 0.1,
+        ..Noise::default()
+    };
+    let mut interpreter = Interpreter::new_with_noise(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        NoiseModel::new(Noise::default())
+            .expect("noise should be valid")
+            .with_operation("mresetz", flip)
+            .expect("noise should be valid"),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_seed(Some(7));
+
+    let matrix =
+        calibrate(&mut interpreter, 2, Model::Tensored, 2000).expect("calibration should run");
+    let reads_correctly = matrix
+        .probability("00", "00")
+        .expect("bitstrings are valid");
+    assert!((reads_correctly - 0.81).abs() < 0.05, "{reads_correctly}");
+    let flips = matrix
+        .probability("01", "11")
+        .expect("bitstrings are valid");
+    assert!((flips - 0.09).abs() < 0.03, "{flips}");
+}
+
+#[test]
+fn linear_extrapolation_fits_line() {
+    let scaled = [(1, 0.8), (3, 0.4), (5, 0.0)];
+    let value = Extrapolation::Linear
+        .extrapolate(&scaled)
+        .expect("there are enough scales");
+    assert_close(value, 1.0);
+}
+
+#[test]
+fn richardson_extrapolation_interpolates_polynomial() {
+    // 1 - x/10 + x²/100 through three scales.
+    let scaled = [(1, 0.91), (3, 0.79), (5, 0.75)];
+    let value = Extrapolation::Richardson
+        .extrapolate(&scaled)
+        .expect("there are enough scales");
+    assert_close(value, 1.0);
+    let linear = Extrapolation::Linear
+        .extrapolate(&scaled)
+        .expect("there are enough scales");
+    assert!((linear - 1.0).abs() > 1e-3, "{linear}");
+}
+
+#[test]
+fn invalid_scales() {
+    assert!(matches!(
+        Extrapolation::Linear.extrapolate(&[(1, 0.5), (1, 0.6)]),
+        Err(Error::TooFewScales)
+    ));
+
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    let expr = "{ use q = Qubit(); MResetZ(q) }";
+    assert!(matches!(
+        extrapolate_zero_noise(
+            &mut interpreter,
+            &mut receiver,
+            expr,
+            1,
+            &[1, 2],
+            Extrapolation::Linear
+        ),
+        Err(Error::InvalidScale(2))
+    ));
+    assert!(matches!(
+        extrapolate_zero_noise(
+            &mut interpreter,
+            &mut receiver,
+            expr,
+            1,
+            &[3, 3],
+            Extrapolation::Linear
+        ),
+        Err(Error::TooFewScales)
+    ));
+}
+
+#[test]
+fn zero_noise_extrapolation_on_noisy_simulator() {
+    let flip = Noise {
+        bit_flip: 0.05,
+        ..Noise::default()
+    };
+    let mut interpreter = Interpreter::new_with_noise(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        NoiseModel::new(Noise::default())
+            .expect("noise should be valid")
+            .with_operation("x", flip)
+            .expect("noise should be valid"),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_seed(Some(11));
+    interpreter.add_reducer("parity", Box::new(Parity::new()));
+
+    let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    // Without noise the qubit always measures `Zero`, and each flip of an `X` is noise.
+    let estimates = extrapolate_zero_noise(
+        &mut interpreter,
+        &mut receiver,
+        "{ use q = Qubit(); X(q); X(q); MResetZ(q) }",
+        2000,
+        &[5, 1, 3],
+        Extrapolation::Richardson,
+    )
+    .expect("extrapolation should run");
+    assert_eq!(interpreter.gate_folds(), 0);
+
+    let estimate = &estimates["parity"];
+    assert_eq!(
+        estimate
+            .scaled
+            .iter()
+            .map(|&(scale, _)| scale)
+            .collect::<Vec<_>>(),
+        [1, 3, 5]
+    );
+    // Each shot applies `X` twice per scale, so the parity there is about 0.9^(2 × scale).
+    let unmitigated = estimate.scaled[0].1;
+    assert!((unmitigated - 0.81).abs() < 0.05, "{unmitigated}");
+    assert!(
+        (estimate.value - 1.0).abs() < (unmitigated - 1.0).abs(),
+        "{} is not closer to 1 than {unmitigated}",
+        estimate.value
+    );
+}
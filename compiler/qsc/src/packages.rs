@@ -0,0 +1,119 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Building the package graph of a project, as loaded from its `qsharp.json` manifests, into a
+//! package store. Each dependency is compiled as a package of its own, against only its own
+//! dependencies, so that packages can declare the same namespaces and a package only sees the
+//! items of the packages it depends on.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    cache::{CacheKey, DiskCache},
+    compile::{self, compile},
+    precompiled,
+};
+use qsc_data_structures::target::TargetCapabilityFlags;
+use qsc_frontend::compile::{PackageStore, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+use qsc_project::{PackageGraphSources, PackageInfo};
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+
+/// Compiles the packages that the root of the graph depends on into the store, each one after
+/// its own dependencies. `std` is the standard library in the store, if any, which every package
+/// depends on unless its manifest sets `noStdlib`.
+///
+/// Returns the packages for the root to be compiled against, by the same rules, along with the
+/// errors from compiling the dependencies. Packages with errors are still added to the store,
+/// so that the packages that depend on them only report errors of their own.
+pub fn compile_dependencies(
+    store: &mut PackageStore,
+    std: Option<PackageId>,
+    graph: &PackageGraphSources,
+    capabilities: TargetCapabilityFlags,
+) -> (Vec<PackageId>, Vec<compile::Error>) {
+    let mut errors = Vec::new();
+    let dependencies = build(std, graph, |dependencies, info| {
+        let (unit, mut unit_errors) = compile(
+            store,
+            dependencies,
+            SourceMap::new(info.sources.clone(), None),
+            PackageType::Lib,
+            capabilities,
+            info.language_features,
+        );
+        errors.append(&mut unit_errors);
+        store.insert(unit)
+    });
+    (dependencies, errors)
+}
+
+/// Compiles the packages that the root of the graph depends on like [`compile_dependencies`],
+/// loading the ones that are unchanged since an earlier build from the cache, see
+/// [`precompiled::compile_package`]. `std` is given with its key, such as from
+/// [`precompiled::std_key`], and so is each returned package.
+pub fn compile_dependencies_cached(
+    store: &mut PackageStore,
+    std: Option<(PackageId, CacheKey)>,
+    graph: &PackageGraphSources,
+    capabilities: TargetCapabilityFlags,
+    cache: &DiskCache,
+) -> (Vec<(PackageId, CacheKey)>, Vec<compile::Error>) {
+    let mut errors = Vec::new();
+    let dependencies = build(std, graph, |dependencies, info| {
+        let (unit, key, mut unit_errors) = precompiled::compile_package(
+            store,
+            dependencies,
+            SourceMap::new(info.sources.clone(), None),
+            PackageType::Lib,
+            capabilities,
+            info.language_features,
+            cache,
+        );
+        errors.append(&mut unit_errors);
+        (store.insert(unit), key)
+    });
+    (dependencies, errors)
+}
+
+/// Builds every package that the root of the graph depends on with `build_package`, each one
+/// after its own dependencies, and returns the packages for the root to be built against.
+fn build<T: Copy + PartialEq>(
+    std: Option<T>,
+    graph: &PackageGraphSources,
+    mut build_package: impl FnMut(&[T], &PackageInfo) -> T,
+) -> Vec<T> {
+    let mut ids = FxHashMap::<&Arc<str>, T>::default();
+    for (key, info) in graph.dependency_order() {
+        let dependencies = dependencies(info, std, &ids);
+        ids.insert(key, build_package(&dependencies, info));
+    }
+    dependencies(&graph.root, std, &ids)
+}
+
+/// The packages that a package is compiled against: the standard library first, and then its
+/// dependencies in the order of their aliases. A dependency that was not compiled, because it
+/// failed to load or is part of a cycle, is left out.
+fn dependencies<T: Copy + PartialEq>(
+    info: &PackageInfo,
+    std: Option<T>,
+    ids: &FxHashMap<&Arc<str>, T>,
+) -> Vec<T> {
+    let mut aliases = info.dependencies.iter().collect::<Vec<_>>();
+    aliases.sort();
+    let mut dependencies = std
+        .filter(|_| !info.no_stdlib)
+        .into_iter()
+        .collect::<Vec<_>>();
+    for (_, key) in aliases {
+        if let Some(&id) = ids.get(key) {
+            if !dependencies.contains(&id) {
+                dependencies.push(id);
+            }
+        }
+    }
+    dependencies
+}
@@ -0,0 +1,191 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{compile_dependencies, compile_dependencies_cached};
+use crate::{
+    cache::DiskCache,
+    compile,
+    interpret::{GenericReceiver, Interpreter},
+    precompiled, PackageStore,
+};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::StdFeatures;
+use qsc_passes::PackageType;
+use qsc_project::{PackageGraphSources, PackageInfo};
+use std::sync::Arc;
+
+fn package(source: &str, dependencies: &[(&str, &str)]) -> PackageInfo {
+    PackageInfo {
+        sources: vec![("Main.qs".into(), source.into())],
+        language_features: LanguageFeatures::default(),
+        dependencies: dependencies
+            .iter()
+            .map(|&(alias, key)| (Arc::from(alias), Arc::from(key)))
+            .collect(),
+        no_stdlib: false,
+    }
+}
+
+/// A root package that depends on `Lib`, which depends on `Base`.
+fn graph() -> PackageGraphSources {
+    PackageGraphSources {
+        root: package(
+            indoc! {"
+                namespace Main {
+                    open Library;
+                    @EntryPoint()
+                    operation Main() : Int { Twice(Length([1, 2, 3])) }
+                }
+            "},
+            &[("Lib", "lib")],
+        ),
+        packages: [
+            (
+                "lib".into(),
+                package(
+                    indoc! {"
+                        namespace Library {
+                            function Twice(x : Int) : Int { Base.Add(x, x) }
+                        }
+                    "},
+                    &[("Base", "base")],
+                ),
+            ),
+            (
+                "base".into(),
+                package(
+                    indoc! {"
+                        namespace Base {
+                            function Add(a : Int, b : Int) : Int { a + b }
+                        }
+                    "},
+                    &[],
+                ),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    }
+}
+
+#[test]
+fn dependencies_are_compiled_as_separate_packages() {
+    let capabilities = TargetCapabilityFlags::all();
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, capabilities));
+    let graph = graph();
+    let (dependencies, errors) = compile_dependencies(&mut store, Some(std), &graph, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(dependencies.len(), 2);
+    assert_eq!(dependencies[0], std);
+    // The core and standard libraries, and the two dependencies.
+    assert_eq!(store.iter().count(), 4);
+}
+
+#[test]
+fn cached_dependencies_are_rebuilt_when_a_dependency_of_theirs_changes() {
+    let capabilities = TargetCapabilityFlags::all();
+    let cache = DiskCache::new(
+        std::env::temp_dir().join(format!("qsc-packages-test-{}-cached", std::process::id())),
+    );
+    cache.clear().expect("cache should be cleared");
+    let build = |graph: &PackageGraphSources| {
+        let mut store = PackageStore::new(compile::core());
+        let std = store.insert(compile::std(&store, capabilities));
+        let std = Some((std, precompiled::std_key(capabilities, StdFeatures::all())));
+        let (dependencies, errors) =
+            compile_dependencies_cached(&mut store, std, graph, capabilities, &cache);
+        assert!(errors.is_empty(), "{errors:?}");
+        dependencies
+    };
+
+    let graph = graph();
+    let keys = build(&graph);
+    assert_eq!(keys.len(), 2);
+    assert_eq!(build(&graph), keys);
+
+    let mut changed = graph;
+    let base = changed
+        .packages
+        .get_mut("base")
+        .expect("graph should have base package");
+    base.sources[0].1 = format!("{}\n", base.sources[0].1).into();
+    let changed_keys = build(&changed);
+    // `Lib` is not changed itself, but is built against the changed `Base`.
+    assert_ne!(changed_keys[1].1, keys[1].1);
+}
+
+#[test]
+fn interpreter_runs_package_graph() {
+    let mut interpreter = Interpreter::from_package_graph(
+        graph(),
+        None,
+        PackageType::Exe,
+        TargetCapabilityFlags::all(),
+    )
+    .expect("package graph should compile");
+    let mut out = Vec::new();
+    let value = interpreter
+        .eval_entry(&mut GenericReceiver::new(&mut out))
+        .expect("entry should run");
+    expect!["6"].assert_eq(&value.to_string());
+}
+
+#[test]
+fn package_without_stdlib_cannot_use_it() {
+    let mut graph = graph();
+    let base = graph
+        .packages
+        .get_mut("base")
+        .expect("graph should have base package");
+    base.sources[0].1 = indoc! {"
+        namespace Base {
+            function Add(a : Int, b : Int) : Int { Length([a]) + b }
+        }
+    "}
+    .into();
+    base.no_stdlib = true;
+
+    let errors = Interpreter::from_package_graph(
+        graph,
+        None,
+        PackageType::Exe,
+        TargetCapabilityFlags::all(),
+    )
+    .err()
+    .expect("package without stdlib should fail to compile");
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.to_string().contains("Length")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn root_without_stdlib_only_sees_its_dependencies() {
+    let mut graph = graph();
+    graph.root.no_stdlib = true;
+    graph.root.sources[0].1 = indoc! {"
+        namespace Main {
+            @EntryPoint()
+            operation Main() : Int { Library.Twice(4) }
+        }
+    "}
+    .into();
+
+    let mut interpreter = Interpreter::from_package_graph(
+        graph,
+        None,
+        PackageType::Exe,
+        TargetCapabilityFlags::all(),
+    )
+    .expect("package graph should compile");
+    let mut out = Vec::new();
+    let value = interpreter
+        .eval_entry(&mut GenericReceiver::new(&mut out))
+        .expect("entry should run");
+    expect!["8"].assert_eq(&value.to_string());
+}
@@ -0,0 +1,263 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compiled packages saved to disk and loaded back into a package store without reparsing them,
+//! so that the standard library and large libraries only have to be compiled once. A saved
+//! package keeps its HIR and its sources, but not its AST, so a loaded package can be used as a
+//! dependency of other packages and run, but it cannot be compiled incrementally or served by the
+//! language service.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    cache::{CacheKey, DiskCache, BUILD_ID},
+    compile,
+};
+use log::info;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{
+    preprocess::TrackedName, CompileUnit, PackageStore, SourceMap, StdFeatures,
+};
+use qsc_hir::{
+    assigner::Assigner as HirAssigner,
+    hir::{self, PackageId},
+};
+use qsc_passes::PackageType;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// The kind of the cache entries that hold saved packages.
+const KIND: &str = "hir";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("the package was saved by a different compiler")]
+    Version,
+    #[error("the package was compiled for different target capabilities")]
+    Capabilities,
+    #[error("dependency {0} of the package is not in the store")]
+    MissingDependency(PackageId),
+    #[error("the package could not be read: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+/// A saved package. Borrowed parts are saved, and owned parts are loaded.
+///
+/// Packages are only loaded by the build of the compiler that saved them, identified by
+/// [`BUILD_ID`], since any change to the compiler sources can change the layout of the HIR or
+/// how packages are compiled.
+#[derive(Deserialize, Serialize)]
+struct PackageFile<'a> {
+    compiler: Cow<'a, str>,
+    capabilities: u32,
+    dependencies: Cow<'a, [PackageId]>,
+    package: Cow<'a, hir::Package>,
+    assigner: Cow<'a, HirAssigner>,
+    sources: Cow<'a, SourceMap>,
+    dropped_names: Cow<'a, [TrackedName]>,
+}
+
+/// The start of a saved package, which is read first so that a package from a different
+/// compiler is reported as such rather than as malformed.
+#[derive(Deserialize)]
+struct Header {
+    compiler: String,
+}
+
+/// Saves a package that was compiled without errors against the given dependencies for the
+/// given target capabilities.
+///
+/// # Errors
+///
+/// Returns an error if the package cannot be serialized.
+pub fn save(
+    unit: &CompileUnit,
+    dependencies: &[PackageId],
+    capabilities: TargetCapabilityFlags,
+) -> Result<String, Error> {
+    let file = PackageFile {
+        compiler: BUILD_ID.into(),
+        capabilities: capabilities.bits(),
+        dependencies: dependencies.into(),
+        package: Cow::Borrowed(&unit.package),
+        assigner: Cow::Borrowed(&unit.assigner),
+        sources: Cow::Borrowed(&unit.sources),
+        dropped_names: unit.dropped_names.as_slice().into(),
+    };
+    Ok(serde_json::to_string(&file)?)
+}
+
+/// Loads a saved package to be inserted into the store. The dependencies it was compiled
+/// against must be in the store with the same IDs that they had when it was compiled, which
+/// holds for the standard library when it is the first package inserted after core.
+///
+/// # Errors
+///
+/// Returns an error if the package was saved by a different compiler, was compiled for
+/// different target capabilities, depends on a package that is not in the store, or cannot be
+/// read.
+pub fn load(
+    store: &PackageStore,
+    contents: &str,
+    capabilities: TargetCapabilityFlags,
+) -> Result<CompileUnit, Error> {
+    let header: Header = from_str(contents)?;
+    if header.compiler != BUILD_ID {
+        return Err(Error::Version);
+    }
+
+    let file: PackageFile = from_str(contents)?;
+    if file.capabilities != capabilities.bits() {
+        return Err(Error::Capabilities);
+    }
+    if let Some(&id) = file
+        .dependencies
+        .iter()
+        .find(|&&id| store.get(id).is_none())
+    {
+        return Err(Error::MissingDependency(id));
+    }
+
+    Ok(CompileUnit {
+        package: file.package.into_owned(),
+        assigner: file.assigner.into_owned(),
+        sources: file.sources.into_owned(),
+        dropped_names: file.dropped_names.into_owned(),
+        ..CompileUnit::default()
+    })
+}
+
+/// Deserializes a saved package or its header. The HIR nests as deeply as the source, which can
+/// be deeper than the default limit of the deserializer, and the compiler already recursed
+/// through the same tree to compile it, so the depth is not limited.
+fn from_str<'a, T: Deserialize<'a>>(contents: &'a str) -> serde_json::Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(contents);
+    deserializer.disable_recursion_limit();
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// The key that the standard library for the given target capabilities and feature groups is
+/// cached under by [`std`], for the keys of the packages compiled against it.
+#[must_use]
+pub fn std_key(capabilities: TargetCapabilityFlags, features: StdFeatures) -> CacheKey {
+    let capabilities_part = capabilities.bits().to_string();
+    let features_part = features.bits().to_string();
+    // The key covers the build of the compiler like every cache key. The sources of core and the
+    // library are part of it as well, so that the key changes along with the library that a
+    // build embeds, whose items refer to the items of the core it was compiled against.
+    CacheKey::from_parts(
+        ["std", &capabilities_part, &features_part]
+            .into_iter()
+            .chain(
+                library::CORE_LIB
+                    .iter()
+                    .chain(library::std_lib_sources(features))
+                    .flat_map(|(name, contents)| [*name, *contents]),
+            ),
+    )
+}
+
+/// Compiles the standard library like [`compile::std_with_features`], unless it was saved to the
+/// cache by an earlier compilation for the same target capabilities and feature groups, in which
+/// case it is loaded from there. The store must hold only core, so that the standard library is
+/// inserted with the same ID that it was compiled with.
+#[must_use]
+pub fn std(
+    store: &PackageStore,
+    capabilities: TargetCapabilityFlags,
+    features: StdFeatures,
+    cache: &DiskCache,
+) -> CompileUnit {
+    let key = std_key(capabilities, features);
+    if let Some(contents) = cache.get(key, KIND) {
+        match load(store, &contents, capabilities) {
+            Ok(unit) => return unit,
+            Err(err) => info!("Could not load the standard library from cache: {err}"),
+        }
+    }
+
+    let unit = compile::std_with_features(store, capabilities, features);
+    match save(&unit, &[PackageId::CORE], capabilities) {
+        Ok(contents) => {
+            if let Err(err) = cache.put(key, KIND, &contents) {
+                info!("Could not write the standard library to cache: {err}");
+            }
+        }
+        Err(err) => info!("Could not save the standard library: {err}"),
+    }
+    unit
+}
+
+/// Compiles a package like [`compile::compile`], unless it was saved to the cache by an earlier
+/// compilation of the same sources and options against the same dependencies, in which case it
+/// is loaded from there without being parsed or lowered again. Each dependency is given with the
+/// key it was compiled or loaded under, such as from [`std_key`] or an earlier call, so that a
+/// package is compiled again whenever one of its dependencies changes. Packages with errors are
+/// compiled every time, so that their errors are reported.
+///
+/// Returns the package, the key to give for it when compiling packages against it, and the errors
+/// from compiling it.
+pub fn compile_package(
+    store: &PackageStore,
+    dependencies: &[(PackageId, CacheKey)],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: TargetCapabilityFlags,
+    language_features: LanguageFeatures,
+    cache: &DiskCache,
+) -> (CompileUnit, CacheKey, Vec<compile::Error>) {
+    let capabilities_part = capabilities.bits().to_string();
+    let package_type_part = format!("{package_type:?}");
+    let features: Vec<String> = language_features.into();
+    let dependency_parts = dependencies
+        .iter()
+        .map(|(id, key)| format!("{id} {key}"))
+        .collect::<Vec<_>>();
+    let entry = sources.entry().map(|entry| &*entry.contents);
+    let key = CacheKey::from_parts(
+        ["package", &capabilities_part, &package_type_part]
+            .into_iter()
+            .chain(features.iter().map(String::as_str))
+            .chain(["dependencies"])
+            .chain(dependency_parts.iter().map(String::as_str))
+            // The entry is tagged, so that an entry differs from no entry at all.
+            .chain(entry.map_or(["no entry", ""], |entry| ["entry", entry]))
+            .chain(
+                sources
+                    .iter()
+                    .flat_map(|source| [&*source.name, &*source.contents]),
+            ),
+    );
+
+    if let Some(contents) = cache.get(key, KIND) {
+        match load(store, &contents, capabilities) {
+            Ok(unit) => return (unit, key, Vec::new()),
+            Err(err) => info!("Could not load the package from cache: {err}"),
+        }
+    }
+
+    let ids = dependencies.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+    let (unit, errors) = compile::compile(
+        store,
+        &ids,
+        sources,
+        package_type,
+        capabilities,
+        language_features,
+    );
+    if errors.is_empty() {
+        match save(&unit, &ids, capabilities) {
+            Ok(contents) => {
+                if let Err(err) = cache.put(key, KIND, &contents) {
+                    info!("Could not write the package to cache: {err}");
+                }
+            }
+            Err(err) => info!("Could not save the package: {err}"),
+        }
+    }
+    (unit, key, errors)
+}
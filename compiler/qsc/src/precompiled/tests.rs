@@ -0,0 +1,261 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{compile_package, load, save, Error};
+use crate::{
+    cache::{CacheKey, DiskCache, BUILD_ID},
+    compile,
+    interpret::{GenericReceiver, Interpreter},
+};
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::val::Value;
+use qsc_frontend::compile::{CompileUnit, PackageStore, SourceMap, StdFeatures};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+
+fn temp_cache(name: &str) -> DiskCache {
+    let dir = std::env::temp_dir().join(format!(
+        "qsc-precompiled-test-{}-{name}",
+        std::process::id()
+    ));
+    let cache = DiskCache::new(dir);
+    cache.clear().expect("cache should be cleared");
+    cache
+}
+
+/// A small library compiled against core only.
+fn library(store: &PackageStore) -> CompileUnit {
+    let sources = SourceMap::new(
+        [(
+            "A.qs".into(),
+            "namespace A { function Answer() : Int { 42 } }".into(),
+        )],
+        None,
+    );
+    let (unit, errors) = compile::compile(
+        store,
+        &[PackageId::CORE],
+        sources,
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    unit
+}
+
+#[test]
+fn loaded_package_matches_saved_package() {
+    let store = PackageStore::new(compile::core());
+    let unit = library(&store);
+    let contents = save(&unit, &[PackageId::CORE], TargetCapabilityFlags::all())
+        .expect("package should be saved");
+
+    let loaded =
+        load(&store, &contents, TargetCapabilityFlags::all()).expect("package should be loaded");
+    assert_eq!(loaded.package.to_string(), unit.package.to_string());
+    assert_eq!(
+        loaded
+            .sources
+            .find_by_name("A.qs")
+            .map(|source| &source.contents),
+        unit.sources
+            .find_by_name("A.qs")
+            .map(|source| &source.contents)
+    );
+    assert!(loaded.ast.package.nodes.is_empty());
+}
+
+#[test]
+fn loaded_std_is_used_as_dependency() {
+    let store = PackageStore::new(compile::core());
+    let std = compile::std(&store, TargetCapabilityFlags::all());
+    let contents =
+        save(&std, &[PackageId::CORE], TargetCapabilityFlags::all()).expect("std should be saved");
+
+    let mut store = PackageStore::new(compile::core());
+    let std = load(&store, &contents, TargetCapabilityFlags::all()).expect("std should be loaded");
+    let std_id = store.insert(std);
+    let mut interpreter = Interpreter::with_dependencies(
+        store,
+        vec![std_id],
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+
+    let mut out = Vec::new();
+    let value = interpreter
+        .eval_fragments(
+            &mut GenericReceiver::new(&mut out),
+            "Microsoft.Quantum.Math.AbsI(-3)",
+        )
+        .expect("fragment should evaluate");
+    assert_eq!(value, Value::Int(3));
+}
+
+#[test]
+fn package_from_other_compiler_is_not_loaded() {
+    let store = PackageStore::new(compile::core());
+    let contents = save(
+        &library(&store),
+        &[PackageId::CORE],
+        TargetCapabilityFlags::all(),
+    )
+    .expect("package should be saved");
+    let contents = contents.replacen(
+        &format!(r#""compiler":"{BUILD_ID}""#),
+        r#""compiler":"other""#,
+        1,
+    );
+    assert!(matches!(
+        load(&store, &contents, TargetCapabilityFlags::all()),
+        Err(Error::Version)
+    ));
+}
+
+#[test]
+fn package_for_other_capabilities_is_not_loaded() {
+    let store = PackageStore::new(compile::core());
+    let contents = save(
+        &library(&store),
+        &[PackageId::CORE],
+        TargetCapabilityFlags::all(),
+    )
+    .expect("package should be saved");
+    assert!(matches!(
+        load(&store, &contents, TargetCapabilityFlags::empty()),
+        Err(Error::Capabilities)
+    ));
+}
+
+#[test]
+fn package_with_missing_dependency_is_not_loaded() {
+    let store = PackageStore::new(compile::core());
+    let missing = PackageId::from(1);
+    let contents = save(
+        &library(&store),
+        &[PackageId::CORE, missing],
+        TargetCapabilityFlags::all(),
+    )
+    .expect("package should be saved");
+    assert!(matches!(
+        load(&store, &contents, TargetCapabilityFlags::all()),
+        Err(Error::MissingDependency(id)) if id == missing
+    ));
+}
+
+#[test]
+fn malformed_package_is_not_loaded() {
+    let store = PackageStore::new(compile::core());
+    assert!(matches!(
+        load(&store, "{", TargetCapabilityFlags::all()),
+        Err(Error::Format(_))
+    ));
+}
+
+#[test]
+fn std_is_compiled_once_and_then_loaded_from_cache() {
+    let cache = temp_cache("std");
+    let store = PackageStore::new(compile::core());
+    let compiled = super::std(
+        &store,
+        TargetCapabilityFlags::all(),
+        StdFeatures::all(),
+        &cache,
+    );
+    assert!(!compiled.ast.package.nodes.is_empty());
+
+    let loaded = super::std(
+        &store,
+        TargetCapabilityFlags::all(),
+        StdFeatures::all(),
+        &cache,
+    );
+    assert!(loaded.ast.package.nodes.is_empty());
+    assert_eq!(loaded.package.to_string(), compiled.package.to_string());
+
+    // Other feature groups make a different standard library.
+    let other = super::std(
+        &store,
+        TargetCapabilityFlags::all(),
+        StdFeatures::empty(),
+        &cache,
+    );
+    assert!(!other.ast.package.nodes.is_empty());
+}
+
+#[test]
+fn package_is_compiled_once_and_then_loaded_from_cache() {
+    let cache = temp_cache("package");
+    let store = PackageStore::new(compile::core());
+    let core = (PackageId::CORE, CacheKey::from_parts(["core"]));
+    let sources = || {
+        SourceMap::new(
+            [(
+                "A.qs".into(),
+                "namespace A { function Answer() : Int { 42 } }".into(),
+            )],
+            None,
+        )
+    };
+    let compile = |dependencies: &[(PackageId, CacheKey)]| {
+        let (unit, key, errors) = compile_package(
+            &store,
+            dependencies,
+            sources(),
+            PackageType::Lib,
+            TargetCapabilityFlags::all(),
+            LanguageFeatures::default(),
+            &cache,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        (unit, key)
+    };
+
+    let (compiled, key) = compile(&[core]);
+    assert!(!compiled.ast.package.nodes.is_empty());
+    let (loaded, loaded_key) = compile(&[core]);
+    assert!(loaded.ast.package.nodes.is_empty());
+    assert_eq!(loaded_key, key);
+    assert_eq!(loaded.package.to_string(), compiled.package.to_string());
+
+    // A changed dependency makes the package compile again, under a different key.
+    let (recompiled, other_key) = compile(&[(PackageId::CORE, CacheKey::from_parts(["other"]))]);
+    assert!(!recompiled.ast.package.nodes.is_empty());
+    assert_ne!(other_key, key);
+}
+
+#[test]
+fn package_with_errors_is_not_cached() {
+    let cache = temp_cache("errors");
+    let store = PackageStore::new(compile::core());
+    for _ in 0..2 {
+        let (_, _, errors) = compile_package(
+            &store,
+            &[(PackageId::CORE, CacheKey::from_parts(["core"]))],
+            SourceMap::new(
+                [(
+                    "A.qs".into(),
+                    "namespace A { function F() : Int { } }".into(),
+                )],
+                None,
+            ),
+            PackageType::Lib,
+            TargetCapabilityFlags::all(),
+            LanguageFeatures::default(),
+            &cache,
+        );
+        assert!(!errors.is_empty());
+    }
+}
+
+#[test]
+fn std_key_depends_on_features() {
+    assert_ne!(
+        super::std_key(TargetCapabilityFlags::all(), StdFeatures::all()),
+        super::std_key(TargetCapabilityFlags::all(), StdFeatures::empty())
+    );
+}
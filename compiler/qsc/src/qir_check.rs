@@ -0,0 +1,490 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Checks that a QIR module conforms to the requirements of a QIR profile, such as the Base or
+//! Adaptive profile, and lists every part of the module that does not. Modules can come from this
+//! compiler or from any other QIR generator, so that programs a target cannot run are rejected
+//! before they are submitted to it.
+//!
+//! The module is checked in LLVM's text format, one line at a time, as generators emit it. It is
+//! not verified to be valid LLVM IR.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_data_structures::target::TargetCapabilityFlags;
+use rustc_hash::FxHashMap;
+
+use crate::target::Profile;
+
+/// The module flags that every profile requires.
+const MODULE_FLAGS: [&str; 4] = [
+    "qir_major_version",
+    "qir_minor_version",
+    "dynamic_qubit_management",
+    "dynamic_result_management",
+];
+
+/// The attributes that every profile requires the entry point to have.
+const ENTRY_POINT_ATTRIBUTES: [&str; 4] = [
+    "qir_profiles",
+    "output_labeling_schema",
+    "required_num_qubits",
+    "required_num_results",
+];
+
+/// The runtime functions that record the output of the program.
+const OUTPUT_FUNCTIONS: [&str; 6] = [
+    "__quantum__rt__initialize",
+    "__quantum__rt__array_record_output",
+    "__quantum__rt__tuple_record_output",
+    "__quantum__rt__result_record_output",
+    "__quantum__rt__bool_record_output",
+    "__quantum__rt__int_record_output",
+];
+
+const INTEGER_INSTRUCTIONS: [&str; 17] = [
+    "add", "sub", "mul", "udiv", "sdiv", "urem", "srem", "and", "or", "xor", "shl", "lshr", "ashr",
+    "icmp", "zext", "sext", "trunc",
+];
+
+const FLOAT_INSTRUCTIONS: [&str; 9] = [
+    "fadd", "fsub", "fmul", "fdiv", "frem", "fneg", "fcmp", "sitofp", "fptosi",
+];
+
+/// A part of a module that does not conform to the profile it is checked against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The line of the module it is on, starting from 1, or `None` if something the module
+    /// must declare is missing.
+    pub line: Option<usize>,
+    pub kind: ViolationKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// No function is defined with the `entry_point` attribute.
+    MissingEntryPoint,
+    /// Another function is defined with the `entry_point` attribute.
+    MultipleEntryPoints(String),
+    /// The entry point takes parameters.
+    EntryPointParameters(String),
+    /// The entry point does not have an attribute that the profile requires.
+    MissingAttribute(&'static str),
+    /// The entry point declares a different profile in its `qir_profiles` attribute.
+    ProfileMismatch(String),
+    /// The module does not have a module flag that the profile requires.
+    MissingModuleFlag(&'static str),
+    /// The module flag for the dynamic management of qubits or results is not `false`.
+    DynamicManagement(&'static str),
+    /// A function other than the entry point is defined.
+    FunctionDefinition(String),
+    /// The entry point uses an instruction that the profile does not support.
+    Instruction(String),
+    /// The entry point calls a function that the profile does not support.
+    Call(String),
+    /// The entry point branches on a condition.
+    ConditionalBranch,
+    /// The entry point branches to a block at or before the current one.
+    BackwardsBranch(String),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.kind),
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ViolationKind::MissingEntryPoint => {
+                f.write_str("no function has the `entry_point` attribute")
+            }
+            ViolationKind::MultipleEntryPoints(name) => {
+                write!(
+                    f,
+                    "`{name}` is another entry point, but only one is allowed"
+                )
+            }
+            ViolationKind::EntryPointParameters(name) => {
+                write!(f, "entry point `{name}` must not take parameters")
+            }
+            ViolationKind::MissingAttribute(attribute) => {
+                write!(f, "entry point does not have the `{attribute}` attribute")
+            }
+            ViolationKind::ProfileMismatch(declared) => {
+                write!(f, "entry point declares the `{declared}` profile")
+            }
+            ViolationKind::MissingModuleFlag(flag) => {
+                write!(f, "module does not have the `{flag}` module flag")
+            }
+            ViolationKind::DynamicManagement(flag) => write!(
+                f,
+                "module flag `{flag}` must be `false` because the profile does not support it"
+            ),
+            ViolationKind::FunctionDefinition(name) => write!(
+                f,
+                "`{name}` is defined, but the profile only supports defining the entry point"
+            ),
+            ViolationKind::Instruction(opcode) => {
+                write!(
+                    f,
+                    "`{opcode}` instructions are not supported by the profile"
+                )
+            }
+            ViolationKind::Call(name) => {
+                write!(f, "calls to `{name}` are not supported by the profile")
+            }
+            ViolationKind::ConditionalBranch => {
+                f.write_str("conditional branches are not supported by the profile")
+            }
+            ViolationKind::BackwardsBranch(label) => write!(
+                f,
+                "branch to `{label}` goes backwards, which is not supported by the profile"
+            ),
+        }
+    }
+}
+
+/// The result of checking a module against a profile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub profile: Profile,
+    /// The violations, in the order of the lines they are on, followed by the missing
+    /// declarations.
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    /// Whether the module conforms to the profile.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.passed() {
+            return writeln!(
+                f,
+                "module conforms to the {} profile",
+                self.profile.to_str()
+            );
+        }
+        writeln!(
+            f,
+            "module does not conform to the {} profile:",
+            self.profile.to_str()
+        )?;
+        for violation in &self.violations {
+            writeln!(f, "  {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The profile that the entry point of a module declares in its `qir_profiles` attribute, if it
+/// is a profile that this checker knows.
+#[must_use]
+pub fn declared_profile(qir: &str) -> Option<Profile> {
+    let module = Module::parse(qir);
+    let entry = module.functions.iter().find(|f| module.is_entry_point(f))?;
+    match attribute_value(module.attributes(entry), "qir_profiles")? {
+        "base_profile" => Some(Profile::Base),
+        "adaptive_profile" => Some(Profile::AdaptiveRI),
+        _ => None,
+    }
+}
+
+/// Checks a module in LLVM's text format against the profile. Checking against the
+/// unrestricted profile only checks the entry point and the module flags that every module
+/// must have.
+#[must_use]
+pub fn check(qir: &str, profile: Profile) -> Report {
+    let module = Module::parse(qir);
+    let capabilities = TargetCapabilityFlags::from(profile);
+    let mut violations = Vec::new();
+    let mut missing = Vec::new();
+
+    let mut entry = None;
+    for function in &module.functions {
+        if module.is_entry_point(function) {
+            if entry.is_some() {
+                violations.push(Violation {
+                    line: Some(function.line),
+                    kind: ViolationKind::MultipleEntryPoints(function.name.to_string()),
+                });
+            } else {
+                entry = Some(function);
+            }
+        } else if capabilities != TargetCapabilityFlags::all() {
+            violations.push(Violation {
+                line: Some(function.line),
+                kind: ViolationKind::FunctionDefinition(function.name.to_string()),
+            });
+        }
+    }
+
+    match entry {
+        Some(entry) => {
+            if !entry.params.trim().is_empty() {
+                violations.push(Violation {
+                    line: Some(entry.line),
+                    kind: ViolationKind::EntryPointParameters(entry.name.to_string()),
+                });
+            }
+            let attributes = module.attributes(entry);
+            for attribute in ENTRY_POINT_ATTRIBUTES {
+                if !attributes.contains(&format!("\"{attribute}\"")) {
+                    missing.push(ViolationKind::MissingAttribute(attribute));
+                }
+            }
+            let expected = match profile {
+                Profile::Unrestricted => None,
+                Profile::Base => Some("base_profile"),
+                Profile::AdaptiveRI => Some("adaptive_profile"),
+            };
+            if let (Some(declared), Some(expected)) =
+                (attribute_value(attributes, "qir_profiles"), expected)
+            {
+                if declared != expected {
+                    violations.push(Violation {
+                        line: Some(entry.line),
+                        kind: ViolationKind::ProfileMismatch(declared.to_string()),
+                    });
+                }
+            }
+            check_body(entry, capabilities, &mut violations);
+        }
+        None => missing.push(ViolationKind::MissingEntryPoint),
+    }
+
+    for flag in MODULE_FLAGS {
+        match module.flags.get(flag) {
+            None => missing.push(ViolationKind::MissingModuleFlag(flag)),
+            Some(&(line, text))
+                if flag.starts_with("dynamic_")
+                    && capabilities != TargetCapabilityFlags::all()
+                    && !text.contains("i1 false") =>
+            {
+                violations.push(Violation {
+                    line: Some(line),
+                    kind: ViolationKind::DynamicManagement(flag),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    violations.sort_by_key(|violation| violation.line);
+    violations.extend(
+        missing
+            .into_iter()
+            .map(|kind| Violation { line: None, kind }),
+    );
+    Report {
+        profile,
+        violations,
+    }
+}
+
+fn check_body(
+    entry: &Function<'_>,
+    capabilities: TargetCapabilityFlags,
+    violations: &mut Vec<Violation>,
+) {
+    let labels = entry
+        .body
+        .iter()
+        .filter_map(|&(_, text)| text.strip_suffix(':'))
+        .collect::<Vec<_>>();
+    let mut block = 0;
+    for &(line, text) in &entry.body {
+        if let Some(label) = text.strip_suffix(':') {
+            block = labels
+                .iter()
+                .position(|&other| other == label)
+                .unwrap_or_default();
+            continue;
+        }
+        let mut violation = |kind| {
+            violations.push(Violation {
+                line: Some(line),
+                kind,
+            });
+        };
+
+        let instruction = match text.split_once(" = ") {
+            Some((result, instruction)) if result.starts_with('%') => instruction,
+            _ => text,
+        };
+        let opcode = instruction.split_whitespace().next().unwrap_or_default();
+        match opcode {
+            "ret" => {}
+            "call" | "tail" | "musttail" | "notail" => {
+                if let Some(callee) = callee(instruction) {
+                    if !supports_call(callee, capabilities) {
+                        violation(ViolationKind::Call(callee.to_string()));
+                    }
+                }
+            }
+            "br" => {
+                if instruction.starts_with("br i1")
+                    && !capabilities.contains(TargetCapabilityFlags::Adaptive)
+                {
+                    violation(ViolationKind::ConditionalBranch);
+                }
+                if !capabilities.contains(TargetCapabilityFlags::BackwardsBranching) {
+                    for target in instruction.split("label %").skip(1) {
+                        let target = target
+                            .split(|c: char| c == ',' || c.is_whitespace())
+                            .next()
+                            .unwrap_or_default();
+                        if labels
+                            .iter()
+                            .position(|&label| label == target)
+                            .is_some_and(|index| index <= block)
+                        {
+                            violation(ViolationKind::BackwardsBranch(target.to_string()));
+                        }
+                    }
+                }
+            }
+            "phi" | "select" if capabilities.contains(TargetCapabilityFlags::Adaptive) => {}
+            _ if INTEGER_INSTRUCTIONS.contains(&opcode)
+                && capabilities.contains(TargetCapabilityFlags::IntegerComputations) => {}
+            _ if FLOAT_INSTRUCTIONS.contains(&opcode)
+                && capabilities.contains(TargetCapabilityFlags::FloatingPointComputations) => {}
+            _ if capabilities == TargetCapabilityFlags::all() => {}
+            _ => violation(ViolationKind::Instruction(opcode.to_string())),
+        }
+    }
+}
+
+fn supports_call(callee: &str, capabilities: TargetCapabilityFlags) -> bool {
+    if capabilities == TargetCapabilityFlags::all() {
+        return true;
+    }
+    match callee {
+        "__quantum__qis__read_result__body" => {
+            capabilities.contains(TargetCapabilityFlags::Adaptive)
+        }
+        "__quantum__qis__reset__body" => capabilities.contains(TargetCapabilityFlags::QubitReset),
+        "__quantum__rt__bool_record_output" | "__quantum__rt__int_record_output" => {
+            capabilities.contains(TargetCapabilityFlags::Adaptive)
+        }
+        _ => callee.starts_with("__quantum__qis__") || OUTPUT_FUNCTIONS.contains(&callee),
+    }
+}
+
+/// The name of the function that a call instruction calls, without the `@`.
+fn callee(instruction: &str) -> Option<&str> {
+    let (_, rest) = instruction.split_once('@')?;
+    let end = rest.find('(')?;
+    Some(&rest[..end])
+}
+
+/// The value of a string attribute in the text of an attribute group.
+fn attribute_value<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = attributes.split_once(&format!("\"{name}\"=\""))?;
+    let end = rest.find('"')?;
+    Some(&rest[..end])
+}
+
+/// A function definition, with the lines of its body.
+struct Function<'a> {
+    name: &'a str,
+    line: usize,
+    params: &'a str,
+    /// The attribute groups the definition refers to, such as `#0`.
+    attribute_groups: Vec<&'a str>,
+    body: Vec<(usize, &'a str)>,
+}
+
+#[derive(Default)]
+struct Module<'a> {
+    functions: Vec<Function<'a>>,
+    /// The content of each attribute group, by its name.
+    attribute_groups: FxHashMap<&'a str, &'a str>,
+    /// The line and text of each named module flag.
+    flags: FxHashMap<&'a str, (usize, &'a str)>,
+}
+
+impl<'a> Module<'a> {
+    fn parse(qir: &'a str) -> Self {
+        let mut module = Self::default();
+        let mut current: Option<Function<'a>> = None;
+        for (index, text) in qir.lines().enumerate() {
+            let line = index + 1;
+            let text = match text.split_once(';') {
+                Some((code, _)) if !text.contains('"') => code,
+                _ => text,
+            }
+            .trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            if let Some(function) = &mut current {
+                if text == "}" {
+                    module.functions.extend(current.take());
+                } else {
+                    function.body.push((line, text));
+                }
+            } else if let Some(function) = parse_define(text, line) {
+                current = Some(function);
+            } else if let Some(rest) = text.strip_prefix("attributes ") {
+                if let Some((group, content)) = rest.split_once(" = ") {
+                    module.attribute_groups.insert(group.trim(), content);
+                }
+            } else if text.starts_with('!') {
+                if let Some((_, rest)) = text.split_once("!\"") {
+                    if let Some(end) = rest.find('"') {
+                        module.flags.insert(&rest[..end], (line, text));
+                    }
+                }
+            }
+        }
+        module
+    }
+
+    /// The text of the attribute group of the function that has the `entry_point` attribute, or
+    /// an empty string if it has none.
+    fn attributes(&self, function: &Function<'_>) -> &'a str {
+        function
+            .attribute_groups
+            .iter()
+            .filter_map(|group| self.attribute_groups.get(group).copied())
+            .find(|content| content.contains("\"entry_point\""))
+            .unwrap_or_default()
+    }
+
+    fn is_entry_point(&self, function: &Function<'_>) -> bool {
+        self.attributes(function).contains("\"entry_point\"")
+    }
+}
+
+fn parse_define(text: &str, line: usize) -> Option<Function<'_>> {
+    let rest = text.strip_prefix("define ")?;
+    let (_, rest) = rest.split_once('@')?;
+    let (name, rest) = rest.split_once('(')?;
+    let (params, rest) = rest.split_once(')')?;
+    let attribute_groups = rest
+        .split_whitespace()
+        .filter(|token| token.starts_with('#'))
+        .collect();
+    Some(Function {
+        name: name.trim_matches('"'),
+        line,
+        params,
+        attribute_groups,
+        body: Vec::new(),
+    })
+}
@@ -0,0 +1,226 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{check, declared_profile};
+use crate::{codegen::get_qir, target::Profile};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::SourceMap;
+
+const BASE_MODULE: &str = indoc! {r#"
+    %Result = type opaque
+    %Qubit = type opaque
+
+    declare void @__quantum__qis__h__body(%Qubit*)
+
+    declare void @__quantum__qis__cx__body(%Qubit*, %Qubit*)
+
+    declare void @__quantum__qis__m__body(%Qubit*, %Result*) #1
+
+    declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+    define void @ENTRYPOINT__main() #0 {
+    block_0:
+      call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+      call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*))
+      call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+      call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+      ret void
+    }
+
+    attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="2" "required_num_results"="1" }
+    attributes #1 = { "irreversible" }
+
+    ; module flags
+
+    !llvm.module.flags = !{!0, !1, !2, !3}
+
+    !0 = !{i32 1, !"qir_major_version", i32 1}
+    !1 = !{i32 7, !"qir_minor_version", i32 0}
+    !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+    !3 = !{i32 1, !"dynamic_result_management", i1 false}
+"#};
+
+const ADAPTIVE_MODULE: &str = indoc! {r#"
+    %Result = type opaque
+    %Qubit = type opaque
+
+    declare void @__quantum__qis__h__body(%Qubit*)
+
+    declare void @__quantum__qis__x__body(%Qubit*)
+
+    declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+    declare i1 @__quantum__qis__read_result__body(%Result*)
+
+    declare void @__quantum__rt__int_record_output(i64, i8*)
+
+    define void @ENTRYPOINT__main() #0 {
+    block_0:
+      call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+      call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+      %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+      br i1 %var_0, label %block_1, label %block_2
+    block_1:
+      call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+      br label %block_2
+    block_2:
+      %var_1 = zext i1 %var_0 to i64
+      %var_2 = add i64 %var_1, 1
+      call void @__quantum__rt__int_record_output(i64 %var_2, i8* null)
+      ret void
+    }
+
+    attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="2" "required_num_results"="1" }
+    attributes #1 = { "irreversible" }
+
+    ; module flags
+
+    !llvm.module.flags = !{!0, !1, !2, !3}
+
+    !0 = !{i32 1, !"qir_major_version", i32 1}
+    !1 = !{i32 7, !"qir_minor_version", i32 0}
+    !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+    !3 = !{i32 1, !"dynamic_result_management", i1 false}
+"#};
+
+#[test]
+fn base_module_conforms_to_base_profile() {
+    expect![[r#"
+        module conforms to the Base profile
+    "#]]
+    .assert_eq(&check(BASE_MODULE, Profile::Base).to_string());
+    assert!(check(BASE_MODULE, Profile::AdaptiveRI)
+        .violations
+        .iter()
+        .all(|violation| matches!(violation.kind, super::ViolationKind::ProfileMismatch(..))));
+}
+
+#[test]
+fn adaptive_module_conforms_to_adaptive_profile() {
+    expect![[r#"
+        module conforms to the Adaptive_RI profile
+    "#]]
+    .assert_eq(&check(ADAPTIVE_MODULE, Profile::AdaptiveRI).to_string());
+}
+
+#[test]
+fn adaptive_module_violates_base_profile() {
+    expect![[r#"
+        module does not conform to the Base profile:
+          line 14: entry point declares the `adaptive_profile` profile
+          line 18: calls to `__quantum__qis__read_result__body` are not supported by the profile
+          line 19: conditional branches are not supported by the profile
+          line 24: `zext` instructions are not supported by the profile
+          line 25: `add` instructions are not supported by the profile
+          line 26: calls to `__quantum__rt__int_record_output` are not supported by the profile
+    "#]]
+    .assert_eq(&check(ADAPTIVE_MODULE, Profile::Base).to_string());
+}
+
+#[test]
+fn unsupported_adaptive_features_are_listed() {
+    let qir = indoc! {r#"
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          %var_0 = fadd double 1.0, 2.0
+          call void @__quantum__qis__reset__body(%Qubit* null)
+          call void @__quantum__rt__qubit_allocate()
+          br label %block_0
+        }
+
+        define void @helper(i64 %n) {
+          ret void
+        }
+
+        attributes #0 = { "entry_point" "qir_profiles"="adaptive_profile" }
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 1, !"dynamic_qubit_management", i1 true}
+    "#};
+    expect![[r#"
+        module does not conform to the Adaptive_RI profile:
+          line 3: `fadd` instructions are not supported by the profile
+          line 5: calls to `__quantum__rt__qubit_allocate` are not supported by the profile
+          line 6: branch to `block_0` goes backwards, which is not supported by the profile
+          line 9: `helper` is defined, but the profile only supports defining the entry point
+          line 16: module flag `dynamic_qubit_management` must be `false` because the profile does not support it
+          entry point does not have the `output_labeling_schema` attribute
+          entry point does not have the `required_num_qubits` attribute
+          entry point does not have the `required_num_results` attribute
+          module does not have the `qir_minor_version` module flag
+          module does not have the `dynamic_result_management` module flag
+    "#]]
+    .assert_eq(&check(qir, Profile::AdaptiveRI).to_string());
+}
+
+#[test]
+fn entry_points_must_be_unique_and_take_no_parameters() {
+    let qir = indoc! {r#"
+        define void @first(i64 %n) #0 {
+          ret void
+        }
+
+        define void @second() #0 {
+          ret void
+        }
+
+        attributes #0 = { "entry_point" }
+    "#};
+    let violations = check(qir, Profile::Unrestricted)
+        .violations
+        .into_iter()
+        .filter(|violation| violation.line.is_some())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    expect![[r#"
+        [
+            "line 1: entry point `first` must not take parameters",
+            "line 5: `second` is another entry point, but only one is allowed",
+        ]
+    "#]]
+    .assert_debug_eq(&violations);
+}
+
+#[test]
+fn module_without_entry_point_is_reported() {
+    expect![[r#"
+        module does not conform to the Base profile:
+          no function has the `entry_point` attribute
+          module does not have the `qir_major_version` module flag
+          module does not have the `qir_minor_version` module flag
+          module does not have the `dynamic_qubit_management` module flag
+          module does not have the `dynamic_result_management` module flag
+    "#]]
+    .assert_eq(&check("", Profile::Base).to_string());
+}
+
+#[test]
+fn declared_profile_is_read_from_entry_point() {
+    assert_eq!(declared_profile(BASE_MODULE), Some(Profile::Base));
+    assert_eq!(declared_profile(ADAPTIVE_MODULE), Some(Profile::AdaptiveRI));
+    assert_eq!(declared_profile(""), None);
+}
+
+#[test]
+fn generated_qir_conforms_to_its_profile() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[2];
+                H(qs[0]);
+                CNOT(qs[0], qs[1]);
+                [MResetZ(qs[0]), MResetZ(qs[1])]
+            }
+        }
+    "};
+    for profile in [Profile::Base, Profile::AdaptiveRI] {
+        let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+        let qir = get_qir(sources, LanguageFeatures::default(), profile.into())
+            .expect("the program should generate QIR");
+        let report = check(&qir, profile);
+        assert!(report.passed(), "{report}");
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! An index of where the items and locals of a package store are declared and used, for
+//! go-to-definition and find-all-references. Uses in one package of items declared in another
+//! are found by indexing the packages together.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_ast::ast::NodeId;
+use qsc_data_structures::span::Span;
+use qsc_frontend::{
+    compile::PackageStore,
+    lookup::{self, Target},
+};
+use qsc_hir::hir::{ItemId, PackageId};
+use rustc_hash::FxHashMap;
+
+/// A declaration or use of a name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Reference {
+    pub package: PackageId,
+    /// The span of the name, relative to the package's source map.
+    pub span: Span,
+    pub is_declaration: bool,
+}
+
+/// What a name refers to. Unlike [`Target`], items always have a package, so that the same item
+/// has the same key in every package.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Item(ItemId),
+    Local(PackageId, NodeId),
+}
+
+/// An index of the declarations and uses of the names in a package store.
+#[derive(Debug, Default)]
+pub struct ReferenceIndex {
+    references: FxHashMap<Key, Vec<Reference>>,
+}
+
+impl ReferenceIndex {
+    /// Indexes every package in the store.
+    #[must_use]
+    pub fn new(store: &PackageStore) -> Self {
+        let mut references = FxHashMap::<Key, Vec<Reference>>::default();
+        for (package, unit) in store {
+            for found in lookup::names(unit) {
+                let Some(key) = key(package, found.target) else {
+                    continue;
+                };
+                references.entry(key).or_default().push(Reference {
+                    package,
+                    span: found.span,
+                    is_declaration: found.is_declaration,
+                });
+            }
+        }
+        for references in references.values_mut() {
+            references.sort_unstable();
+            references.dedup();
+        }
+        Self { references }
+    }
+
+    /// Where the item is declared, if it is declared in source. The item's package must be set.
+    #[must_use]
+    pub fn definition_of(&self, item: ItemId) -> Option<Reference> {
+        declaration(self.references.get(&Key::Item(item))?)
+    }
+
+    /// The uses of the item in every indexed package, ordered by package and then by span. The
+    /// item's package must be set.
+    #[must_use]
+    pub fn references_to(&self, item: ItemId) -> Vec<Reference> {
+        uses(
+            self.references
+                .get(&Key::Item(item))
+                .map_or(&[], Vec::as_slice),
+        )
+    }
+
+    /// Where the name at the offset is declared, which can be in another package. The offset
+    /// is relative to the package's source map, and an offset at either end of a name is part of
+    /// the name. A name that is itself a declaration is its own definition.
+    #[must_use]
+    pub fn definition(
+        &self,
+        store: &PackageStore,
+        package: PackageId,
+        offset: u32,
+    ) -> Option<Reference> {
+        declaration(self.references.get(&key_at(store, package, offset)?)?)
+    }
+
+    /// The uses of what the name at the offset refers to, found like [`Self::definition`],
+    /// ordered by package and then by span. The declaration is included first when
+    /// `include_declaration` is set.
+    #[must_use]
+    pub fn references(
+        &self,
+        store: &PackageStore,
+        package: PackageId,
+        offset: u32,
+        include_declaration: bool,
+    ) -> Vec<Reference> {
+        let Some(references) =
+            key_at(store, package, offset).and_then(|key| self.references.get(&key))
+        else {
+            return Vec::new();
+        };
+        let declaration = declaration(references).filter(|_| include_declaration);
+        declaration.into_iter().chain(uses(references)).collect()
+    }
+}
+
+fn key_at(store: &PackageStore, package: PackageId, offset: u32) -> Option<Key> {
+    let found = lookup::lookup(store.get(package)?, offset)?;
+    key(package, found.target)
+}
+
+fn key(package: PackageId, target: Target) -> Option<Key> {
+    match target {
+        Target::Item(item) => Some(Key::Item(ItemId {
+            package: Some(item.package.unwrap_or(package)),
+            item: item.item,
+        })),
+        Target::Local(node) => Some(Key::Local(package, node)),
+        // Type parameters are numbered within their callable, so they do not identify a
+        // declaration on their own.
+        Target::Param(_) => None,
+    }
+}
+
+fn declaration(references: &[Reference]) -> Option<Reference> {
+    references
+        .iter()
+        .find(|reference| reference.is_declaration)
+        .copied()
+}
+
+fn uses(references: &[Reference]) -> Vec<Reference> {
+    references
+        .iter()
+        .filter(|reference| !reference.is_declaration)
+        .copied()
+        .collect()
+}
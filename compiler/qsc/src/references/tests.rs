@@ -0,0 +1,176 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Reference;
+use crate::{incremental::Compiler, PackageType, SourceMap};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_hir::hir::PackageId;
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        function Double(x : Int) : Int { x * 2 }
+        function Quadruple(x : Int) : Int { Double(Double(x)) }
+    }
+"};
+
+const FRAGMENTS: &str = "let y = Test.Double(Length([1, 2]));";
+
+fn compiler() -> Compiler {
+    let mut compiler = Compiler::new(
+        true,
+        SourceMap::new([("test.qs".into(), SOURCE.into())], None),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("compiler should be created");
+    let increment = compiler
+        .compile_fragments_fail_fast("fragments", FRAGMENTS)
+        .expect("fragments should compile");
+    compiler.update(increment);
+    compiler
+}
+
+/// The offset of the first occurrence of the text in the named source of the package.
+fn offset(compiler: &Compiler, package: PackageId, source: &str, text: &str) -> u32 {
+    let source = compiler
+        .package_store()
+        .get(package)
+        .and_then(|unit| unit.sources.find_by_name(source))
+        .expect("source should exist");
+    let index = source.contents.find(text).expect("text should occur");
+    source.offset + u32::try_from(index).expect("offset should fit into u32")
+}
+
+/// The text of each reference.
+fn texts(compiler: &Compiler, references: &[Reference]) -> Vec<String> {
+    references
+        .iter()
+        .map(|reference| {
+            let unit = compiler
+                .package_store()
+                .get(reference.package)
+                .expect("package should exist");
+            let source = unit
+                .sources
+                .find_by_offset(reference.span.lo)
+                .expect("source should exist");
+            source.contents[reference.span - source.offset].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn definition_is_in_another_package() {
+    let compiler = compiler();
+    let index = compiler.references();
+    let use_site = offset(&compiler, compiler.package_id(), "fragments", "Double");
+    let definition = index
+        .definition(compiler.package_store(), compiler.package_id(), use_site)
+        .expect("definition should be found");
+    assert_eq!(definition.package, compiler.source_package_id());
+    assert!(definition.is_declaration);
+    assert_eq!(
+        definition.span.lo,
+        offset(&compiler, compiler.source_package_id(), "test.qs", "Double")
+    );
+}
+
+#[test]
+fn references_are_found_across_packages() {
+    let compiler = compiler();
+    let index = compiler.references();
+    let declaration = offset(&compiler, compiler.source_package_id(), "test.qs", "Double");
+    let references = index.references(
+        compiler.package_store(),
+        compiler.source_package_id(),
+        declaration,
+        true,
+    );
+    assert_eq!(texts(&compiler, &references), ["Double"; 4]);
+    assert!(references[0].is_declaration);
+    assert_eq!(
+        references
+            .iter()
+            .map(|reference| reference.package)
+            .collect::<Vec<_>>(),
+        [
+            compiler.source_package_id(),
+            compiler.source_package_id(),
+            compiler.source_package_id(),
+            compiler.package_id(),
+        ]
+    );
+
+    let without_declaration = index.references(
+        compiler.package_store(),
+        compiler.source_package_id(),
+        declaration,
+        false,
+    );
+    assert_eq!(without_declaration, references[1..]);
+}
+
+#[test]
+fn references_to_library_items_include_user_code() {
+    let compiler = compiler();
+    let index = compiler.references();
+    let use_site = offset(&compiler, compiler.package_id(), "fragments", "Length");
+    let definition = index
+        .definition(compiler.package_store(), compiler.package_id(), use_site)
+        .expect("definition should be found");
+    assert_ne!(definition.package, compiler.package_id());
+
+    let references = index.references(
+        compiler.package_store(),
+        compiler.package_id(),
+        use_site,
+        false,
+    );
+    assert!(references.iter().any(
+        |reference| reference.package == compiler.package_id() && reference.span.lo == use_site
+    ));
+}
+
+#[test]
+fn locals_are_indexed_within_their_package() {
+    let compiler = compiler();
+    let index = compiler.references();
+    let parameter = offset(&compiler, compiler.source_package_id(), "test.qs", "x :");
+    let references = index.references(
+        compiler.package_store(),
+        compiler.source_package_id(),
+        parameter,
+        true,
+    );
+    assert_eq!(texts(&compiler, &references), ["x", "x"]);
+}
+
+#[test]
+fn offset_outside_of_names_has_no_references() {
+    let compiler = compiler();
+    let index = compiler.references();
+    let keyword = offset(
+        &compiler,
+        compiler.source_package_id(),
+        "test.qs",
+        "namespace",
+    );
+    assert_eq!(
+        index.definition(
+            compiler.package_store(),
+            compiler.source_package_id(),
+            keyword
+        ),
+        None
+    );
+    assert!(index
+        .references(
+            compiler.package_store(),
+            compiler.source_package_id(),
+            keyword,
+            true
+        )
+        .is_empty());
+}
@@ -0,0 +1,382 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Renderers for errors, call stacks and state dumps, producing either ANSI-colored text for
+//! terminals or HTML that is safe to embed in a page, for circuits, producing SVG, and for large
+//! classical results, producing aligned text tables.
+
+#[cfg(test)]
+mod tests;
+
+use crate::interpret::{Error, Value};
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_circuit::{Circuit, Operation};
+use qsc_eval::state::{fmt_basis_state_label, fmt_complex, format_state_id, get_phase};
+use std::fmt::Write;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders the error, preceded by its call stack if it has one, with ANSI colors.
+#[must_use]
+pub fn error_to_ansi(error: &Error) -> String {
+    let mut output = String::new();
+    if let Some(stack_trace) = error.stack_trace() {
+        output.push_str(&call_stack_to_ansi(stack_trace));
+    }
+    output.push_str(&render_report(error, GraphicalTheme::unicode()));
+    output
+}
+
+/// Renders the error, followed by its call stack if it has one, as HTML. All text taken from
+/// the error and the sources is escaped.
+#[must_use]
+pub fn error_to_html(error: &Error) -> String {
+    let mut output = format!(
+        "<pre class=\"qs-error\">{}</pre>",
+        escape_html(&render_report(error, GraphicalTheme::unicode_nocolor()))
+    );
+    if let Some(stack_trace) = error.stack_trace() {
+        output.push_str(&call_stack_to_html(stack_trace));
+    }
+    output
+}
+
+/// Renders a call stack, as formatted by the interpreter, with the frames dimmed.
+#[must_use]
+pub fn call_stack_to_ansi(stack_trace: &str) -> String {
+    stack_trace.lines().fold(String::new(), |mut output, line| {
+        if line.starts_with(' ') {
+            let _ = writeln!(output, "{DIM}{line}{RESET}");
+        } else {
+            let _ = writeln!(output, "{BOLD}{line}{RESET}");
+        }
+        output
+    })
+}
+
+/// Renders a call stack, as formatted by the interpreter, as HTML.
+#[must_use]
+pub fn call_stack_to_html(stack_trace: &str) -> String {
+    format!(
+        "<pre class=\"qs-call-stack\">{}</pre>",
+        escape_html(stack_trace)
+    )
+}
+
+/// Renders a state dump with ANSI colors, one basis state per line along with its measurement
+/// probability and phase.
+#[must_use]
+pub fn state_to_ansi(state: &[(BigUint, Complex64)], qubit_count: usize) -> String {
+    state.iter().fold(
+        format!("{BOLD}STATE:{RESET}"),
+        |mut output, (id, amplitude)| {
+            let _ = write!(
+                output,
+                "\n{CYAN}{}{RESET}: {} {DIM}({:.4}%, phase {:.4}){RESET}",
+                format_state_id(id, qubit_count),
+                fmt_complex(amplitude),
+                amplitude.norm().powi(2) * 100.0,
+                get_phase(amplitude),
+            );
+            output
+        },
+    )
+}
+
+/// Renders a state dump as plain text, one basis state per line along with its measurement
+/// probability and phase.
+#[must_use]
+pub fn state_to_text(state: &[(BigUint, Complex64)], qubit_count: usize) -> String {
+    state
+        .iter()
+        .fold("STATE:".to_string(), |mut output, (id, amplitude)| {
+            let _ = write!(
+                output,
+                "\n{}: {} ({:.4}%, phase {:.4})",
+                format_state_id(id, qubit_count),
+                fmt_complex(amplitude),
+                amplitude.norm().powi(2) * 100.0,
+                get_phase(amplitude),
+            );
+            output
+        })
+}
+
+/// Renders a state dump as an HTML table, one basis state per row along with its measurement
+/// probability and phase.
+#[must_use]
+pub fn state_to_html(state: &[(BigUint, Complex64)], qubit_count: usize) -> String {
+    format!(
+        include_str!("render/state_header_template.html"),
+        state
+            .iter()
+            .fold(String::new(), |mut output, (id, amplitude)| {
+                let probability = amplitude.norm().powi(2) * 100.0;
+                let _ = write!(
+                    output,
+                    include_str!("render/state_row_template.html"),
+                    fmt_basis_state_label(id, qubit_count),
+                    fmt_complex(amplitude),
+                    probability,
+                    probability,
+                    get_phase(amplitude),
+                    get_phase(amplitude)
+                );
+                output
+            })
+    )
+}
+
+/// Renders an array or tuple as an aligned text table, one row per element, or returns `None`
+/// for other values. Arrays of tuples and arrays of arrays get a column per item, and other
+/// values a single `Value` column, with the index of each element in the first column. Columns
+/// of numbers are right-aligned. If there are more than `max_rows` elements, only the first and
+/// last ones are shown, separated by a line counting the rest.
+#[must_use]
+pub fn value_to_table(value: &Value, max_rows: usize) -> Option<String> {
+    let items: &[Value] = match value {
+        Value::Array(items) => items,
+        Value::Tuple(items) => items,
+        _ => return None,
+    };
+    if items.is_empty() {
+        return None;
+    }
+
+    let nested = |item: &Value| match item {
+        Value::Array(inner) => Some(inner.to_vec()),
+        Value::Tuple(inner) => Some(inner.to_vec()),
+        _ => None,
+    };
+    let tabular =
+        matches!(value, Value::Array(_)) && items.iter().all(|item| nested(item).is_some());
+    let rows = if tabular {
+        items.iter().filter_map(nested).collect::<Vec<_>>()
+    } else {
+        items.iter().map(|item| vec![item.clone()]).collect()
+    };
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let mut header = vec!["#".to_string()];
+    if tabular {
+        header.extend((0..columns).map(|column| column.to_string()));
+    } else {
+        header.push("Value".to_string());
+    }
+
+    let shown = if rows.len() > max_rows {
+        let head = max_rows.div_ceil(2);
+        (0..head)
+            .chain(rows.len() - (max_rows - head)..rows.len())
+            .collect::<Vec<_>>()
+    } else {
+        (0..rows.len()).collect()
+    };
+
+    let is_number =
+        |value: &Value| matches!(value, Value::Int(_) | Value::BigInt(_) | Value::Double(_));
+    let mut right_aligned = vec![true; header.len()];
+    let mut cells = Vec::new();
+    for &index in &shown {
+        let mut row = vec![index.to_string()];
+        for column in 0..columns {
+            match rows[index].get(column) {
+                Some(value) => {
+                    right_aligned[column + 1] &= is_number(value);
+                    row.push(value.to_string());
+                }
+                None => row.push(String::new()),
+            }
+        }
+        cells.push((index, row));
+    }
+    let mut widths = header
+        .iter()
+        .map(|cell| cell.chars().count())
+        .collect::<Vec<_>>();
+    for (_, row) in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let write_row = |output: &mut String, row: &[String]| {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .zip(&right_aligned)
+            .map(|((cell, &width), &right)| {
+                if right {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(output, "{}", line.trim_end());
+    };
+    let mut output = String::new();
+    write_row(&mut output, &header);
+    write_row(
+        &mut output,
+        &widths
+            .iter()
+            .map(|&width| "-".repeat(width))
+            .collect::<Vec<_>>(),
+    );
+    let mut next = 0;
+    for (index, row) in &cells {
+        if *index > next {
+            let _ = writeln!(output, "... {} more rows", index - next);
+        }
+        write_row(&mut output, row);
+        next = index + 1;
+    }
+    if rows.len() > next {
+        let _ = writeln!(output, "... {} more rows", rows.len() - next);
+    }
+    Some(output)
+}
+
+const WIRE_SPACING: usize = 40;
+const COLUMN_WIDTH: usize = 60;
+const GATE_SIZE: usize = 30;
+const LABEL_WIDTH: usize = 50;
+
+/// Renders a circuit as a standalone SVG image. Each qubit is a horizontal wire, and operations
+/// are placed left to right in the first column where none of the wires they span are in use.
+/// Controlled operations show a dot on each control qubit, and measurements a box labeled `M`.
+/// Operations with children are drawn as a single box labeled with their name.
+#[must_use]
+pub fn circuit_to_svg(circuit: &Circuit) -> String {
+    let wire_y = |q_id: usize| WIRE_SPACING / 2 + q_id * WIRE_SPACING;
+    let column_x = |column: usize| LABEL_WIDTH + COLUMN_WIDTH / 2 + column * COLUMN_WIDTH;
+
+    let mut next_column = vec![0; circuit.qubits.len()];
+    let mut body = String::new();
+    for operation in &circuit.operations {
+        let (boxes, dots) = operation_qubits(operation);
+        let qubits = boxes.iter().chain(&dots).copied();
+        let (Some(lo), Some(hi)) = (qubits.clone().min(), qubits.max()) else {
+            continue;
+        };
+        if hi >= next_column.len() {
+            next_column.resize(hi + 1, 0);
+        }
+        let column = next_column[lo..=hi]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default();
+        next_column[lo..=hi].fill(column + 1);
+
+        let x = column_x(column);
+        if lo != hi {
+            let _ = write!(
+                body,
+                "<line class=\"qs-connector\" x1=\"{x}\" y1=\"{}\" x2=\"{x}\" y2=\"{}\" stroke=\"black\"/>",
+                wire_y(lo),
+                wire_y(hi)
+            );
+        }
+        for q_id in dots {
+            let _ = write!(
+                body,
+                "<circle class=\"qs-control\" cx=\"{x}\" cy=\"{}\" r=\"5\" fill=\"black\"/>",
+                wire_y(q_id)
+            );
+        }
+        let label = escape_html(&gate_label(operation));
+        for q_id in boxes {
+            let _ = write!(
+                body,
+                "<rect class=\"qs-gate\" x=\"{}\" y=\"{}\" width=\"{GATE_SIZE}\" height=\"{GATE_SIZE}\" fill=\"white\" stroke=\"black\"/>\
+                 <text x=\"{x}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{label}</text>",
+                x - GATE_SIZE / 2,
+                wire_y(q_id) - GATE_SIZE / 2,
+                wire_y(q_id)
+            );
+        }
+    }
+
+    let width = LABEL_WIDTH + next_column.iter().copied().max().unwrap_or_default() * COLUMN_WIDTH;
+    let height = next_column.len() * WIRE_SPACING;
+    let mut wires = String::new();
+    for q_id in 0..next_column.len() {
+        let y = wire_y(q_id);
+        let _ = write!(
+            wires,
+            "<text x=\"5\" y=\"{y}\" dominant-baseline=\"middle\">q{q_id}</text>\
+             <line class=\"qs-wire\" x1=\"{LABEL_WIDTH}\" y1=\"{y}\" x2=\"{width}\" y2=\"{y}\" stroke=\"black\"/>"
+        );
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"qs-circuit\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"14\">{wires}{body}</svg>"
+    )
+}
+
+/// Returns the qubits an operation draws a box on and the qubits it draws a control dot on.
+/// Measurements refer to the measured qubit as a control and to the result as a target.
+fn operation_qubits(operation: &Operation) -> (Vec<usize>, Vec<usize>) {
+    let quantum = |controls: bool| {
+        let registers = if controls {
+            &operation.controls
+        } else {
+            &operation.targets
+        };
+        registers
+            .iter()
+            .filter(|register| register.c_id.is_none())
+            .map(|register| register.q_id)
+            .collect::<Vec<_>>()
+    };
+    if operation.is_measurement {
+        (quantum(true), Vec::new())
+    } else {
+        (quantum(false), quantum(true))
+    }
+}
+
+fn gate_label(operation: &Operation) -> String {
+    if operation.is_measurement {
+        return "M".to_string();
+    }
+    let mut label = operation.gate.clone();
+    if operation.is_adjoint {
+        label.push('†');
+    }
+    if let Some(args) = &operation.display_args {
+        let _ = write!(label, "({args})");
+    }
+    label
+}
+
+/// Escapes the characters that have special meaning in HTML text and attribute values.
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_report(diagnostic: &dyn Diagnostic, theme: GraphicalTheme) -> String {
+    let mut output = String::new();
+    GraphicalReportHandler::new_themed(theme)
+        .render_report(&mut output, diagnostic)
+        .expect("writing to string should succeed");
+    output
+}
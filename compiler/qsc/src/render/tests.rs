@@ -0,0 +1,218 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    call_stack_to_ansi, call_stack_to_html, circuit_to_svg, error_to_ansi, error_to_html,
+    escape_html, state_to_ansi, state_to_html, state_to_text, value_to_table,
+};
+use crate::interpret::{CircuitEntryPoint, Error, Interpreter, Value};
+use expect_test::expect;
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+use std::rc::Rc;
+
+fn errors(line: &str) -> Vec<Error> {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let mut sink = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut sink);
+    interpreter
+        .eval_fragments(&mut receiver, line)
+        .expect_err("line should fail")
+}
+
+#[test]
+fn escape_html_escapes_special_characters() {
+    assert_eq!(
+        escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+    );
+}
+
+#[test]
+fn compile_error_html_escapes_source() {
+    let errors = errors("let x = <Undefined>;");
+    let html = error_to_html(&errors[0]);
+    assert!(html.starts_with("<pre class=\"qs-error\">"), "{html}");
+    assert!(html.contains("&lt;Undefined&gt;"), "{html}");
+    assert!(!html.contains("<Undefined>"), "{html}");
+    assert!(!html.contains('\x1b'), "{html}");
+}
+
+#[test]
+fn runtime_error_renders_call_stack() {
+    let errors = errors(
+        "function Fails() : Unit { fail \"<boom>\"; } function Calls() : Unit { Fails(); } Calls()",
+    );
+    let error = &errors[0];
+    assert!(error.stack_trace().is_some());
+
+    let ansi = error_to_ansi(error);
+    assert!(ansi.contains("\x1b["), "{ansi}");
+    assert!(ansi.contains("Fails"), "{ansi}");
+
+    let html = error_to_html(error);
+    assert!(html.contains("<pre class=\"qs-call-stack\">"), "{html}");
+    assert!(html.contains("&lt;boom&gt;"), "{html}");
+}
+
+#[test]
+fn call_stack_ansi_dims_frames() {
+    assert_eq!(
+        call_stack_to_ansi("Error: failed\nCall stack:\n    at Foo in line_0\n"),
+        "\x1b[1mError: failed\x1b[0m\n\x1b[1mCall stack:\x1b[0m\n\x1b[2m    at Foo in line_0\x1b[0m\n"
+    );
+    assert_eq!(
+        call_stack_to_html("    at <lambda> in line_0\n"),
+        "<pre class=\"qs-call-stack\">    at &lt;lambda&gt; in line_0\n</pre>"
+    );
+}
+
+#[test]
+fn state_renders_as_ansi_and_html() {
+    let amplitude = std::f64::consts::FRAC_1_SQRT_2;
+    let state = vec![
+        (BigUint::from(0_u64), Complex64::new(amplitude, 0.0)),
+        (BigUint::from(3_u64), Complex64::new(0.0, amplitude)),
+    ];
+    assert_eq!(
+        state_to_ansi(&state, 2),
+        "\x1b[1mSTATE:\x1b[0m\n\x1b[36m|00⟩\x1b[0m: 0.7071+0.0000𝑖 \x1b[2m(50.0000%, phase 0.0000)\x1b[0m\n\x1b[36m|11⟩\x1b[0m: 0.0000+0.7071𝑖 \x1b[2m(50.0000%, phase 1.5708)\x1b[0m"
+    );
+
+    let html = state_to_html(&state, 2);
+    assert!(html.starts_with("<table>"), "{html}");
+    assert_eq!(html.matches("<tr>").count(), 3, "{html}");
+    assert!(html.contains("|11⟩"), "{html}");
+    assert!(html.contains("rotate(1.5708rad)"), "{html}");
+
+    assert_eq!(
+        state_to_text(&state, 2),
+        "STATE:\n|00⟩: 0.7071+0.0000𝑖 (50.0000%, phase 0.0000)\n|11⟩: 0.0000+0.7071𝑖 (50.0000%, phase 1.5708)"
+    );
+}
+
+#[test]
+fn circuit_renders_as_svg() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created");
+    let circuit = interpreter
+        .circuit(
+            CircuitEntryPoint::EntryExpr(
+                "{ use qs = Qubit[3]; H(qs[0]); CNOT(qs[0], qs[2]); X(qs[1]); Adjoint S(qs[1]); M(qs[2]) }"
+                    .to_string(),
+            ),
+            false,
+        )
+        .expect("circuit should be generated");
+    let svg = circuit_to_svg(&circuit);
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""),
+        "{svg}"
+    );
+    assert!(svg.ends_with("</svg>"), "{svg}");
+    assert_eq!(svg.matches("class=\"qs-wire\"").count(), 3, "{svg}");
+    assert_eq!(svg.matches("class=\"qs-control\"").count(), 1, "{svg}");
+    assert_eq!(svg.matches("class=\"qs-gate\"").count(), 5, "{svg}");
+    for label in [">H<", ">X<", ">S†<", ">M<"] {
+        assert!(svg.contains(label), "{label} is missing from {svg}");
+    }
+    // `M` shares a column with the first `X`, since they act on different wires.
+    assert!(svg.contains("width=\"290\""), "{svg}");
+}
+
+fn array(items: Vec<Value>) -> Value {
+    Value::Array(Rc::new(items))
+}
+
+fn tuple(items: Vec<Value>) -> Value {
+    Value::Tuple(items.into())
+}
+
+#[test]
+fn array_of_tuples_renders_as_table() {
+    let value = array(
+        [(1, "one"), (20, "twenty"), (300, "three hundred")]
+            .into_iter()
+            .map(|(number, name)| tuple(vec![Value::Int(number), Value::String(name.into())]))
+            .collect(),
+    );
+    expect![[r#"
+        #    0  1
+        -  ---  -------------
+        0    1  one
+        1   20  twenty
+        2  300  three hundred
+    "#]]
+    .assert_eq(&value_to_table(&value, 10).expect("array should render as table"));
+}
+
+#[test]
+fn jagged_array_renders_with_empty_cells() {
+    let value = array(vec![
+        array(vec![Value::Double(0.5), Value::Double(1.0)]),
+        array(vec![Value::Double(2.25)]),
+    ]);
+    expect![[r#"
+        #     0    1
+        -  ----  ---
+        0   0.5  1.0
+        1  2.25
+    "#]]
+    .assert_eq(&value_to_table(&value, 10).expect("array should render as table"));
+}
+
+#[test]
+fn long_array_is_truncated() {
+    let value = array((0..10).map(Value::Int).collect());
+    expect![[r#"
+        #  Value
+        -  -----
+        0      0
+        1      1
+        ... 7 more rows
+        9      9
+    "#]]
+    .assert_eq(&value_to_table(&value, 3).expect("array should render as table"));
+    expect![[r#"
+        #  Value
+        -  -----
+        0      0
+        ... 9 more rows
+    "#]]
+    .assert_eq(&value_to_table(&value, 1).expect("array should render as table"));
+}
+
+#[test]
+fn tuple_renders_one_row_per_item() {
+    let value = tuple(vec![Value::Int(1), array(vec![Value::Bool(true)])]);
+    expect![[r#"
+        #  Value
+        -  ------
+        0  1
+        1  [true]
+    "#]]
+    .assert_eq(&value_to_table(&value, 10).expect("tuple should render as table"));
+}
+
+#[test]
+fn scalars_do_not_render_as_table() {
+    assert_eq!(value_to_table(&Value::Int(1), 10), None);
+    assert_eq!(value_to_table(&array(Vec::new()), 10), None);
+}
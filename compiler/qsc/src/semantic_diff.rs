@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compares two versions of a package and reports the differences in their public API, such
+//! as removed items, changed callable signatures and changed specializations.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::{
+    compile::{SourceMap, StdFeatures},
+    error::WithSource,
+};
+use qsc_hir::{
+    hir::{
+        CallableDecl, CallableKind, Item, ItemKind, Package, SpecBody, SpecDecl, SpecGen,
+        Visibility,
+    },
+    ty::{FunctorSetValue, GenericParam, UdtDef, UdtDefKind},
+};
+use rustc_hash::FxHashMap;
+
+use crate::compile::{self, ErrorKind};
+
+/// A difference between two versions of a public item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// The fully qualified name of the item.
+    pub name: String,
+    /// What changed.
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The item only exists in the new version.
+    Added,
+    /// The item only exists in the old version.
+    Removed,
+    /// The item's declaration changed, including changes to parameter types, return type,
+    /// type parameters, supported functors or type definition.
+    SignatureChanged { old: String, new: String },
+    /// The set of specializations or how they are generated changed.
+    SpecializationsChanged { old: String, new: String },
+}
+
+impl ChangeKind {
+    /// Whether the change can break code that depends on the old version.
+    #[must_use]
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, ChangeKind::Added)
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added => write!(f, "added `{}`", self.name),
+            ChangeKind::Removed => write!(f, "removed `{}`", self.name),
+            ChangeKind::SignatureChanged { old, new } => {
+                write!(f, "changed `{}` from `{old}` to `{new}`", self.name)
+            }
+            ChangeKind::SpecializationsChanged { old, new } => write!(
+                f,
+                "changed specializations of `{}` from `{old}` to `{new}`",
+                self.name
+            ),
+        }
+    }
+}
+
+/// Compiles both versions of a package against the standard library and compares their
+/// public items.
+///
+/// # Errors
+///
+/// Returns the compilation errors if either version fails to compile.
+pub fn compare(
+    old: SourceMap,
+    new: SourceMap,
+    capabilities: TargetCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> Result<Vec<Change>, Vec<compile::Error>> {
+    let (store, std_id) = compile::shared_store(capabilities, Some(StdFeatures::all()));
+    let std_id = std_id.expect("store should have std");
+
+    let compile_version = |sources: SourceMap| {
+        // Passes are not run, so that generated specializations are still recorded as
+        // generators rather than as their expanded bodies.
+        let unit = qsc_frontend::compile::compile(
+            &store,
+            &[std_id],
+            sources,
+            capabilities,
+            language_features,
+        );
+        let errors = unit
+            .errors
+            .iter()
+            .map(|error| WithSource::from_map(&unit.sources, ErrorKind::Frontend(error.clone())))
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(unit)
+        } else {
+            Err(errors)
+        }
+    };
+
+    let old = compile_version(old)?;
+    let new = compile_version(new)?;
+    Ok(compare_packages(&old.package, &new.package))
+}
+
+/// Compares the public items of two versions of a package. Changes are ordered by item name.
+#[must_use]
+pub fn compare_packages(old: &Package, new: &Package) -> Vec<Change> {
+    let old_items = public_items(old);
+    let new_items = public_items(new);
+
+    let mut changes = Vec::new();
+    for (name, old_item) in &old_items {
+        match new_items.get(name) {
+            None => changes.push(Change {
+                name: name.clone(),
+                kind: ChangeKind::Removed,
+            }),
+            Some(new_item) => {
+                changes.extend(compare_items(old_item, new_item).map(|kind| Change {
+                    name: name.clone(),
+                    kind,
+                }))
+            }
+        }
+    }
+    for name in new_items.keys() {
+        if !old_items.contains_key(name) {
+            changes.push(Change {
+                name: name.clone(),
+                kind: ChangeKind::Added,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+/// Collects the public items declared directly in a namespace, keyed by fully qualified name.
+fn public_items(package: &Package) -> FxHashMap<String, &Item> {
+    let mut items = FxHashMap::default();
+    for item in package.items.values() {
+        let ItemKind::Namespace(namespace, members) = &item.kind else {
+            continue;
+        };
+        for member in members.iter().filter_map(|id| package.items.get(*id)) {
+            if member.visibility == Visibility::Internal {
+                continue;
+            }
+            let name = match &member.kind {
+                ItemKind::Callable(decl) => &decl.name.name,
+                ItemKind::Ty(name, _) => &name.name,
+                ItemKind::Namespace(..) => continue,
+            };
+            items.insert(format!("{}.{name}", namespace.name()), member);
+        }
+    }
+    items
+}
+
+fn compare_items(old: &Item, new: &Item) -> Option<ChangeKind> {
+    let (old_sig, new_sig) = (signature(old), signature(new));
+    if old_sig != new_sig {
+        return Some(ChangeKind::SignatureChanged {
+            old: old_sig,
+            new: new_sig,
+        });
+    }
+
+    if let (ItemKind::Callable(old), ItemKind::Callable(new)) = (&old.kind, &new.kind) {
+        let (old_specs, new_specs) = (specializations(old), specializations(new));
+        if old_specs != new_specs {
+            return Some(ChangeKind::SpecializationsChanged {
+                old: old_specs,
+                new: new_specs,
+            });
+        }
+    }
+
+    None
+}
+
+fn signature(item: &Item) -> String {
+    match &item.kind {
+        ItemKind::Callable(decl) => callable_signature(decl),
+        ItemKind::Ty(name, udt) => format!("newtype {} = {}", name.name, udt_def(&udt.definition)),
+        ItemKind::Namespace(name, _) => format!("namespace {}", name.name()),
+    }
+}
+
+/// The callable's declaration without parameter names, since parameters are positional and
+/// renaming them does not affect callers.
+fn callable_signature(decl: &CallableDecl) -> String {
+    let kind = match decl.kind {
+        CallableKind::Function => "function",
+        CallableKind::Operation => "operation",
+    };
+    let ty_params = decl
+        .generics
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Ty(name) => Some(name.name.to_string()),
+            GenericParam::Functor(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let ty_params = if ty_params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", ty_params.join(", "))
+    };
+    let input = decl.input.ty.display();
+    let input = if input.starts_with('(') {
+        input
+    } else {
+        format!("({input})")
+    };
+    let functors = if decl.functors == FunctorSetValue::Empty {
+        String::new()
+    } else {
+        format!(" is {}", decl.functors)
+    };
+    format!(
+        "{kind} {}{ty_params}{input} : {}{functors}",
+        decl.name.name,
+        decl.output.display()
+    )
+}
+
+fn udt_def(def: &UdtDef) -> String {
+    match &def.kind {
+        UdtDefKind::Field(field) => match &field.name {
+            Some(name) => format!("{name} : {}", field.ty.display()),
+            None => field.ty.display(),
+        },
+        UdtDefKind::Tuple(defs) if defs.len() == 1 => format!("({},)", udt_def(&defs[0])),
+        UdtDefKind::Tuple(defs) => {
+            let defs = defs.iter().map(udt_def).collect::<Vec<_>>();
+            format!("({})", defs.join(", "))
+        }
+    }
+}
+
+/// Summarizes which specializations a callable declares and how each one is provided, e.g.
+/// `body intrinsic; adjoint self`. Changes to the contents of explicit implementations are not
+/// included.
+fn specializations(decl: &CallableDecl) -> String {
+    [
+        ("body", Some(&decl.body)),
+        ("adjoint", decl.adj.as_ref()),
+        ("controlled", decl.ctl.as_ref()),
+        ("controlled adjoint", decl.ctl_adj.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(keyword, spec)| spec.map(|spec| spec_summary(keyword, spec)))
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
+fn spec_summary(keyword: &str, spec: &SpecDecl) -> String {
+    match &spec.body {
+        SpecBody::Gen(gen) => {
+            let gen = match gen {
+                SpecGen::Auto => "auto",
+                SpecGen::Distribute => "distribute",
+                SpecGen::Intrinsic => "intrinsic",
+                SpecGen::Invert => "invert",
+                SpecGen::Slf => "self",
+            };
+            format!("{keyword} {gen}")
+        }
+        SpecBody::Impl(..) => keyword.to_string(),
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+
+use super::{compare, Change};
+
+fn check(old: &str, new: &str) -> Vec<Change> {
+    compare(
+        SourceMap::new([("old.qs".into(), old.into())], None),
+        SourceMap::new([("new.qs".into(), new.into())], None),
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("both versions should compile")
+}
+
+fn descriptions(changes: &[Change]) -> Vec<String> {
+    changes.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn identical_packages_have_no_changes() {
+    let source = indoc! {"
+        namespace Test {
+            function F(x : Int) : Int { x }
+        }
+    "};
+    assert!(check(source, source).is_empty());
+}
+
+#[test]
+fn renamed_parameter_and_body_change_are_not_reported() {
+    let changes = check(
+        "namespace Test { function F(x : Int) : Int { x } }",
+        "namespace Test { function F(y : Int) : Int { y + 1 } }",
+    );
+    assert!(changes.is_empty(), "{changes:?}");
+}
+
+#[test]
+fn removed_and_added_items_are_reported() {
+    let changes = check(
+        "namespace Test { function F() : Unit {} }",
+        "namespace Test { function G() : Unit {} }",
+    );
+    assert_eq!(
+        descriptions(&changes),
+        vec!["removed `Test.F`", "added `Test.G`"]
+    );
+    assert!(changes[0].kind.is_breaking());
+    assert!(!changes[1].kind.is_breaking());
+}
+
+#[test]
+fn internal_items_are_ignored() {
+    let changes = check(
+        "namespace Test { internal function F() : Unit {} }",
+        "namespace Test { internal function F(x : Int) : Unit {} }",
+    );
+    assert!(changes.is_empty(), "{changes:?}");
+}
+
+#[test]
+fn parameter_type_change_is_reported() {
+    let changes = check(
+        "namespace Test { function F(x : Int) : Int { x } }",
+        "namespace Test { function F(x : Double) : Int { 0 } }",
+    );
+    assert_eq!(
+        descriptions(&changes),
+        vec!["changed `Test.F` from `function F(Int) : Int` to `function F(Double) : Int`"]
+    );
+}
+
+#[test]
+fn functor_support_change_is_reported() {
+    let changes = check(
+        "namespace Test { operation Op(q : Qubit) : Unit is Adj { body intrinsic; } }",
+        "namespace Test { operation Op(q : Qubit) : Unit { body intrinsic; } }",
+    );
+    assert_eq!(
+        descriptions(&changes),
+        vec![
+            "changed `Test.Op` from `operation Op(Qubit) : Unit is Adj` to `operation Op(Qubit) : Unit`"
+        ]
+    );
+}
+
+#[test]
+fn specialization_change_is_reported() {
+    let changes = check(
+        indoc! {"
+            namespace Test {
+                operation Op(q : Qubit) : Unit is Adj {
+                    body intrinsic;
+                    adjoint self;
+                }
+            }
+        "},
+        indoc! {"
+            namespace Test {
+                operation Op(q : Qubit) : Unit is Adj {
+                    body intrinsic;
+                    adjoint intrinsic;
+                }
+            }
+        "},
+    );
+    assert_eq!(
+        descriptions(&changes),
+        vec![
+            "changed specializations of `Test.Op` from `body intrinsic; adjoint self` to `body intrinsic; adjoint intrinsic`"
+        ]
+    );
+}
+
+#[test]
+fn newtype_field_rename_is_reported() {
+    let changes = check(
+        "namespace Test { newtype Pair = (First : Int, Second : Int); }",
+        "namespace Test { newtype Pair = (Fst : Int, Snd : Int); }",
+    );
+    assert_eq!(
+        descriptions(&changes),
+        vec![
+            "changed `Test.Pair` from `newtype Pair = (First : Int, Second : Int)` to `newtype Pair = (Fst : Int, Snd : Int)`"
+        ]
+    );
+}
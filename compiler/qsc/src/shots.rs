@@ -0,0 +1,143 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Runs many shots of a program's entry expression in parallel.
+//!
+//! Each shot gets its own simulator and random number streams, derived from the master seed
+//! and the shot's index. Which thread runs a shot therefore has no effect on its result, so
+//! the results are identical for any number of threads.
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "distributed")]
+pub mod distributed;
+
+use std::{num::NonZeroUsize, thread};
+
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_eval::{backend::SparseSim, output::GenericReceiver};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+use crate::{
+    interpret::{Error, Interpreter},
+    telemetry,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShotsConfig {
+    /// The number of shots to run.
+    pub shots: usize,
+    /// The master seed that the random number streams of every shot are derived from.
+    pub seed: u64,
+    /// The number of threads to use. Defaults to the available parallelism of the machine.
+    pub threads: Option<NonZeroUsize>,
+}
+
+/// The outcome of a single shot.
+#[derive(Debug)]
+pub struct ShotResult {
+    /// The value of the entry expression, rendered as a string since values cannot be
+    /// shared between threads, or the errors that occurred while evaluating it.
+    pub value: Result<String, Vec<Error>>,
+    /// The output written by the shot, such as messages and state dumps.
+    pub output: String,
+}
+
+/// Compiles the sources and runs their entry expression once per shot, in parallel.
+///
+/// Results are returned in shot order.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources fail to compile.
+pub fn run_shots(
+    sources: SourceMap,
+    capabilities: TargetCapabilityFlags,
+    language_features: LanguageFeatures,
+    config: ShotsConfig,
+) -> Result<Vec<ShotResult>, Vec<Error>> {
+    let new_interpreter = |sources: SourceMap| {
+        Interpreter::new(
+            true,
+            sources,
+            PackageType::Exe,
+            capabilities,
+            language_features,
+        )
+    };
+
+    // Compile once up front, so that compilation errors are reported once rather than
+    // by every thread.
+    let interpreter = new_interpreter(sources.clone())?;
+    let threads = config
+        .threads
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(config.shots)
+        .max(1);
+    if threads == 1 {
+        let mut interpreter = interpreter;
+        return Ok((0..config.shots)
+            .map(|shot| run_shot(&mut interpreter, config.seed, shot))
+            .collect());
+    }
+    drop(interpreter);
+
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|worker| {
+                let sources = sources.clone();
+                scope.spawn(move || {
+                    let mut interpreter = new_interpreter(sources).expect("sources should compile");
+                    (worker..config.shots)
+                        .step_by(threads)
+                        .map(|shot| (shot, run_shot(&mut interpreter, config.seed, shot)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("shot worker should not panic"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(shot, _)| *shot);
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+pub(crate) fn run_shot(interpreter: &mut Interpreter, seed: u64, shot: usize) -> ShotResult {
+    let span = telemetry::Span::new(telemetry::SHOT);
+    span.attribute("qsc.shot", i64::try_from(shot).unwrap_or(i64::MAX));
+    let (quantum_seed, classical_seed) = shot_seeds(seed, shot);
+    interpreter.set_quantum_seed(Some(quantum_seed));
+    interpreter.set_classical_seed(Some(classical_seed));
+
+    let mut output = Vec::new();
+    let mut receiver = GenericReceiver::new(&mut output);
+    let value = interpreter
+        .eval_entry_with_sim(&mut SparseSim::new(), &mut receiver)
+        .map(|value| interpreter.format_value(&value));
+    ShotResult {
+        value,
+        output: String::from_utf8_lossy(&output).into_owned(),
+    }
+}
+
+/// Derives the quantum and classical seeds for a shot from the master seed, so that every
+/// shot has independent random number streams.
+#[must_use]
+pub fn shot_seeds(seed: u64, shot: usize) -> (u64, u64) {
+    let stream = splitmix64(seed ^ splitmix64(shot as u64));
+    (splitmix64(stream), splitmix64(!stream))
+}
+
+/// The `SplitMix64` mixing function, which maps nearby inputs to unrelated outputs.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
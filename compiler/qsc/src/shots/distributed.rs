@@ -0,0 +1,365 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Shards shots across workers running in other processes or on other machines, and merges
+//! their results into a single histogram.
+//!
+//! Workers are given ranges of shot indices rather than shot counts, and derive the seeds of
+//! each shot the same way as [`super::run_shots`], so the merged histogram is identical to
+//! running every shot locally. A shard that fails on one worker is retried on another.
+//!
+//! Workers are reached through the [`Worker`] trait. [`TcpWorker`] and [`serve`] implement
+//! it with a line based protocol over TCP:
+//!
+//! ```text
+//! > RUN <start> <count> <seed>
+//! < VALUE <count> <value>
+//! < FAILED <count>
+//! < DONE
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+use super::run_shot;
+use crate::interpret::{Error, Interpreter};
+
+/// A contiguous range of shots to run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// The index of the first shot.
+    pub start: usize,
+    /// The number of shots.
+    pub count: usize,
+    /// The master seed that each shot's seeds are derived from.
+    pub seed: u64,
+}
+
+/// The number of times each result was seen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// The counts of each successful result, keyed by the result value.
+    pub counts: BTreeMap<String, usize>,
+    /// The number of shots that failed with a runtime error.
+    pub failures: usize,
+}
+
+impl Histogram {
+    pub fn merge(&mut self, other: Histogram) {
+        for (value, count) in other.counts {
+            *self.counts.entry(value).or_default() += count;
+        }
+        self.failures += other.failures;
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.counts.values().sum::<usize>() + self.failures
+    }
+}
+
+/// Something that can run shards of shots, such as a connection to a remote process.
+pub trait Worker: Send {
+    /// Runs every shot in the shard.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the worker could not run the shard. The shard will be retried on
+    /// another worker and this worker will not be used again.
+    fn run(&mut self, shard: Shard) -> io::Result<Histogram>;
+}
+
+#[derive(Debug)]
+pub enum DistributedError {
+    /// Every worker failed before all shards were run.
+    AllWorkersFailed {
+        /// The number of shots that were not run.
+        remaining_shots: usize,
+        /// The last error reported by a worker.
+        last_error: io::Error,
+    },
+    /// There were no workers to run shards on.
+    NoWorkers,
+}
+
+impl Display for DistributedError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DistributedError::AllWorkersFailed {
+                remaining_shots,
+                last_error,
+            } => write!(
+                f,
+                "all workers failed with {remaining_shots} shots remaining: {last_error}"
+            ),
+            DistributedError::NoWorkers => f.write_str("no workers were provided"),
+        }
+    }
+}
+
+impl std::error::Error for DistributedError {}
+
+struct Queue {
+    pending: VecDeque<Shard>,
+    in_flight: usize,
+    alive: usize,
+    histogram: Histogram,
+    last_error: Option<io::Error>,
+}
+
+/// Splits `shots` into shards of at most `shard_size` shots and runs them on the workers in
+/// parallel, merging the results.
+///
+/// # Errors
+///
+/// Returns an error if there are no workers, or if every worker fails before all shards are run.
+pub fn run_distributed(
+    workers: Vec<Box<dyn Worker>>,
+    shots: usize,
+    seed: u64,
+    shard_size: usize,
+) -> Result<Histogram, DistributedError> {
+    if workers.is_empty() {
+        return Err(DistributedError::NoWorkers);
+    }
+
+    let shard_size = shard_size.max(1);
+    let pending = (0..shots)
+        .step_by(shard_size)
+        .map(|start| Shard {
+            start,
+            count: shard_size.min(shots - start),
+            seed,
+        })
+        .collect();
+    let queue = Mutex::new(Queue {
+        pending,
+        in_flight: 0,
+        alive: workers.len(),
+        histogram: Histogram::default(),
+        last_error: None,
+    });
+    let ready = Condvar::new();
+
+    thread::scope(|scope| {
+        for mut worker in workers {
+            let queue = &queue;
+            let ready = &ready;
+            scope.spawn(move || loop {
+                let shard = {
+                    let mut state = queue.lock().expect("queue lock should not be poisoned");
+                    loop {
+                        if let Some(shard) = state.pending.pop_front() {
+                            state.in_flight += 1;
+                            break shard;
+                        }
+                        if state.in_flight == 0 {
+                            // Nothing is left to run, and no running shard can fail
+                            // and be returned to the queue.
+                            ready.notify_all();
+                            return;
+                        }
+                        state = ready
+                            .wait(state)
+                            .expect("queue lock should not be poisoned");
+                    }
+                };
+
+                let result = worker.run(shard);
+                let mut state = queue.lock().expect("queue lock should not be poisoned");
+                state.in_flight -= 1;
+                match result {
+                    Ok(histogram) => {
+                        state.histogram.merge(histogram);
+                        ready.notify_all();
+                    }
+                    Err(err) => {
+                        state.pending.push_back(shard);
+                        state.alive -= 1;
+                        state.last_error = Some(err);
+                        ready.notify_all();
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    let state = queue
+        .into_inner()
+        .expect("queue lock should not be poisoned");
+    if state.pending.is_empty() {
+        Ok(state.histogram)
+    } else {
+        Err(DistributedError::AllWorkersFailed {
+            remaining_shots: state.pending.iter().map(|shard| shard.count).sum(),
+            last_error: state
+                .last_error
+                .expect("shards should only remain if a worker failed"),
+        })
+    }
+}
+
+/// Runs every shot in the shard and counts the results.
+fn run_shard(interpreter: &mut Interpreter, shard: Shard) -> Histogram {
+    let mut histogram = Histogram::default();
+    for shot in shard.start..shard.start + shard.count {
+        match run_shot(interpreter, shard.seed, shot).value {
+            Ok(value) => *histogram.counts.entry(value).or_default() += 1,
+            Err(_) => histogram.failures += 1,
+        }
+    }
+    histogram
+}
+
+/// Connects to a worker process started with [`serve`].
+pub struct TcpWorker {
+    addr: SocketAddr,
+    timeout: Option<Duration>,
+}
+
+impl TcpWorker {
+    /// Creates a worker for the given address. If `timeout` is given, the worker fails
+    /// if it does not receive a response for that long.
+    #[must_use]
+    pub fn new(addr: SocketAddr, timeout: Option<Duration>) -> Self {
+        Self { addr, timeout }
+    }
+}
+
+impl Worker for TcpWorker {
+    fn run(&mut self, shard: Shard) -> io::Result<Histogram> {
+        let mut stream = match self.timeout {
+            Some(timeout) => TcpStream::connect_timeout(&self.addr, timeout)?,
+            None => TcpStream::connect(self.addr)?,
+        };
+        stream.set_read_timeout(self.timeout)?;
+        writeln!(stream, "RUN {} {} {}", shard.start, shard.count, shard.seed)?;
+        stream.flush()?;
+        read_histogram(BufReader::new(stream))
+    }
+}
+
+/// Serves shards from coordinators, one connection at a time, until accepting a connection
+/// fails. The sources must be the same ones the coordinator expects to be run.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources fail to compile.
+pub fn serve(
+    listener: &TcpListener,
+    sources: SourceMap,
+    capabilities: TargetCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> Result<(), Vec<Error>> {
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        capabilities,
+        language_features,
+    )?;
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            break;
+        };
+        // A coordinator that disconnects or sends a malformed request only affects its
+        // own connection.
+        let _ = serve_connection(&mut interpreter, stream);
+    }
+    Ok(())
+}
+
+fn serve_connection(interpreter: &mut Interpreter, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let shard = parse_request(&line)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed request"))?;
+    let histogram = run_shard(interpreter, shard);
+    write_histogram(stream, &histogram)
+}
+
+fn parse_request(line: &str) -> Option<Shard> {
+    let mut parts = line.trim_end().split(' ');
+    if parts.next()? != "RUN" {
+        return None;
+    }
+    let shard = Shard {
+        start: parts.next()?.parse().ok()?,
+        count: parts.next()?.parse().ok()?,
+        seed: parts.next()?.parse().ok()?,
+    };
+    parts.next().is_none().then_some(shard)
+}
+
+fn write_histogram(mut writer: impl Write, histogram: &Histogram) -> io::Result<()> {
+    for (value, count) in &histogram.counts {
+        writeln!(writer, "VALUE {count} {}", escape(value))?;
+    }
+    if histogram.failures > 0 {
+        writeln!(writer, "FAILED {}", histogram.failures)?;
+    }
+    writeln!(writer, "DONE")?;
+    writer.flush()
+}
+
+fn read_histogram(reader: impl BufRead) -> io::Result<Histogram> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed response");
+    let mut histogram = Histogram::default();
+    for line in reader.lines() {
+        let line = line?;
+        let (kind, rest) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+        match kind {
+            "VALUE" => {
+                let (count, value) = rest.split_once(' ').ok_or_else(invalid)?;
+                let count: usize = count.parse().map_err(|_| invalid())?;
+                *histogram.counts.entry(unescape(value)).or_default() += count;
+            }
+            "FAILED" => histogram.failures += rest.parse::<usize>().map_err(|_| invalid())?,
+            "DONE" => return Ok(histogram),
+            _ => return Err(invalid()),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "worker disconnected before finishing the shard",
+    ))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
@@ -0,0 +1,187 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::{
+    io,
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+
+use super::{
+    escape, parse_request, read_histogram, run_distributed, serve, unescape, write_histogram,
+    DistributedError, Histogram, Shard, TcpWorker, Worker,
+};
+use crate::shots::{run_shots, ShotsConfig};
+
+/// A worker that counts each shot under its index, so tests can check every shot ran once.
+struct FakeWorker {
+    fail_after: Option<usize>,
+    runs: Arc<AtomicUsize>,
+}
+
+impl Worker for FakeWorker {
+    fn run(&mut self, shard: Shard) -> io::Result<Histogram> {
+        let runs = self.runs.fetch_add(1, Ordering::SeqCst);
+        if self.fail_after.is_some_and(|limit| runs >= limit) {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "worker died",
+            ));
+        }
+        let mut histogram = Histogram::default();
+        for shot in shard.start..shard.start + shard.count {
+            histogram.counts.insert(shot.to_string(), 1);
+        }
+        Ok(histogram)
+    }
+}
+
+fn fake(fail_after: Option<usize>) -> Box<dyn Worker> {
+    Box::new(FakeWorker {
+        fail_after,
+        runs: Arc::new(AtomicUsize::new(0)),
+    })
+}
+
+#[test]
+fn every_shot_runs_once() {
+    let histogram = run_distributed(vec![fake(None), fake(None), fake(None)], 100, 0, 7)
+        .expect("shots should run");
+    assert_eq!(histogram.total(), 100);
+    assert!(histogram.counts.values().all(|count| *count == 1));
+}
+
+#[test]
+fn failed_shards_are_retried_on_other_workers() {
+    let histogram = run_distributed(vec![fake(Some(1)), fake(Some(0)), fake(None)], 50, 0, 5)
+        .expect("shots should run");
+    assert_eq!(histogram.total(), 50);
+    assert!(histogram.counts.values().all(|count| *count == 1));
+}
+
+#[test]
+fn all_workers_failing_is_error() {
+    let result = run_distributed(vec![fake(Some(0)), fake(Some(1))], 50, 0, 5);
+    match result {
+        Err(DistributedError::AllWorkersFailed {
+            remaining_shots, ..
+        }) => assert!(remaining_shots > 0 && remaining_shots <= 50),
+        other => panic!("expected all workers to fail, got {other:?}"),
+    }
+}
+
+#[test]
+fn no_workers_is_error() {
+    assert!(matches!(
+        run_distributed(Vec::new(), 1, 0, 1),
+        Err(DistributedError::NoWorkers)
+    ));
+}
+
+#[test]
+fn request_is_parsed() {
+    assert_eq!(
+        parse_request("RUN 10 5 42\n"),
+        Some(Shard {
+            start: 10,
+            count: 5,
+            seed: 42
+        })
+    );
+    assert_eq!(parse_request("RUN 10 5\n"), None);
+    assert_eq!(parse_request("RUN 10 5 42 1\n"), None);
+    assert_eq!(parse_request("STOP\n"), None);
+}
+
+#[test]
+fn histogram_round_trips() {
+    let mut histogram = Histogram::default();
+    histogram.counts.insert("[Zero, One]".into(), 3);
+    histogram.counts.insert("line\nbreak \\ slash".into(), 1);
+    histogram.failures = 2;
+
+    let mut buf = Vec::new();
+    write_histogram(&mut buf, &histogram).expect("histogram should be written");
+    let read = read_histogram(buf.as_slice()).expect("histogram should be read");
+    assert_eq!(read, histogram);
+}
+
+#[test]
+fn truncated_response_is_error() {
+    assert!(read_histogram("VALUE 1 Zero\n".as_bytes()).is_err());
+}
+
+#[test]
+fn escape_round_trips() {
+    for value in ["", "\\n", "a\nb", "\\\\\n"] {
+        assert_eq!(unescape(&escape(value)), value);
+    }
+}
+
+#[test]
+fn tcp_workers_match_local_shots() {
+    let program = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] {
+                use qs = Qubit[2];
+                ApplyToEach(H, qs);
+                MResetEachZ(qs)
+            }
+        }
+    "};
+    let sources = || SourceMap::new([("test".into(), program.into())], None);
+
+    let mut workers: Vec<Box<dyn Worker>> = Vec::new();
+    for _ in 0..2 {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+        let addr = listener
+            .local_addr()
+            .expect("listener should have an address");
+        let sources = sources();
+        thread::spawn(move || {
+            serve(
+                &listener,
+                sources,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("worker sources should compile");
+        });
+        workers.push(Box::new(TcpWorker::new(
+            addr,
+            Some(Duration::from_secs(60)),
+        )));
+    }
+
+    let distributed = run_distributed(workers, 20, 7, 3).expect("shots should run");
+
+    let mut local = Histogram::default();
+    for shot in run_shots(
+        sources(),
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        ShotsConfig {
+            shots: 20,
+            seed: 7,
+            threads: None,
+        },
+    )
+    .expect("program should compile")
+    {
+        *local
+            .counts
+            .entry(shot.value.expect("shot should succeed"))
+            .or_default() += 1;
+    }
+    assert_eq!(distributed, local);
+}
@@ -0,0 +1,94 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::num::NonZeroUsize;
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+
+use super::{run_shots, shot_seeds, ShotsConfig};
+
+const PROGRAM: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Random;
+        @EntryPoint()
+        operation Main() : (Result[], Int) {
+            use qs = Qubit[4];
+            ApplyToEach(H, qs);
+            Message(\"shot\");
+            (MResetEachZ(qs), DrawRandomInt(0, 1000))
+        }
+    }
+"};
+
+fn values(threads: usize, seed: u64) -> Vec<String> {
+    let sources = SourceMap::new([("test".into(), PROGRAM.into())], None);
+    run_shots(
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        ShotsConfig {
+            shots: 16,
+            seed,
+            threads: NonZeroUsize::new(threads),
+        },
+    )
+    .expect("program should compile")
+    .into_iter()
+    .map(|shot| {
+        assert_eq!(shot.output, "shot\n");
+        shot.value.expect("shot should succeed")
+    })
+    .collect()
+}
+
+#[test]
+fn results_do_not_depend_on_thread_count() {
+    let single = values(1, 42);
+    assert_eq!(single.len(), 16);
+    assert_eq!(single, values(3, 42));
+    assert_eq!(single, values(16, 42));
+}
+
+#[test]
+fn shots_are_independent() {
+    let results = values(1, 42);
+    assert!(results.iter().any(|r| r != &results[0]), "{results:?}");
+}
+
+#[test]
+fn seed_changes_results() {
+    assert_ne!(values(2, 1), values(2, 2));
+}
+
+#[test]
+fn shot_seeds_differ_between_shots_and_streams() {
+    let (quantum0, classical0) = shot_seeds(7, 0);
+    let (quantum1, classical1) = shot_seeds(7, 1);
+    assert_ne!(quantum0, classical0);
+    assert_ne!(quantum0, quantum1);
+    assert_ne!(classical0, classical1);
+}
+
+#[test]
+fn compilation_errors_are_returned() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Test { operation Main() : Int { true } }".into(),
+        )],
+        Some("Test.Main()".into()),
+    );
+    let result = run_shots(
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+        ShotsConfig {
+            shots: 4,
+            seed: 0,
+            threads: None,
+        },
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,105 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Names for items that do not change between runs or compiler versions. Item IDs are assigned
+//! in the order that items are lowered, so adding an item to the standard library renumbers every
+//! item after it. External caches and baselines should key items by their stable ID instead.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_hir::hir::{CallableKind, Item, ItemKind, LocalItemId, Package};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// The kind and the fully qualified name of an item, such as
+/// `function:Microsoft.Quantum.Arrays.Reversed`. Items declared inside a callable are qualified
+/// by the name of the callable. Stable IDs are unique within a package, except that an item that
+/// is redefined in an interactive session has the same ID as the item it replaces.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StableId(String);
+
+impl StableId {
+    /// The stable ID of an item in the package, or `None` for compiler-generated items such as
+    /// lambdas, which have no name that can be written in source.
+    #[must_use]
+    pub fn new(package: &Package, item: &Item) -> Option<Self> {
+        let (kind, name) = kind_and_name(item);
+        if name.starts_with('<') {
+            return None;
+        }
+
+        let mut path = vec![name];
+        let mut parent = item.parent;
+        while let Some(id) = parent {
+            let parent_item = package.items.get(id)?;
+            let (_, name) = kind_and_name(parent_item);
+            if name.starts_with('<') {
+                return None;
+            }
+            path.push(name);
+            parent = parent_item.parent;
+        }
+        path.reverse();
+        Some(Self(format!("{kind}:{}", path.join("."))))
+    }
+
+    /// Parses a stable ID that was written with [`Display`], or returns `None` if the text is
+    /// not of the form `kind:name`.
+    #[must_use]
+    pub fn parse(id: &str) -> Option<Self> {
+        let (kind, name) = id.split_once(':')?;
+        let known = matches!(kind, "namespace" | "function" | "operation" | "type");
+        (known && !name.is_empty()).then(|| Self(id.to_string()))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for StableId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The stable IDs of the items in the package. When two items have the same ID, the one that was
+/// declared last is kept.
+#[must_use]
+pub fn stable_ids(package: &Package) -> BTreeMap<StableId, LocalItemId> {
+    package
+        .items
+        .values()
+        .filter_map(|item| Some((StableId::new(package, item)?, item.id)))
+        .collect()
+}
+
+/// Finds the item in the package with the given stable ID. Like [`stable_ids`], this finds the
+/// item that was declared last when more than one has the ID.
+#[must_use]
+pub fn find(package: &Package, id: &StableId) -> Option<LocalItemId> {
+    package
+        .items
+        .values()
+        .filter(|item| StableId::new(package, item).as_ref() == Some(id))
+        .map(|item| item.id)
+        .last()
+}
+
+fn kind_and_name(item: &Item) -> (&'static str, String) {
+    match &item.kind {
+        ItemKind::Callable(decl) => (
+            match decl.kind {
+                CallableKind::Function => "function",
+                CallableKind::Operation => "operation",
+            },
+            decl.name.name.to_string(),
+        ),
+        ItemKind::Ty(name, _) => ("type", name.name.to_string()),
+        ItemKind::Namespace(name, _) => ("namespace", name.name().to_string()),
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{find, stable_ids, StableId};
+use crate::{compile, PackageStore, SourceMap};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::CompileUnit;
+use qsc_passes::PackageType;
+
+fn compile(source: &str) -> (CompileUnit, Vec<compile::Error>) {
+    let capabilities = TargetCapabilityFlags::all();
+    let mut store = PackageStore::new(compile::core());
+    let dependencies = vec![store.insert(compile::std(&store, capabilities))];
+    compile::compile(
+        &store,
+        &dependencies,
+        SourceMap::new([("test.qs".into(), source.into())], None),
+        PackageType::Lib,
+        capabilities,
+        LanguageFeatures::default(),
+    )
+}
+
+fn ids(source: &str) -> String {
+    let (unit, errors) = compile(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    stable_ids(&unit.package)
+        .keys()
+        .map(StableId::as_str)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const SOURCE: &str = indoc! {"
+    namespace Sample.Items {
+        newtype Pair = (First : Int, Second : Int);
+
+        function Add(a : Int, b : Int) : Int { a + b }
+
+        operation Prepare(q : Qubit) : Unit {
+            function Angle() : Double { 0.5 }
+            let f = x -> x + 1;
+            Rx(Angle(), q);
+        }
+    }
+"};
+
+#[test]
+fn ids_are_kinds_and_qualified_names() {
+    expect![[r#"
+        function:Sample.Items.Add
+        function:Sample.Items.Prepare.Angle
+        namespace:Sample.Items
+        operation:Sample.Items.Prepare
+        type:Sample.Items.Pair"#]]
+    .assert_eq(&ids(SOURCE));
+}
+
+#[test]
+fn ids_do_not_change_when_items_are_added() {
+    let (before, _) = compile(SOURCE);
+    let (after, _) = compile(&SOURCE.replace(
+        "namespace Sample.Items {",
+        "namespace Sample.Items {\n    function Zero() : Int { 0 }",
+    ));
+
+    let id = StableId::parse("function:Sample.Items.Add").expect("id should parse");
+    let before_item = find(&before.package, &id).expect("item should be found");
+    let after_item = find(&after.package, &id).expect("item should be found");
+    assert_ne!(before_item, after_item);
+    let item = after
+        .package
+        .items
+        .get(after_item)
+        .expect("item should exist");
+    assert_eq!(StableId::new(&after.package, item).as_ref(), Some(&id));
+}
+
+#[test]
+fn ids_are_parsed() {
+    let id = StableId::parse("operation:Sample.Items.Prepare").expect("id should parse");
+    assert_eq!(id.to_string(), "operation:Sample.Items.Prepare");
+    assert_eq!(StableId::parse("Sample.Items.Prepare"), None);
+    assert_eq!(StableId::parse("variable:x"), None);
+    assert_eq!(StableId::parse("function:"), None);
+}
+
+#[test]
+fn compilation_is_deterministic() {
+    let source = indoc! {"
+        namespace Sample {
+            function Broken(a : Int) : Bool { a + Undefined() }
+            operation AlsoBroken() : Unit { let x : Int = 1.0; Missing.Call(); }
+            function Fine() : Int { 42 }
+        }
+    "};
+    let (first, first_errors) = compile(source);
+    let (second, second_errors) = compile(source);
+    assert!(!first_errors.is_empty());
+    assert_eq!(format!("{first_errors:?}"), format!("{second_errors:?}"));
+    assert_eq!(first.package.to_string(), second.package.to_string());
+    assert_eq!(stable_ids(&first.package), stable_ids(&second.package));
+}
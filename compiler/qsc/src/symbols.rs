@@ -0,0 +1,245 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! An index of the items in a package store, with fuzzy search over their names. Editors use it
+//! for workspace-wide symbol navigation, and the REPL for its `:search` command.
+
+#[cfg(test)]
+mod tests;
+
+use crate::stable_id::StableId;
+use qsc_data_structures::span::Span;
+use qsc_doc_gen::display::{hir_callable_signature, hir_udt_signature, parse_doc_for_summary};
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::hir::{CallableKind, Idents, Item, ItemKind, Package, PackageId, Visibility};
+use rustc_hash::FxHashSet;
+use std::rc::Rc;
+
+/// The kind of an indexed symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Namespace,
+    Function,
+    Operation,
+    Type,
+}
+
+/// An item in the package store.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    /// The stable ID of the item, which does not change between compiler versions.
+    pub id: StableId,
+    /// The name of the item.
+    pub name: Rc<str>,
+    /// The namespace the item is declared in, or the empty string for items declared outside of
+    /// a namespace, such as in interpreter fragments. For namespaces, this is the parent
+    /// namespace.
+    pub namespace: Rc<str>,
+    pub kind: SymbolKind,
+    /// The declaration of the item, such as `function Length<'T>(a : 'T[]) : Int`.
+    pub signature: String,
+    /// The summary section of the item's doc comment.
+    pub summary: String,
+    /// The package that declares the item.
+    pub package: PackageId,
+    /// The span of the item's name, relative to the package's source map.
+    pub span: Span,
+}
+
+impl Symbol {
+    /// The fully qualified name of the item.
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+}
+
+/// An index of the items in a package store.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIndex {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolIndex {
+    /// Indexes the items in the package store. Internal items are only indexed for the given user
+    /// packages, since they cannot be referred to from anywhere else. Namespaces declared by more
+    /// than one package are indexed once.
+    #[must_use]
+    pub fn new(store: &PackageStore, user_packages: &[PackageId]) -> Self {
+        let mut symbols = Vec::new();
+        let mut namespaces = FxHashSet::default();
+        for (id, unit) in store.iter() {
+            let include_internal = user_packages.contains(&id);
+            for item in unit.package.items.values() {
+                if item.visibility == Visibility::Internal && !include_internal {
+                    continue;
+                }
+                if let Some(symbol) = symbol(&unit.package, id, item) {
+                    if symbol.kind != SymbolKind::Namespace || namespaces.insert(symbol.full_name())
+                    {
+                        symbols.push(symbol);
+                    }
+                }
+            }
+        }
+        Self { symbols }
+    }
+
+    /// All the indexed symbols, in the order of their packages and then of their declarations.
+    #[must_use]
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Returns up to `limit` symbols that match the query, best matches first. The query matches
+    /// a name if its characters appear in the name in order, ignoring case, so `GetBits` matches
+    /// `GetBitsFromInt` and `mz` matches `MResetZ`. Matches at the start of a word and runs of
+    /// consecutive characters rank higher. A query containing `.` is matched against fully
+    /// qualified names instead.
+    #[must_use]
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&Symbol> {
+        let mut matches = self
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                let score = if query.contains('.') {
+                    fuzzy_score(query, &symbol.full_name())
+                } else {
+                    fuzzy_score(query, &symbol.name)
+                }?;
+                Some((score, symbol))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a.name.len().cmp(&b.name.len()))
+                .then_with(|| a.full_name().cmp(&b.full_name()))
+        });
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, symbol)| symbol)
+            .collect()
+    }
+}
+
+fn symbol(package: &Package, id: PackageId, item: &Item) -> Option<Symbol> {
+    // Compiler-generated items, such as lambdas, have names that cannot be written in source and
+    // so have no stable ID.
+    let stable_id = StableId::new(package, item)?;
+    let (name, kind, signature, span) = match &item.kind {
+        ItemKind::Callable(decl) => (
+            decl.name.name.clone(),
+            match decl.kind {
+                CallableKind::Function => SymbolKind::Function,
+                CallableKind::Operation => SymbolKind::Operation,
+            },
+            hir_callable_signature(decl).to_string(),
+            decl.name.span,
+        ),
+        ItemKind::Ty(name, udt) => (
+            name.name.clone(),
+            SymbolKind::Type,
+            hir_udt_signature(udt).to_string(),
+            name.span,
+        ),
+        ItemKind::Namespace(name, _) => {
+            let full_name = join(name);
+            let (namespace, last) = full_name.rsplit_once('.').unwrap_or(("", &full_name));
+            return Some(Symbol {
+                id: stable_id,
+                name: last.into(),
+                namespace: namespace.into(),
+                kind: SymbolKind::Namespace,
+                signature: format!("namespace {full_name}"),
+                summary: parse_doc_for_summary(&item.doc),
+                package: id,
+                span: name.span(),
+            });
+        }
+    };
+
+    let namespace = item
+        .parent
+        .and_then(|parent| match &package.items.get(parent)?.kind {
+            ItemKind::Namespace(name, _) => Some(join(name)),
+            _ => None,
+        })
+        .unwrap_or_default();
+    Some(Symbol {
+        id: stable_id,
+        name,
+        namespace: namespace.into(),
+        kind,
+        signature,
+        summary: parse_doc_for_summary(&item.doc),
+        package: id,
+        span,
+    })
+}
+
+pub(crate) fn join(idents: &Idents) -> String {
+    idents
+        .iter()
+        .map(|ident| ident.name.as_ref())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Scores how well the query matches the text as a case-insensitive subsequence, or returns
+/// `None` if it does not match. Every matched character scores a point, with bonuses for
+/// characters at the start of a word, for characters that follow the previous match directly,
+/// and for matching the whole text or a prefix of it.
+fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text = text.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars() {
+        let index = position
+            + text[position..]
+                .iter()
+                .position(|t| t.to_lowercase().eq(q.to_lowercase()))?;
+        score += 1;
+        if index == 0 || is_word_start(&text, index) {
+            score += 8;
+        }
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 4;
+        }
+        if text[index] == q {
+            score += 1;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    let query_len = query.chars().count();
+    let lower = |chars: &[char]| {
+        chars
+            .iter()
+            .flat_map(|c| c.to_lowercase())
+            .collect::<String>()
+    };
+    if lower(&text) == query.to_lowercase() {
+        score += 100;
+    } else if text.len() >= query_len && lower(&text[..query_len]) == query.to_lowercase() {
+        score += 20;
+    }
+    Some(score)
+}
+
+/// Whether the character at the index starts a word, as in `Get` and `Bits` in `GetBits`, or
+/// `Bits` in `get_bits`.
+fn is_word_start(text: &[char], index: usize) -> bool {
+    let previous = text[index - 1];
+    let current = text[index];
+    matches!(previous, '.' | '_')
+        || (current.is_uppercase() && !previous.is_uppercase())
+        || (current.is_ascii_digit() && !previous.is_ascii_digit())
+}
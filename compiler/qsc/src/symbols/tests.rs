@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{fuzzy_score, SymbolIndex, SymbolKind};
+use crate::interpret::Interpreter;
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::SourceMap;
+use qsc_passes::PackageType;
+
+fn index() -> SymbolIndex {
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            indoc! {"
+                namespace Sample.Arithmetic {
+                    /// # Summary
+                    /// Adds two integers.
+                    function AddInts(a : Int, b : Int) : Int { a + b }
+
+                    internal function Helper() : Unit {}
+
+                    newtype Pair = (First : Int, Second : Int);
+
+                    operation PrepareBell(q1 : Qubit, q2 : Qubit) : Unit is Adj {
+                        H(q1);
+                        CNOT(q1, q2);
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("interpreter should be created")
+    .symbols()
+}
+
+fn names(index: &SymbolIndex, query: &str) -> Vec<String> {
+    index
+        .search(query, 5)
+        .into_iter()
+        .map(super::Symbol::full_name)
+        .collect()
+}
+
+#[test]
+fn symbols_have_signatures_and_summaries() {
+    let index = index();
+    let add = index
+        .symbols()
+        .iter()
+        .find(|symbol| symbol.name.as_ref() == "AddInts")
+        .expect("AddInts should be indexed");
+    assert_eq!(add.id.as_str(), "function:Sample.Arithmetic.AddInts");
+    assert_eq!(add.namespace.as_ref(), "Sample.Arithmetic");
+    assert_eq!(add.kind, SymbolKind::Function);
+    assert_eq!(add.signature, "function AddInts(a : Int, b : Int) : Int");
+    assert_eq!(add.summary, "Adds two integers.");
+
+    let pair = index
+        .symbols()
+        .iter()
+        .find(|symbol| symbol.name.as_ref() == "Pair")
+        .expect("Pair should be indexed");
+    assert_eq!(pair.kind, SymbolKind::Type);
+    assert_eq!(pair.signature, "newtype Pair = (First : Int, Second : Int)");
+}
+
+#[test]
+fn user_internal_items_are_indexed() {
+    assert_eq!(names(&index(), "Helper")[0], "Sample.Arithmetic.Helper");
+}
+
+#[test]
+fn namespaces_are_indexed_once() {
+    let index = index();
+    let intrinsic = index
+        .symbols()
+        .iter()
+        .filter(|symbol| symbol.full_name() == "Microsoft.Quantum.Intrinsic")
+        .collect::<Vec<_>>();
+    assert_eq!(intrinsic.len(), 1);
+    assert_eq!(intrinsic[0].kind, SymbolKind::Namespace);
+}
+
+#[test]
+fn search_matches_word_starts() {
+    let index = index();
+    assert_eq!(
+        names(&index, "PrepBell")[0],
+        "Sample.Arithmetic.PrepareBell"
+    );
+    assert_eq!(names(&index, "addints")[0], "Sample.Arithmetic.AddInts");
+    assert!(names(&index, "zzzqqq").is_empty());
+}
+
+#[test]
+fn search_with_dot_matches_qualified_names() {
+    let index = index();
+    assert_eq!(names(&index, "Arith.Pair")[0], "Sample.Arithmetic.Pair");
+    assert!(names(&index, "Intrinsic.H")
+        .iter()
+        .any(|name| name == "Microsoft.Quantum.Intrinsic.H"));
+}
+
+#[test]
+fn search_ranks_std_items() {
+    let index = index();
+    assert_eq!(
+        names(&index, "MResetZ")[0],
+        "Microsoft.Quantum.Measurement.MResetZ"
+    );
+}
+
+#[test]
+fn fuzzy_score_prefers_word_starts_and_runs() {
+    let word_starts = fuzzy_score("gb", "GetBits").expect("should match");
+    let inside = fuzzy_score("eb", "GetBits").expect("should match");
+    assert!(word_starts > inside);
+
+    let run = fuzzy_score("bits", "GetBits").expect("should match");
+    let scattered = fuzzy_score("bits", "BigIntToString").expect("should match");
+    assert!(run > scattered);
+
+    assert!(fuzzy_score("Length", "Length") > fuzzy_score("Length", "LengthOf"));
+    assert_eq!(fuzzy_score("xyz", "GetBits"), None);
+}
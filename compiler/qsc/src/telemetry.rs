@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Spans around the compilation, evaluation and shot boundaries of the interpreter. With the
+//! `opentelemetry` feature enabled, they are emitted through the global OpenTelemetry tracer
+//! provider under the `qsc` tracer, as children of the span that is current when the interpreter
+//! is called, so that services embedding the interpreter can correlate Q# execution with the rest
+//! of their distributed traces. Without the feature they compile to nothing.
+
+/// The span for compiling the sources that an interpreter is created with.
+pub(crate) const COMPILE_SOURCES: &str = "qsc.compile_sources";
+/// The span for compiling and lowering a line of fragments.
+pub(crate) const COMPILE_FRAGMENTS: &str = "qsc.compile_fragments";
+/// The span for compiling and lowering an entry expression.
+pub(crate) const COMPILE_ENTRY: &str = "qsc.compile_entry";
+/// The span for evaluating compiled code.
+pub(crate) const EVAL: &str = "qsc.eval";
+/// The span for one shot of a multi-shot run.
+pub(crate) const SHOT: &str = "qsc.shot";
+
+#[cfg(feature = "opentelemetry")]
+mod otel {
+    use opentelemetry::{
+        global,
+        trace::{Status, TraceContextExt, Tracer},
+        Context, ContextGuard, KeyValue,
+    };
+    use std::collections::BTreeMap;
+
+    /// A span that is current, so that spans started while it is alive are nested in it, until
+    /// it is dropped.
+    pub(crate) struct Span {
+        cx: Context,
+        _guard: ContextGuard,
+    }
+
+    impl Span {
+        pub(crate) fn new(name: &'static str) -> Self {
+            let span = global::tracer("qsc").start(name);
+            let cx = Context::current_with_span(span);
+            let guard = cx.clone().attach();
+            Self { cx, _guard: guard }
+        }
+
+        pub(crate) fn attribute(&self, key: &'static str, value: i64) {
+            self.cx.span().set_attribute(KeyValue::new(key, value));
+        }
+
+        /// Adds the session metadata as `qsc.session.<key>` attributes.
+        pub(crate) fn metadata(&self, metadata: &BTreeMap<String, String>) {
+            let span = self.cx.span();
+            for (key, value) in metadata {
+                span.set_attribute(KeyValue::new(format!("qsc.session.{key}"), value.clone()));
+            }
+        }
+
+        /// Marks the span as failed with the number of errors.
+        pub(crate) fn fail<E>(&self, errors: &[E]) {
+            let span = self.cx.span();
+            span.set_attribute(KeyValue::new(
+                "qsc.error_count",
+                i64::try_from(errors.len()).unwrap_or(i64::MAX),
+            ));
+            span.set_status(Status::error("Q# compilation or evaluation failed"));
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            self.cx.span().end();
+        }
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+pub(crate) use otel::Span;
+
+#[cfg(not(feature = "opentelemetry"))]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "opentelemetry"))]
+impl Span {
+    pub(crate) fn new(_name: &'static str) -> Self {
+        Self
+    }
+
+    pub(crate) fn attribute(&self, _key: &'static str, _value: i64) {}
+
+    pub(crate) fn metadata(&self, _metadata: &std::collections::BTreeMap<String, String>) {}
+
+    pub(crate) fn fail<E>(&self, _errors: &[E]) {}
+}
@@ -0,0 +1,454 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Checks a student's solution to an assignment for education platforms: that it compiles, that
+//! it does not use forbidden callables, that it acts like a reference implementation, and that
+//! it stays within resource limits. Each check is reported as passed, failed or skipped, so that
+//! graders can show students exactly what went wrong.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_data_structures::{language_features::LanguageFeatures, line_column::Encoding, span::Span};
+use qsc_frontend::compile::{PackageStore, SourceContents, SourceMap, SourceName, StdFeatures};
+use qsc_hir::{
+    hir::{Expr, ExprKind, Item, ItemId, ItemKind, LocalItemId, Package, PackageId, Res},
+    visit::{self, Visitor},
+};
+use qsc_passes::PackageType;
+
+use crate::{
+    compile,
+    interpret::{output::GenericReceiver, CircuitEntryPoint, Interpreter, Value},
+    location::Location,
+    target::Profile,
+};
+
+/// An assignment that solutions are verified against.
+#[derive(Clone, Debug, Default)]
+pub struct Assignment {
+    /// The operation the solution implements, such as `Kata.ApplyX`. It must take a single
+    /// `Qubit[]` argument.
+    pub operation: String,
+    /// The sources that declare the reference implementation. They are compiled together with
+    /// the solution, so their namespaces must not clash with the solution's.
+    pub reference_sources: Vec<(SourceName, SourceContents)>,
+    /// The reference operation, such as `Reference.ApplyX`, or `None` to skip the equivalence
+    /// check. It must take a single `Qubit[]` argument and be adjointable.
+    pub reference: Option<String>,
+    /// The number of qubits the operations are applied to.
+    pub qubit_count: usize,
+    /// The fully qualified names of callables the solution must not call or refer to, such as
+    /// `Microsoft.Quantum.Intrinsic.CNOT`.
+    pub forbidden_callables: Vec<String>,
+    /// The limits on the resources the solution uses.
+    pub limits: ResourceLimits,
+}
+
+/// Limits on the resources used by a single application of the solution. `None` means no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// The number of qubits, including the ones passed to the operation.
+    pub max_qubits: Option<usize>,
+    /// The number of gates and measurements.
+    pub max_operations: Option<usize>,
+}
+
+impl ResourceLimits {
+    fn is_unlimited(self) -> bool {
+        self.max_qubits.is_none() && self.max_operations.is_none()
+    }
+}
+
+/// A check performed on a solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    Compilation,
+    ForbiddenCallables,
+    Equivalence,
+    Resources,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Check::Compilation => "compilation",
+            Check::ForbiddenCallables => "forbidden callables",
+            Check::Equivalence => "equivalence",
+            Check::Resources => "resources",
+        })
+    }
+}
+
+/// A reason a check failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+    /// The solution or the reference failed to compile or to run.
+    Error(String),
+    /// The solution calls or refers to a forbidden callable.
+    Forbidden {
+        callable: String,
+        location: Location,
+    },
+    /// The solution does not act like the reference on some input state, up to global phase.
+    NotEquivalent,
+    /// The solution uses more qubits than allowed.
+    TooManyQubits { used: usize, max: usize },
+    /// The solution applies more operations than allowed.
+    TooManyOperations { used: usize, max: usize },
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Failure::Error(message) => f.write_str(message),
+            Failure::Forbidden { callable, location } => write!(
+                f,
+                "`{callable}` is not allowed, but is used at {}:{}:{}",
+                location.source,
+                location.range.start.line + 1,
+                location.range.start.column + 1
+            ),
+            Failure::NotEquivalent => f.write_str("the solution does not match the reference"),
+            Failure::TooManyQubits { used, max } => {
+                write!(
+                    f,
+                    "the solution uses {used} qubits, but at most {max} are allowed"
+                )
+            }
+            Failure::TooManyOperations { used, max } => write!(
+                f,
+                "the solution applies {used} operations, but at most {max} are allowed"
+            ),
+        }
+    }
+}
+
+/// The outcome of a check.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Passed,
+    Failed(Vec<Failure>),
+    /// The check was not performed because the solution failed to compile.
+    Skipped,
+}
+
+/// The outcome of a single check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckReport {
+    pub check: Check,
+    pub outcome: Outcome,
+}
+
+/// The outcome of every check requested by an assignment, in the order they were performed.
+/// Compilation is always checked first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub checks: Vec<CheckReport>,
+}
+
+impl Report {
+    /// Whether every check passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.outcome == Outcome::Passed)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                Outcome::Passed => writeln!(f, "{}: passed", check.check)?,
+                Outcome::Skipped => writeln!(f, "{}: skipped", check.check)?,
+                Outcome::Failed(failures) => {
+                    writeln!(f, "{}: failed", check.check)?;
+                    for failure in failures {
+                        writeln!(f, "  {failure}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verifies a solution against the assignment. Only the checks the assignment asks for are
+/// performed: forbidden callables when any are listed, equivalence when there is a reference,
+/// and resources when there is a limit.
+#[must_use]
+pub fn verify(
+    solution: &[(SourceName, SourceContents)],
+    assignment: &Assignment,
+    language_features: LanguageFeatures,
+) -> Report {
+    let mut checks = Vec::new();
+    let compiled = compile_solution(solution, language_features);
+    let forbidden_uses = match compiled {
+        Ok((store, package_id)) => {
+            checks.push(CheckReport {
+                check: Check::Compilation,
+                outcome: Outcome::Passed,
+            });
+            Some(forbidden_uses(
+                &store,
+                package_id,
+                &assignment.forbidden_callables,
+            ))
+        }
+        Err(failures) => {
+            checks.push(CheckReport {
+                check: Check::Compilation,
+                outcome: Outcome::Failed(failures),
+            });
+            None
+        }
+    };
+
+    let mut requested = Vec::new();
+    if !assignment.forbidden_callables.is_empty() {
+        requested.push(Check::ForbiddenCallables);
+    }
+    if assignment.reference.is_some() {
+        requested.push(Check::Equivalence);
+    }
+    if !assignment.limits.is_unlimited() {
+        requested.push(Check::Resources);
+    }
+
+    let Some(forbidden_uses) = forbidden_uses else {
+        checks.extend(requested.into_iter().map(|check| CheckReport {
+            check,
+            outcome: Outcome::Skipped,
+        }));
+        return Report { checks };
+    };
+
+    let mut interpreter = None;
+    for check in requested {
+        let failures = match check {
+            Check::ForbiddenCallables => forbidden_uses.clone(),
+            Check::Equivalence | Check::Resources => {
+                let interpreter = interpreter.get_or_insert_with(|| {
+                    assignment_interpreter(solution, assignment, language_features)
+                });
+                match interpreter {
+                    Ok(interpreter) if check == Check::Equivalence => {
+                        check_equivalence(interpreter, assignment)
+                    }
+                    Ok(interpreter) => check_resources(interpreter, assignment),
+                    Err(failures) => failures.clone(),
+                }
+            }
+            Check::Compilation => unreachable!("compilation is always checked first"),
+        };
+        checks.push(CheckReport {
+            check,
+            outcome: if failures.is_empty() {
+                Outcome::Passed
+            } else {
+                Outcome::Failed(failures)
+            },
+        });
+    }
+
+    Report { checks }
+}
+
+/// Compiles the solution on its own against the standard library, returning the store and the
+/// id of the solution's package.
+fn compile_solution(
+    solution: &[(SourceName, SourceContents)],
+    language_features: LanguageFeatures,
+) -> Result<(PackageStore, PackageId), Vec<Failure>> {
+    let capabilities = Profile::Unrestricted.into();
+    let (mut store, std_id) = compile::shared_store(capabilities, Some(StdFeatures::all()));
+    let std_id = std_id.expect("store should have std");
+    let (unit, errors) = compile::compile(
+        &store,
+        &[std_id],
+        SourceMap::new(solution.iter().cloned(), None),
+        PackageType::Lib,
+        capabilities,
+        language_features,
+    );
+    if errors.is_empty() {
+        let package_id = store.insert(unit);
+        Ok((store, package_id))
+    } else {
+        Err(errors
+            .iter()
+            .map(|error| Failure::Error(error.to_string()))
+            .collect())
+    }
+}
+
+/// Creates an interpreter for the solution together with the reference sources.
+fn assignment_interpreter(
+    solution: &[(SourceName, SourceContents)],
+    assignment: &Assignment,
+    language_features: LanguageFeatures,
+) -> Result<Interpreter, Vec<Failure>> {
+    let sources = solution
+        .iter()
+        .chain(&assignment.reference_sources)
+        .cloned();
+    Interpreter::new(
+        true,
+        SourceMap::new(sources, None),
+        PackageType::Lib,
+        Profile::Unrestricted.into(),
+        language_features,
+    )
+    .map_err(|errors| errors_to_failures(&errors))
+}
+
+fn check_equivalence(interpreter: &mut Interpreter, assignment: &Assignment) -> Vec<Failure> {
+    let Some(reference) = &assignment.reference else {
+        return Vec::new();
+    };
+    let expr = format!(
+        "Microsoft.Quantum.Diagnostics.CheckOperationsAreEqual({}, {}, {reference})",
+        assignment.qubit_count, assignment.operation
+    );
+    let mut sink = std::io::sink();
+    let mut out = GenericReceiver::new(&mut sink);
+    match interpreter.run(&mut out, &expr) {
+        Ok(Ok(Value::Bool(true))) => Vec::new(),
+        Ok(Ok(_)) => vec![Failure::NotEquivalent],
+        Ok(Err(errors)) | Err(errors) => errors_to_failures(&errors),
+    }
+}
+
+/// Traces a single application of the solution to count the qubits and operations it uses.
+fn check_resources(interpreter: &mut Interpreter, assignment: &Assignment) -> Vec<Failure> {
+    let expr = format!(
+        "{{ use qs = Qubit[{}]; {}(qs); }}",
+        assignment.qubit_count, assignment.operation
+    );
+    let circuit = match interpreter.circuit(CircuitEntryPoint::EntryExpr(expr), true) {
+        Ok(circuit) => circuit,
+        Err(errors) => return errors_to_failures(&errors),
+    };
+
+    let mut failures = Vec::new();
+    let limits = assignment.limits;
+    let used = circuit.qubits.len();
+    if let Some(max) = limits.max_qubits.filter(|max| used > *max) {
+        failures.push(Failure::TooManyQubits { used, max });
+    }
+    let used = circuit.operations.len();
+    if let Some(max) = limits.max_operations.filter(|max| used > *max) {
+        failures.push(Failure::TooManyOperations { used, max });
+    }
+    failures
+}
+
+fn errors_to_failures(errors: &[crate::interpret::Error]) -> Vec<Failure> {
+    errors
+        .iter()
+        .map(|error| Failure::Error(error.to_string()))
+        .collect()
+}
+
+/// Finds the uses of forbidden callables in the package, ordered by location.
+fn forbidden_uses(
+    store: &PackageStore,
+    package_id: PackageId,
+    forbidden: &[String],
+) -> Vec<Failure> {
+    if forbidden.is_empty() {
+        return Vec::new();
+    }
+
+    let package = &store
+        .get(package_id)
+        .expect("solution package should be in the store")
+        .package;
+    item_uses(store, package, |name| forbidden.iter().any(|f| f == name))
+        .into_iter()
+        .map(|(callable, span)| Failure::Forbidden {
+            callable,
+            location: Location::from(span, package_id, store, Encoding::Utf8),
+        })
+        .collect()
+}
+
+/// Finds the expressions in the package that refer to a callable or type whose fully qualified
+/// name matches the predicate, returning each name along with the span of the expression,
+/// ordered by location. A use in code the compiler generates from the source, such as an adjoint
+/// specialization, is reported once at its location in the source. Items of the package
+/// itself are looked up in the package, and other items in the store.
+pub(crate) fn item_uses(
+    store: &PackageStore,
+    package: &Package,
+    matches: impl Fn(&str) -> bool,
+) -> Vec<(String, Span)> {
+    let mut finder = ItemUseFinder {
+        store,
+        package,
+        matches,
+        uses: Vec::new(),
+    };
+    finder.visit_package(package);
+
+    let mut uses = finder.uses;
+    uses.sort_by_key(|(_, span)| span.lo);
+    uses.dedup();
+    uses
+}
+
+struct ItemUseFinder<'a, F> {
+    store: &'a PackageStore,
+    package: &'a Package,
+    matches: F,
+    uses: Vec<(String, Span)>,
+}
+
+impl<F> ItemUseFinder<'_, F> {
+    fn item_name(&self, id: ItemId) -> Option<String> {
+        let package = match id.package {
+            None => self.package,
+            Some(package_id) => &self.store.get(package_id)?.package,
+        };
+        qualified_name(package, id.item)
+    }
+}
+
+/// The fully qualified name of a callable or type in the package.
+pub(crate) fn qualified_name(package: &Package, id: LocalItemId) -> Option<String> {
+    let item = package.items.get(id)?;
+    let name = match &item.kind {
+        ItemKind::Callable(decl) => &decl.name.name,
+        ItemKind::Ty(name, _) => &name.name,
+        ItemKind::Namespace(..) => return None,
+    };
+    match namespace(package, item) {
+        Some(namespace) => Some(format!("{namespace}.{name}")),
+        None => Some(name.to_string()),
+    }
+}
+
+impl<'a, F: Fn(&str) -> bool> Visitor<'a> for ItemUseFinder<'a, F> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Var(Res::Item(id), _) = &expr.kind {
+            if let Some(name) = self.item_name(*id) {
+                if (self.matches)(&name) {
+                    self.uses.push((name, expr.span));
+                }
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn namespace(package: &Package, item: &Item) -> Option<String> {
+    match &package.items.get(item.parent?)?.kind {
+        ItemKind::Namespace(name, _) => Some(name.name().to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{verify, Assignment, Check, Failure, Outcome, ResourceLimits};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+
+const REFERENCE: &str = indoc! {"
+    namespace Reference {
+        operation ApplyX(qs : Qubit[]) : Unit is Adj {
+            X(qs[0]);
+        }
+    }
+"};
+
+fn assignment() -> Assignment {
+    Assignment {
+        operation: "Kata.ApplyX".to_string(),
+        reference_sources: vec![("reference.qs".into(), REFERENCE.into())],
+        reference: Some("Reference.ApplyX".to_string()),
+        qubit_count: 1,
+        ..Assignment::default()
+    }
+}
+
+fn verify_solution(solution: &str, assignment: &Assignment) -> super::Report {
+    verify(
+        &[("solution.qs".into(), solution.into())],
+        assignment,
+        LanguageFeatures::default(),
+    )
+}
+
+#[test]
+fn solution_equal_up_to_global_phase_passes() {
+    let report = verify_solution(
+        indoc! {"
+            namespace Kata {
+                operation ApplyX(qs : Qubit[]) : Unit {
+                    Y(qs[0]);
+                    Z(qs[0]);
+                }
+            }
+        "},
+        &assignment(),
+    );
+    assert!(report.passed(), "{report}");
+    expect![[r#"
+        compilation: passed
+        equivalence: passed
+    "#]]
+    .assert_eq(&report.to_string());
+}
+
+#[test]
+fn solution_acting_differently_fails_equivalence() {
+    let report = verify_solution(
+        indoc! {"
+            namespace Kata {
+                operation ApplyX(qs : Qubit[]) : Unit {
+                    H(qs[0]);
+                }
+            }
+        "},
+        &assignment(),
+    );
+    assert!(!report.passed());
+    expect![[r#"
+        compilation: passed
+        equivalence: failed
+          the solution does not match the reference
+    "#]]
+    .assert_eq(&report.to_string());
+}
+
+#[test]
+fn forbidden_callable_is_reported_at_its_use() {
+    let assignment = Assignment {
+        qubit_count: 2,
+        reference: None,
+        forbidden_callables: vec!["Microsoft.Quantum.Intrinsic.CNOT".to_string()],
+        ..assignment()
+    };
+    let report = verify_solution(
+        indoc! {"
+            namespace Kata {
+                operation ApplyX(qs : Qubit[]) : Unit is Adj + Ctl {
+                    CNOT(qs[0], qs[1]);
+                    Controlled X([qs[0]], qs[1]);
+                }
+            }
+        "},
+        &assignment,
+    );
+    expect![[r#"
+        compilation: passed
+        forbidden callables: failed
+          `Microsoft.Quantum.Intrinsic.CNOT` is not allowed, but is used at solution.qs:3:9
+    "#]]
+    .assert_eq(&report.to_string());
+}
+
+#[test]
+fn resource_limits_are_enforced() {
+    let assignment = Assignment {
+        qubit_count: 2,
+        reference: None,
+        limits: ResourceLimits {
+            max_qubits: Some(2),
+            max_operations: Some(2),
+        },
+        ..assignment()
+    };
+    let report = verify_solution(
+        indoc! {"
+            namespace Kata {
+                operation ApplyX(qs : Qubit[]) : Unit {
+                    use aux = Qubit();
+                    X(aux);
+                    CNOT(aux, qs[0]);
+                    X(aux);
+                }
+            }
+        "},
+        &assignment,
+    );
+    expect![[r#"
+        compilation: passed
+        resources: failed
+          the solution uses 3 qubits, but at most 2 are allowed
+          the solution applies 3 operations, but at most 2 are allowed
+    "#]]
+    .assert_eq(&report.to_string());
+}
+
+#[test]
+fn checks_are_skipped_when_solution_does_not_compile() {
+    let assignment = Assignment {
+        forbidden_callables: vec!["Microsoft.Quantum.Intrinsic.CNOT".to_string()],
+        ..assignment()
+    };
+    let report = verify_solution(
+        indoc! {"
+            namespace Kata {
+                operation ApplyX(qs : Qubit[]) : Unit {
+                    Undefined(qs[0]);
+                }
+            }
+        "},
+        &assignment,
+    );
+    assert!(!report.passed());
+    assert_eq!(report.checks.len(), 3);
+    assert_eq!(report.checks[0].check, Check::Compilation);
+    assert!(matches!(
+        report.checks[0].outcome.clone(),
+        Outcome::Failed(failures) if matches!(failures[..], [Failure::Error(_), ..])
+    ));
+    for check in &report.checks[1..] {
+        assert_eq!(check.outcome, Outcome::Skipped);
+    }
+}
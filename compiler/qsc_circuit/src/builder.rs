@@ -192,6 +192,12 @@ impl Backend for Builder {
     }
 
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        // Arithmetic operations record their implementation for the resource estimator, which
+        // is not a gate.
+        if name == "AccountForArithmeticInternal" {
+            return Some(Ok(Value::unit()));
+        }
+
         // The qubit arguments are treated as the targets for custom gates.
         // Any remaining arguments will be kept in the display_args field
         // to be shown as part of the gate label when the circuit is rendered.
@@ -214,6 +220,11 @@ impl Backend for Builder {
             _ => Some(Ok(Value::unit())),
         }
     }
+
+    fn close(&mut self) {
+        self.circuit = Circuit::default();
+        self.remapper = Remapper::default();
+    }
 }
 
 impl Builder {
@@ -267,3 +267,37 @@ fn two_targets() {
     "]]
     .assert_eq(&c.to_string());
 }
+
+#[test]
+fn json_round_trip() {
+    let c: Circuit = serde_json::from_str(
+        r#"{
+            "operations": [
+                { "gate": "H", "targets": [{ "qId": 0, "type": 0 }] },
+                {
+                    "gate": "X",
+                    "isControlled": true,
+                    "controls": [{ "qId": 0, "type": 0 }],
+                    "targets": [{ "qId": 1, "type": 0 }]
+                },
+                {
+                    "gate": "Measure",
+                    "isMeasurement": true,
+                    "controls": [{ "qId": 1, "type": 0 }],
+                    "targets": [{ "qId": 1, "type": 1, "cId": 0 }]
+                }
+            ],
+            "qubits": [{ "id": 0, "numChildren": 0 }, { "id": 1, "numChildren": 1 }]
+        }"#,
+    )
+    .expect("circuit should deserialize");
+
+    assert_eq!(c.operations.len(), 3);
+    assert!(!c.operations[0].is_controlled && c.operations[0].controls.is_empty());
+    assert_eq!(c.operations[1].controls, [Register::quantum(0)]);
+    assert_eq!(c.operations[2].targets, [Register::classical(1, 0)]);
+
+    let json = serde_json::to_string(&c).expect("circuit should serialize");
+    let round_trip: Circuit = serde_json::from_str(&json).expect("circuit should deserialize");
+    assert_eq!(round_trip, c);
+}
@@ -0,0 +1,293 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{Builder, Circuit, Config, Error};
+use qsc_eval::backend::Backend;
+use std::fmt::Write;
+
+/// A circuit that a host program constructs gate by gate, for example
+/// `CircuitBuilder::new().h(0).cx(0, 1).m(1)`, so that it can be drawn as a [`Circuit`] or
+/// defined as a Q# operation, without writing Q# source for it.
+///
+/// Qubits are identified by their index, and the circuit acts on as many qubits as the
+/// largest index used plus one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitBuilder {
+    gates: Vec<Gate>,
+    num_qubits: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Gate {
+    H(usize),
+    X(usize),
+    Y(usize),
+    Z(usize),
+    S(usize),
+    SAdj(usize),
+    T(usize),
+    TAdj(usize),
+    Cx(usize, usize),
+    Cy(usize, usize),
+    Cz(usize, usize),
+    Ccx(usize, usize, usize),
+    Swap(usize, usize),
+    Rx(f64, usize),
+    Ry(f64, usize),
+    Rz(f64, usize),
+    Rxx(f64, usize, usize),
+    Ryy(f64, usize, usize),
+    Rzz(f64, usize, usize),
+    Reset(usize),
+    M(usize),
+}
+
+impl CircuitBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of qubits that the circuit acts on.
+    #[must_use]
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    #[must_use]
+    pub fn h(self, q: usize) -> Self {
+        self.push(Gate::H(q), &[q])
+    }
+
+    #[must_use]
+    pub fn x(self, q: usize) -> Self {
+        self.push(Gate::X(q), &[q])
+    }
+
+    #[must_use]
+    pub fn y(self, q: usize) -> Self {
+        self.push(Gate::Y(q), &[q])
+    }
+
+    #[must_use]
+    pub fn z(self, q: usize) -> Self {
+        self.push(Gate::Z(q), &[q])
+    }
+
+    #[must_use]
+    pub fn s(self, q: usize) -> Self {
+        self.push(Gate::S(q), &[q])
+    }
+
+    #[must_use]
+    pub fn sadj(self, q: usize) -> Self {
+        self.push(Gate::SAdj(q), &[q])
+    }
+
+    #[must_use]
+    pub fn t(self, q: usize) -> Self {
+        self.push(Gate::T(q), &[q])
+    }
+
+    #[must_use]
+    pub fn tadj(self, q: usize) -> Self {
+        self.push(Gate::TAdj(q), &[q])
+    }
+
+    #[must_use]
+    pub fn cx(self, ctl: usize, q: usize) -> Self {
+        self.push(Gate::Cx(ctl, q), &[ctl, q])
+    }
+
+    #[must_use]
+    pub fn cy(self, ctl: usize, q: usize) -> Self {
+        self.push(Gate::Cy(ctl, q), &[ctl, q])
+    }
+
+    #[must_use]
+    pub fn cz(self, ctl: usize, q: usize) -> Self {
+        self.push(Gate::Cz(ctl, q), &[ctl, q])
+    }
+
+    #[must_use]
+    pub fn ccx(self, ctl0: usize, ctl1: usize, q: usize) -> Self {
+        self.push(Gate::Ccx(ctl0, ctl1, q), &[ctl0, ctl1, q])
+    }
+
+    #[must_use]
+    pub fn swap(self, q0: usize, q1: usize) -> Self {
+        self.push(Gate::Swap(q0, q1), &[q0, q1])
+    }
+
+    #[must_use]
+    pub fn rx(self, theta: f64, q: usize) -> Self {
+        self.push(Gate::Rx(theta, q), &[q])
+    }
+
+    #[must_use]
+    pub fn ry(self, theta: f64, q: usize) -> Self {
+        self.push(Gate::Ry(theta, q), &[q])
+    }
+
+    #[must_use]
+    pub fn rz(self, theta: f64, q: usize) -> Self {
+        self.push(Gate::Rz(theta, q), &[q])
+    }
+
+    #[must_use]
+    pub fn rxx(self, theta: f64, q0: usize, q1: usize) -> Self {
+        self.push(Gate::Rxx(theta, q0, q1), &[q0, q1])
+    }
+
+    #[must_use]
+    pub fn ryy(self, theta: f64, q0: usize, q1: usize) -> Self {
+        self.push(Gate::Ryy(theta, q0, q1), &[q0, q1])
+    }
+
+    #[must_use]
+    pub fn rzz(self, theta: f64, q0: usize, q1: usize) -> Self {
+        self.push(Gate::Rzz(theta, q0, q1), &[q0, q1])
+    }
+
+    #[must_use]
+    pub fn reset(self, q: usize) -> Self {
+        self.push(Gate::Reset(q), &[q])
+    }
+
+    /// Measures the qubit in the Pauli Z basis. The results of the measurements are returned by
+    /// the operation that the circuit is defined as, in the order they are measured.
+    #[must_use]
+    pub fn m(self, q: usize) -> Self {
+        self.push(Gate::M(q), &[q])
+    }
+
+    /// The circuit representation of the gates, as it would be traced from running them.
+    #[must_use]
+    pub fn to_circuit(&self) -> Circuit {
+        let mut builder = Builder::new(Config::default());
+        let qubits = (0..self.num_qubits)
+            .map(|_| builder.qubit_allocate())
+            .collect::<Vec<_>>();
+        for gate in &self.gates {
+            gate.apply(&mut builder, &qubits);
+        }
+        builder.finish()
+    }
+
+    /// Generates the Q# declaration of the circuit as an operation with the given name, that
+    /// applies the gates to a qubit array of at least [`Self::num_qubits`] qubits.
+    ///
+    /// The operation returns the measurement results as a `Result[]` if the circuit measures any
+    /// qubits, and otherwise is `Unit`, and is also `Adj + Ctl` if the circuit does not reset
+    /// any qubits. Rotation angles must be finite.
+    /// # Errors
+    /// Returns an error if the name is not an identifier.
+    pub fn to_qsharp(&self, name: &str) -> Result<String, Error> {
+        let mut chars = name.chars();
+        if !(chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_'))
+        {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+
+        let mut body = String::new();
+        let mut results = vec![];
+        for gate in &self.gates {
+            if let Gate::M(..) = gate {
+                let result = format!("r{}", results.len());
+                let _ = writeln!(body, "    let {result} = {};", gate.qsharp());
+                results.push(result);
+            } else {
+                let _ = writeln!(body, "    {};", gate.qsharp());
+            }
+        }
+
+        let output = if results.is_empty() {
+            if self
+                .gates
+                .iter()
+                .any(|gate| matches!(gate, Gate::Reset(..)))
+            {
+                "Unit"
+            } else {
+                "Unit is Adj + Ctl"
+            }
+        } else {
+            let _ = writeln!(body, "    [{}]", results.join(", "));
+            "Result[]"
+        };
+        Ok(format!(
+            "operation {name}(qs : Qubit[]) : {output} {{\n{body}}}\n"
+        ))
+    }
+
+    fn push(mut self, gate: Gate, qubits: &[usize]) -> Self {
+        for q in qubits {
+            self.num_qubits = self.num_qubits.max(q + 1);
+        }
+        self.gates.push(gate);
+        self
+    }
+}
+
+impl Gate {
+    fn apply(self, sim: &mut impl Backend, qubits: &[usize]) {
+        let q = |i: usize| qubits[i];
+        match self {
+            Gate::H(a) => sim.h(q(a)),
+            Gate::X(a) => sim.x(q(a)),
+            Gate::Y(a) => sim.y(q(a)),
+            Gate::Z(a) => sim.z(q(a)),
+            Gate::S(a) => sim.s(q(a)),
+            Gate::SAdj(a) => sim.sadj(q(a)),
+            Gate::T(a) => sim.t(q(a)),
+            Gate::TAdj(a) => sim.tadj(q(a)),
+            Gate::Cx(a, b) => sim.cx(q(a), q(b)),
+            Gate::Cy(a, b) => sim.cy(q(a), q(b)),
+            Gate::Cz(a, b) => sim.cz(q(a), q(b)),
+            Gate::Ccx(a, b, c) => sim.ccx(q(a), q(b), q(c)),
+            Gate::Swap(a, b) => sim.swap(q(a), q(b)),
+            Gate::Rx(theta, a) => sim.rx(theta, q(a)),
+            Gate::Ry(theta, a) => sim.ry(theta, q(a)),
+            Gate::Rz(theta, a) => sim.rz(theta, q(a)),
+            Gate::Rxx(theta, a, b) => sim.rxx(theta, q(a), q(b)),
+            Gate::Ryy(theta, a, b) => sim.ryy(theta, q(a), q(b)),
+            Gate::Rzz(theta, a, b) => sim.rzz(theta, q(a), q(b)),
+            Gate::Reset(a) => sim.reset(q(a)),
+            Gate::M(a) => {
+                sim.m(q(a));
+            }
+        }
+    }
+
+    fn qsharp(self) -> String {
+        match self {
+            Gate::H(a) => format!("H(qs[{a}])"),
+            Gate::X(a) => format!("X(qs[{a}])"),
+            Gate::Y(a) => format!("Y(qs[{a}])"),
+            Gate::Z(a) => format!("Z(qs[{a}])"),
+            Gate::S(a) => format!("S(qs[{a}])"),
+            Gate::SAdj(a) => format!("Adjoint S(qs[{a}])"),
+            Gate::T(a) => format!("T(qs[{a}])"),
+            Gate::TAdj(a) => format!("Adjoint T(qs[{a}])"),
+            Gate::Cx(a, b) => format!("CNOT(qs[{a}], qs[{b}])"),
+            Gate::Cy(a, b) => format!("CY(qs[{a}], qs[{b}])"),
+            Gate::Cz(a, b) => format!("CZ(qs[{a}], qs[{b}])"),
+            Gate::Ccx(a, b, c) => format!("CCNOT(qs[{a}], qs[{b}], qs[{c}])"),
+            Gate::Swap(a, b) => format!("SWAP(qs[{a}], qs[{b}])"),
+            // The debug format of a finite `f64` is a valid Q# `Double` literal that
+            // round-trips, such as `1.0` or `1e-7`.
+            Gate::Rx(theta, a) => format!("Rx({theta:?}, qs[{a}])"),
+            Gate::Ry(theta, a) => format!("Ry({theta:?}, qs[{a}])"),
+            Gate::Rz(theta, a) => format!("Rz({theta:?}, qs[{a}])"),
+            Gate::Rxx(theta, a, b) => format!("Rxx({theta:?}, qs[{a}], qs[{b}])"),
+            Gate::Ryy(theta, a, b) => format!("Ryy({theta:?}, qs[{a}], qs[{b}])"),
+            Gate::Rzz(theta, a, b) => format!("Rzz({theta:?}, qs[{a}], qs[{b}])"),
+            Gate::Reset(a) => format!("Reset(qs[{a}])"),
+            Gate::M(a) => format!("M(qs[{a}])"),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::CircuitBuilder;
+use crate::Error;
+use expect_test::expect;
+
+#[test]
+fn measured_circuit_returns_results() {
+    let circuit = CircuitBuilder::new().h(0).cx(0, 2).rx(0.5, 1).m(2).m(0);
+    assert_eq!(circuit.num_qubits(), 3);
+    expect![[r"
+        operation Bell(qs : Qubit[]) : Result[] {
+            H(qs[0]);
+            CNOT(qs[0], qs[2]);
+            Rx(0.5, qs[1]);
+            let r0 = M(qs[2]);
+            let r1 = M(qs[0]);
+            [r0, r1]
+        }
+    "]]
+    .assert_eq(&circuit.to_qsharp("Bell").expect("name should be valid"));
+}
+
+#[test]
+fn unitary_circuit_is_adjointable_and_controllable() {
+    let circuit = CircuitBuilder::new().sadj(0).ccx(0, 1, 2).rzz(-1e-7, 1, 2);
+    expect![[r"
+        operation Unitary(qs : Qubit[]) : Unit is Adj + Ctl {
+            Adjoint S(qs[0]);
+            CCNOT(qs[0], qs[1], qs[2]);
+            Rzz(-1e-7, qs[1], qs[2]);
+        }
+    "]]
+    .assert_eq(&circuit.to_qsharp("Unitary").expect("name should be valid"));
+}
+
+#[test]
+fn circuit_with_reset_is_not_adjointable() {
+    let circuit = CircuitBuilder::new().x(0).reset(0);
+    expect![[r"
+        operation Prepare(qs : Qubit[]) : Unit {
+            X(qs[0]);
+            Reset(qs[0]);
+        }
+    "]]
+    .assert_eq(&circuit.to_qsharp("Prepare").expect("name should be valid"));
+}
+
+#[test]
+fn invalid_name_is_error() {
+    let circuit = CircuitBuilder::new().h(0);
+    assert!(matches!(
+        circuit.to_qsharp("Test.Bell"),
+        Err(Error::InvalidName(name)) if name == "Test.Bell"
+    ));
+}
+
+#[test]
+fn circuit_is_drawn_as_traced() {
+    let circuit = CircuitBuilder::new().h(0).cx(0, 1).m(1).to_circuit();
+    let gates = circuit
+        .operations
+        .iter()
+        .map(|op| (op.gate.as_str(), op.is_controlled, op.is_measurement))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        gates,
+        [
+            ("H", false, false),
+            ("X", true, false),
+            ("Measure", false, true)
+        ]
+    );
+    assert_eq!(circuit.qubits.len(), 2);
+    assert_eq!(circuit.qubits[1].num_children, 1);
+}
@@ -3,8 +3,11 @@
 
 mod builder;
 mod circuit;
+mod circuit_builder;
 pub mod operations;
+mod quirk;
 
 pub use builder::Builder;
 pub use circuit::{Circuit, Config, Operation};
+pub use circuit_builder::CircuitBuilder;
 pub use operations::Error;
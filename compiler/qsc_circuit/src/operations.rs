@@ -25,6 +25,19 @@ pub enum Error {
         "controlled invocations are not currently supported. consider wrapping the invocation in a lambda expression"
     ))]
     ControlledUnsupported,
+    #[error("`{0}` is not a valid name for a circuit")]
+    #[diagnostic(code("Qsc.Circuit.InvalidName"))]
+    #[diagnostic(help("circuit names must be Q# identifiers, without a namespace"))]
+    InvalidName(String),
+    #[error("invalid Quirk circuit: {0}")]
+    #[diagnostic(code("Qsc.Circuit.InvalidQuirkJson"))]
+    InvalidQuirkJson(String),
+    #[error("Quirk gate `{0}` in column {1} is not supported")]
+    #[diagnostic(code("Qsc.Circuit.UnsupportedQuirkGate"))]
+    #[diagnostic(help(
+        "only Pauli, H, S, T, swap and measurement gates, controlled X, Y and Z gates, and doubly controlled X and Z gates can be imported"
+    ))]
+    UnsupportedQuirkGate(String, usize),
 }
 
 /// If the item is a callable, returns the information that would
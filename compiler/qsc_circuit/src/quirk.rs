@@ -0,0 +1,94 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{CircuitBuilder, Error};
+use serde_json::Value;
+
+impl CircuitBuilder {
+    /// Reads a circuit exported from the Quirk circuit simulator, whose JSON has a `cols` array
+    /// with the gates of each column from the top wire down, so that a circuit drawn there can be
+    /// turned into a Q# operation. Wire `i` is qubit `i`.
+    ///
+    /// The Pauli, Hadamard, S and T gates are supported, as are measurements, swaps, and `X`, `Y`
+    /// and `Z` gates with one control or `X` and `Z` gates with two, where controls can also be
+    /// anti-controls.
+    /// # Errors
+    /// Returns an error if the JSON is not a Quirk circuit or uses a gate that is not supported.
+    pub fn from_quirk(json: &str) -> Result<Self, Error> {
+        let circuit: Value =
+            serde_json::from_str(json).map_err(|e| Error::InvalidQuirkJson(e.to_string()))?;
+        let cols = circuit
+            .get("cols")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::InvalidQuirkJson("missing `cols` array".to_string()))?;
+
+        let mut builder = Self::new();
+        for (column, cells) in cols.iter().enumerate() {
+            let cells = cells.as_array().ok_or_else(|| {
+                Error::InvalidQuirkJson(format!("column {column} is not an array"))
+            })?;
+            builder = builder.quirk_column(column, cells)?;
+        }
+        Ok(builder)
+    }
+
+    fn quirk_column(mut self, column: usize, cells: &[Value]) -> Result<Self, Error> {
+        let unsupported = |gate: &str| Error::UnsupportedQuirkGate(gate.to_string(), column);
+        let mut controls = vec![];
+        let mut anti_controls = vec![];
+        let mut swaps = vec![];
+        let mut targets = vec![];
+        for (q, cell) in cells.iter().enumerate() {
+            match cell {
+                // Quirk exports an empty wire as `1`, and `…` only spaces out the drawing.
+                Value::Number(n) if n.as_u64() == Some(1) => {}
+                Value::String(gate) => match gate.as_str() {
+                    "…" => {}
+                    "•" => controls.push(q),
+                    "◦" => {
+                        controls.push(q);
+                        anti_controls.push(q);
+                    }
+                    "Swap" => swaps.push(q),
+                    gate => targets.push((q, gate)),
+                },
+                cell => return Err(unsupported(&cell.to_string())),
+            }
+        }
+
+        for &q in &anti_controls {
+            self = self.x(q);
+        }
+        match (&swaps[..], &controls[..]) {
+            ([], _) => {}
+            (&[q0, q1], []) => self = self.swap(q0, q1),
+            _ => return Err(unsupported("Swap")),
+        }
+        for (q, gate) in targets {
+            self = match (gate, &controls[..]) {
+                ("H", []) => self.h(q),
+                ("X", []) => self.x(q),
+                ("Y", []) => self.y(q),
+                ("Z", []) => self.z(q),
+                ("Z^½", []) => self.s(q),
+                ("Z^-½", []) => self.sadj(q),
+                ("Z^¼", []) => self.t(q),
+                ("Z^-¼", []) => self.tadj(q),
+                ("Measure", []) => self.m(q),
+                ("X", &[ctl]) => self.cx(ctl, q),
+                ("Y", &[ctl]) => self.cy(ctl, q),
+                ("Z", &[ctl]) => self.cz(ctl, q),
+                ("X", &[ctl0, ctl1]) => self.ccx(ctl0, ctl1, q),
+                ("Z", &[ctl0, ctl1]) => self.h(q).ccx(ctl0, ctl1, q).h(q),
+                (gate, _) => return Err(unsupported(gate)),
+            };
+        }
+        for &q in &anti_controls {
+            self = self.x(q);
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::{CircuitBuilder, Error};
+use expect_test::expect;
+
+#[test]
+fn bell_circuit_is_imported() {
+    let circuit = CircuitBuilder::from_quirk(r#"{"cols":[["H"],["•","X"],["Measure","Measure"]]}"#)
+        .expect("circuit should be imported");
+    assert_eq!(circuit, CircuitBuilder::new().h(0).cx(0, 1).m(0).m(1));
+}
+
+#[test]
+fn phase_gates_swaps_and_empty_wires_are_imported() {
+    let circuit = CircuitBuilder::from_quirk(
+        r#"{"cols":[[1,"Z^½","Z^-¼"],["Swap",1,"Swap"],["…","Z^¼"],["•","•","Z"]]}"#,
+    )
+    .expect("circuit should be imported");
+    expect![[r"
+        operation Imported(qs : Qubit[]) : Unit is Adj + Ctl {
+            S(qs[1]);
+            Adjoint T(qs[2]);
+            SWAP(qs[0], qs[2]);
+            T(qs[1]);
+            H(qs[2]);
+            CCNOT(qs[0], qs[1], qs[2]);
+            H(qs[2]);
+        }
+    "]]
+    .assert_eq(&circuit.to_qsharp("Imported").expect("name should be valid"));
+}
+
+#[test]
+fn anti_controls_are_conjugated_by_x() {
+    let circuit =
+        CircuitBuilder::from_quirk(r#"{"cols":[["◦","Y"]]}"#).expect("circuit should be imported");
+    assert_eq!(circuit, CircuitBuilder::new().x(0).cy(0, 1).x(0));
+}
+
+#[test]
+fn unsupported_gate_is_error() {
+    let error = CircuitBuilder::from_quirk(r#"{"cols":[["H"],["•","•","•","X"]]}"#)
+        .expect_err("three controls should not be supported");
+    assert!(matches!(error, Error::UnsupportedQuirkGate(gate, 1) if gate == "X"));
+
+    let error = CircuitBuilder::from_quirk(r#"{"cols":[[{"id":"Rxft","arg":"pi/2"}]]}"#)
+        .expect_err("parameterized gates should not be supported");
+    assert!(matches!(error, Error::UnsupportedQuirkGate(_, 0)));
+}
+
+#[test]
+fn invalid_json_is_error() {
+    for json in ["[", r#"{"gates":[]}"#, r#"{"cols":["H"]}"#] {
+        let error = CircuitBuilder::from_quirk(json).expect_err("json should not be a circuit");
+        assert!(matches!(error, Error::InvalidQuirkJson(..)), "{json}");
+    }
+}
@@ -0,0 +1,957 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Generates Q# source from HIR. Unlike the AST based generator in [`crate::qsharp`], the
+//! output reflects the program after compiler passes have run, so generated specializations
+//! and other compiler-produced code are written out explicitly.
+//!
+//! Since HIR is available for every package in the store, including compiled packages such as
+//! the standard library, this can be used to show source for items that have none of their own.
+
+#[cfg(test)]
+mod tests;
+
+use std::rc::Rc;
+
+use qsc_formatter::formatter::format_str;
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    hir::{
+        Attr, BinOp, Block, CallableDecl, CallableKind, Expr, ExprKind, Field, Functor, Item,
+        ItemId, ItemKind, Lit, LocalItemId, Mutability, NodeId, PackageId, Pat, PatKind, Pauli,
+        PrimField, QubitInit, QubitInitKind, QubitSource, Res, Result, SpecBody, SpecDecl, SpecGen,
+        Stmt, StmtKind, StringComponent, UnOp, Visibility,
+    },
+    ty::{Arrow, FunctorSet, FunctorSetValue, GenericParam, Prim, Ty, Udt, UdtDef, UdtDefKind},
+    visit::{self, Visitor},
+};
+use rustc_hash::FxHashMap;
+
+/// Writes the given callable as Q# source with all of its specializations written out
+/// explicitly, as produced by specialization generation (e.g. `adjoint auto`).
+///
+/// Returns `None` if the package or item does not exist, or the item is not a callable.
+#[must_use]
+pub fn write_callable_specs(
+    store: &PackageStore,
+    package: PackageId,
+    item: LocalItemId,
+) -> Option<String> {
+    let unit = store.get(package)?;
+    let item = unit.package.items.get(item)?;
+    let ItemKind::Callable(decl) = &item.kind else {
+        return None;
+    };
+
+    let mut gen = HirQSharpGen::new(store, package);
+    gen.write_doc(&item.doc);
+    gen.write_attrs(&item.attrs);
+    gen.write_visibility(item.visibility);
+    gen.write_callable_decl(decl, true);
+    Some(format_str(&gen.output))
+}
+
+/// Writes the given item as Q# source. Callables are written with the specializations they
+/// were declared with, types as `newtype` declarations and namespaces with all of their items.
+///
+/// Returns `None` if the package or item does not exist.
+#[must_use]
+pub fn write_item(store: &PackageStore, package: PackageId, item: LocalItemId) -> Option<String> {
+    let unit = store.get(package)?;
+    let item = unit.package.items.get(item)?;
+    let mut gen = HirQSharpGen::new(store, package);
+    gen.write_item(item);
+    Some(format_str(&gen.output))
+}
+
+/// Writes all of the namespaces in the given package as Q# source.
+///
+/// Returns `None` if the package does not exist.
+#[must_use]
+pub fn write_package(store: &PackageStore, package: PackageId) -> Option<String> {
+    let unit = store.get(package)?;
+    let mut gen = HirQSharpGen::new(store, package);
+    for item in unit.package.items.values() {
+        if item.parent.is_none() && matches!(item.kind, ItemKind::Namespace(..)) {
+            gen.write_item(item);
+        }
+    }
+    Some(format_str(&gen.output))
+}
+
+struct HirQSharpGen<'a> {
+    store: &'a PackageStore,
+    package: PackageId,
+    locals: FxHashMap<NodeId, Rc<str>>,
+    output: String,
+}
+
+impl<'a> HirQSharpGen<'a> {
+    fn new(store: &'a PackageStore, package: PackageId) -> Self {
+        let mut locals = LocalNames::default();
+        if let Some(unit) = store.get(package) {
+            locals.visit_package(&unit.package);
+        }
+        Self {
+            store,
+            package,
+            locals: locals.names,
+            output: String::new(),
+        }
+    }
+
+    fn write(&mut self, args: &str) {
+        self.output.push_str(args);
+    }
+
+    fn writeln(&mut self, args: &str) {
+        self.write(args);
+        self.write("\n");
+    }
+
+    fn write_item(&mut self, item: &Item) {
+        match &item.kind {
+            ItemKind::Callable(decl) => {
+                self.write_doc(&item.doc);
+                self.write_attrs(&item.attrs);
+                self.write_visibility(item.visibility);
+                self.write_callable_decl(decl, false);
+            }
+            ItemKind::Namespace(name, items) => {
+                self.write_doc(&item.doc);
+                self.write("namespace ");
+                self.write(&name.name());
+                self.writeln(" {");
+                let store = self.store;
+                let package = self.package;
+                if let Some(unit) = store.get(package) {
+                    for item in items {
+                        if let Some(item) = unit.package.items.get(*item) {
+                            self.write_item(item);
+                        }
+                    }
+                }
+                self.writeln("}");
+            }
+            ItemKind::Ty(name, udt) => {
+                self.write_doc(&item.doc);
+                self.write_visibility(item.visibility);
+                self.write("newtype ");
+                self.write(&ident_name(&name.name));
+                self.write(" = ");
+                self.write_udt_def(&udt.definition);
+                self.writeln(";");
+            }
+        }
+    }
+
+    fn write_udt_def(&mut self, def: &UdtDef) {
+        match &def.kind {
+            UdtDefKind::Field(field) => {
+                if let Some(name) = &field.name {
+                    self.write(name);
+                    self.write(" : ");
+                }
+                self.write_ty(&field.ty);
+            }
+            UdtDefKind::Tuple(defs) => {
+                self.write("(");
+                self.write_sep(defs, ", ", Self::write_udt_def);
+                if defs.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+        }
+    }
+
+    fn write_doc(&mut self, doc: &str) {
+        for line in doc.lines() {
+            self.write("/// ");
+            self.writeln(line);
+        }
+    }
+
+    fn write_attrs(&mut self, attrs: &[Attr]) {
+        for attr in attrs {
+            match attr {
+                Attr::EntryPoint => self.writeln("@EntryPoint()"),
+                Attr::Unimplemented => self.writeln("@Unimplemented()"),
+                Attr::SimulatableIntrinsic => self.writeln("@SimulatableIntrinsic()"),
+                Attr::Inline => self.writeln("@Inline()"),
+                // The arguments of `@Config` are not preserved in HIR, and the item
+                // is only present because its configuration was satisfied.
+                Attr::Config => {}
+            }
+        }
+    }
+
+    fn write_visibility(&mut self, visibility: Visibility) {
+        if visibility == Visibility::Internal {
+            self.write("internal ");
+        }
+    }
+
+    fn write_callable_decl(&mut self, decl: &CallableDecl, explicit_specs: bool) {
+        match decl.kind {
+            CallableKind::Function => self.write("function "),
+            CallableKind::Operation => self.write("operation "),
+        }
+        self.write(&ident_name(&decl.name.name));
+
+        let ty_params = decl
+            .generics
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Ty(name) => Some(name.name.to_string()),
+                GenericParam::Functor(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if !ty_params.is_empty() {
+            self.write("<");
+            self.write(&ty_params.join(", "));
+            self.write(">");
+        }
+
+        self.write_input_pat(&decl.input);
+        self.write(" : ");
+        self.write_ty(&decl.output);
+        if decl.functors != FunctorSetValue::Empty {
+            self.write(" is ");
+            self.write(&decl.functors.to_string());
+        }
+
+        let has_specs = decl.adj.is_some() || decl.ctl.is_some() || decl.ctl_adj.is_some();
+        match &decl.body.body {
+            SpecBody::Impl(_, block) if !has_specs && !explicit_specs => {
+                self.write(" ");
+                self.write_block(block);
+                self.writeln("");
+            }
+            SpecBody::Impl(_, block) if decl.kind == CallableKind::Function => {
+                self.write(" ");
+                self.write_block(block);
+                self.writeln("");
+            }
+            _ => {
+                self.writeln(" {");
+                self.write_spec_decl("body", &decl.body);
+                for (keyword, spec) in [
+                    ("adjoint", &decl.adj),
+                    ("controlled", &decl.ctl),
+                    ("controlled adjoint", &decl.ctl_adj),
+                ] {
+                    if let Some(spec) = spec {
+                        self.write_spec_decl(keyword, spec);
+                    }
+                }
+                self.writeln("}");
+            }
+        }
+    }
+
+    fn write_spec_decl(&mut self, keyword: &str, decl: &SpecDecl) {
+        self.write(keyword);
+        self.write(" ");
+        match &decl.body {
+            SpecBody::Gen(gen) => {
+                self.write(match gen {
+                    SpecGen::Auto => "auto",
+                    SpecGen::Distribute => "distribute",
+                    SpecGen::Intrinsic => "intrinsic",
+                    SpecGen::Invert => "invert",
+                    SpecGen::Slf => "self",
+                });
+                self.writeln(";");
+            }
+            SpecBody::Impl(ctls, block) => {
+                self.write("(");
+                if let Some(ctls) = ctls {
+                    self.write_pat(ctls, false);
+                    self.write(", ");
+                }
+                self.write("...) ");
+                self.write_block(block);
+                self.writeln("");
+            }
+        }
+    }
+
+    fn write_input_pat(&mut self, pat: &Pat) {
+        match &pat.kind {
+            PatKind::Tuple(_) => self.write_pat(pat, true),
+            _ => {
+                self.write("(");
+                self.write_pat(pat, true);
+                self.write(")");
+            }
+        }
+    }
+
+    fn write_pat(&mut self, pat: &Pat, typed: bool) {
+        match &pat.kind {
+            PatKind::Bind(name) => {
+                self.write(&ident_name(&name.name));
+                if typed {
+                    self.write(" : ");
+                    self.write_ty(&pat.ty);
+                }
+            }
+            PatKind::Discard => {
+                self.write("_");
+                if typed {
+                    self.write(" : ");
+                    self.write_ty(&pat.ty);
+                }
+            }
+            PatKind::Tuple(pats) => {
+                self.write("(");
+                self.write_sep(pats, ", ", |gen, pat| gen.write_pat(pat, typed));
+                if pats.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            PatKind::Err => self.write("_"),
+        }
+    }
+
+    fn write_ty(&mut self, ty: &Ty) {
+        match ty {
+            Ty::Array(item) => {
+                self.write_ty(item);
+                self.write("[]");
+            }
+            Ty::Arrow(arrow) => self.write_arrow(arrow),
+            Ty::Param(name, _) => self.write(name),
+            Ty::Prim(prim) => self.write(match prim {
+                Prim::BigInt => "BigInt",
+                Prim::Bool => "Bool",
+                Prim::Double => "Double",
+                Prim::Int => "Int",
+                Prim::Pauli => "Pauli",
+                Prim::Qubit => "Qubit",
+                Prim::Range | Prim::RangeTo | Prim::RangeFrom | Prim::RangeFull => "Range",
+                Prim::Result => "Result",
+                Prim::String => "String",
+            }),
+            Ty::Tuple(items) if items.is_empty() => self.write("Unit"),
+            Ty::Tuple(items) => {
+                self.write("(");
+                self.write_sep(items, ", ", Self::write_ty);
+                if items.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            Ty::Udt(name, res) => match res {
+                Res::Item(id) => {
+                    let name = self.item_name(*id);
+                    self.write(&name);
+                }
+                _ => self.write(name),
+            },
+            Ty::Infer(_) | Ty::Err => self.write("_"),
+        }
+    }
+
+    fn write_arrow(&mut self, arrow: &Arrow) {
+        self.write("(");
+        self.write_ty(&arrow.input);
+        match arrow.kind {
+            CallableKind::Function => self.write(" -> "),
+            CallableKind::Operation => self.write(" => "),
+        }
+        self.write_ty(&arrow.output);
+        let functors = match arrow.functors {
+            FunctorSet::Value(functors) | FunctorSet::Param(_, functors) => functors,
+            FunctorSet::Infer(_) => FunctorSetValue::Empty,
+        };
+        if functors != FunctorSetValue::Empty {
+            self.write(" is ");
+            self.write(&functors.to_string());
+        }
+        self.write(")");
+    }
+
+    fn write_block(&mut self, block: &Block) {
+        self.writeln("{");
+        for stmt in &block.stmts {
+            self.write_stmt(stmt);
+        }
+        self.write("}");
+    }
+
+    fn write_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => {
+                self.write_expr(expr);
+                self.writeln("");
+            }
+            StmtKind::Semi(expr) => {
+                self.write_expr(expr);
+                self.writeln(";");
+            }
+            StmtKind::Item(item) => self.write_local_item(*item),
+            StmtKind::Local(mutability, pat, value) => {
+                match mutability {
+                    Mutability::Immutable => self.write("let "),
+                    Mutability::Mutable => self.write("mutable "),
+                }
+                self.write_pat(pat, false);
+                self.write(" = ");
+                self.write_expr(value);
+                self.writeln(";");
+            }
+            StmtKind::Qubit(source, pat, init, block) => {
+                match source {
+                    QubitSource::Fresh => self.write("use "),
+                    QubitSource::Dirty => self.write("borrow "),
+                }
+                self.write_pat(pat, false);
+                self.write(" = ");
+                self.write_qubit_init(init);
+                match block {
+                    Some(block) => {
+                        self.write(" ");
+                        self.write_block(block);
+                        self.writeln("");
+                    }
+                    None => self.writeln(";"),
+                }
+            }
+        }
+    }
+
+    fn write_local_item(&mut self, item: LocalItemId) {
+        let Some(item) = self
+            .store
+            .get(self.package)
+            .and_then(|unit| unit.package.items.get(item))
+        else {
+            return;
+        };
+        if let ItemKind::Callable(decl) = &item.kind {
+            self.write_doc(&item.doc);
+            self.write_attrs(&item.attrs);
+            self.write_callable_decl(decl, false);
+        }
+    }
+
+    fn write_qubit_init(&mut self, init: &QubitInit) {
+        match &init.kind {
+            QubitInitKind::Array(len) => {
+                self.write("Qubit[");
+                self.write_expr(len);
+                self.write("]");
+            }
+            QubitInitKind::Single => self.write("Qubit()"),
+            QubitInitKind::Tuple(inits) => {
+                self.write("(");
+                self.write_sep(inits, ", ", Self::write_qubit_init);
+                if inits.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            QubitInitKind::Err => self.write("_"),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn write_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Array(items) => {
+                self.write("[");
+                self.write_sep(items, ", ", Self::write_expr);
+                self.write("]");
+            }
+            ExprKind::ArrayRepeat(item, size) => {
+                self.write("[");
+                self.write_expr(item);
+                self.write(", size = ");
+                self.write_expr(size);
+                self.write("]");
+            }
+            ExprKind::Assign(lhs, rhs) => {
+                self.write("set ");
+                self.write_expr(lhs);
+                self.write(" = ");
+                self.write_expr(rhs);
+            }
+            ExprKind::AssignOp(op, lhs, rhs) => {
+                self.write("set ");
+                self.write_expr(lhs);
+                self.write(" ");
+                self.write(binop_as_str(*op));
+                self.write("= ");
+                self.write_expr(rhs);
+            }
+            ExprKind::AssignField(record, field, value) => {
+                self.write("set ");
+                self.write_expr(record);
+                self.write(" w/= ");
+                self.write_field_name(&record.ty, field);
+                self.write(" <- ");
+                self.write_expr(value);
+            }
+            ExprKind::AssignIndex(array, index, value) => {
+                self.write("set ");
+                self.write_expr(array);
+                self.write(" w/= ");
+                self.write_expr(index);
+                self.write(" <- ");
+                self.write_expr(value);
+            }
+            ExprKind::BinOp(op, lhs, rhs) => {
+                self.write_operand(lhs);
+                self.write(" ");
+                self.write(binop_as_str(*op));
+                self.write(" ");
+                self.write_operand(rhs);
+            }
+            ExprKind::Block(block) => self.write_block(block),
+            ExprKind::Call(callee, arg) => {
+                match &callee.kind {
+                    ExprKind::Var(..) | ExprKind::UnOp(UnOp::Functor(_), _) => {
+                        self.write_expr(callee);
+                    }
+                    _ => self.write_parenthesized(callee),
+                }
+                match &arg.kind {
+                    ExprKind::Tuple(_) => self.write_expr(arg),
+                    _ => self.write_parenthesized(arg),
+                }
+            }
+            ExprKind::Closure(_, item) => self.write_closure(*item),
+            ExprKind::Conjugate(within, apply) => {
+                self.write("within ");
+                self.write_block(within);
+                self.write(" apply ");
+                self.write_block(apply);
+            }
+            ExprKind::Fail(msg) => {
+                self.write("fail ");
+                self.write_expr(msg);
+            }
+            ExprKind::Field(record, field) => {
+                self.write_operand(record);
+                if matches!(field, Field::Path(path) if path.indices.is_empty()) {
+                    self.write("!");
+                } else {
+                    self.write(".");
+                    self.write_field_name(&record.ty, field);
+                }
+            }
+            ExprKind::For(pat, iterable, body) => {
+                self.write("for ");
+                self.write_pat(pat, false);
+                self.write(" in ");
+                self.write_expr(iterable);
+                self.write(" ");
+                self.write_block(body);
+            }
+            ExprKind::Hole => self.write("_"),
+            ExprKind::If(cond, body, otherwise) => {
+                self.write("if ");
+                self.write_expr(cond);
+                self.write(" ");
+                self.write_expr(body);
+                if let Some(otherwise) = otherwise {
+                    if matches!(otherwise.kind, ExprKind::If(..)) {
+                        // Writing the nested `if` after this prefix produces `elif`.
+                        self.write(" el");
+                    } else {
+                        self.write(" else ");
+                    }
+                    self.write_expr(otherwise);
+                }
+            }
+            ExprKind::Index(array, index) => {
+                self.write_operand(array);
+                self.write("[");
+                self.write_expr(index);
+                self.write("]");
+            }
+            ExprKind::Lit(lit) => self.write_lit(lit),
+            ExprKind::Range(start, step, end) => {
+                if let Some(start) = start {
+                    self.write_operand(start);
+                }
+                if let Some(step) = step {
+                    self.write(if start.is_some() { ".." } else { "..." });
+                    self.write_operand(step);
+                }
+                match end {
+                    Some(end) => {
+                        self.write(if start.is_some() || step.is_some() {
+                            ".."
+                        } else {
+                            "..."
+                        });
+                        self.write_operand(end);
+                    }
+                    None => self.write("..."),
+                }
+            }
+            ExprKind::Repeat(body, until, fixup) => {
+                self.write("repeat ");
+                self.write_block(body);
+                self.write(" until ");
+                self.write_expr(until);
+                if let Some(fixup) = fixup {
+                    self.write(" fixup ");
+                    self.write_block(fixup);
+                }
+            }
+            ExprKind::Return(value) => {
+                self.write("return ");
+                self.write_expr(value);
+            }
+            ExprKind::Struct(res, copy, fields) => {
+                self.write("new ");
+                if let Res::Item(id) = res {
+                    let name = self.item_name(*id);
+                    self.write(&name);
+                }
+                self.write(" { ");
+                if let Some(copy) = copy {
+                    self.write("...");
+                    self.write_expr(copy);
+                    if !fields.is_empty() {
+                        self.write(", ");
+                    }
+                }
+                self.write_sep(fields, ", ", |gen, assign| {
+                    gen.write_field_name(&expr.ty, &assign.field);
+                    gen.write(" = ");
+                    gen.write_expr(&assign.value);
+                });
+                self.write(" }");
+            }
+            ExprKind::String(components) => self.write_string(components),
+            ExprKind::UpdateIndex(array, index, value) => {
+                self.write_operand(array);
+                self.write(" w/ ");
+                self.write_expr(index);
+                self.write(" <- ");
+                self.write_operand(value);
+            }
+            ExprKind::Tuple(items) => {
+                self.write("(");
+                self.write_sep(items, ", ", Self::write_expr);
+                if items.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            ExprKind::UnOp(UnOp::Unwrap, operand) => {
+                self.write_operand(operand);
+                self.write("!");
+            }
+            ExprKind::UnOp(op, operand) => {
+                self.write(unop_as_str(*op));
+                match (op, &operand.kind) {
+                    (UnOp::Functor(_), ExprKind::Var(..) | ExprKind::UnOp(UnOp::Functor(_), _)) => {
+                        self.write_expr(operand);
+                    }
+                    _ => self.write_operand(operand),
+                }
+            }
+            ExprKind::UpdateField(record, field, value) => {
+                self.write_operand(record);
+                self.write(" w/ ");
+                self.write_field_name(&record.ty, field);
+                self.write(" <- ");
+                self.write_operand(value);
+            }
+            ExprKind::Var(res, _) => match res {
+                Res::Item(id) => {
+                    let name = self.item_name(*id);
+                    self.write(&name);
+                }
+                Res::Local(id) => {
+                    let name = self
+                        .locals
+                        .get(id)
+                        .map_or_else(|| format!("__local_{id}"), |name| ident_name(name));
+                    self.write(&name);
+                }
+                Res::Err => self.write("_"),
+            },
+            ExprKind::While(cond, body) => {
+                self.write("while ");
+                self.write_expr(cond);
+                self.write(" ");
+                self.write_block(body);
+            }
+            ExprKind::Err => self.write("_"),
+        }
+    }
+
+    /// Writes an expression that is the operand of an operator, adding parentheses unless the
+    /// expression cannot be split by operator precedence.
+    fn write_operand(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Array(_)
+            | ExprKind::ArrayRepeat(..)
+            | ExprKind::Call(..)
+            | ExprKind::Field(..)
+            | ExprKind::Hole
+            | ExprKind::Index(..)
+            | ExprKind::String(_)
+            | ExprKind::Tuple(_)
+            | ExprKind::Var(..) => self.write_expr(expr),
+            ExprKind::Lit(Lit::Int(value)) if *value < 0 => self.write_parenthesized(expr),
+            ExprKind::Lit(Lit::Double(value)) if value.is_sign_negative() => {
+                self.write_parenthesized(expr);
+            }
+            ExprKind::Lit(_) => self.write_expr(expr),
+            _ => self.write_parenthesized(expr),
+        }
+    }
+
+    fn write_parenthesized(&mut self, expr: &Expr) {
+        self.write("(");
+        self.write_expr(expr);
+        self.write(")");
+    }
+
+    fn write_closure(&mut self, item: LocalItemId) {
+        let Some(ItemKind::Callable(decl)) = self
+            .store
+            .get(self.package)
+            .and_then(|unit| unit.package.items.get(item))
+            .map(|item| &item.kind)
+        else {
+            self.write("_");
+            return;
+        };
+
+        // Lifted lambdas take their captured variables as leading inputs, followed by the
+        // original lambda input as the last element of the input tuple.
+        let input = match &decl.input.kind {
+            PatKind::Tuple(items) => items.last(),
+            _ => None,
+        };
+        self.write("(");
+        match input {
+            Some(pat) => match &pat.kind {
+                PatKind::Tuple(_) => self.write_pat(pat, false),
+                _ => {
+                    self.write("(");
+                    self.write_pat(pat, false);
+                    self.write(")");
+                }
+            },
+            None => self.write("()"),
+        }
+        match decl.kind {
+            CallableKind::Function => self.write(" -> "),
+            CallableKind::Operation => self.write(" => "),
+        }
+        if let SpecBody::Impl(_, block) = &decl.body.body {
+            match block.stmts.as_slice() {
+                [Stmt {
+                    kind: StmtKind::Expr(body),
+                    ..
+                }] => self.write_expr(body),
+                _ => self.write_block(block),
+            }
+        }
+        self.write(")");
+    }
+
+    fn write_field_name(&mut self, record_ty: &Ty, field: &Field) {
+        match field {
+            Field::Path(path) => {
+                let name = self
+                    .udt(record_ty)
+                    .and_then(|udt| udt.find_field(path))
+                    .and_then(|field| field.name.clone());
+                match name {
+                    Some(name) => self.write(&name),
+                    None => self.write("_"),
+                }
+            }
+            Field::Prim(field) => self.write(match field {
+                PrimField::Start => "Start",
+                PrimField::Step => "Step",
+                PrimField::End => "End",
+            }),
+            Field::Err => self.write("_"),
+        }
+    }
+
+    fn write_lit(&mut self, lit: &Lit) {
+        match lit {
+            Lit::BigInt(value) => {
+                self.write(&value.to_string());
+                self.write("L");
+            }
+            Lit::Bool(value) => self.write(if *value { "true" } else { "false" }),
+            Lit::Double(value) => {
+                let value = if value.fract() == 0.0 {
+                    format!("{value}.")
+                } else {
+                    format!("{value}")
+                };
+                self.write(&value);
+            }
+            Lit::Int(value) => self.write(&value.to_string()),
+            Lit::Pauli(pauli) => self.write(match pauli {
+                Pauli::I => "PauliI",
+                Pauli::X => "PauliX",
+                Pauli::Y => "PauliY",
+                Pauli::Z => "PauliZ",
+            }),
+            Lit::Result(result) => self.write(match result {
+                Result::Zero => "Zero",
+                Result::One => "One",
+            }),
+        }
+    }
+
+    fn write_string(&mut self, components: &[StringComponent]) {
+        let interpolated = components
+            .iter()
+            .any(|c| matches!(c, StringComponent::Expr(_)));
+        if interpolated {
+            self.write("$");
+        }
+        self.write("\"");
+        for component in components {
+            match component {
+                StringComponent::Expr(expr) => {
+                    self.write("{");
+                    self.write_expr(expr);
+                    self.write("}");
+                }
+                StringComponent::Lit(lit) => {
+                    let escaped = escape_string(lit, interpolated);
+                    self.write(&escaped);
+                }
+            }
+        }
+        self.write("\"");
+    }
+
+    fn write_sep<T>(&mut self, items: &[T], sep: &str, mut f: impl FnMut(&mut Self, &T)) {
+        for (ix, item) in items.iter().enumerate() {
+            if ix > 0 {
+                self.write(sep);
+            }
+            f(self, item);
+        }
+    }
+
+    fn udt(&self, ty: &Ty) -> Option<&'a Udt> {
+        let Ty::Udt(_, Res::Item(id)) = ty else {
+            return None;
+        };
+        let package = id.package.unwrap_or(self.package);
+        match &self.store.get(package)?.package.items.get(id.item)?.kind {
+            ItemKind::Ty(_, udt) => Some(udt),
+            _ => None,
+        }
+    }
+
+    /// The fully qualified name of an item, so that generated code does not depend on
+    /// any `open` statements.
+    fn item_name(&self, id: ItemId) -> String {
+        let package = id.package.unwrap_or(self.package);
+        let Some(items) = self.store.get(package).map(|unit| &unit.package.items) else {
+            return "_".to_string();
+        };
+        let Some(item) = items.get(id.item) else {
+            return "_".to_string();
+        };
+        let name = match &item.kind {
+            ItemKind::Callable(decl) => ident_name(&decl.name.name),
+            ItemKind::Ty(name, _) => ident_name(&name.name),
+            ItemKind::Namespace(name, _) => return name.name().to_string(),
+        };
+        match item.parent.and_then(|parent| items.get(parent)) {
+            Some(parent) => match &parent.kind {
+                ItemKind::Namespace(namespace, _) => format!("{}.{name}", namespace.name()),
+                _ => name,
+            },
+            None => name,
+        }
+    }
+}
+
+/// Collects the names of all local bindings so that variable references can be written by name.
+#[derive(Default)]
+struct LocalNames {
+    names: FxHashMap<NodeId, Rc<str>>,
+}
+
+impl Visitor<'_> for LocalNames {
+    fn visit_pat(&mut self, pat: &Pat) {
+        if let PatKind::Bind(name) = &pat.kind {
+            self.names.insert(name.id, name.name.clone());
+        }
+        visit::walk_pat(self, pat);
+    }
+}
+
+/// Compiler generated names start with `@`, which is not valid in Q# source.
+fn ident_name(name: &str) -> String {
+    match name.strip_prefix('@') {
+        Some(rest) => format!("__{rest}"),
+        None => name.to_string(),
+    }
+}
+
+fn escape_string(lit: &str, interpolated: bool) -> String {
+    let mut escaped = String::with_capacity(lit.len());
+    for c in lit.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '{' if interpolated => escaped.push_str("\\{"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn binop_as_str(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Add => "+",
+        BinOp::AndB => "&&&",
+        BinOp::AndL => "and",
+        BinOp::Div => "/",
+        BinOp::Eq => "==",
+        BinOp::Exp => "^",
+        BinOp::Gt => ">",
+        BinOp::Gte => ">=",
+        BinOp::Lt => "<",
+        BinOp::Lte => "<=",
+        BinOp::Mod => "%",
+        BinOp::Mul => "*",
+        BinOp::Neq => "!=",
+        BinOp::OrB => "|||",
+        BinOp::OrL => "or",
+        BinOp::Shl => "<<<",
+        BinOp::Shr => ">>>",
+        BinOp::Sub => "-",
+        BinOp::XorB => "^^^",
+    }
+}
+
+fn unop_as_str(op: UnOp) -> &'static str {
+    match op {
+        UnOp::Functor(Functor::Adj) => "Adjoint ",
+        UnOp::Functor(Functor::Ctl) => "Controlled ",
+        UnOp::Neg => "-",
+        UnOp::NotB => "~~~",
+        UnOp::NotL => "not ",
+        UnOp::Pos => "+",
+        UnOp::Unwrap => "!",
+    }
+}
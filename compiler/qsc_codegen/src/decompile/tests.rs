@@ -0,0 +1,269 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{self, compile, PackageStore, SourceMap};
+use qsc_hir::hir::{ItemKind, LocalItemId, PackageId};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+
+use super::{write_callable_specs, write_item, write_package};
+
+fn compile_program(program: &str) -> (PackageStore, PackageId) {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(store.core(), &mut unit, PackageType::Lib).is_empty());
+    let package = store.insert(unit);
+    (store, package)
+}
+
+fn find_item(store: &PackageStore, package: PackageId, name: &str) -> LocalItemId {
+    store
+        .get(package)
+        .expect("package should exist")
+        .package
+        .items
+        .iter()
+        .find_map(|(id, item)| match &item.kind {
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == name => Some(id),
+            _ => None,
+        })
+        .expect("callable should exist")
+}
+
+/// Writes the named callable and checks that, ignoring whitespace, the output contains each of
+/// the expected fragments. The output is then compiled alongside the original program to ensure
+/// it is valid Q#.
+fn check(program: &str, name: &str, fragments: &[&str]) {
+    let (store, package) = compile_program(program);
+    let item = find_item(&store, package, name);
+    let qsharp = write_callable_specs(&store, package, item).expect("callable should be written");
+
+    let stripped: String = qsharp.chars().filter(|c| !c.is_whitespace()).collect();
+    for fragment in fragments {
+        assert!(
+            stripped.contains(fragment),
+            "expected `{fragment}` in:\n{qsharp}"
+        );
+    }
+
+    assert_compiles(&format!(
+        "{program}\nnamespace Decompiled {{\n{qsharp}\n}}\n"
+    ));
+}
+
+fn assert_compiles(source: &str) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("round_trip".into(), source.into())], None);
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}\n{source}", unit.errors);
+}
+
+#[test]
+fn function_body_is_written_as_block() {
+    check(
+        indoc! {"
+            namespace Test {
+                function F(x : Int) : Int { x + 1 }
+            }
+        "},
+        "F",
+        &["functionF(x:Int):Int{x+1}"],
+    );
+}
+
+#[test]
+fn operator_precedence_is_preserved() {
+    check(
+        indoc! {"
+            namespace Test {
+                function F(a : Int, b : Int) : Int { (a + b) * 2 }
+            }
+        "},
+        "F",
+        &["(a+b)*2"],
+    );
+}
+
+#[test]
+fn adjoint_auto_is_written_explicitly() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation B(q : Qubit) : Unit is Adj + Ctl { body intrinsic; }
+                operation C(q : Qubit) : Unit is Adj + Ctl { body intrinsic; }
+                operation A(q : Qubit) : Unit is Adj + Ctl {
+                    B(q);
+                    C(q);
+                }
+            }
+        "},
+        "A",
+        &[
+            "operationA(q:Qubit):UnitisAdj+Ctl{",
+            "body(...){Test.B(q);Test.C(q);}",
+            "adjoint(...){AdjointTest.C(q);AdjointTest.B(q);}",
+            "ControlledTest.B(",
+            "ControlledAdjointTest.C(",
+        ],
+    );
+}
+
+#[test]
+fn intrinsic_body_is_written_as_generator() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation B(q : Qubit) : Unit { body intrinsic; }
+            }
+        "},
+        "B",
+        &["bodyintrinsic;"],
+    );
+}
+
+#[test]
+fn lambda_is_written_inline() {
+    check(
+        indoc! {"
+            namespace Test {
+                function F() : Int {
+                    let f = x -> x + 1;
+                    f(1)
+                }
+            }
+        "},
+        "F",
+        &["letf=((x)->x+1);", "f(1)"],
+    );
+}
+
+#[test]
+fn non_callable_item_is_not_written() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            newtype Pair = (Int, Int);
+        }
+    "});
+    let item = store
+        .get(package)
+        .expect("package should exist")
+        .package
+        .items
+        .iter()
+        .find_map(|(id, item)| matches!(item.kind, ItemKind::Ty(..)).then_some(id))
+        .expect("type should exist");
+    assert!(write_callable_specs(&store, package, item).is_none());
+}
+
+fn find_namespace(store: &PackageStore, package: PackageId, name: &str) -> LocalItemId {
+    store
+        .get(package)
+        .expect("package should exist")
+        .package
+        .items
+        .iter()
+        .find_map(|(id, item)| match &item.kind {
+            ItemKind::Namespace(ns, _) if ns.name().as_ref() == name => Some(id),
+            _ => None,
+        })
+        .expect("namespace should exist")
+}
+
+fn strip_whitespace(s: &str) -> String {
+    s.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[test]
+fn newtype_is_written_with_named_fields() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            newtype Pair = (First : Int, Second : (Double, Bool));
+        }
+    "});
+    let item = store
+        .get(package)
+        .expect("package should exist")
+        .package
+        .items
+        .iter()
+        .find_map(|(id, item)| matches!(item.kind, ItemKind::Ty(..)).then_some(id))
+        .expect("type should exist");
+    let qsharp = write_item(&store, package, item).expect("type should be written");
+    assert_eq!(
+        strip_whitespace(&qsharp),
+        "newtypePair=(First:Int,Second:(Double,Bool));"
+    );
+}
+
+#[test]
+fn callable_item_keeps_declared_specializations() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            function F(x : Int) : Int { x }
+        }
+    "});
+    let item = find_item(&store, package, "F");
+    let qsharp = write_item(&store, package, item).expect("callable should be written");
+    assert_eq!(strip_whitespace(&qsharp), "functionF(x:Int):Int{x}");
+}
+
+#[test]
+fn namespace_is_written_with_items() {
+    let (store, package) = compile_program(indoc! {"
+        namespace Test {
+            newtype Wrapped = Int;
+            internal function Unwrap(w : Wrapped) : Int { w! }
+        }
+    "});
+    let item = find_namespace(&store, package, "Test");
+    let qsharp = write_item(&store, package, item).expect("namespace should be written");
+    let stripped = strip_whitespace(&qsharp);
+    assert!(stripped.starts_with("namespaceTest{"), "{qsharp}");
+    assert!(stripped.contains("newtypeWrapped=Int;"), "{qsharp}");
+    assert!(
+        stripped.contains("internalfunctionUnwrap(w:Test.Wrapped):Int{w!}"),
+        "{qsharp}"
+    );
+}
+
+#[test]
+fn package_round_trips() {
+    let program = indoc! {"
+        namespace A {
+            newtype Point = (X : Int, Y : Int);
+            function Origin() : Point { Point(0, 0) }
+        }
+        namespace B {
+            operation Flip(q : Qubit) : Unit is Adj { body intrinsic; }
+            operation Main() : Int {
+                use q = Qubit();
+                within { Flip(q); } apply { Flip(q); }
+                let p = A.Origin();
+                p::X + p.Y
+            }
+        }
+    "};
+    let (store, package) = compile_program(program);
+    let qsharp = write_package(&store, package).expect("package should be written");
+    let stripped = strip_whitespace(&qsharp);
+    assert!(stripped.contains("namespaceA{"), "{qsharp}");
+    assert!(stripped.contains("namespaceB{"), "{qsharp}");
+    assert_compiles(&qsharp);
+}
@@ -0,0 +1,84 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Kernels, which are callables compiled to standalone QIR modules whose entry point is a C
+//! function that takes no arguments and returns nothing. The modules are LLVM IR text, which
+//! `qsc --kernel` compiles to object files with clang when it is available, and the application
+//! links the objects with a QIR runtime and calls the kernels through the declarations of
+//! [`header`]. Each kernel records its output with the output recording functions of the
+//! runtime, like any QIR entry point.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Write;
+
+use qsc_rir::rir;
+
+use crate::{qir::rir_to_qir, target::CodegenTarget};
+
+/// The name that QIR generation gives to the entry point.
+const ENTRY_POINT: &str = "@ENTRYPOINT__main(";
+
+/// The C symbol of the kernel for a callable, which joins the parts of its fully qualified name
+/// with `_`, such as `Test_Main` for `Test.Main`. Returns `None` if the name is not a path of
+/// identifiers, so that names that are not valid C identifiers are never emitted. Names whose
+/// parts contain `_` can join to the same symbol, so callers that emit several kernels must
+/// check that their symbols differ.
+#[must_use]
+pub fn symbol(callable: &str) -> Option<String> {
+    let is_ident = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    callable
+        .split('.')
+        .all(is_ident)
+        .then(|| callable.replace('.', "_"))
+}
+
+/// The QIR target for a kernel, which generates QIR like [`crate::target::Qir`] but names the
+/// entry point after the kernel, so that the modules of several kernels can be linked together.
+pub struct Kernel {
+    symbol: String,
+}
+
+impl Kernel {
+    #[must_use]
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+        }
+    }
+}
+
+impl CodegenTarget for Kernel {
+    fn name(&self) -> &str {
+        "kernel"
+    }
+
+    fn extension(&self) -> &str {
+        "ll"
+    }
+
+    fn generate(&self, program: &rir::Program) -> Result<String, String> {
+        Ok(rir_to_qir(program).replacen(ENTRY_POINT, &format!("@{}(", self.symbol), 1))
+    }
+}
+
+/// The C header that declares the entry points of the kernels, given as pairs of the fully
+/// qualified name of each callable and its symbol.
+#[must_use]
+pub fn header<'a>(kernels: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut header = String::from(
+        "// Generated by qsc.\n\n#pragma once\n\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n",
+    );
+    for (callable, symbol) in kernels {
+        write!(header, "\n// Runs `{callable}`.\nvoid {symbol}(void);\n")
+            .expect("writing to a string should succeed");
+    }
+    header.push_str("\n#ifdef __cplusplus\n}\n#endif\n");
+    header
+}
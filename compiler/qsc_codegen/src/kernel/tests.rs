@@ -0,0 +1,72 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{header, symbol, Kernel};
+use crate::{qir::rir_to_qir, target::CodegenTarget};
+use expect_test::expect;
+use qsc_rir::builder;
+
+#[test]
+fn symbol_joins_the_parts_of_the_name() {
+    assert_eq!(symbol("Main").as_deref(), Some("Main"));
+    assert_eq!(
+        symbol("Microsoft.Quantum_1.Run").as_deref(),
+        Some("Microsoft_Quantum_1_Run")
+    );
+}
+
+#[test]
+fn symbol_rejects_names_that_are_not_paths() {
+    for name in [
+        "",
+        "Test.",
+        ".Main",
+        "Test.1Main",
+        "Main()",
+        "Test Main",
+        "Tëst",
+    ] {
+        assert_eq!(symbol(name), None, "{name}");
+    }
+}
+
+#[test]
+fn kernel_entry_point_is_named_after_the_kernel() {
+    let program = builder::bell_program();
+    let qir = Kernel::new("Test_Bell")
+        .generate(&program)
+        .expect("generation should succeed");
+    assert!(qir.contains("define void @Test_Bell() #0 {"), "{qir}");
+    assert!(!qir.contains("ENTRYPOINT__main"), "{qir}");
+    assert_eq!(
+        qir.replace("@Test_Bell(", "@ENTRYPOINT__main("),
+        rir_to_qir(&program)
+    );
+}
+
+#[test]
+fn header_declares_each_kernel() {
+    expect![[r#"
+        // Generated by qsc.
+
+        #pragma once
+
+        #ifdef __cplusplus
+        extern "C" {
+        #endif
+
+        // Runs `Test.Bell`.
+        void Test_Bell(void);
+
+        // Runs `Test.Ghz`.
+        void Test_Ghz(void);
+
+        #ifdef __cplusplus
+        }
+        #endif
+    "#]]
+    .assert_eq(&header([
+        ("Test.Bell", "Test_Bell"),
+        ("Test.Ghz", "Test_Ghz"),
+    ]));
+}
@@ -1,6 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod decompile;
+pub mod kernel;
 pub mod qir;
 pub mod qsharp;
 pub mod remapper;
+pub mod stats;
+pub mod target;
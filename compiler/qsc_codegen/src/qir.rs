@@ -43,9 +43,27 @@ pub fn fir_to_qir(
     compute_properties: Option<PackageStoreComputeProperties>,
     entry: &ProgramEntry,
 ) -> Result<String, qsc_partial_eval::Error> {
+    let program = fir_to_rir(fir_store, capabilities, compute_properties, entry)?;
+    Ok(rir_to_qir(&program))
+}
+
+/// Partially evaluates the entry expression to the RIR program that code generation targets
+/// generate code from, checked and transformed for the given capabilities.
+pub fn fir_to_rir(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    entry: &ProgramEntry,
+) -> Result<rir::Program, qsc_partial_eval::Error> {
     let mut program = get_rir_from_compilation(fir_store, compute_properties, entry, capabilities)?;
     check_and_transform(&mut program);
-    Ok(ToQir::<String>::to_qir(&program, &program))
+    Ok(program)
+}
+
+/// Generates the QIR of a checked and transformed RIR program.
+#[must_use]
+pub fn rir_to_qir(program: &rir::Program) -> String {
+    ToQir::<String>::to_qir(program, program)
 }
 
 fn get_rir_from_compilation(
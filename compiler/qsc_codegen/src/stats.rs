@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Size statistics for compiled packages, to help find what to trim when targeting hardware
+//! with limits on program size.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display, Formatter};
+
+use qsc_hir::{
+    hir::{Block, CallableDecl, Expr, ExprKind, ItemKind, Package, Pat, SpecBody, SpecGen, Stmt},
+    visit::{self, Visitor},
+};
+
+/// Estimated size in bytes of a QIR instruction generated for a call, e.g.
+/// `call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))`.
+const CALL_BYTES: usize = 64;
+/// Estimated size in bytes of a QIR instruction generated for any other expression.
+const EXPR_BYTES: usize = 24;
+/// Estimated size in bytes of the declaration emitted for an intrinsic callable.
+const INTRINSIC_BYTES: usize = 48;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackageStats {
+    pub namespaces: Vec<NamespaceStats>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NamespaceStats {
+    pub name: String,
+    pub callables: usize,
+    pub types: usize,
+    /// The total number of HIR nodes in the namespace's items.
+    pub nodes: usize,
+    /// The total estimated contribution of the namespace's items to generated QIR.
+    pub estimated_qir_bytes: usize,
+    /// Statistics for each item, largest estimated contribution first.
+    pub items: Vec<ItemStats>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemStats {
+    pub name: String,
+    /// The number of statements, expressions, patterns and blocks in the item.
+    pub nodes: usize,
+    /// An estimate of the bytes of QIR generated for each use of the item. This is based on the
+    /// item's source only, so it does not account for loops being unrolled or for callables
+    /// being specialized for each call site.
+    pub estimated_qir_bytes: usize,
+}
+
+/// Collects size statistics for each namespace in the package.
+#[must_use]
+pub fn package_stats(package: &Package) -> PackageStats {
+    let mut namespaces = package
+        .items
+        .values()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Namespace(name, items) => {
+                let mut stats = NamespaceStats {
+                    name: name.name().to_string(),
+                    ..NamespaceStats::default()
+                };
+                for item in items.iter().filter_map(|id| package.items.get(*id)) {
+                    match &item.kind {
+                        ItemKind::Callable(decl) => {
+                            stats.callables += 1;
+                            stats.items.push(callable_stats(decl));
+                        }
+                        ItemKind::Ty(name, _) => {
+                            stats.types += 1;
+                            stats.items.push(ItemStats {
+                                name: name.name.to_string(),
+                                nodes: 1,
+                                estimated_qir_bytes: 0,
+                            });
+                        }
+                        ItemKind::Namespace(..) => {}
+                    }
+                }
+                stats.nodes = stats.items.iter().map(|item| item.nodes).sum();
+                stats.estimated_qir_bytes = stats
+                    .items
+                    .iter()
+                    .map(|item| item.estimated_qir_bytes)
+                    .sum();
+                stats.items.sort_by(|a, b| {
+                    b.estimated_qir_bytes
+                        .cmp(&a.estimated_qir_bytes)
+                        .then_with(|| a.name.cmp(&b.name))
+                });
+                Some(stats)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    namespaces.sort_by(|a, b| {
+        b.estimated_qir_bytes
+            .cmp(&a.estimated_qir_bytes)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    PackageStats { namespaces }
+}
+
+fn callable_stats(decl: &CallableDecl) -> ItemStats {
+    let mut counter = SizeCounter::default();
+    counter.visit_callable_decl(decl);
+
+    let intrinsic = matches!(decl.body.body, SpecBody::Gen(SpecGen::Intrinsic));
+    ItemStats {
+        name: decl.name.name.to_string(),
+        nodes: counter.nodes,
+        estimated_qir_bytes: counter.calls * CALL_BYTES
+            + counter.exprs * EXPR_BYTES
+            + if intrinsic { INTRINSIC_BYTES } else { 0 },
+    }
+}
+
+#[derive(Default)]
+struct SizeCounter {
+    nodes: usize,
+    calls: usize,
+    exprs: usize,
+}
+
+impl Visitor<'_> for SizeCounter {
+    fn visit_block(&mut self, block: &Block) {
+        self.nodes += 1;
+        visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.nodes += 1;
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.nodes += 1;
+        match expr.kind {
+            ExprKind::Call(..) => self.calls += 1,
+            // Blocks and references to variables or callables do not produce
+            // instructions on their own.
+            ExprKind::Block(_) | ExprKind::Var(..) | ExprKind::Lit(_) | ExprKind::Tuple(_) => {}
+            _ => self.exprs += 1,
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &Pat) {
+        self.nodes += 1;
+        visit::walk_pat(self, pat);
+    }
+}
+
+impl Display for PackageStats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40} {:>9} {:>6} {:>8} {:>14}",
+            "namespace / item", "callables", "types", "nodes", "est. QIR bytes"
+        )?;
+        for namespace in &self.namespaces {
+            writeln!(
+                f,
+                "{:<40} {:>9} {:>6} {:>8} {:>14}",
+                namespace.name,
+                namespace.callables,
+                namespace.types,
+                namespace.nodes,
+                namespace.estimated_qir_bytes
+            )?;
+            for item in &namespace.items {
+                writeln!(
+                    f,
+                    "  {:<38} {:>9} {:>6} {:>8} {:>14}",
+                    item.name, "", "", item.nodes, item.estimated_qir_bytes
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
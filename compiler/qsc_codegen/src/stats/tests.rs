@@ -0,0 +1,90 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{self, compile, PackageStore, SourceMap};
+
+use super::{package_stats, PackageStats};
+
+fn stats(program: &str) -> PackageStats {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    package_stats(&unit.package)
+}
+
+#[test]
+fn items_are_counted_per_namespace() {
+    let stats = stats(indoc! {"
+        namespace A {
+            newtype Pair = (Int, Int);
+            function F() : Unit {}
+            function G() : Unit {}
+        }
+        namespace B {
+            function H() : Unit {}
+        }
+    "});
+
+    let a = stats
+        .namespaces
+        .iter()
+        .find(|ns| ns.name == "A")
+        .expect("namespace should exist");
+    assert_eq!((a.callables, a.types, a.items.len()), (2, 1, 3));
+    let b = stats
+        .namespaces
+        .iter()
+        .find(|ns| ns.name == "B")
+        .expect("namespace should exist");
+    assert_eq!((b.callables, b.types, b.items.len()), (1, 0, 1));
+}
+
+#[test]
+fn larger_items_are_listed_first() {
+    let stats = stats(indoc! {"
+        namespace A {
+            operation X(q : Qubit) : Unit { body intrinsic; }
+            operation Small(q : Qubit) : Unit { X(q); }
+            operation Large(q : Qubit) : Unit { X(q); X(q); X(q); }
+        }
+    "});
+
+    let names = stats.namespaces[0]
+        .items
+        .iter()
+        .map(|item| item.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Large", "Small", "X"]);
+    let ns = &stats.namespaces[0];
+    assert_eq!(
+        ns.estimated_qir_bytes,
+        ns.items
+            .iter()
+            .map(|i| i.estimated_qir_bytes)
+            .sum::<usize>()
+    );
+}
+
+#[test]
+fn report_lists_namespaces_and_items() {
+    let report = stats(indoc! {"
+        namespace A {
+            function F() : Int { 1 + 2 }
+        }
+    "})
+    .to_string();
+    let lines = report.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{report}");
+    assert!(lines[0].starts_with("namespace / item"), "{report}");
+    assert!(lines[1].starts_with('A'), "{report}");
+    assert!(lines[2].starts_with("  F"), "{report}");
+}
@@ -0,0 +1,83 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Code generation targets, which turn the RIR program that partial evaluation produces for an
+//! entry expression into an output format, such as QIR. Other formats, such as QIR flavors,
+//! OpenQASM or vendor formats, can be implemented by other crates and added to a [`Registry`],
+//! so that they can be selected by name like the built-in targets.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_rir::rir;
+
+use crate::qir::rir_to_qir;
+
+/// An output format that programs can be generated in.
+pub trait CodegenTarget {
+    /// The name the target is selected by, such as `qir`.
+    fn name(&self) -> &str;
+
+    /// The extension of the files that the generated code is written to, such as `ll`.
+    fn extension(&self) -> &str;
+
+    /// Generates the code for the program, which has been checked and transformed for the
+    /// capabilities it was partially evaluated for.
+    /// # Errors
+    /// Returns a message describing why the program cannot be generated for this target.
+    fn generate(&self, program: &rir::Program) -> Result<String, String>;
+}
+
+/// The QIR target, which generates LLVM IR in text format for the profile that matches the
+/// capabilities of the program.
+pub struct Qir;
+
+impl CodegenTarget for Qir {
+    fn name(&self) -> &str {
+        "qir"
+    }
+
+    fn extension(&self) -> &str {
+        "ll"
+    }
+
+    fn generate(&self, program: &rir::Program) -> Result<String, String> {
+        Ok(rir_to_qir(program))
+    }
+}
+
+/// The code generation targets that can be selected by name. The default registry has the
+/// built-in targets.
+pub struct Registry {
+    targets: Vec<Box<dyn CodegenTarget>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            targets: vec![Box::new(Qir)],
+        }
+    }
+}
+
+impl Registry {
+    /// Adds a target, replacing the target with the same name if there is one.
+    pub fn register(&mut self, target: impl CodegenTarget + 'static) {
+        self.targets.retain(|other| other.name() != target.name());
+        self.targets.push(Box::new(target));
+    }
+
+    /// The target with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn CodegenTarget> {
+        self.targets
+            .iter()
+            .find(|target| target.name() == name)
+            .map(AsRef::as_ref)
+    }
+
+    /// The names of the targets, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.targets.iter().map(|target| target.name())
+    }
+}
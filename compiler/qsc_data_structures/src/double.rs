@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Formatting and parsing of `Double` values. Both are culture-invariant: the decimal separator
+//! is always `.`, there are no digit group separators, and the exponent marker is `e`, whatever
+//! the locale of the host. Values rendered with [`DoubleFormat`] without a precision can be
+//! parsed back to the same value with [`parse`].
+
+#[cfg(test)]
+mod tests;
+
+/// How `Double` values are rendered. The default options render whole numbers with one decimal
+/// place, to tell them apart from integers, and other numbers with as many digits as are needed
+/// to represent them exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DoubleFormat {
+    /// The number of digits after the decimal point, or `None` for as many as are needed to
+    /// represent the value exactly.
+    pub precision: Option<usize>,
+    /// The magnitude of the decimal exponent at which values are rendered in scientific notation,
+    /// as in `1.5e-7`, or `None` to never use scientific notation. With a threshold of 6, values
+    /// of at least one million and values less than one millionth are rendered in scientific
+    /// notation. Zero is never rendered in scientific notation.
+    pub scientific_threshold: Option<u32>,
+}
+
+impl DoubleFormat {
+    /// Renders the value. Values that are not finite are rendered as `NaN`, `inf` and `-inf`.
+    #[must_use]
+    pub fn format(self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let scientific = format!("{value:e}");
+        let is_scientific = self.scientific_threshold.is_some_and(|threshold| {
+            value != 0.0 && exponent(&scientific).unsigned_abs() >= threshold
+        });
+
+        match (is_scientific, self.precision) {
+            (true, Some(precision)) => format!("{value:.precision$e}"),
+            (true, None) => match scientific.split_once('e') {
+                // A whole mantissa gets one decimal place, like whole numbers in fixed notation.
+                Some((mantissa, exponent)) if !mantissa.contains('.') => {
+                    format!("{mantissa}.0e{exponent}")
+                }
+                _ => scientific,
+            },
+            (false, Some(precision)) => format!("{value:.precision$}"),
+            (false, None) if (value.floor() - value.ceil()).abs() < f64::EPSILON => {
+                format!("{value:.1}")
+            }
+            (false, None) => value.to_string(),
+        }
+    }
+}
+
+/// The decimal exponent of a value rendered in Rust's scientific notation.
+fn exponent(scientific: &str) -> i32 {
+    scientific
+        .split_once('e')
+        .and_then(|(_, exponent)| exponent.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Parses a `Double` written as digits with an optional fractional part after a `.` and an
+/// optional exponent after an `e` or `E`, with an optional leading sign. Unlike [`str::parse`],
+/// this does not accept names such as `inf` or `NaN` or a number without digits before the
+/// decimal point, so that it accepts the same numbers as a Q# literal.
+#[must_use]
+pub fn parse(text: &str) -> Option<f64> {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let (number, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+    let is_valid = !whole.is_empty()
+        && is_digits(whole)
+        && is_digits(fraction)
+        && exponent.is_none_or(|exponent| {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            !digits.is_empty() && is_digits(digits)
+        });
+    if is_valid {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{parse, DoubleFormat};
+use expect_test::expect;
+
+const VALUES: [f64; 8] = [0.0, 2.0, -2.5, 1.0 / 3.0, 1e-7, 123_456_789.0, 1e21, -0.0];
+
+fn format_all(format: DoubleFormat) -> String {
+    VALUES
+        .iter()
+        .map(|&value| format.format(value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[test]
+fn default_format() {
+    expect!["0.0, 2.0, -2.5, 0.3333333333333333, 0.0000001, 123456789.0, 1000000000000000000000.0, -0.0"]
+        .assert_eq(&format_all(DoubleFormat::default()));
+}
+
+#[test]
+fn precision() {
+    let format = DoubleFormat {
+        precision: Some(2),
+        ..DoubleFormat::default()
+    };
+    expect!["0.00, 2.00, -2.50, 0.33, 0.00, 123456789.00, 1000000000000000000000.00, -0.00"]
+        .assert_eq(&format_all(format));
+}
+
+#[test]
+fn scientific_threshold() {
+    let format = DoubleFormat {
+        scientific_threshold: Some(6),
+        ..DoubleFormat::default()
+    };
+    expect!["0.0, 2.0, -2.5, 0.3333333333333333, 1.0e-7, 1.23456789e8, 1.0e21, -0.0"]
+        .assert_eq(&format_all(format));
+}
+
+#[test]
+fn scientific_threshold_with_precision() {
+    let format = DoubleFormat {
+        precision: Some(3),
+        scientific_threshold: Some(6),
+    };
+    expect!["0.000, 2.000, -2.500, 0.333, 1.000e-7, 1.235e8, 1.000e21, -0.000"]
+        .assert_eq(&format_all(format));
+}
+
+#[test]
+fn values_that_are_not_finite() {
+    let format = DoubleFormat {
+        precision: Some(2),
+        scientific_threshold: Some(0),
+    };
+    expect!["NaN, inf, -inf"].assert_eq(
+        &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY]
+            .iter()
+            .map(|&value| format.format(value))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+}
+
+#[test]
+fn formatted_values_round_trip() {
+    for threshold in [None, Some(0), Some(6)] {
+        let format = DoubleFormat {
+            precision: None,
+            scientific_threshold: threshold,
+        };
+        for value in VALUES {
+            let text = format.format(value);
+            assert_eq!(
+                parse(&text).map(f64::to_bits),
+                Some(value.to_bits()),
+                "{text}"
+            );
+        }
+    }
+}
+
+#[test]
+fn parse_accepts_literals() {
+    assert_eq!(parse("1"), Some(1.0));
+    assert_eq!(parse("1."), Some(1.0));
+    assert_eq!(parse("1.25"), Some(1.25));
+    assert_eq!(parse("-1.25e2"), Some(-125.0));
+    assert_eq!(parse("+1E-2"), Some(0.01));
+    assert_eq!(parse("2e+3"), Some(2000.0));
+}
+
+#[test]
+fn parse_rejects_other_notations() {
+    for text in [
+        "", "-", ".5", "1,5", "1.000,5", "1 000", "1e", "1e+", "1.2.3", "inf", "NaN", "infinity",
+        " 1", "0x10",
+    ] {
+        assert_eq!(parse(text), None, "{text}");
+    }
+}
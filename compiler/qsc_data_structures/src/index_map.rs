@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt::{self, Debug, Formatter},
     iter::Enumerate,
@@ -160,6 +161,23 @@ impl<K, V: Debug> Debug for IndexMap<K, V> {
     }
 }
 
+/// The map is serialized as its values in the order of their keys, with a gap for each key
+/// that has no value.
+impl<K, V: Serialize> Serialize for IndexMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.values.serialize(serializer)
+    }
+}
+
+impl<'de, K, V: Deserialize<'de>> Deserialize<'de> for IndexMap<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            _keys: PhantomData,
+            values: Vec::deserialize(deserializer)?,
+        })
+    }
+}
+
 impl<K, V> Default for IndexMap<K, V> {
     fn default() -> Self {
         Self {
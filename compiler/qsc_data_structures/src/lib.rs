@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 pub mod display;
+pub mod double;
 pub mod functors;
 pub mod index_map;
 pub mod language_features;
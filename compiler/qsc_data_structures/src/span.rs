@@ -2,13 +2,16 @@
 // Licensed under the MIT License.
 
 use miette::SourceSpan;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display, Formatter},
     ops::{Add, Index, Sub},
 };
 
 /// A region between two offsets in an array. Spans are the half-open interval `[lo, hi)`.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct Span {
     /// The smallest offset contained in the span.
     pub lo: u32,
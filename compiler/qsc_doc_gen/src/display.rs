@@ -117,6 +117,19 @@ impl<'a> CodeDisplay<'a> {
     // but there's no reason they couldn't be
 }
 
+/// Displays the signature of a HIR callable declaration. HIR declarations carry their own types,
+/// so unlike [`CodeDisplay`] this does not need a [`Lookup`].
+#[must_use]
+pub fn hir_callable_signature(decl: &hir::CallableDecl) -> impl Display + '_ {
+    HirCallableDecl { decl }
+}
+
+/// Displays the declaration of a HIR user-defined type. See [`hir_callable_signature`].
+#[must_use]
+pub fn hir_udt_signature(udt: &ty::Udt) -> impl Display + '_ {
+    HirUdt::new(udt)
+}
+
 // Display impls for each syntax/hir element we may encounter
 
 struct IdentTy<'a> {
@@ -5,9 +5,25 @@ use num_bigint::BigUint;
 use num_complex::Complex;
 use quantum_sparse_sim::QuantumSim;
 use rand::RngCore;
+use std::{collections::BTreeMap, f64::consts::TAU};
 
 use crate::val::Value;
 
+mod differential;
+mod folded;
+#[cfg(not(any(target_family = "wasm")))]
+mod mapped;
+mod noise;
+mod stabilizer;
+
+pub use differential::{Differential, Divergence, DivergenceKind};
+pub use folded::Folded;
+pub use noise::{InvalidNoise, Noise, NoiseModel, NoisySim};
+pub use stabilizer::{NonClifford, StabilizerSim};
+
+#[cfg(not(any(target_family = "wasm")))]
+pub use mapped::{MappedSim, Precision};
+
 /// The trait that must be implemented by a quantum backend, whose functions will be invoked when
 /// quantum intrinsics are called.
 pub trait Backend {
@@ -91,17 +107,201 @@ pub trait Backend {
     fn qubit_is_zero(&mut self, _q: usize) -> bool {
         unimplemented!("qubit_is_zero operation");
     }
+    /// Replaces the simulator state with `state` over `qubit_count` newly allocated qubits, using
+    /// the same index convention as `capture_quantum_state`. Any previously allocated qubits are
+    /// discarded. Backends that cannot prepare arbitrary states, such as hardware, return the
+    /// reason instead.
+    fn restore_quantum_state(
+        &mut self,
+        _state: &[(BigUint, Complex<f64>)],
+        _qubit_count: usize,
+    ) -> Result<(), String> {
+        Err("the backend does not support restoring a quantum state".to_string())
+    }
+
+    /// Returns the global phase, in radians, that the backend tracks separately from the relative
+    /// phases of its state. This is the phase applied by uncontrolled `GlobalPhase` intrinsics,
+    /// and it is already included in the amplitudes returned by `capture_quantum_state`.
+    fn global_phase(&mut self) -> f64 {
+        0.0
+    }
+
+    /// Returns an upper bound on the numerical error accumulated in the state, as the norm of
+    /// its difference from the exact state, for backends that store amplitudes with reduced
+    /// precision. Backends that do not estimate their error return `None`.
+    fn error_estimate(&mut self) -> Option<f64> {
+        None
+    }
 
     fn custom_intrinsic(&mut self, _name: &str, _arg: Value) -> Option<Result<Value, String>> {
         None
     }
 
     fn set_seed(&mut self, _seed: Option<u64>) {}
+
+    /// Releases the memory held for the quantum state, leaving the backend with no allocated
+    /// qubits, for hosts that are done with the backend but may not drop it right away.
+    fn close(&mut self) {}
+
+    /// Takes the reason that the last operation could not be applied, if any. The evaluator
+    /// checks this after every intrinsic, so that backends which support only some operations
+    /// can fail the program at the operation they reject.
+    fn take_failure(&mut self) -> Option<String> {
+        None
+    }
 }
 
-/// Default backend used when targeting sparse simulation.
+/// Forwards to the boxed backend, so that a backend chosen at run time, such as one that a host
+/// plugs into the interpreter, can be used wherever a backend is expected.
+impl<B: Backend + ?Sized> Backend for Box<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        (**self).ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        (**self).cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        (**self).cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        (**self).cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        (**self).h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        (**self).m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        (**self).mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        (**self).reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        (**self).rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        (**self).rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        (**self).ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        (**self).ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        (**self).rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        (**self).rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        (**self).sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        (**self).s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        (**self).swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        (**self).tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        (**self).t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        (**self).x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        (**self).y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        (**self).z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        (**self).qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        (**self).qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        (**self).capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        (**self).qubit_is_zero(q)
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        (**self).restore_quantum_state(state, qubit_count)
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        (**self).global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        (**self).error_estimate()
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        (**self).custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        (**self).set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        (**self).close();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        (**self).take_failure()
+    }
+}
+
+/// Default backend used when targeting sparse simulation. The state is kept as a map from each
+/// basis state with a nonzero amplitude to that amplitude, so its memory grows with the number of
+/// nonzero amplitudes rather than with the number of qubits, and states such as wide GHZ states
+/// or the registers of arithmetic circuits on basis states stay small.
 pub struct SparseSim {
     pub sim: QuantumSim,
+    /// The global phase applied by uncontrolled `GlobalPhase` intrinsics, which is kept out of
+    /// the simulator state so that it can be reported on its own.
+    global_phase: f64,
 }
 
 impl Default for SparseSim {
@@ -115,6 +315,7 @@ impl SparseSim {
     pub fn new() -> Self {
         Self {
             sim: QuantumSim::new(None),
+            global_phase: 0.0,
         }
     }
 }
@@ -257,6 +458,10 @@ impl Backend for SparseSim {
             })
             .collect::<Vec<_>>();
         new_state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        if self.global_phase != 0.0 {
+            let phase = Complex::from_polar(1.0, self.global_phase);
+            new_state.iter_mut().for_each(|(_, val)| *val *= phase);
+        }
         (new_state, count)
     }
 
@@ -264,11 +469,80 @@ impl Backend for SparseSim {
         self.sim.qubit_is_zero(q)
     }
 
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.sim = QuantumSim::new(None);
+        // The saved amplitudes already include any global phase.
+        self.global_phase = 0.0;
+        let qubits = (0..qubit_count)
+            .map(|_| self.sim.allocate())
+            .collect::<Vec<_>>();
+        // Bit `qubit_count - 1 - k` of an index holds the value of qubit `k`.
+        let bit = |idx: &BigUint, k: usize| idx.bit((qubit_count - 1 - k) as u64);
+
+        // Prepare the magnitudes one qubit at a time: for every prefix of already prepared
+        // qubits, rotate the next qubit so that its branches carry the right share of the weight.
+        for k in 0..qubit_count {
+            let mut weights: BTreeMap<BigUint, (f64, f64)> = BTreeMap::new();
+            for (idx, amp) in state {
+                let entry = weights.entry(idx >> (qubit_count - k)).or_default();
+                if bit(idx, k) {
+                    entry.1 += amp.norm_sqr();
+                } else {
+                    entry.0 += amp.norm_sqr();
+                }
+            }
+            for (prefix, (zero, one)) in weights {
+                if one <= 0.0 {
+                    continue;
+                }
+                let theta = 2.0 * one.sqrt().atan2(zero.sqrt());
+                let ctls = &qubits[..k];
+                let flips = (0..k)
+                    .filter(|&j| !prefix.bit((k - 1 - j) as u64))
+                    .collect::<Vec<_>>();
+                flips.iter().for_each(|&j| self.sim.x(qubits[j]));
+                if ctls.is_empty() {
+                    self.sim.ry(theta, qubits[k]);
+                } else {
+                    // A controlled Ry built from an uncontrolled half rotation whose sign is
+                    // flipped only when the controls are satisfied.
+                    self.sim.ry(theta / 2.0, qubits[k]);
+                    self.sim.mcx(ctls, qubits[k]);
+                    self.sim.ry(-theta / 2.0, qubits[k]);
+                    self.sim.mcx(ctls, qubits[k]);
+                }
+                flips.iter().for_each(|&j| self.sim.x(qubits[j]));
+            }
+        }
+
+        // Apply the phase of each basis state with the same fresh qubit technique used by
+        // `GlobalPhase`, controlled on the qubits matching that basis state.
+        for (idx, amp) in state {
+            let phase = amp.arg();
+            if amp.norm_sqr() <= 0.0 || phase.abs() <= f64::EPSILON {
+                continue;
+            }
+            let flips = (0..qubit_count)
+                .filter(|&k| !bit(idx, k))
+                .collect::<Vec<_>>();
+            flips.iter().for_each(|&k| self.sim.x(qubits[k]));
+            let q = self.sim.allocate();
+            self.sim.mcrz(&qubits, -2.0 * phase, q);
+            self.sim.release(q);
+            flips.iter().for_each(|&k| self.sim.x(qubits[k]));
+        }
+        Ok(())
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         match name {
             "GlobalPhase" => {
-                // Apply a global phase to the simulation by doing an Rz to a fresh qubit.
-                // The controls list may be empty, in which case the phase is applied unconditionally.
+                // An uncontrolled phase is tracked separately. A controlled phase is applied to
+                // the simulation by doing an Rz to a fresh qubit.
                 let [ctls_val, theta] = &*arg.unwrap_tuple() else {
                     panic!("tuple arity for GlobalPhase intrinsic should be 2");
                 };
@@ -278,17 +552,22 @@ impl Backend for SparseSim {
                     .iter()
                     .map(|q| q.clone().unwrap_qubit().0)
                     .collect::<Vec<_>>();
+                let theta = theta.clone().unwrap_double();
+                if ctls.is_empty() {
+                    self.global_phase = (self.global_phase + theta).rem_euclid(TAU);
+                    return Some(Ok(Value::unit()));
+                }
                 let q = self.sim.allocate();
                 // The new qubit is by-definition in the |0⟩ state, so by reversing the sign of the
                 // angle we can apply the phase to the entire state without increasing its size in memory.
-                self.sim
-                    .mcrz(&ctls, -2.0 * theta.clone().unwrap_double(), q);
+                self.sim.mcrz(&ctls, -2.0 * theta, q);
                 self.sim.release(q);
                 Some(Ok(Value::unit()))
             }
             "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
             "EndEstimateCaching"
             | "AccountForEstimatesInternal"
+            | "AccountForArithmeticInternal"
             | "BeginRepeatEstimatesInternal"
             | "EndRepeatEstimatesInternal" => Some(Ok(Value::unit())),
             _ => None,
@@ -301,6 +580,11 @@ impl Backend for SparseSim {
             None => self.sim.set_rng_seed(rand::thread_rng().next_u64()),
         }
     }
+
+    fn close(&mut self) {
+        self.sim = QuantumSim::new(None);
+        self.global_phase = 0.0;
+    }
 }
 
 /// Simple struct that chains two backends together so that the chained
@@ -470,6 +754,28 @@ where
         self.main.qubit_is_zero(q)
     }
 
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.main.restore_quantum_state(state, qubit_count)?;
+        // The chained backend only needs to know about the qubits, not their state.
+        for _ in 0..qubit_count {
+            let _ = self.chained.qubit_allocate();
+        }
+        Ok(())
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        let _ = self.chained.global_phase();
+        self.main.global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        self.main.error_estimate()
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         let _ = self.chained.custom_intrinsic(name, arg.clone());
         self.main.custom_intrinsic(name, arg)
@@ -479,4 +785,14 @@ where
         self.chained.set_seed(seed);
         self.main.set_seed(seed);
     }
+
+    fn close(&mut self) {
+        self.chained.close();
+        self.main.close();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        let chained = self.chained.take_failure();
+        self.main.take_failure().or(chained)
+    }
 }
@@ -0,0 +1,404 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::{hooks::StmtHooks, state::state_fidelity, val::Value, Env, PackageSpan};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+/// How two backends disagreed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DivergenceKind {
+    /// The states of the backends differ by more than global phase, so measuring them would give
+    /// different distributions of results. The fidelity is 1 for equal states and 0 for
+    /// orthogonal ones, or for states over different numbers of qubits.
+    State { fidelity: f64 },
+    /// The backends started a measurement in the same state and reported different results,
+    /// although the secondary backend's result was impossible in that state.
+    Result { primary: bool, secondary: bool },
+}
+
+/// A difference between the backends of a [`Differential`], found right after an operation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Divergence {
+    /// The operation after which the backends disagreed, such as `h` or `mresetz`.
+    pub operation: &'static str,
+    pub kind: DivergenceKind,
+    /// The innermost statement that was running, if the statements are known. See
+    /// [`Differential::stmt_hooks`].
+    pub stmt: Option<PackageSpan>,
+}
+
+/// The statements that are running, innermost last.
+#[derive(Default)]
+struct StmtStack(Vec<PackageSpan>);
+
+impl StmtHooks for StmtStack {
+    fn before_stmt(&mut self, span: PackageSpan, _env: &Env) {
+        self.0.push(span);
+    }
+
+    fn after_stmt(&mut self, _span: PackageSpan, _env: &Env) {
+        self.0.pop();
+    }
+}
+
+/// A backend that runs every operation on two backends and records where their states or
+/// measurement results diverge, for validating a new simulator against a trusted one.
+///
+/// Results and state dumps come from the primary backend. After each operation the states of
+/// both backends are compared up to global phase, so both must implement
+/// `capture_quantum_state`. Measurements with random results legitimately differ between
+/// backends, so whenever the backends end up in different states, the secondary backend is
+/// reset to a copy of the primary backend's state with `restore_quantum_state` and the run
+/// continues from there. This keeps one divergence from being reported again for every later
+/// operation, and requires the secondary backend to implement `restore_quantum_state`: the run
+/// fails at the first divergence if it does not.
+pub struct Differential<T1, T2> {
+    pub primary: T1,
+    pub secondary: T2,
+    /// States with a fidelity at least this far below 1 are reported as divergent, as are
+    /// measurement results whose probability is below it.
+    pub tolerance: f64,
+    /// The secondary backend's id for each qubit allocated by the primary backend.
+    qubits: BTreeMap<usize, usize>,
+    stmts: Rc<RefCell<StmtStack>>,
+    divergences: Vec<Divergence>,
+    /// The reason that the secondary backend could not be resynchronized, if it could not.
+    failure: Option<String>,
+}
+
+impl<T1, T2> Differential<T1, T2>
+where
+    T1: Backend<ResultType = bool>,
+    T2: Backend<ResultType = bool>,
+{
+    pub fn new(primary: T1, secondary: T2) -> Self {
+        Self {
+            primary,
+            secondary,
+            tolerance: 1e-9,
+            qubits: BTreeMap::new(),
+            stmts: Rc::default(),
+            divergences: Vec::new(),
+            failure: None,
+        }
+    }
+
+    /// Hooks that track the running statement, so that divergences can be attributed to it.
+    /// Install them in the environment that evaluates the program.
+    #[must_use]
+    pub fn stmt_hooks(&self) -> Rc<RefCell<dyn StmtHooks>> {
+        self.stmts.clone()
+    }
+
+    /// The divergences found so far, in the order they happened.
+    #[must_use]
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+
+    fn secondary_id(&self, q: usize) -> usize {
+        *self
+            .qubits
+            .get(&q)
+            .expect("qubit should be allocated on both backends")
+    }
+
+    fn record(&mut self, operation: &'static str, kind: DivergenceKind) {
+        let stmt = self.stmts.borrow().0.last().copied();
+        self.divergences.push(Divergence {
+            operation,
+            kind,
+            stmt,
+        });
+    }
+
+    /// Compares the states of the backends after an operation, resynchronizing them if they
+    /// differ.
+    fn compare(&mut self, operation: &'static str) {
+        let (state, qubit_count) = self.primary.capture_quantum_state();
+        let (other_state, other_qubit_count) = self.secondary.capture_quantum_state();
+        let fidelity = if qubit_count == other_qubit_count {
+            state_fidelity(&state, &other_state)
+        } else {
+            0.0
+        };
+        if fidelity < 1.0 - self.tolerance {
+            self.record(operation, DivergenceKind::State { fidelity });
+            self.resync(&state, qubit_count);
+        }
+    }
+
+    /// Replaces the secondary backend's state with the primary backend's, which is over the
+    /// qubits allocated on the primary backend, in allocation order.
+    fn resync(&mut self, state: &[(BigUint, Complex<f64>)], qubit_count: usize) {
+        if let Err(reason) = self.secondary.restore_quantum_state(state, qubit_count) {
+            self.failure = Some(format!(
+                "the secondary backend could not be resynchronized: {reason}"
+            ));
+        }
+        for (index, id) in self.qubits.values_mut().enumerate() {
+            *id = index;
+        }
+    }
+
+    /// The probability of measuring the result on the qubit, in a state captured from the
+    /// primary backend.
+    fn probability(&self, state: &[(BigUint, Complex<f64>)], q: usize, result: bool) -> f64 {
+        let position = self.qubits.range(..q).count();
+        // Bit `qubit_count - 1 - k` of an index holds the value of the `k`th qubit.
+        let bit = (self.qubits.len() - 1 - position) as u64;
+        let total = state.iter().map(|(_, amp)| amp.norm_sqr()).sum::<f64>();
+        let matching = state
+            .iter()
+            .filter(|(index, _)| index.bit(bit) == result)
+            .map(|(_, amp)| amp.norm_sqr())
+            .sum::<f64>();
+        matching / total
+    }
+
+    fn measure(&mut self, operation: &'static str, q: usize) -> bool {
+        let (state, _) = self.primary.capture_quantum_state();
+        let other_q = self.secondary_id(q);
+        let (result, other_result) = if operation == "m" {
+            (self.primary.m(q), self.secondary.m(other_q))
+        } else {
+            (self.primary.mresetz(q), self.secondary.mresetz(other_q))
+        };
+        if result == other_result {
+            self.compare(operation);
+        } else {
+            if self.probability(&state, q, other_result) < self.tolerance {
+                self.record(
+                    operation,
+                    DivergenceKind::Result {
+                        primary: result,
+                        secondary: other_result,
+                    },
+                );
+            }
+            // The backends collapsed to different states, so continue from the primary's.
+            let (state, qubit_count) = self.primary.capture_quantum_state();
+            self.resync(&state, qubit_count);
+        }
+        result
+    }
+}
+
+impl<T1, T2> Backend for Differential<T1, T2>
+where
+    T1: Backend<ResultType = bool>,
+    T2: Backend<ResultType = bool>,
+{
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.primary.ccx(ctl0, ctl1, q);
+        self.secondary.ccx(
+            self.secondary_id(ctl0),
+            self.secondary_id(ctl1),
+            self.secondary_id(q),
+        );
+        self.compare("ccx");
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.primary.cx(ctl, q);
+        self.secondary
+            .cx(self.secondary_id(ctl), self.secondary_id(q));
+        self.compare("cx");
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.primary.cy(ctl, q);
+        self.secondary
+            .cy(self.secondary_id(ctl), self.secondary_id(q));
+        self.compare("cy");
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.primary.cz(ctl, q);
+        self.secondary
+            .cz(self.secondary_id(ctl), self.secondary_id(q));
+        self.compare("cz");
+    }
+
+    fn h(&mut self, q: usize) {
+        self.primary.h(q);
+        self.secondary.h(self.secondary_id(q));
+        self.compare("h");
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measure("m", q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.measure("mresetz", q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.primary.reset(q);
+        self.secondary.reset(self.secondary_id(q));
+        self.compare("reset");
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.primary.rx(theta, q);
+        self.secondary.rx(theta, self.secondary_id(q));
+        self.compare("rx");
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.primary.rxx(theta, q0, q1);
+        self.secondary
+            .rxx(theta, self.secondary_id(q0), self.secondary_id(q1));
+        self.compare("rxx");
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.primary.ry(theta, q);
+        self.secondary.ry(theta, self.secondary_id(q));
+        self.compare("ry");
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.primary.ryy(theta, q0, q1);
+        self.secondary
+            .ryy(theta, self.secondary_id(q0), self.secondary_id(q1));
+        self.compare("ryy");
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.primary.rz(theta, q);
+        self.secondary.rz(theta, self.secondary_id(q));
+        self.compare("rz");
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.primary.rzz(theta, q0, q1);
+        self.secondary
+            .rzz(theta, self.secondary_id(q0), self.secondary_id(q1));
+        self.compare("rzz");
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.primary.sadj(q);
+        self.secondary.sadj(self.secondary_id(q));
+        self.compare("sadj");
+    }
+
+    fn s(&mut self, q: usize) {
+        self.primary.s(q);
+        self.secondary.s(self.secondary_id(q));
+        self.compare("s");
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.primary.swap(q0, q1);
+        self.secondary
+            .swap(self.secondary_id(q0), self.secondary_id(q1));
+        self.compare("swap");
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.primary.tadj(q);
+        self.secondary.tadj(self.secondary_id(q));
+        self.compare("tadj");
+    }
+
+    fn t(&mut self, q: usize) {
+        self.primary.t(q);
+        self.secondary.t(self.secondary_id(q));
+        self.compare("t");
+    }
+
+    fn x(&mut self, q: usize) {
+        self.primary.x(q);
+        self.secondary.x(self.secondary_id(q));
+        self.compare("x");
+    }
+
+    fn y(&mut self, q: usize) {
+        self.primary.y(q);
+        self.secondary.y(self.secondary_id(q));
+        self.compare("y");
+    }
+
+    fn z(&mut self, q: usize) {
+        self.primary.z(q);
+        self.secondary.z(self.secondary_id(q));
+        self.compare("z");
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let q = self.primary.qubit_allocate();
+        let other = self.secondary.qubit_allocate();
+        self.qubits.insert(q, other);
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.primary.qubit_release(q);
+        self.secondary.qubit_release(self.secondary_id(q));
+        self.qubits.remove(&q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.primary.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.primary.qubit_is_zero(q)
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.primary.restore_quantum_state(state, qubit_count)?;
+        self.secondary.restore_quantum_state(state, qubit_count)?;
+        self.qubits = (0..qubit_count).map(|q| (q, q)).collect();
+        Ok(())
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        self.primary.global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        self.primary.error_estimate()
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        // Custom intrinsics take qubits as values, which cannot be mapped to the secondary
+        // backend's qubits, so the secondary backend is resynchronized afterwards instead.
+        let result = self.primary.custom_intrinsic(name, arg);
+        let (state, qubit_count) = self.primary.capture_quantum_state();
+        self.resync(&state, qubit_count);
+        result
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.primary.set_seed(seed);
+        self.secondary.set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        self.primary.close();
+        self.secondary.close();
+        self.qubits.clear();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        let secondary = self.secondary.take_failure();
+        let primary = self.primary.take_failure();
+        self.failure.take().or(primary).or(secondary)
+    }
+}
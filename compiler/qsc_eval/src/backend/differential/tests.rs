@@ -0,0 +1,129 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Differential, DivergenceKind};
+use crate::backend::{Backend, SparseSim};
+use num_bigint::BigUint;
+use num_complex::Complex;
+
+/// A sparse simulator whose `T` gate is wrongly implemented as `S`.
+struct FaultyT(SparseSim);
+
+impl Backend for FaultyT {
+    type ResultType = bool;
+
+    fn h(&mut self, q: usize) {
+        self.0.h(q);
+    }
+    fn t(&mut self, q: usize) {
+        self.0.s(q);
+    }
+    fn x(&mut self, q: usize) {
+        self.0.x(q);
+    }
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.0.cx(ctl, q);
+    }
+    fn mresetz(&mut self, q: usize) -> bool {
+        self.0.mresetz(q)
+    }
+    fn qubit_allocate(&mut self) -> usize {
+        self.0.qubit_allocate()
+    }
+    fn qubit_release(&mut self, q: usize) {
+        self.0.qubit_release(q);
+    }
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.0.capture_quantum_state()
+    }
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.0.restore_quantum_state(state, qubit_count)
+    }
+}
+
+/// A sparse simulator whose measurements always report `One`.
+struct AlwaysOne(SparseSim);
+
+impl Backend for AlwaysOne {
+    type ResultType = bool;
+
+    fn x(&mut self, q: usize) {
+        self.0.x(q);
+    }
+    fn mresetz(&mut self, q: usize) -> bool {
+        self.0.mresetz(q);
+        true
+    }
+    fn qubit_allocate(&mut self) -> usize {
+        self.0.qubit_allocate()
+    }
+    fn qubit_release(&mut self, q: usize) {
+        self.0.qubit_release(q);
+    }
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.0.capture_quantum_state()
+    }
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.0.restore_quantum_state(state, qubit_count)
+    }
+}
+
+#[test]
+fn equal_backends_do_not_diverge_on_random_measurements() {
+    let mut sim = Differential::new(SparseSim::new(), SparseSim::new());
+    for _ in 0..20 {
+        let (a, b) = (sim.qubit_allocate(), sim.qubit_allocate());
+        sim.h(a);
+        sim.cx(a, b);
+        let first = sim.mresetz(a);
+        let second = sim.mresetz(b);
+        assert_eq!(first, second);
+        sim.qubit_release(a);
+        sim.qubit_release(b);
+    }
+    assert_eq!(sim.divergences(), []);
+}
+
+#[test]
+fn wrong_gate_is_reported_once_at_the_gate() {
+    let mut sim = Differential::new(SparseSim::new(), FaultyT(SparseSim::new()));
+    let (a, b) = (sim.qubit_allocate(), sim.qubit_allocate());
+    sim.h(a);
+    sim.cx(a, b);
+    sim.t(b);
+    sim.h(a);
+    sim.h(b);
+    let divergences = sim.divergences();
+    assert_eq!(divergences.len(), 1, "{divergences:?}");
+    assert_eq!(divergences[0].operation, "t");
+    assert!(matches!(
+        divergences[0].kind,
+        DivergenceKind::State { fidelity } if fidelity < 0.9
+    ));
+    assert!(divergences[0].stmt.is_none());
+}
+
+#[test]
+fn impossible_measurement_result_is_reported() {
+    let mut sim = Differential::new(SparseSim::new(), AlwaysOne(SparseSim::new()));
+    let q = sim.qubit_allocate();
+    assert!(!sim.mresetz(q));
+    sim.x(q);
+    assert!(sim.mresetz(q));
+    assert_eq!(sim.divergences().len(), 1);
+    assert_eq!(
+        sim.divergences()[0].kind,
+        DivergenceKind::Result {
+            primary: false,
+            secondary: true
+        }
+    );
+}
@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::Complex;
+
+/// A backend that folds every gate of the program as it is applied, replacing a gate `G` with
+/// `G (G† G)ᵏ`. The folded program has the same ideal behavior, but each gate is applied `2k + 1`
+/// times, so on a noisy backend like [`super::NoisySim`] its gate noise is scaled by that factor,
+/// as zero-noise extrapolation needs. Measurements and resets are not folded.
+pub struct Folded<B> {
+    pub sim: B,
+    folds: usize,
+}
+
+impl<B: Backend> Folded<B> {
+    /// Wraps the backend so that every gate is followed by `folds` pairs of its inverse and
+    /// itself, which scales the noise of the gates by `2 * folds + 1`.
+    #[must_use]
+    pub fn new(sim: B, folds: usize) -> Self {
+        Self { sim, folds }
+    }
+
+    fn fold(&mut self, gate: impl Fn(&mut B), inverse: impl Fn(&mut B)) {
+        gate(&mut self.sim);
+        for _ in 0..self.folds {
+            inverse(&mut self.sim);
+            gate(&mut self.sim);
+        }
+    }
+}
+
+impl<B: Backend> Backend for Folded<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        let gate = |sim: &mut B| sim.ccx(ctl0, ctl1, q);
+        self.fold(gate, gate);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        let gate = |sim: &mut B| sim.cx(ctl, q);
+        self.fold(gate, gate);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        let gate = |sim: &mut B| sim.cy(ctl, q);
+        self.fold(gate, gate);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        let gate = |sim: &mut B| sim.cz(ctl, q);
+        self.fold(gate, gate);
+    }
+
+    fn h(&mut self, q: usize) {
+        let gate = |sim: &mut B| sim.h(q);
+        self.fold(gate, gate);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.sim.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.sim.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.sim.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.fold(|sim| sim.rx(theta, q), |sim| sim.rx(-theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.fold(|sim| sim.rxx(theta, q0, q1), |sim| sim.rxx(-theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.fold(|sim| sim.ry(theta, q), |sim| sim.ry(-theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.fold(|sim| sim.ryy(theta, q0, q1), |sim| sim.ryy(-theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.fold(|sim| sim.rz(theta, q), |sim| sim.rz(-theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.fold(|sim| sim.rzz(theta, q0, q1), |sim| sim.rzz(-theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.fold(|sim| sim.sadj(q), |sim| sim.s(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.fold(|sim| sim.s(q), |sim| sim.sadj(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        let gate = |sim: &mut B| sim.swap(q0, q1);
+        self.fold(gate, gate);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.fold(|sim| sim.tadj(q), |sim| sim.t(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.fold(|sim| sim.t(q), |sim| sim.tadj(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        let gate = |sim: &mut B| sim.x(q);
+        self.fold(gate, gate);
+    }
+
+    fn y(&mut self, q: usize) {
+        let gate = |sim: &mut B| sim.y(q);
+        self.fold(gate, gate);
+    }
+
+    fn z(&mut self, q: usize) {
+        let gate = |sim: &mut B| sim.z(q);
+        self.fold(gate, gate);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.sim.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.sim.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.sim.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.sim.qubit_is_zero(q)
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.sim.restore_quantum_state(state, qubit_count)
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        self.sim.global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        self.sim.error_estimate()
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.sim.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.sim.set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        self.sim.close();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        self.sim.take_failure()
+    }
+}
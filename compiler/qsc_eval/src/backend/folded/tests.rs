@@ -0,0 +1,116 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Folded;
+use crate::backend::{Backend, SparseSim};
+use expect_test::expect;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A backend that records the operations applied to it.
+#[derive(Default)]
+struct Recorder {
+    ops: String,
+}
+
+impl Backend for Recorder {
+    type ResultType = bool;
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        let _ = writeln!(self.ops, "cx {ctl} {q}");
+    }
+
+    fn mresetz(&mut self, q: usize) -> bool {
+        let _ = writeln!(self.ops, "mresetz {q}");
+        false
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        let _ = writeln!(self.ops, "rx {theta} {q}");
+    }
+
+    fn sadj(&mut self, q: usize) {
+        let _ = writeln!(self.ops, "sadj {q}");
+    }
+
+    fn s(&mut self, q: usize) {
+        let _ = writeln!(self.ops, "s {q}");
+    }
+}
+
+#[test]
+fn gates_are_followed_by_pairs_of_inverse_and_gate() {
+    let mut sim = Folded::new(Recorder::default(), 1);
+    sim.s(0);
+    sim.rx(0.5, 1);
+    sim.cx(0, 1);
+    sim.mresetz(0);
+    expect![[r#"
+        s 0
+        sadj 0
+        s 0
+        rx 0.5 1
+        rx -0.5 1
+        rx 0.5 1
+        cx 0 1
+        cx 0 1
+        cx 0 1
+        mresetz 0
+    "#]]
+    .assert_eq(&sim.sim.ops);
+}
+
+#[test]
+fn no_folds_applies_gates_once() {
+    let mut sim = Folded::new(Recorder::default(), 0);
+    sim.s(0);
+    sim.mresetz(0);
+    expect![[r#"
+        s 0
+        mresetz 0
+    "#]]
+    .assert_eq(&sim.sim.ops);
+}
+
+#[test]
+fn folded_program_has_same_state() {
+    fn prepare(sim: &mut impl Backend) {
+        let q0 = sim.qubit_allocate();
+        let q1 = sim.qubit_allocate();
+        sim.h(q0);
+        sim.t(q0);
+        sim.ry(0.3, q1);
+        sim.cx(q0, q1);
+        sim.rzz(0.7, q0, q1);
+        sim.s(q1);
+    }
+
+    let mut ideal = SparseSim::new();
+    prepare(&mut ideal);
+    let mut folded = Folded::new(SparseSim::new(), 2);
+    prepare(&mut folded);
+
+    let (ideal, ideal_qubits) = ideal.capture_quantum_state();
+    let (folded, folded_qubits) = folded.capture_quantum_state();
+    assert_eq!(ideal_qubits, folded_qubits);
+    let amplitudes = |state: Vec<(BigUint, Complex<f64>)>| {
+        state
+            .into_iter()
+            .filter(|(_, amplitude)| amplitude.norm() > 1e-9)
+            .collect::<BTreeMap<_, _>>()
+    };
+    let ideal = amplitudes(ideal);
+    let folded = amplitudes(folded);
+    assert_eq!(
+        ideal.keys().collect::<Vec<_>>(),
+        folded.keys().collect::<Vec<_>>()
+    );
+    for (index, amplitude) in &ideal {
+        assert!(
+            (amplitude - folded[index]).norm() < 1e-9,
+            "{amplitude} != {}",
+            folded[index]
+        );
+    }
+}
@@ -0,0 +1,678 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::val::Value;
+use memmap2::MmapMut;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use std::{
+    f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4, TAU},
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Amplitudes with a smaller squared magnitude are treated as zero when reporting the state.
+const ZERO_THRESHOLD: f64 = 1e-24;
+
+/// The number of amplitudes that a gate reads from the mapping, updates and writes back at a
+/// time, which keeps the working set of a gate to a few pages however large the state is.
+const CHUNK: usize = 1 << 12;
+
+type Matrix = [[Complex<f64>; 2]; 2];
+
+/// The precision used to store amplitudes. Gates are always computed in double precision, so
+/// single precision only loses accuracy when results are written back, in exchange for half the
+/// memory and I/O.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    Single,
+    #[default]
+    Double,
+}
+
+impl Precision {
+    /// Size in bytes of one stored amplitude: the real and imaginary parts as little-endian
+    /// floats.
+    fn amplitude_bytes(self) -> usize {
+        match self {
+            Precision::Single => 8,
+            Precision::Double => 16,
+        }
+    }
+
+    /// A bound on the relative error introduced each time the state is written back.
+    fn epsilon(self) -> f64 {
+        match self {
+            Precision::Single => f64::from(f32::EPSILON),
+            Precision::Double => f64::EPSILON,
+        }
+    }
+}
+
+/// Where the amplitudes live: a file owned by the simulator, or anonymous memory.
+enum Storage {
+    File { path: PathBuf, file: File },
+    Memory,
+}
+
+/// A dense state vector backend that keeps its amplitudes in a memory-mapped file instead of
+/// in process memory. This is slower than [`super::SparseSim`], but lets the operating system
+/// page the state in and out, so programs whose fully entangled state is slightly larger than
+/// the available RAM can still be simulated given enough disk space.
+///
+/// Gates are applied in fixed-size chunks of amplitudes, taken in index order, so that pages are
+/// faulted in sequentially regardless of which qubit is targeted. A gate on a qubit whose
+/// amplitude pairs lie further apart than a chunk updates two chunks at a time, one from each
+/// half of the pairs.
+///
+/// The file grows by doubling when a qubit is allocated and shrinks by half when one is
+/// released. It is removed when the simulator is dropped.
+///
+/// The simulator can also keep its state in anonymous memory, which together with
+/// [`Precision::Single`] makes it a dense simulator using half the memory of a double precision
+/// one.
+pub struct MappedSim {
+    storage: Storage,
+    map: MmapMut,
+    precision: Precision,
+    /// The position of each qubit id in the state index, or `None` for released ids.
+    positions: Vec<Option<usize>>,
+    rng: StdRng,
+    /// An upper bound on the norm of the difference between the stored state and the exact
+    /// state, accumulated from the rounding of each write back.
+    rounding_error: f64,
+    /// The global phase applied by uncontrolled `GlobalPhase` intrinsics, kept out of the
+    /// stored state.
+    global_phase: f64,
+    /// The reason that the last qubit could not be allocated or released, if it could not.
+    failure: Option<String>,
+}
+
+impl MappedSim {
+    /// Creates a simulator storing its state in double precision in a new file at `path`. An
+    /// existing file at that path is overwritten.
+    /// # Errors
+    /// Returns an error if the file cannot be created or mapped.
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+        Self::with_precision(path, Precision::Double)
+    }
+
+    /// Creates a simulator storing its state with the given precision in a new file at `path`.
+    /// An existing file at that path is overwritten.
+    /// # Errors
+    /// Returns an error if the file cannot be created or mapped.
+    pub fn with_precision(path: impl Into<PathBuf>, precision: Precision) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len(precision.amplitude_bytes() as u64)?;
+        let map = map(&file)?;
+        Ok(Self::with_storage(
+            Storage::File { path, file },
+            map,
+            precision,
+        ))
+    }
+
+    /// Creates a simulator storing its state with the given precision in anonymous memory.
+    /// # Errors
+    /// Returns an error if the memory cannot be mapped.
+    pub fn in_memory(precision: Precision) -> io::Result<Self> {
+        let map = MmapMut::map_anon(precision.amplitude_bytes())?;
+        Ok(Self::with_storage(Storage::Memory, map, precision))
+    }
+
+    fn with_storage(storage: Storage, map: MmapMut, precision: Precision) -> Self {
+        let mut sim = Self {
+            storage,
+            map,
+            precision,
+            positions: Vec::new(),
+            rng: StdRng::from_entropy(),
+            rounding_error: 0.0,
+            global_phase: 0.0,
+            failure: None,
+        };
+        sim.set(0, Complex::new(1.0, 0.0));
+        sim
+    }
+
+    /// The path of the file holding the state, if it is stored in a file.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match &self.storage {
+            Storage::File { path, .. } => Some(path),
+            Storage::Memory => None,
+        }
+    }
+
+    #[must_use]
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Estimates the accumulated error in the current state, as an upper bound on the norm of
+    /// its difference from the state an exact simulation would have produced. Each pass that
+    /// writes the state back adds at most the precision's machine epsilon, since the gates
+    /// themselves preserve the norm of any earlier error.
+    #[must_use]
+    pub fn estimated_error(&self) -> f64 {
+        self.rounding_error
+    }
+
+    /// Records that the whole state has been written back once.
+    fn add_rounding_error(&mut self) {
+        self.rounding_error += self.precision.epsilon();
+    }
+
+    fn qubit_count(&self) -> usize {
+        self.positions.iter().flatten().count()
+    }
+
+    fn len(&self) -> usize {
+        1 << self.qubit_count()
+    }
+
+    fn get(&self, index: usize) -> Complex<f64> {
+        let size = self.precision.amplitude_bytes();
+        let (re, im) = self.map[index * size..(index + 1) * size].split_at(size / 2);
+        match self.precision {
+            Precision::Single => Complex::new(
+                f64::from(f32::from_le_bytes(
+                    re.try_into().expect("slice should be 4 bytes"),
+                )),
+                f64::from(f32::from_le_bytes(
+                    im.try_into().expect("slice should be 4 bytes"),
+                )),
+            ),
+            Precision::Double => Complex::new(
+                f64::from_le_bytes(re.try_into().expect("slice should be 8 bytes")),
+                f64::from_le_bytes(im.try_into().expect("slice should be 8 bytes")),
+            ),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn set(&mut self, index: usize, value: Complex<f64>) {
+        let size = self.precision.amplitude_bytes();
+        let (re, im) = self.map[index * size..(index + 1) * size].split_at_mut(size / 2);
+        match self.precision {
+            Precision::Single => {
+                re.copy_from_slice(&(value.re as f32).to_le_bytes());
+                im.copy_from_slice(&(value.im as f32).to_le_bytes());
+            }
+            Precision::Double => {
+                re.copy_from_slice(&value.re.to_le_bytes());
+                im.copy_from_slice(&value.im.to_le_bytes());
+            }
+        }
+    }
+
+    /// Resizes the storage to hold `len` amplitudes. New amplitudes are zero. If the storage
+    /// cannot be resized, such as when the disk or the address space is full, it keeps its
+    /// previous size.
+    fn resize(&mut self, len: usize) -> io::Result<()> {
+        let bytes = len
+            .checked_mul(self.precision.amplitude_bytes())
+            .ok_or_else(too_large)?;
+        match &self.storage {
+            Storage::File { file, .. } => {
+                self.map.flush()?;
+                // Unmap the file before resizing it, since not all platforms allow resizing a
+                // file that is mapped.
+                self.map = MmapMut::map_anon(1)?;
+                let resized = file.set_len(bytes as u64);
+                self.map = map(file)?;
+                resized
+            }
+            Storage::Memory => {
+                let mut map = MmapMut::map_anon(bytes)?;
+                let kept = bytes.min(self.map.len());
+                map[..kept].copy_from_slice(&self.map[..kept]);
+                self.map = map;
+                Ok(())
+            }
+        }
+    }
+
+    fn position(&self, q: usize) -> usize {
+        self.positions
+            .get(q)
+            .copied()
+            .flatten()
+            .unwrap_or_else(|| panic!("qubit {q} should be allocated"))
+    }
+
+    fn mask(&self, ctls: &[usize]) -> usize {
+        ctls.iter()
+            .fold(0, |mask, &q| mask | (1 << self.position(q)))
+    }
+
+    /// Reads the amplitudes starting at `start` into `chunk`.
+    fn read_chunk(&self, start: usize, chunk: &mut [Complex<f64>]) {
+        for (offset, amp) in chunk.iter_mut().enumerate() {
+            *amp = self.get(start + offset);
+        }
+    }
+
+    /// Writes `chunk` back to the amplitudes starting at `start`.
+    fn write_chunk(&mut self, start: usize, chunk: &[Complex<f64>]) {
+        for (offset, &amp) in chunk.iter().enumerate() {
+            self.set(start + offset, amp);
+        }
+    }
+
+    /// Applies a single-qubit unitary to `target`, conditioned on all `ctls` being one.
+    fn apply(&mut self, ctls: &[usize], target: usize, matrix: &Matrix) {
+        let ctl_mask = self.mask(ctls);
+        let bit = 1 << self.position(target);
+        let len = self.len();
+        let size = CHUNK.min(len);
+        let update = |a0: &mut Complex<f64>, a1: &mut Complex<f64>| {
+            (*a0, *a1) = (
+                matrix[0][0] * *a0 + matrix[0][1] * *a1,
+                matrix[1][0] * *a0 + matrix[1][1] * *a1,
+            );
+        };
+
+        let mut zeros = vec![Complex::default(); size];
+        if bit < size {
+            // Both amplitudes of every pair are in the same chunk.
+            for start in (0..len).step_by(size) {
+                self.read_chunk(start, &mut zeros);
+                for zero in 0..size {
+                    if zero & bit == 0 && (start + zero) & ctl_mask == ctl_mask {
+                        let (low, high) = zeros.split_at_mut(zero + 1);
+                        update(&mut low[zero], &mut high[bit - 1]);
+                    }
+                }
+                self.write_chunk(start, &zeros);
+            }
+        } else {
+            // The pairs of a chunk in the lower half are at the same offsets in its partner
+            // chunk in the upper half.
+            let mut ones = vec![Complex::default(); size];
+            for start in (0..len).step_by(size).filter(|start| start & bit == 0) {
+                self.read_chunk(start, &mut zeros);
+                self.read_chunk(start | bit, &mut ones);
+                for (offset, (a0, a1)) in zeros.iter_mut().zip(ones.iter_mut()).enumerate() {
+                    if (start + offset) & ctl_mask == ctl_mask {
+                        update(a0, a1);
+                    }
+                }
+                self.write_chunk(start, &zeros);
+                self.write_chunk(start | bit, &ones);
+            }
+        }
+        self.add_rounding_error();
+    }
+
+    fn probability_of_one(&self, q: usize) -> f64 {
+        let bit = 1 << self.position(q);
+        (0..self.len())
+            .filter(|index| index & bit != 0)
+            .map(|index| self.get(index).norm_sqr())
+            .sum()
+    }
+
+    /// Measures the qubit in the computational basis, collapsing the state.
+    fn measure(&mut self, q: usize) -> bool {
+        let one_probability = self.probability_of_one(q);
+        let result = self.rng.gen::<f64>() < one_probability;
+        let bit = 1 << self.position(q);
+        let kept_probability = if result {
+            one_probability
+        } else {
+            1.0 - one_probability
+        };
+        let scale = 1.0 / kept_probability.sqrt();
+        for index in 0..self.len() {
+            let value = if (index & bit != 0) == result {
+                self.get(index) * scale
+            } else {
+                Complex::default()
+            };
+            self.set(index, value);
+        }
+        self.add_rounding_error();
+        result
+    }
+
+    /// Exchanges the positions of two bits in every state index.
+    fn swap_positions(&mut self, p0: usize, p1: usize) {
+        if p0 == p1 {
+            return;
+        }
+        let (bit0, bit1) = (1 << p0, 1 << p1);
+        for index in 0..self.len() {
+            // Visit each pair once, from the index with the first bit set and the second clear.
+            if index & bit0 != 0 && index & bit1 == 0 {
+                let other = index ^ bit0 ^ bit1;
+                let (a, b) = (self.get(index), self.get(other));
+                self.set(index, b);
+                self.set(other, a);
+            }
+        }
+    }
+
+    fn rzz_impl(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.apply(&[q0], q1, &x_matrix());
+        self.apply(&[], q1, &rz_matrix(theta));
+        self.apply(&[q0], q1, &x_matrix());
+    }
+}
+
+impl Drop for MappedSim {
+    fn drop(&mut self) {
+        if let Storage::File { path, .. } = &self.storage {
+            // The file only holds scratch state, so failing to remove it is not worth a panic.
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::OutOfMemory,
+        "the state is too large to address",
+    )
+}
+
+fn map(file: &File) -> io::Result<MmapMut> {
+    // SAFETY: the file is created and exclusively owned by the simulator, and it is only resized
+    // while unmapped.
+    unsafe { MmapMut::map_mut(file) }
+}
+
+fn c(re: f64, im: f64) -> Complex<f64> {
+    Complex::new(re, im)
+}
+
+fn x_matrix() -> Matrix {
+    [[c(0.0, 0.0), c(1.0, 0.0)], [c(1.0, 0.0), c(0.0, 0.0)]]
+}
+
+fn y_matrix() -> Matrix {
+    [[c(0.0, 0.0), c(0.0, -1.0)], [c(0.0, 1.0), c(0.0, 0.0)]]
+}
+
+fn phase_matrix(phase: f64) -> Matrix {
+    [
+        [c(1.0, 0.0), c(0.0, 0.0)],
+        [c(0.0, 0.0), Complex::from_polar(1.0, phase)],
+    ]
+}
+
+fn h_matrix() -> Matrix {
+    [
+        [c(FRAC_1_SQRT_2, 0.0), c(FRAC_1_SQRT_2, 0.0)],
+        [c(FRAC_1_SQRT_2, 0.0), c(-FRAC_1_SQRT_2, 0.0)],
+    ]
+}
+
+fn rx_matrix(theta: f64) -> Matrix {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    [[c(cos, 0.0), c(0.0, -sin)], [c(0.0, -sin), c(cos, 0.0)]]
+}
+
+fn ry_matrix(theta: f64) -> Matrix {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    [[c(cos, 0.0), c(-sin, 0.0)], [c(sin, 0.0), c(cos, 0.0)]]
+}
+
+fn rz_matrix(theta: f64) -> Matrix {
+    [
+        [Complex::from_polar(1.0, -theta / 2.0), c(0.0, 0.0)],
+        [c(0.0, 0.0), Complex::from_polar(1.0, theta / 2.0)],
+    ]
+}
+
+impl Backend for MappedSim {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.apply(&[ctl0, ctl1], q, &x_matrix());
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.apply(&[ctl], q, &x_matrix());
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.apply(&[ctl], q, &y_matrix());
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.apply(&[ctl], q, &phase_matrix(std::f64::consts::PI));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.apply(&[], q, &h_matrix());
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measure(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let result = self.measure(q);
+        if result {
+            self.apply(&[], q, &x_matrix());
+        }
+        result
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.mresetz(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.apply(&[], q, &rx_matrix(theta));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.h(q0);
+        self.h(q1);
+        self.rzz_impl(theta, q0, q1);
+        self.h(q0);
+        self.h(q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.apply(&[], q, &ry_matrix(theta));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rx(FRAC_PI_2, q0);
+        self.rx(FRAC_PI_2, q1);
+        self.rzz_impl(theta, q0, q1);
+        self.rx(-FRAC_PI_2, q0);
+        self.rx(-FRAC_PI_2, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.apply(&[], q, &rz_matrix(theta));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rzz_impl(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.apply(&[], q, &phase_matrix(-FRAC_PI_2));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.apply(&[], q, &phase_matrix(FRAC_PI_2));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        // Swapping the qubit ids is equivalent to swapping the states and avoids touching the file.
+        self.positions.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.apply(&[], q, &phase_matrix(-FRAC_PI_4));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.apply(&[], q, &phase_matrix(FRAC_PI_4));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.apply(&[], q, &x_matrix());
+    }
+
+    fn y(&mut self, q: usize) {
+        self.apply(&[], q, &y_matrix());
+    }
+
+    fn z(&mut self, q: usize) {
+        self.apply(&[], q, &phase_matrix(std::f64::consts::PI));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        // The new qubit takes the next position, and since the new upper half of the file is
+        // zero-filled it starts out in the |0⟩ state.
+        let position = self.qubit_count();
+        let resized = u32::try_from(position + 1)
+            .ok()
+            .and_then(|bits| 1_usize.checked_shl(bits))
+            .ok_or_else(too_large)
+            .and_then(|len| self.resize(len));
+        if let Err(err) = resized {
+            self.failure = Some(format!(
+                "the state of {} qubits could not be stored: {err}",
+                position + 1
+            ));
+            // The id is never used, since the program fails at the allocation.
+            return self.positions.len();
+        }
+        match self.positions.iter().position(Option::is_none) {
+            Some(id) => {
+                self.positions[id] = Some(position);
+                id
+            }
+            None => {
+                self.positions.push(Some(position));
+                self.positions.len() - 1
+            }
+        }
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.reset(q);
+        // Move the qubit to the last position so that dropping the upper half of the file
+        // removes it.
+        let last = self.qubit_count() - 1;
+        let position = self.position(q);
+        self.swap_positions(position, last);
+        for p in self.positions.iter_mut().flatten() {
+            if *p == last {
+                *p = position;
+            }
+        }
+        self.positions[q] = None;
+        // Storage that cannot shrink still holds the whole state, so only the program's view of
+        // its size changes.
+        let _ = self.resize(1 << last);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        // Report qubits in id order with the first qubit as the most significant bit, matching
+        // the sparse simulator.
+        let positions = self.positions.iter().flatten().copied().collect::<Vec<_>>();
+        let count = positions.len();
+        let mut state = (0..self.len())
+            .filter_map(|index| {
+                let value = self.get(index);
+                (value.norm_sqr() > ZERO_THRESHOLD).then(|| {
+                    let mut label = BigUint::default();
+                    for (rank, &position) in positions.iter().enumerate() {
+                        if index & (1 << position) != 0 {
+                            label.set_bit((count - 1 - rank) as u64, true);
+                        }
+                    }
+                    (label, value)
+                })
+            })
+            .collect::<Vec<_>>();
+        state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        if self.global_phase != 0.0 {
+            let phase = Complex::from_polar(1.0, self.global_phase);
+            state.iter_mut().for_each(|(_, value)| *value *= phase);
+        }
+        (state, count)
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.probability_of_one(q) <= ZERO_THRESHOLD
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        self.global_phase
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            "GlobalPhase" => {
+                let [ctls_val, theta] = &*arg.unwrap_tuple() else {
+                    panic!("tuple arity for GlobalPhase intrinsic should be 2");
+                };
+                let ctls = ctls_val
+                    .clone()
+                    .unwrap_array()
+                    .iter()
+                    .map(|q| q.clone().unwrap_qubit().0)
+                    .collect::<Vec<_>>();
+                let theta = theta.clone().unwrap_double();
+                if ctls.is_empty() {
+                    self.global_phase = (self.global_phase + theta).rem_euclid(TAU);
+                    return Some(Ok(Value::unit()));
+                }
+                let phase = Complex::from_polar(1.0, theta);
+                let ctl_mask = self.mask(&ctls);
+                for index in 0..self.len() {
+                    if index & ctl_mask == ctl_mask {
+                        let value = self.get(index);
+                        self.set(index, value * phase);
+                    }
+                }
+                self.add_rounding_error();
+                Some(Ok(Value::unit()))
+            }
+            "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
+            "EndEstimateCaching"
+            | "AccountForEstimatesInternal"
+            | "AccountForArithmeticInternal"
+            | "BeginRepeatEstimatesInternal"
+            | "EndRepeatEstimatesInternal" => Some(Ok(Value::unit())),
+            _ => None,
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::seed_from_u64(rand::thread_rng().next_u64()),
+        };
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        Some(self.rounding_error)
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        self.failure.take()
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{MappedSim, Precision};
+use crate::backend::{Backend, SparseSim};
+use std::path::PathBuf;
+
+fn state_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("qsc-mapped-{name}-{}", std::process::id()))
+}
+
+/// Applies the same program to the mapped and sparse simulators and checks that the resulting
+/// states agree.
+fn check_matches_sparse(name: &str, program: impl Fn(&mut dyn Backend<ResultType = bool>)) {
+    let mapped = MappedSim::new(state_path(name)).expect("simulator should be created");
+    check_matches_sparse_within(mapped, 1e-9, program);
+}
+
+fn check_matches_sparse_within(
+    mut mapped: MappedSim,
+    tolerance: f64,
+    program: impl Fn(&mut dyn Backend<ResultType = bool>),
+) {
+    let mut sparse = SparseSim::new();
+    program(&mut mapped);
+    program(&mut sparse);
+
+    let (mapped_state, mapped_count) = mapped.capture_quantum_state();
+    let (sparse_state, sparse_count) = sparse.capture_quantum_state();
+    assert_eq!(mapped_count, sparse_count);
+    assert_eq!(
+        mapped_state.len(),
+        sparse_state.len(),
+        "{mapped_state:?}\n{sparse_state:?}"
+    );
+    for ((mapped_index, mapped_amp), (sparse_index, sparse_amp)) in
+        mapped_state.iter().zip(sparse_state.iter())
+    {
+        assert_eq!(mapped_index, sparse_index);
+        assert!(
+            (mapped_amp - sparse_amp).norm() < tolerance,
+            "{mapped_amp} != {sparse_amp}"
+        );
+    }
+}
+
+#[test]
+fn single_qubit_gates_match_sparse_simulator() {
+    check_matches_sparse("single", |sim| {
+        let q0 = sim.qubit_allocate();
+        let q1 = sim.qubit_allocate();
+        sim.h(q0);
+        sim.t(q0);
+        sim.rx(0.3, q1);
+        sim.ry(1.2, q1);
+        sim.rz(-0.7, q1);
+        sim.s(q1);
+        sim.y(q0);
+        sim.tadj(q1);
+        sim.z(q0);
+        sim.sadj(q0);
+    });
+}
+
+#[test]
+fn gates_across_chunks_match_sparse_simulator() {
+    // With more qubits than fit in a chunk, gates on the last qubits pair amplitudes from
+    // different chunks, and controls can fall on either side of the chunk boundary.
+    check_matches_sparse("chunks", |sim| {
+        let qs = (0..14).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+        sim.h(qs[13]);
+        sim.h(qs[0]);
+        sim.cx(qs[13], qs[1]);
+        sim.cx(qs[0], qs[12]);
+        sim.ry(0.4, qs[12]);
+        sim.ccx(qs[1], qs[12], qs[13]);
+        sim.rzz(0.7, qs[0], qs[13]);
+        sim.t(qs[13]);
+    });
+}
+
+#[test]
+fn multi_qubit_gates_match_sparse_simulator() {
+    check_matches_sparse("multi", |sim| {
+        let qs = (0..3).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+        sim.h(qs[0]);
+        sim.cx(qs[0], qs[1]);
+        sim.ry(0.4, qs[2]);
+        sim.ccx(qs[0], qs[2], qs[1]);
+        sim.cy(qs[1], qs[2]);
+        sim.cz(qs[2], qs[0]);
+        sim.rxx(0.5, qs[0], qs[1]);
+        sim.ryy(0.6, qs[1], qs[2]);
+        sim.rzz(0.7, qs[0], qs[2]);
+        sim.swap(qs[0], qs[2]);
+        sim.h(qs[2]);
+    });
+}
+
+#[test]
+fn released_qubits_are_removed_from_state() {
+    check_matches_sparse("release", |sim| {
+        let q0 = sim.qubit_allocate();
+        let q1 = sim.qubit_allocate();
+        let q2 = sim.qubit_allocate();
+        sim.h(q0);
+        sim.x(q2);
+        sim.cx(q0, q1);
+        sim.x(q2);
+        sim.qubit_release(q2);
+        let q3 = sim.qubit_allocate();
+        sim.ry(0.9, q3);
+        sim.cx(q1, q0);
+        sim.h(q1);
+        sim.qubit_release(q1);
+    });
+}
+
+#[test]
+fn measurement_collapses_state() {
+    let mut sim = MappedSim::new(state_path("measure")).expect("simulator should be created");
+    sim.set_seed(Some(42));
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    let first = sim.m(q0);
+    assert_eq!(sim.m(q1), first);
+
+    assert_eq!(sim.mresetz(q0), first);
+    assert!(sim.qubit_is_zero(q0));
+    let (state, count) = sim.capture_quantum_state();
+    assert_eq!(count, 2);
+    assert_eq!(state.len(), 1);
+}
+
+#[test]
+fn state_file_grows_with_qubits_and_is_removed_on_drop() {
+    let path = state_path("file");
+    let mut sim = MappedSim::new(&path).expect("simulator should be created");
+    let file_len = || {
+        std::fs::metadata(&path)
+            .expect("state file should exist")
+            .len()
+    };
+    assert_eq!(file_len(), 16);
+
+    let qs = (0..4).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+    assert_eq!(file_len(), 16 << 4);
+    sim.qubit_release(qs[1]);
+    assert_eq!(file_len(), 16 << 3);
+
+    drop(sim);
+    assert!(!path.exists());
+}
+
+#[test]
+fn single_precision_in_memory_matches_sparse_simulator_within_error_estimate() {
+    let program = |sim: &mut dyn Backend<ResultType = bool>| {
+        let qs = (0..3).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+        for i in 0..20_u32 {
+            sim.ry(0.1 * f64::from(i), qs[i as usize % 3]);
+            sim.cx(qs[0], qs[(i as usize % 2) + 1]);
+            sim.t(qs[2]);
+        }
+    };
+    let mut mapped = MappedSim::in_memory(Precision::Single).expect("simulator should be created");
+    assert!(mapped.path().is_none());
+    program(&mut mapped);
+    let error = mapped.estimated_error();
+    assert!(error > 0.0);
+    check_matches_sparse_within(
+        MappedSim::in_memory(Precision::Single).expect("simulator should be created"),
+        error,
+        program,
+    );
+}
@@ -0,0 +1,300 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+/// The probabilities of the errors that affect each qubit an operation acts on, which must be
+/// between 0 and 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Noise {
+    /// The probability that one of `X`, `Y` or `Z`, chosen uniformly, is applied.
+    pub depolarizing: f64,
+    /// The probability that `X` is applied.
+    pub bit_flip: f64,
+    /// The probability γ that an excited qubit decays to |0〉, applied as the channel with Kraus
+    /// operators |0〉〈0| + √(1-γ)|1〉〈1| and √γ|0〉〈1|.
+    pub amplitude_damping: f64,
+}
+
+impl Noise {
+    fn validate(self) -> Result<Self, InvalidNoise> {
+        for (kind, probability) in [
+            ("depolarizing", self.depolarizing),
+            ("bit flip", self.bit_flip),
+            ("amplitude damping", self.amplitude_damping),
+        ] {
+            // Also false for NaN.
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(InvalidNoise { kind, probability });
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// A probability of [`Noise`] that is not between 0 and 1.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("the {kind} probability must be between 0 and 1, but is {probability}")]
+pub struct InvalidNoise {
+    /// The kind of error that the probability is for, such as `bit flip`.
+    pub kind: &'static str,
+    pub probability: f64,
+}
+
+/// The noise that a [`NoisySim`] applies, for every operation or for each operation by its name.
+///
+/// Operations are named like the methods of [`Backend`] that apply them, such as `h`, `cx`, `rz`,
+/// `reset`, `m` and `mresetz`. Noise is applied to the qubits of a gate or reset after it, and
+/// to the qubit of a measurement before it, so that it also models readout errors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoiseModel {
+    default: Noise,
+    operations: FxHashMap<String, Noise>,
+}
+
+impl NoiseModel {
+    /// A noise model that applies the same noise after every operation.
+    /// # Errors
+    /// Returns an error if a probability of the noise is not between 0 and 1.
+    pub fn new(noise: Noise) -> Result<Self, InvalidNoise> {
+        Ok(Self {
+            default: noise.validate()?,
+            operations: FxHashMap::default(),
+        })
+    }
+
+    /// Applies the given noise instead of the default for the operation with the given name.
+    /// # Errors
+    /// Returns an error if a probability of the noise is not between 0 and 1.
+    pub fn with_operation(mut self, name: &str, noise: Noise) -> Result<Self, InvalidNoise> {
+        self.operations.insert(name.to_string(), noise.validate()?);
+        Ok(self)
+    }
+
+    fn noise(&self, name: &str) -> Noise {
+        self.operations.get(name).copied().unwrap_or(self.default)
+    }
+}
+
+/// A backend that applies a [`NoiseModel`] around the operations of another backend, choosing
+/// one Kraus operator of each channel at random, so that sampling shots of a program gives
+/// samples of its noisy behavior.
+///
+/// Amplitude damping is applied by coupling the qubit to an auxiliary qubit that is measured and
+/// released, so any backend that supports the gates and measurements can be made noisy. Because
+/// noise can leave a qubit that the program returned to |0〉 in another state, qubits are reset
+/// when they are released and are always reported to be in |0〉.
+pub struct NoisySim<B> {
+    pub sim: B,
+    model: NoiseModel,
+    rng: StdRng,
+}
+
+impl<B: Backend> NoisySim<B> {
+    #[must_use]
+    pub fn new(sim: B, model: NoiseModel) -> Self {
+        Self {
+            sim,
+            model,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    fn apply_noise(&mut self, name: &str, qubits: &[usize]) {
+        let noise = self.model.noise(name);
+        if noise == Noise::default() {
+            return;
+        }
+        for &q in qubits {
+            if self.rng.gen_bool(noise.depolarizing) {
+                match self.rng.gen_range(0..3) {
+                    0 => self.sim.x(q),
+                    1 => self.sim.y(q),
+                    _ => self.sim.z(q),
+                }
+            }
+            if self.rng.gen_bool(noise.bit_flip) {
+                self.sim.x(q);
+            }
+            if noise.amplitude_damping > 0.0 {
+                self.damp(q, noise.amplitude_damping);
+            }
+        }
+    }
+
+    /// Rotates an auxiliary qubit by an angle θ with sin²(θ/2) = γ controlled on `q`, and
+    /// returns the excitation to |0〉 if the auxiliary qubit picked it up, so that measuring the
+    /// auxiliary qubit applies one of the Kraus operators of amplitude damping to `q`.
+    fn damp(&mut self, q: usize, gamma: f64) {
+        let theta = 2.0 * gamma.sqrt().asin();
+        let aux = self.sim.qubit_allocate();
+        self.sim.ry(theta / 2.0, aux);
+        self.sim.cx(q, aux);
+        self.sim.ry(-theta / 2.0, aux);
+        self.sim.cx(q, aux);
+        self.sim.cx(aux, q);
+        self.sim.mresetz(aux);
+        self.sim.qubit_release(aux);
+    }
+
+    fn gate(&mut self, name: &str, qubits: &[usize], apply: impl FnOnce(&mut B)) {
+        apply(&mut self.sim);
+        self.apply_noise(name, qubits);
+    }
+}
+
+impl<B: Backend> Backend for NoisySim<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.gate("ccx", &[ctl0, ctl1, q], |sim| sim.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.gate("cx", &[ctl, q], |sim| sim.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.gate("cy", &[ctl, q], |sim| sim.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.gate("cz", &[ctl, q], |sim| sim.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.gate("h", &[q], |sim| sim.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.apply_noise("m", &[q]);
+        self.sim.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.apply_noise("mresetz", &[q]);
+        self.sim.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.gate("reset", &[q], |sim| sim.reset(q));
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.gate("rx", &[q], |sim| sim.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rxx", &[q0, q1], |sim| sim.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.gate("ry", &[q], |sim| sim.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("ryy", &[q0, q1], |sim| sim.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.gate("rz", &[q], |sim| sim.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rzz", &[q0, q1], |sim| sim.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.gate("sadj", &[q], |sim| sim.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.gate("s", &[q], |sim| sim.s(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.gate("swap", &[q0, q1], |sim| sim.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.gate("tadj", &[q], |sim| sim.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.gate("t", &[q], |sim| sim.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.gate("x", &[q], |sim| sim.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.gate("y", &[q], |sim| sim.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.gate("z", &[q], |sim| sim.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.sim.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.sim.reset(q);
+        self.sim.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.sim.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, _q: usize) -> bool {
+        true
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        self.sim.restore_quantum_state(state, qubit_count)
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        self.sim.global_phase()
+    }
+
+    fn error_estimate(&mut self) -> Option<f64> {
+        self.sim.error_estimate()
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.sim.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.sim.set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        self.sim.close();
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        self.sim.take_failure()
+    }
+}
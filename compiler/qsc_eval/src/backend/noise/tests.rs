@@ -0,0 +1,145 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{InvalidNoise, Noise, NoiseModel, NoisySim};
+use crate::backend::{Backend, SparseSim};
+
+fn noisy(model: NoiseModel, seed: u64) -> NoisySim<SparseSim> {
+    let mut sim = NoisySim::new(SparseSim::new(), model);
+    sim.set_seed(Some(seed));
+    sim
+}
+
+fn count_ones(
+    sim: &mut NoisySim<SparseSim>,
+    shots: usize,
+    prepare: impl Fn(&mut NoisySim<SparseSim>, usize),
+) -> usize {
+    let mut ones = 0;
+    for _ in 0..shots {
+        let q = sim.qubit_allocate();
+        prepare(sim, q);
+        ones += usize::from(sim.mresetz(q));
+        sim.qubit_release(q);
+    }
+    ones
+}
+
+#[test]
+fn noiseless_model_matches_ideal_simulation() {
+    let mut sim = noisy(NoiseModel::default(), 0);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.x(q)), 16);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.z(q)), 0);
+}
+
+#[test]
+fn certain_bit_flip_flips_every_gate() {
+    let model = NoiseModel::new(Noise {
+        bit_flip: 1.0,
+        ..Noise::default()
+    })
+    .expect("noise should be valid")
+    .with_operation("mresetz", Noise::default())
+    .expect("noise should be valid");
+    let mut sim = noisy(model, 0);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.x(q)), 0);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.z(q)), 16);
+}
+
+#[test]
+fn bit_flip_before_measurement_is_a_readout_error() {
+    let model = NoiseModel::default()
+        .with_operation(
+            "mresetz",
+            Noise {
+                bit_flip: 1.0,
+                ..Noise::default()
+            },
+        )
+        .expect("noise should be valid");
+    let mut sim = noisy(model, 0);
+    assert_eq!(count_ones(&mut sim, 16, |_, _| ()), 16);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.x(q)), 0);
+}
+
+#[test]
+fn certain_amplitude_damping_decays_to_zero() {
+    let model = NoiseModel::default()
+        .with_operation(
+            "x",
+            Noise {
+                amplitude_damping: 1.0,
+                ..Noise::default()
+            },
+        )
+        .expect("noise should be valid");
+    let mut sim = noisy(model, 0);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.x(q)), 0);
+    assert_eq!(count_ones(&mut sim, 16, |sim, q| sim.y(q)), 16);
+}
+
+#[test]
+fn partial_amplitude_damping_decays_some_shots() {
+    let model = NoiseModel::default()
+        .with_operation(
+            "x",
+            Noise {
+                amplitude_damping: 0.5,
+                ..Noise::default()
+            },
+        )
+        .expect("noise should be valid");
+    let mut sim = noisy(model, 0);
+    let ones = count_ones(&mut sim, 200, |sim, q| sim.x(q));
+    assert!(60 < ones && ones < 140, "{ones}");
+}
+
+#[test]
+fn depolarizing_noise_is_reproducible_with_a_seed() {
+    let model = NoiseModel::new(Noise {
+        depolarizing: 0.5,
+        ..Noise::default()
+    })
+    .expect("noise should be valid");
+    let shots = |seed| {
+        let mut sim = noisy(model.clone(), seed);
+        (0..32)
+            .map(|_| count_ones(&mut sim, 1, |sim, q| sim.x(q)))
+            .collect::<Vec<_>>()
+    };
+    let samples = shots(7);
+    assert_eq!(samples, shots(7));
+    assert!(samples.contains(&0) && samples.contains(&1), "{samples:?}");
+}
+
+#[test]
+fn probabilities_outside_unit_interval_are_rejected() {
+    assert_eq!(
+        NoiseModel::new(Noise {
+            bit_flip: 1.5,
+            ..Noise::default()
+        }),
+        Err(InvalidNoise {
+            kind: "bit flip",
+            probability: 1.5
+        })
+    );
+    assert!(NoiseModel::default()
+        .with_operation(
+            "h",
+            Noise {
+                depolarizing: -0.1,
+                ..Noise::default()
+            }
+        )
+        .is_err());
+    assert!(NoiseModel::default()
+        .with_operation(
+            "x",
+            Noise {
+                amplitude_damping: f64::NAN,
+                ..Noise::default()
+            }
+        )
+        .is_err());
+}
@@ -0,0 +1,718 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Backend, SparseSim};
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+use std::f64::consts::FRAC_PI_2;
+
+/// What a [`StabilizerSim`] does when a program applies an operation outside the Clifford group,
+/// such as a `T` gate or a rotation by an angle that is not a multiple of π/2.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NonClifford {
+    /// Convert the stabilizer state to a state vector and continue on a sparse simulator.
+    #[default]
+    Fallback,
+    /// Fail the operation, so that programs that were expected to be Clifford-only stop at the
+    /// first operation that is not.
+    Fail,
+}
+
+/// A backend that simulates Clifford operations and measurements on a stabilizer tableau, which
+/// takes time and memory polynomial in the number of qubits, so that programs such as error
+/// correction prototypes can run on hundreds of qubits.
+///
+/// The tableau has a destabilizer and a stabilizer generator for every qubit, as described in
+/// [Aaronson and Gottesman](https://arxiv.org/abs/quant-ph/0406196). Global phase is not tracked,
+/// so state dumps match other simulators up to global phase.
+pub struct StabilizerSim {
+    /// The stabilizer state, or `None` once the simulation has fallen back to `sparse`.
+    tableau: Option<Tableau>,
+    /// The simulator that takes over after a non-Clifford operation, which also handles the
+    /// custom intrinsics that do not affect the state.
+    sparse: SparseSim,
+    non_clifford: NonClifford,
+    rng: StdRng,
+    /// Why the last operation could not be applied, until taken by the evaluator.
+    failure: Option<String>,
+}
+
+impl Default for StabilizerSim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StabilizerSim {
+    /// Creates a stabilizer simulator that falls back to a sparse simulator at the first
+    /// non-Clifford operation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_non_clifford(NonClifford::Fallback)
+    }
+
+    /// Creates a stabilizer simulator that fails every non-Clifford operation.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self::with_non_clifford(NonClifford::Fail)
+    }
+
+    #[must_use]
+    pub fn with_non_clifford(non_clifford: NonClifford) -> Self {
+        Self {
+            tableau: Some(Tableau::default()),
+            sparse: SparseSim::new(),
+            non_clifford,
+            rng: StdRng::from_entropy(),
+            failure: None,
+        }
+    }
+
+    /// Whether the simulation is still on the stabilizer tableau, rather than having fallen
+    /// back to the sparse simulator.
+    #[must_use]
+    pub fn is_stabilizer(&self) -> bool {
+        self.tableau.is_some()
+    }
+
+    fn clifford(
+        &mut self,
+        on_tableau: impl FnOnce(&mut Tableau),
+        on_sparse: impl FnOnce(&mut SparseSim),
+    ) {
+        match &mut self.tableau {
+            Some(tableau) => on_tableau(tableau),
+            None => on_sparse(&mut self.sparse),
+        }
+    }
+
+    /// Applies an operation that the tableau cannot simulate, falling back to the sparse
+    /// simulator first if needed, or recording a failure in strict mode.
+    fn non_clifford(&mut self, operation: &str, on_sparse: impl FnOnce(&mut SparseSim)) {
+        if self.leave_tableau(operation) {
+            on_sparse(&mut self.sparse);
+        }
+    }
+
+    /// Moves the state from the tableau to the sparse simulator, returning whether the sparse
+    /// simulator now holds the state.
+    fn leave_tableau(&mut self, operation: &str) -> bool {
+        let Some(tableau) = &self.tableau else {
+            return true;
+        };
+        if self.non_clifford == NonClifford::Fail {
+            self.failure = Some(format!(
+                "{operation} is not a Clifford operation, which the stabilizer simulator does not support"
+            ));
+            return false;
+        }
+        let qubits = (0..tableau.len()).collect::<Vec<_>>();
+        let state = tableau.state(&qubits);
+        let free = qubits
+            .into_iter()
+            .filter(|&q| !tableau.live[q])
+            .collect::<Vec<_>>();
+        // Restoring allocates the qubits with the same ids that the tableau used.
+        self.sparse
+            .restore_quantum_state(&state, tableau.len())
+            .expect("sparse simulator should restore any state");
+        for q in free {
+            self.sparse.qubit_release(q);
+        }
+        self.tableau = None;
+        true
+    }
+
+    fn rotation(
+        &mut self,
+        operation: &str,
+        theta: f64,
+        on_tableau: impl FnOnce(&mut Tableau, u8),
+        on_sparse: impl FnOnce(&mut SparseSim),
+    ) {
+        match (&mut self.tableau, quarter_turns(theta)) {
+            (Some(tableau), Some(turns)) => on_tableau(tableau, turns),
+            _ => self.non_clifford(operation, on_sparse),
+        }
+    }
+}
+
+impl Backend for StabilizerSim {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.non_clifford("CCX", |sim| sim.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.clifford(|t| t.cx(ctl, q), |sim| sim.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.clifford(|t| t.cy(ctl, q), |sim| sim.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.clifford(|t| t.cz(ctl, q), |sim| sim.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.clifford(|t| t.h(q), |sim| sim.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> bool {
+        match &mut self.tableau {
+            Some(tableau) => tableau.measure(q, || self.rng.gen()),
+            None => self.sparse.m(q),
+        }
+    }
+
+    fn mresetz(&mut self, q: usize) -> bool {
+        match &mut self.tableau {
+            Some(tableau) => {
+                let result = tableau.measure(q, || self.rng.gen());
+                if result {
+                    tableau.x(q);
+                }
+                result
+            }
+            None => self.sparse.mresetz(q),
+        }
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.clifford(|t| t.reset(q), |sim| sim.reset(q));
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.rotation("Rx", theta, |t, k| t.rx(k, q), |sim| sim.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Rxx",
+            theta,
+            |t, k| t.rxx(k, q0, q1),
+            |sim| sim.rxx(theta, q0, q1),
+        );
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.rotation("Ry", theta, |t, k| t.ry(k, q), |sim| sim.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Ryy",
+            theta,
+            |t, k| t.ryy(k, q0, q1),
+            |sim| sim.ryy(theta, q0, q1),
+        );
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.rotation("Rz", theta, |t, k| t.rz(k, q), |sim| sim.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(
+            "Rzz",
+            theta,
+            |t, k| t.rzz(k, q0, q1),
+            |sim| sim.rzz(theta, q0, q1),
+        );
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.clifford(|t| t.sadj(q), |sim| sim.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.clifford(|t| t.s(q), |sim| sim.s(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.clifford(|t| t.swap(q0, q1), |sim| sim.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.non_clifford("T†", |sim| sim.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.non_clifford("T", |sim| sim.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.clifford(|t| t.x(q), |sim| sim.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.clifford(|t| t.y(q), |sim| sim.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.clifford(|t| t.z(q), |sim| sim.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        match &mut self.tableau {
+            Some(tableau) => tableau.allocate(),
+            None => self.sparse.qubit_allocate(),
+        }
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.clifford(|t| t.release(q), |sim| sim.qubit_release(q));
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        match &self.tableau {
+            Some(tableau) => {
+                let live = (0..tableau.len())
+                    .filter(|&q| tableau.live[q])
+                    .collect::<Vec<_>>();
+                (tableau.state(&live), live.len())
+            }
+            None => self.sparse.capture_quantum_state(),
+        }
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        match &self.tableau {
+            Some(tableau) => tableau.is_zero(q),
+            None => self.sparse.qubit_is_zero(q),
+        }
+    }
+
+    fn restore_quantum_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        qubit_count: usize,
+    ) -> Result<(), String> {
+        // An arbitrary state has no stabilizer description, so it is restored on the sparse
+        // simulator.
+        if self.leave_tableau("restoring a state") {
+            self.sparse.restore_quantum_state(state, qubit_count)
+        } else {
+            Err(self
+                .failure
+                .take()
+                .expect("failure should be recorded when the tableau is kept"))
+        }
+    }
+
+    fn global_phase(&mut self) -> f64 {
+        match &mut self.tableau {
+            Some(_) => 0.0,
+            None => self.sparse.global_phase(),
+        }
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        if name != "GlobalPhase" || self.tableau.is_none() {
+            return self.sparse.custom_intrinsic(name, arg);
+        }
+        let [ctls, theta] = &*arg.clone().unwrap_tuple() else {
+            panic!("tuple arity for GlobalPhase intrinsic should be 2");
+        };
+        let ctls = ctls
+            .clone()
+            .unwrap_array()
+            .iter()
+            .map(|q| q.clone().unwrap_qubit().0)
+            .collect::<Vec<_>>();
+        let theta = theta.clone().unwrap_double();
+        match (&mut self.tableau, ctls.as_slice(), quarter_turns(theta)) {
+            // An uncontrolled phase is a global phase, which the tableau does not track.
+            (Some(_), [], _) | (Some(_), _, Some(0)) => Some(Ok(Value::unit())),
+            // A singly controlled phase is a phase gate on the control.
+            (Some(tableau), [ctl], Some(turns)) => {
+                tableau.phase(turns, *ctl);
+                Some(Ok(Value::unit()))
+            }
+            _ => {
+                if self.leave_tableau("a controlled global phase") {
+                    self.sparse.custom_intrinsic(name, arg)
+                } else {
+                    Some(Ok(Value::unit()))
+                }
+            }
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.sparse.set_seed(seed);
+    }
+
+    fn close(&mut self) {
+        self.tableau = Some(Tableau::default());
+        self.sparse.close();
+        self.failure = None;
+    }
+
+    fn take_failure(&mut self) -> Option<String> {
+        self.failure.take()
+    }
+}
+
+/// The number of quarter turns that a rotation by `theta` makes, if it is a multiple of π/2.
+fn quarter_turns(theta: f64) -> Option<u8> {
+    let turns = theta / FRAC_PI_2;
+    let rounded = turns.round();
+    if (turns - rounded).abs() > 1e-9 {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let turns = (rounded as i64).rem_euclid(4);
+    u8::try_from(turns).ok()
+}
+
+/// A Pauli operator on every qubit of a tableau, with a sign. Qubit `j` has `X` when only `x[j]`
+/// is set, `Z` when only `z[j]` is set and `Y` when both are.
+#[derive(Clone, Debug)]
+struct Pauli {
+    x: Vec<bool>,
+    z: Vec<bool>,
+    negative: bool,
+}
+
+impl Pauli {
+    fn identity(len: usize) -> Self {
+        Self {
+            x: vec![false; len],
+            z: vec![false; len],
+            negative: false,
+        }
+    }
+
+    /// The operator `Z` on qubit `q`, or `-Z` when `negative`.
+    fn z(len: usize, q: usize, negative: bool) -> Self {
+        let mut pauli = Self::identity(len);
+        pauli.z[q] = true;
+        pauli.negative = negative;
+        pauli
+    }
+
+    /// Multiplies the operator by `other`. The sign of the product is only meaningful when the
+    /// two commute, which is all that the signs of stabilizers need.
+    fn multiply(&mut self, other: &Pauli) {
+        let mut power = 2 * (i32::from(self.negative) + i32::from(other.negative));
+        for j in 0..self.x.len() {
+            power += product_power(other.x[j], other.z[j], self.x[j], self.z[j]);
+            self.x[j] ^= other.x[j];
+            self.z[j] ^= other.z[j];
+        }
+        self.negative = power.rem_euclid(4) == 2;
+    }
+
+    /// Applies the operator to a basis state whose bit `j` holds qubit `j`, returning the basis
+    /// state it maps to and the phase it picks up.
+    fn apply(&self, index: &BigUint) -> (BigUint, Complex<f64>) {
+        let mut image = index.clone();
+        // The phase is i to this power. `Y` is `iXZ`, so it contributes a factor of i on top of
+        // the sign that its `Z` part gives.
+        let mut power = if self.negative { 2 } else { 0 };
+        for j in 0..self.x.len() {
+            let bit = index.bit(j as u64);
+            if self.x[j] {
+                image.set_bit(j as u64, !bit);
+            }
+            if self.x[j] && self.z[j] {
+                power += 1;
+            }
+            if self.z[j] && bit {
+                power += 2;
+            }
+        }
+        let phase = match power % 4 {
+            0 => Complex::new(1.0, 0.0),
+            1 => Complex::new(0.0, 1.0),
+            2 => Complex::new(-1.0, 0.0),
+            _ => Complex::new(0.0, -1.0),
+        };
+        (image, phase)
+    }
+}
+
+/// The power of i in the product of the single-qubit Paulis `(x1, z1)` and `(x2, z2)`.
+fn product_power(x1: bool, z1: bool, x2: bool, z2: bool) -> i32 {
+    let (x2, z2) = (i32::from(x2), i32::from(z2));
+    match (x1, z1) {
+        (false, false) => 0,
+        (true, true) => z2 - x2,
+        (true, false) => z2 * (2 * x2 - 1),
+        (false, true) => x2 * (1 - 2 * z2),
+    }
+}
+
+/// The destabilizer and stabilizer generators of a stabilizer state, one of each per qubit.
+#[derive(Clone, Default)]
+struct Tableau {
+    destabilizers: Vec<Pauli>,
+    stabilizers: Vec<Pauli>,
+    /// Whether each qubit is allocated. Released qubits are left in |0⟩ and are reused first.
+    live: Vec<bool>,
+}
+
+impl Tableau {
+    fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    fn generators(&mut self) -> impl Iterator<Item = &mut Pauli> {
+        self.destabilizers
+            .iter_mut()
+            .chain(self.stabilizers.iter_mut())
+    }
+
+    fn allocate(&mut self) -> usize {
+        if let Some(q) = self.live.iter().position(|live| !live) {
+            self.live[q] = true;
+            return q;
+        }
+        let q = self.len();
+        for generator in self.generators() {
+            generator.x.push(false);
+            generator.z.push(false);
+        }
+        let mut destabilizer = Pauli::identity(q + 1);
+        destabilizer.x[q] = true;
+        self.destabilizers.push(destabilizer);
+        self.stabilizers.push(Pauli::z(q + 1, q, false));
+        self.live.push(true);
+        q
+    }
+
+    fn release(&mut self, q: usize) {
+        self.reset(q);
+        self.live[q] = false;
+    }
+
+    fn reset(&mut self, q: usize) {
+        if self.measure(q, || false) {
+            self.x(q);
+        }
+    }
+
+    fn h(&mut self, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^= generator.x[q] && generator.z[q];
+            let x = generator.x[q];
+            generator.x[q] = generator.z[q];
+            generator.z[q] = x;
+        }
+    }
+
+    fn s(&mut self, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^= generator.x[q] && generator.z[q];
+            generator.z[q] ^= generator.x[q];
+        }
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.z(q);
+        self.s(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^= generator.z[q];
+        }
+    }
+
+    fn y(&mut self, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^= generator.x[q] ^ generator.z[q];
+        }
+    }
+
+    fn z(&mut self, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^= generator.x[q];
+        }
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        for generator in self.generators() {
+            generator.negative ^=
+                generator.x[ctl] && generator.z[q] && !(generator.x[q] ^ generator.z[ctl]);
+            generator.x[q] ^= generator.x[ctl];
+            generator.z[ctl] ^= generator.z[q];
+        }
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.sadj(q);
+        self.cx(ctl, q);
+        self.s(q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.h(q);
+        self.cx(ctl, q);
+        self.h(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.cx(q0, q1);
+        self.cx(q1, q0);
+        self.cx(q0, q1);
+    }
+
+    /// Applies the phase gate that multiplies |1⟩ by i to the given power.
+    fn phase(&mut self, turns: u8, q: usize) {
+        match turns {
+            1 => self.s(q),
+            2 => self.z(q),
+            3 => self.sadj(q),
+            _ => {}
+        }
+    }
+
+    /// Applies `Rz` by the given number of quarter turns, which is a phase gate up to global
+    /// phase.
+    fn rz(&mut self, turns: u8, q: usize) {
+        self.phase(turns, q);
+    }
+
+    fn rx(&mut self, turns: u8, q: usize) {
+        self.h(q);
+        self.rz(turns, q);
+        self.h(q);
+    }
+
+    fn ry(&mut self, turns: u8, q: usize) {
+        // `S X S†` is `Y`.
+        self.sadj(q);
+        self.rx(turns, q);
+        self.s(q);
+    }
+
+    fn rzz(&mut self, turns: u8, q0: usize, q1: usize) {
+        self.cx(q0, q1);
+        self.rz(turns, q1);
+        self.cx(q0, q1);
+    }
+
+    fn rxx(&mut self, turns: u8, q0: usize, q1: usize) {
+        self.h(q0);
+        self.h(q1);
+        self.rzz(turns, q0, q1);
+        self.h(q0);
+        self.h(q1);
+    }
+
+    fn ryy(&mut self, turns: u8, q0: usize, q1: usize) {
+        self.sadj(q0);
+        self.sadj(q1);
+        self.rxx(turns, q0, q1);
+        self.s(q0);
+        self.s(q1);
+    }
+
+    /// Measures qubit `q` in the computational basis, using `random` for the outcome when it is
+    /// not determined by the state.
+    fn measure(&mut self, q: usize, random: impl FnOnce() -> bool) -> bool {
+        let Some(pivot) = self.stabilizers.iter().position(|s| s.x[q]) else {
+            return self.deterministic_outcome(q);
+        };
+        let anticommuting = self.stabilizers[pivot].clone();
+        for destabilizer in &mut self.destabilizers {
+            if destabilizer.x[q] {
+                destabilizer.multiply(&anticommuting);
+            }
+        }
+        for (i, stabilizer) in self.stabilizers.iter_mut().enumerate() {
+            if i != pivot && stabilizer.x[q] {
+                stabilizer.multiply(&anticommuting);
+            }
+        }
+        let outcome = random();
+        self.destabilizers[pivot] = anticommuting;
+        self.stabilizers[pivot] = Pauli::z(self.len(), q, outcome);
+        outcome
+    }
+
+    /// The outcome of measuring qubit `q`, which every stabilizer must commute with `Z` on.
+    fn deterministic_outcome(&self, q: usize) -> bool {
+        let mut product = Pauli::identity(self.len());
+        for (destabilizer, stabilizer) in self.destabilizers.iter().zip(&self.stabilizers) {
+            if destabilizer.x[q] {
+                product.multiply(stabilizer);
+            }
+        }
+        product.negative
+    }
+
+    fn is_zero(&self, q: usize) -> bool {
+        !self.stabilizers.iter().any(|s| s.x[q]) && !self.deterministic_outcome(q)
+    }
+
+    /// The amplitudes of the state over the given qubits, in the index convention of
+    /// `Backend::capture_quantum_state`. The other qubits must be in |0⟩.
+    fn state(&self, qubits: &[usize]) -> Vec<(BigUint, Complex<f64>)> {
+        // Find a basis state in the support by measuring every qubit, taking the first outcome
+        // whenever it is random.
+        let mut measured = self.clone();
+        let mut basis = BigUint::default();
+        for q in 0..self.len() {
+            if measured.measure(q, || false) {
+                basis.set_bit(q as u64, true);
+            }
+        }
+
+        // Projecting the basis state onto the +1 eigenspace of every stabilizer gives the
+        // state. Every intermediate state is a stabilizer state too, so its nonzero amplitudes
+        // all have the same magnitude, which is rescaled to one to keep the numbers small and
+        // tell exact cancellations apart from rounding.
+        let mut amplitudes = FxHashMap::default();
+        amplitudes.insert(basis, Complex::new(1.0, 0.0));
+        for stabilizer in &self.stabilizers {
+            let mut projected = amplitudes.clone();
+            for (index, amplitude) in &amplitudes {
+                let (image, phase) = stabilizer.apply(index);
+                *projected.entry(image).or_default() += amplitude * phase;
+            }
+            projected.retain(|_, amplitude| amplitude.norm_sqr() > 0.5);
+            let scale = projected
+                .values()
+                .next()
+                .expect("the basis state should be in the support")
+                .norm();
+            amplitudes = projected
+                .into_iter()
+                .map(|(index, amplitude)| (index, amplitude / scale))
+                .collect();
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let norm = (amplitudes.len() as f64).sqrt();
+        let mut state = amplitudes
+            .into_iter()
+            .map(|(index, amplitude)| {
+                // Bit `qubits.len() - 1 - k` of the index holds the value of the `k`th qubit.
+                let mut reordered = BigUint::default();
+                for (k, &q) in qubits.iter().enumerate() {
+                    if index.bit(q as u64) {
+                        reordered.set_bit((qubits.len() - 1 - k) as u64, true);
+                    }
+                }
+                (reordered, amplitude / norm)
+            })
+            .collect::<Vec<_>>();
+        state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        state
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::StabilizerSim;
+use crate::backend::{Backend, Differential, SparseSim};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+
+fn assert_state(sim: &mut impl Backend, expected: &[(u64, Complex<f64>)]) {
+    let (state, _) = sim.capture_quantum_state();
+    assert_eq!(state.len(), expected.len(), "{state:?}");
+    for ((index, amplitude), (expected_index, expected_amplitude)) in state.iter().zip(expected) {
+        assert_eq!(*index, BigUint::from(*expected_index), "{state:?}");
+        assert!((amplitude - expected_amplitude).norm() < 1e-9, "{state:?}");
+    }
+}
+
+#[test]
+fn bell_pairs_measure_equal_results_of_both_values() {
+    let mut sim = StabilizerSim::new();
+    let mut ones = 0;
+    for seed in 0..32 {
+        sim.set_seed(Some(seed));
+        let (a, b) = (sim.qubit_allocate(), sim.qubit_allocate());
+        sim.h(a);
+        sim.cx(a, b);
+        let result = sim.mresetz(a);
+        assert_eq!(sim.mresetz(b), result);
+        ones += usize::from(result);
+        sim.qubit_release(a);
+        sim.qubit_release(b);
+    }
+    assert!(0 < ones && ones < 32, "{ones}");
+    assert!(sim.is_stabilizer());
+}
+
+#[test]
+fn bell_state_is_captured_as_amplitudes() {
+    let mut sim = StabilizerSim::new();
+    let (a, b) = (sim.qubit_allocate(), sim.qubit_allocate());
+    sim.h(a);
+    sim.cx(a, b);
+    let amplitude = Complex::new(FRAC_1_SQRT_2, 0.0);
+    assert_state(&mut sim, &[(0b00, amplitude), (0b11, amplitude)]);
+}
+
+#[test]
+fn relative_phases_are_captured() {
+    let mut sim = StabilizerSim::new();
+    let (a, b) = (sim.qubit_allocate(), sim.qubit_allocate());
+    sim.h(a);
+    sim.s(a);
+    sim.x(b);
+    // The first qubit is the most significant bit of the index.
+    assert_state(
+        &mut sim,
+        &[
+            (0b01, Complex::new(FRAC_1_SQRT_2, 0.0)),
+            (0b11, Complex::new(0.0, FRAC_1_SQRT_2)),
+        ],
+    );
+}
+
+#[test]
+fn random_clifford_circuits_match_sparse_simulator() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut sim = Differential::new(StabilizerSim::new(), SparseSim::new());
+    let qubits = (0..5).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+    for _ in 0..400 {
+        let q = qubits[rng.gen_range(0..qubits.len())];
+        let other = qubits[(q + rng.gen_range(1..qubits.len())) % qubits.len()];
+        let turns = f64::from(rng.gen_range(-4..5)) * FRAC_PI_2;
+        match rng.gen_range(0..18) {
+            0 => sim.h(q),
+            1 => sim.s(q),
+            2 => sim.sadj(q),
+            3 => sim.x(q),
+            4 => sim.y(q),
+            5 => sim.z(q),
+            6 => sim.cx(q, other),
+            7 => sim.cy(q, other),
+            8 => sim.cz(q, other),
+            9 => sim.swap(q, other),
+            10 => sim.rx(turns, q),
+            11 => sim.ry(turns, q),
+            12 => sim.rz(turns, q),
+            13 => sim.rxx(turns, q, other),
+            14 => sim.ryy(turns, q, other),
+            15 => sim.rzz(turns, q, other),
+            16 => {
+                sim.m(q);
+            }
+            _ => sim.reset(q),
+        }
+    }
+    assert_eq!(sim.divergences(), []);
+    assert!(sim.primary.is_stabilizer());
+}
+
+#[test]
+fn non_clifford_gate_falls_back_to_sparse_simulator() {
+    let mut sim = Differential::new(StabilizerSim::new(), SparseSim::new());
+    let (a, b, c) = (
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+    );
+    let free = sim.qubit_allocate();
+    sim.h(a);
+    sim.cx(a, b);
+    sim.qubit_release(free);
+    sim.t(b);
+    sim.h(b);
+    sim.ccx(a, b, c);
+    sim.rx(0.3, a);
+    assert_eq!(sim.divergences(), []);
+    assert!(!sim.primary.is_stabilizer());
+}
+
+#[test]
+fn non_clifford_gate_fails_in_strict_mode() {
+    let mut sim = StabilizerSim::strict();
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    assert_eq!(sim.take_failure(), None);
+    sim.rz(PI / 3.0, q);
+    assert!(sim
+        .take_failure()
+        .is_some_and(|failure| failure.starts_with("Rz is not a Clifford operation")));
+    assert_eq!(sim.take_failure(), None);
+    assert!(sim.is_stabilizer());
+}
+
+#[test]
+fn wide_ghz_state_stays_on_the_tableau() {
+    let mut sim = StabilizerSim::new();
+    let qubits = (0..500).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+    sim.h(qubits[0]);
+    for pair in qubits.windows(2) {
+        sim.cx(pair[0], pair[1]);
+    }
+    let result = sim.m(qubits[250]);
+    for &q in &qubits {
+        assert_eq!(sim.mresetz(q), result);
+        sim.qubit_release(q);
+    }
+    assert!(sim.is_stabilizer());
+}
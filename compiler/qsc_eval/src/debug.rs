@@ -39,6 +39,11 @@ impl CallStack {
         self.frames.push(frame);
     }
 
+    #[must_use]
+    pub fn last(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
     pub fn pop_frame(&mut self) -> Option<Frame> {
         self.frames.pop()
     }
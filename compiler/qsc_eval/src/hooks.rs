@@ -0,0 +1,40 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::{Env, PackageSpan, SavedState};
+use num_bigint::BigUint;
+use num_complex::Complex;
+
+/// Host callbacks that are invoked around each evaluated statement.
+///
+/// Statements are only visible to the evaluator when the program was lowered with debug
+/// information, so hooks are not invoked for code lowered without it. Statements from generated
+/// code, which have no span, are skipped. Nested statements, such as those in the body of a loop,
+/// are reported between the `before_stmt` and `after_stmt` calls of the statement containing them.
+/// A statement that is interrupted by an error is not reported as finished.
+pub trait StmtHooks {
+    /// Called when evaluation of a statement begins, with the bindings visible at that point.
+    fn before_stmt(&mut self, _span: PackageSpan, _env: &Env) {}
+
+    /// Called when evaluation of a statement finishes, including when it finishes by returning
+    /// from the enclosing callable.
+    fn after_stmt(&mut self, _span: PackageSpan, _env: &Env) {}
+}
+
+/// A host callback that saves evaluations in progress, so that a long evaluation can be resumed
+/// from the last checkpoint after the host is interrupted.
+///
+/// The callback is invoked at the start of one statement in every interval given to
+/// [`Env::set_checkpoint_hook`], so like [`StmtHooks`] it is only invoked for code lowered with
+/// debug information.
+pub trait CheckpointHook {
+    /// Called with the paused evaluation, the environment holding its global bindings and qubit
+    /// allocations, and the simulator state as returned by
+    /// [`crate::backend::Backend::capture_quantum_state`].
+    fn checkpoint(
+        &mut self,
+        saved: SavedState,
+        env: &Env,
+        quantum_state: (Vec<(BigUint, Complex<f64>)>, usize),
+    );
+}
@@ -0,0 +1,42 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A handle that hosts use to interrupt evaluation, such as when the user presses Ctrl+C.
+///
+/// Clones of a handle share the same flag, so a clone can be moved to another thread or into a
+/// signal handler. Triggering the handle only stores to an atomic flag, which is safe to do from a
+/// signal handler. Evaluation checks the flag at safe points, before each statement and at each
+/// loop iteration, and stops there with [`crate::Error::Interrupted`]. Calls into intrinsics, such
+/// as simulator operations, finish before the interruption is observed.
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that evaluation stop at the next safe point.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether an interruption has been requested and not yet cleared.
+    #[must_use]
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Clears a requested interruption, so that evaluation can run again.
+    pub fn clear(&self) {
+        self.interrupted.store(false, Ordering::SeqCst);
+    }
+}
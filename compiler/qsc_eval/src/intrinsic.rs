@@ -38,7 +38,11 @@ pub(crate) fn call(
         "IntAsBigInt" => Ok(Value::BigInt(BigInt::from(arg.unwrap_int()))),
         "DumpMachine" => {
             let (state, qubit_count) = sim.capture_quantum_state();
-            match out.state(state, qubit_count) {
+            let phase = sim.global_phase();
+            match out
+                .state(state, qubit_count)
+                .and_then(|()| out.global_phase(phase))
+            {
                 Ok(()) => Ok(Value::unit()),
                 Err(_) => Err(Error::OutputFail(name_span)),
             }
@@ -104,6 +108,13 @@ pub(crate) fn call(
             let p = arg.unwrap_double();
             Ok(Value::Bool(rng.gen_bool(p)))
         }
+        "ReadMonotonicTimer" => read_timer().map(Value::Int).ok_or_else(|| {
+            Error::IntrinsicFail(
+                name.to_string(),
+                "the timer is not available on this platform".to_string(),
+                name_span,
+            )
+        }),
         #[allow(clippy::cast_possible_truncation)]
         "Truncate" => Ok(Value::Int(arg.unwrap_double() as i64)),
         "__quantum__rt__qubit_allocate" => Ok(Value::Qubit(Qubit(sim.qubit_allocate()))),
@@ -167,6 +178,22 @@ pub(crate) fn call(
     }
 }
 
+/// Reads the monotonic timer, in nanoseconds since it was first read in this process. Every
+/// evaluator reads the same timer, so readings from different runs can be compared.
+#[cfg(not(any(target_family = "wasm")))]
+fn read_timer() -> Option<i64> {
+    use std::{sync::OnceLock, time::Instant};
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    let origin = ORIGIN.get_or_init(Instant::now);
+    i64::try_from(origin.elapsed().as_nanos()).ok()
+}
+
+/// Browsers and other wasm hosts have no monotonic timer that the evaluator can read.
+#[cfg(any(target_family = "wasm"))]
+fn read_timer() -> Option<i64> {
+    None
+}
+
 fn one_qubit_gate(mut gate: impl FnMut(usize), arg: Value) -> Value {
     gate(arg.unwrap_qubit().0);
     Value::unit()
@@ -694,6 +694,28 @@ fn draw_random_bool() {
     );
 }
 
+#[test]
+fn read_monotonic_timer() {
+    check_intrinsic_value(
+        "",
+        indoc! {"{
+            let start = Microsoft.Quantum.Diagnostics.ReadMonotonicTimer();
+            let stop = Microsoft.Quantum.Diagnostics.ReadMonotonicTimer();
+            start >= 0 and stop >= start
+        }"},
+        &Value::Bool(true),
+    );
+}
+
+#[test]
+fn elapsed_seconds() {
+    check_intrinsic_value(
+        "",
+        "Microsoft.Quantum.Diagnostics.ElapsedSeconds(500_000_000, 2_000_000_000)",
+        &Value::Double(1.5),
+    );
+}
+
 #[test]
 fn truncate() {
     check_intrinsic_value("", "Microsoft.Quantum.Math.Truncate(3.1)", &Value::Int(3));
@@ -20,9 +20,12 @@ mod tests;
 pub mod backend;
 pub mod debug;
 mod error;
+pub mod hooks;
+pub mod interrupt;
 mod intrinsic;
 pub mod output;
 pub mod state;
+pub mod trace;
 pub mod val;
 
 use crate::val::{
@@ -31,6 +34,8 @@ use crate::val::{
 use backend::Backend;
 use debug::{CallStack, Frame};
 pub use error::PackageSpan;
+use hooks::{CheckpointHook, StmtHooks};
+use interrupt::InterruptHandle;
 use miette::Diagnostic;
 use num_bigint::BigInt;
 use output::Receiver;
@@ -43,16 +48,18 @@ use qsc_fir::fir::{
 use qsc_fir::ty::Ty;
 use qsc_lowerer::map_fir_package_to_hir;
 use rand::{rngs::StdRng, SeedableRng};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::ops;
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fmt::{self, Display, Formatter},
     iter,
     ops::Neg,
     rc::Rc,
 };
 use thiserror::Error;
+use trace::CallTrace;
 use val::update_functor_app;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -114,6 +121,13 @@ pub enum Error {
     #[diagnostic(code("Qsc.Eval.RangeStepZero"))]
     RangeStepZero(#[label("invalid range")] PackageSpan),
 
+    #[error("qubit limit of {0} exceeded")]
+    #[diagnostic(help(
+        "the host does not allow this program to have more than {0} qubits allocated at once"
+    ))]
+    #[diagnostic(code("Qsc.Eval.QubitLimitExceeded"))]
+    QubitLimitExceeded(usize, #[label("cannot allocate another qubit")] PackageSpan),
+
     #[error("Qubit{0} released while not in |0⟩ state")]
     #[diagnostic(help("qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state"))]
     #[diagnostic(code("Qsc.Eval.ReleasedQubitNotZero"))]
@@ -143,6 +157,18 @@ pub enum Error {
     #[error("program failed: {0}")]
     #[diagnostic(code("Qsc.Eval.UserFail"))]
     UserFail(String, #[label("explicit fail")] PackageSpan),
+
+    #[error("evaluation interrupted")]
+    #[diagnostic(code("Qsc.Eval.Interrupted"))]
+    Interrupted(#[label("interrupted here")] PackageSpan),
+
+    #[error("callable is already being counted")]
+    #[diagnostic(code("Qsc.Eval.CallableAlreadyCounted"))]
+    CallableAlreadyCounted(#[label("counting already started for this callable")] PackageSpan),
+
+    #[error("callable is not being counted")]
+    #[diagnostic(code("Qsc.Eval.CallableNotCounted"))]
+    CallableNotCounted(#[label("counting was not started for this callable")] PackageSpan),
 }
 
 impl Error {
@@ -161,6 +187,7 @@ impl Error {
             | Error::OutputFail(span)
             | Error::QubitUniqueness(span)
             | Error::QubitsNotSeparable(span)
+            | Error::QubitLimitExceeded(_, span)
             | Error::RangeStepZero(span)
             | Error::ReleasedQubitNotZero(_, span)
             | Error::ResultComparisonUnsupported(span)
@@ -168,6 +195,9 @@ impl Error {
             | Error::UnknownIntrinsic(_, span)
             | Error::UnsupportedIntrinsicType(_, span)
             | Error::UserFail(_, span)
+            | Error::Interrupted(span)
+            | Error::CallableAlreadyCounted(span)
+            | Error::CallableNotCounted(span)
             | Error::InvalidArrayLength(_, span) => span,
         }
     }
@@ -233,8 +263,44 @@ pub fn eval(
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
 ) -> Result<Value, (Error, Vec<Frame>)> {
-    let mut state = State::new(package, exec_graph, seed);
-    let res = state.eval(globals, env, sim, receiver, &[], StepAction::Continue)?;
+    let scopes = env.len();
+    eval_to_end(
+        State::new(package, exec_graph, seed),
+        scopes,
+        globals,
+        env,
+        sim,
+        receiver,
+    )
+}
+
+/// Continues an evaluation restored with [`State::resume`] until it returns. If it fails, the
+/// environment is left with only its global scope.
+/// # Errors
+/// Returns the first error encountered during execution.
+/// # Panics
+/// On internal error where no result is returned.
+pub fn resume(
+    state: State,
+    globals: &impl PackageStoreLookup,
+    env: &mut Env,
+    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    receiver: &mut impl Receiver,
+) -> Result<Value, (Error, Vec<Frame>)> {
+    eval_to_end(state, 1, globals, env, sim, receiver)
+}
+
+fn eval_to_end(
+    mut state: State,
+    scopes: usize,
+    globals: &impl PackageStoreLookup,
+    env: &mut Env,
+    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    receiver: &mut impl Receiver,
+) -> Result<Value, (Error, Vec<Frame>)> {
+    let res = state
+        .eval(globals, env, sim, receiver, &[], StepAction::Continue)
+        .inspect_err(|_| env.leave_scopes_above(scopes))?;
     let StepResult::Return(value) = res else {
         panic!("eval should always return a value");
     };
@@ -322,24 +388,56 @@ impl Range {
     }
 }
 
-pub struct Env(Vec<Scope>);
+pub struct Env {
+    scopes: Vec<Scope>,
+    /// The call stack that allocated each live qubit, keyed by qubit id.
+    qubit_allocations: BTreeMap<usize, Vec<Frame>>,
+    /// The filter for calls that are traced to the receiver, if tracing is enabled.
+    call_trace: Option<CallTrace>,
+    /// The host callbacks invoked around each evaluated statement, if any.
+    stmt_hooks: Option<Rc<RefCell<dyn StmtHooks>>>,
+    /// The host callback that saves running evaluations, with the number of statements between
+    /// its invocations, if any.
+    checkpoint_hook: Option<(usize, Rc<RefCell<dyn CheckpointHook>>)>,
+    /// The number of statements started since the checkpoint hook was last invoked.
+    stmts_since_checkpoint: usize,
+    /// The flag that the host sets to interrupt evaluation.
+    interrupt: InterruptHandle,
+    /// The largest number of qubits that can be allocated at once, if limited.
+    qubit_limit: Option<usize>,
+    /// The number of calls so far to each callable specialization that is being counted.
+    call_counts: FxHashMap<(StoreItemId, FunctorApp), i64>,
+}
 
 impl Default for Env {
     #[must_use]
     fn default() -> Self {
         // Always create a global scope for top-level statements.
-        Self(vec![Scope::default()])
+        Self {
+            scopes: vec![Scope::default()],
+            qubit_allocations: BTreeMap::new(),
+            call_trace: None,
+            stmt_hooks: None,
+            checkpoint_hook: None,
+            stmts_since_checkpoint: 0,
+            interrupt: InterruptHandle::default(),
+            qubit_limit: None,
+            call_counts: FxHashMap::default(),
+        }
     }
 }
 
 impl Env {
     #[must_use]
     pub fn get(&self, id: LocalVarId) -> Option<&Variable> {
-        self.0.iter().rev().find_map(|scope| scope.bindings.get(id))
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(id))
     }
 
     fn get_mut(&mut self, id: LocalVarId) -> Option<&mut Variable> {
-        self.0
+        self.scopes
             .iter_mut()
             .rev()
             .find_map(|scope| scope.bindings.get_mut(id))
@@ -350,14 +448,14 @@ impl Env {
             frame_id,
             ..Default::default()
         };
-        self.0.push(scope);
+        self.scopes.push(scope);
     }
 
     pub fn leave_scope(&mut self) {
         // Only pop the scope if there is more than one scope in the stack,
         // because the global/top-level scope cannot be exited.
-        if self.0.len() > 1 {
-            self.0
+        if self.scopes.len() > 1 {
+            self.scopes
                 .pop()
                 .expect("scope should have more than one entry.");
         }
@@ -365,7 +463,7 @@ impl Env {
 
     pub fn leave_current_frame(&mut self) {
         let current_frame_id = self
-            .0
+            .scopes
             .last()
             .expect("should be at least one scope")
             .frame_id;
@@ -373,11 +471,12 @@ impl Env {
             // Do not remove the global scope.
             return;
         }
-        self.0.retain(|scope| scope.frame_id != current_frame_id);
+        self.scopes
+            .retain(|scope| scope.frame_id != current_frame_id);
     }
 
     pub fn bind_variable_in_top_frame(&mut self, local_var_id: LocalVarId, var: Variable) {
-        let Some(scope) = self.0.last_mut() else {
+        let Some(scope) = self.scopes.last_mut() else {
             panic!("no frames in scope");
         };
 
@@ -386,7 +485,7 @@ impl Env {
 
     #[must_use]
     pub fn get_variables_in_top_frame(&self) -> Vec<VariableInfo> {
-        if let Some(scope) = self.0.last() {
+        if let Some(scope) = self.scopes.last() {
             self.get_variables_in_frame(scope.frame_id)
         } else {
             vec![]
@@ -396,7 +495,7 @@ impl Env {
     #[must_use]
     pub fn get_variables_in_frame(&self, frame_id: usize) -> Vec<VariableInfo> {
         let candidate_scopes: Vec<_> = self
-            .0
+            .scopes
             .iter()
             .filter(|scope| scope.frame_id == frame_id)
             .map(|scope| scope.bindings.iter())
@@ -418,10 +517,145 @@ impl Env {
         variables_by_scope.into_iter().flatten().collect::<Vec<_>>()
     }
 
+    /// Returns the variables bound in the global scope, which holds the bindings made by
+    /// top-level statements.
+    pub fn global_variables(&self) -> impl Iterator<Item = (LocalVarId, &Variable)> {
+        self.scopes
+            .first()
+            .expect("global scope should exist")
+            .bindings
+            .iter()
+    }
+
+    /// Returns the live qubits along with the call stacks that allocated them, in order of
+    /// qubit id.
+    pub fn qubit_allocations(&self) -> impl Iterator<Item = (usize, &[Frame])> {
+        self.qubit_allocations
+            .iter()
+            .map(|(id, frames)| (*id, frames.as_slice()))
+    }
+
+    /// Replaces the recorded qubit allocations, such as when restoring a saved session whose
+    /// qubits were allocated by an earlier evaluation.
+    pub fn set_qubit_allocations(
+        &mut self,
+        allocations: impl IntoIterator<Item = (usize, Vec<Frame>)>,
+    ) {
+        self.qubit_allocations = allocations.into_iter().collect();
+    }
+
+    fn track_qubit_allocation(&mut self, qubit: usize, frames: Vec<Frame>) {
+        self.qubit_allocations.insert(qubit, frames);
+    }
+
+    fn track_qubit_release(&mut self, qubit: usize) {
+        self.qubit_allocations.remove(&qubit);
+    }
+
+    /// Enables tracing of the calls matched by the given filter, or disables tracing when `None`.
+    pub fn set_call_trace(&mut self, call_trace: Option<CallTrace>) {
+        self.call_trace = call_trace;
+    }
+
+    #[must_use]
+    pub fn call_trace(&self) -> Option<&CallTrace> {
+        self.call_trace.as_ref()
+    }
+
+    /// Sets the host callbacks invoked around each evaluated statement, or removes them when
+    /// `None`.
+    pub fn set_stmt_hooks(&mut self, stmt_hooks: Option<Rc<RefCell<dyn StmtHooks>>>) {
+        self.stmt_hooks = stmt_hooks;
+    }
+
+    #[must_use]
+    pub fn stmt_hooks(&self) -> Option<&Rc<RefCell<dyn StmtHooks>>> {
+        self.stmt_hooks.as_ref()
+    }
+
+    /// Sets the host callback that saves running evaluations and the number of statements to
+    /// evaluate between its invocations, or removes it when `None`.
+    pub fn set_checkpoint_hook(
+        &mut self,
+        checkpoint_hook: Option<(usize, Rc<RefCell<dyn CheckpointHook>>)>,
+    ) {
+        self.checkpoint_hook = checkpoint_hook.map(|(interval, hook)| (interval.max(1), hook));
+        self.stmts_since_checkpoint = 0;
+    }
+
+    /// Counts a started statement, returning the checkpoint hook if it is due to be invoked.
+    fn checkpoint_due(&mut self) -> Option<Rc<RefCell<dyn CheckpointHook>>> {
+        let (interval, hook) = self.checkpoint_hook.as_ref()?;
+        self.stmts_since_checkpoint += 1;
+        if self.stmts_since_checkpoint < *interval {
+            return None;
+        }
+        self.stmts_since_checkpoint = 0;
+        Some(hook.clone())
+    }
+
+    /// Sets the handle that the host triggers to interrupt evaluation in this environment.
+    pub fn set_interrupt_handle(&mut self, interrupt: InterruptHandle) {
+        self.interrupt = interrupt;
+    }
+
+    /// The handle that interrupts evaluation in this environment.
+    #[must_use]
+    pub fn interrupt_handle(&self) -> &InterruptHandle {
+        &self.interrupt
+    }
+
+    /// Limits the number of qubits that can be allocated at once, or removes the limit when
+    /// `None`. Allocating a qubit past the limit fails with [`Error::QubitLimitExceeded`].
+    pub fn set_qubit_limit(&mut self, qubit_limit: Option<usize>) {
+        self.qubit_limit = qubit_limit;
+    }
+
+    #[must_use]
+    pub fn qubit_limit(&self) -> Option<usize> {
+        self.qubit_limit
+    }
+
+    /// Starts counting the calls to the given specialization of a callable.
+    fn start_counting(
+        &mut self,
+        callable: (StoreItemId, FunctorApp),
+        span: PackageSpan,
+    ) -> Result<(), Error> {
+        if self.call_counts.insert(callable, 0).is_some() {
+            return Err(Error::CallableAlreadyCounted(span));
+        }
+        Ok(())
+    }
+
+    /// Stops counting the calls to the given specialization of a callable, returning the number
+    /// of calls since counting started.
+    fn stop_counting(
+        &mut self,
+        callable: (StoreItemId, FunctorApp),
+        span: PackageSpan,
+    ) -> Result<i64, Error> {
+        self.call_counts
+            .remove(&callable)
+            .ok_or(Error::CallableNotCounted(span))
+    }
+
+    fn count_call(&mut self, callable: (StoreItemId, FunctorApp)) {
+        if let Some(count) = self.call_counts.get_mut(&callable) {
+            *count += 1;
+        }
+    }
+
+    /// Leaves every scope entered after the environment had the given number of scopes, such as
+    /// the scopes of the callables that were running when evaluation stopped with an error.
+    pub fn leave_scopes_above(&mut self, len: usize) {
+        self.scopes.truncate(len.max(1));
+    }
+
     #[allow(clippy::len_without_is_empty)]
     #[must_use]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.scopes.len()
     }
 
     pub fn update_variable_in_top_frame(&mut self, local_var_id: LocalVarId, value: Value) {
@@ -438,6 +672,25 @@ struct Scope {
     frame_id: usize,
 }
 
+/// An evaluation paused at the start of a statement, which can be continued with
+/// [`State::resume`]. The execution graphs of the frames are not saved, since they can be found
+/// again from the callables that the frames belong to.
+#[derive(Clone, Debug, Default)]
+pub struct SavedState {
+    /// The index of the next node to evaluate in each execution graph on the stack, starting
+    /// with the graph that the evaluation was started with.
+    pub idx: Vec<u32>,
+    /// The value register.
+    pub val_register: Option<Value>,
+    /// The values stored for each execution graph on the stack.
+    pub val_stack: Vec<Vec<Value>>,
+    /// The frames of the callables being evaluated, outermost first.
+    pub frames: Vec<Frame>,
+    /// The scopes entered above the global scope, outermost first, with the id of the frame
+    /// each one belongs to and its bindings.
+    pub scopes: Vec<(usize, Vec<(LocalVarId, Variable)>)>,
+}
+
 pub struct State {
     exec_graph_stack: Vec<Rc<[ExecGraphNode]>>,
     idx: u32,
@@ -449,6 +702,17 @@ pub struct State {
     call_stack: CallStack,
     current_span: Span,
     rng: RefCell<StdRng>,
+    /// The statements reported to the statement hooks that have not yet finished, innermost last.
+    open_stmts: Vec<OpenStmt>,
+}
+
+/// A statement that has started evaluating, tracked so that its end can be reported.
+struct OpenStmt {
+    /// The depth of the execution graph stack that the statement belongs to.
+    depth: usize,
+    /// The index in the execution graph just past the end of the statement.
+    end: usize,
+    span: PackageSpan,
 }
 
 impl State {
@@ -473,9 +737,96 @@ impl State {
             call_stack: CallStack::default(),
             current_span: Span::default(),
             rng,
+            open_stmts: Vec::new(),
         }
     }
 
+    /// Saves the evaluation, which must be paused at the start of a statement, along with the
+    /// scopes it entered in the environment.
+    #[must_use]
+    pub fn save(&self, env: &Env) -> SavedState {
+        SavedState {
+            idx: self
+                .idx_stack
+                .iter()
+                .copied()
+                .chain(iter::once(self.idx))
+                .collect(),
+            val_register: self.val_register.clone(),
+            val_stack: self.val_stack.clone(),
+            frames: self.call_stack.clone().into_frames(),
+            scopes: env
+                .scopes
+                .iter()
+                .skip(1)
+                .map(|scope| {
+                    (
+                        scope.frame_id,
+                        scope
+                            .bindings
+                            .iter()
+                            .map(|(id, var)| (id, var.clone()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Continues an evaluation saved with [`State::save`] that was started with the given
+    /// execution graph, entering its saved scopes above the global scope of the environment.
+    /// Returns `None` if the saved frames do not match the callables in `globals`, such as when
+    /// the program has changed since the evaluation was saved.
+    #[must_use]
+    pub fn resume(
+        package: PackageId,
+        exec_graph: Rc<[ExecGraphNode]>,
+        saved: SavedState,
+        globals: &impl PackageStoreLookup,
+        env: &mut Env,
+        classical_seed: Option<u64>,
+    ) -> Option<Self> {
+        let levels = saved.frames.len() + 1;
+        if saved.idx.len() != levels || saved.val_stack.len() != levels {
+            return None;
+        }
+
+        let mut state = Self::new(package, exec_graph, classical_seed);
+        for frame in saved.frames {
+            let Some(Global::Callable(callee)) = globals.get_global(frame.id) else {
+                return None;
+            };
+            let spec_decl = match &callee.implementation {
+                CallableImpl::Intrinsic => return None,
+                CallableImpl::Spec(spec_impl) => match spec_from_functor_app(frame.functor) {
+                    Spec::Body => Some(&spec_impl.body),
+                    Spec::Adj => spec_impl.adj.as_ref(),
+                    Spec::Ctl => spec_impl.ctl.as_ref(),
+                    Spec::CtlAdj => spec_impl.ctl_adj.as_ref(),
+                }?,
+                CallableImpl::SimulatableIntrinsic(spec_decl) => spec_decl,
+            };
+            state.exec_graph_stack.push(spec_decl.exec_graph.clone());
+            state.package = frame.id.package;
+            state.call_stack.push_frame(frame);
+        }
+        let mut idx = saved.idx;
+        state.idx = idx
+            .pop()
+            .expect("saved state should have an index for each graph");
+        state.idx_stack = idx;
+        state.val_register = saved.val_register;
+        state.val_stack = saved.val_stack;
+
+        env.leave_scopes_above(1);
+        env.scopes
+            .extend(saved.scopes.into_iter().map(|(frame_id, bindings)| Scope {
+                bindings: bindings.into_iter().collect(),
+                frame_id,
+            }));
+        Some(state)
+    }
+
     fn push_frame(
         &mut self,
         exec_graph: Rc<[ExecGraphNode]>,
@@ -504,6 +855,68 @@ impl State {
         self.exec_graph_stack.pop();
     }
 
+    /// Reports the start of a statement to the statement hooks, if any, remembering where the
+    /// statement ends in the current execution graph.
+    fn start_stmt(&mut self, env: &Env, end: usize) {
+        let Some(hooks) = env.stmt_hooks() else {
+            return;
+        };
+        if self.current_span == Span::default() {
+            // Generated code has no span and is not reported.
+            return;
+        }
+        let span = self.to_global_span(self.current_span);
+        hooks.borrow_mut().before_stmt(span, env);
+        self.open_stmts.push(OpenStmt {
+            depth: self.exec_graph_stack.len(),
+            end,
+            span,
+        });
+    }
+
+    /// Reports the end of each open statement in the current execution graph that execution has
+    /// moved past, or of every open statement in the graph when `leaving` it.
+    fn finish_stmts(&mut self, env: &Env, leaving: bool) {
+        let Some(hooks) = env.stmt_hooks() else {
+            return;
+        };
+        let depth = self.exec_graph_stack.len();
+        while let Some(stmt) = self.open_stmts.last() {
+            if stmt.depth < depth || (!leaving && (self.idx as usize) < stmt.end) {
+                break;
+            }
+            let span = stmt.span;
+            self.open_stmts.pop();
+            hooks.borrow_mut().after_stmt(span, env);
+        }
+    }
+
+    /// Sends the exit message for the current frame to the receiver if its callable is traced.
+    fn trace_exit(
+        &self,
+        env: &Env,
+        globals: &impl PackageStoreLookup,
+        out: &mut impl Receiver,
+    ) -> Result<(), Error> {
+        let (Some(call_trace), Some(frame)) = (env.call_trace(), self.call_stack.last()) else {
+            return Ok(());
+        };
+        let Some(Global::Callable(callee)) = globals.get_global(frame.id) else {
+            return Ok(());
+        };
+        if !call_trace.matches(&callee.name.name) {
+            return Ok(());
+        }
+        let msg = call_trace.exit_message(
+            self.call_stack.len() - 1,
+            &callee.name.name,
+            frame.functor,
+            self.val_register.as_ref(),
+        );
+        out.trace(&msg)
+            .map_err(|_| Error::OutputFail(self.to_global_span(self.current_span)))
+    }
+
     fn push_scope(&mut self, env: &mut Env) {
         env.push_scope(self.call_stack.len());
     }
@@ -566,6 +979,7 @@ impl State {
     ) -> Result<StepResult, (Error, Vec<Frame>)> {
         let current_frame = self.call_stack.len();
         while !self.exec_graph_stack.is_empty() {
+            self.finish_stmts(env, false);
             let exec_graph = self
                 .exec_graph_stack
                 .last()
@@ -583,8 +997,18 @@ impl State {
                     continue;
                 }
                 Some(ExecGraphNode::Stmt(stmt)) => {
+                    if let Some(hook) = env.checkpoint_due() {
+                        // Saving before the statement starts lets a resumed evaluation start it
+                        // again like any other.
+                        let saved = self.save(env);
+                        hook.borrow_mut()
+                            .checkpoint(saved, env, sim.capture_quantum_state());
+                    }
                     self.idx += 1;
-                    self.current_span = globals.get_stmt((self.package, *stmt).into()).span;
+                    let stmt_decl = globals.get_stmt((self.package, *stmt).into());
+                    self.current_span = stmt_decl.span;
+                    self.check_for_interrupt(env)?;
+                    self.start_stmt(env, stmt_decl.exec_graph_range.end);
 
                     match self.check_for_break(breakpoints, *stmt, step, current_frame) {
                         Some(value) => value,
@@ -592,6 +1016,9 @@ impl State {
                     }
                 }
                 Some(ExecGraphNode::Jump(idx)) => {
+                    // Loops jump back to their condition on every iteration, even when their
+                    // body has no statements.
+                    self.check_for_interrupt(env)?;
                     self.idx = *idx;
                     continue;
                 }
@@ -624,11 +1051,17 @@ impl State {
                     continue;
                 }
                 Some(ExecGraphNode::Ret) => {
+                    self.trace_exit(env, globals, out)
+                        .map_err(|e| (e, self.get_stack_frames()))?;
+                    self.finish_stmts(env, true);
                     self.leave_frame();
                     env.leave_scope();
                     continue;
                 }
                 Some(ExecGraphNode::RetFrame) => {
+                    self.trace_exit(env, globals, out)
+                        .map_err(|e| (e, self.get_stack_frames()))?;
+                    self.finish_stmts(env, true);
                     self.leave_frame();
                     env.leave_current_frame();
                     continue;
@@ -648,6 +1081,7 @@ impl State {
                     // usually indicating the partial execution of a single sub-expression.
                     // This means we should pop the execution graph but not the current environment scope,
                     // so bound variables are still accessible after completion.
+                    self.finish_stmts(env, true);
                     self.exec_graph_stack.pop();
                     assert!(self.exec_graph_stack.is_empty());
                     continue;
@@ -664,6 +1098,18 @@ impl State {
         Ok(StepResult::Return(self.get_result()))
     }
 
+    /// Stops evaluation if the host has requested an interruption.
+    fn check_for_interrupt(&self, env: &Env) -> Result<(), (Error, Vec<Frame>)> {
+        if env.interrupt.is_interrupted() {
+            Err((
+                Error::Interrupted(self.to_global_span(self.current_span)),
+                self.get_stack_frames(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn check_for_break(
         &self,
         breakpoints: &[StmtId],
@@ -971,6 +1417,20 @@ impl State {
         };
 
         let callee_span = self.to_global_span(callee.span);
+        env.count_call((callee_id, functor));
+
+        if let Some(call_trace) = env.call_trace() {
+            if call_trace.matches(&callee.name.name) {
+                let msg = call_trace.enter_message(
+                    self.call_stack.len(),
+                    &callee.name.name,
+                    functor,
+                    &arg,
+                );
+                out.trace(&msg)
+                    .map_err(|_| Error::OutputFail(self.to_global_span(callable_span)))?;
+            }
+        }
 
         let spec = spec_from_functor_app(functor);
         match &callee.implementation {
@@ -978,22 +1438,56 @@ impl State {
                 self.push_frame(Vec::new().into(), callee_id, functor);
 
                 let name = &callee.name.name;
-                let val = intrinsic::call(
-                    name,
-                    callee_span,
-                    arg,
-                    arg_span,
-                    sim,
-                    &mut self.rng.borrow_mut(),
-                    out,
-                )?;
+                let qubit_arg = match &arg {
+                    Value::Qubit(q) => Some(q.0),
+                    _ => None,
+                };
+                if name.as_ref() == "__quantum__rt__qubit_allocate" {
+                    if let Some(limit) = env
+                        .qubit_limit
+                        .filter(|limit| env.qubit_allocations.len() >= *limit)
+                    {
+                        return Err(Error::QubitLimitExceeded(limit, arg_span));
+                    }
+                }
+                let val = match name.as_ref() {
+                    "StartCountingOperation" | "StartCountingFunction" => {
+                        env.start_counting(counted_callable(&arg), arg_span)?;
+                        Value::unit()
+                    }
+                    "StopCountingOperation" | "StopCountingFunction" => {
+                        Value::Int(env.stop_counting(counted_callable(&arg), arg_span)?)
+                    }
+                    _ => intrinsic::call(
+                        name,
+                        callee_span,
+                        arg,
+                        arg_span,
+                        sim,
+                        &mut self.rng.borrow_mut(),
+                        out,
+                    )?,
+                };
+                if let Some(message) = sim.take_failure() {
+                    return Err(Error::IntrinsicFail(name.to_string(), message, callee_span));
+                }
                 if val == Value::unit() && callee.output != Ty::UNIT {
                     return Err(Error::UnsupportedIntrinsicType(
                         callee.name.name.to_string(),
                         callee_span,
                     ));
                 }
+                // Remember where each qubit was allocated so that leaked qubits can be traced
+                // back to their `use` statements.
+                match (name.as_ref(), &val, qubit_arg) {
+                    ("__quantum__rt__qubit_allocate", Value::Qubit(q), _) => {
+                        env.track_qubit_allocation(q.0, self.get_stack_frames());
+                    }
+                    ("__quantum__rt__qubit_release", _, Some(q)) => env.track_qubit_release(q),
+                    _ => {}
+                }
                 self.set_val_register(val);
+                self.trace_exit(env, globals, out)?;
                 self.leave_frame();
                 Ok(())
             }
@@ -1503,6 +1997,16 @@ fn spec_from_functor_app(functor: FunctorApp) -> Spec {
     }
 }
 
+/// The callable specialization that a counting intrinsic is given, where a closure stands for
+/// the callable that it wraps.
+fn counted_callable(arg: &Value) -> (StoreItemId, FunctorApp) {
+    match arg {
+        Value::Closure(inner) => (inner.id, inner.functor),
+        Value::Global(id, functor) => (*id, *functor),
+        _ => panic!("value is not callable"),
+    }
+}
+
 pub fn resolve_closure(
     env: &Env,
     package: PackageId,
@@ -1,7 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::io::{Cursor, Write};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{Cursor, Write},
+};
 
 use crate::state::{fmt_complex, format_state_id};
 use num_bigint::BigUint;
@@ -20,6 +23,23 @@ pub trait Receiver {
     /// # Errors
     /// This will return an error if handling the output fails.
     fn message(&mut self, msg: &str) -> Result<(), Error>;
+
+    /// Receive the global phase, in radians, that the backend tracked separately from the state
+    /// most recently sent to `state`. The phase is already included in the amplitudes, so
+    /// receivers that only display the state can ignore it.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn global_phase(&mut self, _phase: f64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Receive a call trace message, produced when evaluation enters or exits a traced callable.
+    /// By default, trace messages are handled as generic messages.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn trace(&mut self, msg: &str) -> Result<(), Error> {
+        self.message(msg)
+    }
 }
 
 pub struct GenericReceiver<'a> {
@@ -90,3 +110,210 @@ impl<'a> Receiver for CursorReceiver<'a> {
         writeln!(self.cursor, "{msg}").map_err(|_| Error)
     }
 }
+
+/// The kinds of output that a [`RoutingReceiver`] can route.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutputKind {
+    /// Messages produced by the program, such as through `Message`.
+    Message,
+    /// Quantum state dumps, along with the global phase that accompanies them.
+    State,
+    /// Diagnostic output about the evaluation itself, such as call trace messages.
+    Diagnostic,
+}
+
+struct Route {
+    name: String,
+    kinds: Vec<OutputKind>,
+    receiver: Box<dyn Receiver>,
+}
+
+/// A receiver that forwards each kind of output to every named receiver registered for that kind.
+/// Output of a kind with no registered receiver is dropped.
+#[derive(Default)]
+pub struct RoutingReceiver {
+    routes: Vec<Route>,
+}
+
+impl RoutingReceiver {
+    /// Registers a receiver for the given kinds of output under the given name, returning the
+    /// receiver previously registered under that name, if any.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        kinds: &[OutputKind],
+        receiver: Box<dyn Receiver>,
+    ) -> Option<Box<dyn Receiver>> {
+        let name = name.into();
+        let previous = self.remove(&name);
+        self.routes.push(Route {
+            name,
+            kinds: kinds.to_vec(),
+            receiver,
+        });
+        previous
+    }
+
+    /// Unregisters the receiver with the given name, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Receiver>> {
+        let index = self.routes.iter().position(|route| route.name == name)?;
+        Some(self.routes.remove(index).receiver)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Sends output to each receiver registered for the given kind. Every receiver is sent the
+    /// output even if an earlier one fails, and the failure is reported afterwards.
+    fn send(
+        &mut self,
+        kind: OutputKind,
+        mut send: impl FnMut(&mut dyn Receiver) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut result = Ok(());
+        for route in self
+            .routes
+            .iter_mut()
+            .filter(|route| route.kinds.contains(&kind))
+        {
+            result = result.and(send(route.receiver.as_mut()));
+        }
+        result
+    }
+}
+
+impl Receiver for RoutingReceiver {
+    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error> {
+        self.send(OutputKind::State, |receiver| {
+            receiver.state(state.clone(), qubit_count)
+        })
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), Error> {
+        self.send(OutputKind::Message, |receiver| receiver.message(msg))
+    }
+
+    fn global_phase(&mut self, phase: f64) -> Result<(), Error> {
+        self.send(OutputKind::State, |receiver| receiver.global_phase(phase))
+    }
+
+    fn trace(&mut self, msg: &str) -> Result<(), Error> {
+        self.send(OutputKind::Diagnostic, |receiver| receiver.trace(msg))
+    }
+}
+
+/// Limits on the output of a single evaluation, so that a program printing in a long loop cannot
+/// flood the host. Every message, trace message and state dump counts as one event, and the text
+/// of messages and trace messages counts towards the bytes. `None` means no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutputLimit {
+    pub max_bytes: Option<usize>,
+    pub max_events: Option<usize>,
+}
+
+/// A notice that output was truncated because it exceeded an [`OutputLimit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputLimitExceeded {
+    pub limit: OutputLimit,
+    /// The number of events forwarded before the limit was reached.
+    pub forwarded_events: usize,
+    /// The number of events dropped after the limit was reached.
+    pub dropped_events: usize,
+}
+
+impl Display for OutputLimitExceeded {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "output limit exceeded: {} further output event(s) were dropped",
+            self.dropped_events
+        )
+    }
+}
+
+/// A receiver that forwards output to another receiver until an [`OutputLimit`] is reached, and
+/// drops the output that follows. The event that would exceed the limit is dropped whole, rather
+/// than cut off partway.
+pub struct LimitedReceiver<'a, R: Receiver> {
+    receiver: &'a mut R,
+    limit: OutputLimit,
+    bytes: usize,
+    events: usize,
+    dropped_events: usize,
+}
+
+impl<'a, R: Receiver> LimitedReceiver<'a, R> {
+    pub fn new(receiver: &'a mut R, limit: OutputLimit) -> Self {
+        Self {
+            receiver,
+            limit,
+            bytes: 0,
+            events: 0,
+            dropped_events: 0,
+        }
+    }
+
+    /// The notice for the output that was dropped, or `None` if all output was forwarded.
+    #[must_use]
+    pub fn exceeded(&self) -> Option<OutputLimitExceeded> {
+        (self.dropped_events > 0).then_some(OutputLimitExceeded {
+            limit: self.limit,
+            forwarded_events: self.events,
+            dropped_events: self.dropped_events,
+        })
+    }
+
+    /// Counts an event of the given size, returning whether it is within the limit.
+    fn admit(&mut self, bytes: usize) -> bool {
+        let within = self.dropped_events == 0
+            && !self.limit.max_events.is_some_and(|max| self.events >= max)
+            && !self
+                .limit
+                .max_bytes
+                .is_some_and(|max| self.bytes + bytes > max);
+        if within {
+            self.events += 1;
+            self.bytes += bytes;
+        } else {
+            self.dropped_events += 1;
+        }
+        within
+    }
+}
+
+impl<R: Receiver> Receiver for LimitedReceiver<'_, R> {
+    fn state(&mut self, state: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Result<(), Error> {
+        if self.admit(0) {
+            self.receiver.state(state, qubit_count)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), Error> {
+        if self.admit(msg.len()) {
+            self.receiver.message(msg)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn global_phase(&mut self, phase: f64) -> Result<(), Error> {
+        // The phase belongs to the state dump before it, so it is only dropped along with it.
+        if self.dropped_events == 0 {
+            self.receiver.global_phase(phase)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn trace(&mut self, msg: &str) -> Result<(), Error> {
+        if self.admit(msg.len()) {
+            self.receiver.trace(msg)
+        } else {
+            Ok(())
+        }
+    }
+}
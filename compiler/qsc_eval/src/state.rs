@@ -6,6 +6,7 @@ mod tests;
 
 use num_bigint::BigUint;
 use num_complex::{Complex, Complex64};
+use rustc_hash::FxHashMap;
 use std::fmt::Write;
 
 #[must_use]
@@ -39,6 +40,128 @@ pub fn fmt_basis_state_label(id: &BigUint, qubit_count: usize) -> String {
     format!("{:0>qubit_count$}", id.to_str_radix(2))
 }
 
+/// Computes the fidelity `|⟨a|b⟩|²` between two pure states given as lists of basis state
+/// indices and amplitudes over the same qubits, as returned by `capture_quantum_state`. The
+/// states are normalized first, so the result is between 0 and 1. An empty state has fidelity 0
+/// with any other state.
+#[must_use]
+pub fn state_fidelity(a: &[(BigUint, Complex64)], b: &[(BigUint, Complex64)]) -> f64 {
+    let norm = |state: &[(BigUint, Complex64)]| -> f64 {
+        state.iter().map(|(_, amp)| amp.norm_sqr()).sum::<f64>()
+    };
+    let (norm_a, norm_b) = (norm(a), norm(b));
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    let b = b
+        .iter()
+        .map(|(index, amp)| (index, *amp))
+        .collect::<FxHashMap<_, _>>();
+    let overlap: Complex64 = a
+        .iter()
+        .filter_map(|(index, amp)| b.get(index).map(|other| amp.conj() * other))
+        .sum();
+    overlap.norm_sqr() / (norm_a * norm_b)
+}
+
+/// A snapshot of the simulator's state, for hosts that render it themselves, such as in a
+/// histogram or on Bloch spheres, rather than through the text of a `DumpMachine` call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantumState {
+    /// The basis states with nonzero amplitudes as pairs of index and amplitude. Bit
+    /// `qubit_count - 1 - k` of an index holds the value of qubit `k`, so that the labels from
+    /// [`format_state_id`] show the qubits in order of allocation.
+    pub amplitudes: Vec<(BigUint, Complex64)>,
+    /// The number of qubits the state is over.
+    pub qubit_count: usize,
+}
+
+impl QuantumState {
+    #[must_use]
+    pub fn new(amplitudes: Vec<(BigUint, Complex64)>, qubit_count: usize) -> Self {
+        Self {
+            amplitudes,
+            qubit_count,
+        }
+    }
+
+    fn norm_sqr(&self) -> f64 {
+        self.amplitudes.iter().map(|(_, amp)| amp.norm_sqr()).sum()
+    }
+
+    /// The probability of measuring each basis state in the Z basis, normalized to sum to 1.
+    #[must_use]
+    pub fn probabilities(&self) -> Vec<(BigUint, f64)> {
+        let norm = self.norm_sqr();
+        self.amplitudes
+            .iter()
+            .map(|(index, amp)| (index.clone(), amp.norm_sqr() / norm))
+            .collect()
+    }
+
+    /// The probability of measuring the qubit as `One` in the Z basis.
+    /// # Panics
+    /// If the qubit is not in the state.
+    #[must_use]
+    pub fn qubit_probability(&self, qubit: usize) -> f64 {
+        let bit = self.bit(qubit);
+        let one = self
+            .amplitudes
+            .iter()
+            .filter(|(index, _)| index.bit(bit))
+            .map(|(_, amp)| amp.norm_sqr())
+            .sum::<f64>();
+        one / self.norm_sqr()
+    }
+
+    /// The Bloch vector `(x, y, z)` of the qubit's reduced state. Its length is 1 when the qubit
+    /// is in a pure state and shorter when it is entangled with other qubits.
+    /// # Panics
+    /// If the qubit is not in the state.
+    #[must_use]
+    pub fn bloch_vector(&self, qubit: usize) -> (f64, f64, f64) {
+        let bit = self.bit(qubit);
+        let norm = self.norm_sqr();
+        let amplitudes = self
+            .amplitudes
+            .iter()
+            .map(|(index, amp)| (index, *amp))
+            .collect::<FxHashMap<_, _>>();
+
+        // The off-diagonal element ⟨0|ρ|1⟩ and the diagonal difference ⟨0|ρ|0⟩ - ⟨1|ρ|1⟩ of the
+        // reduced density matrix.
+        let mut coherence = Complex64::new(0.0, 0.0);
+        let mut z = 0.0;
+        for (index, amp) in &self.amplitudes {
+            if index.bit(bit) {
+                z -= amp.norm_sqr();
+            } else {
+                z += amp.norm_sqr();
+                let mut flipped = index.clone();
+                flipped.set_bit(bit, true);
+                if let Some(other) = amplitudes.get(&flipped) {
+                    coherence += amp * other.conj();
+                }
+            }
+        }
+        (
+            2.0 * coherence.re / norm,
+            -2.0 * coherence.im / norm,
+            z / norm,
+        )
+    }
+
+    fn bit(&self, qubit: usize) -> u64 {
+        assert!(
+            qubit < self.qubit_count,
+            "qubit {qubit} should be one of the {} qubits in the state",
+            self.qubit_count
+        );
+        u64::try_from(self.qubit_count - 1 - qubit).expect("qubit index should fit in u64")
+    }
+}
+
 #[must_use]
 fn is_significant(x: f64) -> bool {
     x.abs() > 1e-9
@@ -10,9 +10,14 @@ use super::{
     RationalNumber, RealNumber, Term,
 };
 use crate::state::{is_fractional_part_significant, is_significant};
+use crate::state::{state_fidelity, QuantumState};
 use expect_test::{expect, Expect};
+use num_bigint::BigUint;
 use num_complex::Complex64;
-use std::{f64::consts::PI, time::Instant};
+use std::{
+    f64::consts::{FRAC_1_SQRT_2, PI},
+    time::Instant,
+};
 
 #[test]
 fn check_is_significant() {
@@ -707,3 +712,120 @@ fn check_get_latex_perf() {
         Instant::now().duration_since(start)
     );
 }
+
+#[test]
+fn fidelity_of_identical_states_is_one() {
+    let state = vec![
+        (BigUint::from(0_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        (BigUint::from(3_u32), Complex64::new(0.0, FRAC_1_SQRT_2)),
+    ];
+    assert!((state_fidelity(&state, &state) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn fidelity_ignores_global_phase() {
+    let a = vec![
+        (BigUint::from(0_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        (BigUint::from(1_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+    ];
+    let phase = Complex64::from_polar(1.0, 0.7);
+    let b = a
+        .iter()
+        .map(|(index, amp)| (index.clone(), amp * phase))
+        .collect::<Vec<_>>();
+    assert!((state_fidelity(&a, &b) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn fidelity_of_orthogonal_states_is_zero() {
+    let a = vec![(BigUint::from(0_u32), Complex64::new(1.0, 0.0))];
+    let b = vec![(BigUint::from(1_u32), Complex64::new(1.0, 0.0))];
+    assert!(state_fidelity(&a, &b).abs() < 1e-12);
+}
+
+#[test]
+fn fidelity_of_overlapping_states_is_normalized() {
+    let a = vec![(BigUint::from(0_u32), Complex64::new(2.0, 0.0))];
+    let b = vec![
+        (BigUint::from(0_u32), Complex64::new(1.0, 0.0)),
+        (BigUint::from(1_u32), Complex64::new(0.0, -1.0)),
+    ];
+    assert!((state_fidelity(&a, &b) - 0.5).abs() < 1e-12);
+}
+
+fn assert_close(actual: (f64, f64, f64), expected: (f64, f64, f64)) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-12
+            && (actual.1 - expected.1).abs() < 1e-12
+            && (actual.2 - expected.2).abs() < 1e-12,
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn quantum_state_probabilities_are_normalized() {
+    let state = QuantumState::new(
+        vec![
+            (BigUint::from(0_u32), Complex64::new(1.0, 0.0)),
+            (BigUint::from(3_u32), Complex64::new(0.0, 1.0)),
+        ],
+        2,
+    );
+    assert_eq!(
+        state.probabilities(),
+        [(BigUint::from(0_u32), 0.5), (BigUint::from(3_u32), 0.5)]
+    );
+    assert!((state.qubit_probability(0) - 0.5).abs() < 1e-12);
+    assert!((state.qubit_probability(1) - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn quantum_state_qubit_probability_follows_allocation_order() {
+    // |10⟩: qubit 0 is `One` and qubit 1 is `Zero`.
+    let state = QuantumState::new(vec![(BigUint::from(2_u32), Complex64::new(1.0, 0.0))], 2);
+    assert!((state.qubit_probability(0) - 1.0).abs() < 1e-12);
+    assert!(state.qubit_probability(1).abs() < 1e-12);
+    assert_close(state.bloch_vector(0), (0.0, 0.0, -1.0));
+    assert_close(state.bloch_vector(1), (0.0, 0.0, 1.0));
+}
+
+#[test]
+fn quantum_state_bloch_vectors_of_single_qubit_states() {
+    let plus = QuantumState::new(
+        vec![
+            (BigUint::from(0_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+            (BigUint::from(1_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        ],
+        1,
+    );
+    assert_close(plus.bloch_vector(0), (1.0, 0.0, 0.0));
+
+    let plus_i = QuantumState::new(
+        vec![
+            (BigUint::from(0_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+            (BigUint::from(1_u32), Complex64::new(0.0, FRAC_1_SQRT_2)),
+        ],
+        1,
+    );
+    assert_close(plus_i.bloch_vector(0), (0.0, 1.0, 0.0));
+}
+
+#[test]
+fn quantum_state_bloch_vector_of_entangled_qubit_is_shrunk() {
+    let bell = QuantumState::new(
+        vec![
+            (BigUint::from(0_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+            (BigUint::from(3_u32), Complex64::new(FRAC_1_SQRT_2, 0.0)),
+        ],
+        2,
+    );
+    assert_close(bell.bloch_vector(0), (0.0, 0.0, 0.0));
+    assert_close(bell.bloch_vector(1), (0.0, 0.0, 0.0));
+}
+
+#[test]
+#[should_panic(expected = "should be one of the 1 qubits")]
+fn quantum_state_rejects_missing_qubit() {
+    let state = QuantumState::new(vec![(BigUint::from(0_u32), Complex64::new(1.0, 0.0))], 1);
+    let _ = state.qubit_probability(1);
+}
@@ -4295,3 +4295,17 @@ fn partial_eval_stmt_function_calls_from_library() {
         &expect!["3"],
     );
 }
+
+#[test]
+fn sparse_sim_close_releases_state() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.h(q1);
+    sim.close();
+    let (_, qubit_count) = sim.capture_quantum_state();
+    assert_eq!(qubit_count, 0);
+    let q = sim.qubit_allocate();
+    assert!(sim.qubit_is_zero(q));
+}
@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::val::Value;
+use qsc_data_structures::functors::FunctorApp;
+use std::fmt::Write;
+
+/// The default number of characters of a rendered argument or return value that a trace message
+/// includes before the rest is elided.
+pub const DEFAULT_MAX_VALUE_LEN: usize = 80;
+
+/// Configures tracing of calls to callables whose names match one of a set of filters.
+///
+/// A filter matches a callable name exactly, unless it ends in `*`, in which case it matches any
+/// name that starts with the text before the `*`. The filter `*` matches every callable.
+/// Each traced call produces an `enter` message with the call arguments and an `exit` message with
+/// the returned value, which are sent to [`crate::output::Receiver::trace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallTrace {
+    filters: Vec<String>,
+    max_value_len: usize,
+}
+
+impl CallTrace {
+    #[must_use]
+    pub fn new(filters: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            filters: filters.into_iter().map(Into::into).collect(),
+            max_value_len: DEFAULT_MAX_VALUE_LEN,
+        }
+    }
+
+    /// Sets the number of characters of each rendered argument or return value to include in the
+    /// trace messages.
+    #[must_use]
+    pub fn with_max_value_len(mut self, max_value_len: usize) -> Self {
+        self.max_value_len = max_value_len;
+        self
+    }
+
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| match filter.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == filter,
+            })
+    }
+
+    pub(crate) fn enter_message(
+        &self,
+        depth: usize,
+        name: &str,
+        functor: FunctorApp,
+        arg: &Value,
+    ) -> String {
+        let mut msg = header(depth, "enter", name, functor);
+        let arg = self.render(arg);
+        if arg.starts_with('(') {
+            msg.push_str(&arg);
+        } else {
+            write!(msg, "({arg})").expect("writing to string should succeed");
+        }
+        msg
+    }
+
+    pub(crate) fn exit_message(
+        &self,
+        depth: usize,
+        name: &str,
+        functor: FunctorApp,
+        ret: Option<&Value>,
+    ) -> String {
+        let mut msg = header(depth, "exit", name, functor);
+        match ret {
+            Some(ret) if *ret != Value::unit() => {
+                write!(msg, " = {}", self.render(ret)).expect("writing to string should succeed");
+            }
+            _ => {}
+        }
+        msg
+    }
+
+    fn render(&self, value: &Value) -> String {
+        let rendered = value.to_string();
+        match rendered.char_indices().nth(self.max_value_len) {
+            Some((end, _)) => format!("{}…", &rendered[..end]),
+            None => rendered,
+        }
+    }
+}
+
+fn header(depth: usize, event: &str, name: &str, functor: FunctorApp) -> String {
+    let mut msg = "  ".repeat(depth);
+    msg.push_str(event);
+    msg.push(' ');
+    if functor != FunctorApp::default() {
+        write!(msg, "{functor} ").expect("writing to string should succeed");
+    }
+    msg.push_str(name);
+    msg
+}
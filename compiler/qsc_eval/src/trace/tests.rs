@@ -0,0 +1,64 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::CallTrace;
+use crate::val::Value;
+use qsc_data_structures::functors::FunctorApp;
+use std::rc::Rc;
+
+#[test]
+fn filters_match_exact_names_and_prefixes() {
+    let trace = CallTrace::new(["Foo", "Bar*"]);
+    assert!(trace.matches("Foo"));
+    assert!(!trace.matches("FooBar"));
+    assert!(trace.matches("Bar"));
+    assert!(trace.matches("BarBaz"));
+    assert!(!trace.matches("Baz"));
+    assert!(CallTrace::new(["*"]).matches("Anything"));
+    assert!(!CallTrace::new(Vec::<String>::new()).matches("Anything"));
+}
+
+#[test]
+fn messages_render_arguments_and_return_values() {
+    let trace = CallTrace::new(["*"]);
+    let args = Value::Tuple(Rc::new([Value::Int(1), Value::Bool(true)]));
+    assert_eq!(
+        trace.enter_message(0, "Foo", FunctorApp::default(), &args),
+        "enter Foo(1, true)"
+    );
+    assert_eq!(
+        trace.enter_message(1, "Bar", FunctorApp::default(), &Value::Int(3)),
+        "  enter Bar(3)"
+    );
+    assert_eq!(
+        trace.exit_message(1, "Bar", FunctorApp::default(), Some(&Value::Int(6))),
+        "  exit Bar = 6"
+    );
+    assert_eq!(
+        trace.exit_message(0, "Foo", FunctorApp::default(), Some(&Value::unit())),
+        "exit Foo"
+    );
+}
+
+#[test]
+fn messages_include_functors() {
+    let trace = CallTrace::new(["*"]);
+    let functor = FunctorApp {
+        adjoint: true,
+        controlled: 1,
+    };
+    assert_eq!(
+        trace.exit_message(0, "Foo", functor, None),
+        "exit Controlled Adjoint Foo"
+    );
+}
+
+#[test]
+fn long_values_are_truncated() {
+    let trace = CallTrace::new(["*"]).with_max_value_len(5);
+    let arr = Value::Array((0..10).map(Value::Int).collect::<Vec<_>>().into());
+    assert_eq!(
+        trace.enter_message(0, "Foo", FunctorApp::default(), &arr),
+        "enter Foo([0, 1…)"
+    );
+}
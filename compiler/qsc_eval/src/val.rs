@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 use num_bigint::BigInt;
-use qsc_data_structures::{display::join, functors::FunctorApp};
+use qsc_data_structures::{display::join, double::DoubleFormat, functors::FunctorApp};
 use qsc_fir::fir::{Functor, Pauli, StoreItemId};
 use std::{
     fmt::{self, Display, Formatter},
@@ -107,15 +107,9 @@ impl Display for Value {
             Value::BigInt(v) => write!(f, "{v}"),
             Value::Bool(v) => write!(f, "{v}"),
             Value::Closure(..) => f.write_str("<closure>"),
-            Value::Double(v) => {
-                if (v.floor() - v.ceil()).abs() < f64::EPSILON {
-                    // The value is a whole number, which by convention is displayed with one decimal point
-                    // to differentiate it from an integer value.
-                    write!(f, "{v:.1}")
-                } else {
-                    write!(f, "{v}")
-                }
-            }
+            // Whole numbers are displayed with one decimal point by convention, to differentiate
+            // them from integer values.
+            Value::Double(v) => f.write_str(&DoubleFormat::default().format(*v)),
             Value::Global(id, functor) if functor == &FunctorApp::default() => id.fmt(f),
             Value::Global(id, functor) => write!(f, "{functor} {id}"),
             Value::Int(v) => write!(f, "{v}"),
@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
 use qsc_frontend::{
     keyword::Keyword,
     lex::{
@@ -10,6 +10,7 @@ use qsc_frontend::{
         Delim, InterpolatedEnding, InterpolatedStart,
     },
 };
+use qsc_parse::Error;
 
 #[cfg(test)]
 mod tests;
@@ -32,6 +33,22 @@ pub fn format_str(code: &str) -> String {
     new_code
 }
 
+/// Formats the given code like [`format_str`], after checking that it parses,
+/// so that code with syntax errors is left as it is. The code can be either
+/// namespaces or top-level items and statements. Comments are kept.
+///
+/// # Errors
+///
+/// Returns the syntax errors in the code.
+pub fn format_source(code: &str) -> Result<String, Vec<Error>> {
+    let (_, errors) = qsc_parse::top_level_nodes(code, LanguageFeatures::default());
+    if errors.is_empty() {
+        Ok(format_str(code))
+    } else {
+        Err(errors)
+    }
+}
+
 /// Applies formatting rules to the given code str, generating edits where
 /// the source code needs to be changed to comply with the format rules.
 pub fn calculate_format_edits(code: &str) -> Vec<TextEdit> {
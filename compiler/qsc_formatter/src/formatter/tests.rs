@@ -1105,6 +1105,40 @@ fn remove_extra_whitespace_from_start_of_code() {
     check(input, &expect!["namespace Foo {}"]);
 }
 
+// Source is only formatted when it parses
+
+#[test]
+fn format_source_keeps_comments() {
+    let input = indoc! {r#"
+        namespace Foo {
+        // Comment
+        function Bar() : Unit {}
+        }
+    "#};
+
+    let formatted = super::format_source(input).expect("source should parse");
+    expect![[r#"
+        namespace Foo {
+            // Comment
+            function Bar() : Unit {}
+        }
+    "#]]
+    .assert_eq(&formatted);
+}
+
+#[test]
+fn format_source_accepts_top_level_statements() {
+    let formatted = super::format_source("let x = 3;   // Comment").expect("source should parse");
+    expect!["let x = 3;   // Comment"].assert_eq(&formatted);
+}
+
+#[test]
+fn format_source_rejects_syntax_errors() {
+    let errors = super::format_source("namespace Foo { function Bar( : Unit {} }")
+        .expect_err("source should not parse");
+    assert!(!errors.is_empty());
+}
+
 // Extra test cases for sanity
 
 #[test]
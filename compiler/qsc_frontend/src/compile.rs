@@ -35,9 +35,12 @@ use qsc_hir::{
     validate::Validator as HirValidator,
     visit::Visitor as _,
 };
-use std::{fmt::Debug, sync::Arc};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, rc::Rc, sync::Arc};
 use thiserror::Error;
 
+pub use library::StdFeatures;
+
 #[derive(Debug, Default)]
 pub struct CompileUnit {
     pub package: hir::Package,
@@ -56,9 +59,12 @@ pub struct AstPackage {
     pub locals: Locals,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct SourceMap {
     sources: Vec<Source>,
+    /// The version of each source, in the same order as `sources`. Sources that are not versioned
+    /// have version 0.
+    versions: Vec<u32>,
     /// The common prefix of the sources
     /// e.g. if the sources all start with `/Users/microsoft/code/qsharp/src`, then this value is
     /// `/Users/microsoft/code/qsharp/src`.
@@ -71,47 +77,28 @@ impl SourceMap {
         sources: impl IntoIterator<Item = (SourceName, SourceContents)>,
         entry: Option<Arc<str>>,
     ) -> Self {
-        let mut offset_sources = Vec::new();
-
         let entry_source = entry.map(|contents| Source {
             name: "<entry>".into(),
             contents,
             offset: 0,
         });
 
-        let mut offset = next_offset(entry_source.as_ref());
-        for (name, contents) in sources {
-            let source = Source {
+        let sources: Vec<_> = sources
+            .into_iter()
+            .map(|(name, contents)| Source {
                 name,
                 contents,
-                offset,
-            };
-            offset = next_offset(Some(&source));
-            offset_sources.push(source);
-        }
-
-        // Each source has a name, which is a string. The project root dir is calculated as the
-        // common prefix of all of the sources.
-        // Calculate the common prefix.
-        let common_prefix: String = longest_common_prefix(
-            &offset_sources
-                .iter()
-                .map(|source| source.name.as_ref())
-                .collect::<Vec<_>>(),
-        )
-        .to_string();
-
-        let common_prefix: Arc<str> = Arc::from(common_prefix);
-
-        Self {
-            sources: offset_sources,
-            common_prefix: if common_prefix.is_empty() {
-                None
-            } else {
-                Some(common_prefix)
-            },
+                offset: 0,
+            })
+            .collect();
+        let mut map = Self {
+            versions: vec![0; sources.len()],
+            sources,
+            common_prefix: None,
             entry: entry_source,
-        }
+        };
+        map.layout();
+        map
     }
 
     pub fn push(&mut self, name: SourceName, contents: SourceContents) -> u32 {
@@ -122,10 +109,91 @@ impl SourceMap {
             contents,
             offset,
         });
+        self.versions.push(0);
 
         offset
     }
 
+    /// Adds a source, or replaces the contents of the source with the same name. Sources are
+    /// identified by name, like documents by URI in an editor, and the version is the version of
+    /// the document that the contents come from. Returns whether the contents changed: a source
+    /// updated to the contents it already has, such as after an edit is undone, keeps its
+    /// offsets, and anything compiled from it is still valid.
+    ///
+    /// When the contents do change, the sources after the updated one move to new offsets, so
+    /// packages compiled from the map must be compiled again.
+    pub fn update(&mut self, name: SourceName, contents: SourceContents, version: u32) -> bool {
+        match self.sources.iter().position(|s| s.name == name) {
+            Some(index) => {
+                self.versions[index] = version;
+                if self.sources[index].contents == contents {
+                    return false;
+                }
+                self.sources[index].contents = contents;
+            }
+            None => {
+                self.sources.push(Source {
+                    name,
+                    contents,
+                    offset: 0,
+                });
+                self.versions.push(version);
+            }
+        }
+        self.layout();
+        true
+    }
+
+    /// Removes the source with the given name and returns it. The sources after it move to new
+    /// offsets, like when a source is updated.
+    pub fn remove(&mut self, name: &str) -> Option<Source> {
+        let index = self.sources.iter().position(|s| s.name.as_ref() == name)?;
+        let source = self.sources.remove(index);
+        self.versions.remove(index);
+        self.layout();
+        Some(source)
+    }
+
+    /// The version of the source with the given name, as last given to [`SourceMap::update`].
+    #[must_use]
+    pub fn version(&self, name: &str) -> Option<u32> {
+        let index = self.sources.iter().position(|s| s.name.as_ref() == name)?;
+        Some(self.versions[index])
+    }
+
+    /// Assigns consecutive offsets to the sources, after the entry, and computes their common
+    /// prefix.
+    fn layout(&mut self) {
+        let mut offset = next_offset(self.entry.as_ref());
+        for source in &mut self.sources {
+            source.offset = offset;
+            offset = next_offset(Some(source));
+        }
+
+        // Each source has a name, which is a string. The project root dir is calculated as the
+        // common prefix of all of the sources.
+        // Calculate the common prefix.
+        let common_prefix = longest_common_prefix(
+            &self
+                .sources
+                .iter()
+                .map(|source| source.name.as_ref())
+                .collect::<Vec<_>>(),
+        )
+        .to_string();
+        self.common_prefix = if common_prefix.is_empty() {
+            None
+        } else {
+            Some(common_prefix.into())
+        };
+    }
+
+    /// The source of the entry expression, if there is one.
+    #[must_use]
+    pub fn entry(&self) -> Option<&Source> {
+        self.entry.as_ref()
+    }
+
     #[must_use]
     pub fn find_by_offset(&self, offset: u32) -> Option<&Source> {
         self.sources
@@ -164,7 +232,7 @@ impl SourceMap {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Source {
     pub name: SourceName,
     pub contents: SourceContents,
@@ -195,7 +263,7 @@ pub(super) enum ErrorKind {
 
 pub struct PackageStore {
     core: global::Table,
-    units: IndexMap<PackageId, CompileUnit>,
+    units: IndexMap<PackageId, Rc<CompileUnit>>,
     next_id: PackageId,
 }
 
@@ -208,6 +276,12 @@ impl Debug for PackageStore {
 impl PackageStore {
     #[must_use]
     pub fn new(core: CompileUnit) -> Self {
+        Self::with_shared_core(Rc::new(core))
+    }
+
+    /// Creates a store with a core library that can also be in other stores.
+    #[must_use]
+    pub fn with_shared_core(core: Rc<CompileUnit>) -> Self {
         let table = global::iter_package(Some(PackageId::CORE), &core.package).collect();
         let mut units = IndexMap::new();
         units.insert(PackageId::CORE, core);
@@ -224,6 +298,13 @@ impl PackageStore {
     }
 
     pub fn insert(&mut self, unit: CompileUnit) -> PackageId {
+        self.insert_shared(Rc::new(unit))
+    }
+
+    /// Inserts a package that can also be in other stores, such as a standard library that is
+    /// compiled once and then used by many stores. It must have been compiled against packages
+    /// with the same IDs as the packages it depends on in this store.
+    pub fn insert_shared(&mut self, unit: Rc<CompileUnit>) -> PackageId {
         let id = self.next_id;
         self.next_id = id.successor();
         self.units.insert(id, unit);
@@ -232,7 +313,7 @@ impl PackageStore {
 
     #[must_use]
     pub fn get(&self, id: PackageId) -> Option<&CompileUnit> {
-        self.units.get(id)
+        self.units.get(id).map(Rc::as_ref)
     }
 
     #[must_use]
@@ -247,7 +328,7 @@ impl PackageStore {
     pub fn open(mut self) -> OpenPackageStore {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, CompileUnit::default());
+        self.units.insert(id, Rc::default());
 
         OpenPackageStore {
             store: self,
@@ -290,12 +371,14 @@ impl OpenPackageStore {
     pub fn get_open_mut(&mut self) -> (&global::Table, &mut CompileUnit) {
         let id = self.open;
 
+        let unit = self
+            .store
+            .units
+            .get_mut(id)
+            .expect("open package id should exist in store");
         (
             &self.store.core,
-            self.store
-                .units
-                .get_mut(id)
-                .expect("open package id should exist in store"),
+            Rc::get_mut(unit).expect("open package should not be shared"),
         )
     }
 
@@ -307,13 +390,13 @@ impl OpenPackageStore {
     }
 }
 
-pub struct Iter<'a>(index_map::Iter<'a, PackageId, CompileUnit>);
+pub struct Iter<'a>(index_map::Iter<'a, PackageId, Rc<CompileUnit>>);
 
 impl<'a> Iterator for Iter<'a> {
     type Item = (PackageId, &'a CompileUnit);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|(id, unit)| (id, unit.as_ref()))
     }
 }
 
@@ -439,8 +522,21 @@ pub fn core() -> CompileUnit {
 /// Panics if the standard library does not compile without errors.
 #[must_use]
 pub fn std(store: &PackageStore, capabilities: TargetCapabilityFlags) -> CompileUnit {
-    let std: Vec<(SourceName, SourceContents)> = library::STD_LIB
-        .iter()
+    std_with_features(store, capabilities, StdFeatures::all())
+}
+
+/// Compiles the standard library with only the selected optional feature groups.
+///
+/// # Panics
+///
+/// Panics if the standard library does not compile without errors.
+#[must_use]
+pub fn std_with_features(
+    store: &PackageStore,
+    capabilities: TargetCapabilityFlags,
+    features: StdFeatures,
+) -> CompileUnit {
+    let std: Vec<(SourceName, SourceContents)> = library::std_lib_sources(features)
         .map(|(name, contents)| ((*name).into(), (*contents).into()))
         .collect();
     let sources = SourceMap::new(std, None);
@@ -1458,3 +1458,45 @@ fn test_longest_common_prefix_only_root_common() {
 fn test_longest_common_prefix_only_root_common_no_leading() {
     expect![""].assert_eq(longest_common_prefix(&["a/b", "b/c"]));
 }
+
+#[test]
+fn source_map_update_moves_later_sources() {
+    let mut sources = SourceMap::new(
+        [
+            ("a.qs".into(), "namespace A {}".into()),
+            ("b.qs".into(), "namespace B {}".into()),
+        ],
+        None,
+    );
+    assert_eq!(sources.find_by_name("b.qs").map(|s| s.offset), Some(15));
+
+    assert!(sources.update("a.qs".into(), "namespace Longer {}".into(), 2));
+    assert_eq!(sources.version("a.qs"), Some(2));
+    assert_eq!(sources.version("b.qs"), Some(0));
+    assert_eq!(sources.find_by_name("b.qs").map(|s| s.offset), Some(20));
+    let b = sources
+        .find_by_offset(20)
+        .expect("offset should be in a source");
+    assert_eq!(b.name.as_ref(), "b.qs");
+}
+
+#[test]
+fn source_map_update_with_same_contents_only_changes_version() {
+    let mut sources = SourceMap::new([("a.qs".into(), "namespace A {}".into())], None);
+    assert!(!sources.update("a.qs".into(), "namespace A {}".into(), 3));
+    assert_eq!(sources.version("a.qs"), Some(3));
+}
+
+#[test]
+fn source_map_update_adds_and_remove_removes() {
+    let mut sources = SourceMap::default();
+    assert!(sources.update("a.qs".into(), "namespace A {}".into(), 1));
+    assert!(sources.update("b.qs".into(), "namespace B {}".into(), 1));
+    assert_eq!(sources.iter().count(), 2);
+
+    let removed = sources.remove("a.qs").expect("source should exist");
+    assert_eq!(removed.name.as_ref(), "a.qs");
+    assert!(sources.remove("a.qs").is_none());
+    assert_eq!(sources.version("a.qs"), None);
+    assert_eq!(sources.find_by_name("b.qs").map(|s| s.offset), Some(0));
+}
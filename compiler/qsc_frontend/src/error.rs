@@ -4,11 +4,17 @@
 #[cfg(test)]
 mod tests;
 
-use crate::compile::{Source, SourceMap};
+use crate::compile::{Source, SourceMap, SourceName};
 use miette::{Diagnostic, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents};
+use qsc_data_structures::{
+    line_column::{Encoding, Range},
+    span::Span,
+};
+use serde_json::json;
 use std::{
     error::Error,
-    fmt::{self, Debug, Display, Formatter},
+    fmt::{self, Debug, Display, Formatter, Write},
+    iter,
 };
 
 #[derive(Clone, Debug)]
@@ -57,6 +63,21 @@ impl<E: Diagnostic + Send + Sync> WithSource<E> {
         }
     }
 
+    /// Whether the sources the error refers to still have the same contents in the given source
+    /// map, so that its labels point at the text they were reported for. This makes it possible
+    /// to keep showing diagnostics for sources that have not changed while others are edited. A
+    /// source that was updated back to the contents the error was reported against, such as after
+    /// an edit is undone, counts as unchanged.
+    #[must_use]
+    pub fn is_current(&self, sources: &SourceMap) -> bool {
+        self.sources.iter().all(|source| {
+            sources
+                .find_by_name(&source.name)
+                .or(sources.entry().filter(|entry| entry.name == source.name))
+                .is_some_and(|latest| latest.contents == source.contents)
+        })
+    }
+
     pub fn into_with_source<T>(self) -> WithSource<T>
     where
         T: From<E>,
@@ -80,6 +101,145 @@ impl<E: Diagnostic + Send + Sync> WithSource<E> {
             .expect("expected to find source at span");
         (source, with_offset(span, |o| o - (source.offset as usize)))
     }
+
+    /// Resolves the labels of the error to the sources they fall into, with their ranges in the
+    /// given encoding, in the order the error reports them.
+    pub fn resolve_labels(&self, encoding: Encoding) -> Vec<DiagnosticLabel> {
+        self.error
+            .labels()
+            .into_iter()
+            .flatten()
+            .map(|label| {
+                let (source, span) = self.resolve_span(label.inner());
+                let lo = u32::try_from(span.offset()).expect("offset should fit into u32");
+                let hi = lo + u32::try_from(span.len()).expect("length should fit into u32");
+                let span = Span { lo, hi };
+                DiagnosticLabel {
+                    source: source.name.clone(),
+                    span,
+                    range: Range::from_span(encoding, &source.contents, &span),
+                    message: label.label().map(ToString::to_string),
+                }
+            })
+            .collect()
+    }
+
+    /// The error in a structured form, with its labels resolved to ranges in the given encoding.
+    pub fn to_structured(&self, encoding: Encoding) -> StructuredDiagnostic {
+        StructuredDiagnostic::new(self, self.resolve_labels(encoding))
+    }
+
+    /// The error as a JSON object. See [`StructuredDiagnostic::to_json`].
+    pub fn to_json(&self, encoding: Encoding) -> serde_json::Value {
+        self.to_structured(encoding).to_json()
+    }
+}
+
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Advice,
+}
+
+impl Severity {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Advice => "advice",
+        }
+    }
+}
+
+impl From<miette::Severity> for Severity {
+    fn from(severity: miette::Severity) -> Self {
+        match severity {
+            miette::Severity::Error => Severity::Error,
+            miette::Severity::Warning => Severity::Warning,
+            miette::Severity::Advice => Severity::Advice,
+        }
+    }
+}
+
+/// A span that a diagnostic points at, resolved to the source it falls into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticLabel {
+    pub source: SourceName,
+    /// The span in utf-8 byte offsets, relative to the start of the source.
+    pub span: Span,
+    pub range: Range,
+    pub message: Option<String>,
+}
+
+/// A diagnostic in a stable, structured form, for hosts that show or filter diagnostics
+/// themselves instead of rendering them with miette.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuredDiagnostic {
+    /// The diagnostic code, such as `Qsc.Resolve.NotFound`.
+    pub code: Option<String>,
+    pub severity: Severity,
+    /// The message, followed by the messages of the errors that caused it, each after a `: `.
+    pub message: String,
+    /// The first label of the diagnostic, which points at where it was reported.
+    pub primary: Option<DiagnosticLabel>,
+    /// The other labels of the diagnostic.
+    pub related: Vec<DiagnosticLabel>,
+    pub help: Option<String>,
+    pub url: Option<String>,
+}
+
+impl StructuredDiagnostic {
+    /// Creates the structured form of a diagnostic with the given resolved labels, which are
+    /// usually the labels of the diagnostic resolved with [`WithSource::resolve_labels`].
+    pub fn new(diagnostic: &dyn Diagnostic, labels: Vec<DiagnosticLabel>) -> Self {
+        let mut message = diagnostic.to_string();
+        for source in iter::successors(diagnostic.source(), |e| e.source()) {
+            write!(message, ": {source}").expect("message should be writable");
+        }
+        let mut labels = labels.into_iter();
+        Self {
+            code: diagnostic.code().map(|code| code.to_string()),
+            severity: diagnostic
+                .severity()
+                .map_or(Severity::Error, Severity::from),
+            message,
+            primary: labels.next(),
+            related: labels.collect(),
+            help: diagnostic.help().map(|help| help.to_string()),
+            url: diagnostic.url().map(|url| url.to_string()),
+        }
+    }
+
+    /// The diagnostic as a JSON object, with `code`, `severity`, `message`, `primary`, `related`,
+    /// `help` and `url` fields. Missing values are `null`, and each label is an object with
+    /// `source`, `span`, `range` and `message` fields, where lines and columns start from 0.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "code": self.code,
+            "severity": self.severity.as_str(),
+            "message": self.message,
+            "primary": self.primary.as_ref().map(label_to_json),
+            "related": self.related.iter().map(label_to_json).collect::<Vec<_>>(),
+            "help": self.help,
+            "url": self.url,
+        })
+    }
+}
+
+fn label_to_json(label: &DiagnosticLabel) -> serde_json::Value {
+    json!({
+        "source": label.source.as_ref(),
+        "span": { "lo": label.span.lo, "hi": label.span.hi },
+        "range": {
+            "start": { "line": label.range.start.line, "column": label.range.start.column },
+            "end": { "line": label.range.end.line, "column": label.range.end.column },
+        },
+        "message": label.message,
+    })
 }
 
 impl<E: Diagnostic> Error for WithSource<E> {
@@ -7,6 +7,7 @@ use super::WithSource;
 use crate::compile::SourceMap;
 use expect_test::expect;
 use miette::Diagnostic;
+use qsc_data_structures::line_column::Encoding;
 use qsc_data_structures::span::Span;
 use std::{error::Error, fmt::Write, iter, str::from_utf8};
 use thiserror::Error;
@@ -23,6 +24,10 @@ enum TestError {
         #[label("first label")] Span,
         #[label("second label")] Span,
     ),
+    #[error("Warning: {0}")]
+    #[diagnostic(code("Qsc.Test.Warning.WithHelp"))]
+    #[diagnostic(severity(Warning), help("try something else"))]
+    WithHelp(String, #[label] Span),
 }
 
 #[test]
@@ -164,6 +169,152 @@ fn resolve_spans() {
     .assert_debug_eq(&resolved_spans);
 }
 
+#[test]
+fn is_current_until_referenced_source_changes() {
+    let test1_contents = "namespace Foo {}";
+    let mut sources = SourceMap::default();
+    let test1_offset = sources.push("test1.qs".into(), test1_contents.into());
+    sources.push("test2.qs".into(), "namespace Bar {}".into());
+
+    let error = WithSource::from_map(
+        &sources,
+        TestError::TwoSpans(
+            "value".into(),
+            span_with_offset(test1_offset, 0, 9),
+            span_with_offset(test1_offset, 10, 13),
+        ),
+    );
+    assert!(error.is_current(&sources));
+
+    // Other sources do not affect the error.
+    assert!(sources.update("test2.qs".into(), "namespace Baz {}".into(), 1));
+    assert!(error.is_current(&sources));
+
+    assert!(sources.update("test1.qs".into(), "namespace Qux {}".into(), 1));
+    assert!(!error.is_current(&sources));
+
+    assert!(sources.update("test1.qs".into(), test1_contents.into(), 2));
+    assert!(error.is_current(&sources));
+
+    sources.remove("test1.qs");
+    assert!(!error.is_current(&sources));
+}
+
+#[test]
+fn structured_diagnostic_has_resolved_labels() {
+    let mut sources = SourceMap::default();
+    sources.push("test1.qs".into(), "namespace Foo {}".into());
+    let test2_offset = sources.push("test2.qs".into(), "// Bar\nnamespace Bar {}".into());
+
+    let error = WithSource::from_map(
+        &sources,
+        TestError::WithHelp("value".into(), span_with_offset(test2_offset, 17, 20)),
+    );
+
+    expect![[r#"
+        StructuredDiagnostic {
+            code: Some(
+                "Qsc.Test.Warning.WithHelp",
+            ),
+            severity: Warning,
+            message: "Warning: value",
+            primary: Some(
+                DiagnosticLabel {
+                    source: "test2.qs",
+                    span: Span {
+                        lo: 17,
+                        hi: 20,
+                    },
+                    range: Range {
+                        start: Position {
+                            line: 1,
+                            column: 10,
+                        },
+                        end: Position {
+                            line: 1,
+                            column: 13,
+                        },
+                    },
+                    message: None,
+                },
+            ),
+            related: [],
+            help: Some(
+                "try something else",
+            ),
+            url: None,
+        }
+    "#]]
+    .assert_debug_eq(&error.to_structured(Encoding::Utf8));
+}
+
+#[test]
+fn structured_diagnostic_json() {
+    let test1_contents = "namespace Foo {}";
+    let mut sources = SourceMap::default();
+    let test1_offset = sources.push("test1.qs".into(), test1_contents.into());
+
+    let error = WithSource::from_map(
+        &sources,
+        TestError::TwoSpans(
+            "value".into(),
+            span_with_offset(test1_offset, 0, 9),
+            span_with_offset(test1_offset, 10, 13),
+        ),
+    );
+
+    expect![[r#"
+        {
+          "code": "Qsc.Test.Error.TwoSpans",
+          "help": null,
+          "message": "Error: value",
+          "primary": {
+            "message": "first label",
+            "range": {
+              "end": {
+                "column": 9,
+                "line": 0
+              },
+              "start": {
+                "column": 0,
+                "line": 0
+              }
+            },
+            "source": "test1.qs",
+            "span": {
+              "hi": 9,
+              "lo": 0
+            }
+          },
+          "related": [
+            {
+              "message": "second label",
+              "range": {
+                "end": {
+                  "column": 13,
+                  "line": 0
+                },
+                "start": {
+                  "column": 10,
+                  "line": 0
+                }
+              },
+              "source": "test1.qs",
+              "span": {
+                "hi": 13,
+                "lo": 10
+              }
+            }
+          ],
+          "severity": "error",
+          "url": null
+        }"#]]
+    .assert_eq(
+        &serde_json::to_string_pretty(&error.to_json(Encoding::Utf8))
+            .expect("diagnostic should serialize"),
+    );
+}
+
 fn span_with_offset(offset: u32, lo: u32, hi: u32) -> Span {
     Span {
         lo: lo + offset,
@@ -8,7 +8,7 @@ use crate::{
     compile::{self, preprocess, AstPackage, CompileUnit, Offsetter, PackageStore, SourceMap},
     error::WithSource,
     lower::Lowerer,
-    resolve::{self, Resolver},
+    resolve::{self, Redefinition, Resolver},
     typeck::{self, Checker},
 };
 use qsc_ast::{
@@ -25,7 +25,7 @@ use qsc_hir::{
     validate::Validator as HirValidator,
     visit::Visitor as HirVisitor,
 };
-use std::mem::take;
+use std::{mem::take, rc::Rc};
 
 /// The frontend for an incremental compiler.
 /// It is used to update a single `CompileUnit`
@@ -48,6 +48,8 @@ pub type Error = WithSource<compile::Error>;
 pub struct Increment {
     pub ast: AstPackage,
     pub hir: hir::Package,
+    /// The callables from earlier increments that this increment declared again.
+    pub redefinitions: Vec<Redefinition>,
 }
 
 impl Compiler {
@@ -122,6 +124,32 @@ impl Compiler {
         self.compile_fragments_internal(unit, ast, parse_errors, accumulate_errors)
     }
 
+    /// Compiles a whole Q# source file, like one that is part of a project, rather than fragments.
+    ///
+    /// Unlike fragments, the file can only contain items. Items outside of a `namespace` block are
+    /// placed in a namespace named after the source, as they are when compiling a project.
+    ///
+    /// See [`Compiler::compile_fragments`] for how the result and errors are handled.
+    pub fn compile_source<F, E>(
+        &mut self,
+        unit: &mut CompileUnit,
+        source_name: &str,
+        source_contents: &str,
+        accumulate_errors: F,
+    ) -> Result<Increment, E>
+    where
+        F: FnMut(Vec<Error>) -> Result<(), E>,
+    {
+        let (ast, parse_errors) = Self::parse_source(
+            &mut unit.sources,
+            source_name,
+            source_contents,
+            self.language_features,
+        );
+
+        self.compile_fragments_internal(unit, ast, parse_errors, accumulate_errors)
+    }
+
     /// Compiles Q# AST fragments.
     ///
     /// Uses the assigners and other mutable state from the passed in
@@ -173,7 +201,7 @@ impl Compiler {
     {
         accumulate_errors(parse_errors)?;
 
-        let (hir, errors) = self.resolve_check_lower(unit, &mut ast);
+        let (hir, redefinitions, errors) = self.resolve_check_lower(unit, &mut ast);
 
         accumulate_errors(errors)?;
 
@@ -185,6 +213,7 @@ impl Compiler {
                 tys: self.checker.table().clone(),
             },
             hir,
+            redefinitions,
         })
     }
 
@@ -210,7 +239,7 @@ impl Compiler {
             return Err(parse_errors);
         }
 
-        let (hir, errors) = self.resolve_check_lower(unit, &mut ast);
+        let (hir, redefinitions, errors) = self.resolve_check_lower(unit, &mut ast);
 
         if !errors.is_empty() {
             return Err(errors);
@@ -224,6 +253,7 @@ impl Compiler {
                 tys: self.checker.table().clone(),
             },
             hir,
+            redefinitions,
         })
     }
 
@@ -242,11 +272,18 @@ impl Compiler {
         extend_hir(&mut unit.package, new.hir);
     }
 
+    /// The namespaces opened by the top-level statements of the fragments compiled so far, in the
+    /// order they were opened, with the alias each one was opened as.
+    #[must_use]
+    pub fn opens(&self) -> Vec<(String, Option<Rc<str>>)> {
+        self.resolver.top_level_opens()
+    }
+
     fn resolve_check_lower(
         &mut self,
         unit: &mut CompileUnit,
         ast: &mut ast::Package,
-    ) -> (hir::Package, Vec<Error>) {
+    ) -> (hir::Package, Vec<Redefinition>, Vec<Error>) {
         let mut cond_compile = preprocess::Conditional::new(self.capabilities);
         cond_compile.visit_package(ast);
 
@@ -254,6 +291,7 @@ impl Compiler {
 
         self.resolver
             .extend_dropped_names(cond_compile.into_names());
+        let checkpoint = self.resolver.checkpoint();
         self.resolver.bind_fragments(ast, &mut unit.assigner);
         self.resolver.bind_and_resolve_imports_and_exports(ast);
         self.resolver.with(&mut unit.assigner).visit_package(ast);
@@ -280,11 +318,16 @@ impl Compiler {
             .map(|e| WithSource::from_map(&unit.sources, e))
             .collect::<Vec<_>>();
 
+        let mut redefinitions = self.resolver.take_redefinitions();
         if !errors.is_empty() {
             self.lowerer.clear_items();
+            // Keep the fragments that failed to compile from replacing callables that work or
+            // opening namespaces.
+            self.resolver.revert_redefinitions(take(&mut redefinitions));
+            self.resolver.restore(checkpoint);
         }
 
-        (package, errors)
+        (package, redefinitions, errors)
     }
 
     /// Creates a new `Package` by combining two packages.
@@ -346,6 +389,30 @@ impl Compiler {
         (package, with_source(errors, sources, offset))
     }
 
+    fn parse_source(
+        sources: &mut SourceMap,
+        source_name: &str,
+        source_contents: &str,
+        features: LanguageFeatures,
+    ) -> (ast::Package, Vec<Error>) {
+        let offset = sources.push(source_name.into(), source_contents.into());
+        let (mut namespaces, errors) =
+            qsc_parse::namespaces(source_contents, Some(source_name), features);
+        let mut offsetter = Offsetter(offset);
+        for namespace in &mut namespaces {
+            offsetter.visit_namespace(namespace);
+        }
+        let package = ast::Package {
+            id: ast::NodeId::default(),
+            nodes: namespaces
+                .into_iter()
+                .map(ast::TopLevelNode::Namespace)
+                .collect(),
+            entry: None,
+        };
+        (package, with_source(errors, sources, offset))
+    }
+
     /// offset all top level nodes based on the source input
     /// and return the updated package and errors
     fn offset_ast_fragments(
@@ -375,6 +442,34 @@ impl Compiler {
     }
 }
 
+/// Returns true if the input looks like a whole source file rather than fragments, such as when a
+/// file is pasted into a REPL: it parses without errors, contains only declarations, and either
+/// declares a namespace or has a callable marked with the `@EntryPoint()` attribute.
+#[must_use]
+pub fn is_source_file(source_contents: &str, language_features: LanguageFeatures) -> bool {
+    let (nodes, errors) = qsc_parse::top_level_nodes(source_contents, language_features);
+    if !errors.is_empty() || nodes.is_empty() {
+        return false;
+    }
+
+    let mut items = Vec::new();
+    for node in &nodes {
+        match node {
+            ast::TopLevelNode::Namespace(_) => return true,
+            ast::TopLevelNode::Stmt(stmt) => match &*stmt.kind {
+                ast::StmtKind::Item(item) => items.push(item),
+                ast::StmtKind::Empty => {}
+                _ => return false,
+            },
+        }
+    }
+    items.iter().any(|item| {
+        item.attrs
+            .iter()
+            .any(|attr| attr.name.name.as_ref() == "EntryPoint")
+    })
+}
+
 /// Extends the `Package` with the contents of another `Package`.
 /// `other` should not contain any `LocalItemId`s
 /// that conflict with the current `Package`.
@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use super::{Compiler, Increment};
+use super::{is_source_file, Compiler, Increment};
 use crate::{
     compile::{self, CompileUnit, PackageStore},
     incremental::Error,
@@ -9,7 +9,9 @@ use crate::{
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use miette::Diagnostic;
+use qsc_ast::ast;
 use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_hir::hir::{self, LocalItemId};
 use std::fmt::Write;
 
 #[allow(clippy::too_many_lines)]
@@ -470,6 +472,249 @@ fn continue_after_lower_error() {
     "#]].assert_debug_eq(&errors);
 }
 
+#[test]
+fn source_without_namespace_is_named_after_file() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+    let increment = compiler
+        .compile_source(
+            &mut unit,
+            "Pasted.qs",
+            "function One() : Int { 1 }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+
+    let names = increment
+        .ast
+        .package
+        .nodes
+        .iter()
+        .map(|node| match node {
+            ast::TopLevelNode::Namespace(namespace) => namespace.name.name().to_string(),
+            ast::TopLevelNode::Stmt(_) => panic!("source should only contain namespaces"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Pasted"]);
+    assert!(unit.sources.find_by_name("Pasted.qs").is_some());
+}
+
+#[test]
+fn source_with_statement_is_error() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    compiler
+        .compile_source(
+            &mut CompileUnit::default(),
+            "Pasted.qs",
+            "let x = 1;",
+            fail_on_error,
+        )
+        .expect_err("statements should not be allowed outside of callables");
+}
+
+#[test]
+fn whole_files_are_detected() {
+    for (input, expected) in [
+        ("namespace Foo { function One() : Int { 1 } }", true),
+        ("@EntryPoint() operation Main() : Unit {}", true),
+        ("/// Docs\n@EntryPoint()\noperation Main() : Unit {}", true),
+        ("function One() : Int { 1 }", false),
+        ("function One() : Int { 1 } One()", false),
+        ("let x = 1;", false),
+        ("namespace Foo {", false),
+        ("", false),
+    ] {
+        assert_eq!(
+            is_source_file(input, LanguageFeatures::default()),
+            expected,
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn callable_in_namespace_can_be_redefined() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+    let first = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { function One() : Int { 0 } }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    let old = callable_id(&first.hir, "One");
+    compiler.update(&mut unit, first);
+
+    let second = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_2",
+            "namespace Foo { function One() : Int { 1 } }",
+            fail_on_error,
+        )
+        .expect("redefinition should succeed");
+    let [redefinition] = second.redefinitions.as_slice() else {
+        panic!("expected one redefinition: {:?}", second.redefinitions);
+    };
+    assert_eq!(&*redefinition.name, "One");
+    assert_eq!(redefinition.old.item, old);
+    assert_eq!(redefinition.new.item, callable_id(&second.hir, "One"));
+}
+
+#[test]
+fn top_level_callable_can_be_redefined() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+    let first = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "function One() : Int { 0 }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    assert!(first.redefinitions.is_empty());
+    let old = callable_id(&first.hir, "One");
+    compiler.update(&mut unit, first);
+
+    let second = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_2",
+            "function One() : Int { 1 }",
+            fail_on_error,
+        )
+        .expect("redefinition should succeed");
+    let [redefinition] = second.redefinitions.as_slice() else {
+        panic!("expected one redefinition: {:?}", second.redefinitions);
+    };
+    assert_eq!(redefinition.old.item, old);
+}
+
+#[test]
+fn callable_declared_twice_in_one_increment_is_error() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let errors = compiler
+        .compile_fragments(
+            &mut CompileUnit::default(),
+            "test_1",
+            "namespace Foo { function One() : Int { 0 } function One() : Int { 1 } }",
+            fail_on_error,
+        )
+        .expect_err("duplicate callables should be an error");
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(
+        errors[0].code().map(|code| code.to_string()).as_deref(),
+        Some("Qsc.Resolve.Duplicate")
+    );
+}
+
+#[test]
+fn type_cannot_be_redefined() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+    let first = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { newtype Pair = (Int, Int); }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    compiler.update(&mut unit, first);
+
+    for source in [
+        "namespace Foo { newtype Pair = (Int, Int); }",
+        "namespace Foo { function Pair() : Int { 0 } }",
+    ] {
+        compiler
+            .compile_fragments(&mut unit, "test_2", source, fail_on_error)
+            .expect_err("type should not be redefined");
+    }
+}
+
+#[test]
+fn failed_redefinition_keeps_previous_callable() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    let mut unit = CompileUnit::default();
+    let first = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { function One() : Int { 0 } }",
+            fail_on_error,
+        )
+        .expect("compilation should succeed");
+    let old = callable_id(&first.hir, "One");
+    compiler.update(&mut unit, first);
+
+    compiler
+        .compile_fragments(
+            &mut unit,
+            "test_2",
+            "namespace Foo { function One() : Int { Undefined() } }",
+            fail_on_error,
+        )
+        .expect_err("redefinition should fail to compile");
+
+    let third = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_3",
+            "namespace Foo { function One() : Int { 1 } }",
+            fail_on_error,
+        )
+        .expect("redefinition should succeed");
+    let [redefinition] = third.redefinitions.as_slice() else {
+        panic!("expected one redefinition: {:?}", third.redefinitions);
+    };
+    assert_eq!(redefinition.old.item, old);
+}
+
 fn check_unit(expect: &Expect, actual: &Increment) {
     let ast = format!("ast:\n{}", actual.ast.package);
 
@@ -507,6 +752,17 @@ fn check_unit(expect: &Expect, actual: &Increment) {
     );
 }
 
+fn callable_id(package: &hir::Package, name: &str) -> LocalItemId {
+    package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            hir::ItemKind::Callable(decl) if &*decl.name.name == name => Some(item.id),
+            _ => None,
+        })
+        .expect("callable should be in the package")
+}
+
 fn fail_on_error(errors: Vec<Error>) -> Result<(), Vec<Error>> {
     if !errors.is_empty() {
         return Err(errors);
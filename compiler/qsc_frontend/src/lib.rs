@@ -5,6 +5,7 @@ mod closure;
 pub mod compile;
 pub mod error;
 pub mod incremental;
+pub mod lookup;
 mod lower;
 pub mod resolve;
 pub mod typeck;
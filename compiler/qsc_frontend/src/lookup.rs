@@ -0,0 +1,184 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Finds what the name at an offset in a compile unit refers to, using the names and types that
+//! the resolver and type checker left in the unit's AST tables. Hosts use it to show the
+//! declaration and type of the name under the cursor, without walking the AST themselves.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compile::CompileUnit,
+    resolve::{path_as_field_accessor, Res},
+};
+use qsc_ast::{
+    ast::{self, ExprKind, Ident, NodeId, PatKind, TyKind},
+    visit::{self, Visitor},
+};
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    hir::ItemId,
+    ty::{ParamId, Ty},
+};
+use std::rc::Rc;
+
+/// What a name refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// A global or local item. Items in the unit's own package have no package ID.
+    Item(ItemId),
+    /// A local variable or parameter, identified by the node of the name that declares it.
+    Local(NodeId),
+    /// A type parameter of the enclosing callable.
+    Param(ParamId),
+}
+
+/// A name and what it refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lookup {
+    pub name: Rc<str>,
+    /// The span of the name, relative to the unit's source map.
+    pub span: Span,
+    pub target: Target,
+    /// Whether the name is the one that declares what it refers to, rather than a use of it.
+    pub is_declaration: bool,
+    /// The type of the expression or pattern that the name is part of, as inferred by the type
+    /// checker, so the type arguments of a generic callable are filled in. Names in types and in
+    /// item declarations have no type.
+    pub ty: Option<Ty>,
+}
+
+/// Finds the name at the offset, which is relative to the unit's source map. An offset at either
+/// end of a name is part of the name, so that a cursor directly after a name finds it.
+#[must_use]
+pub fn lookup(unit: &CompileUnit, offset: u32) -> Option<Lookup> {
+    let mut finder = Finder {
+        unit,
+        offset: Some(offset),
+        found: Vec::new(),
+    };
+    finder.visit_package(&unit.ast.package);
+    // Names are found from the outside in, so the last one is the innermost.
+    finder.found.pop()
+}
+
+/// Finds every resolved name in the unit, in the order they appear in its AST.
+#[must_use]
+pub fn names(unit: &CompileUnit) -> Vec<Lookup> {
+    let mut finder = Finder {
+        unit,
+        offset: None,
+        found: Vec::new(),
+    };
+    finder.visit_package(&unit.ast.package);
+    finder.found
+}
+
+/// Finds the span of the name that declares the local variable or parameter, relative to the
+/// unit's source map.
+#[must_use]
+pub fn local_declaration(unit: &CompileUnit, id: NodeId) -> Option<Span> {
+    let mut finder = DeclarationFinder { id, span: None };
+    finder.visit_package(&unit.ast.package);
+    finder.span
+}
+
+struct Finder<'a> {
+    unit: &'a CompileUnit,
+    /// Only the names at this offset are found, or all names if there is no offset.
+    offset: Option<u32>,
+    found: Vec<Lookup>,
+}
+
+impl Finder<'_> {
+    /// Records the name if it is at the offset and was resolved. `id` is the node that the
+    /// resolver mapped, which is not always the node of the name, and `ty_id` is the node that
+    /// the type checker mapped.
+    fn find(&mut self, name: &Ident, id: NodeId, ty_id: Option<NodeId>, is_declaration: bool) {
+        if self
+            .offset
+            .is_some_and(|offset| name.span.lo > offset || offset > name.span.hi)
+        {
+            return;
+        }
+
+        let target = match self.unit.ast.names.get(id) {
+            Some(&Res::Item(item, _)) => Target::Item(item),
+            Some(&Res::Local(node)) => Target::Local(node),
+            Some(&Res::Param(param)) => Target::Param(param),
+            Some(Res::PrimTy(_) | Res::UnitTy) | None => return,
+        };
+        self.found.push(Lookup {
+            name: name.name.clone(),
+            span: name.span,
+            target,
+            is_declaration,
+            ty: ty_id.and_then(|id| self.unit.ast.tys.terms.get(id).cloned()),
+        });
+    }
+}
+
+impl<'a> Visitor<'a> for Finder<'_> {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        match &*item.kind {
+            ast::ItemKind::Callable(decl) => self.find(&decl.name, decl.name.id, None, true),
+            ast::ItemKind::Ty(name, _) => self.find(name, name.id, None, true),
+            ast::ItemKind::Struct(decl) => self.find(&decl.name, decl.name.id, None, true),
+            _ => {}
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+        for param in &*decl.generics {
+            self.find(param, param.id, None, true);
+        }
+        visit::walk_callable_decl(self, decl);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if let ExprKind::Path(path) = &*expr.kind {
+            // The rest of a field accessor path are field names, which do not resolve to a
+            // declaration.
+            if let Some((_, parts)) = path_as_field_accessor(&self.unit.ast.names, path) {
+                let first = &parts[0];
+                self.find(first, first.id, Some(first.id), false);
+            } else {
+                self.find(&path.name, path.id, Some(expr.id), false);
+            }
+        } else {
+            visit::walk_expr(self, expr);
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let PatKind::Bind(name, _) = &*pat.kind {
+            self.find(name, name.id, Some(pat.id), true);
+        }
+        visit::walk_pat(self, pat);
+    }
+
+    fn visit_ty(&mut self, ty: &'a ast::Ty) {
+        match &*ty.kind {
+            TyKind::Path(path) => self.find(&path.name, path.id, None, false),
+            TyKind::Param(name) => self.find(name, name.id, None, false),
+            _ => {}
+        }
+        visit::walk_ty(self, ty);
+    }
+}
+
+struct DeclarationFinder {
+    id: NodeId,
+    span: Option<Span>,
+}
+
+impl<'a> Visitor<'a> for DeclarationFinder {
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        match &*pat.kind {
+            PatKind::Bind(name, _) if name.id == self.id => self.span = Some(name.span),
+            _ => visit::walk_pat(self, pat),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{local_declaration, lookup, names, Lookup, Target};
+use crate::compile::{self, compile, CompileUnit, PackageStore, SourceMap};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        function Id<'T>(x : 'T) : 'T { x }
+        newtype Pair = (Int, Int);
+        function Main() : Int {
+            let value = Id(42);
+            let pair = Pair(value, 1);
+            value + 1
+        }
+    }
+"};
+
+fn unit() -> CompileUnit {
+    let unit = compile(
+        &PackageStore::new(compile::core()),
+        &[],
+        SourceMap::new([("test".into(), SOURCE.into())], None),
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    unit
+}
+
+/// Looks up the name at the start of the first occurrence of the text.
+fn at(unit: &CompileUnit, text: &str) -> Option<Lookup> {
+    let index = SOURCE.find(text).expect("text should occur in the source");
+    lookup(
+        unit,
+        u32::try_from(index).expect("offset should fit into u32"),
+    )
+}
+
+#[test]
+fn callable_use_has_instantiated_type() {
+    let unit = unit();
+    let found = at(&unit, "Id(42)").expect("name should be found");
+    assert_eq!(found.name.as_ref(), "Id");
+    assert_eq!(
+        found.ty.map(|ty| ty.to_string()).as_deref(),
+        Some("(Int -> Int)")
+    );
+
+    let decl = at(&unit, "Id<").expect("declaration should be found");
+    assert_eq!(decl.target, found.target);
+    assert_eq!(decl.ty, None);
+    assert!(decl.is_declaration && !found.is_declaration);
+    assert!(matches!(found.target, Target::Item(item) if item.package.is_none()));
+}
+
+#[test]
+fn local_use_refers_to_its_declaration() {
+    let unit = unit();
+    let decl = at(&unit, "value").expect("declaration should be found");
+    let used = at(&unit, "value + 1").expect("use should be found");
+    assert_eq!(used.target, decl.target);
+    assert!(matches!(decl.target, Target::Local(id) if unit.ast.names.get(id).is_some()));
+    assert_eq!(used.ty.map(|ty| ty.to_string()).as_deref(), Some("Int"));
+
+    let Target::Local(id) = used.target else {
+        panic!("use should refer to a local");
+    };
+    assert_eq!(local_declaration(&unit, id), Some(decl.span));
+}
+
+#[test]
+fn offset_at_end_of_name_finds_it() {
+    let unit = unit();
+    let index = SOURCE
+        .find("pair =")
+        .expect("text should occur in the source");
+    let end = u32::try_from(index + "pair".len()).expect("offset should fit into u32");
+    let found = lookup(&unit, end).expect("name should be found");
+    assert_eq!(found.name.as_ref(), "pair");
+    assert_eq!(found.span.hi, end);
+}
+
+#[test]
+fn type_names_and_parameters_are_found() {
+    let unit = unit();
+    let pair = at(&unit, "Pair(value").expect("constructor should be found");
+    let decl = at(&unit, "Pair =").expect("declaration should be found");
+    assert_eq!(pair.target, decl.target);
+
+    let param = at(&unit, "T)").expect("type parameter should be found");
+    let generic = at(&unit, "T>").expect("type parameter should be found");
+    assert_eq!(param.target, generic.target);
+    assert!(matches!(param.target, Target::Param(_)));
+}
+
+#[test]
+fn offset_outside_of_names_finds_nothing() {
+    let unit = unit();
+    assert_eq!(at(&unit, "namespace"), None);
+    assert_eq!(at(&unit, "42"), None);
+}
+
+#[test]
+fn all_names_are_listed_in_source_order() {
+    let unit = unit();
+    let value = at(&unit, "value").expect("declaration should be found");
+    let spans = names(&unit)
+        .into_iter()
+        .filter(|found| found.target == value.target)
+        .map(|found| (&SOURCE[found.span], found.is_declaration))
+        .collect::<Vec<_>>();
+    assert_eq!(spans, [("value", true), ("value", false), ("value", false)]);
+}
@@ -407,6 +407,44 @@ pub(super) struct Resolver {
     globals: GlobalScope,
     locals: Locals,
     errors: Vec<Error>,
+    /// Tracks callables that are declared again by later fragments. Only incremental compilation
+    /// can redefine callables, so this is `None` for whole packages.
+    redefinitions: Option<Redefinitions>,
+}
+
+/// A callable from an earlier increment that was declared again, replacing it for the code that
+/// is compiled after it. Code that was compiled before it still refers to the old callable.
+#[derive(Clone, Debug)]
+pub struct Redefinition {
+    pub name: Rc<str>,
+    pub old: ItemId,
+    pub new: ItemId,
+    previous: Previous,
+}
+
+/// The state of the top-level scope before a fragment was bound.
+pub(super) struct Checkpoint {
+    opens: FxHashMap<Vec<Rc<str>>, Vec<Open>>,
+}
+
+/// The binding that a redefinition replaced, so that it can be restored.
+#[derive(Clone, Debug)]
+enum Previous {
+    Global(NamespaceId, Res),
+    Local(ScopeItemEntry),
+}
+
+#[derive(Default)]
+struct Redefinitions {
+    /// The callables bound by the current increment, which it cannot redefine.
+    bound: FxHashSet<ItemId>,
+    redefinitions: Vec<Redefinition>,
+}
+
+impl Redefinitions {
+    fn can_redefine(&self, old: ItemId) -> bool {
+        old.package.is_none() && !self.bound.contains(&old)
+    }
 }
 
 /// This visitor is used for an intermediate step between binding and full resolution.
@@ -500,6 +538,7 @@ impl Resolver {
             locals: Locals::default(),
             curr_scope_chain: Vec::new(),
             errors: Vec::new(),
+            redefinitions: None,
         }
     }
 
@@ -523,6 +562,7 @@ impl Resolver {
             locals,
             curr_scope_chain: vec![scope_id],
             errors: Vec::new(),
+            redefinitions: Some(Redefinitions::default()),
         }
     }
 
@@ -559,21 +599,130 @@ impl Resolver {
     }
 
     pub(super) fn bind_fragments(&mut self, ast: &ast::Package, assigner: &mut Assigner) {
-        for node in &mut ast.nodes.iter() {
-            match node {
-                TopLevelNode::Namespace(namespace) => {
-                    bind_global_items(
-                        &mut self.names,
-                        &mut self.globals,
-                        namespace,
-                        assigner,
-                        &mut self.errors,
-                    );
+        if let Some(redefinitions) = &mut self.redefinitions {
+            redefinitions.bound.clear();
+        }
+        // Namespaces are bound first, so that a top-level `open` can refer to a namespace that is
+        // declared later in the same fragments.
+        for node in &*ast.nodes {
+            if let TopLevelNode::Namespace(namespace) = node {
+                bind_global_items(
+                    &mut self.names,
+                    &mut self.globals,
+                    namespace,
+                    assigner,
+                    &mut self.errors,
+                    self.redefinitions.as_mut(),
+                );
+            }
+        }
+        for node in &*ast.nodes {
+            if let TopLevelNode::Stmt(stmt) = node {
+                if let ast::StmtKind::Item(item) = stmt.kind.as_ref() {
+                    self.bind_top_level_item(assigner, item);
                 }
-                TopLevelNode::Stmt(stmt) => {
-                    if let ast::StmtKind::Item(item) = stmt.kind.as_ref() {
-                        self.bind_local_item(assigner, item, None);
-                    }
+            }
+        }
+    }
+
+    /// The namespaces opened by top-level statements, in the order they were opened, with the
+    /// alias each one was opened as.
+    pub(super) fn top_level_opens(&self) -> Vec<(String, Option<Rc<str>>)> {
+        let Some(&scope_id) = self.curr_scope_chain.first() else {
+            return Vec::new();
+        };
+        let mut opens = self
+            .locals
+            .get_scope(scope_id)
+            .opens
+            .iter()
+            .flat_map(|(alias, opens)| opens.iter().map(move |open| (alias.first(), open)))
+            .collect::<Vec<_>>();
+        opens.sort_by_key(|(_, open)| open.span.lo);
+        opens
+            .into_iter()
+            .map(|(alias, open)| {
+                let (name, _) = self
+                    .globals
+                    .namespaces
+                    .find_namespace_by_id(&open.namespace);
+                (name.join("."), alias.cloned())
+            })
+            .collect()
+    }
+
+    /// Saves the opens of the top-level scope, so that the opens of fragments that fail to compile
+    /// can be undone with [`Resolver::restore`].
+    pub(super) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            opens: self
+                .curr_scope_chain
+                .first()
+                .map(|&scope_id| self.locals.get_scope(scope_id).opens.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub(super) fn restore(&mut self, checkpoint: Checkpoint) {
+        if let Some(&scope_id) = self.curr_scope_chain.first() {
+            self.locals.get_scope_mut(scope_id).opens = checkpoint.opens;
+        }
+    }
+
+    /// Binds an item declared by a top-level statement. A callable replaces the callable with the
+    /// same name that an earlier increment declared.
+    fn bind_top_level_item(&mut self, assigner: &mut Assigner, item: &ast::Item) {
+        let ast::ItemKind::Callable(decl) = item.kind.as_ref() else {
+            self.bind_local_item(assigner, item, None);
+            return;
+        };
+        let scope = self.current_scope_mut();
+        let previous = scope
+            .terms
+            .get(&decl.name.name)
+            .filter(|_| !scope.tys.contains_key(&decl.name.name))
+            .cloned();
+        self.bind_local_item(assigner, item, None);
+        let (Some(redefinitions), Some(&Res::Item(new, _))) =
+            (&mut self.redefinitions, self.names.get(decl.name.id))
+        else {
+            return;
+        };
+        redefinitions.bound.insert(new);
+        if let Some(previous) = previous.filter(|previous| redefinitions.can_redefine(previous.id))
+        {
+            redefinitions.redefinitions.push(Redefinition {
+                name: Rc::clone(&decl.name.name),
+                old: previous.id,
+                new,
+                previous: Previous::Local(previous),
+            });
+        }
+    }
+
+    /// Takes the redefinitions made by the fragments that were bound since they were last taken.
+    pub(super) fn take_redefinitions(&mut self) -> Vec<Redefinition> {
+        self.redefinitions
+            .as_mut()
+            .map(|redefinitions| std::mem::take(&mut redefinitions.redefinitions))
+            .unwrap_or_default()
+    }
+
+    /// Restores the callables that the given redefinitions replaced, for when the fragments that
+    /// made them fail to compile.
+    pub(super) fn revert_redefinitions(&mut self, redefinitions: Vec<Redefinition>) {
+        for redefinition in redefinitions.into_iter().rev() {
+            match redefinition.previous {
+                Previous::Global(namespace, res) => {
+                    self.globals
+                        .terms
+                        .get_mut_or_default(namespace)
+                        .insert(redefinition.name, res);
+                }
+                Previous::Local(entry) => {
+                    self.current_scope_mut()
+                        .terms
+                        .insert(redefinition.name, entry);
                 }
             }
         }
@@ -1341,6 +1490,7 @@ impl GlobalTable {
                         namespace,
                         assigner,
                         &mut errors,
+                        None,
                     );
                 }
                 TopLevelNode::Stmt(_) => {
@@ -1394,6 +1544,7 @@ fn bind_global_items(
     namespace: &ast::Namespace,
     assigner: &mut Assigner,
     errors: &mut Vec<Error>,
+    mut redefinitions: Option<&mut Redefinitions>,
 ) {
     names.insert(
         namespace.id,
@@ -1409,6 +1560,7 @@ fn bind_global_items(
             namespace_id,
             || intrapackage(assigner.next_item()),
             item,
+            redefinitions.as_deref_mut(),
         ) {
             Ok(()) => {}
             Err(mut e) => errors.append(&mut e),
@@ -1464,10 +1616,11 @@ fn bind_global_item(
     namespace: NamespaceId,
     next_id: impl FnOnce() -> ItemId,
     item: &ast::Item,
+    redefinitions: Option<&mut Redefinitions>,
 ) -> Result<(), Vec<Error>> {
     match &*item.kind {
         ast::ItemKind::Callable(decl) => {
-            bind_callable(decl, namespace, next_id, item, names, scope)
+            bind_callable(decl, namespace, next_id, item, names, scope, redefinitions)
         }
         ast::ItemKind::Ty(name, _) => bind_ty(name, namespace, next_id, item, names, scope),
         ast::ItemKind::Struct(decl) => bind_ty(&decl.name, namespace, next_id, item, names, scope),
@@ -1482,6 +1635,7 @@ fn bind_callable(
     item: &ast::Item,
     names: &mut IndexMap<NodeId, Res>,
     scope: &mut GlobalScope,
+    mut redefinitions: Option<&mut Redefinitions>,
 ) -> Result<(), Vec<Error>> {
     let item_id = next_id();
     let status = ItemStatus::from_attrs(&ast_attrs_as_hir_attrs(item.attrs.as_ref()));
@@ -1489,11 +1643,31 @@ fn bind_callable(
     names.insert(decl.name.id, res);
     let mut errors = Vec::new();
 
-    match scope
-        .terms
-        .get_mut_or_default(namespace)
-        .entry(Rc::clone(&decl.name.name))
-    {
+    let is_ty = scope
+        .tys
+        .get(namespace)
+        .is_some_and(|tys| tys.contains_key(&decl.name.name));
+    let terms = scope.terms.get_mut_or_default(namespace);
+    let redefined = match (terms.get(&decl.name.name), redefinitions.as_deref()) {
+        (Some(&Res::Item(old, _)), Some(redefinitions))
+            if !is_ty && redefinitions.can_redefine(old) =>
+        {
+            Some(old)
+        }
+        _ => None,
+    };
+    match terms.entry(Rc::clone(&decl.name.name)) {
+        Entry::Occupied(mut entry) if redefined.is_some() => {
+            let previous = entry.insert(res);
+            if let (Some(old), Some(redefinitions)) = (redefined, redefinitions.as_deref_mut()) {
+                redefinitions.redefinitions.push(Redefinition {
+                    name: Rc::clone(&decl.name.name),
+                    old,
+                    new: item_id,
+                    previous: Previous::Global(namespace, previous),
+                });
+            }
+        }
         Entry::Occupied(_) => {
             let namespace_name = scope
                 .namespaces
@@ -1510,7 +1684,13 @@ fn bind_callable(
             entry.insert(res);
         }
     }
-    if decl_is_intrinsic(decl) && !scope.intrinsics.insert(Rc::clone(&decl.name.name)) {
+    if let Some(redefinitions) = redefinitions {
+        redefinitions.bound.insert(item_id);
+    }
+    if redefined.is_none()
+        && decl_is_intrinsic(decl)
+        && !scope.intrinsics.insert(Rc::clone(&decl.name.name))
+    {
         errors.push(Error::DuplicateIntrinsic(
             decl.name.name.to_string(),
             decl.name.span,
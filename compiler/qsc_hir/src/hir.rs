@@ -8,6 +8,7 @@ use crate::ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, GenericParam, Sc
 use indenter::{indented, Indented};
 use num_bigint::BigInt;
 use qsc_data_structures::{index_map::IndexMap, span::Span};
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
@@ -30,7 +31,7 @@ fn set_indentation<'a, 'b>(
 }
 
 /// A unique identifier for an HIR node.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct NodeId(u32);
 
 impl NodeId {
@@ -110,7 +111,7 @@ impl Hash for NodeId {
 }
 
 /// A unique identifier for a package within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct PackageId(usize);
 
 impl PackageId {
@@ -143,7 +144,9 @@ impl From<usize> for PackageId {
 }
 
 /// A unique identifier for an item within a package.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct LocalItemId(usize);
 
 impl LocalItemId {
@@ -173,7 +176,7 @@ impl From<LocalItemId> for usize {
 }
 
 /// A unique identifier for an item within a package store.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ItemId {
     /// The package ID or `None` for the local package.
     pub package: Option<PackageId>,
@@ -191,7 +194,7 @@ impl Display for ItemId {
 }
 
 /// The status of an item.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum ItemStatus {
     /// The item is defined normally.
     Available,
@@ -214,7 +217,7 @@ impl ItemStatus {
 
 /// A resolution. This connects a usage of a name with the declaration of that name by uniquely
 /// identifying the node that declared it.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Res {
     /// An invalid resolution.
     Err,
@@ -249,7 +252,7 @@ impl Display for Res {
 }
 
 /// The root node of the HIR.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Package {
     /// The items in the package.
     pub items: IndexMap<LocalItemId, Item>,
@@ -278,7 +281,7 @@ impl Display for Package {
 }
 
 /// An item.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Item {
     /// The ID.
     pub id: LocalItemId,
@@ -327,7 +330,7 @@ impl Display for Item {
 }
 
 /// An item kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ItemKind {
     /// A `function` or `operation` declaration.
     Callable(CallableDecl),
@@ -360,7 +363,7 @@ impl Display for ItemKind {
 }
 
 /// A callable declaration header.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CallableDecl {
     /// The node ID.
     pub id: NodeId,
@@ -443,7 +446,7 @@ impl Display for CallableDecl {
 }
 
 /// A specialization declaration.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpecDecl {
     /// The node ID.
     pub id: NodeId,
@@ -460,7 +463,7 @@ impl Display for SpecDecl {
 }
 
 /// The body of a specialization.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum SpecBody {
     /// The strategy to use to automatically generate the specialization.
     Gen(SpecGen),
@@ -487,7 +490,7 @@ impl Display for SpecBody {
 }
 
 /// A sequenced block of statements.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Block {
     /// The node ID.
     pub id: NodeId,
@@ -520,7 +523,7 @@ impl Display for Block {
 }
 
 /// A statement.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Stmt {
     /// The node ID.
     pub id: NodeId,
@@ -537,7 +540,7 @@ impl Display for Stmt {
 }
 
 /// A statement kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StmtKind {
     /// An expression without a trailing semicolon.
     Expr(Expr),
@@ -579,7 +582,7 @@ impl Display for StmtKind {
 }
 
 /// An expression.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Expr {
     /// The node ID.
     pub id: NodeId,
@@ -602,7 +605,7 @@ impl Display for Expr {
 }
 
 /// An expression kind.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum ExprKind {
     /// An array: `[a, b, c]`.
     Array(Vec<Expr>),
@@ -1026,7 +1029,7 @@ fn display_while(mut indent: Indented<Formatter>, cond: &Expr, block: &Block) ->
 }
 
 /// A field assignment in a struct constructor expression.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct FieldAssign {
     /// The node ID.
     pub id: NodeId,
@@ -1049,7 +1052,7 @@ impl Display for FieldAssign {
 }
 
 /// A string component.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StringComponent {
     /// An expression.
     Expr(Expr),
@@ -1058,7 +1061,7 @@ pub enum StringComponent {
 }
 
 /// A pattern.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Pat {
     /// The node ID.
     pub id: NodeId,
@@ -1081,7 +1084,7 @@ impl Display for Pat {
 }
 
 /// A pattern kind.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum PatKind {
     /// A binding.
     Bind(Ident),
@@ -1119,7 +1122,7 @@ impl Display for PatKind {
 }
 
 /// A qubit initializer.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct QubitInit {
     /// The node ID.
     pub id: NodeId,
@@ -1142,7 +1145,7 @@ impl Display for QubitInit {
 }
 
 /// A qubit initializer kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum QubitInitKind {
     /// An array of qubits: `Qubit[a]`.
     Array(Box<Expr>),
@@ -1184,7 +1187,7 @@ impl Display for QubitInitKind {
 /// A [`Idents`] represents a sequence of idents. It provides a helpful abstraction
 /// that is more powerful than a simple `Vec<Ident>`, and is primarily used to represent
 /// dot-separated paths.
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Default)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Default, Deserialize, Serialize)]
 pub struct Idents(pub Box<[Ident]>);
 
 impl<'a> IntoIterator for &'a Idents {
@@ -1292,7 +1295,7 @@ impl Idents {
     }
 }
 /// An identifier.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Ident {
     /// The node ID.
     pub id: NodeId,
@@ -1309,7 +1312,7 @@ impl Display for Ident {
 }
 
 /// An attribute.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Attr {
     /// Provide pre-processing information about when an item should be included in compilation.
     Config,
@@ -1320,6 +1323,8 @@ pub enum Attr {
     /// Indicates that an item should be treated as an intrinsic callable for QIR code generation
     /// and any implementation should be ignored.
     SimulatableIntrinsic,
+    /// Indicates that calls to a callable should be inlined at the call site when possible.
+    Inline,
 }
 
 impl FromStr for Attr {
@@ -1331,13 +1336,14 @@ impl FromStr for Attr {
             "EntryPoint" => Ok(Self::EntryPoint),
             "Unimplemented" => Ok(Self::Unimplemented),
             "SimulatableIntrinsic" => Ok(Self::SimulatableIntrinsic),
+            "Inline" => Ok(Self::Inline),
             _ => Err(()),
         }
     }
 }
 
 /// A field.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Field {
     /// A field path.
     Path(FieldPath),
@@ -1358,14 +1364,14 @@ impl Display for Field {
 }
 
 /// A path to a field in a tuple or user-defined type.
-#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct FieldPath {
     /// The tuple item indices to follow in order from top to bottom.
     pub indices: Vec<usize>,
 }
 
 /// A primitive field for a built-in type.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum PrimField {
     /// The start of a range.
     Start,
@@ -1389,7 +1395,7 @@ impl FromStr for PrimField {
 }
 
 /// The visibility of a declaration.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Visibility {
     /// Visible everywhere.
     Public,
@@ -1398,7 +1404,7 @@ pub enum Visibility {
 }
 
 /// A callable kind.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CallableKind {
     /// A function.
     Function,
@@ -1416,7 +1422,7 @@ impl Display for CallableKind {
 }
 
 /// The mutability of a binding.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Mutability {
     /// An immutable binding.
     Immutable,
@@ -1425,7 +1431,7 @@ pub enum Mutability {
 }
 
 /// The source of an allocated qubit.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum QubitSource {
     /// A qubit initialized to the zero state.
     Fresh,
@@ -1435,10 +1441,10 @@ pub enum QubitSource {
 }
 
 /// A literal.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Lit {
     /// A big integer literal.
-    BigInt(BigInt),
+    BigInt(#[serde(with = "big_int")] BigInt),
     /// A boolean literal.
     Bool(bool),
     /// A floating-point literal.
@@ -1465,8 +1471,26 @@ impl Display for Lit {
     }
 }
 
+/// Big integer literals are serialized as their decimal strings.
+mod big_int {
+    use num_bigint::BigInt;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(val: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(val)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BigInt, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
 /// A measurement result.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Result {
     /// The zero eigenvalue.
     Zero,
@@ -1475,7 +1499,7 @@ pub enum Result {
 }
 
 /// A Pauli operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Pauli {
     /// The Pauli I operator.
     I,
@@ -1488,7 +1512,7 @@ pub enum Pauli {
 }
 
 /// A functor that may be applied to an operation.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Functor {
     /// The adjoint functor.
     Adj,
@@ -1506,7 +1530,7 @@ impl Display for Functor {
 }
 
 /// A strategy for generating a specialization.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SpecGen {
     /// Choose a strategy automatically.
     Auto,
@@ -1522,7 +1546,7 @@ pub enum SpecGen {
 }
 
 /// A unary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum UnOp {
     /// A functor application.
     Functor(Functor),
@@ -1549,7 +1573,7 @@ impl Display for UnOp {
 }
 
 /// A binary operator.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BinOp {
     /// Addition: `+`.
     Add,
@@ -1591,7 +1615,7 @@ pub enum BinOp {
     XorB,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 /// Represents an export declaration.
 pub struct ExportDecl {
     /// The span.
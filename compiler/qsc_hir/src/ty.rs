@@ -4,6 +4,7 @@
 use indenter::{indented, Indented};
 use qsc_data_structures::span::Span;
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
 use crate::hir::{CallableKind, FieldPath, Functor, ItemId, PackageId, Res};
 use std::{
@@ -24,7 +25,7 @@ fn set_indentation<'a, 'b>(
 }
 
 /// A type.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Ty {
     /// An array type.
     Array(Box<Ty>),
@@ -265,7 +266,7 @@ impl Display for GenericParam {
 }
 
 /// The kind of a generic parameter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum GenericParam {
     /// A type parameter.
     Ty(TypeParamName),
@@ -274,7 +275,7 @@ pub enum GenericParam {
 }
 
 /// The name of a generic type parameter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct TypeParamName {
     /// The span.
     pub span: Span,
@@ -289,7 +290,7 @@ impl Display for TypeParamName {
 }
 
 /// A generic parameter ID.
-#[derive(Clone, Copy, Default, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub struct ParamId(u32);
 
 impl ParamId {
@@ -323,7 +324,7 @@ impl Display for ParamId {
 }
 
 /// An argument to a generic parameter.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum GenericArg {
     /// A type argument.
     Ty(Ty),
@@ -341,7 +342,7 @@ impl Display for GenericArg {
 }
 
 /// An arrow type: `->` for a function or `=>` for an operation.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Arrow {
     /// Whether the callable is a function or an operation.
     pub kind: CallableKind,
@@ -381,7 +382,7 @@ impl Display for Arrow {
 }
 
 /// A primitive type.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Prim {
     /// The big integer type.
     BigInt,
@@ -410,7 +411,7 @@ pub enum Prim {
 }
 
 /// A set of functors.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum FunctorSet {
     /// An evaluated set.
     Value(FunctorSetValue),
@@ -446,7 +447,7 @@ impl Display for FunctorSet {
 }
 
 /// The value of a functor set.
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum FunctorSetValue {
     /// The empty set.
     #[default]
@@ -523,7 +524,7 @@ impl Display for FunctorSetValue {
 }
 
 /// The item for a user-defined type.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Udt {
     /// The span.
     pub span: Span,
@@ -673,7 +674,7 @@ impl Display for Udt {
 }
 
 /// A UDT type definition.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UdtDef {
     /// The span.
     pub span: Span,
@@ -688,7 +689,7 @@ impl Display for UdtDef {
 }
 
 /// A UDT type definition kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum UdtDefKind {
     /// A field definition with an optional name but required type.
     Field(UdtField),
@@ -722,7 +723,7 @@ impl Display for UdtDefKind {
 }
 
 /// A user-defined type.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UdtField {
     /// The span of the field name.
     pub name_span: Option<Span>,
@@ -745,7 +746,7 @@ impl Display for UdtField {
 }
 
 /// A placeholder type variable used during type inference.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct InferTyId(usize);
 
 impl InferTyId {
@@ -775,7 +776,9 @@ impl From<InferTyId> for usize {
 }
 
 /// A placeholder functor variable used during type inference.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct InferFunctorId(usize);
 
 impl InferFunctorId {
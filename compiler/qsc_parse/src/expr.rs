@@ -8,6 +8,7 @@
 mod tests;
 
 use crate::{
+    grammar::Expected,
     keyword::Keyword,
     lex::{
         ClosedBinOp, Delim, InterpolatedEnding, InterpolatedStart, Radix, StringToken, Token,
@@ -23,7 +24,7 @@ use qsc_ast::ast::{
     self, BinOp, CallableKind, Expr, ExprKind, FieldAssign, Functor, Lit, NodeId, Pat, PatKind,
     Path, Pauli, StringComponent, TernOp, UnOp,
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{double, span::Span};
 use std::{result, str::FromStr};
 
 struct PrefixOp {
@@ -146,9 +147,15 @@ fn expr_op(s: &mut ParserContext, context: OpContext) -> Result<Box<Expr>> {
         });
     }
 
+    s.expect(Expected::Operator);
     Ok(lhs)
 }
 
+/// The tokens that can continue an expression as an operator.
+pub(super) fn operator_tokens() -> impl Iterator<Item = TokenKind> {
+    enum_iterator::all::<TokenKind>().filter(|&token| mixfix_op(OpName::Token(token)).is_some())
+}
+
 fn expr_base(s: &mut ParserContext) -> Result<Box<Expr>> {
     let lo = s.peek().span.lo;
     let kind = if token(s, TokenKind::Open(Delim::Paren)).is_ok() {
@@ -424,9 +431,8 @@ fn lit_token(lexeme: &str, token: Token) -> Result<Option<Lit>> {
         }
         TokenKind::Float => {
             let lexeme = lexeme.replace('_', "");
-            let value = lexeme
-                .parse()
-                .map_err(|_| Error(ErrorKind::Lit("floating-point", token.span)))?;
+            let value = double::parse(&lexeme)
+                .ok_or(Error(ErrorKind::Lit("floating-point", token.span)))?;
             Ok(Some(Lit::Double(value)))
         }
         TokenKind::Int(radix) => {
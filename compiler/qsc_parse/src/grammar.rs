@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A machine-readable description of the syntax accepted by the parser, for documentation tools
+//! and editors, along with a way to find out which tokens the parser would have accepted where
+//! parsing failed.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{expr, item, keyword::Keyword, lex::TokenKind, scan::ParserContext};
+use qsc_data_structures::language_features::LanguageFeatures;
+
+/// The Q# grammar in ISO 14977 EBNF. Terminals are quoted, and lexical tokens that are not fixed
+/// strings, like identifiers and literals, are written in angle brackets.
+pub const EBNF: &str = include_str!("grammar/qsharp.ebnf");
+
+/// Returns every keyword of the language.
+pub fn keywords() -> impl Iterator<Item = &'static str> {
+    enum_iterator::all::<Keyword>().map(Keyword::as_str)
+}
+
+/// Something the parser looked for at a token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Expected {
+    /// A specific token.
+    Token(TokenKind),
+    /// A syntax rule, such as an expression or a type.
+    Rule(&'static str),
+    /// An operator continuing the preceding expression.
+    Operator,
+}
+
+/// What the parser would have accepted at the position of a parse error.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedAt {
+    /// The offset of the token where the error occurred.
+    pub offset: u32,
+    /// The tokens that could have appeared at the offset.
+    pub tokens: Vec<TokenKind>,
+    /// The syntax rules, such as `expression` or `type`, that could have started at the offset.
+    pub rules: Vec<&'static str>,
+}
+
+/// Parses the input as interpreter fragments and returns what the parser would have accepted at
+/// the first parse error, or `None` if the input parses without errors. Lexical errors are not
+/// considered, since they do not depend on the grammar.
+#[must_use]
+pub fn expected_at_error(input: &str, language_features: LanguageFeatures) -> Option<ExpectedAt> {
+    let mut scanner = ParserContext::new(input, language_features);
+    if let Err(error) = item::parse_top_level_nodes(&mut scanner) {
+        scanner.record_expected(&error);
+    }
+
+    let (offset, expected) = scanner.into_expected_at_error()?;
+    let mut expected_at = ExpectedAt {
+        offset,
+        ..ExpectedAt::default()
+    };
+    for expected in expected {
+        match expected {
+            Expected::Token(token) => push_unique(&mut expected_at.tokens, token),
+            Expected::Rule(rule) => push_unique(&mut expected_at.rules, rule),
+            Expected::Operator => {
+                for token in expr::operator_tokens() {
+                    push_unique(&mut expected_at.tokens, token);
+                }
+            }
+        }
+    }
+    Some(expected_at)
+}
+
+fn push_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
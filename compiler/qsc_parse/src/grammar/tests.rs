@@ -0,0 +1,127 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{expected_at_error, keywords, ExpectedAt, EBNF};
+use crate::{
+    keyword::Keyword,
+    lex::{ClosedBinOp, Delim, TokenKind},
+};
+use qsc_data_structures::language_features::LanguageFeatures;
+use std::collections::BTreeSet;
+
+/// Returns the names of the rules defined by the grammar, and of the rules it refers to.
+fn rule_names() -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut defined = BTreeSet::new();
+    let mut referenced = BTreeSet::new();
+    let mut chars = EBNF.chars().peekable();
+    let mut word = String::new();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() || (c == '-' && !word.is_empty()) {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            let mut rest = chars.clone().skip_while(|c| *c == ' ');
+            if c == '=' || (c == ' ' && rest.next() == Some('=')) {
+                defined.insert(std::mem::take(&mut word));
+            } else {
+                referenced.insert(std::mem::take(&mut word));
+            }
+        }
+        match c {
+            '"' => {
+                chars.by_ref().find(|c| *c == '"');
+            }
+            '<' => {
+                chars.by_ref().find(|c| *c == '>');
+            }
+            '(' if chars.peek() == Some(&'*') => {
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == ')' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            _ => {}
+        }
+    }
+    (defined, referenced)
+}
+
+#[test]
+fn grammar_mentions_every_keyword() {
+    for keyword in keywords() {
+        assert!(
+            EBNF.contains(&format!("\"{keyword}\"")),
+            "keyword `{keyword}` is missing from the grammar"
+        );
+    }
+}
+
+#[test]
+fn grammar_defines_every_referenced_rule() {
+    let (defined, referenced) = rule_names();
+    assert!(defined.contains("expr"));
+    assert!(defined.contains("stmt"));
+    let undefined: Vec<_> = referenced.difference(&defined).collect();
+    assert!(undefined.is_empty(), "undefined rules: {undefined:?}");
+}
+
+#[test]
+fn no_expected_tokens_without_error() {
+    assert_eq!(
+        expected_at_error("let x = 1; x + 1", LanguageFeatures::default()),
+        None
+    );
+}
+
+#[test]
+fn expected_identifier_after_callable_keyword() {
+    assert_eq!(
+        expected_at_error(
+            "namespace Foo { operation () : Unit {} }",
+            LanguageFeatures::default()
+        ),
+        Some(ExpectedAt {
+            offset: 26,
+            tokens: vec![TokenKind::Ident],
+            rules: vec!["identifier"],
+        })
+    );
+}
+
+#[test]
+fn expected_expression_after_equals() {
+    let expected = expected_at_error("let x = ;", LanguageFeatures::default())
+        .expect("input should have a parse error");
+    assert_eq!(expected.offset, 8);
+    assert_eq!(expected.rules, vec!["expression"]);
+    for token in [
+        TokenKind::Open(Delim::Paren),
+        TokenKind::Open(Delim::Bracket),
+        TokenKind::Keyword(Keyword::If),
+        TokenKind::Ident,
+    ] {
+        assert!(expected.tokens.contains(&token), "{token}");
+    }
+    assert!(!expected.tokens.contains(&TokenKind::Semi));
+}
+
+#[test]
+fn expected_operator_or_semicolon_after_expression() {
+    let expected = expected_at_error("let x = 1 2;", LanguageFeatures::default())
+        .expect("input should have a parse error");
+    assert_eq!(expected.offset, 10);
+    assert!(expected.rules.is_empty());
+    for token in [
+        TokenKind::Semi,
+        TokenKind::ClosedBinOp(ClosedBinOp::Plus),
+        TokenKind::ClosedBinOp(ClosedBinOp::And),
+        TokenKind::Open(Delim::Paren),
+    ] {
+        assert!(expected.tokens.contains(&token), "{token}");
+    }
+    assert!(!expected.tokens.contains(&TokenKind::Eq));
+}
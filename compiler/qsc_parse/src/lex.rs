@@ -3,6 +3,7 @@
 
 pub mod concrete;
 pub mod cooked;
+pub mod highlight;
 pub mod raw;
 
 use enum_iterator::Sequence;
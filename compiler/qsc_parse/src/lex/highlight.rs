@@ -0,0 +1,197 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A token stream for syntax highlighters. Every character of the input belongs to exactly one
+//! token, including whitespace and comments, and each token is classified into a small, stable
+//! set of categories that do not change when the compiler's internal token kinds do.
+//!
+//! Input can be lexed all at once with [`tokenize`], or incrementally with a [`Tokenizer`] as it
+//! arrives in chunks, such as lines typed into a terminal REPL.
+
+#[cfg(test)]
+mod tests;
+
+use super::{
+    concrete::{ConcreteTokenIterator, ConcreteTokenKind},
+    cooked::{StringToken, TokenKind},
+    InterpolatedEnding, InterpolatedStart,
+};
+use qsc_data_structures::span::Span;
+
+/// The highlighting category of a token.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HighlightKind {
+    /// A keyword, including keyword literals like `true` and `PauliX`.
+    Keyword,
+    /// An identifier.
+    Ident,
+    /// A type parameter name like `'T`.
+    TypeParam,
+    /// A numeric literal.
+    Number,
+    /// A string literal, or a literal part of an interpolated string.
+    String,
+    /// A comment.
+    Comment,
+    /// A doc comment.
+    DocComment,
+    /// An operator like `+`, `==` or `w/`.
+    Operator,
+    /// A delimiter or separator like `(`, `,` or `;`.
+    Punctuation,
+    /// Whitespace, including line breaks.
+    Whitespace,
+    /// Text that is not a valid token, such as an unknown character or an unterminated string.
+    Error,
+}
+
+/// A highlighted token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HighlightToken {
+    pub kind: HighlightKind,
+    /// The span of the token, as byte offsets into the input.
+    pub span: Span,
+}
+
+/// Splits the input into highlighted tokens.
+#[must_use]
+pub fn tokenize(input: &str) -> Vec<HighlightToken> {
+    lex(input, 0).map(|(token, _)| token).collect()
+}
+
+/// Splits input that arrives in chunks into highlighted tokens. Token spans are offsets into the
+/// concatenation of all chunks pushed so far.
+///
+/// A token that touches the end of the input may continue in the next chunk, so the last token of
+/// each chunk is held back until more input arrives or [`Tokenizer::finish`] is called. Text
+/// inside an unfinished interpolated string is lexed again with each chunk, since its meaning
+/// depends on the rest of the string, but every token is returned only once.
+#[derive(Debug, Default)]
+pub struct Tokenizer {
+    /// The input from the last point where lexing can safely resume.
+    pending: String,
+    /// The offset of the start of `pending`.
+    offset: u32,
+    /// The offset up to which tokens have been returned.
+    returned: u32,
+}
+
+impl Tokenizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the chunk to the input and returns the tokens that are now complete.
+    pub fn push(&mut self, chunk: &str) -> Vec<HighlightToken> {
+        self.pending.push_str(chunk);
+        let mut tokens: Vec<_> = lex(&self.pending, self.offset).collect();
+        // The last token may continue in the next chunk.
+        tokens.pop();
+
+        let mut resume = self.offset;
+        let mut complete = Vec::new();
+        for (token, interpolation) in tokens {
+            if interpolation == 0 {
+                resume = token.span.hi;
+            }
+            if token.span.lo >= self.returned {
+                complete.push(token);
+            }
+        }
+
+        if let Some(last) = complete.last() {
+            self.returned = last.span.hi;
+        }
+        self.pending.drain(..(resume - self.offset) as usize);
+        self.offset = resume;
+        complete
+    }
+
+    /// Ends the input and returns the remaining tokens.
+    pub fn finish(&mut self) -> Vec<HighlightToken> {
+        let returned = self.returned;
+        let tokens = lex(&self.pending, self.offset)
+            .map(|(token, _)| token)
+            .filter(|token| token.span.lo >= returned)
+            .collect();
+        let end =
+            self.offset + u32::try_from(self.pending.len()).expect("length should fit into u32");
+        self.pending.clear();
+        self.offset = end;
+        self.returned = end;
+        tokens
+    }
+}
+
+/// Lexes the input, returning each highlighted token along with the depth of interpolated strings
+/// the lexer is inside of after the token.
+fn lex(input: &str, offset: u32) -> impl Iterator<Item = (HighlightToken, u32)> + '_ {
+    let mut interpolation = 0u32;
+    ConcreteTokenIterator::new(input).map(move |token| {
+        let kind = match token.kind {
+            ConcreteTokenKind::Syntax(kind) => {
+                if let TokenKind::String(StringToken::Interpolated(start, end)) = kind {
+                    match (start, end) {
+                        (InterpolatedStart::DollarQuote, InterpolatedEnding::LBrace) => {
+                            interpolation += 1;
+                        }
+                        (InterpolatedStart::RBrace, InterpolatedEnding::Quote) => {
+                            interpolation = interpolation.saturating_sub(1);
+                        }
+                        _ => {}
+                    }
+                }
+                highlight_kind(kind)
+            }
+            ConcreteTokenKind::Error(_) => HighlightKind::Error,
+            ConcreteTokenKind::WhiteSpace => HighlightKind::Whitespace,
+            ConcreteTokenKind::Comment => HighlightKind::Comment,
+        };
+        let token = HighlightToken {
+            kind,
+            span: token.span + offset,
+        };
+        (token, interpolation)
+    })
+}
+
+fn highlight_kind(kind: TokenKind) -> HighlightKind {
+    match kind {
+        TokenKind::Keyword(_) => HighlightKind::Keyword,
+        TokenKind::Ident => HighlightKind::Ident,
+        TokenKind::AposIdent => HighlightKind::TypeParam,
+        TokenKind::BigInt(_) | TokenKind::Float | TokenKind::Int(_) => HighlightKind::Number,
+        TokenKind::String(_) => HighlightKind::String,
+        TokenKind::DocComment => HighlightKind::DocComment,
+        TokenKind::Bang
+        | TokenKind::Bar
+        | TokenKind::BinOpEq(_)
+        | TokenKind::ClosedBinOp(_)
+        | TokenKind::DotDot
+        | TokenKind::DotDotDot
+        | TokenKind::Eq
+        | TokenKind::EqEq
+        | TokenKind::FatArrow
+        | TokenKind::Gt
+        | TokenKind::Gte
+        | TokenKind::LArrow
+        | TokenKind::Lt
+        | TokenKind::Lte
+        | TokenKind::Ne
+        | TokenKind::Question
+        | TokenKind::RArrow
+        | TokenKind::TildeTildeTilde
+        | TokenKind::WSlash
+        | TokenKind::WSlashEq => HighlightKind::Operator,
+        TokenKind::At
+        | TokenKind::Close(_)
+        | TokenKind::Colon
+        | TokenKind::ColonColon
+        | TokenKind::Comma
+        | TokenKind::Dot
+        | TokenKind::Eof
+        | TokenKind::Open(_)
+        | TokenKind::Semi => HighlightKind::Punctuation,
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{tokenize, HighlightKind, HighlightToken, Tokenizer};
+use expect_test::expect;
+use qsc_data_structures::span::Span;
+use std::fmt::Write;
+
+fn render(input: &str, tokens: &[HighlightToken]) -> String {
+    tokens.iter().fold(String::new(), |mut output, token| {
+        let _ = writeln!(output, "{:?} {:?}", token.kind, &input[token.span]);
+        output
+    })
+}
+
+fn tokenize_in_chunks(chunks: &[&str]) -> Vec<HighlightToken> {
+    let mut tokenizer = Tokenizer::new();
+    let mut tokens = Vec::new();
+    for chunk in chunks {
+        tokens.extend(tokenizer.push(chunk));
+    }
+    tokens.extend(tokenizer.finish());
+    tokens
+}
+
+#[test]
+fn tokens_cover_the_input() {
+    let input =
+        "/// Doc\noperation Foo<'T>(x : 'T) : Unit is Adj { let y = x w/ 0 <- 1.5; } // done";
+    let tokens = tokenize(input);
+    let mut offset = 0;
+    for token in &tokens {
+        assert_eq!(token.span.lo, offset);
+        offset = token.span.hi;
+    }
+    assert_eq!(offset as usize, input.len());
+}
+
+#[test]
+fn tokens_are_classified() {
+    let input =
+        "@EntryPoint()\noperation Main<'T>() : Int { let s = $\"n = {1 + 2L}\"; 0x1 } // c\n§";
+    expect![[r#"
+        Punctuation "@"
+        Ident "EntryPoint"
+        Punctuation "("
+        Punctuation ")"
+        Whitespace "\n"
+        Keyword "operation"
+        Whitespace " "
+        Ident "Main"
+        Operator "<"
+        TypeParam "'T"
+        Operator ">"
+        Punctuation "("
+        Punctuation ")"
+        Whitespace " "
+        Punctuation ":"
+        Whitespace " "
+        Ident "Int"
+        Whitespace " "
+        Punctuation "{"
+        Whitespace " "
+        Keyword "let"
+        Whitespace " "
+        Ident "s"
+        Whitespace " "
+        Operator "="
+        Whitespace " "
+        String "$\"n = {"
+        Number "1"
+        Whitespace " "
+        Operator "+"
+        Whitespace " "
+        Number "2L"
+        String "}\""
+        Punctuation ";"
+        Whitespace " "
+        Number "0x1"
+        Whitespace " "
+        Punctuation "}"
+        Whitespace " "
+        Comment "// c"
+        Whitespace "\n"
+        Error "§"
+    "#]]
+    .assert_eq(&render(input, &tokenize(input)));
+}
+
+#[test]
+fn chunked_tokens_match_whole_input() {
+    let input = "/// Doc\nlet x = $\"a {y} b {z + 1}\";\n// note\nset x w/= 0 <- \"s\\\"t\"; 1..2";
+    let whole = tokenize(input);
+    for split in (0..=input.len()).filter(|&i| input.is_char_boundary(i)) {
+        let (first, second) = input.split_at(split);
+        assert_eq!(
+            tokenize_in_chunks(&[first, second]),
+            whole,
+            "split at {split}"
+        );
+    }
+    let chars: Vec<String> = input.chars().map(String::from).collect();
+    let chunks: Vec<&str> = chars.iter().map(String::as_str).collect();
+    assert_eq!(tokenize_in_chunks(&chunks), whole);
+}
+
+#[test]
+fn last_token_is_held_until_more_input() {
+    let mut tokenizer = Tokenizer::new();
+    assert_eq!(
+        tokenizer.push("let ope"),
+        vec![
+            HighlightToken {
+                kind: HighlightKind::Keyword,
+                span: Span { lo: 0, hi: 3 },
+            },
+            HighlightToken {
+                kind: HighlightKind::Whitespace,
+                span: Span { lo: 3, hi: 4 },
+            },
+        ]
+    );
+    assert_eq!(
+        tokenizer.push("ration"),
+        Vec::new(),
+        "the keyword may still continue"
+    );
+    assert_eq!(
+        tokenizer.finish(),
+        vec![HighlightToken {
+            kind: HighlightKind::Keyword,
+            span: Span { lo: 4, hi: 13 },
+        }]
+    );
+}
@@ -6,6 +6,7 @@
 //! unique identifiers by a later stage.
 
 mod expr;
+pub mod grammar;
 mod item;
 pub mod keyword;
 pub mod lex;
@@ -14,6 +15,7 @@ mod scan;
 mod stmt;
 #[cfg(test)]
 mod tests;
+pub mod trivia;
 mod ty;
 
 use crate::item::parse_doc;
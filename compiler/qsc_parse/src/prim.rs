@@ -6,6 +6,7 @@ mod tests;
 
 use super::{keyword::Keyword, scan::ParserContext, ty::ty, Error, Parser, Result};
 use crate::{
+    grammar::Expected,
     item::throw_away_doc,
     lex::{Delim, TokenKind},
     ErrorKind,
@@ -39,6 +40,7 @@ pub(super) fn token(s: &mut ParserContext, t: TokenKind) -> Result<()> {
         s.advance();
         Ok(())
     } else {
+        s.expect(Expected::Token(t));
         Err(Error(ErrorKind::Token(t, s.peek().kind, s.peek().span)))
     }
 }
@@ -54,6 +56,7 @@ pub(super) fn apos_ident(s: &mut ParserContext) -> Result<Box<Ident>> {
             name,
         }))
     } else {
+        s.expect(Expected::Token(TokenKind::AposIdent));
         Err(Error(ErrorKind::Rule(
             "generic parameter",
             peek.kind,
@@ -73,6 +76,7 @@ pub(super) fn ident(s: &mut ParserContext) -> Result<Box<Ident>> {
             name,
         }))
     } else {
+        s.expect(Expected::Token(TokenKind::Ident));
         Err(Error(ErrorKind::Rule("identifier", peek.kind, peek.span)))
     }
 }
@@ -3,6 +3,7 @@
 
 use super::Error;
 use crate::{
+    grammar::Expected,
     lex::{Lexer, Token, TokenKind},
     ErrorKind,
 };
@@ -28,6 +29,10 @@ pub(super) struct Scanner<'a> {
     recovered_eof: bool,
     peek: Token,
     offset: u32,
+    /// What the parser has looked for at the current token since it was peeked.
+    expected: Vec<Expected>,
+    /// What the parser looked for at the first parse error, along with the offset of the error.
+    expected_at_error: Option<(u32, Vec<Expected>)>,
 }
 
 impl<'a> ParserContext<'a> {
@@ -80,6 +85,21 @@ impl<'a> ParserContext<'a> {
         self.scanner.into_errors()
     }
 
+    /// Records that the parser looked for the given token or rule at the current token.
+    pub(super) fn expect(&mut self, expected: Expected) {
+        self.scanner.expect(expected);
+    }
+
+    /// Records the expected tokens and rules for the given error, if it is the first parse error.
+    pub(super) fn record_expected(&mut self, error: &Error) {
+        self.scanner.record_expected(error);
+    }
+
+    /// Returns the offset of the first parse error along with what the parser looked for there.
+    pub(super) fn into_expected_at_error(self) -> Option<(u32, Vec<Expected>)> {
+        self.scanner.expected_at_error
+    }
+
     pub(crate) fn contains_language_feature(&self, feat: LanguageFeatures) -> bool {
         self.language_features.contains(feat)
     }
@@ -100,6 +120,8 @@ impl<'a> Scanner<'a> {
             recovered_eof: false,
             peek: peek.unwrap_or_else(|| eof(input.len())),
             offset: 0,
+            expected: Vec::new(),
+            expected_at_error: None,
         }
     }
 
@@ -125,6 +147,7 @@ impl<'a> Scanner<'a> {
             self.errors
                 .extend(errors.into_iter().map(|e| Error(ErrorKind::Lex(e))));
             self.peek = peek.unwrap_or_else(|| eof(self.input.len()));
+            self.expected.clear();
         }
     }
 
@@ -160,6 +183,7 @@ impl<'a> Scanner<'a> {
     }
 
     pub(super) fn push_error(&mut self, error: Error) {
+        self.record_expected(&error);
         let is_eof_err = matches!(
             error.0,
             ErrorKind::Token(_, TokenKind::Eof, _) | ErrorKind::Rule(_, TokenKind::Eof, _)
@@ -173,6 +197,25 @@ impl<'a> Scanner<'a> {
     pub(super) fn into_errors(self) -> Vec<Error> {
         self.errors
     }
+
+    fn expect(&mut self, expected: Expected) {
+        if !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+
+    fn record_expected(&mut self, error: &Error) {
+        // Only errors about the current token say what was expected in its place.
+        let expected = match error.0 {
+            ErrorKind::Token(token, _, _) => Expected::Token(token),
+            ErrorKind::Rule(rule, _, _) => Expected::Rule(rule),
+            _ => return,
+        };
+        if self.expected_at_error.is_none() {
+            self.expect(expected);
+            self.expected_at_error = Some((self.peek.span.lo, self.expected.clone()));
+        }
+    }
 }
 
 fn eof(offset: usize) -> Token {
@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The comments and whitespace between tokens, which the parser discards. AST nodes only have
+//! spans for their syntax, so tools that edit source, like refactorings and code actions, use the
+//! trivia of a source to find the comments that belong to a node and keep them with it.
+//!
+//! Each token owns the trivia around it. The trailing trivia of a token runs from the end of the
+//! token up to and including the end of its line, and the leading trivia of a token is the rest
+//! of the trivia before it. Together, the tokens and their trivia cover the whole source with no
+//! gaps or overlaps, so the source can be reproduced exactly from them.
+
+#[cfg(test)]
+mod tests;
+
+use crate::lex::concrete::{ConcreteTokenIterator, ConcreteTokenKind};
+use qsc_data_structures::span::Span;
+
+/// The kind of a piece of trivia.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TriviaKind {
+    /// Whitespace, including line breaks.
+    Whitespace,
+    /// A `//` comment, without the line break that ends it. Doc comments are syntax, since they
+    /// are part of the items they document.
+    Comment,
+}
+
+/// A piece of trivia.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Trivium {
+    pub kind: TriviaKind,
+    pub span: Span,
+    /// The number of line breaks in the trivium.
+    pub lines: usize,
+}
+
+/// A token along with the trivia it owns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    /// The span of the token itself.
+    pub span: Span,
+    pub leading: Vec<Trivium>,
+    pub trailing: Vec<Trivium>,
+}
+
+/// The tokens of a source with their trivia.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trivia {
+    tokens: Vec<Token>,
+    /// The trivia after the line of the last token, which no token owns.
+    end: Vec<Trivium>,
+}
+
+impl Trivia {
+    /// Lexes the input and assigns its trivia to its tokens. Spans are byte offsets into the
+    /// input plus the given offset, so that they can be compared with the spans of a package
+    /// compiled from several sources.
+    #[must_use]
+    pub fn new(input: &str, offset: u32) -> Self {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut pending = Vec::new();
+        // Whether the trivia since the last token has reached the end of its line.
+        let mut line_ended = false;
+        for token in ConcreteTokenIterator::new(input) {
+            let span = token.span + offset;
+            let kind = match token.kind {
+                ConcreteTokenKind::Syntax(_) | ConcreteTokenKind::Error(_) => {
+                    tokens.push(Token {
+                        span,
+                        leading: std::mem::take(&mut pending),
+                        trailing: Vec::new(),
+                    });
+                    line_ended = false;
+                    continue;
+                }
+                ConcreteTokenKind::WhiteSpace => TriviaKind::Whitespace,
+                ConcreteTokenKind::Comment => TriviaKind::Comment,
+            };
+
+            let text = &input[token.span];
+            let lines = text.matches('\n').count();
+            let Some(last) = tokens.last_mut().filter(|_| !line_ended) else {
+                pending.push(Trivium { kind, span, lines });
+                continue;
+            };
+            match text.find('\n') {
+                Some(newline) => {
+                    // The line break ends the trailing trivia, and the rest of the whitespace
+                    // leads the next token.
+                    let split = span.lo + u32::try_from(newline + 1).expect("offset should fit");
+                    last.trailing.push(Trivium {
+                        kind,
+                        span: Span {
+                            lo: span.lo,
+                            hi: split,
+                        },
+                        lines: 1,
+                    });
+                    if split < span.hi {
+                        pending.push(Trivium {
+                            kind,
+                            span: Span {
+                                lo: split,
+                                hi: span.hi,
+                            },
+                            lines: lines - 1,
+                        });
+                    }
+                    line_ended = true;
+                }
+                None => last.trailing.push(Trivium { kind, span, lines }),
+            }
+        }
+
+        Self {
+            tokens,
+            end: pending,
+        }
+    }
+
+    /// The tokens of the source in order.
+    #[must_use]
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The trivia after the line of the last token, or all of the trivia if there are no tokens.
+    #[must_use]
+    pub fn end(&self) -> &[Trivium] {
+        &self.end
+    }
+
+    /// Returns the comments that belong to the syntax starting at the offset: the comments on
+    /// the lines directly above it, stopping at the first blank line, in source order.
+    #[must_use]
+    pub fn leading_comments(&self, lo: u32) -> Vec<Span> {
+        let Some(token) = self.token_starting_at(lo) else {
+            return Vec::new();
+        };
+        let mut comments = Vec::new();
+        for trivium in token.leading.iter().rev() {
+            match trivium.kind {
+                TriviaKind::Comment => comments.push(trivium.span),
+                TriviaKind::Whitespace if trivium.lines > 1 => break,
+                TriviaKind::Whitespace => {}
+            }
+        }
+        comments.reverse();
+        comments
+    }
+
+    /// Returns the comment on the same line after the syntax ending at the offset, if there is
+    /// one.
+    #[must_use]
+    pub fn trailing_comment(&self, hi: u32) -> Option<Span> {
+        let index = self
+            .tokens
+            .binary_search_by_key(&hi, |token| token.span.hi)
+            .ok()?;
+        self.tokens[index]
+            .trailing
+            .iter()
+            .find(|trivium| trivium.kind == TriviaKind::Comment)
+            .map(|trivium| trivium.span)
+    }
+
+    /// Extends the span of a syntax node to cover the comments that belong to it, so that an edit
+    /// that moves or removes the node takes its comments along.
+    #[must_use]
+    pub fn with_comments(&self, span: Span) -> Span {
+        let lo = self
+            .leading_comments(span.lo)
+            .first()
+            .map_or(span.lo, |comment| comment.lo);
+        let hi = self
+            .trailing_comment(span.hi)
+            .map_or(span.hi, |comment| comment.hi);
+        Span { lo, hi }
+    }
+
+    fn token_starting_at(&self, lo: u32) -> Option<&Token> {
+        let index = self
+            .tokens
+            .binary_search_by_key(&lo, |token| token.span.lo)
+            .ok()?;
+        Some(&self.tokens[index])
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Trivia, TriviaKind};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::span::Span;
+use std::fmt::Write;
+
+fn render(input: &str, trivia: &Trivia) -> String {
+    let mut output = String::new();
+    for token in trivia.tokens() {
+        let _ = writeln!(
+            output,
+            "{:?} {:?} {:?}",
+            token
+                .leading
+                .iter()
+                .map(|trivium| &input[trivium.span])
+                .collect::<Vec<_>>(),
+            &input[token.span],
+            token
+                .trailing
+                .iter()
+                .map(|trivium| &input[trivium.span])
+                .collect::<Vec<_>>(),
+        );
+    }
+    let _ = write!(
+        output,
+        "end {:?}",
+        trivia
+            .end()
+            .iter()
+            .map(|trivium| &input[trivium.span])
+            .collect::<Vec<_>>()
+    );
+    output
+}
+
+fn find(input: &str, text: &str) -> Span {
+    let lo = u32::try_from(input.find(text).expect("text should be in input"))
+        .expect("offset should fit into u32");
+    Span {
+        lo,
+        hi: lo + u32::try_from(text.len()).expect("length should fit into u32"),
+    }
+}
+
+#[test]
+fn trivia_covers_the_input() {
+    let input = "// a\n\nlet x = 1; // b\n    // c\n  x\n\n// d\n";
+    let trivia = Trivia::new(input, 0);
+    let mut pieces = Vec::new();
+    for token in trivia.tokens() {
+        pieces.extend(token.leading.iter().map(|trivium| trivium.span));
+        pieces.push(token.span);
+        pieces.extend(token.trailing.iter().map(|trivium| trivium.span));
+    }
+    pieces.extend(trivia.end().iter().map(|trivium| trivium.span));
+    let mut offset = 0;
+    for span in pieces {
+        assert_eq!(span.lo, offset);
+        offset = span.hi;
+    }
+    assert_eq!(offset as usize, input.len());
+}
+
+#[test]
+fn trailing_trivia_ends_at_line_break() {
+    let input = "// a\n\nlet x = 1; // b\n    // c\n  x\n\n// d\n";
+    let trivia = Trivia::new(input, 0);
+    expect![[r#"
+        ["// a", "\n\n"] "let" [" "]
+        [] "x" [" "]
+        [] "=" [" "]
+        [] "1" []
+        [] ";" [" ", "// b", "\n"]
+        ["    ", "// c", "\n  "] "x" ["\n"]
+        end ["\n", "// d", "\n"]"#]]
+    .assert_eq(&render(input, &trivia));
+}
+
+#[test]
+fn trivia_kinds_and_lines() {
+    let input = "x; // a\n\n\n// b\ny";
+    let trivia = Trivia::new(input, 0);
+    let leading = &trivia.tokens()[2].leading;
+    assert_eq!(leading.len(), 3);
+    assert_eq!(leading[0].kind, TriviaKind::Whitespace);
+    assert_eq!(leading[0].lines, 2);
+    assert_eq!(leading[1].kind, TriviaKind::Comment);
+    assert_eq!(leading[1].lines, 0);
+}
+
+#[test]
+fn doc_comments_are_syntax() {
+    let input = "/// Doc\nfunction F() : Unit {}";
+    let trivia = Trivia::new(input, 0);
+    assert_eq!(&input[trivia.tokens()[0].span], "/// Doc");
+}
+
+#[test]
+fn spans_include_offset() {
+    let input = "x // a";
+    let trivia = Trivia::new(input, 10);
+    assert_eq!(trivia.tokens()[0].span, Span { lo: 10, hi: 11 });
+    assert_eq!(trivia.trailing_comment(11), Some(Span { lo: 12, hi: 16 }));
+}
+
+#[test]
+fn leading_comments_stop_at_blank_line() {
+    let input = indoc! {"
+        let x = 1;
+        // not attached
+
+        // first
+        // second
+        let y = 2;
+    "};
+    let trivia = Trivia::new(input, 0);
+    let comments = trivia.leading_comments(find(input, "let y").lo);
+    let comments: Vec<_> = comments.iter().map(|span| &input[*span]).collect();
+    assert_eq!(comments, ["// first", "// second"]);
+}
+
+#[test]
+fn leading_comments_exclude_trailing_comment_of_previous_line() {
+    let input = "let x = 1; // x\nlet y = 2;";
+    let trivia = Trivia::new(input, 0);
+    assert!(trivia.leading_comments(find(input, "let y").lo).is_empty());
+}
+
+#[test]
+fn leading_comments_at_start_of_input() {
+    let input = "// first\nlet x = 1;";
+    let trivia = Trivia::new(input, 0);
+    assert_eq!(
+        trivia.leading_comments(find(input, "let").lo),
+        [find(input, "// first")]
+    );
+}
+
+#[test]
+fn no_comments_inside_token() {
+    let input = "let x = 1; // x";
+    let trivia = Trivia::new(input, 0);
+    assert!(trivia.leading_comments(find(input, "et").lo).is_empty());
+    assert_eq!(trivia.trailing_comment(find(input, "le").hi), None);
+}
+
+#[test]
+fn trailing_comment_on_same_line() {
+    let input = "let x = 1; // x\n// next\nlet y = 2;";
+    let trivia = Trivia::new(input, 0);
+    assert_eq!(
+        trivia.trailing_comment(find(input, "let x = 1;").hi),
+        Some(find(input, "// x"))
+    );
+    assert_eq!(trivia.trailing_comment(find(input, "let y = 2;").hi), None);
+}
+
+#[test]
+fn with_comments_extends_span() {
+    let input = indoc! {"
+        {
+            // about x
+            let x = 1; // one
+            let y = 2;
+        }
+    "};
+    let trivia = Trivia::new(input, 0);
+    let span = trivia.with_comments(find(input, "let x = 1;"));
+    assert_eq!(&input[span], "// about x\n    let x = 1; // one");
+    let span = trivia.with_comments(find(input, "let y = 2;"));
+    assert_eq!(&input[span], "let y = 2;");
+}
@@ -1298,20 +1298,20 @@ impl<'a> PartialEvaluator<'a> {
             "BeginEstimateCaching" => Ok(Value::Bool(true)),
             "DumpRegister"
             | "AccountForEstimatesInternal"
+            | "AccountForArithmeticInternal"
             | "BeginRepeatEstimatesInternal"
             | "EndRepeatEstimatesInternal"
             | "GlobalPhase" => Ok(Value::unit()),
             // The following intrinsic functions and operations should never make it past conditional compilation and
             // the capabilities check pass.
-            "CheckZero" | "DrawRandomInt" | "DrawRandomDouble" | "DrawRandomBool" | "Length" => {
-                Err(Error::Unexpected(
-                    format!(
-                        "`{}` is not a supported by partial evaluation",
-                        callable_decl.name.name
-                    ),
-                    callee_expr_span,
-                ))
-            }
+            "CheckZero" | "DrawRandomInt" | "DrawRandomDouble" | "DrawRandomBool" | "Length"
+            | "ReadMonotonicTimer" => Err(Error::Unexpected(
+                format!(
+                    "`{}` is not a supported by partial evaluation",
+                    callable_decl.name.name
+                ),
+                callee_expr_span,
+            )),
             _ => self.eval_expr_call_to_intrinsic_qis(
                 store_item_id,
                 callable_decl,
@@ -105,7 +105,9 @@ impl Backend for QuantumIntrinsicsChecker {
     ) -> Option<std::result::Result<Value, String>> {
         match name {
             "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
-            "EndEstimateCaching" | "GlobalPhase" => Some(Ok(Value::unit())),
+            "EndEstimateCaching" | "AccountForArithmeticInternal" | "GlobalPhase" => {
+                Some(Ok(Value::unit()))
+            }
             _ => None,
         }
     }
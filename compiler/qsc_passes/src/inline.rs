@@ -0,0 +1,203 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::id_update::NodeIdRefresher;
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{
+        Attr, Block, CallableDecl, Expr, ExprKind, ItemId, ItemKind, LocalItemId, Mutability,
+        Package, Pat, Res, SpecBody, Stmt, StmtKind,
+    },
+    mut_visit::{self, MutVisitor},
+    ty::Ty,
+    visit::{self, Visitor},
+};
+use rustc_hash::FxHashMap;
+
+/// The limits applied when inlining callables marked with `@Inline()`.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct InlineConfig {
+    /// The maximum number of statements and expressions in a callable body for it to be inlined.
+    pub(super) max_size: usize,
+    /// The maximum number of nested inline expansions performed at a single call site.
+    pub(super) max_depth: usize,
+}
+
+impl Default for InlineConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 64,
+            max_depth: 8,
+        }
+    }
+}
+
+/// Replaces calls to callables marked with `@Inline()` with the body of the callee,
+/// updating the package in-place.
+///
+/// `@Inline()` is a hint: callables that are too large, generic, intrinsic, contain
+/// `return` expressions or nested item declarations are left as regular calls. Only callables
+/// declared in the same package are inlined, and recursive expansion stops once a callable
+/// is already being expanded at the current call site.
+pub(super) fn inline_calls(package: &mut Package, assigner: &mut Assigner, config: InlineConfig) {
+    let candidates = collect_candidates(package, config);
+    if candidates.is_empty() {
+        return;
+    }
+
+    Inliner {
+        assigner,
+        candidates: &candidates,
+        config,
+        stack: Vec::new(),
+    }
+    .visit_package(package);
+}
+
+struct Candidate {
+    input: Pat,
+    body: Block,
+}
+
+fn collect_candidates(
+    package: &Package,
+    config: InlineConfig,
+) -> FxHashMap<LocalItemId, Candidate> {
+    package
+        .items
+        .iter()
+        .filter(|(_, item)| {
+            item.attrs.contains(&Attr::Inline) && !item.attrs.contains(&Attr::SimulatableIntrinsic)
+        })
+        .filter_map(|(id, item)| match &item.kind {
+            ItemKind::Callable(decl) => candidate(decl, config).map(|c| (id, c)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn candidate(decl: &CallableDecl, config: InlineConfig) -> Option<Candidate> {
+    if !decl.generics.is_empty() {
+        return None;
+    }
+
+    let SpecBody::Impl(None, block) = &decl.body.body else {
+        return None;
+    };
+
+    let mut size = SizeCounter::default();
+    size.visit_block(block);
+    if size.has_return || size.has_item || size.size > config.max_size {
+        return None;
+    }
+
+    Some(Candidate {
+        input: decl.input.clone(),
+        body: block.clone(),
+    })
+}
+
+#[derive(Default)]
+struct SizeCounter {
+    size: usize,
+    has_return: bool,
+    has_item: bool,
+}
+
+impl Visitor<'_> for SizeCounter {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.size += 1;
+        if let StmtKind::Item(_) = stmt.kind {
+            self.has_item = true;
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.size += 1;
+        if let ExprKind::Return(_) = expr.kind {
+            self.has_return = true;
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+struct Inliner<'a> {
+    assigner: &'a mut Assigner,
+    candidates: &'a FxHashMap<LocalItemId, Candidate>,
+    config: InlineConfig,
+    stack: Vec<LocalItemId>,
+}
+
+impl Inliner<'_> {
+    fn inline_target(&self, expr: &Expr) -> Option<LocalItemId> {
+        let ExprKind::Call(callee, _) = &expr.kind else {
+            return None;
+        };
+        let ExprKind::Var(
+            Res::Item(ItemId {
+                package: None,
+                item,
+            }),
+            generics,
+        ) = &callee.kind
+        else {
+            return None;
+        };
+
+        (generics.is_empty()
+            && self.candidates.contains_key(item)
+            && !self.stack.contains(item)
+            && self.stack.len() < self.config.max_depth)
+            .then_some(*item)
+    }
+
+    fn expand(&mut self, item: LocalItemId, ty: Ty, span: Span, arg: Expr) -> Block {
+        let candidate = self
+            .candidates
+            .get(&item)
+            .expect("inline candidate should exist");
+        let mut input = candidate.input.clone();
+        let mut body = candidate.body.clone();
+
+        let mut refresher = NodeIdRefresher::new(self.assigner);
+        refresher.visit_pat(&mut input);
+        refresher.visit_block(&mut body);
+
+        // Calls in the copied body may themselves be inlined, so expand them with
+        // this callable on the stack to guard against unbounded recursion.
+        self.stack.push(item);
+        self.visit_block(&mut body);
+        self.stack.pop();
+
+        body.stmts.insert(
+            0,
+            Stmt {
+                id: self.assigner.next_node(),
+                span: Span::default(),
+                kind: StmtKind::Local(Mutability::Immutable, input, arg),
+            },
+        );
+        body.ty = ty;
+        body.span = span;
+        body
+    }
+}
+
+impl MutVisitor for Inliner<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        mut_visit::walk_expr(self, expr);
+
+        if let Some(item) = self.inline_target(expr) {
+            let ExprKind::Call(_, arg) = std::mem::replace(&mut expr.kind, ExprKind::Err) else {
+                unreachable!("inline target should be a call expression");
+            };
+            let block = self.expand(item, expr.ty.clone(), expr.span, *arg);
+            expr.kind = ExprKind::Block(block);
+        }
+    }
+}
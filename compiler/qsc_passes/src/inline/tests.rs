@@ -0,0 +1,207 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{self, compile, PackageStore, SourceMap};
+use qsc_hir::{
+    hir::{Expr, ExprKind, ItemKind, Package, Res},
+    validate::Validator,
+    visit::{self, Visitor},
+};
+
+use crate::inline::{inline_calls, InlineConfig};
+
+struct CallCounter<'a> {
+    package: &'a Package,
+    name: &'a str,
+    count: usize,
+}
+
+impl<'a> Visitor<'a> for CallCounter<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Call(callee, _) = &expr.kind {
+            if let ExprKind::Var(Res::Item(id), _) = &callee.kind {
+                if let Some(ItemKind::Callable(decl)) =
+                    self.package.items.get(id.item).map(|i| &i.kind)
+                {
+                    if id.package.is_none() && decl.name.name.as_ref() == self.name {
+                        self.count += 1;
+                    }
+                }
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn check_calls(file: &str, config: InlineConfig, name: &str, expected: usize) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    inline_calls(&mut unit.package, &mut unit.assigner, config);
+    Validator::default().visit_package(&unit.package);
+
+    let mut counter = CallCounter {
+        package: &unit.package,
+        name,
+        count: 0,
+    };
+    counter.visit_package(&unit.package);
+    assert_eq!(counter.count, expected, "{}", unit.package);
+}
+
+#[test]
+fn inline_attr_call_is_replaced() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                function Double(x : Int) : Int { x * 2 }
+                function Main() : Int {
+                    Double(1) + Double(2)
+                }
+            }
+        "},
+        InlineConfig::default(),
+        "Double",
+        0,
+    );
+}
+
+#[test]
+fn call_without_attr_is_not_replaced() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                function Double(x : Int) : Int { x * 2 }
+                function Main() : Int {
+                    Double(1) + Double(2)
+                }
+            }
+        "},
+        InlineConfig::default(),
+        "Double",
+        2,
+    );
+}
+
+#[test]
+fn nested_inline_calls_are_replaced() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                operation Flip(q : Qubit) : Unit { X(q); }
+                @Inline()
+                operation FlipTwice(q : Qubit) : Unit { Flip(q); Flip(q); }
+                operation X(q : Qubit) : Unit { body intrinsic; }
+                operation Main() : Unit {
+                    use q = Qubit();
+                    FlipTwice(q);
+                }
+            }
+        "},
+        InlineConfig::default(),
+        "Flip",
+        0,
+    );
+}
+
+#[test]
+fn recursive_inline_callable_is_expanded_once() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                function Count(n : Int) : Int {
+                    if n <= 0 { 0 } else { 1 + Count(n - 1) }
+                }
+                function Main() : Int {
+                    Count(3)
+                }
+            }
+        "},
+        InlineConfig::default(),
+        "Count",
+        2,
+    );
+}
+
+#[test]
+fn callable_with_return_is_not_inlined() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                function Abs(x : Int) : Int {
+                    if x < 0 { return -x; }
+                    x
+                }
+                function Main() : Int {
+                    Abs(-1)
+                }
+            }
+        "},
+        InlineConfig::default(),
+        "Abs",
+        1,
+    );
+}
+
+#[test]
+fn callable_over_size_limit_is_not_inlined() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                function Sum(x : Int) : Int {
+                    let a = x + 1;
+                    let b = a + 2;
+                    let c = b + 3;
+                    c
+                }
+                function Main() : Int {
+                    Sum(1)
+                }
+            }
+        "},
+        InlineConfig {
+            max_size: 4,
+            max_depth: 8,
+        },
+        "Sum",
+        1,
+    );
+}
+
+#[test]
+fn expansion_stops_at_max_depth() {
+    check_calls(
+        indoc! {"
+            namespace Test {
+                @Inline()
+                function A() : Int { B() + 1 }
+                @Inline()
+                function B() : Int { 1 }
+                function Main() : Int {
+                    A()
+                }
+            }
+        "},
+        InlineConfig {
+            max_size: 64,
+            max_depth: 1,
+        },
+        "B",
+        1,
+    );
+}
@@ -8,11 +8,13 @@ mod common;
 mod conjugate_invert;
 mod entry_point;
 mod id_update;
+mod inline;
 mod invert_block;
 mod logic_sep;
 mod loop_unification;
 mod replace_qubit_allocation;
 mod spec_gen;
+mod warnings;
 
 use callable_limits::CallableLimits;
 use capabilitiesck::{check_supported_capabilities, lower_store, run_rca_pass};
@@ -21,7 +23,7 @@ use loop_unification::LoopUni;
 use miette::Diagnostic;
 use qsc_data_structures::target::TargetCapabilityFlags;
 use qsc_fir::fir;
-use qsc_frontend::compile::CompileUnit;
+use qsc_frontend::{compile::CompileUnit, resolve::Redefinition};
 use qsc_hir::{
     assigner::Assigner,
     global::{self, Table},
@@ -35,6 +37,8 @@ use qsc_rca::{PackageComputeProperties, PackageStoreComputeProperties};
 use replace_qubit_allocation::ReplaceQubitAllocation;
 use thiserror::Error;
 
+pub use warnings::Warning;
+
 pub(crate) static CORE_NAMESPACE: &[&str] = &["Microsoft", "Quantum", "Core"];
 pub(crate) static QIR_RUNTIME_NAMESPACE: &[&str] = &["QIR", "Runtime"];
 
@@ -68,6 +72,7 @@ pub fn lower_hir_to_fir(
 
 pub struct PassContext {
     borrow_check: borrowck::Checker,
+    warnings: Vec<Warning>,
 }
 
 impl Default for PassContext {
@@ -81,10 +86,12 @@ impl PassContext {
     pub fn new() -> Self {
         Self {
             borrow_check: borrowck::Checker::default(),
+            warnings: Vec::new(),
         }
     }
 
-    /// Run the default set of passes required for evaluation.
+    /// Run the default set of passes required for evaluation. The warnings for the package are
+    /// kept until they are taken with [`PassContext::take_warnings`].
     pub fn run_default_passes(
         &mut self,
         package: &mut Package,
@@ -92,6 +99,8 @@ impl PassContext {
         core: &Table,
         package_type: PackageType,
     ) -> Vec<Error> {
+        self.warnings.extend(warnings::check(package));
+
         let mut call_limits = CallableLimits::default();
         call_limits.visit_package(package);
         let callable_errors = call_limits.errors;
@@ -105,6 +114,9 @@ impl PassContext {
         let conjugate_errors = conjugate_invert::invert_conjugate_exprs(core, package, assigner);
         Validator::default().visit_package(package);
 
+        inline::inline_calls(package, assigner, inline::InlineConfig::default());
+        Validator::default().visit_package(package);
+
         let entry_point_errors = if package_type == PackageType::Exe {
             let entry_point_errors = generate_entry_expr(package, assigner);
             Validator::default().visit_package(package);
@@ -129,6 +141,19 @@ impl PassContext {
             .collect()
     }
 
+    /// Checks the callables that are already in the package for uses of the callables that an
+    /// increment redefined. The warnings are kept with those of the default passes.
+    pub fn check_redefinitions(&mut self, package: &Package, redefinitions: &[Redefinition]) {
+        self.warnings
+            .extend(warnings::check_redefinitions(package, redefinitions));
+    }
+
+    /// Takes the warnings for the packages that the default passes have run on since the
+    /// warnings were last taken.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     pub fn run_fir_passes_on_fir(
         fir_store: &qsc_fir::fir::PackageStore,
         package_id: qsc_fir::fir::PackageId,
@@ -147,6 +172,24 @@ pub fn run_default_passes(
     PassContext::new().run_default_passes(&mut unit.package, &mut unit.assigner, core, package_type)
 }
 
+/// Run the passes that only analyze the package, without transforming it. Unlike the default
+/// passes, these can run on a package that has compilation errors.
+#[must_use]
+pub fn run_analysis_passes(unit: &CompileUnit) -> Vec<Error> {
+    let mut call_limits = CallableLimits::default();
+    call_limits.visit_package(&unit.package);
+
+    let mut borrow_check = borrowck::Checker::default();
+    borrow_check.visit_package(&unit.package);
+
+    call_limits
+        .errors
+        .into_iter()
+        .map(Error::CallableLimits)
+        .chain(borrow_check.errors.into_iter().map(Error::BorrowCk))
+        .collect()
+}
+
 pub fn run_core_passes(core: &mut CompileUnit) -> Vec<Error> {
     let mut borrow_check = borrowck::Checker::default();
     borrow_check.visit_package(&core.package);
@@ -0,0 +1,207 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_frontend::resolve::Redefinition;
+use qsc_hir::{
+    hir::{
+        Block, Expr, ExprKind, Ident, ItemKind, LocalItemId, NodeId, Package, Pat, PatKind, Res,
+        Stmt, StmtKind,
+    },
+    visit::{walk_block, walk_expr, walk_stmt, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// A diagnostic about code that compiles and runs, but is likely to be a mistake. Unlike an
+/// [`crate::Error`], a warning does not stop compilation.
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Warning {
+    #[error("unused variable `{0}`")]
+    #[diagnostic(help("if this is intentional, prefix the name with an underscore: `_{0}`"))]
+    #[diagnostic(code("Qsc.Warning.UnusedVariable"))]
+    #[diagnostic(severity(Warning))]
+    UnusedVariable(String, #[label] Span),
+
+    #[error("unreachable code")]
+    #[diagnostic(code("Qsc.Warning.UnreachableCode"))]
+    #[diagnostic(severity(Warning))]
+    UnreachableCode(
+        #[label("this code is never run")] Span,
+        #[label("because this expression always exits the block")] Span,
+    ),
+
+    #[error("`{1}` still uses the previous definition of `{0}`")]
+    #[diagnostic(help("declare `{1}` again to use the new definition of `{0}`"))]
+    #[diagnostic(code("Qsc.Warning.StaleReference"))]
+    #[diagnostic(severity(Warning))]
+    StaleReference(String, String, #[label] Span),
+}
+
+/// Checks the package for warnings. This must run before the passes that transform the package,
+/// since they introduce variables and statements of their own.
+///
+/// Variables bound by the top-level statements of the package are not checked for uses, since
+/// later fragments of an interactive session can still use them.
+#[must_use]
+pub fn check(package: &Package) -> Vec<Warning> {
+    let mut checker = Checker::default();
+    package
+        .items
+        .values()
+        .for_each(|item| checker.visit_item(item));
+    for stmt in &package.stmts {
+        checker.top_level = true;
+        checker.visit_stmt(stmt);
+    }
+    package
+        .entry
+        .iter()
+        .for_each(|entry| checker.visit_expr(entry));
+    checker.check_stmts(&package.stmts);
+    checker.into_warnings()
+}
+
+/// Checks the callables of the package for uses of the callables that were redefined by a later
+/// increment, since those uses are not updated to the new definitions.
+#[must_use]
+pub fn check_redefinitions(package: &Package, redefinitions: &[Redefinition]) -> Vec<Warning> {
+    let redefined = redefinitions
+        .iter()
+        .map(|redefinition| (redefinition.old.item, Rc::clone(&redefinition.name)))
+        .collect::<FxHashMap<_, _>>();
+    let mut warnings = Vec::new();
+    for item in package.items.values() {
+        if let ItemKind::Callable(decl) = &item.kind {
+            if !redefined.contains_key(&item.id) {
+                RedefinitionChecker {
+                    redefined: &redefined,
+                    caller: &decl.name.name,
+                    warnings: &mut warnings,
+                }
+                .visit_item(item);
+            }
+        }
+    }
+    warnings
+}
+
+struct RedefinitionChecker<'a> {
+    redefined: &'a FxHashMap<LocalItemId, Rc<str>>,
+    caller: &'a str,
+    warnings: &'a mut Vec<Warning>,
+}
+
+impl Visitor<'_> for RedefinitionChecker<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Var(Res::Item(id), _) = &expr.kind {
+            if let Some(name) = self
+                .redefined
+                .get(&id.item)
+                .filter(|_| id.package.is_none())
+            {
+                let warning =
+                    Warning::StaleReference(name.to_string(), self.caller.to_string(), expr.span);
+                // Generated specializations copy the body, so the same use can be found again.
+                if !self.warnings.contains(&warning) {
+                    self.warnings.push(warning);
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    top_level: bool,
+    bindings: Vec<Ident>,
+    used: FxHashSet<NodeId>,
+    warnings: Vec<Warning>,
+}
+
+impl Checker {
+    fn bind(&mut self, pat: &Pat) {
+        match &pat.kind {
+            PatKind::Bind(ident) => {
+                if !ident.name.starts_with('_') {
+                    self.bindings.push(ident.clone());
+                }
+            }
+            PatKind::Discard | PatKind::Err => {}
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind(pat)),
+        }
+    }
+
+    /// Reports the statements that follow the first statement that always exits the block.
+    fn check_stmts(&mut self, stmts: &[Stmt]) {
+        let exit = stmts.iter().position(|stmt| match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => {
+                matches!(expr.kind, ExprKind::Return(_) | ExprKind::Fail(_))
+            }
+            _ => false,
+        });
+        if let Some(exit) = exit {
+            if let (Some(first), Some(last)) = (stmts.get(exit + 1), stmts.last()) {
+                self.warnings.push(Warning::UnreachableCode(
+                    Span {
+                        lo: first.span.lo,
+                        hi: last.span.hi,
+                    },
+                    stmts[exit].span,
+                ));
+            }
+        }
+    }
+
+    fn into_warnings(mut self) -> Vec<Warning> {
+        let used = &self.used;
+        let mut warnings = self
+            .bindings
+            .iter()
+            .filter(|ident| !used.contains(&ident.id))
+            .map(|ident| Warning::UnusedVariable(ident.name.to_string(), ident.span))
+            .collect::<Vec<_>>();
+        warnings.append(&mut self.warnings);
+        warnings.sort_by_key(|warning| match warning {
+            Warning::UnusedVariable(_, span)
+            | Warning::UnreachableCode(span, _)
+            | Warning::StaleReference(_, _, span) => span.lo,
+        });
+        warnings
+    }
+}
+
+impl Visitor<'_> for Checker {
+    fn visit_block(&mut self, block: &Block) {
+        self.check_stmts(&block.stmts);
+        walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        let top_level = std::mem::take(&mut self.top_level);
+        match &stmt.kind {
+            StmtKind::Local(_, pat, _) | StmtKind::Qubit(_, pat, _, _) if !top_level => {
+                self.bind(pat);
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Var(Res::Local(id), _) => {
+                self.used.insert(*id);
+            }
+            ExprKind::Closure(captures, _) => self.used.extend(captures.iter().copied()),
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
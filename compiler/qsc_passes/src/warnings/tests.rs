@@ -0,0 +1,168 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
+use qsc_frontend::compile::{self, compile, PackageStore, SourceMap};
+
+use super::{check, Warning};
+
+fn check_warnings(source: &str, expect: &Expect) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile(
+        &store,
+        &[],
+        sources,
+        TargetCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let snippet = |span: qsc_data_structures::span::Span| {
+        &source[usize::try_from(span.lo).expect("offset should fit")
+            ..usize::try_from(span.hi).expect("offset should fit")]
+    };
+    let warnings = check(&unit.package)
+        .into_iter()
+        .map(|warning| match &warning {
+            Warning::UnusedVariable(_, span) | Warning::StaleReference(_, _, span) => {
+                format!("{warning}: `{}`", snippet(*span))
+            }
+            Warning::UnreachableCode(span, exit) => {
+                format!("{warning}: `{}` after `{}`", snippet(*span), snippet(*exit))
+            }
+        })
+        .collect::<Vec<_>>();
+    expect.assert_debug_eq(&warnings);
+}
+
+#[test]
+fn used_variables_have_no_warnings() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                operation Apply(q : Qubit) : Unit {}
+                operation Main() : Int {
+                    mutable count = 0;
+                    let step = 2;
+                    use q = Qubit();
+                    Apply(q);
+                    set count += 1;
+                    let f = () -> step;
+                    count + f()
+                }
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn unused_variables_are_reported() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                function Main() : Int {
+                    let (a, b) = (1, 2);
+                    let _c = 3;
+                    let d = { let e = 4; 5 };
+                    a
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                "unused variable `b`: `b`",
+                "unused variable `d`: `d`",
+                "unused variable `e`: `e`",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn unused_qubits_are_reported() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                operation Apply(q : Qubit) : Unit {}
+                operation Main() : Unit {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    Apply(q0);
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                "unused variable `q1`: `q1`",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn code_after_return_is_unreachable() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                function Main(x : Int) : Int {
+                    if x > 0 {
+                        return 1;
+                        let y = 2;
+                        y;
+                    }
+                    x
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                "unreachable code: `let y = 2;\n            y;` after `return 1;`",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn code_after_fail_is_unreachable() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                function Main() : Int {
+                    fail \"not implemented\";
+                    0
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                "unreachable code: `0` after `fail \"not implemented\";`",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn return_at_the_end_of_a_block_is_reachable() {
+    check_warnings(
+        indoc! {"
+            namespace Test {
+                function Main(x : Int) : Int {
+                    if x > 0 {
+                        return 1;
+                    }
+                    return x;
+                }
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
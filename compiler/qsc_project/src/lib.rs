@@ -12,14 +12,18 @@ mod error;
 #[cfg(feature = "fs")]
 mod fs;
 mod js;
+mod loader;
 mod manifest;
+mod memory;
 mod project;
 
 pub use error::StdFsError;
 #[cfg(feature = "fs")]
 pub use fs::StdFs;
 pub use js::{JSFileEntry, JSProjectHost};
+pub use loader::{Change, ChangeKind, SourceLoader, Watch};
 pub use manifest::{Manifest, ManifestDescriptor, PackageRef, MANIFEST_FILE_NAME};
+pub use memory::{MemoryEntry, MemoryFs, OverlayFs};
 pub use project::FileSystemAsync;
 pub use project::{
     key_for_package_ref, package_ref_from_key, DirEntry, EntryType, Error, FileSystem,
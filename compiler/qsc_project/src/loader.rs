@@ -0,0 +1,151 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! This module contains [`SourceLoader`], the interface that hosts use to load the sources of
+//! projects and to find out when they change.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{Error, FileSystem, Project};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+/// Loads the sources of Q# projects. Every [`FileSystem`] is a source loader, including the
+/// in-memory [`crate::MemoryFs`] and [`crate::OverlayFs`]. Unlike [`FileSystem`], this trait has
+/// no associated types, so hosts can keep a `dyn SourceLoader` without knowing which filesystem
+/// it is backed by.
+pub trait SourceLoader {
+    /// Reads a file, returning its name and contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    fn read_source(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)>;
+
+    /// Loads the project in the directory, with the sources of its dependencies.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors from loading the manifest or the sources.
+    fn list_project(&self, directory: &Path) -> Result<Project, Vec<Error>>;
+
+    /// Starts watching the files of the project in the directory. See [`Watch`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors from loading the project.
+    fn watch(&self, directory: &Path) -> Result<Watch, Vec<Error>>;
+}
+
+impl<F: FileSystem> SourceLoader for F {
+    fn read_source(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        self.read_file(path)
+    }
+
+    fn list_project(&self, directory: &Path) -> Result<Project, Vec<Error>> {
+        self.load_project(directory, None)
+    }
+
+    fn watch(&self, directory: &Path) -> Result<Watch, Vec<Error>> {
+        let files = snapshot(self, directory)?;
+        Ok(Watch {
+            directory: directory.into(),
+            files,
+        })
+    }
+}
+
+/// How a watched file changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// A change to a file of a watched project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub path: Arc<str>,
+    pub kind: ChangeKind,
+}
+
+/// The files of a project, including its manifest and the sources of its dependencies, as they
+/// were when they were last polled. Watching does not need support from the filesystem: each
+/// poll loads the project again and compares the files, so it works the same for every
+/// [`SourceLoader`].
+#[derive(Debug)]
+pub struct Watch {
+    directory: Box<Path>,
+    files: BTreeMap<Arc<str>, Arc<str>>,
+}
+
+impl Watch {
+    /// Loads the project again and returns the files that were added, modified or removed since
+    /// the last poll, in order of their paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors from loading the project. The files of the last successful poll are
+    /// kept, so a later poll reports the changes since then.
+    pub fn poll(&mut self, loader: &dyn SourceLoader) -> Result<Vec<Change>, Vec<Error>> {
+        let files = snapshot(loader, &self.directory)?;
+        let mut changes = Vec::new();
+        for (path, contents) in &files {
+            match self.files.get(path) {
+                None => changes.push(Change {
+                    path: path.clone(),
+                    kind: ChangeKind::Added,
+                }),
+                Some(old) if old != contents => changes.push(Change {
+                    path: path.clone(),
+                    kind: ChangeKind::Modified,
+                }),
+                Some(_) => {}
+            }
+        }
+        changes.extend(
+            self.files
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .map(|path| Change {
+                    path: path.clone(),
+                    kind: ChangeKind::Removed,
+                }),
+        );
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        self.files = files;
+        Ok(changes)
+    }
+
+    /// The paths of the watched files, in order.
+    #[must_use]
+    pub fn paths(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.files.keys()
+    }
+}
+
+fn snapshot(
+    loader: &(impl SourceLoader + ?Sized),
+    directory: &Path,
+) -> Result<BTreeMap<Arc<str>, Arc<str>>, Vec<Error>> {
+    let project = loader.list_project(directory)?;
+    let manifest = loader
+        .read_source(Path::new(project.path.as_ref()))
+        .map_err(|e| {
+            vec![Error::FileSystem {
+                about_path: project.path.to_string(),
+                error: e.to_string(),
+            }]
+        })?;
+    let graph = project.package_graph_sources;
+    Ok(std::iter::once(manifest)
+        .chain(graph.root.sources)
+        .chain(
+            graph
+                .packages
+                .into_values()
+                .flat_map(|package| package.sources),
+        )
+        .collect())
+}
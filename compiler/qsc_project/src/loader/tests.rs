@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Change, ChangeKind, SourceLoader, Watch};
+use crate::{MemoryFs, OverlayFs};
+use std::path::Path;
+
+fn loader() -> OverlayFs<MemoryFs> {
+    let mut fs = MemoryFs::new();
+    fs.insert("/project/qsharp.json", "{}");
+    fs.insert("/project/src/Main.qs", "namespace Main {}");
+    fs.insert("/project/src/Other.qs", "namespace Other {}");
+    OverlayFs::new(fs)
+}
+
+fn change(path: &str, kind: ChangeKind) -> Change {
+    Change {
+        path: path.into(),
+        kind,
+    }
+}
+
+fn poll(watch: &mut Watch, loader: &dyn SourceLoader) -> Vec<Change> {
+    watch.poll(loader).expect("poll should succeed")
+}
+
+#[test]
+fn loaders_can_be_used_without_knowing_the_filesystem() {
+    let loader = loader();
+    let loader: &dyn SourceLoader = &loader;
+    let project = loader
+        .list_project(Path::new("/project"))
+        .expect("project should load");
+    assert_eq!(project.package_graph_sources.root.sources.len(), 2);
+    let (_, contents) = loader
+        .read_source(Path::new("/project/src/Other.qs"))
+        .expect("file should be read");
+    assert_eq!(contents.as_ref(), "namespace Other {}");
+}
+
+#[test]
+fn watch_reports_changes_since_the_last_poll() {
+    let mut loader = loader();
+    let mut watch = loader
+        .watch(Path::new("/project"))
+        .expect("project should be watched");
+    assert_eq!(
+        watch.paths().map(AsRef::as_ref).collect::<Vec<&str>>(),
+        [
+            "/project/qsharp.json",
+            "/project/src/Main.qs",
+            "/project/src/Other.qs"
+        ]
+    );
+    assert_eq!(poll(&mut watch, &loader), []);
+
+    loader.insert("/project/src/Main.qs", "namespace Edited {}");
+    loader.insert("/project/src/Added.qs", "namespace Added {}");
+    loader.remove("/project/src/Other.qs");
+    assert_eq!(
+        poll(&mut watch, &loader),
+        [
+            change("/project/src/Added.qs", ChangeKind::Added),
+            change("/project/src/Main.qs", ChangeKind::Modified),
+            change("/project/src/Other.qs", ChangeKind::Removed),
+        ]
+    );
+    assert_eq!(poll(&mut watch, &loader), []);
+}
+
+#[test]
+fn failed_poll_keeps_the_previous_files() {
+    let mut loader = loader();
+    let mut watch = loader
+        .watch(Path::new("/project"))
+        .expect("project should be watched");
+
+    loader.insert("/project/qsharp.json", "not json");
+    assert!(watch.poll(&loader).is_err());
+
+    loader.insert("/project/qsharp.json", "{ }");
+    assert_eq!(
+        poll(&mut watch, &loader),
+        [change("/project/qsharp.json", ChangeKind::Modified)]
+    );
+}
@@ -30,6 +30,8 @@ pub struct Manifest {
     pub dependencies: FxHashMap<String, PackageRef>,
     #[serde(default)]
     pub files: Vec<String>,
+    #[serde(default)]
+    pub no_stdlib: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -0,0 +1,222 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! This module contains a project implementation that keeps its files in memory, for hosts
+//! such as WASM and language servers that provide sources without a real filesystem, and an
+//! overlay that puts in-memory files over another filesystem, such as for unsaved editor
+//! buffers.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{project::normalize_path, DirEntry, EntryType, FileSystem};
+use miette::miette;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// A file or folder listed by [`MemoryFs`] or [`OverlayFs`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MemoryEntry {
+    path: PathBuf,
+    is_folder: bool,
+}
+
+impl DirEntry for MemoryEntry {
+    type Error = Infallible;
+
+    fn entry_type(&self) -> Result<EntryType, Self::Error> {
+        Ok(if self.is_folder {
+            EntryType::Folder
+        } else {
+            EntryType::File
+        })
+    }
+
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// A filesystem whose files are kept in memory. Folders are not stored, they exist as long as
+/// there are files in them.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFs {
+    files: BTreeMap<PathBuf, Arc<str>>,
+}
+
+impl MemoryFs {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, replacing any file with the same path.
+    pub fn insert(&mut self, path: impl AsRef<Path>, contents: impl Into<Arc<str>>) {
+        self.files
+            .insert(normalize_path(path.as_ref()), contents.into());
+    }
+
+    /// Removes a file, returning its contents.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<Arc<str>> {
+        self.files.remove(&normalize_path(path.as_ref()))
+    }
+
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize_path(path))
+    }
+
+    fn entries(&self, path: &Path) -> BTreeSet<MemoryEntry> {
+        let path = normalize_path(path);
+        self.files
+            .keys()
+            .filter_map(|file| {
+                let mut rest = file.strip_prefix(&path).ok()?.components();
+                let name = rest.next()?;
+                Some(MemoryEntry {
+                    path: path.join(name),
+                    is_folder: rest.next().is_some(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl FileSystem for MemoryFs {
+    type Entry = MemoryEntry;
+
+    fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        let contents = self
+            .files
+            .get(&normalize_path(path))
+            .ok_or_else(|| miette!("could not read source file `{}`", path.display()))?;
+        Ok((path.to_string_lossy().into(), contents.clone()))
+    }
+
+    fn list_directory(&self, path: &Path) -> miette::Result<Vec<MemoryEntry>> {
+        let entries = self.entries(path);
+        if entries.is_empty() {
+            return Err(miette!("could not read directory `{}`", path.display()));
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    fn resolve_path(&self, base: &Path, path: &Path) -> miette::Result<PathBuf> {
+        Ok(normalize_path(&base.join(path)))
+    }
+
+    fn fetch_github(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _ref: &str,
+        _path: &str,
+    ) -> miette::Result<Arc<str>> {
+        Err(miette!(
+            "github references not supported for this file system"
+        ))
+    }
+}
+
+/// A filesystem that puts the files of a [`MemoryFs`] over another filesystem. Files in the
+/// overlay shadow the files of the base with the same path, and files removed from the overlay
+/// are hidden even if the base has them.
+pub struct OverlayFs<F> {
+    base: F,
+    overlay: MemoryFs,
+    removed: BTreeSet<PathBuf>,
+}
+
+impl<F: FileSystem> OverlayFs<F> {
+    #[must_use]
+    pub fn new(base: F) -> Self {
+        Self {
+            base,
+            overlay: MemoryFs::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn base(&self) -> &F {
+        &self.base
+    }
+
+    /// Puts a file over the base filesystem, replacing any earlier overlay of the same path.
+    pub fn insert(&mut self, path: impl AsRef<Path>, contents: impl Into<Arc<str>>) {
+        self.removed.remove(&normalize_path(path.as_ref()));
+        self.overlay.insert(path, contents);
+    }
+
+    /// Hides a file, whether it is in the overlay or in the base filesystem.
+    pub fn remove(&mut self, path: impl AsRef<Path>) {
+        self.overlay.remove(path.as_ref());
+        self.removed.insert(normalize_path(path.as_ref()));
+    }
+
+    /// Drops the overlay of a file, so that the file of the base filesystem is seen again.
+    pub fn reset(&mut self, path: impl AsRef<Path>) {
+        self.overlay.remove(path.as_ref());
+        self.removed.remove(&normalize_path(path.as_ref()));
+    }
+}
+
+impl<F: FileSystem> FileSystem for OverlayFs<F> {
+    type Entry = MemoryEntry;
+
+    fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        if self.overlay.contains(path) {
+            self.overlay.read_file(path)
+        } else if self.removed.contains(&normalize_path(path)) {
+            Err(miette!("could not read source file `{}`", path.display()))
+        } else {
+            self.base.read_file(path)
+        }
+    }
+
+    fn list_directory(&self, path: &Path) -> miette::Result<Vec<MemoryEntry>> {
+        let overlay = self.overlay.entries(path);
+        let base = match self.base.list_directory(path) {
+            Ok(entries) => entries,
+            // A folder that only exists in the overlay is still a folder.
+            Err(_) if !overlay.is_empty() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = base
+            .into_iter()
+            .filter_map(|entry| {
+                let is_folder = match entry.entry_type().ok()? {
+                    EntryType::File => false,
+                    EntryType::Folder => true,
+                    EntryType::Symlink | EntryType::Unknown => return None,
+                };
+                let path = normalize_path(&entry.path());
+                (!self.removed.contains(&path)).then_some(MemoryEntry { path, is_folder })
+            })
+            .collect::<BTreeSet<_>>();
+        for entry in overlay {
+            entries.retain(|existing| existing.path != entry.path);
+            entries.insert(entry);
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    fn resolve_path(&self, base: &Path, path: &Path) -> miette::Result<PathBuf> {
+        self.base.resolve_path(base, path)
+    }
+
+    fn fetch_github(
+        &self,
+        owner: &str,
+        repo: &str,
+        r#ref: &str,
+        path: &str,
+    ) -> miette::Result<Arc<str>> {
+        self.base.fetch_github(owner, repo, r#ref, path)
+    }
+}
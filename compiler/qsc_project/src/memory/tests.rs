@@ -0,0 +1,109 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{MemoryFs, OverlayFs};
+use crate::{DirEntry, EntryType, FileSystem};
+use std::path::Path;
+
+fn project() -> MemoryFs {
+    let mut fs = MemoryFs::new();
+    fs.insert("/project/qsharp.json", "{}");
+    fs.insert("/project/src/Main.qs", "namespace Main {}");
+    fs.insert("/project/src/lib/Helpers.qs", "namespace Helpers {}");
+    fs.insert("/project/src/.Hidden.qs", "namespace Hidden {}");
+    fs.insert("/project/README.md", "# Project");
+    fs
+}
+
+fn source_names(fs: &impl FileSystem) -> Vec<String> {
+    let project = fs
+        .load_project(Path::new("/project"), None)
+        .expect("project should load");
+    project
+        .package_graph_sources
+        .root
+        .sources
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+#[test]
+fn project_is_loaded_from_memory() {
+    assert_eq!(
+        source_names(&project()),
+        ["/project/src/Main.qs", "/project/src/lib/Helpers.qs"]
+    );
+}
+
+#[test]
+fn directories_list_files_and_folders() {
+    let fs = project();
+    let entries = fs
+        .list_directory(Path::new("/project/src/"))
+        .expect("directory should be listed");
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.entry_name(),
+                entry.entry_type().expect("entry should have a type"),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            (".Hidden.qs".to_string(), EntryType::File),
+            ("Main.qs".to_string(), EntryType::File),
+            ("lib".to_string(), EntryType::Folder),
+        ]
+    );
+    assert!(fs.list_directory(Path::new("/missing")).is_err());
+}
+
+#[test]
+fn paths_are_normalized() {
+    let mut fs = project();
+    fs.insert("/project/src/lib/../Extra.qs", "namespace Extra {}");
+    let (_, contents) = fs
+        .read_file(Path::new("/project/./src/Extra.qs"))
+        .expect("file should be read");
+    assert_eq!(contents.as_ref(), "namespace Extra {}");
+    assert!(fs.read_file(Path::new("/project/src/Missing.qs")).is_err());
+}
+
+#[test]
+fn overlay_shadows_and_hides_base_files() {
+    let mut fs = OverlayFs::new(project());
+    fs.insert("/project/src/Main.qs", "namespace Edited {}");
+    fs.insert("/project/src/new/Added.qs", "namespace Added {}");
+    fs.remove("/project/src/lib/Helpers.qs");
+
+    assert_eq!(
+        source_names(&fs),
+        ["/project/src/Main.qs", "/project/src/new/Added.qs"]
+    );
+    let (_, contents) = fs
+        .read_file(Path::new("/project/src/Main.qs"))
+        .expect("file should be read");
+    assert_eq!(contents.as_ref(), "namespace Edited {}");
+    assert!(fs
+        .read_file(Path::new("/project/src/lib/Helpers.qs"))
+        .is_err());
+
+    fs.reset("/project/src/Main.qs");
+    fs.reset("/project/src/lib/Helpers.qs");
+    let (_, contents) = fs
+        .read_file(Path::new("/project/src/Main.qs"))
+        .expect("file should be read");
+    assert_eq!(contents.as_ref(), "namespace Main {}");
+    assert_eq!(
+        source_names(&fs),
+        [
+            "/project/src/Main.qs",
+            "/project/src/lib/Helpers.qs",
+            "/project/src/new/Added.qs"
+        ]
+    );
+}
@@ -10,7 +10,7 @@ use qsc_linter::LintConfig;
 use rustc_hash::FxHashMap;
 use std::{
     cell::RefCell,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 use thiserror::Error;
@@ -48,6 +48,7 @@ impl Project {
                     sources: vec![(name.clone(), contents)],
                     language_features: LanguageFeatures::default(),
                     dependencies: FxHashMap::default(),
+                    no_stdlib: false,
                 },
                 packages: FxHashMap::default(),
             },
@@ -380,6 +381,7 @@ pub trait FileSystemAsync {
             sources,
             language_features: LanguageFeatures::from_iter(&manifest.manifest.language_features),
             dependencies,
+            no_stdlib: manifest.manifest.no_stdlib,
         })
     }
 
@@ -448,6 +450,7 @@ pub trait FileSystemAsync {
                 .into_iter()
                 .map(|(k, v)| (k.into(), key_for_package_ref(&v)))
                 .collect(),
+            no_stdlib: manifest.no_stdlib,
         })
     }
 
@@ -585,6 +588,9 @@ pub struct PackageInfo {
     pub sources: Sources,
     pub language_features: LanguageFeatures,
     pub dependencies: FxHashMap<PackageAlias, PackageKey>,
+    /// Whether the package is built without the standard library, as set by `noStdlib` in its
+    /// manifest.
+    pub no_stdlib: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -607,6 +613,51 @@ impl PackageGraphSources {
 
         (sources, self.root.language_features)
     }
+
+    /// The packages other than the root, ordered so that each package comes after all of its
+    /// dependencies. Packages in a dependency cycle, which is reported as an error when the
+    /// project is loaded, and packages that depend on them are left out.
+    #[must_use]
+    pub fn dependency_order(&self) -> Vec<(&PackageKey, &PackageInfo)> {
+        let mut keys = self.packages.keys().collect::<Vec<_>>();
+        keys.sort();
+        let mut visited = FxHashMap::default();
+        let mut order = Vec::new();
+        for key in keys {
+            self.visit(key, &mut visited, &mut order);
+        }
+        order
+    }
+
+    /// Adds the package to the order after its dependencies, returning whether it was added.
+    /// A package that is being visited is marked as not added, so that a cycle back to it fails.
+    fn visit<'a>(
+        &'a self,
+        key: &'a PackageKey,
+        visited: &mut FxHashMap<&'a PackageKey, bool>,
+        order: &mut Vec<(&'a PackageKey, &'a PackageInfo)>,
+    ) -> bool {
+        if let Some(&added) = visited.get(key) {
+            return added;
+        }
+        // A dependency that failed to load has already been reported, and has nothing to order.
+        let Some(info) = self.packages.get(key) else {
+            return true;
+        };
+
+        visited.insert(key, false);
+        let mut dependencies = info.dependencies.values().collect::<Vec<_>>();
+        dependencies.sort();
+        let mut added = true;
+        for dependency in dependencies {
+            added &= self.visit(dependency, visited, order);
+        }
+        if added {
+            order.push((key, info));
+        }
+        visited.insert(key, added);
+        added
+    }
 }
 
 /// This trait is used to abstract filesystem logic with regards to Q# projects.
@@ -691,3 +742,33 @@ where
         self.fs.fetch_github(owner, repo, r#ref, path)
     }
 }
+
+/// Normalizes a path by removing `.` components and applying `..` components, without touching
+/// the file system.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    // Adapted from https://github.com/rust-lang/cargo/blob/a879a1ca12e3997d9fdd71b70f34f1f3c866e1da/crates/cargo-util/src/paths.rs#L84
+    let mut components = path.components().peekable();
+    let mut normalized = if let Some(c @ Component::Prefix(..)) = components.peek().copied() {
+        components.next();
+        PathBuf::from(c.as_os_str())
+    } else {
+        PathBuf::new()
+    };
+
+    for component in components {
+        match component {
+            Component::Prefix(..) => unreachable!(),
+            Component::RootDir => {
+                normalized.push(component.as_os_str());
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(c) => {
+                normalized.push(c);
+            }
+        }
+    }
+    normalized
+}
@@ -8,7 +8,10 @@
 mod harness;
 
 use expect_test::expect;
-use harness::check;
+use harness::{check, load};
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_project::{PackageGraphSources, PackageInfo};
+use std::sync::Arc;
 
 #[test]
 fn basic_manifest() {
@@ -38,6 +41,7 @@ fn basic_manifest() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -75,6 +79,7 @@ fn circular_imports() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -112,6 +117,7 @@ fn different_files_same_manifest() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -141,6 +147,7 @@ fn empty_manifest() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -182,6 +189,7 @@ fn folder_structure() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -218,6 +226,7 @@ fn hidden_files() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -258,6 +267,7 @@ fn peer_file() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -287,6 +297,7 @@ fn language_feature() {
                             1,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -318,6 +329,7 @@ fn with_local_dep() {
                         dependencies: {
                             "MyDep": "{\"path\":\"local_dep\"}",
                         },
+                        no_stdlib: false,
                     },
                     packages: {
                         "{\"path\":\"local_dep\"}": PackageInfo {
@@ -331,6 +343,7 @@ fn with_local_dep() {
                                 0,
                             ),
                             dependencies: {},
+                            no_stdlib: false,
                         },
                     },
                 },
@@ -362,6 +375,7 @@ fn transitive_dep() {
                         dependencies: {
                             "MyDep": "{\"path\":\"with_local_dep\"}",
                         },
+                        no_stdlib: false,
                     },
                     packages: {
                         "{\"path\":\"local_dep\"}": PackageInfo {
@@ -375,6 +389,7 @@ fn transitive_dep() {
                                 0,
                             ),
                             dependencies: {},
+                            no_stdlib: false,
                         },
                         "{\"path\":\"with_local_dep\"}": PackageInfo {
                             sources: [
@@ -389,6 +404,7 @@ fn transitive_dep() {
                             dependencies: {
                                 "MyDep": "{\"path\":\"local_dep\"}",
                             },
+                            no_stdlib: false,
                         },
                     },
                 },
@@ -418,6 +434,7 @@ fn explicit_files_list() {
                             0,
                         ),
                         dependencies: {},
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -449,6 +466,7 @@ fn circular_dep() {
                         dependencies: {
                             "MyCircularDep": "{\"path\":\"circular_dep\"}",
                         },
+                        no_stdlib: false,
                     },
                     packages: {},
                 },
@@ -462,3 +480,78 @@ fn circular_dep() {
             }"#]],
     );
 }
+
+#[test]
+fn no_stdlib() {
+    check(
+        &"no_stdlib".into(),
+        &expect![[r#"
+            Project {
+                name: "no_stdlib",
+                path: "no_stdlib/qsharp.json",
+                package_graph_sources: PackageGraphSources {
+                    root: PackageInfo {
+                        sources: [
+                            (
+                                "no_stdlib/src/Main.qs",
+                                "namespace Main {\n    function Main() : Unit {}\n}\n",
+                            ),
+                        ],
+                        language_features: LanguageFeatures(
+                            0,
+                        ),
+                        dependencies: {},
+                        no_stdlib: true,
+                    },
+                    packages: {},
+                },
+                lints: [],
+                errors: [],
+            }"#]],
+    );
+}
+
+#[test]
+fn dependencies_are_ordered_before_dependents() {
+    let project = load(&"transitive_dep".into());
+    let order = project
+        .package_graph_sources
+        .dependency_order()
+        .into_iter()
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        order,
+        [r#"{"path":"local_dep"}"#, r#"{"path":"with_local_dep"}"#]
+    );
+}
+
+#[test]
+fn packages_in_a_cycle_are_not_ordered() {
+    let package = |dependencies: &[&str]| PackageInfo {
+        sources: Vec::new(),
+        language_features: LanguageFeatures::default(),
+        dependencies: dependencies
+            .iter()
+            .map(|&key| (Arc::from(key), Arc::from(key)))
+            .collect(),
+        no_stdlib: false,
+    };
+    let graph = PackageGraphSources {
+        root: package(&["a", "c", "d"]),
+        packages: [
+            ("a".into(), package(&["b"])),
+            ("b".into(), package(&["a"])),
+            ("c".into(), package(&[])),
+            ("d".into(), package(&["a", "c"])),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    let order = graph
+        .dependency_order()
+        .into_iter()
+        .map(|(key, _)| key.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(order, ["c"]);
+}
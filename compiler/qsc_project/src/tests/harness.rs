@@ -14,6 +14,12 @@ use qsc_project::{
 use rustc_hash::FxHashMap;
 
 pub fn check(project_path: &PathBuf, expect: &Expect) {
+    let project = load(project_path);
+    expect.assert_eq(&format!("{project:#?}"));
+}
+
+/// Loads the test project, with paths made relative to the test projects folder.
+pub fn load(project_path: &PathBuf) -> Project {
     let mut root_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     root_path.push(PathBuf::from("src"));
     root_path.push(PathBuf::from("tests"));
@@ -29,8 +35,7 @@ pub fn check(project_path: &PathBuf, expect: &Expect) {
         .expect("project should load");
 
     normalize(&mut project, &root_path);
-
-    expect.assert_eq(&format!("{project:#?}"));
+    project
 }
 
 /// If the `Project` contains absolute paths, replace them with relative paths
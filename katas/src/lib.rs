@@ -5,9 +5,10 @@
 mod tests;
 
 use qsc::{
+    compile,
     interpret::{output::Receiver, Error, Interpreter, Value},
     target::Profile,
-    PackageType, SourceContents, SourceMap, SourceName,
+    PackageType, SourceContents, SourceMap, SourceName, StdFeatures,
 };
 
 use qsc::LanguageFeatures;
@@ -35,11 +36,67 @@ pub fn check_solution(
         Profile::Unrestricted.into(),
         LanguageFeatures::default(),
     )?;
-    interpreter.eval_entry(receiver).map(|value| {
-        if let Value::Bool(success) = value {
-            success
+    interpreter.eval_entry(receiver).map(expect_success)
+}
+
+/// Checks a submission like [`check_solution`], but compiles the verification sources, which
+/// hold the validation operation and any reference solutions, into a sealed package of their
+/// own. The sealed package depends on the submission, so the validation operation can call it,
+/// but the submission cannot refer to anything in the sealed package. Each check runs in a new
+/// session, so nothing is shared between submissions.
+///
+/// # Errors
+///
+/// Returns a vector of errors if either package fails to compile or evaluation failed.
+///
+/// # Panics
+///
+/// Will panic if evaluation does not return a boolean as result.
+pub fn check_submission(
+    submission_sources: Vec<(SourceName, SourceContents)>,
+    verification_sources: Vec<(SourceName, SourceContents)>,
+    receiver: &mut impl Receiver,
+) -> Result<bool, Vec<Error>> {
+    let capabilities = Profile::Unrestricted.into();
+    let language_features = LanguageFeatures::default();
+    let (mut store, std_id) = compile::shared_store(capabilities, Some(StdFeatures::all()));
+    let std_id = std_id.expect("store should have std");
+
+    let mut compile_package = |dependencies: &[_], sources, package_type| {
+        let (unit, errors) = compile::compile(
+            &store,
+            dependencies,
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        );
+        if errors.is_empty() {
+            Ok(store.insert(unit))
         } else {
-            panic!("exercise verification did not return a boolean")
+            Err(errors.into_iter().map(Error::Compile).collect::<Vec<_>>())
         }
-    })
+    };
+    let submission_id = compile_package(
+        &[std_id],
+        SourceMap::new(submission_sources, None),
+        PackageType::Lib,
+    )?;
+    let verification_id = compile_package(
+        &[std_id, submission_id],
+        SourceMap::new(verification_sources, Some(EXERCISE_ENTRY.into())),
+        PackageType::Exe,
+    )?;
+
+    let mut interpreter =
+        Interpreter::from(store, verification_id, capabilities, language_features)?;
+    interpreter.eval_entry(receiver).map(expect_success)
+}
+
+fn expect_success(value: Value) -> bool {
+    if let Value::Bool(success) = value {
+        success
+    } else {
+        panic!("exercise verification did not return a boolean")
+    }
 }
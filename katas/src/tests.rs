@@ -57,3 +57,65 @@ fn test_check_solution_is_incorrect() {
     let verification_source = test_cases_dir().join("apply_x").join("Verification.qs");
     test_check_solution(solution_source, verification_source, false);
 }
+
+fn run_check_submission(submission: &str, verification: &str) -> Result<bool, Vec<Error>> {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut receiver = CursorReceiver::new(&mut cursor);
+    let result = crate::check_submission(
+        vec![("submission".into(), submission.into())],
+        vec![("verification".into(), verification.into())],
+        &mut receiver,
+    );
+    println!("{}", receiver.dump());
+    result
+}
+
+fn read_test_case(name: &str) -> String {
+    fs::read_to_string(test_cases_dir().join("apply_x").join(name))
+        .expect("test case file should be readable")
+}
+
+#[test]
+fn test_check_submission_is_correct() {
+    let result = run_check_submission(
+        &read_test_case("Correct.qs"),
+        &read_test_case("Verification.qs"),
+    )
+    .expect("exercise should run successfully");
+    assert!(result, "exercise result is different than expected");
+}
+
+#[test]
+fn test_check_submission_is_incorrect() {
+    let result = run_check_submission(
+        &read_test_case("Incorrect.qs"),
+        &read_test_case("Verification.qs"),
+    )
+    .expect("exercise should run successfully");
+    assert!(!result, "exercise result is different than expected");
+}
+
+#[test]
+fn test_check_submission_cannot_use_reference_solution() {
+    let errors = run_check_submission(
+        &read_test_case("UsesReference.qs"),
+        &read_test_case("Verification.qs"),
+    )
+    .expect_err("submission should not compile");
+    assert!(
+        errors
+            .iter()
+            .all(|error| matches!(error, Error::Compile(_))),
+        "unexpected errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_check_solution_can_use_reference_solution() {
+    let result = run_check_solution(
+        &read_test_case("UsesReference.qs"),
+        &read_test_case("Verification.qs"),
+    )
+    .expect("exercise should run successfully");
+    assert!(result);
+}
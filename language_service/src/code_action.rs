@@ -1,10 +1,20 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod extract;
+#[cfg(test)]
+mod tests;
+
 use miette::Diagnostic;
 use qsc::{
+    ast::{
+        self,
+        visit::{walk_expr, Visitor},
+    },
     compile::ErrorKind,
+    display::Lookup,
     error::WithSource,
+    hir::ty::Ty,
     line_column::{Encoding, Range},
     Span,
 };
@@ -12,6 +22,7 @@ use qsc::{
 use crate::{
     compilation::Compilation,
     protocol::{CodeAction, CodeActionKind, TextEdit, WorkspaceEdit},
+    qsc_utils::into_range,
 };
 
 pub(crate) fn get_code_actions(
@@ -22,7 +33,14 @@ pub(crate) fn get_code_actions(
 ) -> Vec<CodeAction> {
     // Compute quick_fixes and other code_actions, and then merge them together
     let span = compilation.source_range_to_package_span(source_name, range, position_encoding);
-    quick_fixes(compilation, source_name, span, position_encoding)
+    let mut code_actions = quick_fixes(compilation, source_name, span, position_encoding);
+    code_actions.extend(extract::extract_callable(
+        compilation,
+        source_name,
+        span,
+        position_encoding,
+    ));
+    code_actions
 }
 
 fn quick_fixes(
@@ -65,12 +83,216 @@ fn quick_fixes(
                     is_preferred: None,
                 });
             }
+        } else if is_unresolved_name(diagnostic) {
+            if let Some(code_action) = resolve_span(diagnostic)
+                .and_then(|name_span| generate_stub(compilation, source_name, name_span, encoding))
+            {
+                code_actions.push(code_action);
+            }
         }
     }
 
     code_actions
 }
 
+/// Returns true if the error is a name that could not be resolved. Names that were found but
+/// are not available for the current compilation configuration report the same code, but also
+/// come with help pointing at the item that was found, and should not get a stub.
+fn is_unresolved_name(error: &WithSource<ErrorKind>) -> bool {
+    error
+        .code()
+        .is_some_and(|code| code.to_string() == "Qsc.Resolve.NotFound")
+        && error.help().is_none()
+}
+
+/// Generates a stub declaration for the callable called by the unresolved name at the span. The
+/// stub is inserted after the callable that contains the call, or before the top-level
+/// statement that contains it, and its signature is inferred from the types of the arguments
+/// and the type the call is expected to have. Types that could not be inferred become type
+/// parameters.
+fn generate_stub(
+    compilation: &Compilation,
+    source_name: &str,
+    name_span: Span,
+    encoding: Encoding,
+) -> Option<CodeAction> {
+    let (anchor, enclosing) = find_anchor(&compilation.user_unit().ast.package, name_span)?;
+    let mut finder = CallFinder {
+        name_span,
+        call: None,
+    };
+    match anchor {
+        Anchor::Item(item) => finder.visit_item(item),
+        Anchor::Stmt(stmt) => finder.visit_stmt(stmt),
+    }
+    let (name, input, call) = finder.call?;
+
+    // Functions cannot call operations, so a call from a function needs a function.
+    let kind = match enclosing {
+        Some(ast::CallableKind::Function) => "function",
+        Some(ast::CallableKind::Operation) | None => "operation",
+    };
+
+    let mut type_params = Vec::new();
+    let mut ty_or_param = |ty: Option<&Ty>| match ty {
+        Some(ty) if is_known(ty) => ty.display(),
+        _ => {
+            let param = format!("'T{}", type_params.len() + 1);
+            type_params.push(param.clone());
+            param
+        }
+    };
+
+    let args: Vec<&ast::Expr> = match &*input.kind {
+        ast::ExprKind::Tuple(items) => items.iter().map(AsRef::as_ref).collect(),
+        ast::ExprKind::Paren(item) => vec![item.as_ref()],
+        _ => vec![input],
+    };
+    let mut params: Vec<String> = Vec::new();
+    for (index, arg) in args.into_iter().enumerate() {
+        let ty = ty_or_param(compilation.get_ty(arg.id));
+        let mut param_name = match &*arg.kind {
+            ast::ExprKind::Path(path) if path.segments.is_none() => path.name.name.to_string(),
+            _ => format!("arg{}", index + 1),
+        };
+        if params
+            .iter()
+            .any(|param| param.starts_with(&format!("{param_name} :")))
+        {
+            param_name = format!("{param_name}{}", index + 1);
+        }
+        params.push(format!("{param_name} : {ty}"));
+    }
+
+    let output = match compilation.get_ty(call.id) {
+        Some(ty) if is_known(ty) => ty.display(),
+        _ => "Unit".to_string(),
+    };
+
+    let type_params = if type_params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", type_params.join(", "))
+    };
+
+    let sources = &compilation.user_unit().sources;
+    let anchor_span = match anchor {
+        Anchor::Item(item) => item.span,
+        Anchor::Stmt(stmt) => stmt.span,
+    };
+    let source = sources.find_by_offset(anchor_span.lo)?;
+    let indent = indentation(&source.contents, (anchor_span.lo - source.offset) as usize);
+    let stub = format!(
+        "{kind} {name}{type_params}({}) : {output} {{\n{indent}    fail \"Not implemented\"\n{indent}}}",
+        params.join(", ")
+    );
+    let (offset, new_text) = if enclosing.is_some() {
+        (anchor_span.hi, format!("\n\n{indent}{stub}"))
+    } else {
+        (anchor_span.lo, format!("{stub}\n\n{indent}"))
+    };
+
+    Some(CodeAction {
+        title: format!("Create {kind} `{name}`"),
+        edit: Some(WorkspaceEdit {
+            changes: vec![(
+                source_name.to_string(),
+                vec![TextEdit {
+                    new_text,
+                    range: into_range(
+                        encoding,
+                        Span {
+                            lo: offset,
+                            hi: offset,
+                        },
+                        sources,
+                    ),
+                }],
+            )],
+        }),
+        kind: Some(CodeActionKind::QuickFix),
+        is_preferred: None,
+    })
+}
+
+/// The top-level declaration or statement that contains a call.
+#[derive(Clone, Copy)]
+enum Anchor<'a> {
+    Item(&'a ast::Item),
+    Stmt(&'a ast::Stmt),
+}
+
+/// Finds the namespace item or top-level statement that contains the span, along with the kind
+/// of the callable it declares, if any.
+fn find_anchor(
+    package: &ast::Package,
+    span: Span,
+) -> Option<(Anchor<'_>, Option<ast::CallableKind>)> {
+    let contains = |outer: Span| outer.lo <= span.lo && span.hi <= outer.hi;
+    let callable_kind = |item: &ast::Item| match &*item.kind {
+        ast::ItemKind::Callable(decl) => Some(decl.kind),
+        _ => None,
+    };
+    package.nodes.iter().find_map(|node| match node {
+        ast::TopLevelNode::Namespace(namespace) => namespace
+            .items
+            .iter()
+            .find(|item| contains(item.span))
+            .map(|item| (Anchor::Item(item), callable_kind(item))),
+        ast::TopLevelNode::Stmt(stmt) if contains(stmt.span) => match &*stmt.kind {
+            ast::StmtKind::Item(item) => Some((Anchor::Item(item), callable_kind(item))),
+            _ => Some((Anchor::Stmt(stmt), None)),
+        },
+        ast::TopLevelNode::Stmt(_) => None,
+    })
+}
+
+/// Finds the call expression whose callee is the unresolved name.
+struct CallFinder<'a> {
+    name_span: Span,
+    call: Option<(&'a str, &'a ast::Expr, &'a ast::Expr)>,
+}
+
+impl<'a> Visitor<'a> for CallFinder<'a> {
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if self.call.is_some() {
+            return;
+        }
+        if let ast::ExprKind::Call(callee, input) = &*expr.kind {
+            if let ast::ExprKind::Path(path) = &*callee.kind {
+                if path.segments.is_none() && path.name.span == self.name_span {
+                    self.call = Some((path.name.name.as_ref(), input.as_ref(), expr));
+                    return;
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns true if the type is fully inferred, so that it can be written in a declaration.
+fn is_known(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_known(item),
+        Ty::Arrow(arrow) => is_known(&arrow.input) && is_known(&arrow.output),
+        Ty::Tuple(items) => items.iter().all(is_known),
+        Ty::Infer(_) | Ty::Err | Ty::Param(..) => false,
+        Ty::Prim(_) | Ty::Udt(..) => true,
+    }
+}
+
+/// Returns the whitespace that precedes the offset on its line, or the empty string if the
+/// offset is not the first non-whitespace character on the line.
+fn indentation(contents: &str, offset: usize) -> &str {
+    let before = &contents[..offset];
+    let line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+    if line.chars().all(char::is_whitespace) {
+        line
+    } else {
+        ""
+    }
+}
+
 /// Returns true if the error has a `Range` and it overlaps
 /// with the code action's range.
 fn is_error_relevant(error: &WithSource<ErrorKind>, span: Span) -> bool {
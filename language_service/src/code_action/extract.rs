@@ -0,0 +1,440 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::{find_anchor, indentation, is_known, Anchor};
+use crate::{
+    compilation::Compilation,
+    protocol::{CodeAction, CodeActionKind, TextEdit, WorkspaceEdit},
+    qsc_utils::into_range,
+};
+use qsc::{
+    ast::{
+        self,
+        visit::{walk_block, walk_callable_decl, walk_expr, walk_pat, walk_stmt, Visitor},
+        NodeId,
+    },
+    display::Lookup,
+    hir::{
+        ty::{Prim, Ty},
+        CallableKind,
+    },
+    line_column::Encoding,
+    resolve, trivia, Span,
+};
+use rustc_hash::FxHashSet;
+use std::rc::Rc;
+
+/// Extracts the statements in the span into a new callable, declared after the callable that
+/// contains them, and replaces them with a call to it.
+///
+/// Local variables that the statements use but do not declare become parameters, and variables
+/// that they declare and that are used after them are returned. The new callable is an
+/// operation if the statements allocate or use qubits or call operations, and a function
+/// otherwise. A new operation supports the same functors as the enclosing operation, whose
+/// generated specializations call it. Returns `None` if the span does not cover whole statements
+/// of a single block, or if the statements cannot be moved without changing their meaning, such
+/// as when they assign to a variable declared outside of them, return from the enclosing
+/// callable, or are in one of the explicit specializations of an operation with functors.
+pub(super) fn extract_callable(
+    compilation: &Compilation,
+    source_name: &str,
+    span: Span,
+    encoding: Encoding,
+) -> Option<CodeAction> {
+    if span.lo == span.hi {
+        return None;
+    }
+    let package = &compilation.user_unit().ast.package;
+    let (Anchor::Item(item), Some(enclosing)) = find_anchor(package, span)? else {
+        return None;
+    };
+    let ast::ItemKind::Callable(decl) = &*item.kind else {
+        return None;
+    };
+
+    let mut selection = Selection { span, stmts: None };
+    selection.visit_callable_decl(decl);
+    let (stmts, trailing) = selection.stmts?;
+    let selected = Span {
+        lo: stmts.first()?.span.lo,
+        hi: stmts.last()?.span.hi,
+    };
+
+    let mut usage = Usage {
+        compilation,
+        declared: FxHashSet::default(),
+        captured: Vec::new(),
+        assigned: FxHashSet::default(),
+        returns: false,
+        quantum: false,
+    };
+    for stmt in stmts {
+        if matches!(&*stmt.kind, ast::StmtKind::Item(_)) {
+            return None;
+        }
+        usage.visit_stmt(stmt);
+    }
+    if usage.returns
+        || usage
+            .captured
+            .iter()
+            .any(|(id, _)| usage.assigned.contains(id))
+    {
+        return None;
+    }
+
+    // Variables declared by the statements themselves, rather than in blocks nested in them,
+    // are the only ones still in scope after the statements.
+    let mut bindings = Vec::new();
+    for stmt in stmts {
+        match &*stmt.kind {
+            ast::StmtKind::Local(mutability, pat, _) => {
+                bindings_of(pat, *mutability == ast::Mutability::Mutable, &mut bindings);
+            }
+            ast::StmtKind::Qubit(_, pat, _, _) => bindings_of(pat, false, &mut bindings),
+            _ => {}
+        }
+    }
+    let mut later = LaterUses {
+        compilation,
+        after: selected.hi,
+        used: FxHashSet::default(),
+    };
+    later.visit_callable_decl(decl);
+    let outputs = bindings
+        .into_iter()
+        .filter(|(id, _, _)| later.used.contains(id))
+        .collect::<Vec<_>>();
+    if !outputs.is_empty() && trailing {
+        return None;
+    }
+
+    let mut quantum = usage.quantum;
+    let mut params = Vec::new();
+    for (id, name) in &usage.captured {
+        let ty = compilation.get_ty(*id).filter(|ty| is_known(ty))?;
+        quantum |= has_qubit(ty);
+        params.push(format!("{name} : {}", ty.display()));
+    }
+    let mut output_tys = Vec::new();
+    for (id, _, _) in &outputs {
+        let ty = compilation.get_ty(*id).filter(|ty| is_known(ty))?;
+        // Qubits allocated by the statements are released when the new callable returns, so
+        // they cannot be used after it.
+        if has_qubit(ty) {
+            return None;
+        }
+        output_tys.push(ty.display());
+    }
+    let output = match output_tys.len() {
+        0 if trailing => {
+            let value = match &*stmts.last()?.kind {
+                ast::StmtKind::Expr(expr) => compilation.get_ty(expr.id),
+                _ => None,
+            };
+            value.filter(|ty| is_known(ty))?.display()
+        }
+        0 => "Unit".to_string(),
+        1 => output_tys.remove(0),
+        _ => format!("({})", output_tys.join(", ")),
+    };
+
+    let kind = if enclosing == ast::CallableKind::Function || !quantum {
+        "function"
+    } else {
+        "operation"
+    };
+    let name = unique_name(package);
+
+    let sources = &compilation.user_unit().sources;
+    let source = sources.find_by_offset(selected.lo)?;
+    let contents = &source.contents;
+    let functors = match (&decl.functors, &*decl.body) {
+        (Some(functors), ast::CallableBody::Block(_)) if kind == "operation" => format!(
+            " is {}",
+            &contents[(functors.span.lo - source.offset) as usize
+                ..(functors.span.hi - source.offset) as usize]
+        ),
+        // The specializations that the new operation would need are not generated from a body
+        // that holds only some of the statements of an explicit specialization.
+        (Some(_), ast::CallableBody::Specs(_)) if kind == "operation" => return None,
+        _ => String::new(),
+    };
+    // Comments on the statements move with them.
+    let moved = trivia::Trivia::new(contents, source.offset).with_comments(selected);
+    let text = &contents[(moved.lo - source.offset) as usize..(moved.hi - source.offset) as usize];
+    let stmt_indent = indentation(contents, (moved.lo - source.offset) as usize);
+    let item_indent = indentation(contents, (item.span.lo - source.offset) as usize);
+    let body_indent = format!("{item_indent}    ");
+
+    let mut body = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match line.strip_prefix(stmt_indent) {
+            _ if index == 0 => format!("{body_indent}{line}"),
+            _ if line.trim().is_empty() => String::new(),
+            Some(rest) => format!("{body_indent}{rest}"),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let output_names = outputs
+        .iter()
+        .map(|(_, name, _)| name.as_ref())
+        .collect::<Vec<_>>();
+    match output_names.len() {
+        0 => {}
+        1 => body.push_str(&format!("\n{body_indent}{}", output_names[0])),
+        _ => body.push_str(&format!("\n{body_indent}({})", output_names.join(", "))),
+    }
+
+    let args = usage
+        .captured
+        .iter()
+        .map(|(_, name)| name.as_ref())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let call = format!("{name}({args})");
+    let call = match output_names.len() {
+        0 if trailing => call,
+        0 => format!("{call};"),
+        count => {
+            let binding = if outputs.iter().any(|(_, _, mutable)| *mutable) {
+                "mutable"
+            } else {
+                "let"
+            };
+            if count == 1 {
+                format!("{binding} {} = {call};", output_names[0])
+            } else {
+                format!("{binding} ({}) = {call};", output_names.join(", "))
+            }
+        }
+    };
+    let declaration = format!(
+        "\n\n{item_indent}{kind} {name}({}) : {output}{functors} {{\n{body}\n{item_indent}}}",
+        params.join(", ")
+    );
+
+    let insert = Span {
+        lo: item.span.hi,
+        hi: item.span.hi,
+    };
+    Some(CodeAction {
+        title: format!("Extract into {kind}"),
+        edit: Some(WorkspaceEdit {
+            changes: vec![(
+                source_name.to_string(),
+                vec![
+                    TextEdit {
+                        new_text: call,
+                        range: into_range(encoding, moved, sources),
+                    },
+                    TextEdit {
+                        new_text: declaration,
+                        range: into_range(encoding, insert, sources),
+                    },
+                ],
+            )],
+        }),
+        kind: Some(CodeActionKind::RefactorExtract),
+        is_preferred: None,
+    })
+}
+
+/// Finds the statements of the innermost block that the span covers. The span must cover every
+/// statement that it overlaps completely. Also returns whether the last of the statements is the
+/// value of the block.
+struct Selection<'a> {
+    span: Span,
+    stmts: Option<(&'a [Box<ast::Stmt>], bool)>,
+}
+
+impl<'a> Visitor<'a> for Selection<'a> {
+    fn visit_block(&mut self, block: &'a ast::Block) {
+        if self.stmts.is_some() {
+            return;
+        }
+        let span = self.span;
+        let covers = |stmt: &ast::Stmt| span.lo <= stmt.span.lo && stmt.span.hi <= span.hi;
+        let Some(first) = block.stmts.iter().position(|stmt| covers(stmt)) else {
+            walk_block(self, block);
+            return;
+        };
+        let disjoint = |stmt: &ast::Stmt| stmt.span.hi <= span.lo || span.hi <= stmt.span.lo;
+        if block
+            .stmts
+            .iter()
+            .all(|stmt| covers(stmt) || disjoint(stmt))
+        {
+            let count = block.stmts[first..]
+                .iter()
+                .take_while(|stmt| covers(stmt))
+                .count();
+            let last = first + count - 1;
+            let trailing = last + 1 == block.stmts.len()
+                && matches!(&*block.stmts[last].kind, ast::StmtKind::Expr(_));
+            self.stmts = Some((&block.stmts[first..=last], trailing));
+        }
+    }
+}
+
+/// Collects how the selected statements use local variables.
+struct Usage<'a> {
+    compilation: &'a Compilation,
+    /// Variables declared anywhere in the statements.
+    declared: FxHashSet<NodeId>,
+    /// Variables used by the statements and declared before them, in order of first use.
+    captured: Vec<(NodeId, Rc<str>)>,
+    /// Variables assigned to by the statements.
+    assigned: FxHashSet<NodeId>,
+    /// Whether the statements return from the enclosing callable.
+    returns: bool,
+    /// Whether the statements allocate qubits or call operations.
+    quantum: bool,
+}
+
+impl<'a> Visitor<'a> for Usage<'a> {
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let ast::PatKind::Bind(name, _) = &*pat.kind {
+            self.declared.insert(name.id);
+        }
+        walk_pat(self, pat);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if matches!(&*stmt.kind, ast::StmtKind::Qubit(..)) {
+            self.quantum = true;
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        match &*expr.kind {
+            ast::ExprKind::Path(path) => {
+                if let Some((id, name)) = local(self.compilation, path) {
+                    if !self.declared.contains(&id)
+                        && !self.captured.iter().any(|(captured, _)| *captured == id)
+                    {
+                        self.captured.push((id, name));
+                    }
+                }
+            }
+            ast::ExprKind::Assign(lhs, _)
+            | ast::ExprKind::AssignOp(_, lhs, _)
+            | ast::ExprKind::AssignUpdate(lhs, _, _) => {
+                assigned_locals(self.compilation, lhs, &mut self.assigned);
+            }
+            ast::ExprKind::Call(callee, _) => {
+                if let Some(Ty::Arrow(arrow)) = self.compilation.get_ty(callee.id) {
+                    if arrow.kind == CallableKind::Operation {
+                        self.quantum = true;
+                    }
+                }
+            }
+            ast::ExprKind::Return(_) => self.returns = true,
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Collects the variables used after an offset.
+struct LaterUses<'a> {
+    compilation: &'a Compilation,
+    after: u32,
+    used: FxHashSet<NodeId>,
+}
+
+impl<'a> Visitor<'a> for LaterUses<'a> {
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if let ast::ExprKind::Path(path) = &*expr.kind {
+            if expr.span.lo >= self.after {
+                if let Some((id, _)) = local(self.compilation, path) {
+                    self.used.insert(id);
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns the local variable that the path refers to, either directly or as the record of a
+/// field access like `pair.First`.
+fn local(compilation: &Compilation, path: &ast::Path) -> Option<(NodeId, Rc<str>)> {
+    if path.segments.is_none() {
+        match compilation.get_res(path.id) {
+            Some(resolve::Res::Local(id)) => Some((*id, path.name.name.clone())),
+            _ => None,
+        }
+    } else {
+        let (id, parts) =
+            resolve::path_as_field_accessor(&compilation.user_unit().ast.names, path)?;
+        Some((id, parts.first()?.name.clone()))
+    }
+}
+
+/// Collects the variables assigned to by the left-hand side of an assignment.
+fn assigned_locals(compilation: &Compilation, lhs: &ast::Expr, assigned: &mut FxHashSet<NodeId>) {
+    match &*lhs.kind {
+        ast::ExprKind::Path(path) => {
+            if let Some((id, _)) = local(compilation, path) {
+                assigned.insert(id);
+            }
+        }
+        ast::ExprKind::Paren(inner) => assigned_locals(compilation, inner, assigned),
+        ast::ExprKind::Tuple(items) => {
+            for item in items.iter() {
+                assigned_locals(compilation, item, assigned);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the variables bound by a pattern, along with whether they are mutable.
+fn bindings_of(pat: &ast::Pat, mutable: bool, bindings: &mut Vec<(NodeId, Rc<str>, bool)>) {
+    match &*pat.kind {
+        ast::PatKind::Bind(name, _) => bindings.push((name.id, name.name.clone(), mutable)),
+        ast::PatKind::Paren(inner) => bindings_of(inner, mutable, bindings),
+        ast::PatKind::Tuple(items) => {
+            for item in items.iter() {
+                bindings_of(item, mutable, bindings);
+            }
+        }
+        ast::PatKind::Discard(_) | ast::PatKind::Elided | ast::PatKind::Err => {}
+    }
+}
+
+fn has_qubit(ty: &Ty) -> bool {
+    match ty {
+        Ty::Prim(prim) => *prim == Prim::Qubit,
+        Ty::Array(item) => has_qubit(item),
+        Ty::Tuple(items) => items.iter().any(has_qubit),
+        _ => false,
+    }
+}
+
+/// Returns a name for the new callable that no callable in the package already has.
+fn unique_name(package: &ast::Package) -> String {
+    struct Names(FxHashSet<Rc<str>>);
+    impl<'a> Visitor<'a> for Names {
+        fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+            self.0.insert(decl.name.name.clone());
+            walk_callable_decl(self, decl);
+        }
+    }
+
+    let mut names = Names(FxHashSet::default());
+    names.visit_package(package);
+    let mut name = "Extracted".to_string();
+    let mut suffix = 1;
+    while names.0.contains(name.as_str()) {
+        suffix += 1;
+        name = format!("Extracted{suffix}");
+    }
+    name
+}
@@ -0,0 +1,302 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::{
+    code_action::get_code_actions, protocol::CodeAction,
+    test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding,
+};
+use indoc::indoc;
+
+/// Returns the extract code action for the range between the `◉` markers in the source text.
+fn extract(source_with_markers: &str) -> Option<CodeAction> {
+    let (compilation, target_spans) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    let range = target_spans[0];
+    get_code_actions(&compilation, "<source>", range, Encoding::Utf8)
+        .into_iter()
+        .find(|action| action.title.starts_with("Extract"))
+}
+
+/// Asserts that the statements are extracted with the given title, and returns the text that
+/// replaces them and the declaration of the new callable.
+fn check(source_with_markers: &str, title: &str) -> (String, String) {
+    let action = extract(source_with_markers).expect("statements should be extractable");
+    assert_eq!(action.title, title);
+    let edit = action.edit.expect("code action should have an edit");
+    let (_, edits) = &edit.changes[0];
+    assert_eq!(edits.len(), 2);
+    (edits[0].new_text.clone(), edits[1].new_text.clone())
+}
+
+#[test]
+fn captured_variable_becomes_parameter_and_later_use_is_returned() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                let a = 1;
+                ◉let b = a + 1;
+                let c = b * 2;◉
+                let d = c;
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "let c = Extracted(a);");
+    assert_eq!(
+        declaration,
+        "\n\n    function Extracted(a : Int) : Int {\n        let b = a + 1;\n        let c = b * 2;\n        c\n    }"
+    );
+}
+
+#[test]
+fn multiple_later_uses_are_returned_as_tuple() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                ◉let x = 1;
+                mutable y = 2.0;◉
+                set y += 1.0;
+                let z = (x, y);
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "mutable (x, y) = Extracted();");
+    assert!(
+        declaration.contains("function Extracted() : (Int, Double) {"),
+        "{declaration}"
+    );
+    assert!(
+        declaration.ends_with("\n        (x, y)\n    }"),
+        "{declaration}"
+    );
+}
+
+#[test]
+fn operation_call_makes_operation() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                ◉FakeStdLib.Fake();◉
+            }
+        }
+    "#},
+        "Extract into operation",
+    );
+    assert_eq!(call, "Extracted();");
+    assert_eq!(
+        declaration,
+        "\n\n    operation Extracted() : Unit {\n        FakeStdLib.Fake();\n    }"
+    );
+}
+
+#[test]
+fn operation_copies_functors_of_enclosing_operation() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit is Adj + Ctl {
+                ◉FakeStdLib.FakeCtlAdj();◉
+            }
+        }
+    "#},
+        "Extract into operation",
+    );
+    assert_eq!(call, "Extracted();");
+    assert_eq!(
+        declaration,
+        "\n\n    operation Extracted() : Unit is Adj + Ctl {\n        FakeStdLib.FakeCtlAdj();\n    }"
+    );
+}
+
+#[test]
+fn function_does_not_copy_functors_of_enclosing_operation() {
+    let (_, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit is Adj {
+                ◉let a = 1;◉
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert!(
+        declaration.contains("function Extracted() : Unit {"),
+        "{declaration}"
+    );
+}
+
+#[test]
+fn explicit_specialization_of_operation_with_functors_is_not_extracted() {
+    assert!(extract(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit is Adj {
+                body ... {
+                    ◉FakeStdLib.FakeCtlAdj();◉
+                }
+                adjoint self;
+            }
+        }
+    "#})
+    .is_none());
+}
+
+#[test]
+fn qubit_parameter_makes_operation() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                use q = Qubit();
+                ◉let pair = (q, 1);◉
+            }
+        }
+    "#},
+        "Extract into operation",
+    );
+    assert_eq!(call, "Extracted(q);");
+    assert!(
+        declaration.contains("operation Extracted(q : Qubit) : Unit {"),
+        "{declaration}"
+    );
+}
+
+#[test]
+fn trailing_expression_becomes_return_value() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                let a = 1;
+                ◉a + 1◉
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "Extracted(a)");
+    assert_eq!(
+        declaration,
+        "\n\n    function Extracted(a : Int) : Int {\n        a + 1\n    }"
+    );
+}
+
+#[test]
+fn nested_statements_are_reindented() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            function Main(flag : Bool) : Unit {
+                if flag {
+                    ◉for i in 0..2 {
+                        let j = i;
+                    }◉
+                }
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "Extracted()");
+    assert_eq!(
+        declaration,
+        "\n\n    function Extracted() : Unit {\n        for i in 0..2 {\n            let j = i;\n        }\n    }"
+    );
+}
+
+#[test]
+fn name_does_not_clash_with_existing_callable() {
+    let (call, _) = check(
+        indoc! {r#"
+        namespace Test {
+            function Extracted() : Unit {}
+            function Main() : Unit {
+                ◉let x = 1;◉
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "Extracted2();");
+}
+
+#[test]
+fn assignment_to_captured_variable_is_not_extracted() {
+    assert!(extract(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                mutable x = 0;
+                ◉set x += 1;◉
+            }
+        }
+    "#})
+    .is_none());
+}
+
+#[test]
+fn return_is_not_extracted() {
+    assert!(extract(indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                ◉return 1;◉
+            }
+        }
+    "#})
+    .is_none());
+}
+
+#[test]
+fn partial_statement_is_not_extracted() {
+    assert!(extract(indoc! {r#"
+        namespace Test {
+            function Main() : Unit {
+                ◉let x = 1;
+                let y◉ = 2;
+            }
+        }
+    "#})
+    .is_none());
+}
+
+#[test]
+fn qubit_used_after_statements_is_not_extracted() {
+    assert!(extract(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                ◉use q = Qubit();◉
+                let r = q;
+            }
+        }
+    "#})
+    .is_none());
+}
+
+#[test]
+fn comments_move_with_statements() {
+    let (call, declaration) = check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Unit {
+                // Not moved.
+
+                // Moved.
+                ◉let a = 1;
+                let b = a;◉ // Also moved.
+                // Not moved either.
+            }
+        }
+    "#},
+        "Extract into function",
+    );
+    assert_eq!(call, "Extracted();");
+    assert_eq!(
+        declaration,
+        "\n\n    function Extracted() : Unit {\n        // Moved.\n        let a = 1;\n        let b = a; // Also moved.\n    }"
+    );
+}
@@ -0,0 +1,167 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_code_actions;
+use crate::{
+    protocol::CodeAction,
+    test_utils::{
+        compile_notebook_with_fake_stdlib, compile_with_fake_stdlib_and_markers_no_cursor,
+    },
+    Encoding,
+};
+use indoc::indoc;
+use qsc::line_column::{Position, Range};
+
+/// Returns the code actions for the range between the `◉` markers in the source text.
+fn code_actions(source_with_markers: &str) -> Vec<CodeAction> {
+    let (compilation, target_spans) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    get_code_actions(&compilation, "<source>", target_spans[0], Encoding::Utf8)
+}
+
+/// Asserts that the only code action is a stub with the given title, and returns the text it
+/// inserts along with the position it inserts it at.
+fn single_stub(actions: &[CodeAction], title: &str) -> (String, Position) {
+    assert_eq!(actions.len(), 1, "{actions:?}");
+    let action = &actions[0];
+    assert_eq!(action.title, title);
+    let edit = action
+        .edit
+        .as_ref()
+        .expect("code action should have an edit");
+    let (_, edits) = &edit.changes[0];
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range.start, edits[0].range.end);
+    (edits[0].new_text.clone(), edits[0].range.start)
+}
+
+#[test]
+fn stub_for_operation_call() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                use q = Qubit();
+                ◉Prepare◉(q, 1.0);
+            }
+        }
+    "#});
+    let (text, position) = single_stub(&actions, "Create operation `Prepare`");
+    assert_eq!(
+        text,
+        "\n\n    operation Prepare(q : Qubit, arg2 : Double) : Unit {\n        fail \"Not implemented\"\n    }"
+    );
+    assert_eq!(position, Position { line: 4, column: 5 });
+}
+
+#[test]
+fn stub_in_function_is_function_with_expected_output() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                let x : Int = ◉Twice◉(2);
+                x
+            }
+        }
+    "#});
+    let (text, _) = single_stub(&actions, "Create function `Twice`");
+    assert!(
+        text.contains("function Twice(arg1 : Int) : Int {"),
+        "{text}"
+    );
+}
+
+#[test]
+fn stub_with_no_arguments() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                ◉DoNothing◉();
+            }
+        }
+    "#});
+    let (text, _) = single_stub(&actions, "Create operation `DoNothing`");
+    assert!(text.contains("operation DoNothing() : Unit {"), "{text}");
+}
+
+#[test]
+fn unknown_argument_types_become_type_parameters() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                ◉Process◉([], Missing);
+            }
+        }
+    "#});
+    let (text, _) = single_stub(&actions, "Create operation `Process`");
+    assert!(
+        text.contains("operation Process<'T1, 'T2>(arg1 : 'T1, Missing : 'T2) : Unit {"),
+        "{text}"
+    );
+}
+
+#[test]
+fn duplicate_parameter_names_are_numbered() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                let x = 1;
+                ◉Add◉(x, x);
+            }
+        }
+    "#});
+    let (text, _) = single_stub(&actions, "Create operation `Add`");
+    assert!(
+        text.contains("operation Add(x : Int, x2 : Int) : Unit {"),
+        "{text}"
+    );
+}
+
+#[test]
+fn no_stub_for_unresolved_variable() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                let x = ◉y◉;
+            }
+        }
+    "#});
+    assert!(actions.is_empty(), "{actions:?}");
+}
+
+#[test]
+fn no_stub_for_qualified_name() {
+    let actions = code_actions(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                Other.◉Missing◉();
+            }
+        }
+    "#});
+    assert!(
+        actions
+            .iter()
+            .all(|action| !action.title.starts_with("Create")),
+        "{actions:?}"
+    );
+}
+
+#[test]
+fn stub_in_notebook_is_inserted_before_statement() {
+    let compilation =
+        compile_notebook_with_fake_stdlib([("cell1", "let x = 1;\nCompute(x);")].into_iter());
+    let actions = get_code_actions(
+        &compilation,
+        "cell1",
+        Range {
+            start: Position { line: 1, column: 0 },
+            end: Position { line: 1, column: 7 },
+        },
+        Encoding::Utf8,
+    );
+    let (text, position) = single_stub(&actions, "Create operation `Compute`");
+    assert_eq!(
+        text,
+        "operation Compute(x : Int) : Unit {\n    fail \"Not implemented\"\n}\n\n"
+    );
+    assert_eq!(position, Position { line: 1, column: 0 });
+}
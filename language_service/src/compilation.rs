@@ -12,6 +12,7 @@ use qsc::{
     project, resolve,
     target::Profile,
     CompileUnit, LanguageFeatures, PackageStore, PackageType, PassContext, SourceMap, Span,
+    StdFeatures,
 };
 use qsc_linter::{LintConfig, LintLevel};
 use std::sync::Arc;
@@ -60,11 +61,14 @@ impl Compilation {
 
         let source_map = SourceMap::new(sources.iter().map(|(x, y)| (x.clone(), y.clone())), None);
 
-        let mut package_store = PackageStore::new(compile::core());
-        let std_package_id =
-            package_store.insert(compile::std(&package_store, target_profile.into()));
+        let (mut package_store, std_package_id) =
+            compile::shared_store(target_profile.into(), Some(StdFeatures::all()));
+        let std_package_id = std_package_id.expect("package store should have std");
 
-        let (unit, mut compile_errors) = compile::compile(
+        // Language features like hover and completion need a package even when the sources have
+        // errors, and the user should see every error there is, not just those of the first
+        // phase that failed.
+        let (unit, mut compile_errors) = compile::compile_partial(
             &package_store,
             &[std_package_id],
             source_map,
@@ -298,7 +298,7 @@ impl CompletionListBuilder {
         all_except_core.reverse();
 
         for (package_id, _) in &all_except_core {
-            self.push_sorted_completions(Self::get_callables(
+            self.push_sorted_completions(Self::get_items(
                 compilation,
                 *package_id,
                 opens,
@@ -396,8 +396,8 @@ impl CompletionListBuilder {
         self.current_sort_group += 1;
     }
 
-    /// Get all callables in a package
-    fn get_callables<'a>(
+    /// Get all callables and types in a package
+    fn get_items<'a>(
         compilation: &'a Compilation,
         package_id: PackageId,
         // name and alias
@@ -442,72 +442,76 @@ impl CompletionListBuilder {
                                 }
                             }
                         }
-                        return match &i.kind {
-                            ItemKind::Callable(callable_decl) => {
-                                let name = callable_decl.name.name.as_ref();
-                                let detail =
-                                    Some(display.hir_callable_decl(callable_decl).to_string());
-                                // Everything that starts with a __ goes last in the list
-                                let sort_group = u32::from(name.starts_with("__"));
-                                let mut additional_edits = vec![];
-                                let mut qualification: Option<Vec<Rc<str>>> = None;
-                                match &current_namespace_name {
-                                    Some(curr_ns)
-                                        if *curr_ns == Into::<Vec<_>>::into(namespace) => {}
-                                    _ => {
-                                        // open is an option of option of Rc<str>
-                                        // the first option tells if it found an open with the namespace name
-                                        // the second, nested option tells if that open has an alias
-                                        let open = opens.iter().find_map(|(name, alias)| {
-                                            if *name == Into::<Vec<_>>::into(namespace) {
-                                                Some(alias)
-                                            } else {
-                                                None
-                                            }
-                                        });
-                                        qualification = match open {
-                                            Some(alias) => alias.clone().map(|x| vec![x]),
-                                            None => match insert_open_at {
-                                                Some(start) => {
-                                                    additional_edits.push(TextEdit {
-                                                        new_text: format!(
-                                                            "open {};{indent}",
-                                                            namespace.name()
-                                                        ),
-                                                        range: start,
-                                                    });
-                                                    None
-                                                }
-                                                None => Some(namespace.into()),
-                                            },
-                                        }
+                        let (name, kind, detail) = match &i.kind {
+                            ItemKind::Callable(callable_decl) => (
+                                callable_decl.name.name.as_ref(),
+                                CompletionItemKind::Function,
+                                display.hir_callable_decl(callable_decl).to_string(),
+                            ),
+                            ItemKind::Ty(name, udt) => (
+                                name.name.as_ref(),
+                                CompletionItemKind::Interface,
+                                display.hir_udt(udt).to_string(),
+                            ),
+                            ItemKind::Namespace(..) => return None,
+                        };
+                        // Everything that starts with a __ goes last in the list
+                        let sort_group = u32::from(name.starts_with("__"));
+                        let mut additional_edits = vec![];
+                        let mut qualification: Option<Vec<Rc<str>>> = None;
+                        match &current_namespace_name {
+                            Some(curr_ns) if *curr_ns == Into::<Vec<_>>::into(namespace) => {}
+                            _ => {
+                                // open is an option of option of Rc<str>
+                                // the first option tells if it found an open with the namespace name
+                                // the second, nested option tells if that open has an alias
+                                let open = opens.iter().find_map(|(name, alias)| {
+                                    if *name == Into::<Vec<_>>::into(namespace) {
+                                        Some(alias)
+                                    } else {
+                                        None
                                     }
-                                }
-
-                                let additional_text_edits = if additional_edits.is_empty() {
-                                    None
-                                } else {
-                                    Some(additional_edits)
-                                };
-
-                                let label = if let Some(qualification) = qualification {
-                                    format!("{}.{name}", qualification.join("."))
-                                } else {
-                                    name.to_owned()
-                                };
-                                Some((
-                                    CompletionItem {
-                                        label,
-                                        kind: CompletionItemKind::Function,
-                                        sort_text: None, // This will get filled in during `push_sorted_completions`
-                                        detail,
-                                        additional_text_edits,
+                                });
+                                qualification = match open {
+                                    Some(alias) => alias.clone().map(|x| vec![x]),
+                                    None => match insert_open_at {
+                                        Some(start) => {
+                                            additional_edits.push(TextEdit {
+                                                new_text: format!(
+                                                    "open {};{indent}",
+                                                    namespace.name()
+                                                ),
+                                                range: start,
+                                            });
+                                            None
+                                        }
+                                        None => Some(namespace.into()),
                                     },
-                                    sort_group,
-                                ))
+                                }
                             }
-                            _ => None,
+                        }
+
+                        let additional_text_edits = if additional_edits.is_empty() {
+                            None
+                        } else {
+                            Some(additional_edits)
+                        };
+
+                        let label = if let Some(qualification) = qualification {
+                            format!("{}.{name}", qualification.join("."))
+                        } else {
+                            name.to_owned()
                         };
+                        return Some((
+                            CompletionItem {
+                                label,
+                                kind,
+                                sort_text: None, // This will get filled in during `push_sorted_completions`
+                                detail: Some(detail),
+                                additional_text_edits,
+                            },
+                            sort_group,
+                        ));
                     }
                 }
             }
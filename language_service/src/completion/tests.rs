@@ -819,6 +819,114 @@ fn stdlib_udt() {
     );
 }
 
+#[test]
+fn stdlib_udt_type_auto_open() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation Foo() : Unit {
+                ↘
+            }
+        "#},
+        &["Udt"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Udt",
+                        kind: Interface,
+                        sort_text: Some(
+                            "0700Udt",
+                        ),
+                        detail: Some(
+                            "newtype Udt = (x : Int, y : Int)",
+                        ),
+                        additional_text_edits: Some(
+                            [
+                                TextEdit {
+                                    new_text: "open FakeStdLib;\n    ",
+                                    range: Range {
+                                        start: Position {
+                                            line: 1,
+                                            column: 4,
+                                        },
+                                        end: Position {
+                                            line: 1,
+                                            column: 4,
+                                        },
+                                    },
+                                },
+                            ],
+                        ),
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn stdlib_struct_type_from_open_namespace() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            open FakeStdLib;
+            operation Foo() : Unit {
+                ↘
+            }
+        "#},
+        &["FakeStruct"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "FakeStruct",
+                        kind: Interface,
+                        sort_text: Some(
+                            "0700FakeStruct",
+                        ),
+                        detail: Some(
+                            "struct FakeStruct { x : Int, y : Int }",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn stdlib_udt_type_with_alias() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            open FakeStdLib as Alias;
+            operation Foo() : Unit {
+                ↘
+            }
+        "#},
+        &["Alias.Udt"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Alias.Udt",
+                        kind: Interface,
+                        sort_text: Some(
+                            "0700Alias.Udt",
+                        ),
+                        detail: Some(
+                            "newtype Udt = (x : Int, y : Int)",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn notebook_top_level() {
     check_notebook(
@@ -672,3 +672,8 @@ fn notebook_callable_defined_in_later_cell() {
         ("cell2", "operation Callee() : Unit {}"),
     ]);
 }
+
+#[test]
+fn notebook_local_def_across_cells() {
+    assert_definition_notebook(&[("cell1", "let ◉x◉ = 1;"), ("cell2", "let y = ↘x + 1;")]);
+}
@@ -0,0 +1,183 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::protocol::{DocumentSymbol, DocumentSymbolKind};
+use crate::qsc_utils::into_range;
+use qsc::ast;
+use qsc::display::CodeDisplay;
+use qsc::line_column::Encoding;
+use qsc::Span;
+
+pub(crate) fn get_document_symbols(
+    compilation: &Compilation,
+    source_name: &str,
+    position_encoding: Encoding,
+) -> Vec<DocumentSymbol> {
+    let source_span = compilation.package_span_of_source(source_name);
+    let outline = Outline {
+        compilation,
+        display: CodeDisplay { compilation },
+        position_encoding,
+    };
+
+    compilation
+        .user_unit()
+        .ast
+        .package
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            ast::TopLevelNode::Namespace(namespace) if source_span.contains(namespace.span.lo) => {
+                Some(outline.namespace(namespace))
+            }
+            // Notebook cells and other fragments can declare items outside of a namespace.
+            ast::TopLevelNode::Stmt(stmt) if source_span.contains(stmt.span.lo) => {
+                match &*stmt.kind {
+                    ast::StmtKind::Item(item) => outline.item(item),
+                    _ => None,
+                }
+            }
+            ast::TopLevelNode::Namespace(_) | ast::TopLevelNode::Stmt(_) => None,
+        })
+        .collect()
+}
+
+struct Outline<'a> {
+    compilation: &'a Compilation,
+    display: CodeDisplay<'a>,
+    position_encoding: Encoding,
+}
+
+impl<'a> Outline<'a> {
+    fn symbol(
+        &self,
+        name: impl Into<String>,
+        kind: DocumentSymbolKind,
+        detail: Option<String>,
+        span: Span,
+        name_span: Span,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        let sources = &self.compilation.user_unit().sources;
+        DocumentSymbol {
+            name: name.into(),
+            kind,
+            detail,
+            range: into_range(self.position_encoding, span, sources),
+            selection_range: into_range(self.position_encoding, name_span, sources),
+            children,
+        }
+    }
+
+    fn namespace(&self, namespace: &'a ast::Namespace) -> DocumentSymbol {
+        self.symbol(
+            namespace.name.name().as_ref(),
+            DocumentSymbolKind::Namespace,
+            None,
+            namespace.span,
+            namespace.name.span(),
+            namespace
+                .items
+                .iter()
+                .filter_map(|item| self.item(item))
+                .collect(),
+        )
+    }
+
+    fn item(&self, item: &'a ast::Item) -> Option<DocumentSymbol> {
+        match &*item.kind {
+            ast::ItemKind::Callable(decl) => {
+                let kind = match decl.kind {
+                    ast::CallableKind::Function => DocumentSymbolKind::Function,
+                    ast::CallableKind::Operation => DocumentSymbolKind::Operation,
+                };
+                let specs = match &*decl.body {
+                    ast::CallableBody::Block(_) => vec![],
+                    ast::CallableBody::Specs(specs) => {
+                        specs.iter().map(|spec| self.spec(spec)).collect()
+                    }
+                };
+                Some(self.symbol(
+                    decl.name.name.as_ref(),
+                    kind,
+                    Some(self.display.ast_callable_decl(decl).to_string()),
+                    item.span,
+                    decl.name.span,
+                    specs,
+                ))
+            }
+            ast::ItemKind::Ty(name, def) => {
+                let mut fields = vec![];
+                self.ty_def_fields(def, &mut fields);
+                Some(self.symbol(
+                    name.name.as_ref(),
+                    DocumentSymbolKind::Newtype,
+                    Some(self.display.ident_ty_def(name, def).to_string()),
+                    item.span,
+                    name.span,
+                    fields,
+                ))
+            }
+            ast::ItemKind::Struct(decl) => Some(
+                self.symbol(
+                    decl.name.name.as_ref(),
+                    DocumentSymbolKind::Struct,
+                    Some(self.display.struct_decl(decl).to_string()),
+                    item.span,
+                    decl.name.span,
+                    decl.fields
+                        .iter()
+                        .map(|field| self.field(&field.name, &field.ty, field.span))
+                        .collect(),
+                ),
+            ),
+            ast::ItemKind::Err | ast::ItemKind::Open(..) | ast::ItemKind::ImportOrExport(_) => None,
+        }
+    }
+
+    fn spec(&self, spec: &ast::SpecDecl) -> DocumentSymbol {
+        let name = match spec.spec {
+            ast::Spec::Body => "body",
+            ast::Spec::Adj => "adjoint",
+            ast::Spec::Ctl => "controlled",
+            ast::Spec::CtlAdj => "controlled adjoint",
+        };
+        self.symbol(
+            name,
+            DocumentSymbolKind::Specialization,
+            None,
+            spec.span,
+            spec.span,
+            vec![],
+        )
+    }
+
+    fn field(&self, name: &'a ast::Ident, ty: &'a ast::Ty, span: Span) -> DocumentSymbol {
+        self.symbol(
+            name.name.as_ref(),
+            DocumentSymbolKind::Field,
+            Some(self.display.ident_ty(name, ty).to_string()),
+            span,
+            name.span,
+            vec![],
+        )
+    }
+
+    /// Collects the named fields of a newtype, including those in nested tuples.
+    fn ty_def_fields(&self, def: &'a ast::TyDef, fields: &mut Vec<DocumentSymbol>) {
+        match &*def.kind {
+            ast::TyDefKind::Field(Some(name), ty) => fields.push(self.field(name, ty, def.span)),
+            ast::TyDefKind::Paren(inner) => self.ty_def_fields(inner, fields),
+            ast::TyDefKind::Tuple(defs) => {
+                for def in defs.iter() {
+                    self.ty_def_fields(def, fields);
+                }
+            }
+            ast::TyDefKind::Field(None, _) | ast::TyDefKind::Err => {}
+        }
+    }
+}
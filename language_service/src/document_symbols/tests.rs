@@ -0,0 +1,103 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_document_symbols;
+use crate::{
+    protocol::DocumentSymbol,
+    test_utils::{
+        compile_notebook_with_fake_stdlib, compile_with_fake_stdlib_and_markers_no_cursor,
+    },
+    Encoding,
+};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+fn render(symbols: &[DocumentSymbol], depth: usize, out: &mut String) {
+    for symbol in symbols {
+        out.push_str(&"    ".repeat(depth));
+        out.push_str(&format!("{:?} {}", symbol.kind, symbol.name));
+        if let Some(detail) = &symbol.detail {
+            out.push_str(&format!(": {detail}"));
+        }
+        out.push('\n');
+        render(&symbol.children, depth + 1, out);
+    }
+}
+
+fn check(source: &str, expect: &Expect) {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let symbols = get_document_symbols(&compilation, "<source>", Encoding::Utf8);
+    let mut actual = String::new();
+    render(&symbols, 0, &mut actual);
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn namespaces_and_items() {
+    check(
+        indoc! {r#"
+            namespace A.B {
+                open FakeStdLib;
+                function F(x : Int) : Int { x }
+                operation G(q : Qubit) : Unit is Adj {
+                    body ... {}
+                    adjoint self;
+                }
+            }
+            namespace C {
+                newtype Pair = (First : Int, (Second : Double, Bool));
+                struct Point { X : Double, Y : Double }
+            }
+        "#},
+        &expect![[r#"
+            Namespace A.B
+                Function F: function F(x : Int) : Int
+                Operation G: operation G(q : Qubit) : Unit is Adj
+                    Specialization body
+                    Specialization adjoint
+            Namespace C
+                Newtype Pair: newtype Pair = (First : Int, (Second : Double, Bool))
+                    Field First: First : Int
+                    Field Second: Second : Double
+                Struct Point: struct Point { X : Double, Y : Double }
+                    Field X: X : Double
+                    Field Y: Y : Double
+        "#]],
+    );
+}
+
+#[test]
+fn ranges_cover_declaration_and_select_name() {
+    let source = indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {}
+        }
+    "#};
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let symbols = get_document_symbols(&compilation, "<source>", Encoding::Utf8);
+    let main = &symbols[0].children[0];
+    assert_eq!(main.range.start.line, 1);
+    assert_eq!(main.range.start.column, 4);
+    assert_eq!(main.range.end.column, 30);
+    assert_eq!(main.selection_range.start.column, 14);
+    assert_eq!(main.selection_range.end.column, 18);
+    assert_eq!(symbols[0].selection_range.start.column, 10);
+    assert_eq!(symbols[0].selection_range.end.column, 14);
+}
+
+#[test]
+fn notebook_cell_items_outside_namespace() {
+    let compilation = compile_notebook_with_fake_stdlib(
+        [
+            ("cell1", "operation Foo() : Unit {}\nlet x = 1;"),
+            ("cell2", "function Bar() : Unit {}"),
+        ]
+        .into_iter(),
+    );
+    let symbols = get_document_symbols(&compilation, "cell2", Encoding::Utf8);
+    let names = symbols
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Bar"]);
+}
@@ -0,0 +1,139 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::protocol::{FoldingRange, FoldingRangeKind};
+use crate::qsc_utils::into_range;
+use qsc::ast;
+use qsc::ast::visit::{walk_block, walk_item, walk_namespace, walk_spec_decl, Visitor};
+use qsc::line_column::Encoding;
+use qsc::Span;
+
+pub(crate) fn get_folding_ranges(
+    compilation: &Compilation,
+    source_name: &str,
+    position_encoding: Encoding,
+) -> Vec<FoldingRange> {
+    let source_span = compilation.package_span_of_source(source_name);
+    let mut folder = Folder {
+        compilation,
+        position_encoding,
+        ranges: vec![],
+    };
+    for node in compilation.user_unit().ast.package.nodes.iter() {
+        match node {
+            ast::TopLevelNode::Namespace(namespace) if source_span.contains(namespace.span.lo) => {
+                folder.visit_namespace(namespace);
+            }
+            ast::TopLevelNode::Stmt(stmt) if source_span.contains(stmt.span.lo) => {
+                folder.visit_stmt(stmt);
+            }
+            ast::TopLevelNode::Namespace(_) | ast::TopLevelNode::Stmt(_) => {}
+        }
+    }
+
+    // A callable and its body block usually start on the same line, and only the outer one is
+    // useful to fold.
+    let mut ranges = folder.ranges;
+    ranges.sort_by(|a, b| {
+        a.start_line
+            .cmp(&b.start_line)
+            .then(b.end_line.cmp(&a.end_line))
+    });
+    ranges.dedup_by_key(|range| range.start_line);
+    ranges
+}
+
+struct Folder<'a> {
+    compilation: &'a Compilation,
+    position_encoding: Encoding,
+    ranges: Vec<FoldingRange>,
+}
+
+impl Folder<'_> {
+    /// Adds a range that ends with a closing delimiter, which is left visible.
+    fn push_delimited(&mut self, span: Span) {
+        let range = into_range(
+            self.position_encoding,
+            span,
+            &self.compilation.user_unit().sources,
+        );
+        self.push(
+            range.start.line,
+            range.end.line.saturating_sub(1),
+            FoldingRangeKind::Block,
+        );
+    }
+
+    fn push(&mut self, start_line: u32, end_line: u32, kind: FoldingRangeKind) {
+        if end_line > start_line {
+            self.ranges.push(FoldingRange {
+                start_line,
+                end_line,
+                kind,
+            });
+        }
+    }
+
+    /// Adds a range for each run of consecutive `open`, `import` and `export` statements.
+    fn push_imports(&mut self, items: &[Box<ast::Item>]) {
+        let is_import = |item: &ast::Item| {
+            matches!(
+                &*item.kind,
+                ast::ItemKind::Open(..) | ast::ItemKind::ImportOrExport(_)
+            )
+        };
+        let mut rest = items;
+        while let Some(start) = rest.iter().position(|item| is_import(item)) {
+            let run = rest[start..]
+                .iter()
+                .take_while(|item| is_import(item))
+                .count();
+            let first = &rest[start];
+            let last = &rest[start + run - 1];
+            let range = into_range(
+                self.position_encoding,
+                Span {
+                    lo: first.span.lo,
+                    hi: last.span.hi,
+                },
+                &self.compilation.user_unit().sources,
+            );
+            self.push(range.start.line, range.end.line, FoldingRangeKind::Imports);
+            rest = &rest[start + run..];
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Folder<'_> {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        self.push_delimited(namespace.span);
+        self.push_imports(&namespace.items);
+        walk_namespace(self, namespace);
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        match &*item.kind {
+            ast::ItemKind::Callable(decl) => self.push_delimited(decl.span),
+            ast::ItemKind::Struct(decl) => self.push_delimited(decl.span),
+            ast::ItemKind::Ty(..) => self.push_delimited(item.span),
+            ast::ItemKind::Err | ast::ItemKind::Open(..) | ast::ItemKind::ImportOrExport(_) => {}
+        }
+        walk_item(self, item);
+    }
+
+    fn visit_spec_decl(&mut self, decl: &'a ast::SpecDecl) {
+        self.push_delimited(decl.span);
+        walk_spec_decl(self, decl);
+    }
+
+    fn visit_block(&mut self, block: &'a ast::Block) {
+        // This covers callable bodies as well as the blocks of loops, conditionals, qubit
+        // allocations and `within`/`apply` expressions.
+        self.push_delimited(block.span);
+        walk_block(self, block);
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_folding_ranges;
+use crate::{
+    test_utils::{
+        compile_notebook_with_fake_stdlib, compile_with_fake_stdlib_and_markers_no_cursor,
+    },
+    Encoding,
+};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+fn check(source: &str, expect: &Expect) {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let ranges = get_folding_ranges(&compilation, "<source>", Encoding::Utf8);
+    let lines = source.lines().collect::<Vec<_>>();
+    let actual = ranges
+        .iter()
+        .map(|range| {
+            format!(
+                "{}-{} {:?}: {}\n",
+                range.start_line,
+                range.end_line,
+                range.kind,
+                lines[range.start_line as usize].trim()
+            )
+        })
+        .collect::<String>();
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn namespace_callable_and_nested_blocks() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                open FakeStdLib;
+                open Microsoft.Quantum.Unstable;
+
+                operation Main() : Unit {
+                    use q = Qubit();
+                    within {
+                        Fake();
+                    } apply {
+                        FakeWithParam(1);
+                    }
+                    if true {
+                        let x = 1;
+                    }
+                }
+            }
+        "#},
+        &expect![[r#"
+            0-14 Block: namespace Test {
+            1-2 Imports: open FakeStdLib;
+            4-13 Block: operation Main() : Unit {
+            6-7 Block: within {
+            8-9 Block: } apply {
+            11-12 Block: if true {
+        "#]],
+    );
+}
+
+#[test]
+fn specializations_and_types() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation Op(q : Qubit) : Unit is Adj {
+                    body ... {
+                        Fake();
+                    }
+                    adjoint self;
+                }
+                struct Point {
+                    X : Double,
+                    Y : Double,
+                }
+                newtype Pair = (
+                    First : Int,
+                    Second : Int
+                );
+            }
+        "#},
+        &expect![[r#"
+            0-14 Block: namespace Test {
+            1-5 Block: operation Op(q : Qubit) : Unit is Adj {
+            2-3 Block: body ... {
+            7-9 Block: struct Point {
+            11-13 Block: newtype Pair = (
+        "#]],
+    );
+}
+
+#[test]
+fn single_line_blocks_do_not_fold() {
+    check(
+        indoc! {r#"
+            namespace Test { function F() : Unit { } }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn notebook_cell_items_outside_namespace() {
+    let compilation = compile_notebook_with_fake_stdlib(
+        [
+            ("cell1", "operation Foo() : Unit {\n    Fake();\n}"),
+            ("cell2", "for i in 0..2 {\n    Foo();\n}"),
+        ]
+        .into_iter(),
+    );
+    let ranges = get_folding_ranges(&compilation, "cell2", Encoding::Utf8);
+    assert_eq!(ranges.len(), 1);
+    assert_eq!((ranges[0].start_line, ranges[0].end_line), (0, 1));
+}
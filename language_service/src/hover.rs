@@ -8,10 +8,10 @@ use crate::compilation::Compilation;
 use crate::name_locator::{Handler, Locator, LocatorContext};
 use crate::protocol::Hover;
 use crate::qsc_utils::into_range;
-use qsc::ast::visit::Visitor;
+use qsc::ast::visit::{walk_pat, walk_stmt, Visitor};
 use qsc::display::{parse_doc_for_param, parse_doc_for_summary, CodeDisplay, Lookup};
 use qsc::line_column::{Encoding, Position, Range};
-use qsc::{ast, hir, Span};
+use qsc::{ast, hir, trivia::Trivia, Span};
 use std::fmt::Display;
 use std::rc::Rc;
 
@@ -104,6 +104,7 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
             &def_name.name,
             callable_name,
             &context.current_item_doc,
+            "",
         );
         self.hover = Some(Hover {
             contents,
@@ -130,6 +131,7 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
             &reference.name,
             callable_name,
             &context.current_item_doc,
+            "",
         );
         self.hover = Some(Hover {
             contents,
@@ -245,17 +247,17 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
         } else {
             LocalKind::Local
         };
-        let callable_name = &context
+        // Locals outside of a callable are bound by top-level statements, like in notebooks.
+        let callable_name = context
             .current_callable
-            .expect("locals should only exist in callables")
-            .name
-            .name;
+            .map_or("", |decl| decl.name.name.as_ref());
         let contents = display_local(
             &kind,
             &code,
             &ident.name,
             callable_name,
             &context.current_item_doc,
+            &declaration_comments(self.compilation, ident),
         );
         self.hover = Some(Hover {
             contents,
@@ -271,11 +273,9 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
         definition: &'a ast::Ident,
     ) {
         let local_name = &definition.name;
-        let callable_name = &context
+        let callable_name = context
             .current_callable
-            .expect("locals should only exist in callables")
-            .name
-            .name;
+            .map_or("", |decl| decl.name.name.as_ref());
         let code = markdown_fenced_block(self.display.name_ty_id(local_name, node_id));
         let kind = if is_param(&curr_callable_to_params(context.current_callable), node_id) {
             LocalKind::Param
@@ -290,6 +290,7 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
             local_name,
             callable_name,
             &context.current_item_doc,
+            &declaration_comments(self.compilation, definition),
         );
         self.hover = Some(Hover {
             contents,
@@ -353,20 +354,91 @@ fn is_param(param_pats: &[&ast::Pat], node_id: ast::NodeId) -> bool {
     param_pats.iter().any(|pat| find_in_pat(pat, node_id))
 }
 
+/// Returns the text of the comments on the declaration of a local: the comments on the lines
+/// directly above the statement or parameter that declares it, and the comment at the end of its
+/// line, one per line and without the `//`.
+fn declaration_comments(compilation: &Compilation, ident: &ast::Ident) -> String {
+    let mut finder = DeclarationFinder { ident, span: None };
+    finder.visit_package(&compilation.user_unit().ast.package);
+    let Some(span) = finder.span else {
+        return String::new();
+    };
+    let Some(source) = compilation.user_unit().sources.find_by_offset(span.lo) else {
+        return String::new();
+    };
+
+    let trivia = Trivia::new(&source.contents, source.offset);
+    // The comment after a parameter follows the comma that separates it from the next one.
+    let hi = if source.contents[(span.hi - source.offset) as usize..].starts_with(',') {
+        span.hi + 1
+    } else {
+        span.hi
+    };
+    trivia
+        .leading_comments(span.lo)
+        .into_iter()
+        .chain(trivia.trailing_comment(hi))
+        .map(|comment| {
+            source.contents
+                [(comment.lo - source.offset) as usize..(comment.hi - source.offset) as usize]
+                .trim_start_matches('/')
+                .trim()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Finds the span of the statement or parameter that declares a local.
+struct DeclarationFinder<'a> {
+    ident: &'a ast::Ident,
+    span: Option<Span>,
+}
+
+impl<'a> Visitor<'a> for DeclarationFinder<'_> {
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if self.span.is_some() {
+            return;
+        }
+        match &*stmt.kind {
+            ast::StmtKind::Local(_, pat, _) | ast::StmtKind::Qubit(_, pat, _, _)
+                if pat.span.lo <= self.ident.span.lo && self.ident.span.hi <= pat.span.hi =>
+            {
+                self.span = Some(stmt.span);
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if self.span.is_some() {
+            return;
+        }
+        match &*pat.kind {
+            ast::PatKind::Bind(ident, _) if ident.id == self.ident.id => {
+                self.span = Some(pat.span);
+            }
+            _ => walk_pat(self, pat),
+        }
+    }
+}
+
 fn display_local(
     param_kind: &LocalKind,
     markdown: &String,
     local_name: &str,
     callable_name: &str,
     callable_doc: &str,
+    comments: &str,
 ) -> String {
     match param_kind {
         LocalKind::Param => {
             let param_doc = parse_doc_for_param(callable_doc, local_name);
-            with_doc(
-                &param_doc,
-                format!("parameter of `{callable_name}`\n{markdown}",),
-            )
+            let doc = match (param_doc.is_empty(), comments.is_empty()) {
+                (_, true) => param_doc,
+                (true, false) => comments.to_string(),
+                (false, false) => format!("{param_doc}\n\n{comments}"),
+            };
+            with_doc(&doc, format!("parameter of `{callable_name}`\n{markdown}",))
         }
         LocalKind::TypeParam => {
             let param_doc = parse_doc_for_param(callable_doc, local_name);
@@ -375,8 +447,11 @@ fn display_local(
                 format!("type parameter of `{callable_name}`\n{markdown}",),
             )
         }
-        LocalKind::LambdaParam => format!("lambda parameter\n{markdown}"),
-        LocalKind::Local => format!("local\n{markdown}"),
+        LocalKind::LambdaParam => with_doc(
+            &comments.to_string(),
+            format!("lambda parameter\n{markdown}"),
+        ),
+        LocalKind::Local => with_doc(&comments.to_string(), format!("local\n{markdown}")),
     }
 }
 
@@ -1596,3 +1596,146 @@ fn notebook_callable_defined_in_later_cell() {
         ("cell2", "operation Callee() : Unit {}"),
     ]);
 }
+
+#[test]
+fn notebook_local_def() {
+    check_notebook(
+        &[("cell1", "let ◉↘x◉ = 1;")],
+        &expect![[r#"
+            local
+            ```qsharp
+            x : Int
+            ```
+        "#]],
+    );
+}
+
+#[test]
+fn notebook_local_ref_across_cells() {
+    check_notebook(
+        &[("cell1", "let x = 1;"), ("cell2", "let y = ◉↘x◉ + 1;")],
+        &expect![[r#"
+            local
+            ```qsharp
+            x : Int
+            ```
+        "#]],
+    );
+}
+
+#[test]
+fn local_with_comments() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation Foo() : Unit {
+                // Unrelated.
+
+                // The number of rounds.
+                let ◉↘rounds◉ = 3; // At least one.
+            }
+        }
+    "#},
+        &expect![[r#"
+            local
+            ```qsharp
+            rounds : Int
+            ```
+            ---
+            The number of rounds.
+            At least one.
+        "#]],
+    );
+}
+
+#[test]
+fn local_ref_with_comments() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation Foo() : Unit {
+                let rounds = 3; // The number of rounds.
+                let next = ◉↘rounds◉ + 1;
+            }
+        }
+    "#},
+        &expect![[r#"
+            local
+            ```qsharp
+            rounds : Int
+            ```
+            ---
+            The number of rounds.
+        "#]],
+    );
+}
+
+#[test]
+fn param_with_comments() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation Foo(
+                // The first value.
+                a : Int,
+                b : Int, // The second value.
+                c : Int
+            ) : Unit {
+                let c = a + ◉↘b◉;
+            }
+        }
+    "#},
+        &expect![[r#"
+            parameter of `Foo`
+            ```qsharp
+            b : Int
+            ```
+            ---
+            The second value.
+        "#]],
+    );
+}
+
+#[test]
+fn param_with_doc_and_comments() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            /// # Input
+            /// ## a
+            /// Doc string for `a`
+            operation Foo(
+                ◉↘a◉ : Int // Comment for `a`
+            ) : Unit {}
+        }
+    "#},
+        &expect![[r#"
+            parameter of `Foo`
+            ```qsharp
+            a : Int
+            ```
+            ---
+            Doc string for `a`
+
+            Comment for `a`
+        "#]],
+    );
+}
+
+#[test]
+fn notebook_local_with_comments_across_cells() {
+    check_notebook(
+        &[
+            ("cell1", "// The number of rounds.\nlet x = 1;"),
+            ("cell2", "let y = ◉↘x◉ + 1;"),
+        ],
+        &expect![[r#"
+            local
+            ```qsharp
+            x : Int
+            ```
+            ---
+            The number of rounds.
+        "#]],
+    );
+}
@@ -0,0 +1,206 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::protocol::{InlayHint, InlayHintKind};
+use crate::qsc_utils::into_range;
+use qsc::ast::visit::{walk_callable_decl, walk_expr, walk_stmt, Visitor};
+use qsc::ast::{self, FunctorExprKind};
+use qsc::display::Lookup;
+use qsc::hir::ty::Ty;
+use qsc::line_column::{Encoding, Range};
+use qsc::Span;
+
+pub(crate) fn get_inlay_hints(
+    compilation: &Compilation,
+    source_name: &str,
+    range: Range,
+    position_encoding: Encoding,
+) -> Vec<InlayHint> {
+    let span = compilation.source_range_to_package_span(source_name, range, position_encoding);
+    let mut finder = HintFinder {
+        compilation,
+        span,
+        position_encoding,
+        hints: vec![],
+    };
+    finder.visit_package(&compilation.user_unit().ast.package);
+    finder.hints
+}
+
+struct HintFinder<'a> {
+    compilation: &'a Compilation,
+    span: Span,
+    position_encoding: Encoding,
+    hints: Vec<InlayHint>,
+}
+
+impl HintFinder<'_> {
+    fn push(&mut self, offset: u32, label: String, kind: InlayHintKind) {
+        if offset < self.span.lo || offset > self.span.hi {
+            return;
+        }
+        let position = into_range(
+            self.position_encoding,
+            Span {
+                lo: offset,
+                hi: offset,
+            },
+            &self.compilation.user_unit().sources,
+        )
+        .start;
+        self.hints.push(InlayHint {
+            position,
+            label,
+            kind,
+        });
+    }
+
+    /// Adds the inferred types of the variables bound by a pattern that have no type annotation,
+    /// and the length of the array the pattern binds if it is known.
+    fn push_bindings(&mut self, pat: &ast::Pat, length: Option<usize>) {
+        match &*pat.kind {
+            ast::PatKind::Bind(name, None) => {
+                if let Some(ty) = self.compilation.get_ty(name.id).filter(|ty| is_known(ty)) {
+                    let label = format!(": {}", ty.display());
+                    self.push(name.span.hi, label, InlayHintKind::Type);
+                    if let (Ty::Array(_), Some(length)) = (ty, length) {
+                        self.push(
+                            name.span.hi,
+                            format!("length {length}"),
+                            InlayHintKind::Length,
+                        );
+                    }
+                }
+            }
+            ast::PatKind::Paren(inner) => self.push_bindings(inner, length),
+            ast::PatKind::Tuple(items) => {
+                for item in items.iter() {
+                    self.push_bindings(item, None);
+                }
+            }
+            ast::PatKind::Bind(_, Some(_))
+            | ast::PatKind::Discard(_)
+            | ast::PatKind::Elided
+            | ast::PatKind::Err => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for HintFinder<'a> {
+    fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+        // Specializations of the declared functors that are not written out are generated by
+        // the compiler from the body.
+        if let Some(functors) = &decl.functors {
+            let (adj, ctl) = functor_set(functors);
+            let declared = match &*decl.body {
+                ast::CallableBody::Block(_) => vec![],
+                ast::CallableBody::Specs(specs) => specs.iter().map(|spec| spec.spec).collect(),
+            };
+            let generated = [
+                (adj, ast::Spec::Adj, "adjoint"),
+                (ctl, ast::Spec::Ctl, "controlled"),
+                (adj && ctl, ast::Spec::CtlAdj, "controlled adjoint"),
+            ]
+            .into_iter()
+            .filter(|(supported, spec, _)| *supported && !declared.contains(spec))
+            .map(|(_, _, name)| name)
+            .collect::<Vec<_>>();
+            if !generated.is_empty() {
+                let label = format!("generates {}", generated.join(", "));
+                self.push(functors.span.hi, label, InlayHintKind::Functor);
+            }
+        }
+        walk_callable_decl(self, decl);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        match &*stmt.kind {
+            ast::StmtKind::Local(_, pat, init) => self.push_bindings(pat, array_length(init)),
+            // The type of an allocated qubit or qubit array is already written in the
+            // initializer, but the length may not be.
+            ast::StmtKind::Qubit(_, pat, init, _) => {
+                if let (ast::PatKind::Bind(name, _), ast::QubitInitKind::Array(size)) =
+                    (&*pat.kind, &*init.kind)
+                {
+                    if let Some(length) = int_lit(size) {
+                        self.push(
+                            name.span.hi,
+                            format!("length {length}"),
+                            InlayHintKind::Length,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        match &*expr.kind {
+            ast::ExprKind::For(pat, _, _) => self.push_bindings(pat, None),
+            // The adjoint of the within block runs after the apply block.
+            ast::ExprKind::Conjugate(_, apply) => {
+                self.push(
+                    apply.span.hi,
+                    "Adjoint within".to_string(),
+                    InlayHintKind::Functor,
+                );
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns whether the functor expression includes `Adj` and `Ctl`.
+fn functor_set(expr: &ast::FunctorExpr) -> (bool, bool) {
+    match &*expr.kind {
+        FunctorExprKind::Lit(ast::Functor::Adj) => (true, false),
+        FunctorExprKind::Lit(ast::Functor::Ctl) => (false, true),
+        FunctorExprKind::Paren(inner) => functor_set(inner),
+        FunctorExprKind::BinOp(op, lhs, rhs) => {
+            let (lhs_adj, lhs_ctl) = functor_set(lhs);
+            let (rhs_adj, rhs_ctl) = functor_set(rhs);
+            match op {
+                ast::SetOp::Union => (lhs_adj || rhs_adj, lhs_ctl || rhs_ctl),
+                ast::SetOp::Intersect => (lhs_adj && rhs_adj, lhs_ctl && rhs_ctl),
+            }
+        }
+    }
+}
+
+/// Returns the length of an array literal, or of a repeated array with a literal size.
+fn array_length(expr: &ast::Expr) -> Option<usize> {
+    match &*expr.kind {
+        ast::ExprKind::Array(items) => Some(items.len()),
+        ast::ExprKind::ArrayRepeat(_, size) => int_lit(size),
+        ast::ExprKind::Paren(inner) => array_length(inner),
+        _ => None,
+    }
+}
+
+fn int_lit(expr: &ast::Expr) -> Option<usize> {
+    match &*expr.kind {
+        ast::ExprKind::Lit(lit) => match &**lit {
+            ast::Lit::Int(value) => usize::try_from(*value).ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns true if the type is fully inferred.
+fn is_known(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_known(item),
+        Ty::Arrow(arrow) => is_known(&arrow.input) && is_known(&arrow.output),
+        Ty::Tuple(items) => items.iter().all(is_known),
+        Ty::Infer(_) | Ty::Err => false,
+        Ty::Param(..) | Ty::Prim(_) | Ty::Udt(..) => true,
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_inlay_hints;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::line_column::{Position, Range};
+
+/// Asserts that the source, with the labels of the inlay hints for the whole source inserted
+/// at their positions, matches the expected text.
+fn check(source: &str, expect: &Expect) {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let range = Range {
+        start: Position { line: 0, column: 0 },
+        end: Position {
+            line: u32::MAX,
+            column: 0,
+        },
+    };
+    let mut hints = get_inlay_hints(&compilation, "<source>", range, Encoding::Utf8);
+    hints.sort_by_key(|hint| (hint.position.line, hint.position.column));
+
+    let mut actual = String::new();
+    for (line_number, line) in source.lines().enumerate() {
+        let mut column = 0;
+        for hint in hints
+            .iter()
+            .filter(|hint| hint.position.line as usize == line_number)
+        {
+            let hint_column = hint.position.column as usize;
+            actual.push_str(&line[column..hint_column]);
+            actual.push_str(&format!("⟨{}⟩", hint.label));
+            column = hint_column;
+        }
+        actual.push_str(&line[column..]);
+        actual.push('\n');
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn inferred_types_of_bindings() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation Main() : Unit {
+                    let x = 1;
+                    let (a, b) = (1.0, "s");
+                    let c : Int = 2;
+                    for i in 0..2 {}
+                }
+            }
+        "#},
+        &expect![[r#"
+            namespace Test {
+                operation Main() : Unit {
+                    let x⟨: Int⟩ = 1;
+                    let (a⟨: Double⟩, b⟨: String⟩) = (1.0, "s");
+                    let c : Int = 2;
+                    for i⟨: Int⟩ in 0..2 {}
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn known_array_lengths() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation Main(n : Int) : Unit {
+                    mutable arr = [1, 2, 3];
+                    let rep = [0, size = 4];
+                    let unknown = [0, size = n];
+                    use qs = Qubit[2];
+                    use q = Qubit();
+                }
+            }
+        "#},
+        &expect![[r#"
+            namespace Test {
+                operation Main(n : Int) : Unit {
+                    mutable arr⟨: Int[]⟩⟨length 3⟩ = [1, 2, 3];
+                    let rep⟨: Int[]⟩⟨length 4⟩ = [0, size = 4];
+                    let unknown⟨: Int[]⟩ = [0, size = n];
+                    use qs⟨length 2⟩ = Qubit[2];
+                    use q = Qubit();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn generated_specializations() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation A(q : Qubit) : Unit is Adj + Ctl {}
+                operation B(q : Qubit) : Unit is Adj {
+                    body ... {}
+                    adjoint self;
+                }
+                operation C(q : Qubit) : Unit is Ctl + Adj {
+                    body ... {}
+                    adjoint ... {}
+                }
+            }
+        "#},
+        &expect![[r#"
+            namespace Test {
+                operation A(q : Qubit) : Unit is Adj + Ctl⟨generates adjoint, controlled, controlled adjoint⟩ {}
+                operation B(q : Qubit) : Unit is Adj {
+                    body ... {}
+                    adjoint self;
+                }
+                operation C(q : Qubit) : Unit is Ctl + Adj⟨generates controlled, controlled adjoint⟩ {
+                    body ... {}
+                    adjoint ... {}
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn conjugation_applies_adjoint_of_within() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation Main() : Unit {
+                    within {} apply {}
+                }
+            }
+        "#},
+        &expect![[r#"
+            namespace Test {
+                operation Main() : Unit {
+                    within {} apply {}⟨Adjoint within⟩
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn hints_outside_range_are_excluded() {
+    let source = indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {
+                let x = 1;
+                let y = 2;
+            }
+        }
+    "#};
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let range = Range {
+        start: Position { line: 3, column: 0 },
+        end: Position { line: 4, column: 0 },
+    };
+    let hints = get_inlay_hints(&compilation, "<source>", range, Encoding::Utf8);
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].label, ": Int");
+    assert_eq!(
+        hints[0].position,
+        Position {
+            line: 3,
+            column: 13
+        }
+    );
+}
@@ -6,27 +6,33 @@ pub mod code_lens;
 mod compilation;
 pub mod completion;
 pub mod definition;
+pub mod document_symbols;
+pub mod folding_ranges;
 pub mod format;
 pub mod hover;
+pub mod inlay_hints;
 mod name_locator;
 pub mod protocol;
 mod qsc_utils;
 pub mod references;
 pub mod rename;
+pub mod selection_ranges;
 pub mod signature_help;
 mod state;
 #[cfg(test)]
 mod test_utils;
 #[cfg(test)]
 mod tests;
+pub mod workspace_symbols;
 
 use compilation::Compilation;
 use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
 use futures_util::StreamExt;
 use log::{trace, warn};
 use protocol::{
-    CodeAction, CodeLens, CompletionList, DiagnosticUpdate, Hover, NotebookMetadata, SignatureHelp,
-    TextEdit, WorkspaceConfigurationUpdate,
+    CodeAction, CodeLens, CompletionList, DiagnosticUpdate, DocumentSymbol, FoldingRange, Hover,
+    InlayHint, NotebookMetadata, SignatureHelp, TextEdit, WorkspaceConfigurationUpdate,
+    WorkspaceSymbol,
 };
 use qsc::{
     line_column::{Encoding, Position, Range},
@@ -224,6 +230,32 @@ impl LanguageService {
         )
     }
 
+    /// LSP: textDocument/documentSymbol
+    #[must_use]
+    pub fn get_document_symbols(&self, uri: &str) -> Vec<DocumentSymbol> {
+        self.document_op(
+            |compilation, uri, (), position_encoding| {
+                document_symbols::get_document_symbols(compilation, uri, position_encoding)
+            },
+            "get_document_symbols",
+            uri,
+            (),
+        )
+    }
+
+    /// LSP: textDocument/foldingRange
+    #[must_use]
+    pub fn get_folding_ranges(&self, uri: &str) -> Vec<FoldingRange> {
+        self.document_op(
+            |compilation, uri, (), position_encoding| {
+                folding_ranges::get_folding_ranges(compilation, uri, position_encoding)
+            },
+            "get_folding_ranges",
+            uri,
+            (),
+        )
+    }
+
     /// LSP: textDocument/format
     #[must_use]
     pub fn get_format_changes(&self, uri: &str) -> Vec<TextEdit> {
@@ -243,6 +275,23 @@ impl LanguageService {
         self.document_op(hover::get_hover, "get_hover", uri, position)
     }
 
+    /// LSP: textDocument/inlayHint
+    #[must_use]
+    pub fn get_inlay_hints(&self, uri: &str, range: Range) -> Vec<InlayHint> {
+        self.document_op(inlay_hints::get_inlay_hints, "get_inlay_hints", uri, range)
+    }
+
+    /// LSP: textDocument/selectionRange
+    #[must_use]
+    pub fn get_selection_range(&self, uri: &str, position: Position) -> Vec<Range> {
+        self.document_op(
+            selection_ranges::get_selection_range,
+            "get_selection_range",
+            uri,
+            position,
+        )
+    }
+
     /// LSP textDocument/signatureHelp
     #[must_use]
     pub fn get_signature_help(&self, uri: &str, position: Position) -> Option<SignatureHelp> {
@@ -279,6 +328,23 @@ impl LanguageService {
         )
     }
 
+    /// Searches the items of every open compilation, including the libraries they depend on,
+    /// for symbols whose names fuzzy match the query.
+    ///
+    /// LSP: workspace/symbol
+    #[must_use]
+    pub fn get_workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        trace!("get_workspace_symbols: query: {query}");
+        let compilation_state = self.state.borrow();
+        let res = workspace_symbols::get_workspace_symbols(
+            compilation_state.compilations(),
+            query,
+            self.position_encoding,
+        );
+        trace!("get_workspace_symbols result: {res:?}");
+        res
+    }
+
     /// Executes an operation that takes a document uri, using the current compilation for that document.
     /// All "read" operations should go through this method. This method will borrow the current
     /// compilation state to perform the request.
@@ -5,7 +5,7 @@ use std::mem::replace;
 use std::rc::Rc;
 
 use crate::compilation::Compilation;
-use crate::qsc_utils::find_ident;
+use crate::qsc_utils::{find_ident, find_top_level_ident};
 use qsc::ast::visit::{walk_expr, walk_namespace, walk_pat, walk_ty, walk_ty_def, Visitor};
 use qsc::display::Lookup;
 use qsc::{ast, hir, resolve};
@@ -133,6 +133,15 @@ impl<'inner, 'package, T: Handler<'package>> Locator<'inner, 'package, T> {
         }
     }
 
+    /// Finds the binding of a local. Locals outside of a callable are bound by top-level
+    /// statements, which may be in an earlier notebook cell.
+    fn find_local(&self, node_id: ast::NodeId) -> Option<&'package ast::Ident> {
+        match self.context.current_callable {
+            Some(curr) => find_ident(node_id, curr),
+            None => find_top_level_ident(node_id, &self.compilation.user_unit().ast.package),
+        }
+    }
+
     fn get_field_def<'other>(
         &mut self,
         udt_res: &'package hir::Res,
@@ -389,11 +398,9 @@ impl<'inner, 'package, T: Handler<'package>> Visitor<'package> for Locator<'inne
                         .split_first()
                         .expect("paths should have at least one part");
                     if first.span.touches(self.offset) {
-                        if let Some(curr) = self.context.current_callable {
-                            if let Some(definition) = find_ident(node_id, curr) {
-                                self.inner
-                                    .at_local_ref(&self.context, first, node_id, definition);
-                            }
+                        if let Some(definition) = self.find_local(node_id) {
+                            self.inner
+                                .at_local_ref(&self.context, first, node_id, definition);
                         }
                     } else {
                         // Loop through the parts of the path to find the first part that touches the offset
@@ -438,15 +445,13 @@ impl<'inner, 'package, T: Handler<'package>> Visitor<'package> for Locator<'inne
                         }
                     }
                     Some(resolve::Res::Local(node_id)) => {
-                        if let Some(curr) = self.context.current_callable {
-                            if let Some(definition) = find_ident(*node_id, curr) {
-                                self.inner.at_local_ref(
-                                    &self.context,
-                                    &path.name,
-                                    *node_id,
-                                    definition,
-                                );
-                            }
+                        if let Some(definition) = self.find_local(*node_id) {
+                            self.inner.at_local_ref(
+                                &self.context,
+                                &path.name,
+                                *node_id,
+                                definition,
+                            );
                         }
                     }
                     _ => {}
@@ -2,7 +2,9 @@
 // Licensed under the MIT License.
 
 use miette::Diagnostic;
-use qsc::line_column::Range;
+use qsc::line_column::{Position, Range};
+use qsc::location::Location;
+pub use qsc::symbols::SymbolKind;
 use qsc::{compile, project};
 use qsc::{linter::LintConfig, project::Manifest, target::Profile, LanguageFeatures, PackageType};
 use thiserror::Error;
@@ -121,6 +123,75 @@ impl Hash for CompletionItem {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The namespace the symbol is declared in.
+    pub container_name: String,
+    /// The declaration of the symbol.
+    pub detail: String,
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: DocumentSymbolKind,
+    /// The declaration of the symbol, such as the signature of a callable.
+    pub detail: Option<String>,
+    /// The range of the whole declaration.
+    pub range: Range,
+    /// The range of the name of the symbol.
+    pub selection_range: Range,
+    pub children: Vec<DocumentSymbol>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentSymbolKind {
+    Namespace,
+    Function,
+    Operation,
+    Newtype,
+    Struct,
+    Field,
+    Specialization,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InlayHintKind {
+    /// The inferred type of a variable.
+    Type,
+    /// A functor application or specialization that the compiler generates implicitly.
+    Functor,
+    /// The length of an array, when it is known from the source.
+    Length,
+}
+
+/// A range of lines that an editor can collapse. The end line is the last line that is hidden,
+/// so the closing brace of a block stays visible.
+#[derive(Debug, PartialEq)]
+pub struct FoldingRange {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub kind: FoldingRangeKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldingRangeKind {
+    /// A namespace, declaration or block.
+    Block,
+    /// A run of `open`, `import` or `export` statements.
+    Imports,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Hover {
     pub contents: String,
@@ -53,6 +53,25 @@ pub(crate) fn find_ident(
     finder.ident
 }
 
+/// Finds the binding of a local declared by a top-level statement, such as a `let` in a notebook
+/// cell. Later cells can refer to locals bound by earlier ones, so the whole package is searched.
+pub(crate) fn find_top_level_ident(
+    node_id: ast::NodeId,
+    package: &ast::Package,
+) -> Option<&ast::Ident> {
+    let mut finder = AstIdentFinder {
+        node_id,
+        ident: None,
+    };
+    for node in package.nodes.iter() {
+        if let ast::TopLevelNode::Stmt(stmt) = node {
+            use ast::visit::Visitor;
+            finder.visit_stmt(stmt);
+        }
+    }
+    finder.ident
+}
+
 struct AstIdentFinder<'a> {
     pub node_id: ast::NodeId,
     pub ident: Option<&'a ast::Ident>,
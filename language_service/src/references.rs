@@ -162,9 +162,9 @@ impl<'a> Handler<'a> for NameHandler<'a> {
         ident: &'a ast::Ident,
         _: &'a ast::Pat,
     ) {
-        if let Some(curr) = context.current_callable {
-            self.references = self.reference_finder.for_local(ident.id, curr);
-        }
+        self.references = self
+            .reference_finder
+            .for_local(ident.id, context.current_callable);
     }
 
     fn at_local_ref(
@@ -174,9 +174,9 @@ impl<'a> Handler<'a> for NameHandler<'a> {
         _: ast::NodeId,
         definition: &'a ast::Ident,
     ) {
-        if let Some(curr) = context.current_callable {
-            self.references = self.reference_finder.for_local(definition.id, curr);
-        }
+        self.references = self
+            .reference_finder
+            .for_local(definition.id, context.current_callable);
     }
 }
 
@@ -277,14 +277,30 @@ impl<'a> ReferenceFinder<'a> {
         locations
     }
 
-    pub fn for_local(&self, node_id: ast::NodeId, callable: &ast::CallableDecl) -> Vec<Location> {
+    /// Finds the references to a local declared in the given callable, or by a top-level
+    /// statement if there is no callable. Top-level locals can be referred to from any later
+    /// top-level statement, such as those in later notebook cells.
+    pub fn for_local(
+        &self,
+        node_id: ast::NodeId,
+        callable: Option<&ast::CallableDecl>,
+    ) -> Vec<Location> {
         let mut find_refs = FindLocalLocations {
             node_id,
             compilation: self.compilation,
             include_declaration: self.include_declaration,
             locations: vec![],
         };
-        find_refs.visit_callable_decl(callable);
+        match callable {
+            Some(callable) => find_refs.visit_callable_decl(callable),
+            None => {
+                for node in self.compilation.user_unit().ast.package.nodes.iter() {
+                    if let ast::TopLevelNode::Stmt(stmt) = node {
+                        find_refs.visit_stmt(stmt);
+                    }
+                }
+            }
+        }
         find_refs
             .locations
             .into_iter()
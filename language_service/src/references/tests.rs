@@ -917,3 +917,12 @@ fn notebook_defined_in_later_cell() {
         ("cell2", "operation Callee() : Unit {}"),
     ]);
 }
+
+#[test]
+fn notebook_local_across_cells() {
+    check_notebook_exclude_decl(&[
+        ("cell1", "mutable x = 1;"),
+        ("cell2", "set ◉↘x◉ += 1;"),
+        ("cell3", "let y = ◉x◉;"),
+    ]);
+}
@@ -671,3 +671,40 @@ fn notebook_rename_across_cells() {
         "#]],
     );
 }
+
+#[test]
+fn notebook_rename_local_across_cells() {
+    check_notebook(
+        &[("cell1", "let x = 1;"), ("cell2", "let y = ↘x + 1;")],
+        &expect![[r#"
+            [
+                Location {
+                    source: "cell1",
+                    range: Range {
+                        start: Position {
+                            line: 0,
+                            column: 4,
+                        },
+                        end: Position {
+                            line: 0,
+                            column: 5,
+                        },
+                    },
+                },
+                Location {
+                    source: "cell2",
+                    range: Range {
+                        start: Position {
+                            line: 0,
+                            column: 8,
+                        },
+                        end: Position {
+                            line: 0,
+                            column: 9,
+                        },
+                    },
+                },
+            ]
+        "#]],
+    );
+}
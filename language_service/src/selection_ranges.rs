@@ -0,0 +1,196 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::qsc_utils::into_range;
+use qsc::ast;
+use qsc::ast::visit::{
+    walk_attr, walk_block, walk_callable_decl, walk_expr, walk_field_assign, walk_field_def,
+    walk_functor_expr, walk_idents, walk_item, walk_namespace, walk_pat, walk_path,
+    walk_qubit_init, walk_spec_decl, walk_stmt, walk_struct_decl, walk_ty, walk_ty_def, Visitor,
+};
+use qsc::line_column::{Encoding, Position, Range};
+use qsc::Span;
+
+/// Returns the ranges that selection expands through from the position, innermost first. Each
+/// range contains the previous one, starting from the smallest syntax node at the position, like
+/// an identifier, and ending at the outermost one, like its namespace.
+pub(crate) fn get_selection_range(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Vec<Range> {
+    let offset =
+        compilation.source_position_to_package_offset(source_name, position, position_encoding);
+    let mut collector = SpanCollector {
+        offset,
+        spans: vec![],
+    };
+    collector.visit_package(&compilation.user_unit().ast.package);
+
+    // Nodes that end where the next one starts both touch the position, so pick the smallest
+    // node and keep only the nodes that contain it.
+    let mut spans = collector.spans;
+    spans.sort_by_key(|span| span.hi - span.lo);
+    let mut chain: Vec<Span> = vec![];
+    for span in spans {
+        let extends = match chain.last() {
+            Some(last) => *last != span && contains(span, *last),
+            None => true,
+        };
+        if extends {
+            chain.push(span);
+        }
+    }
+
+    chain
+        .into_iter()
+        .map(|span| into_range(position_encoding, span, &compilation.user_unit().sources))
+        .collect()
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.lo <= inner.lo && inner.hi <= outer.hi
+}
+
+struct SpanCollector {
+    offset: u32,
+    spans: Vec<Span>,
+}
+
+impl SpanCollector {
+    /// Records the span if it touches the offset, and returns whether it does, so that nodes
+    /// elsewhere in the package are not walked.
+    fn push(&mut self, span: Span) -> bool {
+        let touches = span.lo <= self.offset && self.offset <= span.hi;
+        if touches {
+            self.spans.push(span);
+        }
+        touches
+    }
+}
+
+impl<'a> Visitor<'a> for SpanCollector {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        if self.push(namespace.span) {
+            walk_namespace(self, namespace);
+        }
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if self.push(item.span) {
+            walk_item(self, item);
+        }
+    }
+
+    fn visit_attr(&mut self, attr: &'a ast::Attr) {
+        if self.push(attr.span) {
+            walk_attr(self, attr);
+        }
+    }
+
+    fn visit_ty_def(&mut self, def: &'a ast::TyDef) {
+        if self.push(def.span) {
+            walk_ty_def(self, def);
+        }
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+        if self.push(decl.span) {
+            walk_callable_decl(self, decl);
+        }
+    }
+
+    fn visit_struct_decl(&mut self, decl: &'a ast::StructDecl) {
+        if self.push(decl.span) {
+            walk_struct_decl(self, decl);
+        }
+    }
+
+    fn visit_field_def(&mut self, def: &'a ast::FieldDef) {
+        if self.push(def.span) {
+            walk_field_def(self, def);
+        }
+    }
+
+    fn visit_spec_decl(&mut self, decl: &'a ast::SpecDecl) {
+        if self.push(decl.span) {
+            walk_spec_decl(self, decl);
+        }
+    }
+
+    fn visit_functor_expr(&mut self, expr: &'a ast::FunctorExpr) {
+        if self.push(expr.span) {
+            walk_functor_expr(self, expr);
+        }
+    }
+
+    fn visit_ty(&mut self, ty: &'a ast::Ty) {
+        if self.push(ty.span) {
+            walk_ty(self, ty);
+        }
+    }
+
+    fn visit_block(&mut self, block: &'a ast::Block) {
+        if self.push(block.span) {
+            // The statements inside the braces are a step between a statement and the block.
+            if let (Some(first), Some(last)) = (block.stmts.first(), block.stmts.last()) {
+                self.push(Span {
+                    lo: first.span.lo,
+                    hi: last.span.hi,
+                });
+            }
+            walk_block(self, block);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        if self.push(stmt.span) {
+            walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if self.push(expr.span) {
+            walk_expr(self, expr);
+        }
+    }
+
+    fn visit_field_assign(&mut self, assign: &'a ast::FieldAssign) {
+        if self.push(assign.span) {
+            walk_field_assign(self, assign);
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if self.push(pat.span) {
+            walk_pat(self, pat);
+        }
+    }
+
+    fn visit_qubit_init(&mut self, init: &'a ast::QubitInit) {
+        if self.push(init.span) {
+            walk_qubit_init(self, init);
+        }
+    }
+
+    fn visit_path(&mut self, path: &'a ast::Path) {
+        if self.push(path.span) {
+            walk_path(self, path);
+        }
+    }
+
+    fn visit_ident(&mut self, ident: &'a ast::Ident) {
+        self.push(ident.span);
+    }
+
+    fn visit_idents(&mut self, idents: &'a ast::Idents) {
+        if self.push(idents.span()) {
+            walk_idents(self, idents);
+        }
+    }
+}
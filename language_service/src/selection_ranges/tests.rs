@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_selection_range;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers, Encoding};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+fn check(source_with_cursor: &str, expect: &Expect) {
+    let (compilation, position, _) = compile_with_fake_stdlib_and_markers(source_with_cursor);
+    let ranges = get_selection_range(&compilation, "<source>", position, Encoding::Utf8);
+    let source = source_with_cursor.replace('↘', "");
+    let lines = source.lines().collect::<Vec<_>>();
+    let actual = ranges
+        .iter()
+        .map(|range| {
+            let text = if range.start.line == range.end.line {
+                lines[range.start.line as usize]
+                    [range.start.column as usize..range.end.column as usize]
+                    .to_string()
+            } else {
+                format!(
+                    "{} ... {}",
+                    &lines[range.start.line as usize][range.start.column as usize..],
+                    &lines[range.end.line as usize][..range.end.column as usize].trim_start()
+                )
+            };
+            format!("{text}\n")
+        })
+        .collect::<String>();
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn expands_from_identifier_to_namespace() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                function F(x : Int) : Int {
+                    let y = (x + ↘x) * 2;
+                    y
+                }
+            }
+        "#},
+        &expect![[r#"
+            x
+            x + x
+            (x + x)
+            (x + x) * 2
+            let y = (x + x) * 2;
+            let y = (x + x) * 2; ... y
+            { ... }
+            function F(x : Int) : Int { ... }
+            namespace Test { ... }
+        "#]],
+    );
+}
+
+#[test]
+fn parameter_type() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                operation Op(q : Qu↘bit) : Unit {}
+            }
+        "#},
+        &expect![[r#"
+            Qubit
+            q : Qubit
+            (q : Qubit)
+            operation Op(q : Qubit) : Unit {}
+            namespace Test { ... }
+        "#]],
+    );
+}
+
+#[test]
+fn touching_nodes_pick_the_smallest() {
+    check(
+        indoc! {r#"
+            namespace Test {
+                function F(x : Int) : Int { F↘(x) }
+            }
+        "#},
+        &expect![[r#"
+            F
+            F(x)
+            { F(x) }
+            function F(x : Int) : Int { F(x) }
+            namespace Test { ... }
+        "#]],
+    );
+}
@@ -512,6 +512,12 @@ impl<'a> CompilationStateUpdater<'a> {
 }
 
 impl CompilationState {
+    pub(crate) fn compilations(&self) -> impl Iterator<Item = &Compilation> {
+        self.compilations
+            .values()
+            .map(|(compilation, _)| compilation)
+    }
+
     pub(crate) fn get_compilation(&self, uri: &str) -> Option<&Compilation> {
         let compilation_uri = &self
             .open_documents
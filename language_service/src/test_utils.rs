@@ -69,7 +69,7 @@ fn compile_project_with_fake_stdlib_and_markers_cursor_optional(
 
     let source_map = SourceMap::new(sources, None);
     let (mut package_store, std_package_id) = compile_fake_stdlib();
-    let (unit, errors) = compile::compile(
+    let (unit, errors) = compile::compile_partial(
         &package_store,
         &[std_package_id],
         source_map,
@@ -0,0 +1,49 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::protocol::WorkspaceSymbol;
+use crate::qsc_utils::into_location;
+use qsc::line_column::Encoding;
+use qsc::symbols::SymbolIndex;
+
+/// The most symbols returned for a single query. Editors filter the results further as the
+/// user types, so there is no need to return every match for short queries.
+const MAX_RESULTS: usize = 100;
+
+pub(crate) fn get_workspace_symbols<'a>(
+    compilations: impl Iterator<Item = &'a Compilation>,
+    query: &str,
+    position_encoding: Encoding,
+) -> Vec<WorkspaceSymbol> {
+    let mut results: Vec<WorkspaceSymbol> = Vec::new();
+    for compilation in compilations {
+        let index = SymbolIndex::new(&compilation.package_store, &[compilation.user_package_id]);
+        for symbol in index.search(query, MAX_RESULTS) {
+            let result = WorkspaceSymbol {
+                name: symbol.name.to_string(),
+                kind: symbol.kind,
+                container_name: symbol.namespace.to_string(),
+                detail: symbol.signature.clone(),
+                location: into_location(
+                    position_encoding,
+                    compilation,
+                    symbol.span,
+                    symbol.package,
+                ),
+            };
+            // Compilations that share a library, like the standard library, would otherwise
+            // list its symbols once per compilation.
+            if !results.contains(&result) {
+                results.push(result);
+            }
+            if results.len() == MAX_RESULTS {
+                return results;
+            }
+        }
+    }
+    results
+}
@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::get_workspace_symbols;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+use indoc::indoc;
+use qsc::symbols::SymbolKind;
+
+#[test]
+fn user_callable() {
+    let (compilation, target_spans) = compile_with_fake_stdlib_and_markers_no_cursor(indoc! {r#"
+        namespace Test {
+            operation ◉PrepareBell◉() : Unit {}
+        }
+    "#});
+    let symbols = get_workspace_symbols([&compilation].into_iter(), "PrepBell", Encoding::Utf8);
+    let symbol = &symbols[0];
+    assert_eq!(symbol.name, "PrepareBell");
+    assert_eq!(symbol.kind, SymbolKind::Operation);
+    assert_eq!(symbol.container_name, "Test");
+    assert_eq!(symbol.detail, "operation PrepareBell() : Unit");
+    assert_eq!(symbol.location.source.as_ref(), "<source>");
+    assert_eq!(symbol.location.range, target_spans[0]);
+}
+
+#[test]
+fn library_callable() {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(indoc! {r#"
+        namespace Test {
+            operation Main() : Unit {}
+        }
+    "#});
+    let symbols =
+        get_workspace_symbols([&compilation].into_iter(), "FakeWithParam", Encoding::Utf8);
+    let symbol = &symbols[0];
+    assert_eq!(symbol.name, "FakeWithParam");
+    assert_eq!(symbol.container_name, "FakeStdLib");
+    assert_eq!(
+        symbol.location.source.as_ref(),
+        "qsharp-library-source:<std>"
+    );
+}
+
+#[test]
+fn library_internal_items_are_excluded() {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(indoc! {r#"
+        namespace Test {
+            internal operation HiddenHelper() : Unit {}
+        }
+    "#});
+    let names = get_workspace_symbols([&compilation].into_iter(), "Hidden", Encoding::Utf8)
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["HiddenHelper"]);
+}
+
+#[test]
+fn shared_library_symbols_are_listed_once() {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor("");
+    let symbols = get_workspace_symbols(
+        [&compilation, &compilation].into_iter(),
+        "FakeCtlAdj",
+        Encoding::Utf8,
+    );
+    assert_eq!(symbols.len(), 1);
+}
+
+#[test]
+fn no_match() {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor("");
+    let symbols = get_workspace_symbols([&compilation].into_iter(), "Zzyzx", Encoding::Utf8);
+    assert!(symbols.is_empty());
+}
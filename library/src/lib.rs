@@ -4,6 +4,8 @@
 #[cfg(test)]
 mod tests;
 
+use bitflags::bitflags;
+
 // The core prefix on the name is needed to disambiguate from the std
 // files of the same name. This comes in during debugging when we need
 // to load a core/std file from the library.
@@ -76,6 +78,10 @@ pub const STD_LIB: &[(&str, &str)] = &[
         "qsharp-library-source:unstable_arithmetic_internal.qs",
         include_str!("../std/unstable_arithmetic_internal.qs"),
     ),
+    (
+        "qsharp-library-source:unstable_oracles.qs",
+        include_str!("../std/unstable_oracles.qs"),
+    ),
     (
         "qsharp-library-source:unstable_state_preparation.qs",
         include_str!("../std/unstable_state_preparation.qs"),
@@ -85,3 +91,72 @@ pub const STD_LIB: &[(&str, &str)] = &[
         include_str!("../std/unstable_table_lookup.qs"),
     ),
 ];
+
+bitflags! {
+    /// Optional groups of standard library namespaces. Sources that are not part of any group
+    /// are always included, since the rest of the standard library depends on them.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct StdFeatures: u32 {
+        /// `Microsoft.Quantum.Random`.
+        const Random = 0b0000_0001;
+        /// `Microsoft.Quantum.Logical`.
+        const Logical = 0b0000_0010;
+        /// `Microsoft.Quantum.ResourceEstimation`.
+        const ResourceEstimation = 0b0000_0100;
+        /// `Microsoft.Quantum.Unstable.Arithmetic`.
+        const Arithmetic = 0b0000_1000;
+        /// `Microsoft.Quantum.Unstable.StatePreparation`.
+        const StatePreparation = 0b0001_0000;
+        /// `Microsoft.Quantum.Unstable.TableLookup`. Requires `Arithmetic` and
+        /// `ResourceEstimation`.
+        const TableLookup = 0b0010_0000;
+        /// `Microsoft.Quantum.Unstable.Oracles`.
+        const Oracles = 0b0100_0000;
+    }
+}
+
+impl Default for StdFeatures {
+    fn default() -> Self {
+        StdFeatures::all()
+    }
+}
+
+impl StdFeatures {
+    /// Adds the groups that the selected groups depend on.
+    #[must_use]
+    pub fn with_dependencies(self) -> Self {
+        if self.contains(StdFeatures::TableLookup) {
+            self | StdFeatures::Arithmetic | StdFeatures::ResourceEstimation
+        } else {
+            self
+        }
+    }
+
+    /// The group a standard library source belongs to, or `None` if it is always included.
+    #[must_use]
+    pub fn of_source(name: &str) -> Option<Self> {
+        match name.strip_prefix("qsharp-library-source:")? {
+            "random.qs" => Some(StdFeatures::Random),
+            "logical.qs" => Some(StdFeatures::Logical),
+            "re.qs" => Some(StdFeatures::ResourceEstimation),
+            "unstable_arithmetic.qs" | "unstable_arithmetic_internal.qs" => {
+                Some(StdFeatures::Arithmetic)
+            }
+            "unstable_oracles.qs" => Some(StdFeatures::Oracles),
+            "unstable_state_preparation.qs" => Some(StdFeatures::StatePreparation),
+            "unstable_table_lookup.qs" => Some(StdFeatures::TableLookup),
+            _ => None,
+        }
+    }
+}
+
+/// The standard library sources for the selected feature groups, including the groups
+/// they depend on.
+pub fn std_lib_sources(
+    features: StdFeatures,
+) -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    let features = features.with_dependencies();
+    STD_LIB.iter().filter(move |(name, _)| {
+        StdFeatures::of_source(name).map_or(true, |feature| features.contains(feature))
+    })
+}
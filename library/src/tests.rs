@@ -13,6 +13,7 @@ mod intrinsic;
 mod logical;
 mod math;
 mod measurement;
+mod oracles;
 mod state_preparation;
 mod table_lookup;
 
@@ -107,6 +108,67 @@ fn assert_doubles_almost_equal(val1: f64, val2: f64) {
     );
 }
 
+#[test]
+fn std_features_exclude_optional_sources() {
+    let names = crate::std_lib_sources(crate::StdFeatures::empty())
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"qsharp-library-source:arrays.qs"));
+    assert!(!names.contains(&"qsharp-library-source:re.qs"));
+    assert!(!names.contains(&"qsharp-library-source:unstable_arithmetic.qs"));
+}
+
+#[test]
+fn std_features_include_dependencies() {
+    let names = crate::std_lib_sources(crate::StdFeatures::TableLookup)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"qsharp-library-source:unstable_table_lookup.qs"));
+    assert!(names.contains(&"qsharp-library-source:unstable_arithmetic.qs"));
+    assert!(names.contains(&"qsharp-library-source:re.qs"));
+    assert!(!names.contains(&"qsharp-library-source:random.qs"));
+}
+
+#[test]
+fn std_without_optional_features_compiles() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Test { open Microsoft.Quantum.Arrays; function F() : Int[] { Reversed([1, 2]) } }"
+                .into(),
+        )],
+        None,
+    );
+    Interpreter::new_with_std_features(
+        crate::StdFeatures::empty(),
+        sources,
+        PackageType::Lib,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    )
+    .expect("sources should compile against the minimal standard library");
+}
+
+#[test]
+fn std_without_random_feature_does_not_have_random_namespace() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Test { open Microsoft.Quantum.Random; operation F() : Int { DrawRandomInt(0, 1) } }"
+                .into(),
+        )],
+        None,
+    );
+    let result = Interpreter::new_with_std_features(
+        crate::StdFeatures::all() - crate::StdFeatures::Random,
+        sources,
+        PackageType::Lib,
+        Profile::Unrestricted.into(),
+        LanguageFeatures::default(),
+    );
+    assert!(result.is_err());
+}
+
 //
 // Core namespace
 //
@@ -293,6 +293,102 @@ fn check_ripple_carry_cg_inc_by_le_general() {
     );
 }
 
+//
+// RippleCarryCDKMIncByLE
+//
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_exhaustive_bitwidth_1() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check RippleCarryCDKMIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 1)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_exhaustive_bitwidth_2() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check RippleCarryCDKMIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 2)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_exhaustive_bitwidth_3() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check RippleCarryCDKMIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 3)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_exhaustive_bitwidth_4() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check RippleCarryCDKMIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 4)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_ctl_exhaustive_bitwidth_1() {
+    test_expression_with_lib(
+        "Test.TestIncByLECtl(\"Check RippleCarryCDKMIncByLE(Ctl)\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 1)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_ctl_exhaustive_bitwidth_2() {
+    test_expression_with_lib(
+        "Test.TestIncByLECtl(\"Check RippleCarryCDKMIncByLE(Ctl)\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 2)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_ctl_exhaustive_bitwidth_3() {
+    test_expression_with_lib(
+        "Test.TestIncByLECtl(\"Check RippleCarryCDKMIncByLE(Ctl)\",
+            Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCDKMIncByLE, 3)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_ripple_carry_cdkm_inc_by_le_general() {
+    test_expression(
+        {
+            "{  // General cases for RippleCarryCDKMIncByLE
+                open Microsoft.Quantum.Unstable.Arithmetic;
+
+                use x1 = Qubit[10];
+                use y1 = Qubit[10];
+                ApplyXorInPlace(743, x1);
+                ApplyXorInPlace(112, y1);
+                RippleCarryCDKMIncByLE(x1,y1); // 112 += 743
+                let i = MeasureInteger(y1);
+                ResetAll(x1+y1);
+
+                return i;
+            }"
+        },
+        &Value::Int(112 + 743),
+    );
+}
+
 //
 // FourierTDIncByLE
 //
@@ -971,3 +1067,87 @@ fn check_apply_if_less_l_non_x_action() {
         &Value::Tuple(vec![Value::Int(758), Value::Int(753)].into()),
     );
 }
+
+//
+// Modular arithmetic
+//
+
+#[test]
+fn check_modular_inc_by_i_exhaustive() {
+    test_expression(
+        {
+            "{
+                open Microsoft.Quantum.Unstable.Arithmetic;
+                open Microsoft.Quantum.Diagnostics;
+
+                use ys = Qubit[3];
+                for modulus in 1..8 {
+                    for c in 0..modulus - 1 {
+                        for y in 0..modulus - 1 {
+                            ApplyXorInPlace(y, ys);
+                            ModularIncByI(c, modulus, ys);
+                            let actual = MeasureInteger(ys);
+                            Fact(actual == (y + c) % modulus, $\"{y} + {c} mod {modulus} = {actual}\");
+                        }
+                    }
+                }
+            }"
+        },
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_modular_inc_by_le_exhaustive() {
+    test_expression(
+        {
+            "{
+                open Microsoft.Quantum.Unstable.Arithmetic;
+                open Microsoft.Quantum.Diagnostics;
+
+                use (xs, ys) = (Qubit[3], Qubit[3]);
+                for modulus in 1..8 {
+                    for x in 0..modulus - 1 {
+                        for y in 0..modulus - 1 {
+                            ApplyXorInPlace(x, xs);
+                            ApplyXorInPlace(y, ys);
+                            ModularIncByLE(modulus, xs, ys);
+                            let actual = MeasureInteger(ys);
+                            Fact(actual == (x + y) % modulus, $\"{y} + {x} mod {modulus} = {actual}\");
+                            Fact(MeasureInteger(xs) == x, \"xs should be unchanged\");
+                        }
+                    }
+                }
+            }"
+        },
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_modular_inc_by_le_using_cdkm_is_controlled() {
+    test_expression(
+        {
+            "{
+                open Microsoft.Quantum.Unstable.Arithmetic;
+
+                use ctl = Qubit();
+                use (xs, ys) = (Qubit[3], Qubit[3]);
+                ApplyXorInPlace(4, xs);
+                ApplyXorInPlace(3, ys);
+                Controlled ModularIncByLEUsingIncByLE([ctl], (RippleCarryCDKMIncByLE, 5, xs, ys));
+                let unchanged = MeasureInteger(ys);
+                ApplyXorInPlace(3, ys);
+                within {
+                    X(ctl);
+                } apply {
+                    Controlled ModularIncByLEUsingIncByLE([ctl], (RippleCarryCDKMIncByLE, 5, xs, ys));
+                }
+                let sum = MeasureInteger(ys);
+                ResetAll(xs);
+                (unchanged, sum)
+            }"
+        },
+        &Value::Tuple(vec![Value::Int(3), Value::Int(2)].into()),
+    );
+}
@@ -40,3 +40,59 @@ fn check_operations_are_equal() {
         ),
     );
 }
+
+#[test]
+fn check_operation_counts_are_per_specialization() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Diagnostics;
+            use qs = Qubit[3];
+            StartCountingOperation(H);
+            StartCountingOperation(Adjoint H);
+            StartCountingOperation(Controlled X);
+            ApplyToEach(H, qs);
+            Adjoint H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            Controlled X([qs[1]], qs[2]);
+            ResetAll(qs);
+            [StopCountingOperation(H), StopCountingOperation(Adjoint H), StopCountingOperation(Controlled X)]
+        }",
+        &Value::Array(vec![Value::Int(3), Value::Int(1), Value::Int(1)].into()),
+    );
+}
+
+#[test]
+fn check_operation_counts_through_partial_application() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Diagnostics;
+            use q = Qubit();
+            let rotate = Rx(_, q);
+            StartCountingOperation(rotate);
+            StartCountingOperation(Rx);
+            rotate(0.5);
+            rotate(-0.5);
+            Rx(1.0, q);
+            Reset(q);
+            (StopCountingOperation(rotate), StopCountingOperation(Rx))
+        }",
+        &Value::Tuple(vec![Value::Int(2), Value::Int(3)].into()),
+    );
+}
+
+#[test]
+fn check_function_counts() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Diagnostics;
+            function Square(x : Int) : Int { x * x }
+            StartCountingFunction(Square);
+            mutable total = 0;
+            for i in 1..4 {
+                set total += Square(i);
+            }
+            (total, StopCountingFunction(Square))
+        }",
+        &Value::Tuple(vec![Value::Int(30), Value::Int(4)].into()),
+    );
+}
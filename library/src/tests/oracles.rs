@@ -0,0 +1,177 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::test_expression;
+use qsc::interpret::Value;
+
+// Tests for Microsoft.Quantum.Unstable.Oracles namespace
+
+fn bools(values: &[bool]) -> Value {
+    Value::Array(
+        values
+            .iter()
+            .copied()
+            .map(Value::Bool)
+            .collect::<Vec<_>>()
+            .into(),
+    )
+}
+
+#[test]
+fn check_oracle_from_function_marks_every_input() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Arrays;
+            open Microsoft.Quantum.Unstable.Arithmetic;
+            open Microsoft.Quantum.Unstable.Oracles;
+            function HasTwoSet(bits : Bool[]) : Bool {
+                Length(Filtered(bit -> bit, bits)) == 2
+            }
+            use (inputs, target) = (Qubit[3], Qubit());
+            mutable marked = [];
+            for x in 0..7 {
+                ApplyXorInPlace(x, inputs);
+                ApplyOracleFromFunction(HasTwoSet, inputs, target);
+                set marked += [MResetZ(target) == One];
+                ApplyXorInPlace(x, inputs);
+            }
+            marked
+        }",
+        &bools(&[false, false, false, true, false, true, true, false]),
+    );
+}
+
+#[test]
+fn check_oracle_from_int_function_releases_auxiliary_qubits() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Unstable.Arithmetic;
+            open Microsoft.Quantum.Unstable.Oracles;
+            use (inputs, target) = (Qubit[4], Qubit());
+            mutable marked = [];
+            for x in 0..15 {
+                ApplyXorInPlace(x, inputs);
+                ApplyOracleFromIntFunction(n -> n % 3 == 0, inputs, target);
+                set marked += [MResetZ(target) == One];
+                ApplyXorInPlace(x, inputs);
+            }
+            marked
+        }",
+        &bools(&[
+            true, false, false, true, false, false, true, false, false, true, false, false, true,
+            false, false, true,
+        ]),
+    );
+}
+
+#[test]
+fn check_controlled_phase_oracle_kicks_back_the_function() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Unstable.Arithmetic;
+            open Microsoft.Quantum.Unstable.Oracles;
+            use (control, inputs) = (Qubit(), Qubit[2]);
+            mutable marked = [];
+            for x in 0..3 {
+                ApplyXorInPlace(x, inputs);
+                H(control);
+                Controlled ApplyPhaseOracleFromIntFunction([control], (n -> n == 2, inputs));
+                H(control);
+                set marked += [MResetZ(control) == One];
+                ApplyXorInPlace(x, inputs);
+            }
+            marked
+        }",
+        &bools(&[false, false, true, false]),
+    );
+}
+
+#[test]
+fn check_phase_oracle_matches_equivalent_gates() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Diagnostics;
+            open Microsoft.Quantum.Unstable.Oracles;
+            operation ApplyCZ(qs : Qubit[]) : Unit is Adj {
+                CZ(qs[0], qs[1]);
+            }
+            operation ApplyCCNOT(qs : Qubit[]) : Unit is Adj {
+                CCNOT(qs[0], qs[1], qs[2]);
+            }
+            operation ApplyAndOracle(qs : Qubit[]) : Unit is Adj {
+                ApplyOracleFromFunction(bits -> bits[0] and bits[1], qs[0..1], qs[2]);
+            }
+            [
+                CheckOperationsAreEqual(2, ApplyPhaseOracleFromFunction(bits -> bits[0] and bits[1], _), ApplyCZ),
+                CheckOperationsAreEqual(3, ApplyAndOracle, ApplyCCNOT),
+                CheckOperationsAreEqual(2, ApplyPhaseOracleFromFunction(bits -> bits[0], _), ApplyCZ)
+            ]
+        }",
+        &bools(&[true, true, false]),
+    );
+}
+
+#[test]
+fn check_oracle_from_truth_table_marks_listed_inputs() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Unstable.Arithmetic;
+            open Microsoft.Quantum.Unstable.Oracles;
+            use (inputs, target) = (Qubit[2], Qubit());
+            mutable marked = [];
+            for x in 0..3 {
+                ApplyXorInPlace(x, inputs);
+                ApplyOracleFromTruthTable([false, true, true, true], inputs, target);
+                set marked += [MResetZ(target) == One];
+                ApplyXorInPlace(x, inputs);
+            }
+            marked
+        }",
+        &bools(&[false, true, true, true]),
+    );
+}
+
+#[test]
+fn check_permutation_oracle_maps_every_basis_state() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Unstable.Arithmetic;
+            open Microsoft.Quantum.Unstable.Oracles;
+            open Microsoft.Quantum.Measurement;
+            let permutation = [3, 6, 0, 5, 7, 1, 4, 2];
+            use qs = Qubit[3];
+            mutable images = [];
+            for x in 0..7 {
+                ApplyXorInPlace(x, qs);
+                ApplyPermutationOracle(permutation, qs);
+                set images += [MeasureInteger(qs)];
+            }
+            images
+        }",
+        &Value::Array(
+            [3, 6, 0, 5, 7, 1, 4, 2]
+                .into_iter()
+                .map(Value::Int)
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+    );
+}
+
+#[test]
+fn check_adjoint_permutation_oracle_inverts_it() {
+    test_expression(
+        "{
+            open Microsoft.Quantum.Diagnostics;
+            open Microsoft.Quantum.Unstable.Oracles;
+            operation ApplyIncrement(qs : Qubit[]) : Unit is Adj {
+                ApplyPermutationOracle([1, 2, 3, 0], qs);
+            }
+            operation ApplyDecrement(qs : Qubit[]) : Unit is Adj {
+                ApplyPermutationOracle([3, 0, 1, 2], qs);
+            }
+            CheckOperationsAreEqual(2, Adjoint ApplyIncrement, ApplyDecrement)
+        }",
+        &Value::Bool(true),
+    );
+}
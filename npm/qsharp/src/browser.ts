@@ -157,6 +157,9 @@ export type {
   ICodeAction,
   ICodeLens,
   IDocFile,
+  IDocumentSymbol,
+  IFoldingRange,
+  IInlayHint,
   ILocation,
   IOperationInfo,
   IPosition,
@@ -167,6 +170,7 @@ export type {
   IStackFrame,
   IStructStepResult,
   IWorkspaceEdit,
+  IWorkspaceSymbol,
   ProjectLoader,
   VSDiagnostic,
 } from "../lib/web/qsc_wasm.js";
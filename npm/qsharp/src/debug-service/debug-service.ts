@@ -33,6 +33,7 @@ export interface IDebugService {
   ): Promise<string>;
   getBreakpoints(path: string): Promise<IBreakpointSpan[]>;
   getLocalVariables(): Promise<Array<IVariable>>;
+  getFrameVariables(depth: number): Promise<Array<IVariable>>;
   captureQuantumState(): Promise<Array<IQuantumState>>;
   getCircuit(): Promise<CircuitData>;
   getStackFrames(): Promise<IStackFrame[]>;
@@ -86,6 +87,11 @@ export class QSharpDebugService implements IDebugService {
     return variable_list.variables;
   }
 
+  async getFrameVariables(depth: number): Promise<Array<IVariable>> {
+    const variable_list = this.debugService.get_locals_in_frame(depth);
+    return variable_list.variables;
+  }
+
   async captureQuantumState(): Promise<Array<IQuantumState>> {
     const state = this.debugService.capture_quantum_state();
     return state.entries;
@@ -181,6 +187,7 @@ export const debugServiceProtocol: ServiceProtocol<
     loadProgram: "request",
     getBreakpoints: "request",
     getLocalVariables: "request",
+    getFrameVariables: "request",
     captureQuantumState: "request",
     getCircuit: "request",
     getStackFrames: "request",
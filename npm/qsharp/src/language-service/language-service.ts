@@ -5,7 +5,10 @@ import type {
   ICodeAction,
   ICodeLens,
   ICompletionList,
+  IDocumentSymbol,
+  IFoldingRange,
   IHover,
+  IInlayHint,
   ILocation,
   INotebookMetadata,
   IPosition,
@@ -14,6 +17,7 @@ import type {
   ITextEdit,
   IWorkspaceConfiguration,
   IWorkspaceEdit,
+  IWorkspaceSymbol,
   LanguageService,
   VSDiagnostic,
 } from "../../lib/web/qsc_wasm.js";
@@ -58,6 +62,8 @@ export interface ILanguageService {
     documentUri: string,
     position: IPosition,
   ): Promise<ICompletionList>;
+  getDocumentSymbols(documentUri: string): Promise<IDocumentSymbol[]>;
+  getFoldingRanges(documentUri: string): Promise<IFoldingRange[]>;
   getFormatChanges(documentUri: string): Promise<ITextEdit[]>;
   getHover(
     documentUri: string,
@@ -72,6 +78,11 @@ export interface ILanguageService {
     position: IPosition,
     includeDeclaration: boolean,
   ): Promise<ILocation[]>;
+  getInlayHints(documentUri: string, range: IRange): Promise<IInlayHint[]>;
+  getSelectionRange(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<IRange[]>;
   getSignatureHelp(
     documentUri: string,
     position: IPosition,
@@ -86,6 +97,7 @@ export interface ILanguageService {
     position: IPosition,
   ): Promise<ITextEdit | undefined>;
   getCodeLenses(documentUri: string): Promise<ICodeLens[]>;
+  getWorkspaceSymbols(query: string): Promise<IWorkspaceSymbol[]>;
 
   dispose(): Promise<void>;
 
@@ -174,6 +186,14 @@ export class QSharpLanguageService implements ILanguageService {
     return this.languageService.get_completions(documentUri, position);
   }
 
+  async getDocumentSymbols(documentUri: string): Promise<IDocumentSymbol[]> {
+    return this.languageService.get_document_symbols(documentUri);
+  }
+
+  async getFoldingRanges(documentUri: string): Promise<IFoldingRange[]> {
+    return this.languageService.get_folding_ranges(documentUri);
+  }
+
   async getFormatChanges(documentUri: string): Promise<ITextEdit[]> {
     return this.languageService.get_format_changes(documentUri);
   }
@@ -204,6 +224,20 @@ export class QSharpLanguageService implements ILanguageService {
     );
   }
 
+  async getInlayHints(
+    documentUri: string,
+    range: IRange,
+  ): Promise<IInlayHint[]> {
+    return this.languageService.get_inlay_hints(documentUri, range);
+  }
+
+  async getSelectionRange(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<IRange[]> {
+    return this.languageService.get_selection_range(documentUri, position);
+  }
+
   async getSignatureHelp(
     documentUri: string,
     position: IPosition,
@@ -230,6 +264,10 @@ export class QSharpLanguageService implements ILanguageService {
     return this.languageService.get_code_lenses(documentUri);
   }
 
+  async getWorkspaceSymbols(query: string): Promise<IWorkspaceSymbol[]> {
+    return this.languageService.get_workspace_symbols(query);
+  }
+
   async dispose() {
     this.languageService.stop_background_work();
     await this.backgroundWork;
@@ -287,14 +325,19 @@ export const languageServiceProtocol: ServiceProtocol<
     closeNotebookDocument: "request",
     getCodeActions: "request",
     getCompletions: "request",
+    getDocumentSymbols: "request",
+    getFoldingRanges: "request",
     getFormatChanges: "request",
     getHover: "request",
     getDefinition: "request",
     getReferences: "request",
+    getInlayHints: "request",
+    getSelectionRange: "request",
     getSignatureHelp: "request",
     getRename: "request",
     prepareRename: "request",
     getCodeLenses: "request",
+    getWorkspaceSymbols: "request",
     dispose: "request",
     addEventListener: "addEventListener",
     removeEventListener: "removeEventListener",
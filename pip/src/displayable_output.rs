@@ -5,8 +5,8 @@
 mod tests;
 
 use num_bigint::BigUint;
-use num_complex::{Complex64, ComplexFloat};
-use qsc::{fmt_basis_state_label, fmt_complex, format_state_id, get_latex, get_phase};
+use num_complex::Complex64;
+use qsc::{fmt_complex, format_state_id, get_latex};
 use std::fmt::Write;
 
 #[derive(Clone)]
@@ -31,25 +31,7 @@ impl DisplayableState {
     }
 
     pub fn to_html(&self) -> String {
-        format!(
-            include_str!("state_header_template.html"),
-            self.0
-                .iter()
-                .fold(String::new(), |mut output, (id, state)| {
-                    let amplitude = state.abs().powi(2) * 100.0;
-                    let _ = write!(
-                        output,
-                        include_str!("state_row_template.html"),
-                        fmt_basis_state_label(id, self.1),
-                        fmt_complex(state),
-                        amplitude,
-                        amplitude,
-                        get_phase(state),
-                        get_phase(state)
-                    );
-                    output
-                })
-        )
+        qsc::render::state_to_html(&self.0, self.1)
     }
 
     pub fn to_latex(&self) -> Option<String> {
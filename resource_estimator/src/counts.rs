@@ -9,7 +9,7 @@ use num_complex::Complex;
 use qsc::{interpret::Value, Backend};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use rustc_hash::FxHashMap;
-use std::{array, cell::RefCell, f64::consts::PI, fmt::Debug, iter::Sum};
+use std::{array, cell::RefCell, collections::BTreeMap, f64::consts::PI, fmt::Debug, iter::Sum};
 
 use crate::system::LogicalResourceCounts;
 
@@ -34,6 +34,8 @@ pub struct LogicalCounter {
     ccz_count: usize,
     /// Number of single-qubit and multiple-qubit measurements
     m_count: usize,
+    /// Number of uses of each arithmetic implementation
+    arithmetic: BTreeMap<String, usize>,
     /// Global allocation barrier (when calling global barrier this is advanced
     /// to allocate new qubits after the barrier)
     allocation_barrier: usize,
@@ -58,6 +60,7 @@ impl Default for LogicalCounter {
             r_count: 0,
             ccz_count: 0,
             m_count: 0,
+            arithmetic: BTreeMap::new(),
             allocation_barrier: 0,
             caching_stack: vec![],
             caching_layers: FxHashMap::default(),
@@ -78,6 +81,11 @@ impl LogicalCounter {
             ccz_count: self.ccz_count as _,
             ccix_count: 0,
             measurement_count: self.m_count as _,
+            arithmetic: self
+                .arithmetic
+                .iter()
+                .map(|(name, count)| (name.clone(), *count as _))
+                .collect(),
         }
     }
 
@@ -157,6 +165,7 @@ impl LogicalCounter {
             end_depth,
             combined_layer,
             m_count,
+            arithmetic,
         }) = self.caching_layers.get(&label)
         {
             self.layers.extend_from_within(*start_depth..*end_depth);
@@ -165,6 +174,9 @@ impl LogicalCounter {
             self.r_count += combined_layer.r;
             self.ccz_count += combined_layer.ccz;
             self.m_count += *m_count;
+            for (name, count) in arithmetic {
+                *self.arithmetic.entry(name.clone()).or_default() += count;
+            }
 
             false
         } else {
@@ -175,6 +187,7 @@ impl LogicalCounter {
                 LayerCache::Begin {
                     start_depth: depth,
                     m_count: self.m_count,
+                    arithmetic: self.arithmetic.clone(),
                 },
             );
             self.caching_stack.push(label);
@@ -196,6 +209,7 @@ impl LogicalCounter {
         let LayerCache::Begin {
             start_depth,
             m_count,
+            arithmetic,
         } = entry
         else {
             panic!("layer caching should always have matching begin and end");
@@ -213,6 +227,7 @@ impl LogicalCounter {
                 end_depth,
                 combined_layer: sum,
                 m_count: self.m_count - m_count,
+                arithmetic: self.arithmetic_since(&arithmetic),
             },
         );
 
@@ -229,6 +244,7 @@ impl LogicalCounter {
                 .map_err(|_| format!("Estimate count {count} is too large to fit in a usize.",))?,
             start_depth,
             m_count: self.m_count,
+            arithmetic: self.arithmetic.clone(),
         });
 
         Ok(())
@@ -240,6 +256,7 @@ impl LogicalCounter {
             count,
             start_depth,
             m_count,
+            arithmetic,
         }) = self.repeats.pop()
         {
             if count == 0 {
@@ -283,11 +300,25 @@ impl LogicalCounter {
             self.r_count += combined_r_count;
             self.ccz_count += combined_ccz_count;
             self.m_count += combined_m_count;
+            for (name, uses) in self.arithmetic_since(&arithmetic) {
+                *self.arithmetic.entry(name).or_default() += uses * (count - 1);
+            }
 
             self.global_barrier();
         }
     }
 
+    /// Returns the uses of arithmetic implementations since the `earlier` counts were taken.
+    fn arithmetic_since(&self, earlier: &BTreeMap<String, usize>) -> BTreeMap<String, usize> {
+        self.arithmetic
+            .iter()
+            .filter_map(|(name, count)| {
+                let delta = count - earlier.get(name).copied().unwrap_or_default();
+                (delta > 0).then(|| (name.clone(), delta))
+            })
+            .collect()
+    }
+
     fn add_estimate(
         &mut self,
         estimates: &[(i64, i64)],
@@ -517,6 +548,13 @@ impl Backend for LogicalCounter {
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         match name {
             "GlobalPhase" => Some(Ok(Value::unit())),
+            "AccountForArithmeticInternal" => {
+                *self
+                    .arithmetic
+                    .entry(arg.unwrap_string().to_string())
+                    .or_default() += 1;
+                Some(Ok(Value::unit()))
+            }
             "BeginEstimateCaching" => {
                 let values = arg.unwrap_tuple();
                 let [cache_name, cache_variant] = array::from_fn(|i| values[i].clone());
@@ -606,12 +644,14 @@ enum LayerCache {
     Begin {
         start_depth: usize,
         m_count: usize,
+        arithmetic: BTreeMap<String, usize>,
     },
     End {
         start_depth: usize,
         end_depth: usize,
         combined_layer: LayerInfo,
         m_count: usize,
+        arithmetic: BTreeMap<String, usize>,
     },
 }
 
@@ -619,4 +659,5 @@ struct RepeatEntry {
     count: usize,
     start_depth: usize,
     m_count: usize,
+    arithmetic: BTreeMap<String, usize>,
 }
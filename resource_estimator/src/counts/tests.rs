@@ -3,7 +3,7 @@
 
 #![allow(clippy::needless_raw_string_hashes)]
 
-use std::convert::Into;
+use std::{collections::BTreeMap, convert::Into};
 
 use expect_test::{expect, Expect};
 use indoc::indoc;
@@ -14,8 +14,9 @@ use qsc::{
 };
 
 use super::LogicalCounter;
+use crate::system::LogicalResourceCounts;
 
-fn verify_logical_counts(source: &str, entry: Option<&str>, expect: &Expect) {
+fn logical_counts(source: &str, entry: Option<&str>) -> LogicalResourceCounts {
     let source_map = SourceMap::new([("test".into(), source.into())], entry.map(Into::into));
     let mut interpreter = Interpreter::new(
         true,
@@ -31,7 +32,11 @@ fn verify_logical_counts(source: &str, entry: Option<&str>, expect: &Expect) {
     interpreter
         .eval_entry_with_sim(&mut counter, &mut out)
         .expect("evaluation should succeed");
-    expect.assert_debug_eq(&counter.logical_resources());
+    counter.logical_resources()
+}
+
+fn verify_logical_counts(source: &str, entry: Option<&str>, expect: &Expect) {
+    expect.assert_debug_eq(&logical_counts(source, entry));
 }
 
 #[test]
@@ -71,6 +76,7 @@ fn gates_are_counted() {
                 ccz_count: 2,
                 ccix_count: 0,
                 measurement_count: 10,
+                arithmetic: {},
             }
         "]],
     );
@@ -118,6 +124,7 @@ fn estimate_caching_works() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                arithmetic: {},
             }
         "]],
     );
@@ -165,6 +172,7 @@ fn estimate_repeat_works() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                arithmetic: {},
             }
         "#]],
     );
@@ -204,6 +212,7 @@ fn account_for_estimates_works() {
                 ccz_count: 5,
                 ccix_count: 0,
                 measurement_count: 6,
+                arithmetic: {},
             }
         "]],
     );
@@ -232,7 +241,41 @@ fn pauli_i_rotation_for_global_phase_is_noop() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                arithmetic: {},
             }
         "#]],
     );
 }
+
+#[test]
+fn arithmetic_implementations_are_counted() {
+    let counts = logical_counts(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.ResourceEstimation;
+                open Microsoft.Quantum.Unstable.Arithmetic;
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    use (xs, ys) = (Qubit[4], Qubit[4]);
+                    IncByLE(xs, ys);
+                    within {
+                        RepeatEstimates(3);
+                    }
+                    apply {
+                        RippleCarryCDKMIncByLE(xs, ys);
+                    }
+                    ResetAll(xs + ys);
+                }
+            }
+        "},
+        None,
+    );
+    assert_eq!(
+        counts.arithmetic,
+        BTreeMap::from([
+            ("RippleCarryCDKMIncByLE".to_string(), 3),
+            ("RippleCarryTTKIncByLE".to_string(), 1),
+        ])
+    );
+}
@@ -9,6 +9,7 @@ use crate::{
     },
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::PartitioningOverhead;
 
@@ -43,6 +44,10 @@ pub struct LogicalResourceCounts {
     pub ccix_count: u64,
     #[serde(default)]
     pub measurement_count: u64,
+    /// Number of uses of each implementation of an arithmetic operation, such
+    /// as an adder, by its name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub arithmetic: BTreeMap<String, u64>,
 }
 
 /// Models the logical resources after layout
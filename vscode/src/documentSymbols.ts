@@ -0,0 +1,57 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { IDocumentSymbol, ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { toVscodeRange } from "./common";
+
+export function createDocumentSymbolProvider(
+  languageService: ILanguageService,
+) {
+  return new QSharpDocumentSymbolProvider(languageService);
+}
+
+class QSharpDocumentSymbolProvider implements vscode.DocumentSymbolProvider {
+  constructor(public languageService: ILanguageService) {}
+  async provideDocumentSymbols(
+    document: vscode.TextDocument,
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    token: vscode.CancellationToken,
+  ) {
+    const lsSymbols = await this.languageService.getDocumentSymbols(
+      document.uri.toString(),
+    );
+    return lsSymbols.map(toVscodeDocumentSymbol);
+  }
+}
+
+function toVscodeDocumentSymbol(
+  symbol: IDocumentSymbol,
+): vscode.DocumentSymbol {
+  const documentSymbol = new vscode.DocumentSymbol(
+    symbol.name,
+    symbol.detail ?? "",
+    toVscodeSymbolKind(symbol.kind),
+    toVscodeRange(symbol.range),
+    toVscodeRange(symbol.selectionRange),
+  );
+  documentSymbol.children = symbol.children.map(toVscodeDocumentSymbol);
+  return documentSymbol;
+}
+
+function toVscodeSymbolKind(kind: IDocumentSymbol["kind"]) {
+  switch (kind) {
+    case "namespace":
+      return vscode.SymbolKind.Namespace;
+    case "function":
+      return vscode.SymbolKind.Function;
+    case "operation":
+    case "specialization":
+      return vscode.SymbolKind.Method;
+    case "newtype":
+    case "struct":
+      return vscode.SymbolKind.Struct;
+    case "field":
+      return vscode.SymbolKind.Field;
+  }
+}
@@ -27,6 +27,10 @@ import { createDefinitionProvider } from "./definition";
 import { startCheckingQSharp } from "./diagnostics";
 import { createFormattingProvider } from "./format.js";
 import { createHoverProvider } from "./hover";
+import { createInlayHintsProvider } from "./inlayHints.js";
+import { createDocumentSymbolProvider } from "./documentSymbols.js";
+import { createFoldingRangeProvider } from "./foldingRanges.js";
+import { createSelectionRangeProvider } from "./selectionRanges.js";
 import {
   Logging,
   initLogForwarder,
@@ -59,6 +63,7 @@ import {
   sendTelemetryEvent,
 } from "./telemetry.js";
 import { registerWebViewCommands } from "./webviewPanel.js";
+import { createWorkspaceSymbolProvider } from "./workspaceSymbols.js";
 
 export async function activate(
   context: vscode.ExtensionContext,
@@ -279,6 +284,38 @@ async function activateLanguageService(extensionUri: vscode.Uri) {
     ),
   );
 
+  // inlay hints
+  subscriptions.push(
+    vscode.languages.registerInlayHintsProvider(
+      qsharpLanguageId,
+      createInlayHintsProvider(languageService),
+    ),
+  );
+
+  // document symbols
+  subscriptions.push(
+    vscode.languages.registerDocumentSymbolProvider(
+      qsharpLanguageId,
+      createDocumentSymbolProvider(languageService),
+    ),
+  );
+
+  // folding ranges
+  subscriptions.push(
+    vscode.languages.registerFoldingRangeProvider(
+      qsharpLanguageId,
+      createFoldingRangeProvider(languageService),
+    ),
+  );
+
+  // selection ranges
+  subscriptions.push(
+    vscode.languages.registerSelectionRangeProvider(
+      qsharpLanguageId,
+      createSelectionRangeProvider(languageService),
+    ),
+  );
+
   // go to def
   subscriptions.push(
     vscode.languages.registerDefinitionProvider(
@@ -295,6 +332,13 @@ async function activateLanguageService(extensionUri: vscode.Uri) {
     ),
   );
 
+  // workspace symbol search
+  subscriptions.push(
+    vscode.languages.registerWorkspaceSymbolProvider(
+      createWorkspaceSymbolProvider(languageService),
+    ),
+  );
+
   // signature help
   subscriptions.push(
     vscode.languages.registerSignatureHelpProvider(
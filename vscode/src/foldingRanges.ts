@@ -0,0 +1,33 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+
+export function createFoldingRangeProvider(languageService: ILanguageService) {
+  return new QSharpFoldingRangeProvider(languageService);
+}
+
+class QSharpFoldingRangeProvider implements vscode.FoldingRangeProvider {
+  constructor(public languageService: ILanguageService) {}
+
+  async provideFoldingRanges(
+    document: vscode.TextDocument,
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    context: vscode.FoldingContext,
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    token: vscode.CancellationToken,
+  ) {
+    const ranges = await this.languageService.getFoldingRanges(
+      document.uri.toString(),
+    );
+    return ranges.map(
+      (range) =>
+        new vscode.FoldingRange(
+          range.startLine,
+          range.endLine,
+          range.kind === "imports" ? vscode.FoldingRangeKind.Imports : undefined,
+        ),
+    );
+  }
+}
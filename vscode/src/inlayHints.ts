@@ -0,0 +1,35 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+
+export function createInlayHintsProvider(languageService: ILanguageService) {
+  return new QSharpInlayHintsProvider(languageService);
+}
+
+class QSharpInlayHintsProvider implements vscode.InlayHintsProvider {
+  constructor(public languageService: ILanguageService) {}
+
+  async provideInlayHints(
+    document: vscode.TextDocument,
+    range: vscode.Range,
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    token: vscode.CancellationToken,
+  ) {
+    const hints = await this.languageService.getInlayHints(
+      document.uri.toString(),
+      range,
+    );
+    return hints.map((hint) => {
+      const inlayHint = new vscode.InlayHint(
+        new vscode.Position(hint.position.line, hint.position.character),
+        hint.label,
+        hint.kind === "type" ? vscode.InlayHintKind.Type : undefined,
+      );
+      // Types follow the name they annotate directly, like `x: Int`.
+      inlayHint.paddingLeft = hint.kind !== "type";
+      return inlayHint;
+    });
+  }
+}
@@ -0,0 +1,45 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { toVscodeRange } from "./common";
+
+export function createSelectionRangeProvider(
+  languageService: ILanguageService,
+) {
+  return new QSharpSelectionRangeProvider(languageService);
+}
+
+class QSharpSelectionRangeProvider implements vscode.SelectionRangeProvider {
+  constructor(public languageService: ILanguageService) {}
+
+  async provideSelectionRanges(
+    document: vscode.TextDocument,
+    positions: vscode.Position[],
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    token: vscode.CancellationToken,
+  ) {
+    return Promise.all(
+      positions.map(async (position) => {
+        const ranges = await this.languageService.getSelectionRange(
+          document.uri.toString(),
+          position,
+        );
+        // The language service returns the ranges innermost first, and each one becomes the
+        // parent of the one before it.
+        let selectionRange: vscode.SelectionRange | undefined;
+        for (const range of ranges.reverse()) {
+          selectionRange = new vscode.SelectionRange(
+            toVscodeRange(range),
+            selectionRange,
+          );
+        }
+        return (
+          selectionRange ??
+          new vscode.SelectionRange(new vscode.Range(position, position))
+        );
+      }),
+    );
+  }
+}
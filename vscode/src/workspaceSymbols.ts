@@ -0,0 +1,47 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService, IWorkspaceSymbol } from "qsharp-lang";
+import * as vscode from "vscode";
+import { toVscodeLocation } from "./common";
+
+export function createWorkspaceSymbolProvider(
+  languageService: ILanguageService,
+) {
+  return new QSharpWorkspaceSymbolProvider(languageService);
+}
+
+class QSharpWorkspaceSymbolProvider
+  implements vscode.WorkspaceSymbolProvider
+{
+  constructor(public languageService: ILanguageService) {}
+  async provideWorkspaceSymbols(
+    query: string,
+    // eslint-disable-next-line @typescript-eslint/no-unused-vars
+    token: vscode.CancellationToken,
+  ) {
+    const lsSymbols = await this.languageService.getWorkspaceSymbols(query);
+    return lsSymbols.map(
+      (symbol) =>
+        new vscode.SymbolInformation(
+          symbol.name,
+          toVscodeSymbolKind(symbol.kind),
+          symbol.containerName,
+          toVscodeLocation(symbol.location),
+        ),
+    );
+  }
+}
+
+function toVscodeSymbolKind(kind: IWorkspaceSymbol["kind"]) {
+  switch (kind) {
+    case "namespace":
+      return vscode.SymbolKind.Namespace;
+    case "function":
+      return vscode.SymbolKind.Function;
+    case "operation":
+      return vscode.SymbolKind.Method;
+    case "type":
+      return vscode.SymbolKind.Struct;
+  }
+}
@@ -6,7 +6,7 @@ use crate::project_system::{into_qsc_args, ProgramConfig};
 use crate::{serializable_type, CallbackReceiver};
 use qsc::fir::StmtId;
 use qsc::fmt_complex;
-use qsc::interpret::{Debugger, Error, StepAction, StepResult};
+use qsc::interpret::{Debugger, Error, StepAction, StepResult, VariableInfo};
 use qsc::line_column::Encoding;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -183,16 +183,12 @@ impl DebugService {
     }
 
     pub fn get_locals(&self) -> IVariableList {
-        let locals = self.debugger().get_locals();
-        let variables: Vec<_> = locals
-            .into_iter()
-            .map(|local| Variable {
-                name: (*local.name).to_string(),
-                value: local.value.to_string(),
-                var_type: local.type_name,
-            })
-            .collect();
-        VariableList { variables }.into()
+        variable_list(self.debugger().get_locals())
+    }
+
+    pub fn get_locals_in_frame(&self, depth: u32) -> IVariableList {
+        let depth = usize::try_from(depth).expect("depth should fit in usize");
+        variable_list(self.debugger().get_locals_in_frame(depth))
     }
 
     fn debugger(&self) -> &Debugger {
@@ -208,6 +204,18 @@ impl DebugService {
     }
 }
 
+fn variable_list(locals: Vec<VariableInfo>) -> IVariableList {
+    let variables: Vec<_> = locals
+        .into_iter()
+        .map(|local| Variable {
+            name: (*local.name).to_string(),
+            value: local.value.to_string(),
+            var_type: local.type_name,
+        })
+        .collect();
+    VariableList { variables }.into()
+}
+
 fn render_errors(errors: Vec<Error>) -> String {
     let mut msg = String::new();
     for error in errors {
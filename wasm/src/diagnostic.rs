@@ -228,11 +228,7 @@ pub fn interpret_errors_into_qsharp_errors(errs: &[interpret::Error]) -> Vec<QSh
 
             let vsdiagnostic = VSDiagnostic::new(labels, &doc, err);
 
-            let stack_trace = if let interpret::Error::Eval(_) = err {
-                err.stack_trace().clone()
-            } else {
-                None
-            };
+            let stack_trace = err.stack_trace().clone();
 
             QSharpError {
                 document: doc,
@@ -268,9 +264,21 @@ fn interpret_error_labels(err: &interpret::Error) -> Vec<Label> {
         interpret::Error::Compile(e) => error_labels(e),
         interpret::Error::Pass(e) => error_labels(e),
         interpret::Error::PartialEvaluation(e) => error_labels(e),
+        interpret::Error::Sandbox(e) => error_labels(e),
+        interpret::Error::Explained(e) => interpret_error_labels(e.error()),
         interpret::Error::NoEntryPoint
         | interpret::Error::UnsupportedRuntimeCapabilities
         | interpret::Error::Circuit(_)
-        | interpret::Error::NotAnOperation => Vec::new(),
+        | interpret::Error::NotAnOperation
+        | interpret::Error::UnsupportedCheckpointValue(_)
+        | interpret::Error::CheckpointMismatch
+        | interpret::Error::Simulator(_)
+        | interpret::Error::CheckpointRestoreNotFresh
+        | interpret::Error::CheckpointStateUnsupported(_)
+        | interpret::Error::UnknownCodegenTarget(_)
+        | interpret::Error::CodegenTarget(..)
+        | interpret::Error::InvalidKernelName(_)
+        | interpret::Error::KernelSymbolCollision(..)
+        | interpret::Error::Oracle(_) => Vec::new(),
     }
 }
@@ -203,6 +203,56 @@ impl LanguageService {
             .collect()
     }
 
+    pub fn get_workspace_symbols(&self, query: &str) -> Vec<IWorkspaceSymbol> {
+        let symbols = self.0.get_workspace_symbols(query);
+        symbols
+            .into_iter()
+            .map(|symbol| {
+                WorkspaceSymbol {
+                    name: symbol.name,
+                    kind: (match symbol.kind {
+                        qsls::protocol::SymbolKind::Namespace => "namespace",
+                        qsls::protocol::SymbolKind::Function => "function",
+                        qsls::protocol::SymbolKind::Operation => "operation",
+                        qsls::protocol::SymbolKind::Type => "type",
+                    })
+                    .to_string(),
+                    containerName: symbol.container_name,
+                    detail: symbol.detail,
+                    location: Location::from(symbol.location),
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    pub fn get_document_symbols(&self, uri: &str) -> Vec<IDocumentSymbol> {
+        let symbols = self.0.get_document_symbols(uri);
+        symbols
+            .into_iter()
+            .map(|symbol| DocumentSymbol::from(symbol).into())
+            .collect()
+    }
+
+    pub fn get_folding_ranges(&self, uri: &str) -> Vec<IFoldingRange> {
+        let ranges = self.0.get_folding_ranges(uri);
+        ranges
+            .into_iter()
+            .map(|range| {
+                FoldingRange {
+                    startLine: range.start_line,
+                    endLine: range.end_line,
+                    kind: (match range.kind {
+                        qsls::protocol::FoldingRangeKind::Block => "block",
+                        qsls::protocol::FoldingRangeKind::Imports => "imports",
+                    })
+                    .to_string(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
     pub fn get_format_changes(&self, uri: &str) -> Vec<ITextEdit> {
         let edits = self.0.get_format_changes(uri);
         edits
@@ -229,6 +279,39 @@ impl LanguageService {
         })
     }
 
+    pub fn get_inlay_hints(&self, uri: &str, range: IRange) -> Vec<IInlayHint> {
+        let range: Range = range.into();
+        let hints = self.0.get_inlay_hints(uri, range.into());
+        hints
+            .into_iter()
+            .map(|hint| {
+                InlayHint {
+                    position: hint.position.into(),
+                    label: hint.label,
+                    kind: (match hint.kind {
+                        qsls::protocol::InlayHintKind::Type => "type",
+                        qsls::protocol::InlayHintKind::Functor => "functor",
+                        qsls::protocol::InlayHintKind::Length => "length",
+                    })
+                    .to_string(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    pub fn get_selection_range(&self, uri: &str, position: IPosition) -> Vec<IRange> {
+        let position: Position = position.into();
+        let ranges = self.0.get_selection_range(uri, position.into());
+        ranges
+            .into_iter()
+            .map(|range| {
+                let range: Range = range.into();
+                range.into()
+            })
+            .collect()
+    }
+
     pub fn get_signature_help(&self, uri: &str, position: IPosition) -> Option<ISignatureHelp> {
         let position: Position = position.into();
         let sig_help = self.0.get_signature_help(uri, position.into());
@@ -435,6 +518,98 @@ impl From<qsls::protocol::TextEdit> for TextEdit {
     }
 }
 
+serializable_type! {
+    DocumentSymbol,
+    {
+        pub name: String,
+        pub kind: String,
+        pub detail: Option<String>,
+        pub range: Range,
+        pub selectionRange: Range,
+        pub children: Vec<DocumentSymbol>,
+    },
+    r#"export interface IDocumentSymbol {
+        name: string;
+        kind: "namespace" | "function" | "operation" | "newtype" | "struct" | "field" | "specialization";
+        detail?: string;
+        range: IRange;
+        selectionRange: IRange;
+        children: IDocumentSymbol[];
+    }"#,
+    IDocumentSymbol
+}
+
+impl From<qsls::protocol::DocumentSymbol> for DocumentSymbol {
+    fn from(symbol: qsls::protocol::DocumentSymbol) -> Self {
+        Self {
+            name: symbol.name,
+            kind: (match symbol.kind {
+                qsls::protocol::DocumentSymbolKind::Namespace => "namespace",
+                qsls::protocol::DocumentSymbolKind::Function => "function",
+                qsls::protocol::DocumentSymbolKind::Operation => "operation",
+                qsls::protocol::DocumentSymbolKind::Newtype => "newtype",
+                qsls::protocol::DocumentSymbolKind::Struct => "struct",
+                qsls::protocol::DocumentSymbolKind::Field => "field",
+                qsls::protocol::DocumentSymbolKind::Specialization => "specialization",
+            })
+            .to_string(),
+            detail: symbol.detail,
+            range: symbol.range.into(),
+            selectionRange: symbol.selection_range.into(),
+            children: symbol.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+serializable_type! {
+    FoldingRange,
+    {
+        pub startLine: u32,
+        pub endLine: u32,
+        pub kind: String,
+    },
+    r#"export interface IFoldingRange {
+        startLine: number;
+        endLine: number;
+        kind: "block" | "imports";
+    }"#,
+    IFoldingRange
+}
+
+serializable_type! {
+    InlayHint,
+    {
+        pub position: Position,
+        pub label: String,
+        pub kind: String,
+    },
+    r#"export interface IInlayHint {
+        position: IPosition;
+        label: string;
+        kind: "type" | "functor" | "length";
+    }"#,
+    IInlayHint
+}
+
+serializable_type! {
+    WorkspaceSymbol,
+    {
+        pub name: String,
+        pub kind: String,
+        pub containerName: String,
+        pub detail: String,
+        pub location: Location,
+    },
+    r#"export interface IWorkspaceSymbol {
+        name: string;
+        kind: "namespace" | "function" | "operation" | "type";
+        containerName: string;
+        detail: string;
+        location: ILocation;
+    }"#,
+    IWorkspaceSymbol
+}
+
 serializable_type! {
     Hover,
     {
@@ -21,7 +21,7 @@ use qsc::{
     },
     target::Profile,
     LanguageFeatures, PackageStore, PackageType, SourceContents, SourceMap, SourceName, SparseSim,
-    TargetCapabilityFlags,
+    StdFeatures, TargetCapabilityFlags,
 };
 use resource_estimator::{self as re, estimate_entry};
 use serde::{Deserialize, Serialize};
@@ -42,9 +42,9 @@ mod tests;
 
 thread_local! {
     static STORE_CORE_STD: (PackageStore, PackageId) = {
-        let mut store = PackageStore::new(compile::core());
-        let std = store.insert(compile::std(&store, Profile::Unrestricted.into()));
-        (store, std)
+        let (store, std) =
+            compile::shared_store(Profile::Unrestricted.into(), Some(StdFeatures::all()));
+        (store, std.expect("store should have std"))
     };
 }
 
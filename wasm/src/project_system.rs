@@ -227,6 +227,7 @@ impl From<qsc_project::PackageInfo> for PackageInfo {
                 .into_iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect(),
+            no_stdlib: value.no_stdlib,
         }
     }
 }
@@ -266,11 +267,14 @@ serializable_type! {
         pub sources: Vec<(String, String)>,
         pub language_features: Vec<String>,
         pub dependencies: FxHashMap<PackageAlias,PackageKey>,
+        #[serde(default)]
+        pub no_stdlib: bool,
     },
     r#"export interface IPackageInfo {
         sources: [string, string][];
         languageFeatures: string[];
         dependencies: Record<string,string>;
+        noStdlib?: boolean;
     }"#
 }
 
@@ -350,6 +354,7 @@ impl From<PackageInfo> for qsc_project::PackageInfo {
                 .into_iter()
                 .map(|(k, v)| (Arc::from(k), Arc::from(v)))
                 .collect(),
+            no_stdlib: value.no_stdlib,
         }
     }
 }